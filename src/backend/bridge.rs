@@ -1,9 +1,15 @@
 use crate::backend::factory::{BackendFactory, BackendError, BackendType};
 use crate::backend::ports::{CodeGen, Emitter, Optimizer};
-use crate::backend::ports::codegen::{Module, OptimizationLevel, BackendInput, BackendInputType};
+use crate::backend::ports::codegen::{Module, OptimizationLevel, BackendInput, BackendInputType, DebugInfo, PanicStrategy};
+use crate::backend::link::LinkOptions;
+use crate::backend::objcopy::LoadImage;
 use crate::backend::ports::emitter::EmitType;
+use crate::backend::ports::optimizer::OptimizationPass;
+use crate::backend::stack_usage::{mir_call_graph, StackUsage};
+use crate::core::mir::verify::{verify_all, VerifyError};
 use crate::core::mir::MirFunction;
 use crate::core::hir::Hir;
+use std::collections::HashMap;
 use std::path::Path;
 use thiserror::Error;
 
@@ -14,6 +20,8 @@ pub struct BackendBridge {
     optimizer: Box<dyn Optimizer>,
     emitter: Box<dyn Emitter>,
     backend_type: BackendType,
+    /// call edges of the last compiled input 4 stack usage reports
+    call_graph: HashMap<String, Vec<String>>,
 }
 
 impl BackendBridge {
@@ -24,12 +32,19 @@ impl BackendBridge {
             optimizer: factory.create_optimizer()?,
             emitter: factory.create_emitter()?,
             backend_type: factory.backend_type(),
+            call_graph: HashMap::new(),
         })
     }
     
     /// set optmztn level
     pub fn set_optimization_level(&mut self, level: OptimizationLevel) {
         self.codegen.set_optimization_level(level);
+        self.optimizer.set_optimization_level(level);
+    }
+    
+    /// run `pass` after the level's pipeline, backends w/o a pass pipeline ignore it
+    pub fn add_pass(&mut self, pass: OptimizationPass) {
+        self.optimizer.add_pass(pass);
     }
     
     /// set trgt triple
@@ -37,8 +52,37 @@ impl BackendBridge {
         self.codegen.set_target_triple(triple);
     }
     
+    /// emit debug info 4 the source file
+    pub fn set_debug_info(&mut self, debug_info: DebugInfo) {
+        self.codegen.set_debug_info(debug_info);
+    }
+    
+    /// unwind or abort on panic
+    pub fn set_panic_strategy(&mut self, strategy: PanicStrategy) {
+        self.codegen.set_panic_strategy(strategy);
+    }
+
+    /// compile only this unit's fns out of the MIR input
+    pub fn set_codegen_unit(&mut self, functions: Vec<String>) {
+        self.codegen.set_codegen_unit(functions);
+    }
+    
+    /// set linker script/search paths/libs used when emitting binaries
+    pub fn set_link_options(&mut self, options: LinkOptions) {
+        self.emitter.set_link_options(options);
+    }
+    
+    /// embed bitcode in emitted objects 4 toolchains that run their own lto
+    pub fn set_embed_bitcode(&mut self, embed: bool) {
+        self.emitter.set_embed_bitcode(embed);
+    }
+    
     /// cmpl from HIR or MIR based on backend preference
     pub fn compile(&mut self, input: BackendInput) -> Result<Module, CompileError> {
+        if let BackendInput::Mir(ref mir) = input {
+            verify_all(mir).map_err(CompileError::InvalidMir)?;
+            self.call_graph = mir_call_graph(mir);
+        }
         self.codegen.generate(input)
             .map_err(|e| CompileError::CodeGenFailed(e.to_string()))
     }
@@ -51,6 +95,7 @@ impl BackendBridge {
     
     /// cmpl from MIR
     pub fn compile_from_mir(&mut self, mir: &[MirFunction]) -> Result<Module, CompileError> {
+        verify_all(mir).map_err(CompileError::InvalidMir)?;
         self.codegen.generate_from_mir(mir)
             .map_err(|e| CompileError::CodeGenFailed(e.to_string()))
     }
@@ -72,11 +117,46 @@ impl BackendBridge {
             EmitType::Binary => self.emitter.emit_binary(module, output),
             EmitType::Assembly => self.emitter.emit_assembly(module, output),
             EmitType::LlvmIr => self.emitter.emit_llvm_ir(module, output),
+            EmitType::Bitcode => self.emitter.emit_bitcode(module, output),
             EmitType::Object => self.emitter.emit_object(module, output),
+            EmitType::Wasm => self.emitter.emit_wasm(module, output),
+            EmitType::StaticLib => self.emitter.emit_static_lib(module, output),
+            EmitType::SharedLib => self.emitter.emit_shared_lib(module, output),
+            EmitType::StackUsage => return self.emit_stack_usage(module, output),
+            EmitType::RawBinary | EmitType::Ihex => return self.emit_flat(module, emit_type, output),
+            EmitType::Mir | EmitType::CfgDot => {
+                return Err(CompileError::EmissionFailed("MIR is written before codegen, not from a module".to_string()))
+            }
         }
         .map_err(|e| CompileError::EmissionFailed(e.to_string()))
     }
     
+    /// frame sizes frm the backend + call graph 4 recursion
+    fn emit_stack_usage(&self, module: &Module, output: &Path) -> Result<(), CompileError> {
+        let frames = self.emitter.stack_frames(module)
+            .map_err(|e| CompileError::EmissionFailed(e.to_string()))?;
+        let usage = StackUsage::new(frames).with_calls(&self.call_graph);
+        std::fs::write(output, usage.report())
+            .map_err(|e| CompileError::EmissionFailed(e.to_string()))
+    }
+    
+    /// link an elf next 2 the output then objcopy its loadable segments
+    fn emit_flat(&self, module: &Module, emit_type: EmitType, output: &Path) -> Result<(), CompileError> {
+        let failed = |e: &dyn std::fmt::Display| CompileError::EmissionFailed(e.to_string());
+        
+        let elf_path = output.with_extension("elf");
+        self.emitter.emit_binary(module, &elf_path).map_err(|e| failed(&e))?;
+        
+        let elf = std::fs::read(&elf_path).map_err(|e| failed(&e))?;
+        let image = LoadImage::from_elf(&elf).map_err(|e| failed(&e))?;
+        let bytes = if emit_type == EmitType::Ihex {
+            image.to_ihex().map_err(|e| failed(&e))?.into_bytes()
+        } else {
+            image.to_raw_binary()
+        };
+        std::fs::write(output, bytes).map_err(|e| failed(&e))
+    }
+    
     /// full compilation pipeline: cmpl > optimize > emit
     pub fn compile_and_emit(
         &mut self,
@@ -132,4 +212,7 @@ pub enum CompileError {
     
     #[error("Emission failed: {0}")]
     EmissionFailed(String),
+
+    #[error("{}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n"))]
+    InvalidMir(Vec<VerifyError>),
 }
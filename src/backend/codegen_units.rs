@@ -0,0 +1,29 @@
+use crate::core::mir::MirFunction;
+
+/// split fns in2 at most `units` groups of about equal size, biggest fn first in2 the lightest unit
+/// names keep their input order in each unit so output is deterministic
+pub fn partition(functions: &[MirFunction], units: usize) -> Vec<Vec<String>> {
+    let units = units.clamp(1, functions.len().max(1));
+    let mut by_size: Vec<(usize, usize)> = functions.iter().enumerate().map(|(i, f)| (size(f), i)).collect();
+    by_size.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let mut loads = vec![0; units];
+    let mut assigned = vec![0; functions.len()];
+    for (weight, index) in by_size {
+        let lightest = (0..units).min_by_key(|&u| loads[u]).unwrap_or(0);
+        loads[lightest] += weight;
+        assigned[index] = lightest;
+    }
+
+    let mut partition = vec![Vec::new(); units];
+    for (function, unit) in functions.iter().zip(assigned) {
+        partition[unit].push(function.name.clone());
+    }
+    partition.retain(|unit| !unit.is_empty());
+    partition
+}
+
+/// rough codegen cost, evry fn costs at least 1 so empty ones still spread out
+fn size(function: &MirFunction) -> usize {
+    1 + function.basic_blocks.iter().map(|bb| bb.instructions.len()).sum::<usize>()
+}
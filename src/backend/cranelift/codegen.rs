@@ -0,0 +1,157 @@
+use crate::backend::cranelift::instructions::{FunctionTranslator, Symbols};
+use crate::backend::cranelift::runtime;
+use crate::backend::cranelift::types::{clif_type, is_aggregate};
+use crate::backend::ports::codegen::{BackendInputType, CodeGen, CodeGenError, Module, OptimizationLevel};
+use crate::backend::stack_usage::FrameInfo;
+use crate::core::mir::{MirFunction, PANIC_FN};
+use crate::core::types::ty::Type;
+use cranelift_codegen::ir::{AbiParam, Signature, UserFuncName};
+use cranelift_codegen::isa::OwnedTargetIsa;
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_codegen::Context;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_module::{default_libcall_names, Linkage, Module as _};
+use cranelift_object::{ObjectBuilder, ObjectModule};
+
+/// finished object + what the emitter needs 2 report on it
+/// cranelift compiles each fn as its defined so codegen hands over bytes not ir
+pub(crate) struct CraneliftModuleData {
+    pub object: Vec<u8>,
+    /// vcode disassembly of every fn 4 -S
+    pub assembly: String,
+    pub frames: Vec<FrameInfo>,
+}
+
+/// cranelift code generator - compiles MIR straight 2 an object file
+/// much faster than llvm at the cost of weaker code, meant 4 debug builds
+pub struct CraneliftCodeGen {
+    opt_level: OptimizationLevel,
+    target_triple: Option<String>,
+}
+
+impl CraneliftCodeGen {
+    pub fn new() -> Self {
+        Self {
+            opt_level: OptimizationLevel::None,
+            target_triple: None,
+        }
+    }
+
+    /// host isa unless a triple was set
+    fn isa(&self) -> Result<OwnedTargetIsa, CodeGenError> {
+        let mut flags = settings::builder();
+        let opt_level = match self.opt_level {
+            OptimizationLevel::None => "none",
+            OptimizationLevel::Size | OptimizationLevel::SizePerformance => "speed_and_size",
+            _ => "speed",
+        };
+        let invalid = |e: settings::SetError| CodeGenError::GenerationFailed(e.to_string());
+        flags.set("opt_level", opt_level).map_err(invalid)?;
+        // pic since the linker driver produces pie executables by default
+        flags.set("is_pic", "true").map_err(invalid)?;
+
+        let builder = match self.target_triple {
+            Some(ref triple) => cranelift_codegen::isa::lookup_by_name(triple)
+                .map_err(|e| CodeGenError::InvalidTarget(format!("{}: {}", triple, e)))?,
+            None => cranelift_native::builder().map_err(|e| CodeGenError::InvalidTarget(e.to_string()))?,
+        };
+        builder
+            .finish(settings::Flags::new(flags))
+            .map_err(|e| CodeGenError::InvalidTarget(e.to_string()))
+    }
+
+    fn signature(module: &ObjectModule, mir_func: &MirFunction) -> Result<Signature, CodeGenError> {
+        let ptr_ty = module.target_config().pointer_type();
+        let mut sig = module.make_signature();
+        // no c abi 4 by value aggregates yet
+        let by_value = mir_func.params.iter().map(|p| &p.type_).chain(mir_func.return_type.as_ref()).find(|t| is_aggregate(t));
+        if let Some(ty) = by_value {
+            let what = match ty {
+                Type::Struct(s) => format!("struct '{}'", s.name),
+                _ => "an array".to_string(),
+            };
+            return Err(CodeGenError::UnsupportedFeature(format!(
+                "cranelift backend cannot pass {} by value in '{}', use a ref",
+                what, mir_func.name
+            )));
+        }
+        for param in &mir_func.params {
+            sig.params.extend(clif_type(&param.type_, ptr_ty).map(AbiParam::new));
+        }
+        sig.returns.extend(mir_func.return_type.as_ref().and_then(|t| clif_type(t, ptr_ty)).map(AbiParam::new));
+        Ok(sig)
+    }
+}
+
+impl Default for CraneliftCodeGen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeGen for CraneliftCodeGen {
+    fn generate_from_mir(&mut self, mir_functions: &[MirFunction]) -> Result<Module, CodeGenError> {
+        let builder = ObjectBuilder::new(self.isa()?, "emerald_module", default_libcall_names())
+            .map_err(|e| CodeGenError::GenerationFailed(e.to_string()))?;
+        let mut module = ObjectModule::new(builder);
+        let mut symbols = Symbols::default();
+
+        // declare evrythng first so calls dont depend on definition order
+        let mut ids = Vec::with_capacity(mir_functions.len());
+        for mir_func in mir_functions {
+            let sig = Self::signature(&module, mir_func)?;
+            let id = module
+                .declare_function(&mir_func.name, Linkage::Export, &sig)
+                .map_err(|e| CodeGenError::GenerationFailed(e.to_string()))?;
+            symbols.functions.insert(mir_func.name.clone(), id);
+            ids.push((id, sig));
+        }
+
+        let mut ctx = Context::new();
+        let mut builder_ctx = FunctionBuilderContext::new();
+        let mut assembly = String::new();
+        let mut frames = Vec::new();
+        for (mir_func, (id, sig)) in mir_functions.iter().zip(ids) {
+            // no per fn isa flags in cranelift, @target_feature fns get just the baseline clone
+            ctx.func.signature = sig;
+            ctx.func.name = UserFuncName::user(0, id.as_u32());
+            ctx.set_disasm(true);
+            let builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+            let callees = FunctionTranslator::new(builder, &mut module, &mut symbols).translate(mir_func)?;
+
+            module
+                .define_function(id, &mut ctx)
+                .map_err(|e| CodeGenError::GenerationFailed(format!("'{}': {:?}", mir_func.name, e)))?;
+
+            let frame_size = ctx.func.sized_stack_slots.values().map(|s| s.size as u64).sum();
+            frames.push(FrameInfo { name: mir_func.name.clone(), frame_size, dynamic: false, callees });
+            if let Some(vcode) = ctx.compiled_code().and_then(|c| c.vcode.as_ref()) {
+                assembly.push_str(&format!("{}:\n{}\n", mir_func.name, vcode));
+            }
+            module.clear_context(&mut ctx);
+        }
+        // asserts + bounds checks call the panic fn, the program rarely defines it
+        if let Some(&id) = symbols.functions.get(PANIC_FN).filter(|_| !mir_functions.iter().any(|f| f.name == PANIC_FN)) {
+            runtime::define_panic(&mut module, id)?;
+        }
+
+        let object = module
+            .finish()
+            .emit()
+            .map_err(|e| CodeGenError::GenerationFailed(format!("cannot write object: {}", e)))?;
+        let data = CraneliftModuleData { object, assembly, frames };
+        Ok(Module::with_data("emerald_module".to_string(), Box::new(data)))
+    }
+
+    fn set_optimization_level(&mut self, level: OptimizationLevel) {
+        self.opt_level = level;
+    }
+
+    fn set_target_triple(&mut self, triple: String) {
+        self.target_triple = Some(triple);
+    }
+
+    fn preferred_input(&self) -> BackendInputType {
+        BackendInputType::Mir
+    }
+}
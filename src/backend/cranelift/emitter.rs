@@ -0,0 +1,111 @@
+use crate::backend::cranelift::codegen::CraneliftModuleData;
+use crate::backend::link::{self, LinkOptions, LinkerFlavor};
+use crate::backend::ports::codegen::Module;
+use crate::backend::ports::emitter::{EmitError, Emitter};
+use crate::backend::stack_usage::FrameInfo;
+use std::fs;
+use std::path::Path;
+
+/// cranelift emitter - writes the object codegen produced, links it 4 binaries
+pub struct CraneliftEmitter {
+    link_options: LinkOptions,
+    embed_bitcode: bool,
+}
+
+impl CraneliftEmitter {
+    pub fn new() -> Self {
+        Self {
+            link_options: LinkOptions::default(),
+            embed_bitcode: false,
+        }
+    }
+
+    fn get_data<'m>(&self, module: &'m Module) -> Result<&'m CraneliftModuleData, EmitError> {
+        if self.embed_bitcode {
+            return Err(EmitError::EmissionFailed(
+                "Cranelift backend cannot embed LLVM bitcode (-C embed-bitcode)".to_string(),
+            ));
+        }
+        module
+            .data
+            .as_ref()
+            .and_then(|d| d.downcast_ref::<CraneliftModuleData>())
+            .ok_or_else(|| EmitError::EmissionFailed("Module does not contain a Cranelift object".to_string()))
+    }
+}
+
+impl Default for CraneliftEmitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Emitter for CraneliftEmitter {
+    fn emit_binary(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let data = self.get_data(module)?;
+        let obj_path = if self.link_options.flavor == LinkerFlavor::Msvc {
+            output.with_extension("obj")
+        } else {
+            output.with_extension("o")
+        };
+        fs::write(&obj_path, &data.object)?;
+
+        // link obj into the executable w/ the system linker driver
+        let linked = link::link(&[obj_path.as_path()], output, &self.link_options);
+        if !self.link_options.keeps_objects() {
+            let _ = fs::remove_file(&obj_path);
+        }
+        linked
+    }
+
+    fn emit_assembly(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        // vcode listing, not something an assembler accepts
+        let data = self.get_data(module)?;
+        fs::write(output, &data.assembly)?;
+        Ok(())
+    }
+
+    fn emit_llvm_ir(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(EmitError::EmissionFailed(
+            "Cranelift backend does not produce LLVM IR, use --llvm".to_string(),
+        ))
+    }
+
+    fn emit_object(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let data = self.get_data(module)?;
+        fs::write(output, &data.object)?;
+        link::compress_debug_sections(output, &self.link_options)
+    }
+
+    /// objects r always pic, no visibility control so evry definition is exported
+    fn emit_static_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let obj_path = output.with_extension("o");
+        fs::write(&obj_path, &self.get_data(module)?.object)?;
+        let archived = link::archive(&[obj_path.as_path()], output, &self.link_options);
+        let _ = fs::remove_file(&obj_path);
+        archived
+    }
+
+    fn emit_shared_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let obj_path = output.with_extension("o");
+        fs::write(&obj_path, &self.get_data(module)?.object)?;
+        let options = self.link_options.clone().with_shared(true);
+        let linked = link::link(&[obj_path.as_path()], output, &options);
+        if !options.keeps_objects() {
+            let _ = fs::remove_file(&obj_path);
+        }
+        linked
+    }
+
+    fn stack_frames(&self, module: &Module) -> Result<Vec<FrameInfo>, EmitError> {
+        Ok(self.get_data(module)?.frames.clone())
+    }
+
+    fn set_link_options(&mut self, options: LinkOptions) {
+        self.link_options = options;
+    }
+
+    fn set_embed_bitcode(&mut self, embed: bool) {
+        self.embed_bitcode = embed;
+    }
+}
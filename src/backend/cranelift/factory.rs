@@ -0,0 +1,32 @@
+use crate::backend::factory::{BackendCapabilities, BackendFactory, BackendType, BackendError, TargetSupport};
+use crate::backend::ports::codegen::BackendInputType;
+use crate::backend::ports::{CodeGen, Emitter, Optimizer};
+use crate::backend::cranelift::codegen::CraneliftCodeGen;
+use crate::backend::cranelift::optimizer::CraneliftOptimizer;
+use crate::backend::cranelift::emitter::CraneliftEmitter;
+
+/// Cranelift backend factory
+pub struct CraneliftBackendFactory;
+
+impl BackendFactory for CraneliftBackendFactory {
+    fn create_codegen(&self) -> Result<Box<dyn CodeGen>, BackendError> {
+        Ok(Box::new(CraneliftCodeGen::new()))
+    }
+
+    fn create_optimizer(&self) -> Result<Box<dyn Optimizer>, BackendError> {
+        Ok(Box::new(CraneliftOptimizer::new()))
+    }
+
+    fn create_emitter(&self) -> Result<Box<dyn Emitter>, BackendError> {
+        Ok(Box::new(CraneliftEmitter::new()))
+    }
+
+    fn backend_type(&self) -> BackendType {
+        BackendType::Cranelift
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        // cranelift-codegen is built w/ only its host-arch isa
+        BackendCapabilities { targets: TargetSupport::Host, debug_info: false, jit: false, preferred_input: BackendInputType::Mir }
+    }
+}
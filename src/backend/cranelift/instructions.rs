@@ -0,0 +1,760 @@
+use crate::backend::cranelift::types::{align_of, clif_type, is_aggregate, is_unsigned, size_of, stride_of, struct_layout};
+use crate::backend::ports::codegen::CodeGenError;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Constant, Local, Operand};
+use crate::core::mir::MirFunction;
+use crate::core::types::composite::StructType;
+use crate::core::types::ty::Type;
+use cranelift_codegen::entity::EntityRef;
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{
+    types, AbiParam, Block, Endianness, FuncRef, InstBuilder, MemFlags, StackSlotData, StackSlotKind, TrapCode, Value,
+};
+use cranelift_frontend::{FunctionBuilder, Switch, Variable};
+use cranelift_module::{DataDescription, DataId, FuncId, Linkage, Module};
+use cranelift_object::ObjectModule;
+use std::collections::HashMap;
+
+/// symbols shared by every fn of the object being built
+#[derive(Default)]
+pub struct Symbols {
+    pub functions: HashMap<String, FuncId>,
+    /// read only data (str literals, const tables) keyed by contents + align
+    pub data: HashMap<(Vec<u8>, u64), DataId>,
+}
+
+/// lowers one MIR fn thru a cranelift FunctionBuilder
+/// every MIR local is a frontend Variable so reassigned locals + phis get ssa form 4 free
+pub struct FunctionTranslator<'a> {
+    builder: FunctionBuilder<'a>,
+    module: &'a mut ObjectModule,
+    symbols: &'a mut Symbols,
+    ptr_ty: types::Type,
+    local_types: HashMap<usize, Type>,
+    var_types: HashMap<usize, types::Type>,
+    blocks: Vec<Block>,
+    /// phi inputs assigned at the end of the predecessor: pred -> (dest, value, type)
+    phi_moves: HashMap<usize, Vec<(Local, Operand, Type)>>,
+    return_type: Option<Type>,
+    /// direct callees 4 stack usage reports
+    callees: Vec<String>,
+}
+
+impl<'a> FunctionTranslator<'a> {
+    pub fn new(builder: FunctionBuilder<'a>, module: &'a mut ObjectModule, symbols: &'a mut Symbols) -> Self {
+        let ptr_ty = module.target_config().pointer_type();
+        Self {
+            builder,
+            module,
+            symbols,
+            ptr_ty,
+            local_types: HashMap::new(),
+            var_types: HashMap::new(),
+            blocks: Vec::new(),
+            phi_moves: HashMap::new(),
+            return_type: None,
+            callees: Vec::new(),
+        }
+    }
+
+    /// build the body of `mir_func`, returns the fns it calls directly
+    pub fn translate(mut self, mir_func: &MirFunction) -> Result<Vec<String>, CodeGenError> {
+        self.return_type = mir_func.return_type.clone();
+        for info in &mir_func.locals {
+            self.local_types.insert(info.local.id, info.type_.clone());
+            self.declare_local(info.local, &info.type_);
+        }
+        for param in &mir_func.params {
+            self.local_types.entry(param.local.id).or_insert_with(|| param.type_.clone());
+            self.declare_local(param.local, &param.type_);
+        }
+
+        // separate entry block, cranelift doesnt allow branches back 2 the entry
+        let entry = self.builder.create_block();
+        self.builder.append_block_params_for_function_params(entry);
+        self.blocks = mir_func.basic_blocks.iter().map(|_| self.builder.create_block()).collect();
+        for bb in &mir_func.basic_blocks {
+            for inst in &bb.instructions {
+                if let Instruction::Phi { dest, type_, incoming } = inst {
+                    for (value, pred) in incoming {
+                        self.phi_moves.entry(*pred).or_default().push((*dest, value.clone(), type_.clone()));
+                    }
+                }
+            }
+        }
+
+        self.builder.switch_to_block(entry);
+        let args = self.builder.block_params(entry).to_vec();
+        for (param, arg) in mir_func.params.iter().zip(args) {
+            self.define(param.local, arg);
+        }
+        let first = self.block(mir_func.entry_block)?;
+        self.builder.ins().jump(first, &[]);
+
+        for (idx, bb) in mir_func.basic_blocks.iter().enumerate() {
+            self.builder.switch_to_block(self.blocks[idx]);
+            let mut terminated = false;
+            for inst in &bb.instructions {
+                if is_terminator(inst) {
+                    self.emit_phi_moves(idx)?;
+                }
+                if self.translate_instruction(inst)? {
+                    // mir sometimes appends a jump after a ret, the rest is dead
+                    terminated = true;
+                    break;
+                }
+            }
+            if !terminated {
+                self.emit_phi_moves(idx)?;
+                self.fall_off_end();
+            }
+        }
+
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+        Ok(self.callees)
+    }
+
+    fn declare_local(&mut self, local: Local, ty: &Type) {
+        if self.var_types.contains_key(&local.id) {
+            return;
+        }
+        // void locals (eg the dest of a void call) r never read, any type will do
+        let clif = clif_type(ty, self.ptr_ty).unwrap_or(types::I8);
+        self.builder.declare_var(Variable::new(local.id), clif);
+        self.var_types.insert(local.id, clif);
+    }
+
+    fn define(&mut self, local: Local, value: Value) {
+        if !self.var_types.contains_key(&local.id) {
+            let ty = self.builder.func.dfg.value_type(value);
+            self.builder.declare_var(Variable::new(local.id), ty);
+            self.var_types.insert(local.id, ty);
+        }
+        let value = self.coerce(value, self.var_types[&local.id], false);
+        self.builder.def_var(Variable::new(local.id), value);
+    }
+
+    fn block(&self, id: usize) -> Result<Block, CodeGenError> {
+        self.blocks
+            .get(id)
+            .copied()
+            .ok_or_else(|| CodeGenError::GenerationFailed(format!("branch to missing basic block bb{}", id)))
+    }
+
+    fn emit_phi_moves(&mut self, pred: usize) -> Result<(), CodeGenError> {
+        for (dest, value, ty) in self.phi_moves.get(&pred).cloned().unwrap_or_default() {
+            let value = self.typed_operand(&value, &ty)?;
+            self.define(dest, value);
+        }
+        Ok(())
+    }
+
+    /// block w/o a terminator: void fns return, anything else cant b reached
+    fn fall_off_end(&mut self) {
+        match self.return_type.as_ref().and_then(|t| clif_type(t, self.ptr_ty)) {
+            None => {
+                self.builder.ins().return_(&[]);
+            }
+            Some(_) => {
+                self.builder.ins().trap(TrapCode::unwrap_user(1));
+            }
+        }
+    }
+
+    /// lower one instruction, true if it ended the block
+    fn translate_instruction(&mut self, inst: &Instruction) -> Result<bool, CodeGenError> {
+        match inst {
+            Instruction::Add { dest, left, right, type_ }
+            | Instruction::Sub { dest, left, right, type_ }
+            | Instruction::Mul { dest, left, right, type_ }
+            | Instruction::Div { dest, left, right, type_ }
+            | Instruction::Mod { dest, left, right, type_ } => {
+                let ty = self.arith_type(*dest, type_);
+                let l = self.operand(left, ty)?;
+                let r = self.operand(right, ty)?;
+                let unsigned = is_unsigned(type_);
+                let ins = self.builder.ins();
+                let result = match (inst, ty.is_float()) {
+                    (Instruction::Add { .. }, false) => ins.iadd(l, r),
+                    (Instruction::Add { .. }, true) => ins.fadd(l, r),
+                    (Instruction::Sub { .. }, false) => ins.isub(l, r),
+                    (Instruction::Sub { .. }, true) => ins.fsub(l, r),
+                    (Instruction::Mul { .. }, false) => ins.imul(l, r),
+                    (Instruction::Mul { .. }, true) => ins.fmul(l, r),
+                    (Instruction::Div { .. }, false) if unsigned => ins.udiv(l, r),
+                    (Instruction::Div { .. }, false) => ins.sdiv(l, r),
+                    (Instruction::Div { .. }, true) => ins.fdiv(l, r),
+                    (Instruction::Mod { .. }, false) if unsigned => ins.urem(l, r),
+                    (Instruction::Mod { .. }, false) => ins.srem(l, r),
+                    // no frem in clif, same libm call llvm lowers it 2
+                    _ => self.call_libm_fmod(l, r)?,
+                };
+                self.define(*dest, result);
+            }
+            Instruction::Eq { dest, left, right }
+            | Instruction::Ne { dest, left, right }
+            | Instruction::Lt { dest, left, right }
+            | Instruction::Le { dest, left, right }
+            | Instruction::Gt { dest, left, right }
+            | Instruction::Ge { dest, left, right } => {
+                let result = self.compare(inst, left, right)?;
+                self.define(*dest, result);
+            }
+            Instruction::And { dest, left, right } | Instruction::Or { dest, left, right } => {
+                let ty = self.var_types.get(&dest.id).copied().unwrap_or(types::I8);
+                let l = self.operand(left, ty)?;
+                let r = self.operand(right, ty)?;
+                let result = match inst {
+                    Instruction::And { .. } => self.builder.ins().band(l, r),
+                    _ => self.builder.ins().bor(l, r),
+                };
+                self.define(*dest, result);
+            }
+            Instruction::Not { dest, operand } => {
+                let ty = self.operand_type(operand);
+                let value = self.operand(operand, ty)?;
+                let is_bool = matches!(self.local_types.get(&dest.id), Some(t) if clif_type(t, self.ptr_ty) == Some(types::I8));
+                let result = if is_bool {
+                    self.builder.ins().icmp_imm(IntCC::Equal, value, 0)
+                } else {
+                    self.builder.ins().bnot(value)
+                };
+                self.define(*dest, result);
+            }
+
+            Instruction::Load { dest, source, type_ } | Instruction::VolatileLoad { dest, source, type_ } => {
+                let addr = self.operand(source, self.ptr_ty)?;
+                if matches!(inst, Instruction::VolatileLoad { .. }) {
+                    // clif has no volatile flag, a fence keeps alias analysis from reusing an earlier load
+                    self.builder.ins().fence();
+                }
+                let value = if is_aggregate(type_) {
+                    // aggregate values r addresses, give the copy its own slot
+                    let slot = self.stack_slot(type_);
+                    self.copy_memory(slot, addr, type_);
+                    slot
+                } else {
+                    let ty = clif_type(type_, self.ptr_ty).unwrap_or(types::I8);
+                    self.builder.ins().load(ty, MemFlags::new(), addr, 0)
+                };
+                self.define(*dest, value);
+            }
+            Instruction::Store { dest, source, type_ } | Instruction::VolatileStore { dest, source, type_ } => {
+                let addr = self.operand(dest, self.ptr_ty)?;
+                if matches!(inst, Instruction::VolatileStore { .. }) {
+                    self.builder.ins().fence();
+                }
+                self.store(addr, source, type_)?;
+            }
+            Instruction::Alloca { dest, type_ } => {
+                let slot = self.stack_slot(type_);
+                self.define(*dest, slot);
+            }
+            Instruction::Gep { dest, base, index, type_ } => {
+                let addr = match self.gep_struct_base(base, index) {
+                    Some((s, field)) => {
+                        let base = self.operand(base, self.ptr_ty)?;
+                        let offset = struct_layout(&s, self.ptr_ty.bytes() as usize)
+                            .offsets
+                            .get(field)
+                            .copied()
+                            .ok_or_else(|| {
+                                CodeGenError::GenerationFailed(format!("struct '{}' has no field #{}", s.name, field))
+                            })?;
+                        self.builder.ins().iadd_imm(base, offset as i64)
+                    }
+                    None => self.element_address(base, index, type_)?,
+                };
+                self.define(*dest, addr);
+            }
+            Instruction::PtrOffset { dest, base, offset, type_ } => {
+                let addr = self.element_address(base, offset, type_)?;
+                self.define(*dest, addr);
+            }
+            Instruction::Memset { dest, value, count, type_ } => {
+                let addr = self.operand(dest, self.ptr_ty)?;
+                self.memset(addr, value, *count, type_)?;
+            }
+
+            Instruction::Call { dest, func, args, return_type, .. } => {
+                let results = self.call(func, args, return_type.as_ref())?;
+                if let (Some(dest), Some(result)) = (dest, results.first()) {
+                    self.define(*dest, *result);
+                }
+            }
+            Instruction::Ret { value } => {
+                let ret_ty = self.return_type.as_ref().and_then(|t| clif_type(t, self.ptr_ty));
+                match (value, ret_ty, self.return_type.clone()) {
+                    (Some(value), Some(_), Some(mir_ty)) => {
+                        let value = self.typed_operand(value, &mir_ty)?;
+                        self.builder.ins().return_(&[value]);
+                    }
+                    (None, Some(ty), _) => {
+                        // bare return in a fn w/ a result
+                        let zero = self.zero(ty);
+                        self.builder.ins().return_(&[zero]);
+                    }
+                    _ => {
+                        self.builder.ins().return_(&[]);
+                    }
+                }
+                return Ok(true);
+            }
+            Instruction::Br { condition, then_bb, else_bb } => {
+                let ty = self.operand_type(condition);
+                let cond = self.operand(condition, ty)?;
+                let (then_block, else_block) = (self.block(*then_bb)?, self.block(*else_bb)?);
+                self.builder.ins().brif(cond, then_block, &[], else_block, &[]);
+                return Ok(true);
+            }
+            Instruction::Jump { target } => {
+                let target = self.block(*target)?;
+                self.builder.ins().jump(target, &[]);
+                return Ok(true);
+            }
+            Instruction::Switch { value, arms, default } => {
+                let ty = self.operand_type(value);
+                let val = self.operand(value, ty)?;
+                // entries r unsigned, negative cases wrap at the value's width
+                let mask = if ty.bits() >= 128 { u128::MAX } else { (1u128 << ty.bits()) - 1 };
+                let mut switch = Switch::new();
+                for (case, target) in arms {
+                    switch.set_entry(*case as u128 & mask, self.block(*target)?);
+                }
+                let otherwise = self.block(*default)?;
+                switch.emit(&mut self.builder, val, otherwise);
+                return Ok(true);
+            }
+
+            // incoming values were assigned in the predecessors
+            Instruction::Phi { .. } => {}
+            Instruction::Copy { dest, source, type_ } => {
+                let ty = self.local_types.get(&dest.id).cloned().unwrap_or_else(|| type_.clone());
+                let value = self.typed_operand(source, &ty)?;
+                self.define(*dest, value);
+            }
+            // strings r bare data ptrs here, no len 2 read, concat or compare
+            Instruction::StrLen { .. } | Instruction::StrConcat { .. } | Instruction::StrEq { .. } => {
+                return Err(CodeGenError::UnsupportedFeature(
+                    "string len/concat/eq on the cranelift backend".to_string(),
+                ))
+            }
+            Instruction::MakeDyn { .. } | Instruction::DynCall { .. } => {
+                return Err(CodeGenError::UnsupportedFeature(
+                    "trait objects on the cranelift backend".to_string(),
+                ))
+            }
+            Instruction::MakeVariant { .. } | Instruction::EnumTag { .. } | Instruction::VariantField { .. } => {
+                return Err(CodeGenError::UnsupportedFeature("enums on the cranelift backend".to_string()))
+            }
+        }
+        Ok(false)
+    }
+
+    /// clif type 4 arithmetic, the dest local wins over the (often defaulted) mir type
+    fn arith_type(&self, dest: Local, ty: &Type) -> types::Type {
+        self.var_types
+            .get(&dest.id)
+            .copied()
+            .or_else(|| clif_type(ty, self.ptr_ty))
+            .unwrap_or(types::I32)
+    }
+
+    fn compare(&mut self, inst: &Instruction, left: &Operand, right: &Operand) -> Result<Value, CodeGenError> {
+        // constants take the type of the other side
+        let ty = match (left, right) {
+            (Operand::Local(_), _) => self.operand_type(left),
+            (_, Operand::Local(_)) => self.operand_type(right),
+            _ => self.operand_type(left),
+        };
+        let unsigned = [left, right]
+            .iter()
+            .any(|op| matches!(op, Operand::Local(l) if self.local_types.get(&l.id).map(is_unsigned).unwrap_or(false)));
+        let l = self.operand(left, ty)?;
+        let r = self.operand(right, ty)?;
+
+        if ty.is_float() {
+            let cc = match inst {
+                Instruction::Eq { .. } => FloatCC::Equal,
+                Instruction::Ne { .. } => FloatCC::NotEqual,
+                Instruction::Lt { .. } => FloatCC::LessThan,
+                Instruction::Le { .. } => FloatCC::LessThanOrEqual,
+                Instruction::Gt { .. } => FloatCC::GreaterThan,
+                _ => FloatCC::GreaterThanOrEqual,
+            };
+            return Ok(self.builder.ins().fcmp(cc, l, r));
+        }
+        let cc = match (inst, unsigned) {
+            (Instruction::Eq { .. }, _) => IntCC::Equal,
+            (Instruction::Ne { .. }, _) => IntCC::NotEqual,
+            (Instruction::Lt { .. }, false) => IntCC::SignedLessThan,
+            (Instruction::Lt { .. }, true) => IntCC::UnsignedLessThan,
+            (Instruction::Le { .. }, false) => IntCC::SignedLessThanOrEqual,
+            (Instruction::Le { .. }, true) => IntCC::UnsignedLessThanOrEqual,
+            (Instruction::Gt { .. }, false) => IntCC::SignedGreaterThan,
+            (Instruction::Gt { .. }, true) => IntCC::UnsignedGreaterThan,
+            (_, false) => IntCC::SignedGreaterThanOrEqual,
+            (_, true) => IntCC::UnsignedGreaterThanOrEqual,
+        };
+        Ok(self.builder.ins().icmp(cc, l, r))
+    }
+
+    /// struct + field index when a Gep indexes a struct (or ptr 2 struct) local
+    fn gep_struct_base(&self, base: &Operand, index: &Operand) -> Option<(StructType, usize)> {
+        let (base, field) = match (base, index) {
+            (Operand::Local(base), Operand::Constant(Constant::Int(idx))) => (base, *idx as usize),
+            _ => return None,
+        };
+        match self.local_types.get(&base.id)? {
+            Type::Struct(s) => Some((s.clone(), field)),
+            Type::Pointer(p) => match &*p.pointee {
+                Type::Struct(s) => Some((s.clone(), field)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// base + index * stride of `element`
+    fn element_address(&mut self, base: &Operand, index: &Operand, element: &Type) -> Result<Value, CodeGenError> {
+        let base = self.operand(base, self.ptr_ty)?;
+        let stride = stride_of(element, self.ptr_ty.bytes() as usize) as i64;
+        if let Operand::Constant(Constant::Int(i)) = index {
+            return Ok(self.builder.ins().iadd_imm(base, i * stride));
+        }
+        let index = self.operand(index, self.ptr_ty)?;
+        let offset = self.builder.ins().imul_imm(index, stride);
+        Ok(self.builder.ins().iadd(base, offset))
+    }
+
+    fn stack_slot(&mut self, ty: &Type) -> Value {
+        let ptr_bytes = self.ptr_ty.bytes() as usize;
+        let size = size_of(ty, ptr_bytes) as u32;
+        let align_shift = align_of(ty, ptr_bytes).max(1).trailing_zeros() as u8;
+        let slot = self
+            .builder
+            .create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, size, align_shift));
+        self.builder.ins().stack_addr(self.ptr_ty, slot, 0)
+    }
+
+    fn copy_memory(&mut self, dest: Value, src: Value, ty: &Type) {
+        let size = size_of(ty, self.ptr_ty.bytes() as usize) as u64;
+        let config = self.module.target_config();
+        self.builder.emit_small_memory_copy(config, dest, src, size, 1, 1, true, MemFlags::new());
+    }
+
+    fn store(&mut self, addr: Value, source: &Operand, ty: &Type) -> Result<(), CodeGenError> {
+        if is_aggregate(ty) {
+            if let Operand::Constant(Constant::Null) = source {
+                let size = size_of(ty, self.ptr_ty.bytes() as usize) as u64;
+                let config = self.module.target_config();
+                self.builder.emit_small_memset(config, addr, 0, size, 1, MemFlags::new());
+            } else {
+                let src = self.typed_operand(source, ty)?;
+                self.copy_memory(addr, src, ty);
+            }
+            return Ok(());
+        }
+        let value = self.typed_operand(source, ty)?;
+        self.builder.ins().store(MemFlags::new(), value, addr, 0);
+        Ok(())
+    }
+
+    fn memset(&mut self, addr: Value, value: &Operand, count: usize, ty: &Type) -> Result<(), CodeGenError> {
+        let ptr_bytes = self.ptr_ty.bytes() as usize;
+        let stride = stride_of(ty, ptr_bytes);
+        let byte = match value {
+            Operand::Constant(Constant::Int(0) | Constant::Bool(false) | Constant::Null) => Some(0),
+            Operand::Constant(Constant::Float(f)) if *f == 0.0 && f.is_sign_positive() => Some(0),
+            Operand::Constant(Constant::Int(n)) if size_of(ty, ptr_bytes) == 1 => Some(*n as u8),
+            _ => None,
+        };
+        if let Some(byte) = byte {
+            let config = self.module.target_config();
+            self.builder.emit_small_memset(config, addr, byte, (stride * count) as u64, 1, MemFlags::new());
+            return Ok(());
+        }
+        let value = self.typed_operand(value, ty)?;
+        for i in 0..count {
+            self.builder.ins().store(MemFlags::new(), value, addr, (i * stride) as i32);
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, func: &Operand, args: &[Operand], return_type: Option<&Type>) -> Result<Vec<Value>, CodeGenError> {
+        let returns = return_type.and_then(|t| clif_type(t, self.ptr_ty));
+        match func {
+            Operand::Function(f) => {
+                let params: Vec<types::Type> = args.iter().map(|a| self.operand_type(a)).collect();
+                let func_ref = self.func_ref(&f.name, &params, returns)?;
+                if !self.callees.contains(&f.name) {
+                    self.callees.push(f.name.clone());
+                }
+                let sig = self.builder.func.dfg.ext_funcs[func_ref].signature;
+                let param_types: Vec<types::Type> =
+                    self.builder.func.dfg.signatures[sig].params.iter().map(|p| p.value_type).collect();
+                let mut values = Vec::with_capacity(args.len());
+                for (arg, ty) in args.iter().zip(param_types) {
+                    values.push(self.operand(arg, ty)?);
+                }
+                let call = self.builder.ins().call(func_ref, &values);
+                Ok(self.builder.inst_results(call).to_vec())
+            }
+            _ => {
+                // call thru a fn ptr, signature comes from the args at the call site
+                let callee = self.operand(func, self.ptr_ty)?;
+                let mut sig = self.module.make_signature();
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    let ty = self.operand_type(arg);
+                    sig.params.push(AbiParam::new(ty));
+                    values.push(self.operand(arg, ty)?);
+                }
+                if let Some(ty) = returns {
+                    sig.returns.push(AbiParam::new(ty));
+                }
+                let sig = self.builder.import_signature(sig);
+                let call = self.builder.ins().call_indirect(sig, callee, &values);
+                Ok(self.builder.inst_results(call).to_vec())
+            }
+        }
+    }
+
+    fn call_libm_fmod(&mut self, l: Value, r: Value) -> Result<Value, CodeGenError> {
+        let func_ref = self.func_ref("fmod", &[types::F64, types::F64], Some(types::F64))?;
+        let call = self.builder.ins().call(func_ref, &[l, r]);
+        Ok(self.builder.inst_results(call)[0])
+    }
+
+    /// fn defined in this module, or an import declared w/ the signature of its first use
+    fn func_ref(&mut self, name: &str, params: &[types::Type], returns: Option<types::Type>) -> Result<FuncRef, CodeGenError> {
+        let id = match self.symbols.functions.get(name) {
+            Some(id) => *id,
+            None => {
+                let mut sig = self.module.make_signature();
+                sig.params.extend(params.iter().map(|t| AbiParam::new(*t)));
+                sig.returns.extend(returns.map(AbiParam::new));
+                let id = self
+                    .module
+                    .declare_function(name, Linkage::Import, &sig)
+                    .map_err(|e| CodeGenError::GenerationFailed(format!("cannot declare '{}': {}", name, e)))?;
+                self.symbols.functions.insert(name.to_string(), id);
+                id
+            }
+        };
+        Ok(self.module.declare_func_in_func(id, self.builder.func))
+    }
+
+    /// clif type an operand evaluates 2 w/o any context
+    fn operand_type(&self, op: &Operand) -> types::Type {
+        match op {
+            Operand::Local(l) => self.var_types.get(&l.id).copied().unwrap_or(types::I32),
+            Operand::Constant(Constant::Float(_)) => types::F64,
+            Operand::Constant(Constant::Bool(_)) => types::I8,
+            Operand::Constant(Constant::Int(_) | Constant::Char(_)) => types::I32,
+            _ => self.ptr_ty,
+        }
+    }
+
+    /// operand as a value of clif type `ty`
+    fn operand(&mut self, op: &Operand, ty: types::Type) -> Result<Value, CodeGenError> {
+        let value = match op {
+            Operand::Constant(c) => return self.constant(c, ty),
+            Operand::Local(l) => {
+                if !self.var_types.contains_key(&l.id) {
+                    return Err(CodeGenError::GenerationFailed(format!("use of undeclared local _{}", l.id)));
+                }
+                let unsigned = self.local_types.get(&l.id).map(is_unsigned).unwrap_or(false);
+                let value = self.builder.use_var(Variable::new(l.id));
+                return Ok(self.coerce(value, ty, unsigned));
+            }
+            Operand::Function(f) => {
+                let id = *self.symbols.functions.get(&f.name).ok_or_else(|| {
+                    CodeGenError::UnsupportedFeature(format!("address of undeclared function '{}'", f.name))
+                })?;
+                let func_ref = self.module.declare_func_in_func(id, self.builder.func);
+                self.builder.ins().func_addr(self.ptr_ty, func_ref)
+            }
+        };
+        Ok(self.coerce(value, ty, false))
+    }
+
+    /// operand of MIR type `ty`, aggregate constants become read only data
+    fn typed_operand(&mut self, op: &Operand, ty: &Type) -> Result<Value, CodeGenError> {
+        match op {
+            Operand::Constant(c @ Constant::Aggregate(_)) => {
+                let ptr_bytes = self.ptr_ty.bytes() as usize;
+                let mut bytes = vec![0u8; size_of(ty, ptr_bytes)];
+                let mut strings = Vec::new();
+                self.layout_constant(c, ty, 0, &mut bytes, &mut strings)?;
+                self.data_address(bytes, align_of(ty, ptr_bytes) as u64, strings)
+            }
+            _ => {
+                let clif = clif_type(ty, self.ptr_ty).unwrap_or(types::I8);
+                self.operand(op, clif)
+            }
+        }
+    }
+
+    fn constant(&mut self, c: &Constant, ty: types::Type) -> Result<Value, CodeGenError> {
+        let n = match c {
+            Constant::Int(n) => *n,
+            Constant::Bool(b) => *b as i64,
+            Constant::Char(ch) => *ch as i64,
+            Constant::Null => 0,
+            Constant::Float(f) => {
+                let value = self.builder.ins().f64const(*f);
+                return Ok(self.coerce(value, ty, false));
+            }
+            Constant::String(s) => {
+                let mut bytes = s.as_bytes().to_vec();
+                bytes.push(0);
+                let addr = self.data_address(bytes, 1, Vec::new())?;
+                return Ok(self.coerce(addr, ty, false));
+            }
+            Constant::Aggregate(_) => {
+                return Err(CodeGenError::UnsupportedFeature(
+                    "aggregate constant without a type".to_string(),
+                ))
+            }
+        };
+        Ok(match ty {
+            types::F64 => self.builder.ins().f64const(n as f64),
+            types::F32 => self.builder.ins().f32const(n as f32),
+            _ => self.builder.ins().iconst(ty, n),
+        })
+    }
+
+    fn zero(&mut self, ty: types::Type) -> Value {
+        match ty {
+            types::F64 => self.builder.ins().f64const(0.0),
+            types::F32 => self.builder.ins().f32const(0.0),
+            _ => self.builder.ins().iconst(ty, 0),
+        }
+    }
+
+    /// int widths + int/float conversions mir leaves implicit
+    fn coerce(&mut self, value: Value, to: types::Type, unsigned: bool) -> Value {
+        let from = self.builder.func.dfg.value_type(value);
+        if from == to {
+            return value;
+        }
+        let ins = self.builder.ins();
+        match (from.is_float(), to.is_float()) {
+            (false, false) if from.bits() < to.bits() => {
+                // i8 is bool or byte, both unsigned
+                if unsigned || from == types::I8 {
+                    ins.uextend(to, value)
+                } else {
+                    ins.sextend(to, value)
+                }
+            }
+            (false, false) => ins.ireduce(to, value),
+            (false, true) if unsigned => ins.fcvt_from_uint(to, value),
+            (false, true) => ins.fcvt_from_sint(to, value),
+            (true, false) => ins.fcvt_to_sint_sat(to, value),
+            (true, true) if from.bits() < to.bits() => ins.fpromote(to, value),
+            (true, true) => ins.fdemote(to, value),
+        }
+    }
+
+    /// serialize a comptime constant w/ the target's layout, strings become relocations
+    fn layout_constant(
+        &self,
+        c: &Constant,
+        ty: &Type,
+        at: usize,
+        bytes: &mut [u8],
+        strings: &mut Vec<(usize, Vec<u8>)>,
+    ) -> Result<(), CodeGenError> {
+        let ptr_bytes = self.ptr_ty.bytes() as usize;
+        match (c, ty) {
+            (Constant::Aggregate(values), Type::Array(a)) => {
+                let stride = stride_of(&a.element, ptr_bytes);
+                for (i, value) in values.iter().enumerate().take(a.size) {
+                    self.layout_constant(value, &a.element, at + i * stride, bytes, strings)?;
+                }
+            }
+            (Constant::Aggregate(values), Type::Struct(s)) => {
+                let layout = struct_layout(s, ptr_bytes);
+                for ((value, field), offset) in values.iter().zip(&s.fields).zip(layout.offsets) {
+                    self.layout_constant(value, &field.type_, at + offset, bytes, strings)?;
+                }
+            }
+            (Constant::Aggregate(_), other) => {
+                return Err(CodeGenError::UnsupportedFeature(format!(
+                    "aggregate constant of non aggregate type {:?}",
+                    other
+                )))
+            }
+            (Constant::String(s), _) => {
+                let mut contents = s.as_bytes().to_vec();
+                contents.push(0);
+                strings.push((at, contents));
+            }
+            (Constant::Null, _) => {}
+            (Constant::Float(f), _) => {
+                let size = size_of(ty, ptr_bytes);
+                let raw = if size == 4 { (*f as f32).to_bits() as u64 } else { f.to_bits() };
+                self.write_int(bytes, at, raw, size);
+            }
+            (Constant::Int(_) | Constant::Char(_) | Constant::Bool(_), _) => {
+                let n = match c {
+                    Constant::Int(n) => *n,
+                    Constant::Char(ch) => *ch as i64,
+                    Constant::Bool(b) => *b as i64,
+                    _ => 0,
+                };
+                self.write_int(bytes, at, n as u64, size_of(ty, ptr_bytes));
+            }
+        }
+        Ok(())
+    }
+
+    fn write_int(&self, bytes: &mut [u8], at: usize, value: u64, size: usize) {
+        let size = size.min(8);
+        let Some(dest) = bytes.get_mut(at..at + size) else { return };
+        match self.module.isa().endianness() {
+            Endianness::Little => dest.copy_from_slice(&value.to_le_bytes()[..size]),
+            Endianness::Big => dest.copy_from_slice(&value.to_be_bytes()[8 - size..]),
+        }
+    }
+
+    /// address of a read only data object, identical contents share one symbol
+    fn data_address(&mut self, bytes: Vec<u8>, align: u64, strings: Vec<(usize, Vec<u8>)>) -> Result<Value, CodeGenError> {
+        let id = self.define_data(bytes, align, strings)?;
+        let global = self.module.declare_data_in_func(id, self.builder.func);
+        Ok(self.builder.ins().symbol_value(self.ptr_ty, global))
+    }
+
+    fn define_data(&mut self, bytes: Vec<u8>, align: u64, strings: Vec<(usize, Vec<u8>)>) -> Result<DataId, CodeGenError> {
+        let key = (bytes, align);
+        // data w/ relocations isnt shared, its bytes alone dont identify it
+        let shared = strings.is_empty();
+        if shared {
+            if let Some(id) = self.symbols.data.get(&key) {
+                return Ok(*id);
+            }
+        }
+        let failed = |e: cranelift_module::ModuleError| CodeGenError::GenerationFailed(format!("cannot define data: {}", e));
+        let id = self.module.declare_anonymous_data(false, false).map_err(failed)?;
+        let mut desc = DataDescription::new();
+        desc.define(key.0.clone().into_boxed_slice());
+        desc.set_align(align.max(1));
+        for (offset, contents) in strings {
+            let string = self.define_data(contents, 1, Vec::new())?;
+            let global = self.module.declare_data_in_data(string, &mut desc);
+            desc.write_data_addr(offset as u32, global, 0);
+        }
+        self.module.define_data(id, &desc).map_err(failed)?;
+        if shared {
+            self.symbols.data.insert(key, id);
+        }
+        Ok(id)
+    }
+}
+
+fn is_terminator(inst: &Instruction) -> bool {
+    matches!(inst, Instruction::Ret { .. } | Instruction::Br { .. } | Instruction::Jump { .. } | Instruction::Switch { .. })
+}
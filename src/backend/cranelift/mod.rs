@@ -0,0 +1,12 @@
+pub mod factory;
+pub mod codegen;
+pub mod optimizer;
+pub mod emitter;
+pub mod types;
+pub mod instructions;
+pub mod runtime;
+
+pub use factory::CraneliftBackendFactory;
+pub use codegen::CraneliftCodeGen;
+pub use optimizer::CraneliftOptimizer;
+pub use emitter::CraneliftEmitter;
@@ -0,0 +1,34 @@
+use crate::backend::cranelift::codegen::CraneliftModuleData;
+use crate::backend::ports::codegen::Module;
+use crate::backend::ports::optimizer::{OptimizationError, OptimizationPass, Optimizer};
+
+/// cranelift optimizer - the egraph passes already ran when each fn was compiled
+/// (opt_level set on the codegen), so this only checks the module came from cranelift
+pub struct CraneliftOptimizer;
+
+impl CraneliftOptimizer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CraneliftOptimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Optimizer for CraneliftOptimizer {
+    fn optimize(&mut self, module: &mut Module) -> Result<(), OptimizationError> {
+        match module.data.as_ref().and_then(|d| d.downcast_ref::<CraneliftModuleData>()) {
+            Some(_) => Ok(()),
+            None => Err(OptimizationError::OptimizationFailed(
+                "Module does not contain a Cranelift object".to_string(),
+            )),
+        }
+    }
+
+    fn add_pass(&mut self, _pass: OptimizationPass) {
+        // cranelift has no pass pipeline 2 extend
+    }
+}
@@ -0,0 +1,115 @@
+use crate::backend::ports::codegen::CodeGenError;
+use cranelift_codegen::ir::{types, AbiParam, FuncRef, InstBuilder, MemFlags, StackSlotData, StackSlotKind, TrapCode, UserFuncName, Value};
+use cranelift_codegen::Context;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_module::{DataDescription, FuncId, Linkage, Module};
+use cranelift_object::ObjectModule;
+
+// the runtime support a program needs, same as the llvm backend's runtime.rs. preemptible is
+// cranelift's weak, each object carries a copy + the linker keeps 1
+
+/// gives `emerald_panic(msg : string, file : string, line : int)` the body `id` is missing:
+/// `panicked at file:line: msg` on stderr, then abort
+/// strings r bare nul terminated ptrs here + theres no variadic dprintf, so its pieced 2gether w/ write
+pub(crate) fn define_panic(module: &mut ObjectModule, id: FuncId) -> Result<(), CodeGenError> {
+    let ptr_ty = module.target_config().pointer_type();
+    let decl = module.declarations().get_function_decl(id);
+    let sig = decl.signature.clone();
+    let name = decl.name.clone().unwrap_or_default();
+    let params: Vec<types::Type> = sig.params.iter().map(|p| p.value_type).collect();
+    if !matches!(params.as_slice(), [m, f, l] if *m == ptr_ty && *f == ptr_ty && l.is_int()) {
+        return Ok(());
+    }
+    let failed = |e: cranelift_module::ModuleError| CodeGenError::GenerationFailed(format!("'{}': {}", name, e));
+    module.declare_function(&name, Linkage::Preemptible, &sig).map_err(failed)?;
+
+    let import = |module: &mut ObjectModule, symbol: &str, params: &[types::Type], returns: Option<types::Type>| {
+        let mut sig = module.make_signature();
+        sig.params.extend(params.iter().map(|t| AbiParam::new(*t)));
+        sig.returns.extend(returns.map(AbiParam::new));
+        module.declare_function(symbol, Linkage::Import, &sig).map_err(failed)
+    };
+    let write = import(module, "write", &[types::I32, ptr_ty, ptr_ty], Some(ptr_ty))?;
+    let strlen = import(module, "strlen", &[ptr_ty], Some(ptr_ty))?;
+    let abort = import(module, "abort", &[], None)?;
+    let mut text = Vec::new();
+    for piece in ["panicked at ", ":", ": ", "\n"] {
+        let data = module.declare_anonymous_data(false, false).map_err(failed)?;
+        let mut desc = DataDescription::new();
+        desc.define(piece.as_bytes().to_vec().into_boxed_slice());
+        module.define_data(data, &desc).map_err(failed)?;
+        text.push((data, piece.len() as i64));
+    }
+
+    let mut ctx = Context::new();
+    ctx.func.signature = sig;
+    ctx.func.name = UserFuncName::user(0, id.as_u32());
+    let mut builder_ctx = FunctionBuilderContext::new();
+    let mut b = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+    let entry = b.create_block();
+    b.append_block_params_for_function_params(entry);
+    b.switch_to_block(entry);
+    let (message, file, line) = (b.block_params(entry)[0], b.block_params(entry)[1], b.block_params(entry)[2]);
+    let write = module.declare_func_in_func(write, b.func);
+    let strlen = module.declare_func_in_func(strlen, b.func);
+    let abort = module.declare_func_in_func(abort, b.func);
+    let text: Vec<(Value, Value)> = text
+        .into_iter()
+        .map(|(data, len)| {
+            let global = module.declare_data_in_func(data, b.func);
+            (b.ins().symbol_value(ptr_ty, global), b.ins().iconst(ptr_ty, len))
+        })
+        .collect();
+    let put = |b: &mut FunctionBuilder, (addr, len): (Value, Value)| {
+        let stderr = b.ins().iconst(types::I32, 2);
+        b.ins().call(write, &[stderr, addr, len]);
+    };
+    let put_str = |b: &mut FunctionBuilder, s: Value, strlen: FuncRef| {
+        let call = b.ins().call(strlen, &[s]);
+        let len = b.inst_results(call)[0];
+        put(b, (s, len));
+    };
+
+    put(&mut b, text[0]);
+    put_str(&mut b, file, strlen);
+    put(&mut b, text[1]);
+
+    // line digits written backwards from the end of a slot, 20 fit any u64
+    let slot = b.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 24, 0));
+    let end = b.ins().stack_addr(ptr_ty, slot, 24);
+    let line = match b.func.dfg.value_type(line) {
+        types::I64 => line,
+        _ => b.ins().uextend(types::I64, line),
+    };
+    let (digit, done) = (b.create_block(), b.create_block());
+    b.append_block_param(digit, types::I64);
+    b.append_block_param(digit, ptr_ty);
+    b.append_block_param(done, ptr_ty);
+    b.ins().jump(digit, &[line, end]);
+
+    b.switch_to_block(digit);
+    let (n, at) = (b.block_params(digit)[0], b.block_params(digit)[1]);
+    let at = b.ins().iadd_imm(at, -1);
+    let rem = b.ins().urem_imm(n, 10);
+    let ascii = b.ins().iadd_imm(rem, b'0' as i64);
+    let ascii = b.ins().ireduce(types::I8, ascii);
+    b.ins().store(MemFlags::trusted(), ascii, at, 0);
+    let rest = b.ins().udiv_imm(n, 10);
+    b.ins().brif(rest, digit, &[rest, at], done, &[at]);
+
+    b.switch_to_block(done);
+    let start = b.block_params(done)[0];
+    let len = b.ins().isub(end, start);
+    put(&mut b, (start, len));
+    put(&mut b, text[2]);
+    put_str(&mut b, message, strlen);
+    put(&mut b, text[3]);
+    b.ins().call(abort, &[]);
+    b.ins().trap(TrapCode::unwrap_user(1));
+    b.seal_all_blocks();
+    b.finalize();
+
+    module
+        .define_function(id, &mut ctx)
+        .map_err(|e| CodeGenError::GenerationFailed(format!("'{}': {:?}", name, e)))
+}
@@ -0,0 +1,83 @@
+use crate::core::types::composite::StructType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use cranelift_codegen::ir::types;
+
+/// clif type holding a MIR value, None 4 void
+/// aggregates, strings and other ptr like values r carried as addresses (same as the llvm backend's i8*)
+pub fn clif_type(ty: &Type, ptr_ty: types::Type) -> Option<types::Type> {
+    match ty {
+        Type::Primitive(p) => match p {
+            PrimitiveType::Void => None,
+            PrimitiveType::Byte | PrimitiveType::UByte | PrimitiveType::Bool => Some(types::I8),
+            PrimitiveType::Int | PrimitiveType::UInt | PrimitiveType::Char => Some(types::I32),
+            PrimitiveType::Long | PrimitiveType::ULong => Some(types::I64),
+            PrimitiveType::Size => Some(ptr_ty),
+            PrimitiveType::Float => Some(types::F64),
+        },
+        _ => Some(ptr_ty),
+    }
+}
+
+/// structs + arrays live in memory, their MIR values r addresses
+pub fn is_aggregate(ty: &Type) -> bool {
+    matches!(ty, Type::Struct(_) | Type::Array(_))
+}
+
+/// picks udiv/urem, unsigned compares and zero extension
+pub fn is_unsigned(ty: &Type) -> bool {
+    matches!(ty, Type::Primitive(p) if p.is_unsigned())
+        || matches!(ty, Type::Primitive(PrimitiveType::Bool))
+}
+
+/// field offsets + size + align of a struct, declared offsets (eg from a layout attr) win
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    pub offsets: Vec<usize>,
+    pub size: usize,
+    pub align: usize,
+}
+
+pub fn struct_layout(s: &StructType, ptr_bytes: usize) -> StructLayout {
+    let mut offsets = Vec::with_capacity(s.fields.len());
+    let mut cursor = 0;
+    let mut align = s.align.unwrap_or(1).max(1);
+    for field in &s.fields {
+        let field_align = align_of(&field.type_, ptr_bytes);
+        let at = field.offset.unwrap_or_else(|| align_to(cursor, field_align));
+        offsets.push(at);
+        cursor = cursor.max(at + size_of(&field.type_, ptr_bytes));
+        align = align.max(field_align);
+    }
+    let size = s.size.unwrap_or(0).max(align_to(cursor, align));
+    StructLayout { offsets, size, align }
+}
+
+pub fn size_of(ty: &Type, ptr_bytes: usize) -> usize {
+    match ty {
+        Type::Primitive(PrimitiveType::Size) => ptr_bytes,
+        Type::Primitive(p) => p.size_in_bytes(),
+        Type::Array(a) => stride_of(&a.element, ptr_bytes) * a.size,
+        Type::Struct(s) => struct_layout(s, ptr_bytes).size,
+        _ => ptr_bytes,
+    }
+}
+
+pub fn align_of(ty: &Type, ptr_bytes: usize) -> usize {
+    match ty {
+        Type::Primitive(PrimitiveType::Size) => ptr_bytes,
+        Type::Primitive(p) => p.size_in_bytes().max(1),
+        Type::Array(a) => align_of(&a.element, ptr_bytes),
+        Type::Struct(s) => struct_layout(s, ptr_bytes).align,
+        _ => ptr_bytes,
+    }
+}
+
+/// distance between array elements
+pub fn stride_of(ty: &Type, ptr_bytes: usize) -> usize {
+    align_to(size_of(ty, ptr_bytes), align_of(ty, ptr_bytes))
+}
+
+pub fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align.max(1)) * align.max(1)
+}
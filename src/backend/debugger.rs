@@ -0,0 +1,36 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// gdb printers, gdb auto loads `<binary>-gdb.py` from next 2 the binary
+pub const GDB_PRETTY_PRINTERS: &str = include_str!("debugger/emerald_gdb.py");
+/// lldb summaries, imported w/ `command script import`
+pub const LLDB_PRETTY_PRINTERS: &str = include_str!("debugger/emerald_lldb.py");
+
+/// name lldb imports the script as, has 2 be a valid python module name
+const LLDB_SCRIPT_NAME: &str = "emerald_lldb.py";
+
+/// `a.out` -> `a.out-gdb.py`
+pub fn gdb_script_path(output: &Path) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push("-gdb.py");
+    output.with_file_name(name)
+}
+
+pub fn lldb_script_path(output: &Path) -> PathBuf {
+    output.with_file_name(LLDB_SCRIPT_NAME)
+}
+
+/// write both printer scripts next 2 `output`, returns the paths written
+pub fn write_pretty_printers(output: &Path) -> io::Result<Vec<PathBuf>> {
+    let scripts = [
+        (gdb_script_path(output), GDB_PRETTY_PRINTERS),
+        (lldb_script_path(output), LLDB_PRETTY_PRINTERS),
+    ];
+    let mut written = Vec::new();
+    for (path, contents) in scripts {
+        fs::write(&path, contents)?;
+        written.push(path);
+    }
+    Ok(written)
+}
@@ -0,0 +1,74 @@
+# gdb pretty printers 4 emerald types
+# loaded automatically as <binary>-gdb.py (see `info auto-load`) or w/ `source`
+# emc names the string + trait object structs and describes ref? T as a typedef so they can be matched by name
+
+import gdb
+import gdb.printing
+
+
+def _fields(value):
+    ty = value.type.strip_typedefs()
+    return [f.name for f in ty.fields()] if ty.code == gdb.TYPE_CODE_STRUCT else []
+
+
+class StringPrinter:
+    """string as its text, works 4 both a bare data ptr and a (data, len) pair"""
+
+    def __init__(self, value):
+        self.value = value
+
+    def to_string(self):
+        if "len" in _fields(self.value):
+            data, length = self.value["data"], int(self.value["len"])
+            if int(data) == 0:
+                return '""'
+            return data.lazy_string(length=length)
+        if int(self.value) == 0:
+            return '""'
+        return self.value.lazy_string()
+
+    def display_hint(self):
+        return "string"
+
+
+class OptionalPrinter:
+    """ref? T as `null` or the value it points at"""
+
+    def __init__(self, value):
+        self.value = value
+
+    def to_string(self):
+        if int(self.value) == 0:
+            return "null"
+        return self.value.dereference()
+
+
+class TraitObjectPrinter:
+    """dyn Trait as the trait name + its data ptr"""
+
+    def __init__(self, value):
+        self.value = value
+
+    def to_string(self):
+        data = self.value["data"] if "data" in _fields(self.value) else self.value
+        return "{} @ {:#x}".format(self.value.type.name, int(data))
+
+
+def _lookup(value):
+    name = value.type.name
+    if name is None:
+        return None
+    if name == "string":
+        return StringPrinter(value)
+    if name.startswith("ref? "):
+        return OptionalPrinter(value)
+    if name.startswith("dyn "):
+        return TraitObjectPrinter(value)
+    return None
+
+
+def register(objfile=None):
+    gdb.printing.register_pretty_printer(objfile, _lookup)
+
+
+register(gdb.current_objfile())
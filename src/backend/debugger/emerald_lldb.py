@@ -0,0 +1,51 @@
+# lldb summaries 4 emerald types
+# load w/ `command script import emerald_lldb.py` (or from ~/.lldbinit)
+# emc names the string + trait object structs and describes ref? T as a typedef so they can be matched by name
+
+import lldb
+
+
+def _is_pair(valobj):
+    return valobj.GetNumChildren() == 2 and valobj.GetChildMemberWithName("len").IsValid()
+
+
+def string_summary(valobj, _dict):
+    """string as its text, works 4 both a bare data ptr and a (data, len) pair"""
+    if _is_pair(valobj):
+        data = valobj.GetChildMemberWithName("data").GetValueAsUnsigned()
+        length = valobj.GetChildMemberWithName("len").GetValueAsUnsigned()
+    else:
+        data, length = valobj.GetValueAsUnsigned(), None
+    if data == 0:
+        return '""'
+    process = valobj.GetProcess()
+    error = lldb.SBError()
+    if length is None:
+        text = process.ReadCStringFromMemory(data, 4096, error)
+    else:
+        text = process.ReadMemory(data, length, error).decode("utf-8", "replace") if length else ""
+    if error.Fail():
+        return "<unreadable string @ {:#x}>".format(data)
+    return '"{}"'.format(text)
+
+
+def optional_summary(valobj, _dict):
+    """ref? T as `null` or the value it points at"""
+    if valobj.GetValueAsUnsigned() == 0:
+        return "null"
+    pointee = valobj.Dereference()
+    return pointee.GetSummary() or pointee.GetValue() or str(pointee)
+
+
+def trait_object_summary(valobj, _dict):
+    """dyn Trait as the trait name + its data ptr"""
+    data = valobj.GetChildMemberWithName("data") if _is_pair(valobj) else valobj
+    return "{} @ {:#x}".format(valobj.GetTypeName(), data.GetValueAsUnsigned())
+
+
+def __lldb_init_module(debugger, _dict):
+    category = "emerald"
+    debugger.HandleCommand("type summary add -w {} -F {}.string_summary string".format(category, __name__))
+    debugger.HandleCommand('type summary add -w {} -F {}.optional_summary -x "^ref\\? "'.format(category, __name__))
+    debugger.HandleCommand('type summary add -w {} -F {}.trait_object_summary -x "^dyn "'.format(category, __name__))
+    debugger.HandleCommand("type category enable {}".format(category))
@@ -0,0 +1,299 @@
+use thiserror::Error;
+
+pub const PT_LOAD: u32 = 1;
+pub const SHT_SYMTAB: u32 = 2;
+pub const SHT_RELA: u32 = 4;
+pub const SHT_REL: u32 = 9;
+pub const STT_OBJECT: u8 = 1;
+pub const STT_FUNC: u8 = 2;
+
+#[derive(Debug, Error)]
+pub enum ElfError {
+    #[error("not an ELF file")]
+    NotElf,
+
+    #[error("malformed ELF file: {0}")]
+    Malformed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramHeader {
+    pub p_type: u32,
+    pub offset: u64,
+    pub paddr: u64,
+    pub filesz: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionHeader {
+    pub name: String,
+    pub sh_type: u32,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    /// 4 a relocation section, the section it applies 2
+    pub info: u32,
+    pub entsize: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    /// where in the section it applies 2
+    pub offset: u64,
+    /// index in2 `symbols()`
+    pub symbol: u32,
+    /// none 4 SHT_REL, the addend is then what the relocated bytes hold
+    pub addend: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub value: u64,
+    pub size: u64,
+    /// STT_* from st_info
+    pub kind: u8,
+    /// 0 when undefined
+    pub section: u16,
+}
+
+/// read only view of a 32 or 64 bit elf file of either byte order
+pub struct ElfFile<'a> {
+    bytes: &'a [u8],
+    is_64: bool,
+    big_endian: bool,
+}
+
+impl<'a> ElfFile<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ElfError> {
+        if bytes.len() < 16 || &bytes[0..4] != b"\x7fELF" {
+            return Err(ElfError::NotElf);
+        }
+        let is_64 = match bytes[4] {
+            1 => false,
+            2 => true,
+            c => return Err(ElfError::Malformed(format!("unknown ELF class {}", c))),
+        };
+        Ok(Self { bytes, is_64, big_endian: bytes[5] == 2 })
+    }
+
+    fn read(&self, offset: u64, size: usize) -> Result<u64, ElfError> {
+        let slice = self.data(offset, size as u64)?;
+        let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+        if self.big_endian {
+            Ok(slice.iter().fold(0, fold))
+        } else {
+            Ok(slice.iter().rev().fold(0, fold))
+        }
+    }
+
+    /// read a word that is 4 bytes in elf32 and 8 in elf64
+    fn read_word(&self, offset: u64) -> Result<u64, ElfError> {
+        self.read(offset, if self.is_64 { 8 } else { 4 })
+    }
+
+    pub fn data(&self, offset: u64, size: u64) -> Result<&'a [u8], ElfError> {
+        let start = offset as usize;
+        let end = offset.checked_add(size).map(|e| e as usize);
+        end.and_then(|end| self.bytes.get(start..end))
+            .ok_or_else(|| ElfError::Malformed("truncated file".to_string()))
+    }
+
+    pub fn entry(&self) -> Result<u64, ElfError> {
+        self.read_word(24)
+    }
+
+    pub fn program_headers(&self) -> Result<Vec<ProgramHeader>, ElfError> {
+        let (phoff, phentsize, phnum) = if self.is_64 {
+            (self.read(32, 8)?, self.read(54, 2)?, self.read(56, 2)?)
+        } else {
+            (self.read(28, 4)?, self.read(42, 2)?, self.read(44, 2)?)
+        };
+
+        let mut headers = Vec::new();
+        for i in 0..phnum {
+            let ph = phoff + i * phentsize;
+            headers.push(if self.is_64 {
+                ProgramHeader {
+                    p_type: self.read(ph, 4)? as u32,
+                    offset: self.read(ph + 8, 8)?,
+                    paddr: self.read(ph + 24, 8)?,
+                    filesz: self.read(ph + 32, 8)?,
+                }
+            } else {
+                ProgramHeader {
+                    p_type: self.read(ph, 4)? as u32,
+                    offset: self.read(ph + 4, 4)?,
+                    paddr: self.read(ph + 12, 4)?,
+                    filesz: self.read(ph + 16, 4)?,
+                }
+            });
+        }
+        Ok(headers)
+    }
+
+    pub fn sections(&self) -> Result<Vec<SectionHeader>, ElfError> {
+        let (shoff, shentsize, shnum, shstrndx) = if self.is_64 {
+            (self.read(40, 8)?, self.read(58, 2)?, self.read(60, 2)?, self.read(62, 2)?)
+        } else {
+            (self.read(32, 4)?, self.read(46, 2)?, self.read(48, 2)?, self.read(50, 2)?)
+        };
+
+        // (name offset, header) until the name table is known
+        let mut raw = Vec::new();
+        for i in 0..shnum {
+            let sh = shoff + i * shentsize;
+            let (offset, size, link, info, entsize) = if self.is_64 {
+                (self.read(sh + 24, 8)?, self.read(sh + 32, 8)?, self.read(sh + 40, 4)?, self.read(sh + 44, 4)?, self.read(sh + 56, 8)?)
+            } else {
+                (self.read(sh + 16, 4)?, self.read(sh + 20, 4)?, self.read(sh + 24, 4)?, self.read(sh + 28, 4)?, self.read(sh + 36, 4)?)
+            };
+            raw.push((
+                self.read(sh, 4)?,
+                SectionHeader {
+                    name: String::new(),
+                    sh_type: self.read(sh + 4, 4)? as u32,
+                    offset,
+                    size,
+                    link: link as u32,
+                    info: info as u32,
+                    entsize,
+                },
+            ));
+        }
+
+        let names = raw.get(shstrndx as usize).map(|(_, s)| (s.offset, s.size));
+        let mut sections = Vec::new();
+        for (name_offset, mut section) in raw {
+            if let Some((offset, size)) = names {
+                section.name = self.string(offset, size, name_offset)?;
+            }
+            sections.push(section);
+        }
+        Ok(sections)
+    }
+
+    /// entries of the static symbol table, empty when the file is stripped
+    pub fn symbols(&self) -> Result<Vec<Symbol>, ElfError> {
+        let sections = self.sections()?;
+        let symtab = match sections.iter().find(|s| s.sh_type == SHT_SYMTAB) {
+            Some(symtab) => symtab,
+            None => return Ok(Vec::new()),
+        };
+        let strtab = sections
+            .get(symtab.link as usize)
+            .ok_or_else(|| ElfError::Malformed("symbol table has no string table".to_string()))?;
+
+        let entsize = if symtab.entsize == 0 { if self.is_64 { 24 } else { 16 } } else { symtab.entsize };
+        let mut symbols = Vec::new();
+        for i in 0..symtab.size / entsize {
+            let sym = symtab.offset + i * entsize;
+            let (value, size, info, section) = if self.is_64 {
+                (self.read(sym + 8, 8)?, self.read(sym + 16, 8)?, self.read(sym + 4, 1)?, self.read(sym + 6, 2)?)
+            } else {
+                (self.read(sym + 4, 4)?, self.read(sym + 8, 4)?, self.read(sym + 12, 1)?, self.read(sym + 14, 2)?)
+            };
+            symbols.push(Symbol {
+                name: self.string(strtab.offset, strtab.size, self.read(sym, 4)?)?,
+                value,
+                size,
+                kind: (info & 0xf) as u8,
+                section: section as u16,
+            });
+        }
+        Ok(symbols)
+    }
+
+    /// the relocations applied 2 section `index`, frm evry SHT_REL/SHT_RELA section 4 it
+    pub fn relocations(&self, index: usize) -> Result<Vec<Relocation>, ElfError> {
+        let mut relocations = Vec::new();
+        for section in self.sections()? {
+            let rela = match section.sh_type {
+                SHT_RELA => true,
+                SHT_REL => false,
+                _ => continue,
+            };
+            if section.info as usize != index {
+                continue;
+            }
+            let word = if self.is_64 { 8 } else { 4 };
+            let entsize = if section.entsize == 0 { word * if rela { 3 } else { 2 } } else { section.entsize };
+            for i in 0..section.size / entsize {
+                let entry = section.offset + i * entsize;
+                let info = self.read_word(entry + word)?;
+                let addend = match (rela, self.is_64) {
+                    (false, _) => None,
+                    (true, true) => Some(self.read(entry + 16, 8)? as i64),
+                    (true, false) => Some(self.read(entry + 8, 4)? as u32 as i32 as i64),
+                };
+                relocations.push(Relocation {
+                    offset: self.read_word(entry)?,
+                    symbol: if self.is_64 { (info >> 32) as u32 } else { (info >> 8) as u32 },
+                    addend,
+                });
+            }
+        }
+        Ok(relocations)
+    }
+
+    /// (fn, frame bytes) frm the `.stack_sizes` section llvm writes w/ -stack-size-section, an
+    /// entry is the fn's address then its size as uleb128. in an object the address is a
+    /// relocation against the fn's section, in a linked file its the fn's own
+    pub fn stack_sizes(&self) -> Result<Vec<(String, u64)>, ElfError> {
+        let sections = self.sections()?;
+        let Some(index) = sections.iter().position(|s| s.name == ".stack_sizes") else {
+            return Ok(Vec::new());
+        };
+        let section = &sections[index];
+        let data = self.data(section.offset, section.size)?;
+        let (symbols, relocations) = (self.symbols()?, self.relocations(index)?);
+        let word = if self.is_64 { 8 } else { 4 };
+        let truncated = || ElfError::Malformed("truncated .stack_sizes entry".to_string());
+
+        let mut sizes = Vec::new();
+        let mut at = 0;
+        while at < data.len() {
+            let written = self.read_word(section.offset + at as u64)?;
+            let (size, len) = uleb128(data.get(at + word..).ok_or_else(truncated)?).ok_or_else(truncated)?;
+            let (in_section, address) = match relocations.iter().find(|r| r.offset == at as u64) {
+                Some(relocation) => {
+                    let symbol = symbols
+                        .get(relocation.symbol as usize)
+                        .ok_or_else(|| ElfError::Malformed("relocation against a missing symbol".to_string()))?;
+                    (Some(symbol.section), symbol.value.wrapping_add(relocation.addend.map_or(written, |a| a as u64)))
+                }
+                None => (None, written),
+            };
+            let function = symbols
+                .iter()
+                .find(|s| s.kind == STT_FUNC && s.value == address && in_section.is_none_or(|i| s.section == i));
+            if let Some(function) = function {
+                sizes.push((function.name.clone(), size));
+            }
+            at += word + len;
+        }
+        Ok(sizes)
+    }
+
+    /// nul terminated string at `index` in a string table section
+    fn string(&self, table_offset: u64, table_size: u64, index: u64) -> Result<String, ElfError> {
+        let table = self.data(table_offset, table_size)?;
+        let rest = table
+            .get(index as usize..)
+            .ok_or_else(|| ElfError::Malformed("string index past end of table".to_string()))?;
+        let end = rest.iter().position(|b| *b == 0).unwrap_or(rest.len());
+        Ok(String::from_utf8_lossy(&rest[..end]).to_string())
+    }
+}
+
+/// (value, bytes read) of the uleb128 at the start of `bytes`
+fn uleb128(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
@@ -1,4 +1,6 @@
-use crate::backend::ports::{CodeGen, Emitter, Optimizer};
+use crate::backend::ports::codegen::BackendInputType;
+use crate::backend::ports::{CodeGen, Emitter, ExecutionEngine, Optimizer};
+use crate::core::target::TargetInfo;
 use thiserror::Error;
 
 /// backend idntfr
@@ -10,14 +12,23 @@ pub enum BackendType {
     Llvm,
     /// native codegen backend
     Native,
+    /// cranelift backend 4 fast debug builds (needs the `cranelift` feature)
+    Cranelift,
+    /// MIR interpreter, runs programs w/o any native backend
+    Interp,
 }
 
 impl BackendType {
+    pub const ALL: [BackendType; 5] =
+        [BackendType::Llvm, BackendType::Cranelift, BackendType::Native, BackendType::Interp, BackendType::Null];
+
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "null" => Some(Self::Null),
             "llvm" => Some(Self::Llvm),
             "native" => Some(Self::Native),
+            "cranelift" | "clif" => Some(Self::Cranelift),
+            "interp" | "interpreter" => Some(Self::Interp),
             _ => None,
         }
     }
@@ -27,12 +38,60 @@ impl BackendType {
             BackendType::Null => "null",
             BackendType::Llvm => "llvm",
             BackendType::Native => "native",
+            BackendType::Cranelift => "cranelift",
+            BackendType::Interp => "interp",
         }
     }
+
+    /// the cargo feature that builds it in, None 4 the ones always there or not written yet
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            BackendType::Llvm => Some("llvm21 (or llvm18)"),
+            BackendType::Cranelift => Some("cranelift"),
+            _ => None,
+        }
+    }
+}
+
+/// which target triples a backend generates code 4
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetSupport {
+    /// any triple, a bad 1 fails in the backend itself
+    Any,
+    /// only the arch the compiler runs on
+    Host,
+}
+
+impl TargetSupport {
+    pub fn supports(&self, triple: &str) -> bool {
+        match self {
+            TargetSupport::Any => true,
+            TargetSupport::Host => TargetInfo::from_triple(triple).arch == TargetInfo::host().arch,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetSupport::Any => "any",
+            TargetSupport::Host => "host",
+        }
+    }
+}
+
+/// what a backend can do, so the driver rejects a flag it would ignore or fail on halfway thru
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackendCapabilities {
+    pub targets: TargetSupport,
+    /// honours -g
+    pub debug_info: bool,
+    /// has an `ExecutionEngine` 4 `emerald run` + the repl
+    pub jit: bool,
+    pub preferred_input: BackendInputType,
 }
 
 /// fctry trait 4 creating backend cmpnnts
-pub trait BackendFactory {
+/// shared w/ the threads codegen units r compiled on
+pub trait BackendFactory: Send + Sync {
     /// crt a code gnrtr
     fn create_codegen(&self) -> Result<Box<dyn CodeGen>, BackendError>;
     
@@ -44,6 +103,24 @@ pub trait BackendFactory {
     
     /// get the abckend type
     fn backend_type(&self) -> BackendType;
+
+    /// what it supports, `emerald backends` lists these
+    fn capabilities(&self) -> BackendCapabilities;
+
+    /// what emitted its code, cached objects frm another version r never reused
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    /// create a jit 4 `emerald run`, only backends that can execute in process have one
+    fn create_execution_engine(&self) -> Result<Box<dyn ExecutionEngine>, BackendError> {
+        Err(BackendError::NotAvailable(format!("{} backend cannot execute code", self.backend_type().as_str())))
+    }
+
+    /// how many units the MIR fns r split in2, each compiled in parallel 2 its own object
+    fn codegen_units(&self) -> usize {
+        1
+    }
 }
 
 #[derive(Debug, Error)]
@@ -72,12 +149,17 @@ impl BackendRegistry {
         // register backends in priority order
         // note: nullbackendfactory is always avlbl as fallback
         registry.register(Box::new(crate::backend::null::NullBackendFactory));
+
+        // pure rust so always there, only used when asked 4
+        registry.register(Box::new(crate::backend::interp::InterpBackendFactory));
         
-        // register llvm backend
-        // Note: LLVM backend may fail to compile if llvm-sys is not properly configured
-        // For now, we'll try to register it and let it fail gracefully if needed
-        // TODO: make this conditional on llvm-sys availability
-        registry.register(Box::new(crate::backend::llvm::LlvmBackendFactory));
+        // llvm unless built w/o an llvm* feature (frontend only installs)
+        #[cfg(feature = "llvm")]
+        registry.register(Box::new(crate::backend::llvm::LlvmBackendFactory::new()));
+
+        // cranelift only when built w/ --features cranelift
+        #[cfg(feature = "cranelift")]
+        registry.register(Box::new(crate::backend::cranelift::CraneliftBackendFactory));
         
         // todo: register native backend when implemented
         // registry.register(Box::new(crate::backend::native::NativeBackendFactory));
@@ -85,6 +167,22 @@ impl BackendRegistry {
         registry
     }
     
+    /// split llvm codegen in2 `units` parallel units (--codegen-units)
+    pub fn with_codegen_units(self, units: usize) -> Self {
+        #[cfg(feature = "llvm")]
+        {
+            let mut this = self;
+            this.factories.retain(|f| f.backend_type() != BackendType::Llvm);
+            this.register(Box::new(crate::backend::llvm::LlvmBackendFactory::new().with_codegen_units(units)));
+            this
+        }
+        #[cfg(not(feature = "llvm"))]
+        {
+            let _ = units;
+            self
+        }
+    }
+
     /// rgstr a backend factory
     pub fn register(&mut self, factory: Box<dyn BackendFactory>) {
         self.factories.push(factory);
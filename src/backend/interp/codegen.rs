@@ -0,0 +1,26 @@
+use crate::backend::ports::codegen::{BackendInputType, CodeGen, CodeGenError, Module, OptimizationLevel};
+use crate::core::mir::MirFunction;
+
+/// what an interp module holds, the MIR itself
+pub struct InterpModule {
+    pub functions: Vec<MirFunction>,
+}
+
+/// "compiling" 4 the interpreter just keeps the MIR, it runs as lowered so the opt level is ignored
+pub struct InterpCodeGen;
+
+impl CodeGen for InterpCodeGen {
+    fn generate_from_mir(&mut self, mir: &[MirFunction]) -> Result<Module, CodeGenError> {
+        Ok(Module::with_data("interp_module".to_string(), Box::new(InterpModule { functions: mir.to_vec() })))
+    }
+
+    fn set_optimization_level(&mut self, _level: OptimizationLevel) {}
+
+    fn set_target_triple(&mut self, _triple: String) {
+        // MIR runs the same evrywhere, target.* constants were already folded
+    }
+
+    fn preferred_input(&self) -> BackendInputType {
+        BackendInputType::Mir
+    }
+}
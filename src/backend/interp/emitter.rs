@@ -0,0 +1,33 @@
+use crate::backend::ports::codegen::Module;
+use crate::backend::ports::emitter::{EmitError, Emitter};
+use crate::backend::stack_usage::FrameInfo;
+use std::path::Path;
+
+/// the interpreter only executes, evry artifact needs a native backend
+pub struct InterpEmitter;
+
+fn unsupported(what: &str) -> EmitError {
+    EmitError::EmissionFailed(format!("Interpreter backend cannot emit {}, use `emerald run`", what))
+}
+
+impl Emitter for InterpEmitter {
+    fn emit_binary(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(unsupported("binaries"))
+    }
+
+    fn emit_assembly(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(unsupported("assembly"))
+    }
+
+    fn emit_llvm_ir(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(unsupported("LLVM IR"))
+    }
+
+    fn emit_object(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(unsupported("object files"))
+    }
+
+    fn stack_frames(&self, _module: &Module) -> Result<Vec<FrameInfo>, EmitError> {
+        Err(EmitError::EmissionFailed("Interpreter backend has no stack frames to report".to_string()))
+    }
+}
@@ -0,0 +1,37 @@
+use crate::backend::factory::{BackendCapabilities, BackendError, BackendFactory, BackendType, TargetSupport};
+use crate::backend::ports::codegen::BackendInputType;
+use crate::backend::interp::codegen::InterpCodeGen;
+use crate::backend::interp::emitter::InterpEmitter;
+use crate::backend::interp::interpreter::Interpreter;
+use crate::backend::interp::optimizer::InterpOptimizer;
+use crate::backend::ports::{CodeGen, Emitter, ExecutionEngine, Optimizer};
+
+/// MIR interpreter backend, always built in since it needs no native deps
+pub struct InterpBackendFactory;
+
+impl BackendFactory for InterpBackendFactory {
+    fn create_codegen(&self) -> Result<Box<dyn CodeGen>, BackendError> {
+        Ok(Box::new(InterpCodeGen))
+    }
+
+    fn create_optimizer(&self) -> Result<Box<dyn Optimizer>, BackendError> {
+        Ok(Box::new(InterpOptimizer))
+    }
+
+    fn create_emitter(&self) -> Result<Box<dyn Emitter>, BackendError> {
+        Ok(Box::new(InterpEmitter))
+    }
+
+    fn backend_type(&self) -> BackendType {
+        BackendType::Interp
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        // it runs in this process, so on this machine
+        BackendCapabilities { targets: TargetSupport::Host, debug_info: false, jit: true, preferred_input: BackendInputType::Mir }
+    }
+
+    fn create_execution_engine(&self) -> Result<Box<dyn ExecutionEngine>, BackendError> {
+        Ok(Box::new(Interpreter::new()))
+    }
+}
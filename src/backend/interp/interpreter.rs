@@ -0,0 +1,635 @@
+use crate::backend::interp::codegen::InterpModule;
+use crate::backend::interp::value::{wrap, Pointer, Root, Value};
+use crate::backend::ports::codegen::Module;
+use crate::backend::ports::execution::{EntryValue, ExecutionEngine, ExecutionError};
+use crate::core::mir::{is_heap_alloc, is_heap_free, Instruction, Local, MirFunction, Operand, PANIC_FN};
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Write;
+use std::rc::Rc;
+
+/// emerald calls nested deeper than this trap instead of overflowing the host stack
+const MAX_DEPTH: usize = 1000;
+
+/// walks MIR directly, no llvm needed. 4 `emerald run` w/o a jit, comptime evaluation + checking
+/// the native backends against a reference. fns no module defines r the few host builtins below
+pub struct Interpreter {
+    functions: HashMap<String, Rc<MirFunction>>,
+    cells: Vec<Value>,
+    frames: Vec<HashMap<usize, Value>>,
+    /// what print + putchar wrote once capture_output was called, stdout otherwise
+    captured: Option<Vec<u8>>,
+}
+
+/// why a call didnt return normally
+enum Stop {
+    /// the message + the fn it happened in, filled in as it leaves that fn
+    Trap(String, Option<String>),
+    /// `emerald_panic`, the message already says where
+    Panic(String),
+    Missing(String),
+    Exit(i32),
+}
+
+impl std::fmt::Display for Stop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stop::Trap(message, Some(function)) => write!(f, "{} in '{}'", message, function),
+            Stop::Trap(message, None) | Stop::Panic(message) => write!(f, "{}", message),
+            Stop::Missing(name) => write!(f, "call to undefined function '{}'", name),
+            Stop::Exit(code) => write!(f, "exit({}) called", code),
+        }
+    }
+}
+
+type Flow<T> = Result<T, Stop>;
+
+enum Step {
+    Next,
+    Goto(usize),
+    Return(Value),
+}
+
+fn trap<T>(message: impl Into<String>) -> Flow<T> {
+    Err(Stop::Trap(message.into(), None))
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            functions: HashMap::new(),
+            cells: Vec::new(),
+            frames: Vec::new(),
+            captured: None,
+        }
+    }
+
+    /// keep what the program prints instead of writing it 2 stdout, eg in a browser where theres none
+    pub fn capture_output(&mut self) {
+        self.captured.get_or_insert_with(Vec::new);
+    }
+
+    /// what was printed since capture_output, empty when nothing is captured
+    pub fn take_output(&mut self) -> Vec<u8> {
+        self.captured.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn write_out(&mut self, bytes: &[u8]) {
+        match &mut self.captured {
+            Some(captured) => captured.extend_from_slice(bytes),
+            None => {
+                let _ = std::io::stdout().write_all(bytes);
+            }
+        }
+    }
+
+    /// make `functions` callable, a later definition of the same name replaces the earlier one
+    pub fn add_functions(&mut self, functions: &[MirFunction]) {
+        for func in functions {
+            self.functions.insert(func.name.clone(), Rc::new(func.clone()));
+        }
+    }
+
+    /// call a fn by name w/ already evaluated args
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, ExecutionError> {
+        self.invoke(name, args).map_err(|stop| match stop {
+            Stop::Missing(name) => ExecutionError::SymbolNotFound(name),
+            stop => ExecutionError::Trapped(stop.to_string()),
+        })
+    }
+
+    fn invoke(&mut self, name: &str, args: Vec<Value>) -> Flow<Value> {
+        match self.functions.get(name).cloned() {
+            Some(func) => self.run(&func, args),
+            None => self.host(name, args),
+        }
+    }
+
+    fn run(&mut self, func: &MirFunction, args: Vec<Value>) -> Flow<Value> {
+        if self.frames.len() >= MAX_DEPTH {
+            return trap(format!("stack overflow calling '{}'", func.name));
+        }
+        if args.len() != func.params.len() {
+            return trap(format!("'{}' takes {} arguments but got {}", func.name, func.params.len(), args.len()));
+        }
+        self.frames.push(HashMap::new());
+        let mut bound = Ok(());
+        for (param, arg) in func.params.iter().zip(args) {
+            match self.by_value(arg, &param.type_) {
+                Ok(arg) => {
+                    self.locals().insert(param.local.id, arg);
+                }
+                Err(e) => {
+                    bound = Err(e);
+                    break;
+                }
+            }
+        }
+        let result = bound.and_then(|_| self.execute(func));
+        self.frames.pop();
+        result.map_err(|stop| match stop {
+            Stop::Trap(message, None) => Stop::Trap(message, Some(func.name.clone())),
+            stop => stop,
+        })
+    }
+
+    fn execute(&mut self, func: &MirFunction) -> Flow<Value> {
+        let mut block = func.entry_block;
+        let mut previous = None;
+        'blocks: loop {
+            let Some(bb) = func.get_block(block) else {
+                return trap(format!("jump to missing block bb{}", block));
+            };
+            for inst in &bb.instructions {
+                match self.step(func, inst, previous)? {
+                    Step::Next => {}
+                    Step::Goto(target) => {
+                        previous = Some(block);
+                        block = target;
+                        continue 'blocks;
+                    }
+                    Step::Return(value) => return Ok(value),
+                }
+            }
+            // a void fn can end w/o an explicit ret
+            return Ok(Value::Unit);
+        }
+    }
+
+    fn step(&mut self, func: &MirFunction, inst: &Instruction, previous: Option<usize>) -> Flow<Step> {
+        match inst {
+            Instruction::Add { dest, left, right, type_ }
+            | Instruction::Sub { dest, left, right, type_ }
+            | Instruction::Mul { dest, left, right, type_ }
+            | Instruction::Div { dest, left, right, type_ }
+            | Instruction::Mod { dest, left, right, type_ } => {
+                let (l, r) = (self.eval(left)?, self.eval(right)?);
+                let value = arith(inst, l, r, type_)?;
+                self.set(*dest, value);
+            }
+            Instruction::Eq { dest, left, right }
+            | Instruction::Ne { dest, left, right }
+            | Instruction::Lt { dest, left, right }
+            | Instruction::Le { dest, left, right }
+            | Instruction::Gt { dest, left, right }
+            | Instruction::Ge { dest, left, right } => {
+                let unsigned = [left, right].iter().any(|op| is_unsigned(func, op));
+                let (l, r) = (self.eval(left)?, self.eval(right)?);
+                let ordering = compare(&l, &r, unsigned);
+                let result = match inst {
+                    Instruction::Eq { .. } => ordering == Some(Ordering::Equal),
+                    Instruction::Ne { .. } => ordering != Some(Ordering::Equal),
+                    _ => {
+                        let Some(ordering) = ordering else {
+                            return trap(format!("cannot order {:?} and {:?}", l, r));
+                        };
+                        match inst {
+                            Instruction::Lt { .. } => ordering.is_lt(),
+                            Instruction::Le { .. } => ordering.is_le(),
+                            Instruction::Gt { .. } => ordering.is_gt(),
+                            _ => ordering.is_ge(),
+                        }
+                    }
+                };
+                self.set(*dest, Value::Bool(result));
+            }
+            Instruction::And { dest, left, right } | Instruction::Or { dest, left, right } => {
+                let and = matches!(inst, Instruction::And { .. });
+                let value = match (self.eval(left)?, self.eval(right)?) {
+                    (Value::Bool(a), Value::Bool(b)) => Value::Bool(if and { a && b } else { a || b }),
+                    (Value::Int(a), Value::Int(b)) => Value::Int(if and { a & b } else { a | b }),
+                    (l, r) => return trap(format!("cannot apply a logical op to {:?} and {:?}", l, r)),
+                };
+                self.set(*dest, value);
+            }
+            Instruction::Not { dest, operand } => {
+                let value = match self.eval(operand)? {
+                    Value::Bool(b) => Value::Bool(!b),
+                    Value::Int(i) => Value::Int(!i),
+                    other => return trap(format!("cannot negate {:?}", other)),
+                };
+                self.set(*dest, value);
+            }
+            Instruction::Load { dest, source, .. } | Instruction::VolatileLoad { dest, source, .. } => {
+                let pointer = self.pointer(source)?;
+                let value = self.read(&pointer)?.clone();
+                self.set(*dest, value);
+            }
+            Instruction::Store { dest, source, type_ } | Instruction::VolatileStore { dest, source, type_ } => {
+                let pointer = self.pointer(dest)?;
+                let value = self.eval(source)?;
+                let value = self.by_value(value, type_)?;
+                *self.write(&pointer)? = value;
+            }
+            Instruction::Alloca { dest, type_ } => {
+                self.cells.push(Value::zeroed(type_));
+                let pointer = Pointer { root: Root::Cell(self.cells.len() - 1), path: Vec::new() };
+                self.set(*dest, Value::Ptr(pointer));
+            }
+            Instruction::Gep { dest, base, index, .. } => {
+                let index = self.int(index)?;
+                let mut pointer = match (base, self.eval(base)?) {
+                    (_, Value::Ptr(pointer)) => pointer,
+                    // a struct or array local is addressed where it lives
+                    (Operand::Local(local), Value::Aggregate(_)) => {
+                        Pointer { root: Root::Local { frame: self.frames.len() - 1, local: local.id }, path: Vec::new() }
+                    }
+                    (_, aggregate @ Value::Aggregate(_)) => {
+                        self.cells.push(aggregate);
+                        Pointer { root: Root::Cell(self.cells.len() - 1), path: Vec::new() }
+                    }
+                    (_, Value::Null) => return trap("null pointer dereference"),
+                    (_, other) => return trap(format!("cannot index into {:?}", other)),
+                };
+                match self.read(&pointer)? {
+                    Value::Aggregate(elements) if index >= 0 && (index as usize) < elements.len() => {
+                        pointer.path.push(index as usize);
+                    }
+                    Value::Aggregate(elements) => {
+                        return trap(format!("index {} out of bounds for length {}", index, elements.len()));
+                    }
+                    // a pointer 2 an element, step over its neighbours
+                    _ => pointer = self.offset(pointer, index)?,
+                }
+                self.set(*dest, Value::Ptr(pointer));
+            }
+            Instruction::PtrOffset { dest, base, offset, .. } => {
+                let pointer = self.pointer(base)?;
+                let offset = self.int(offset)?;
+                let pointer = self.offset(pointer, offset)?;
+                self.set(*dest, Value::Ptr(pointer));
+            }
+            Instruction::Memset { dest, value, count, type_ } => {
+                let pointer = self.pointer(dest)?;
+                let value = coerce(self.eval(value)?, type_);
+                match self.write(&pointer)? {
+                    Value::Aggregate(elements) if *count <= elements.len() => {
+                        elements[..*count].fill(value);
+                    }
+                    slot if *count == 1 => *slot = value,
+                    _ => return trap(format!("memset of {} elements out of bounds", count)),
+                }
+            }
+            Instruction::Call { dest, func: callee, args, return_type, .. } => {
+                let name = match self.eval(callee)? {
+                    Value::Func(name) => name,
+                    Value::Null => return trap("call through a null function pointer"),
+                    other => return trap(format!("cannot call {:?}", other)),
+                };
+                let args = args.iter().map(|a| self.eval(a)).collect::<Flow<Vec<_>>>()?;
+                let result = match return_type {
+                    // an allocator the program doesnt define hands out a cell of the type the
+                    // call uses its memory as
+                    Some(Type::Pointer(p)) if is_heap_alloc(&name) && !self.functions.contains_key(&name) => {
+                        self.cells.push(Value::zeroed(&p.pointee));
+                        Value::Ptr(Pointer { root: Root::Cell(self.cells.len() - 1), path: Vec::new() })
+                    }
+                    _ => self.invoke(&name, args)?,
+                };
+                if let Some(dest) = dest {
+                    self.set(*dest, result);
+                }
+            }
+            Instruction::Ret { value } => {
+                let value = match value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Unit,
+                };
+                let value = match &func.return_type {
+                    Some(ty) => self.by_value(value, ty)?,
+                    None => value,
+                };
+                return Ok(Step::Return(value));
+            }
+            Instruction::Br { condition, then_bb, else_bb } => {
+                let condition = self.eval(condition)?;
+                let Some(taken) = condition.truthy() else {
+                    return trap(format!("branch on {:?}", condition));
+                };
+                return Ok(Step::Goto(if taken { *then_bb } else { *else_bb }));
+            }
+            Instruction::Jump { target } => return Ok(Step::Goto(*target)),
+            Instruction::Switch { value, arms, default } => {
+                let value = self.int(value)?;
+                let target = arms.iter().find(|(case, _)| *case == value).map_or(*default, |(_, bb)| *bb);
+                return Ok(Step::Goto(target));
+            }
+            Instruction::StrLen { dest, source } => {
+                let length = self.string(source)?.len();
+                self.set(*dest, Value::Int(length as i64));
+            }
+            Instruction::StrConcat { dest, left, right } => {
+                let joined = self.string(left)? + &self.string(right)?;
+                self.set(*dest, Value::Str(joined));
+            }
+            Instruction::StrEq { dest, left, right } => {
+                let equal = self.string(left)? == self.string(right)?;
+                self.set(*dest, Value::Bool(equal));
+            }
+            Instruction::MakeDyn { dest, data, vtable } => {
+                let data = self.eval(data)?;
+                self.set(*dest, Value::Dyn { data: Box::new(data), vtable: vtable.clone() });
+            }
+            Instruction::DynCall { dest, object, slot, args, .. } => {
+                let (data, vtable) = match self.deref(object)? {
+                    Value::Dyn { data, vtable } => (*data, vtable),
+                    other => return trap(format!("dynamic call on {:?}", other)),
+                };
+                let Some(method) = vtable.methods.get(*slot) else {
+                    return trap(format!("no slot {} in {}", slot, vtable.symbol()));
+                };
+                let mut call_args = vec![data];
+                for arg in args {
+                    call_args.push(self.eval(arg)?);
+                }
+                let result = self.invoke(method, call_args)?;
+                if let Some(dest) = dest {
+                    self.set(*dest, result);
+                }
+            }
+            Instruction::MakeVariant { dest, tag, fields, .. } => {
+                let fields = fields.iter().map(|f| self.eval(f)).collect::<Flow<Vec<_>>>()?;
+                self.set(*dest, Value::Variant { tag: *tag, fields });
+            }
+            Instruction::EnumTag { dest, source } => {
+                let tag = match self.deref(source)? {
+                    Value::Variant { tag, .. } => tag,
+                    other => return trap(format!("tag of {:?}", other)),
+                };
+                self.set(*dest, Value::Int(tag as i64));
+            }
+            Instruction::VariantField { dest, source, tag, index, .. } => {
+                let value = match self.deref(source)? {
+                    Value::Variant { tag: actual, fields } if actual == *tag && *index < fields.len() => fields[*index].clone(),
+                    Value::Variant { tag: actual, .. } if actual != *tag => {
+                        return trap(format!("read field of variant {} from variant {}", tag, actual));
+                    }
+                    other => return trap(format!("field {} of {:?}", index, other)),
+                };
+                self.set(*dest, value);
+            }
+            Instruction::Phi { dest, incoming, .. } => {
+                let Some((value, _)) = incoming.iter().find(|(_, bb)| Some(*bb) == previous) else {
+                    return trap(format!("phi has no value for the edge from {:?}", previous));
+                };
+                let value = self.eval(value)?;
+                self.set(*dest, value);
+            }
+            Instruction::Copy { dest, source, type_ } => {
+                let value = coerce(self.eval(source)?, type_);
+                self.set(*dest, value);
+            }
+        }
+        Ok(Step::Next)
+    }
+
+    /// the handful of fns a program can call w/o defining them
+    fn host(&mut self, name: &str, args: Vec<Value>) -> Flow<Value> {
+        match (name, args.as_slice()) {
+            ("print", [Value::Str(s)]) => {
+                self.write_out(s.as_bytes());
+                let _ = std::io::stdout().flush();
+                Ok(Value::Unit)
+            }
+            ("putchar", [Value::Int(c)]) => {
+                self.write_out(&[*c as u8]);
+                Ok(Value::Int(*c))
+            }
+            ("abort", []) => trap("abort called"),
+            (PANIC_FN, [Value::Str(message), Value::Str(file), Value::Int(line)]) => {
+                Err(Stop::Panic(format!("panicked at {}:{}: {}", file, line, message)))
+            }
+            ("exit", [Value::Int(code)]) => Err(Stop::Exit(*code as i32)),
+            // cells r never reused, theres nothing 2 give back
+            (free, [Value::Ptr(_) | Value::Null]) if is_heap_free(free) => Ok(Value::Unit),
+            _ => Err(Stop::Missing(name.to_string())),
+        }
+    }
+
+    fn locals(&mut self) -> &mut HashMap<usize, Value> {
+        self.frames.last_mut().expect("instructions only run inside a call")
+    }
+
+    fn set(&mut self, local: Local, value: Value) {
+        self.locals().insert(local.id, value);
+    }
+
+    fn eval(&mut self, operand: &Operand) -> Flow<Value> {
+        match operand {
+            Operand::Constant(c) => Ok(Value::from_constant(c)),
+            Operand::Function(f) => Ok(Value::Func(f.name.clone())),
+            Operand::Local(local) => match self.locals().get(&local.id) {
+                Some(value) => Ok(value.clone()),
+                None => trap(format!("_{} read before it was set", local.id)),
+            },
+        }
+    }
+
+    fn int(&mut self, operand: &Operand) -> Flow<i64> {
+        match self.eval(operand)? {
+            Value::Int(i) => Ok(i),
+            other => trap(format!("expected an integer, got {:?}", other)),
+        }
+    }
+
+    fn string(&mut self, operand: &Operand) -> Flow<String> {
+        match self.deref(operand)? {
+            Value::Str(s) => Ok(s),
+            other => trap(format!("expected a string, got {:?}", other)),
+        }
+    }
+
+    fn pointer(&mut self, operand: &Operand) -> Flow<Pointer> {
+        match self.eval(operand)? {
+            Value::Ptr(pointer) => Ok(pointer),
+            Value::Null => trap("null pointer dereference"),
+            other => trap(format!("expected a pointer, got {:?}", other)),
+        }
+    }
+
+    /// the operand's value, or what it points at
+    fn deref(&mut self, operand: &Operand) -> Flow<Value> {
+        match self.eval(operand)? {
+            Value::Ptr(pointer) => Ok(self.read(&pointer)?.clone()),
+            value => Ok(value),
+        }
+    }
+
+    /// struct + array locals r often addresses, a by value use copies what they point at
+    fn by_value(&mut self, value: Value, ty: &Type) -> Flow<Value> {
+        match value {
+            Value::Ptr(pointer) if matches!(ty, Type::Struct(_) | Type::Array(_)) => Ok(self.read(&pointer)?.clone()),
+            value => Ok(coerce(value, ty)),
+        }
+    }
+
+    fn read(&self, pointer: &Pointer) -> Flow<&Value> {
+        let mut value = match pointer.root {
+            Root::Cell(cell) => self.cells.get(cell),
+            Root::Local { frame, local } => self.frames.get(frame).and_then(|f| f.get(&local)),
+        };
+        for &index in &pointer.path {
+            value = match value {
+                Some(Value::Aggregate(elements)) => elements.get(index),
+                _ => None,
+            };
+        }
+        value.map_or_else(|| trap("dangling or out of bounds pointer"), Ok)
+    }
+
+    fn write(&mut self, pointer: &Pointer) -> Flow<&mut Value> {
+        let mut value = match pointer.root {
+            Root::Cell(cell) => self.cells.get_mut(cell),
+            Root::Local { frame, local } => self.frames.get_mut(frame).and_then(|f| f.get_mut(&local)),
+        };
+        for &index in &pointer.path {
+            value = match value {
+                Some(Value::Aggregate(elements)) => elements.get_mut(index),
+                _ => None,
+            };
+        }
+        value.map_or_else(|| trap("dangling or out of bounds pointer"), Ok)
+    }
+
+    /// `pointer` moved `offset` elements within the array it points in2
+    fn offset(&self, mut pointer: Pointer, offset: i64) -> Flow<Pointer> {
+        if offset == 0 {
+            return Ok(pointer);
+        }
+        let Some(last) = pointer.path.pop() else {
+            return trap("pointer offset outside its object");
+        };
+        let length = match self.read(&pointer)? {
+            Value::Aggregate(elements) => elements.len() as i64,
+            _ => 0,
+        };
+        let index = last as i64 + offset;
+        // one past the end is a valid address, just not a readable one
+        if index < 0 || index > length {
+            return trap(format!("pointer offset {} out of bounds for length {}", index, length));
+        }
+        pointer.path.push(index as usize);
+        Ok(pointer)
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecutionEngine for Interpreter {
+    fn add_module(&mut self, module: &Module) -> Result<(), ExecutionError> {
+        match module.data.as_ref().and_then(|d| d.downcast_ref::<InterpModule>()) {
+            Some(data) => {
+                self.add_functions(&data.functions);
+                Ok(())
+            }
+            None => Err(ExecutionError::InvalidModule(format!("'{}' does not contain MIR", module.name))),
+        }
+    }
+
+    fn run_main(&mut self, module: &Module) -> Result<i32, ExecutionError> {
+        self.add_module(module)?;
+        if !self.functions.contains_key("main") {
+            return Err(ExecutionError::SymbolNotFound("main".to_string()));
+        }
+        match self.invoke("main", Vec::new()) {
+            Ok(Value::Int(code)) => Ok(code as i32),
+            Ok(Value::Bool(b)) => Ok(b as i32),
+            Ok(_) => Ok(0),
+            Err(Stop::Exit(code)) => Ok(code),
+            Err(stop @ (Stop::Trap(..) | Stop::Panic(_))) => Err(ExecutionError::Trapped(stop.to_string())),
+            Err(Stop::Missing(name)) => Err(ExecutionError::SymbolNotFound(name)),
+        }
+    }
+
+    fn call_entry(&mut self, module: &Module, name: &str, returns: &Type) -> Result<EntryValue, ExecutionError> {
+        self.add_module(module)?;
+        if !self.functions.contains_key(name) {
+            return Err(ExecutionError::SymbolNotFound(name.to_string()));
+        }
+        Ok(match (self.call(name, Vec::new())?, returns) {
+            (Value::Int(code), Type::Primitive(PrimitiveType::Char)) => {
+                EntryValue::Char(char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
+            }
+            (Value::Int(i), _) => EntryValue::Int(i),
+            (Value::Float(x), _) => EntryValue::Float(x),
+            (Value::Bool(b), _) => EntryValue::Bool(b),
+            (Value::Str(s), _) => EntryValue::Str(s),
+            (Value::Unit, _) => EntryValue::Unit,
+            (value, _) => return Err(ExecutionError::InvalidModule(format!("'{}' returned {:?}, not a primitive or string", name, value))),
+        })
+    }
+}
+
+fn is_unsigned(func: &MirFunction, operand: &Operand) -> bool {
+    let Operand::Local(local) = operand else { return false };
+    func.locals
+        .iter()
+        .find(|info| info.local == *local)
+        .is_some_and(|info| matches!(&info.type_, Type::Primitive(p) if p.is_unsigned()))
+}
+
+/// an int written 2 a narrower slot wraps, written 2 a float slot converts
+fn coerce(value: Value, ty: &Type) -> Value {
+    match (value, ty) {
+        (Value::Int(i), Type::Primitive(PrimitiveType::Float)) => Value::Float(i as f64),
+        (Value::Int(i), ty) => Value::Int(wrap(i, ty)),
+        (value, _) => value,
+    }
+}
+
+fn arith(inst: &Instruction, left: Value, right: Value, ty: &Type) -> Flow<Value> {
+    let unsigned = matches!(ty, Type::Primitive(p) if p.is_unsigned());
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) if !matches!(ty, Type::Primitive(PrimitiveType::Float)) => {
+            let result = match inst {
+                Instruction::Add { .. } => a.wrapping_add(b),
+                Instruction::Sub { .. } => a.wrapping_sub(b),
+                Instruction::Mul { .. } => a.wrapping_mul(b),
+                _ if b == 0 => return trap("division by zero"),
+                Instruction::Div { .. } if unsigned => ((a as u64) / (b as u64)) as i64,
+                Instruction::Div { .. } => a.wrapping_div(b),
+                _ if unsigned => ((a as u64) % (b as u64)) as i64,
+                _ => a.wrapping_rem(b),
+            };
+            Ok(Value::Int(wrap(result, ty)))
+        }
+        (l @ (Value::Int(_) | Value::Float(_)), r @ (Value::Int(_) | Value::Float(_))) => {
+            let (a, b) = (float(&l), float(&r));
+            Ok(Value::Float(match inst {
+                Instruction::Add { .. } => a + b,
+                Instruction::Sub { .. } => a - b,
+                Instruction::Mul { .. } => a * b,
+                Instruction::Div { .. } => a / b,
+                _ => a % b,
+            }))
+        }
+        (l, r) => trap(format!("cannot do arithmetic on {:?} and {:?}", l, r)),
+    }
+}
+
+fn float(value: &Value) -> f64 {
+    match value {
+        Value::Int(i) => *i as f64,
+        Value::Float(f) => *f,
+        _ => 0.0,
+    }
+}
+
+fn compare(left: &Value, right: &Value, unsigned: bool) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Int(a), Value::Int(b)) if unsigned => Some((*a as u64).cmp(&(*b as u64))),
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => float(left).partial_cmp(&float(right)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        // pointers, fns, .. only compare 4 equality
+        (a, b) if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
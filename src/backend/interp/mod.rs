@@ -0,0 +1,13 @@
+pub mod factory;
+pub mod codegen;
+pub mod optimizer;
+pub mod emitter;
+pub mod interpreter;
+pub mod value;
+
+pub use factory::InterpBackendFactory;
+pub use codegen::{InterpCodeGen, InterpModule};
+pub use optimizer::InterpOptimizer;
+pub use emitter::InterpEmitter;
+pub use interpreter::Interpreter;
+pub use value::Value;
@@ -0,0 +1,19 @@
+use crate::backend::interp::codegen::InterpModule;
+use crate::backend::ports::codegen::Module;
+use crate::backend::ports::optimizer::{OptimizationError, OptimizationPass, Optimizer};
+
+/// the MIR optimizer already ran, this only checks the module holds MIR
+pub struct InterpOptimizer;
+
+impl Optimizer for InterpOptimizer {
+    fn optimize(&mut self, module: &mut Module) -> Result<(), OptimizationError> {
+        match module.data.as_ref().and_then(|d| d.downcast_ref::<InterpModule>()) {
+            Some(_) => Ok(()),
+            None => Err(OptimizationError::OptimizationFailed("Module does not contain MIR".to_string())),
+        }
+    }
+
+    fn add_pass(&mut self, _pass: OptimizationPass) {
+        // no backend passes 2 run
+    }
+}
@@ -0,0 +1,90 @@
+use crate::core::mir::{Constant, Vtable};
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+
+/// a runtime value, ints of evry width r kept as i64 wrapped 2 their type + chars r their code point
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Null,
+    Ptr(Pointer),
+    Func(String),
+    /// struct fields in declaration order or array elements
+    Aggregate(Vec<Value>),
+    Variant { tag: usize, fields: Vec<Value> },
+    Dyn { data: Box<Value>, vtable: Vtable },
+}
+
+/// an address: a root slot then field / element indices in2 the aggregate it holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pointer {
+    pub root: Root,
+    pub path: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Root {
+    /// an alloca, lives as long as the interpreter
+    Cell(usize),
+    /// a local of the frame at this call depth, struct locals r addressed in place
+    Local { frame: usize, local: usize },
+}
+
+impl Value {
+    pub fn from_constant(constant: &Constant) -> Self {
+        match constant {
+            Constant::Int(i) => Value::Int(*i),
+            Constant::Float(f) => Value::Float(*f),
+            Constant::Bool(b) => Value::Bool(*b),
+            Constant::Char(c) => Value::Int(*c as i64),
+            Constant::String(s) => Value::Str(s.clone()),
+            Constant::Null => Value::Null,
+            Constant::Aggregate(elements) => Value::Aggregate(elements.iter().map(Value::from_constant).collect()),
+        }
+    }
+
+    /// what an alloca of `ty` holds b4 its first store
+    pub fn zeroed(ty: &Type) -> Self {
+        match ty {
+            Type::Primitive(PrimitiveType::Void) => Value::Unit,
+            Type::Primitive(PrimitiveType::Float) => Value::Float(0.0),
+            Type::Primitive(PrimitiveType::Bool) => Value::Bool(false),
+            Type::Primitive(_) => Value::Int(0),
+            Type::Struct(s) => Value::Aggregate(s.fields.iter().map(|f| Value::zeroed(&f.type_)).collect()),
+            Type::Array(a) => Value::Aggregate(vec![Value::zeroed(&a.element); a.size]),
+            Type::Enum(_) => Value::Variant { tag: 0, fields: Vec::new() },
+            Type::String => Value::Str(String::new()),
+            Type::Pointer(_) | Type::Function(_) | Type::TraitObject(_) => Value::Null,
+            Type::Generic(_) | Type::Error => Value::Unit,
+        }
+    }
+
+    /// a branch condition, C style 4 ints
+    pub fn truthy(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            Value::Int(i) => Some(*i != 0),
+            Value::Null => Some(false),
+            Value::Ptr(_) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// `value` truncated + sign or zero extended back the way a `ty` register holds it
+pub fn wrap(value: i64, ty: &Type) -> i64 {
+    match ty {
+        Type::Primitive(p) if p.is_integer() => match (p.size_in_bytes(), p.is_unsigned()) {
+            (1, false) => value as i8 as i64,
+            (1, true) => value as u8 as i64,
+            (4, false) => value as i32 as i64,
+            (4, true) => value as u32 as i64,
+            _ => value,
+        },
+        _ => value,
+    }
+}
@@ -0,0 +1,605 @@
+use crate::backend::ports::emitter::EmitError;
+use crate::core::target::TargetInfo;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// dflt linker driver (gcc/clang style cli)
+pub const DEFAULT_LINKER: &str = "cc";
+
+/// tools that move debug info out of objects/executables
+pub const OBJCOPY: &str = "objcopy";
+pub const DSYMUTIL: &str = "dsymutil";
+/// compiles a module w/ codegen options the c api cant set, eg -stack-size-section
+pub const LLC: &str = "llc";
+
+/// the crt link.exe gets 4 evry output, the static 1 like cl's dflt /MT
+const MSVC_CRT: [&str; 3] = ["libcmt.lib", "libucrt.lib", "libvcruntime.lib"];
+
+/// where debug info ends up (-C split-debuginfo)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitDebugInfo {
+    /// left in the executable
+    #[default]
+    Off,
+    /// one file 4 the whole executable: `.debug` w/ a gnu debuglink, `.dSYM` bundle or `.pdb`
+    Packed,
+    /// per object: `.dwo` beside the output, the `.o` itself on macos (found via the debug map)
+    Unpacked,
+}
+
+impl SplitDebugInfo {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(Self::Off),
+            "packed" => Some(Self::Packed),
+            "unpacked" => Some(Self::Unpacked),
+            _ => None,
+        }
+    }
+}
+
+/// cli dialect of the linker
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LinkerFlavor {
+    /// cc/gcc/clang driver
+    #[default]
+    Gnu,
+    /// link.exe or lld-link
+    Msvc,
+    /// cc driving ld64 (or ld64.lld) on apple platforms
+    Darwin,
+    /// wasm-ld (lld) producing a .wasm module
+    Wasm,
+}
+
+impl LinkerFlavor {
+    pub fn for_target(target: &TargetInfo) -> Self {
+        if target.is_wasm() {
+            LinkerFlavor::Wasm
+        } else if target.os == "windows" && target.triple.ends_with("msvc") {
+            LinkerFlavor::Msvc
+        } else if target.os == "macos" {
+            LinkerFlavor::Darwin
+        } else {
+            LinkerFlavor::Gnu
+        }
+    }
+
+    pub fn default_archiver(&self) -> &'static str {
+        match self {
+            LinkerFlavor::Msvc if cfg!(windows) => "lib.exe",
+            LinkerFlavor::Msvc => "llvm-lib",
+            LinkerFlavor::Gnu | LinkerFlavor::Darwin => DEFAULT_ARCHIVER,
+            LinkerFlavor::Wasm => LLVM_ARCHIVER,
+        }
+    }
+
+    pub fn default_linker(&self) -> &'static str {
+        match self {
+            LinkerFlavor::Gnu | LinkerFlavor::Darwin => DEFAULT_LINKER,
+            // lld-link can target msvc from any host
+            LinkerFlavor::Msvc if cfg!(windows) => "link.exe",
+            LinkerFlavor::Msvc => "lld-link",
+            LinkerFlavor::Wasm => "wasm-ld",
+        }
+    }
+}
+
+/// archivers 4 static libs, llvm-ar stands in when binutils isnt installed
+pub const DEFAULT_ARCHIVER: &str = "ar";
+pub const LLVM_ARCHIVER: &str = "llvm-ar";
+
+/// visibility of the fns a library defines (--visibility)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymbolVisibility {
+    /// evry fn is exported
+    #[default]
+    Default,
+    /// only fns named w/ --export r, the rest stay inside the library
+    Hidden,
+}
+
+impl SymbolVisibility {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "default" => Some(Self::Default),
+            "hidden" => Some(Self::Hidden),
+            _ => None,
+        }
+    }
+}
+
+/// what the linker drops frm the output (--strip)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strip {
+    #[default]
+    None,
+    DebugInfo,
+    /// debug info + the symbol table
+    Symbols,
+}
+
+impl Strip {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "none" => Some(Self::None),
+            "debuginfo" => Some(Self::DebugInfo),
+            "symbols" => Some(Self::Symbols),
+            _ => None,
+        }
+    }
+}
+
+/// SHF_COMPRESSED format 4 elf debug sections (-C debuginfo-compression)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugCompression {
+    Zlib,
+    Zstd,
+}
+
+impl DebugCompression {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "zlib" => Some(Self::Zlib),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zlib => "zlib",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// options passed 2 the system linker driver when producing an executable
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkOptions {
+    pub linker: Option<String>,
+    pub flavor: LinkerFlavor,
+    pub target: Option<TargetInfo>,
+    pub script: Option<PathBuf>,
+    pub library_paths: Vec<PathBuf>,
+    pub libs: Vec<String>,
+    /// produce a shared library (dll + import .lib on msvc)
+    pub shared: bool,
+    /// macos sdk passed as -isysroot
+    pub sdk_root: Option<PathBuf>,
+    /// minimum macos version (-mmacosx-version-min)
+    pub deployment_target: Option<String>,
+    pub split_debuginfo: SplitDebugInfo,
+    pub strip: Strip,
+    pub compress_debug: Option<DebugCompression>,
+    /// separately compiled objects, eg imported modules, linked in w/ the emitted one
+    pub objects: Vec<PathBuf>,
+    /// ar/lib.exe 4 static libs, dflt per flavor
+    pub archiver: Option<String>,
+    pub visibility: SymbolVisibility,
+    /// fns that stay visible when visibility is hidden
+    pub exports: Vec<String>,
+}
+
+impl LinkOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_linker(mut self, linker: String) -> Self {
+        self.linker = Some(linker);
+        self
+    }
+
+    /// pick the flavor 4 the triple, eg msvc 4 x86_64-pc-windows-msvc
+    pub fn with_target(mut self, target: TargetInfo) -> Self {
+        self.flavor = LinkerFlavor::for_target(&target);
+        self.target = Some(target);
+        self
+    }
+
+    pub fn with_script(mut self, script: PathBuf) -> Self {
+        self.script = Some(script);
+        self
+    }
+
+    pub fn with_library_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.library_paths = paths;
+        self
+    }
+
+    pub fn with_libs(mut self, libs: Vec<String>) -> Self {
+        self.libs = libs;
+        self
+    }
+
+    pub fn with_objects(mut self, objects: Vec<PathBuf>) -> Self {
+        self.objects = objects;
+        self
+    }
+
+    pub fn with_shared(mut self, shared: bool) -> Self {
+        self.shared = shared;
+        self
+    }
+
+    pub fn with_archiver(mut self, archiver: String) -> Self {
+        self.archiver = Some(archiver);
+        self
+    }
+
+    pub fn with_visibility(mut self, visibility: SymbolVisibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    pub fn with_exports(mut self, exports: Vec<String>) -> Self {
+        self.exports = exports;
+        self
+    }
+
+    /// true if the fn `name` is visible outside a library built w/ these options
+    pub fn exports_symbol(&self, name: &str) -> bool {
+        self.visibility == SymbolVisibility::Default || self.exports.iter().any(|e| e == name)
+    }
+
+    pub fn with_sdk_root(mut self, sdk_root: PathBuf) -> Self {
+        self.sdk_root = Some(sdk_root);
+        self
+    }
+
+    pub fn with_deployment_target(mut self, version: String) -> Self {
+        self.deployment_target = Some(version);
+        self
+    }
+
+    pub fn with_split_debuginfo(mut self, split: SplitDebugInfo) -> Self {
+        self.split_debuginfo = split;
+        self
+    }
+
+    pub fn with_strip(mut self, strip: Strip) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    pub fn with_debug_compression(mut self, compression: DebugCompression) -> Self {
+        self.compress_debug = Some(compression);
+        self
+    }
+
+    /// fill in the sdk + deployment target the way xcode toolchains do:
+    /// SDKROOT / MACOSX_DEPLOYMENT_TARGET, else xcrun on a mac host
+    pub fn with_apple_sdk_from_env(mut self) -> Self {
+        if self.sdk_root.is_none() {
+            self.sdk_root = std::env::var_os("SDKROOT").map(PathBuf::from).or_else(|| {
+                if !cfg!(target_os = "macos") {
+                    return None;
+                }
+                let out = Command::new("xcrun").args(["--sdk", "macosx", "--show-sdk-path"]).output().ok()?;
+                let path = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                if out.status.success() && !path.is_empty() { Some(PathBuf::from(path)) } else { None }
+            });
+        }
+        if self.deployment_target.is_none() {
+            self.deployment_target = std::env::var("MACOSX_DEPLOYMENT_TARGET").ok();
+        }
+        self
+    }
+
+    /// windows targets need dllimport/dllexport on symbols crossing a dll
+    pub fn is_windows(&self) -> bool {
+        self.target.as_ref().map(|t| t.os == "windows").unwrap_or(false)
+    }
+
+    /// import library written next 2 a dll
+    pub fn import_lib_path(output: &Path) -> PathBuf {
+        output.with_extension("lib")
+    }
+
+    /// unpacked debug info on macos stays in the objects, emitters must not delete them
+    pub fn keeps_objects(&self) -> bool {
+        self.flavor == LinkerFlavor::Darwin && self.split_debuginfo == SplitDebugInfo::Unpacked
+    }
+
+    /// where split debug info 4 `output` is written, None if it stays in the output/objects
+    pub fn debuginfo_path(&self, output: &Path) -> Option<PathBuf> {
+        let mut name = output.as_os_str().to_owned();
+        match (self.flavor, self.split_debuginfo) {
+            (_, SplitDebugInfo::Off) | (LinkerFlavor::Darwin, SplitDebugInfo::Unpacked) => return None,
+            (LinkerFlavor::Gnu, SplitDebugInfo::Packed) => name.push(".debug"),
+            (LinkerFlavor::Gnu, SplitDebugInfo::Unpacked) => return Some(output.with_extension("dwo")),
+            (LinkerFlavor::Darwin, SplitDebugInfo::Packed) => name.push(".dSYM"),
+            (LinkerFlavor::Msvc, _) => return Some(output.with_extension("pdb")),
+            // dwarf stays in the module's custom sections
+            (LinkerFlavor::Wasm, _) => return None,
+        }
+        Some(PathBuf::from(name))
+    }
+
+    /// linker driver args 4 linking `objects` into `output`
+    pub fn args(&self, objects: &[&Path], output: &Path) -> Vec<String> {
+        match self.flavor {
+            LinkerFlavor::Gnu => self.gnu_args(objects, output),
+            LinkerFlavor::Msvc => self.msvc_args(objects, output),
+            LinkerFlavor::Darwin => self.darwin_args(objects, output),
+            LinkerFlavor::Wasm => self.wasm_args(objects, output),
+        }
+    }
+
+    /// archiver args 4 bundling `objects` in2 the static lib `output`
+    pub fn archive_args(&self, objects: &[&Path], output: &Path) -> Vec<String> {
+        let mut args = match self.flavor {
+            LinkerFlavor::Msvc => vec!["/NOLOGO".to_string(), format!("/OUT:{}", output.to_string_lossy())],
+            // s writes the symbol index the linker searches
+            _ => vec!["crs".to_string(), output.to_string_lossy().to_string()],
+        };
+        args.extend(objects.iter().map(|o| o.to_string_lossy().to_string()));
+        args
+    }
+
+    fn darwin_args(&self, objects: &[&Path], output: &Path) -> Vec<String> {
+        let arch = self.target.as_ref().map(|t| t.arch.as_str()).unwrap_or("arm64");
+        // apple spells aarch64 arm64
+        let arch = if arch == "aarch64" { "arm64" } else { arch };
+        // oldest macos each arch shipped w/
+        let min_version = self
+            .deployment_target
+            .clone()
+            .unwrap_or_else(|| if arch == "arm64" { "11.0" } else { "10.12" }.to_string());
+
+        let mut args: Vec<String> = objects
+            .iter()
+            .map(|o| o.to_string_lossy().to_string())
+            .collect();
+        args.push("-o".to_string());
+        args.push(output.to_string_lossy().to_string());
+        args.push("-arch".to_string());
+        args.push(arch.to_string());
+        args.push(format!("-mmacosx-version-min={}", min_version));
+        if let Some(ref sdk) = self.sdk_root {
+            args.push("-isysroot".to_string());
+            args.push(sdk.to_string_lossy().to_string());
+        }
+        if self.shared {
+            args.push("-dynamiclib".to_string());
+        }
+        // ld64 can drop the debug map + local symbols but not the whole symbol table
+        if self.strip != Strip::None {
+            args.push("-Wl,-S".to_string());
+        }
+        if self.strip == Strip::Symbols {
+            args.push("-Wl,-x".to_string());
+        }
+        for path in &self.library_paths {
+            args.push(format!("-L{}", path.to_string_lossy()));
+        }
+        for lib in &self.libs {
+            args.push(format!("-l{}", lib));
+        }
+        args
+    }
+
+    fn gnu_args(&self, objects: &[&Path], output: &Path) -> Vec<String> {
+        let mut args: Vec<String> = objects
+            .iter()
+            .map(|o| o.to_string_lossy().to_string())
+            .collect();
+        args.push("-o".to_string());
+        args.push(output.to_string_lossy().to_string());
+        if self.shared {
+            args.push("-shared".to_string());
+        }
+        match self.strip {
+            Strip::None => {}
+            Strip::DebugInfo => args.push("-Wl,--strip-debug".to_string()),
+            Strip::Symbols => args.push("-Wl,--strip-all".to_string()),
+        }
+        if let (Strip::None, Some(compression)) = (self.strip, self.compress_debug) {
+            args.push(format!("-Wl,--compress-debug-sections={}", compression.as_str()));
+        }
+
+        // script goes b4 libs so its MEMORY/SECTIONS apply 2 evrythng
+        if let Some(ref script) = self.script {
+            args.push("-T".to_string());
+            args.push(script.to_string_lossy().to_string());
+        }
+        for path in &self.library_paths {
+            args.push(format!("-L{}", path.to_string_lossy()));
+        }
+        for lib in &self.libs {
+            args.push(format!("-l{}", lib));
+        }
+        args
+    }
+
+    fn wasm_args(&self, objects: &[&Path], output: &Path) -> Vec<String> {
+        let mut args: Vec<String> = objects
+            .iter()
+            .map(|o| o.to_string_lossy().to_string())
+            .collect();
+        args.push("-o".to_string());
+        args.push(output.to_string_lossy().to_string());
+        // a library style module: no _start, evry exported fn visible 2 the host
+        args.push("--no-entry".to_string());
+        args.push("--export-dynamic".to_string());
+        match self.strip {
+            Strip::None => {}
+            Strip::DebugInfo => args.push("--strip-debug".to_string()),
+            Strip::Symbols => args.push("--strip-all".to_string()),
+        }
+        for path in &self.library_paths {
+            args.push(format!("-L{}", path.to_string_lossy()));
+        }
+        for lib in &self.libs {
+            args.push(format!("-l{}", lib));
+        }
+        args
+    }
+
+    fn msvc_args(&self, objects: &[&Path], output: &Path) -> Vec<String> {
+        let mut args = vec!["/NOLOGO".to_string(), format!("/OUT:{}", output.to_string_lossy())];
+        if self.shared {
+            args.push("/DLL".to_string());
+            args.push(format!("/IMPLIB:{}", Self::import_lib_path(output).to_string_lossy()));
+        } else {
+            args.push("/SUBSYSTEM:CONSOLE".to_string());
+        }
+        // codeview always lives in a pdb, off means dont write one
+        if let (Strip::None, Some(pdb)) = (self.strip, self.debuginfo_path(output)) {
+            args.push("/DEBUG".to_string());
+            args.push(format!("/PDB:{}", pdb.to_string_lossy()));
+        }
+        args.extend(objects.iter().map(|o| o.to_string_lossy().to_string()));
+        for path in &self.library_paths {
+            args.push(format!("/LIBPATH:{}", path.to_string_lossy()));
+        }
+        // -lfoo means foo.lib
+        for lib in &self.libs {
+            if lib.ends_with(".lib") {
+                args.push(lib.clone());
+            } else {
+                args.push(format!("{}.lib", lib));
+            }
+        }
+        // unlike a cc driver link.exe names no crt, its mainCRTStartup is what calls main
+        args.extend(MSVC_CRT.iter().map(|lib| format!("/DEFAULTLIB:{}", lib)));
+        args
+    }
+}
+
+/// run the linker driver on object files
+pub fn link(objects: &[&Path], output: &Path, options: &LinkOptions) -> Result<(), EmitError> {
+    if let Some(ref script) = options.script {
+        match options.flavor {
+            LinkerFlavor::Msvc => {
+                return Err(EmitError::EmissionFailed(
+                    "linker scripts are not supported by the MSVC linker".to_string(),
+                ))
+            }
+            LinkerFlavor::Darwin => {
+                return Err(EmitError::EmissionFailed(
+                    "linker scripts are not supported by ld64".to_string(),
+                ))
+            }
+            LinkerFlavor::Wasm => {
+                return Err(EmitError::EmissionFailed(
+                    "linker scripts are not supported by wasm-ld".to_string(),
+                ))
+            }
+            LinkerFlavor::Gnu => {}
+        }
+        if !script.exists() {
+            return Err(EmitError::InvalidPath(format!(
+                "linker script not found: {}",
+                script.display()
+            )));
+        }
+    }
+
+    if options.compress_debug.is_some() && options.flavor != LinkerFlavor::Gnu {
+        return Err(EmitError::EmissionFailed(
+            "debug section compression is only supported for ELF targets".to_string(),
+        ));
+    }
+
+    // per object debug info has 2 come out b4 the objects r linked
+    let dwo = if options.flavor == LinkerFlavor::Gnu && options.split_debuginfo == SplitDebugInfo::Unpacked {
+        let mut dwos = Vec::new();
+        for object in objects {
+            let dwo = object.with_extension("dwo");
+            let mut args = vec![arg("--only-keep-debug"), arg(object), arg(&dwo)];
+            args.extend(options.compress_debug.map(|c| format!("--compress-debug-sections={}", c.as_str())));
+            run_tool("objcopy", OBJCOPY, &args)?;
+            run_tool("objcopy", OBJCOPY, &[arg("--strip-debug"), arg(object)])?;
+            dwos.push(dwo);
+        }
+        // a debuglink names one file, so only a single object gets linked back 2 the executable
+        if dwos.len() == 1 { dwos.pop() } else { None }
+    } else {
+        None
+    };
+
+    let linker = options.linker.as_deref().unwrap_or(options.flavor.default_linker());
+    let mut all = objects.to_vec();
+    all.extend(options.objects.iter().map(PathBuf::as_path));
+    run_tool("linker", linker, &options.args(&all, output))?;
+
+    match (options.flavor, options.split_debuginfo) {
+        (LinkerFlavor::Gnu, SplitDebugInfo::Packed) => {
+            let debug = options.debuginfo_path(output).unwrap();
+            run_tool("objcopy", OBJCOPY, &[arg("--only-keep-debug"), arg(output), arg(&debug)])?;
+            run_tool("objcopy", OBJCOPY, &[arg("--strip-debug"), format!("--add-gnu-debuglink={}", debug.display()), arg(output)])
+        }
+        (LinkerFlavor::Gnu, SplitDebugInfo::Unpacked) => match dwo {
+            Some(dwo) => run_tool("objcopy", OBJCOPY, &[format!("--add-gnu-debuglink={}", dwo.display()), arg(output)]),
+            None => Ok(()),
+        },
+        // dsymutil reads the debug map so the objects must still exist here
+        (LinkerFlavor::Darwin, SplitDebugInfo::Packed) => {
+            let dsym = options.debuginfo_path(output).unwrap();
+            run_tool("dsymutil", DSYMUTIL, &[arg(output), arg("-o"), arg(&dsym)])
+        }
+        _ => Ok(()),
+    }
+}
+
+/// bundle objects in2 a static library, the options' extra objects too
+/// an existing archive is replaced, ar would otherwise add 2 it
+pub fn archive(objects: &[&Path], output: &Path, options: &LinkOptions) -> Result<(), EmitError> {
+    let mut all = objects.to_vec();
+    all.extend(options.objects.iter().map(PathBuf::as_path));
+    let args = options.archive_args(&all, output);
+    if output.exists() {
+        std::fs::remove_file(output)?;
+    }
+    match &options.archiver {
+        Some(archiver) => run_tool("archiver", archiver, &args),
+        None => {
+            let archiver = options.flavor.default_archiver();
+            let result = run_tool("archiver", archiver, &args);
+            if result.is_err() && archiver == DEFAULT_ARCHIVER {
+                return run_tool("archiver", LLVM_ARCHIVER, &args).or(result);
+            }
+            result
+        }
+    }
+}
+
+/// compress the debug sections of an emitted elf object in place
+/// llvm + cranelift write them uncompressed so objcopy does it after emission
+pub fn compress_debug_sections(object: &Path, options: &LinkOptions) -> Result<(), EmitError> {
+    match options.compress_debug {
+        Some(compression) if options.flavor == LinkerFlavor::Gnu => run_tool(
+            "objcopy",
+            OBJCOPY,
+            &[format!("--compress-debug-sections={}", compression.as_str()), arg(object)],
+        ),
+        Some(_) => Err(EmitError::EmissionFailed(
+            "debug section compression is only supported for ELF targets".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn arg(path: impl AsRef<Path>) -> String {
+    path.as_ref().to_string_lossy().to_string()
+}
+
+/// run a toolchain program, its output becomes the error if it fails
+pub(crate) fn run_tool(what: &str, tool: &str, args: &[String]) -> Result<(), EmitError> {
+    let result = Command::new(tool)
+        .args(args)
+        .output()
+        .map_err(|e| EmitError::EmissionFailed(format!("failed to run {} '{}': {}", what, tool, e)))?;
+
+    if !result.status.success() {
+        // link.exe reports errors on stdout
+        let mut message = String::from_utf8_lossy(&result.stderr).trim().to_string();
+        if message.is_empty() {
+            message = String::from_utf8_lossy(&result.stdout).trim().to_string();
+        }
+        return Err(EmitError::EmissionFailed(format!("{} '{}' failed: {}", what, tool, message)));
+    }
+    Ok(())
+}
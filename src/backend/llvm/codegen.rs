@@ -1,13 +1,21 @@
-use crate::backend::ports::codegen::{CodeGen, CodeGenError, Module, OptimizationLevel, BackendInputType};
+use crate::backend::ports::codegen::{CodeGen, CodeGenError, Module, OptimizationLevel, BackendInputType, DebugInfo, DebugInfoLevel, PanicStrategy};
+use crate::backend::llvm::debuginfo::{attach_debug_info, collect_variables, DebugVariable};
+use crate::backend::llvm::target::set_module_target;
 use crate::backend::llvm::context::{LlvmContext, create_module_name};
-use crate::backend::llvm::types::mir_type_to_llvm_type;
+use crate::backend::llvm::types::{mir_type_to_llvm_type, string_type, trait_object_type};
 use crate::backend::llvm::instructions::*;
+use crate::backend::llvm::emitter::LINKED_ATTR;
+use crate::backend::llvm::runtime;
+use crate::backend::multiversion::{self, dispatch_kind, DispatchKind};
 use crate::core::mir::MirFunction;
-use crate::core::mir::instruction::Instruction;
+use crate::core::target::{CallConv, TargetInfo};
+use crate::core::mir::instruction::{Instruction, Vtable};
+use crate::core::mir::operand::{Constant, Local, Operand};
+use crate::core::types::ty::Type;
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
-use std::collections::HashMap;
-use std::ffi::CString;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CStr, CString};
 
 /// wrapper for LLVM module that handles disposal
 pub(crate) struct LlvmModuleWrapper {
@@ -44,6 +52,18 @@ pub struct LlvmCodeGen {
     builder: LLVMBuilderRef,
     opt_level: OptimizationLevel,
     target_triple: String,
+    debug_info: Option<DebugInfo>,
+    /// params/locals per emitted llvm fn, only collected at full debug info
+    debug_variables: HashMap<String, Vec<DebugVariable>>,
+    panic_strategy: PanicStrategy,
+    /// interned literal bytes, one private global per distinct string
+    strings: HashMap<String, LLVMValueRef>,
+    /// llvm fn type of evry mir fn, vtables can point at fns not emitted yet
+    fn_types: HashMap<String, LLVMTypeRef>,
+    /// `vtable.Type.Trait` globals already emitted
+    vtables: HashMap<String, LLVMValueRef>,
+    /// fns this codegen unit defines, none = all of them
+    unit: Option<HashSet<String>>,
 }
 
 impl LlvmCodeGen {
@@ -60,36 +80,53 @@ impl LlvmCodeGen {
                 builder,
                 opt_level: OptimizationLevel::Default,
                 target_triple: Self::default_target_triple(),
+                debug_info: None,
+                debug_variables: HashMap::new(),
+                panic_strategy: PanicStrategy::Abort,
+                strings: HashMap::new(),
+                fn_types: HashMap::new(),
+                vtables: HashMap::new(),
+                unit: None,
             }
         }
     }
 
     fn default_target_triple() -> String {
-        // try to detect target triple, fallback to host
-        #[cfg(all(target_arch = "x86_64", target_os = "linux"))]
-        {
-            "x86_64-unknown-linux-gnu".to_string()
-        }
-        
-        #[cfg(all(target_arch = "x86_64", target_os = "macos"))]
-        {
-            "x86_64-apple-darwin".to_string()
-        }
-        
-        #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
-        {
-            "x86_64-pc-windows-msvc".to_string()
-        }
-        
-        #[cfg(not(any(
-            all(target_arch = "x86_64", target_os = "linux"),
-            all(target_arch = "x86_64", target_os = "macos"),
-            all(target_arch = "x86_64", target_os = "windows")
-        )))]
-        {
-            // default fallback
-            "x86_64-unknown-linux-gnu".to_string()
-        }
+        // host triple incl aarch64 macs and linux
+        TargetInfo::host().triple
+    }
+}
+
+impl LlvmCodeGen {
+    /// hand the generated module over, the codegen (and its context) no longer owns it
+    #[cfg(not(feature = "inkwell"))]
+    fn take_module(&mut self) -> Result<Module, CodeGenError> {
+        // create module wrapper with LLVM module stored
+        let module_name = "emerald_module".to_string();
+        // wrap LLVM module in a type that handles disposal
+        let module_wrapper = LlvmModuleWrapper::new(self.module);
+        // don't dispose module in Drop since we're transferring ownership
+        // set module to null to prevent double disposal
+        self.module = std::ptr::null_mut();
+        Ok(Module::with_data(module_name, Box::new(module_wrapper)))
+    }
+
+    /// w/ inkwell the module leaves as bitcode, so it doesnt borrow this codegen's context
+    #[cfg(feature = "inkwell")]
+    fn take_module(&mut self) -> Result<Module, CodeGenError> {
+        use crate::backend::llvm::safe::BitcodeModule;
+        use llvm_sys::bit_writer::LLVMWriteBitcodeToMemoryBuffer;
+
+        let bitcode = unsafe {
+            let buffer = LLVMWriteBitcodeToMemoryBuffer(self.module);
+            let start = LLVMGetBufferStart(buffer) as *const u8;
+            let bitcode = std::slice::from_raw_parts(start, LLVMGetBufferSize(buffer)).to_vec();
+            LLVMDisposeMemoryBuffer(buffer);
+            LLVMDisposeModule(self.module);
+            bitcode
+        };
+        self.module = std::ptr::null_mut();
+        Ok(Module::with_data("emerald_module".to_string(), Box::new(BitcodeModule { bitcode })))
     }
 }
 
@@ -107,24 +144,47 @@ impl Drop for LlvmCodeGen {
 
 impl CodeGen for LlvmCodeGen {
     fn generate_from_mir(&mut self, mir_functions: &[MirFunction]) -> Result<Module, CodeGenError> {
-        // set target triple - use LLVMSetModuleDataLayout or similar if available
-        // Note: LLVMSetTargetTriple might not be available in llvm-sys 211
-        // For now, we'll set it via module properties if the function exists
-        // If not available, the target will be set during emission
+        if self.panic_strategy == PanicStrategy::Unwind {
+            return Err(CodeGenError::UnsupportedFeature("panic=unwind, panics always abort".to_string()));
+        }
+        // triple + data layout go on the module b4 anything is sized, the emitter reads them back
+        unsafe { set_module_target(self.module, &self.target_triple) }.map_err(CodeGenError::InvalidTarget)?;
+
+        for mir_func in mir_functions.iter().filter(|f| f.target_features.is_empty()) {
+            let ty = unsafe { self.function_type(mir_func) };
+            self.fn_types.insert(mir_func.name.clone(), ty);
+        }
+
+        // fns of other units r only declared, on first call
+        let defined: Vec<&MirFunction> = mir_functions.iter()
+            .filter(|f| self.unit.as_ref().is_none_or(|unit| unit.contains(&f.name)))
+            .collect();
 
         // translate each MIR function to LLVM function
-        for mir_func in mir_functions {
+        for mir_func in defined {
             self.translate_function(mir_func)?;
         }
+        unsafe { self.mark_linked(mir_functions) };
+        unsafe { self.emit_panic_strategy() };
+        unsafe { runtime::define_panic(self.module, self.context.get(), &TargetInfo::from_triple(&self.target_triple)) };
+        unsafe { runtime::define_allocator(self.module, self.context.get()) };
+        if let Some(ref debug_info) = self.debug_info {
+            let target = TargetInfo::from_triple(&self.target_triple);
+            let codeview = target.os == "windows" && target.triple.ends_with("msvc");
+            unsafe {
+                attach_debug_info(
+                    self.module,
+                    debug_info,
+                    mir_functions,
+                    &self.debug_variables,
+                    codeview,
+                    self.opt_level != OptimizationLevel::None,
+                );
+            }
+        }
 
-        // create module wrapper with LLVM module stored
-        let module_name = "emerald_module".to_string();
-        // wrap LLVM module in a type that handles disposal
-        let module_wrapper = LlvmModuleWrapper::new(self.module);
-        // don't dispose module in Drop since we're transferring ownership
-        // set module to null to prevent double disposal
-        self.module = std::ptr::null_mut();
-        Ok(Module::with_data(module_name, Box::new(module_wrapper)))
+        unsafe { self.verify_module() }?;
+        self.take_module()
     }
 
     fn set_optimization_level(&mut self, level: OptimizationLevel) {
@@ -132,52 +192,112 @@ impl CodeGen for LlvmCodeGen {
     }
 
     fn set_target_triple(&mut self, triple: String) {
+        // validated when the module is generated
         self.target_triple = triple;
-        // Note: LLVMSetTargetTriple might not be available in llvm-sys 211
-        // Target triple will be set during emission
     }
 
     fn preferred_input(&self) -> BackendInputType {
         BackendInputType::Mir
     }
+
+    fn set_debug_info(&mut self, debug_info: DebugInfo) {
+        self.debug_info = Some(debug_info);
+    }
+
+    fn set_panic_strategy(&mut self, strategy: PanicStrategy) {
+        self.panic_strategy = strategy;
+    }
+
+    fn set_codegen_unit(&mut self, functions: Vec<String>) {
+        self.unit = Some(functions.into_iter().collect());
+    }
 }
 
 impl LlvmCodeGen {
+    /// bad IR otherwise only shows up as a crash in the optimizer or emitter, the error names the
+    /// emerald fns llvm rejected + keeps its message
+    unsafe fn verify_module(&self) -> Result<(), CodeGenError> {
+        use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyFunction, LLVMVerifyModule};
+
+        let mut message = std::ptr::null_mut();
+        let broken = LLVMVerifyModule(self.module, LLVMVerifierFailureAction::LLVMReturnStatusAction, &mut message) != 0;
+        let details = if message.is_null() {
+            String::new()
+        } else {
+            let text = CStr::from_ptr(message).to_string_lossy().trim_end().to_string();
+            LLVMDisposeMessage(message);
+            text
+        };
+        if !broken {
+            return Ok(());
+        }
+
+        let mut functions: Vec<String> = Vec::new();
+        let mut function = LLVMGetFirstFunction(self.module);
+        while !function.is_null() {
+            if LLVMIsDeclaration(function) == 0
+                && LLVMVerifyFunction(function, LLVMVerifierFailureAction::LLVMReturnStatusAction) != 0
+            {
+                let mut len = 0;
+                let name_ptr = LLVMGetValueName2(function, &mut len);
+                let name = String::from_utf8_lossy(std::slice::from_raw_parts(name_ptr as *const u8, len)).to_string();
+                // multiversion clones r `f.avx2`, report `f`
+                let base = name.split('.').next().unwrap_or(&name).to_string();
+                if !functions.contains(&base) {
+                    functions.push(base);
+                }
+            }
+            function = LLVMGetNextFunction(function);
+        }
+
+        let culprit = match functions.as_slice() {
+            [] => "the module".to_string(),
+            [one] => format!("function '{}'", one),
+            many => format!("functions {}", many.iter().map(|f| format!("'{}'", f)).collect::<Vec<_>>().join(", ")),
+        };
+        Err(CodeGenError::GenerationFailed(format!("LLVM rejected the IR generated for {}:\n{}", culprit, details)))
+    }
+
     /// translate a MIR function to LLVM function
     fn translate_function(&mut self, mir_func: &MirFunction) -> Result<(), CodeGenError> {
+        if !mir_func.target_features.is_empty() {
+            return self.translate_multiversioned(mir_func);
+        }
+        self.translate_function_as(mir_func, &mir_func.name, None).map(|_| ())
+    }
+
+    /// emit `mir_func` under `name`, optionally compiled 4 extra isa features
+    fn translate_function_as(
+        &mut self,
+        mir_func: &MirFunction,
+        name: &str,
+        target_features: Option<&str>,
+    ) -> Result<(LLVMValueRef, LLVMTypeRef), CodeGenError> {
         unsafe {
             let context = self.context.get();
-            
-            // get return type
-            let ret_type = mir_func.return_type.as_ref()
-                .map(|t| mir_type_to_llvm_type(context, t))
-                .unwrap_or_else(|| LLVMVoidType());
-
-            // get parameter types
-            let mut param_types: Vec<LLVMTypeRef> = mir_func.params.iter()
-                .map(|p| mir_type_to_llvm_type(context, &p.type_))
-                .collect();
+            let func_type = self.function_type(mir_func);
 
-            // create function type - need mutable pointer
-            let func_type = if param_types.is_empty() {
-                LLVMFunctionType(
-                    ret_type,
-                    std::ptr::null_mut(),
-                    0,
-                    0, // not variadic
-                )
+            // create function
+            let func_name = CString::new(name).unwrap();
+            // a vtable may have declared it already
+            let declared = LLVMGetNamedFunction(self.module, func_name.as_ptr());
+            let func = if !declared.is_null() && LLVMCountBasicBlocks(declared) == 0 {
+                declared
             } else {
-                LLVMFunctionType(
-                    ret_type,
-                    param_types.as_mut_ptr(),
-                    param_types.len() as u32,
-                    0, // not variadic
-                )
+                LLVMAddFunction(self.module, func_name.as_ptr(), func_type)
             };
-
-            // create function
-            let func_name = CString::new(mir_func.name.clone()).unwrap();
-            let func = LLVMAddFunction(self.module, func_name.as_ptr(), func_type);
+            if let Some(features) = target_features {
+                let key = "target-features";
+                let attr = LLVMCreateStringAttribute(
+                    context,
+                    key.as_ptr() as *const i8,
+                    key.len() as u32,
+                    features.as_ptr() as *const i8,
+                    features.len() as u32,
+                );
+                LLVMAddAttributeAtIndex(func, llvm_sys::LLVMAttributeFunctionIndex, attr);
+            }
+            self.apply_panic_strategy(func);
 
             // create basic blocks
             let mut bb_map = HashMap::new();
@@ -190,6 +310,9 @@ impl LlvmCodeGen {
 
             // translate basic blocks
             let mut local_map = HashMap::new();
+            let local_types: HashMap<usize, Type> = mir_func.locals.iter()
+                .map(|l| (l.local.id, l.type_.clone()))
+                .collect();
             
             // set up parameters
             for (idx, param) in mir_func.params.iter().enumerate() {
@@ -204,11 +327,264 @@ impl LlvmCodeGen {
 
                 // translate instructions
                 for inst in &mir_bb.instructions {
-                    self.translate_instruction(inst, &mut local_map, &bb_map, context)?;
+                    self.translate_instruction(inst, &mut local_map, &local_types, &bb_map, context)?;
                 }
             }
 
-            Ok(())
+            if self.debug_info.as_ref().is_some_and(|d| d.level == DebugInfoLevel::Full) {
+                self.debug_variables.insert(name.to_string(), collect_variables(mir_func, func, &local_map));
+            }
+
+            Ok((func, func_type))
+        }
+    }
+
+    /// nounwind, no unwind tables r emitted. unwind is refused b4 any fn is made
+    unsafe fn apply_panic_strategy(&self, func: LLVMValueRef) {
+        if self.panic_strategy == PanicStrategy::Abort {
+            add_enum_attribute(self.context.get(), func, "nounwind");
+        }
+    }
+
+    /// `emerald_panic_strategy` tells the runtime whether 2 unwind or abort (0 unwind, 1 abort)
+    /// weak_odr so evry object of a program can carry it
+    unsafe fn emit_panic_strategy(&self) {
+        let context = self.context.get();
+        let i8_type = LLVMInt8TypeInContext(context);
+        let name = CString::new("emerald_panic_strategy").unwrap();
+        let global = LLVMAddGlobal(self.module, i8_type, name.as_ptr());
+        let value = match self.panic_strategy {
+            PanicStrategy::Unwind => 0,
+            PanicStrategy::Abort => 1,
+        };
+        LLVMSetInitializer(global, LLVMConstInt(i8_type, value, 0));
+        LLVMSetGlobalConstant(global, 1);
+        LLVMSetLinkage(global, llvm_sys::LLVMLinkage::LLVMWeakODRLinkage);
+    }
+
+    /// tags the declarations the rest of the link defines: other units' fns + imported modules',
+    /// whose names r qualified `mod::f`. whatever is left comes frm a library
+    unsafe fn mark_linked(&mut self, mir_functions: &[MirFunction]) {
+        let context = self.context.get();
+        let mut function = LLVMGetFirstFunction(self.module);
+        while !function.is_null() {
+            let mut len = 0;
+            let name = std::slice::from_raw_parts(LLVMGetValueName2(function, &mut len) as *const u8, len);
+            let name = String::from_utf8_lossy(name);
+            if LLVMIsDeclaration(function) != 0 && (name.contains("::") || mir_functions.iter().any(|f| f.name == name)) {
+                let attr = LLVMCreateStringAttribute(
+                    context,
+                    LINKED_ATTR.as_ptr() as *const i8,
+                    LINKED_ATTR.len() as u32,
+                    "".as_ptr() as *const i8,
+                    0,
+                );
+                LLVMAddAttributeAtIndex(function, llvm_sys::LLVMAttributeFunctionIndex, attr);
+            }
+            function = LLVMGetNextFunction(function);
+        }
+    }
+
+    unsafe fn function_type(&self, mir_func: &MirFunction) -> LLVMTypeRef {
+        let context = self.context.get();
+
+        // get return type
+        let ret_type = mir_func.return_type.as_ref()
+            .map(|t| mir_type_to_llvm_type(context, t))
+            .unwrap_or_else(|| LLVMVoidTypeInContext(context));
+
+        // get parameter types
+        let mut param_types: Vec<LLVMTypeRef> = mir_func.params.iter()
+            .map(|p| mir_type_to_llvm_type(context, &p.type_))
+            .collect();
+
+        // create function type - need mutable pointer
+        if param_types.is_empty() {
+            LLVMFunctionType(
+                ret_type,
+                std::ptr::null_mut(),
+                0,
+                0, // not variadic
+            )
+        } else {
+            LLVMFunctionType(
+                ret_type,
+                param_types.as_mut_ptr(),
+                param_types.len() as u32,
+                0, // not variadic
+            )
+        }
+    }
+
+    /// `@target_feature` fn: one clone per feature + a baseline, and the public
+    /// symbol bound 2 a resolver that picks the best clone 4 the running cpu
+    fn translate_multiversioned(&mut self, mir_func: &MirFunction) -> Result<(), CodeGenError> {
+        let dispatch = dispatch_kind(&TargetInfo::from_triple(&self.target_triple))?;
+        let versions = multiversion::versions(&mir_func.name, &mir_func.target_features)?;
+
+        let mut clones = Vec::new();
+        let mut func_type = std::ptr::null_mut();
+        for version in &versions {
+            let features = version.llvm_target_features();
+            let (func, ty) = self.translate_function_as(mir_func, &version.symbol, features.as_deref())?;
+            // clones r only reachable thru the dispatcher
+            unsafe { LLVMSetLinkage(func, llvm_sys::LLVMLinkage::LLVMInternalLinkage) };
+            clones.push((func, version.cpu_bit));
+            func_type = ty;
+        }
+
+        unsafe {
+            let resolver = self.build_resolver(&mir_func.name, func_type, &clones);
+            let name = CString::new(mir_func.name.clone()).unwrap();
+            match dispatch {
+                DispatchKind::IFunc => {
+                    LLVMAddGlobalIFunc(
+                        self.module,
+                        name.as_ptr(),
+                        mir_func.name.len(),
+                        func_type,
+                        0,
+                        resolver,
+                    );
+                }
+                DispatchKind::Thunk => self.build_dispatch_thunk(&mir_func.name, func_type, resolver),
+            }
+        }
+        Ok(())
+    }
+
+    /// `fn() -> ptr` testing `__cpu_model` feature bits newest isa first
+    unsafe fn build_resolver(
+        &mut self,
+        name: &str,
+        func_type: LLVMTypeRef,
+        clones: &[(LLVMValueRef, Option<u32>)],
+    ) -> LLVMValueRef {
+        let context = self.context.get();
+        let i32_type = LLVMInt32TypeInContext(context);
+        let fn_ptr_type = LLVMPointerType(func_type, 0);
+
+        let resolver_name = CString::new(multiversion::resolver_name(name)).unwrap();
+        let resolver_type = LLVMFunctionType(fn_ptr_type, std::ptr::null_mut(), 0, 0);
+        let resolver = LLVMAddFunction(self.module, resolver_name.as_ptr(), resolver_type);
+        LLVMSetLinkage(resolver, llvm_sys::LLVMLinkage::LLVMInternalLinkage);
+
+        // libgcc / compiler-rt cpu model, filled in by __cpu_indicator_init
+        let void_fn_type = LLVMFunctionType(LLVMVoidTypeInContext(context), std::ptr::null_mut(), 0, 0);
+        let mut init = LLVMGetNamedFunction(self.module, b"__cpu_indicator_init\0".as_ptr() as *const i8);
+        if init.is_null() {
+            init = LLVMAddFunction(self.module, b"__cpu_indicator_init\0".as_ptr() as *const i8, void_fn_type);
+        }
+        let mut model_fields = [i32_type, i32_type, i32_type, LLVMArrayType(i32_type, 1)];
+        let model_type = LLVMStructTypeInContext(context, model_fields.as_mut_ptr(), 4, 0);
+        let mut model = LLVMGetNamedGlobal(self.module, b"__cpu_model\0".as_ptr() as *const i8);
+        if model.is_null() {
+            model = LLVMAddGlobal(self.module, model_type, b"__cpu_model\0".as_ptr() as *const i8);
+        }
+
+        let entry = LLVMAppendBasicBlockInContext(context, resolver, b"entry\0".as_ptr() as *const i8);
+        LLVMPositionBuilderAtEnd(self.builder, entry);
+        // ifunc resolvers run b4 constructors so the model may not b initialized yet
+        LLVMBuildCall2(self.builder, void_fn_type, init, std::ptr::null_mut(), 0, b"\0".as_ptr() as *const i8);
+        let mut indices = [
+            LLVMConstInt(i32_type, 0, 0),
+            LLVMConstInt(i32_type, 3, 0),
+            LLVMConstInt(i32_type, 0, 0),
+        ];
+        let features_ptr = LLVMBuildInBoundsGEP2(
+            self.builder,
+            model_type,
+            model,
+            indices.as_mut_ptr(),
+            indices.len() as u32,
+            b"features.ptr\0".as_ptr() as *const i8,
+        );
+        let features = LLVMBuildLoad2(self.builder, i32_type, features_ptr, b"features\0".as_ptr() as *const i8);
+
+        for (func, bit) in clones {
+            match bit {
+                Some(bit) => {
+                    let mask = LLVMConstInt(i32_type, 1u64 << bit, 0);
+                    let masked = LLVMBuildAnd(self.builder, features, mask, b"has\0".as_ptr() as *const i8);
+                    let zero = LLVMConstInt(i32_type, 0, 0);
+                    let has = LLVMBuildICmp(
+                        self.builder,
+                        llvm_sys::LLVMIntPredicate::LLVMIntNE,
+                        masked,
+                        zero,
+                        b"supported\0".as_ptr() as *const i8,
+                    );
+                    let pick = LLVMAppendBasicBlockInContext(context, resolver, b"pick\0".as_ptr() as *const i8);
+                    let next = LLVMAppendBasicBlockInContext(context, resolver, b"next\0".as_ptr() as *const i8);
+                    LLVMBuildCondBr(self.builder, has, pick, next);
+                    LLVMPositionBuilderAtEnd(self.builder, pick);
+                    LLVMBuildRet(self.builder, *func);
+                    LLVMPositionBuilderAtEnd(self.builder, next);
+                }
+                None => {
+                    // baseline is always last
+                    LLVMBuildRet(self.builder, *func);
+                }
+            }
+        }
+        resolver
+    }
+
+    /// public fn that tail calls the clone cached in `<name>.target` w/ the same args, asking the
+    /// resolver 4 it on the 1st call. racing 1st calls both resolve 2 the same clone
+    unsafe fn build_dispatch_thunk(&mut self, name: &str, func_type: LLVMTypeRef, resolver: LLVMValueRef) {
+        let context = self.context.get();
+        let fn_ptr_type = LLVMPointerType(func_type, 0);
+        // atomics need the pointer's natural alignment
+        let align = TargetInfo::from_triple(&self.target_triple).pointer_width / 8;
+        let symbol = CString::new(name).unwrap();
+        let thunk = LLVMAddFunction(self.module, symbol.as_ptr(), func_type);
+        let target_name = CString::new(multiversion::target_name(name)).unwrap();
+        let cache = LLVMAddGlobal(self.module, fn_ptr_type, target_name.as_ptr());
+        LLVMSetInitializer(cache, LLVMConstNull(fn_ptr_type));
+        LLVMSetLinkage(cache, llvm_sys::LLVMLinkage::LLVMInternalLinkage);
+
+        let [resolve_label, call_label, cached_name, unresolved_name, resolved_name] =
+            ["resolve", "call", "cached", "unresolved", "resolved"].map(|n| CString::new(n).unwrap());
+        let entry = LLVMAppendBasicBlockInContext(context, thunk, b"entry\0".as_ptr() as *const i8);
+        let resolve = LLVMAppendBasicBlockInContext(context, thunk, resolve_label.as_ptr());
+        let call = LLVMAppendBasicBlockInContext(context, thunk, call_label.as_ptr());
+        LLVMPositionBuilderAtEnd(self.builder, entry);
+        let cached = LLVMBuildLoad2(self.builder, fn_ptr_type, cache, cached_name.as_ptr());
+        LLVMSetOrdering(cached, llvm_sys::LLVMAtomicOrdering::LLVMAtomicOrderingMonotonic);
+        LLVMSetAlignment(cached, align);
+        let unresolved = LLVMBuildIsNull(self.builder, cached, unresolved_name.as_ptr());
+        LLVMBuildCondBr(self.builder, unresolved, resolve, call);
+
+        LLVMPositionBuilderAtEnd(self.builder, resolve);
+        let resolver_type = LLVMFunctionType(fn_ptr_type, std::ptr::null_mut(), 0, 0);
+        let resolved = LLVMBuildCall2(self.builder, resolver_type, resolver, std::ptr::null_mut(), 0, resolved_name.as_ptr());
+        let store = LLVMBuildStore(self.builder, resolved, cache);
+        LLVMSetOrdering(store, llvm_sys::LLVMAtomicOrdering::LLVMAtomicOrderingMonotonic);
+        LLVMSetAlignment(store, align);
+        LLVMBuildBr(self.builder, call);
+
+        LLVMPositionBuilderAtEnd(self.builder, call);
+        let target = LLVMBuildPhi(self.builder, fn_ptr_type, b"target\0".as_ptr() as *const i8);
+        let mut incoming = [cached, resolved];
+        let mut blocks = [entry, resolve];
+        LLVMAddIncoming(target, incoming.as_mut_ptr(), blocks.as_mut_ptr(), 2);
+        let mut args: Vec<LLVMValueRef> = (0..LLVMCountParams(thunk)).map(|i| LLVMGetParam(thunk, i)).collect();
+        let returns_void = LLVMGetTypeKind(LLVMGetReturnType(func_type)) == llvm_sys::LLVMTypeKind::LLVMVoidTypeKind;
+        let call_name: &[u8] = if returns_void { b"\0" } else { b"result\0" };
+        let result = LLVMBuildCall2(
+            self.builder,
+            func_type,
+            target,
+            args.as_mut_ptr(),
+            args.len() as u32,
+            call_name.as_ptr() as *const i8,
+        );
+        LLVMSetTailCall(result, 1);
+        if returns_void {
+            LLVMBuildRetVoid(self.builder);
+        } else {
+            LLVMBuildRet(self.builder, result);
         }
     }
 
@@ -217,17 +593,30 @@ impl LlvmCodeGen {
         &mut self,
         inst: &Instruction,
         local_map: &mut HashMap<usize, LLVMValueRef>,
+        local_types: &HashMap<usize, Type>,
         bb_map: &HashMap<usize, LLVMBasicBlockRef>,
         context: LLVMContextRef,
     ) -> Result<(), CodeGenError> {
         unsafe {
+            // struct field address - needs the struct type the mir gep only implies
+            if let Some(s) = gep_struct_base(inst, local_types) {
+                if translate_field_gep(self.builder, inst, s, local_map, context).is_some() {
+                    return Ok(());
+                }
+            }
+            if let Some(array) = gep_array_base(inst, local_types) {
+                if translate_element_gep(self.builder, inst, array, local_map, context).is_some() {
+                    return Ok(());
+                }
+            }
+
             // try arithmetic first
-            if let Some(_) = translate_arithmetic(self.builder, inst, local_map, context) {
+            if let Some(_) = translate_arithmetic(self.builder, inst, local_map, local_types, context) {
                 return Ok(());
             }
 
             // try comparison
-            if let Some(_) = translate_comparison(self.builder, inst, local_map, context) {
+            if let Some(_) = translate_comparison(self.builder, inst, local_map, local_types, context) {
                 return Ok(());
             }
 
@@ -236,6 +625,18 @@ impl LlvmCodeGen {
                 return Ok(());
             }
 
+            if translate_string(self.builder, inst, local_map, context).is_some() {
+                return Ok(());
+            }
+
+            if translate_dyn_call(self.builder, inst, local_map, context).is_some() {
+                return Ok(());
+            }
+
+            if translate_enum(self.builder, inst, local_map, context).is_some() {
+                return Ok(());
+            }
+
             // try control flow
             if translate_control_flow(self.builder, inst, local_map, bb_map, context) {
                 return Ok(());
@@ -243,11 +644,15 @@ impl LlvmCodeGen {
 
             // handle other instructions
             match inst {
-                Instruction::Call { dest, func: _func, args: _args, return_type: _return_type } => {
+                Instruction::Call { dest, func: Operand::Function(f), args, return_type, fixed_args, conv } => {
+                    let call = self.translate_call(*dest, &f.name, args, return_type.as_ref(), *fixed_args, local_map);
+                    self.set_call_conv(call, *conv);
+                }
+                Instruction::Call { dest, .. } => {
                     // TODO: implement function calls
                     if let Some(dest_local) = dest {
                         // placeholder - should resolve function and call it
-                        let void_type = LLVMVoidType();
+                        let void_type = LLVMVoidTypeInContext(context);
                         local_map.insert(dest_local.id, LLVMConstNull(void_type));
                     }
                 }
@@ -271,6 +676,20 @@ impl LlvmCodeGen {
                     }
                     local_map.insert(dest.id, phi);
                 }
+                Instruction::Copy { dest, source: Operand::Constant(c @ Constant::Aggregate(_)), type_ } => {
+                    // comptime-known aggregate - private constant global instead of runtime stores
+                    let init = typed_constant(context, c, type_);
+                    let global = LLVMAddGlobal(self.module, LLVMTypeOf(init), b"const\0".as_ptr() as *const i8);
+                    LLVMSetInitializer(global, init);
+                    LLVMSetGlobalConstant(global, 1);
+                    LLVMSetLinkage(global, llvm_sys::LLVMLinkage::LLVMPrivateLinkage);
+                    LLVMSetUnnamedAddress(global, llvm_sys::LLVMUnnamedAddr::LLVMGlobalUnnamedAddr);
+                    local_map.insert(dest.id, global);
+                }
+                Instruction::Copy { dest, source: Operand::Constant(Constant::String(s)), .. } => {
+                    let string = self.string_constant(s);
+                    local_map.insert(dest.id, string);
+                }
                 Instruction::Copy { dest, source, type_: _type_ } => {
                     let src_val = operand_to_llvm_value(context, source, local_map);
                     local_map.insert(dest.id, src_val);
@@ -292,6 +711,23 @@ impl LlvmCodeGen {
                     let result = LLVMBuildNot(self.builder, op_val, b"not\0".as_ptr() as *const i8);
                     local_map.insert(dest.id, result);
                 }
+                Instruction::MakeDyn { dest, data, vtable } => {
+                    let byte_ptr = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+                    let mut data_val = operand_to_llvm_value(context, data, local_map);
+                    if LLVMGetTypeKind(LLVMTypeOf(data_val)) != llvm_sys::LLVMTypeKind::LLVMPointerTypeKind {
+                        // a value in a register, give it an address
+                        let slot = LLVMBuildAlloca(self.builder, LLVMTypeOf(data_val), b"dyndata\0".as_ptr() as *const i8);
+                        LLVMBuildStore(self.builder, data_val, slot);
+                        data_val = slot;
+                    }
+                    let data_ptr = LLVMBuildBitCast(self.builder, data_val, byte_ptr, b"data\0".as_ptr() as *const i8);
+                    let table = self.intern_vtable(vtable);
+                    let table_ptr = LLVMConstPointerCast(table, LLVMPointerType(byte_ptr, 0));
+                    let ty = trait_object_type(context);
+                    let with_data = LLVMBuildInsertValue(self.builder, LLVMGetUndef(ty), data_ptr, 0, b"dyn\0".as_ptr() as *const i8);
+                    let object = LLVMBuildInsertValue(self.builder, with_data, table_ptr, 1, b"dyn\0".as_ptr() as *const i8);
+                    local_map.insert(dest.id, object);
+                }
                 _ => {
                     // unhandled instruction - log warning but continue
                 }
@@ -301,8 +737,234 @@ impl LlvmCodeGen {
         }
     }
 
+    /// direct call, a fn this module doesnt define (another module's) is declared frm the
+    /// call's own arg + result types so the linker resolves it
+    /// a C variadic callee is declared w/ its `fixed_args` params + `...`, returns the call instruction
+    unsafe fn translate_call(
+        &mut self,
+        dest: Option<Local>,
+        name: &str,
+        args: &[Operand],
+        return_type: Option<&Type>,
+        fixed_args: Option<usize>,
+        local_map: &mut HashMap<usize, LLVMValueRef>,
+    ) -> LLVMValueRef {
+        let context = self.context.get();
+        // a string literal folded in2 the call is a `str` like any other string arg
+        let mut values: Vec<LLVMValueRef> = args
+            .iter()
+            .map(|a| match a {
+                Operand::Constant(Constant::String(s)) => self.string_constant(s),
+                _ => operand_to_llvm_value(context, a, local_map),
+            })
+            .collect();
+        let c_name = CString::new(name).unwrap();
+        let mut func = LLVMGetNamedFunction(self.module, c_name.as_ptr());
+        let fn_type = match self.fn_types.get(name) {
+            Some(ty) => *ty,
+            None if !func.is_null() => LLVMGlobalGetValueType(func),
+            None => {
+                let fixed = fixed_args.unwrap_or(values.len()).min(values.len());
+                let mut params: Vec<LLVMTypeRef> = values[..fixed].iter().map(|v| LLVMTypeOf(*v)).collect();
+                let ret = match return_type {
+                    Some(t) if dest.is_some() => mir_type_to_llvm_type(context, t),
+                    _ => LLVMVoidTypeInContext(context),
+                };
+                LLVMFunctionType(ret, params.as_mut_ptr(), params.len() as u32, fixed_args.is_some() as i32)
+            }
+        };
+        if func.is_null() {
+            func = LLVMAddFunction(self.module, c_name.as_ptr(), fn_type);
+        }
+
+        // args go by value, aggregates come out of their stack slots + ints r widened
+        let mut params = vec![std::ptr::null_mut(); LLVMCountParamTypes(fn_type) as usize];
+        LLVMGetParamTypes(fn_type, params.as_mut_ptr());
+        for (value, &param) in values.iter_mut().zip(&params) {
+            let ty = LLVMTypeOf(*value);
+            if ty == param {
+                continue;
+            }
+            let int = llvm_sys::LLVMTypeKind::LLVMIntegerTypeKind;
+            let pointer = llvm_sys::LLVMTypeKind::LLVMPointerTypeKind;
+            if is_pointer_value(*value) && LLVMGetTypeKind(param) != pointer {
+                *value = LLVMBuildLoad2(self.builder, param, *value, b"arg\0".as_ptr() as *const i8);
+            } else if LLVMGetTypeKind(ty) == int && LLVMGetTypeKind(param) == int {
+                *value = LLVMBuildIntCast2(self.builder, *value, param, 1, b"arg\0".as_ptr() as *const i8);
+            } else if LLVMGetTypeKind(ty) == pointer && LLVMGetTypeKind(param) == pointer {
+                *value = LLVMBuildBitCast(self.builder, *value, param, b"arg\0".as_ptr() as *const i8);
+            }
+        }
+        if LLVMIsFunctionVarArg(fn_type) != 0 {
+            for value in values.iter_mut().skip(params.len()) {
+                *value = self.promote_vararg(*value);
+            }
+        }
+        let returns = LLVMGetTypeKind(LLVMGetReturnType(fn_type)) != llvm_sys::LLVMTypeKind::LLVMVoidTypeKind;
+        let label = if returns { b"call\0".as_ptr() } else { b"\0".as_ptr() };
+        let result = LLVMBuildCall2(self.builder, fn_type, func, values.as_mut_ptr(), values.len() as u32, label as *const i8);
+        if let Some(d) = dest {
+            // a fn declared once + called w/ many pointee types, eg the allocator, hands back the
+            // pointer the call site expects
+            let value = match return_type.map(|t| mir_type_to_llvm_type(context, t)) {
+                Some(expected)
+                    if expected != LLVMTypeOf(result)
+                        && is_pointer_value(result)
+                        && LLVMGetTypeKind(expected) == llvm_sys::LLVMTypeKind::LLVMPointerTypeKind =>
+                {
+                    LLVMBuildBitCast(self.builder, result, expected, b"call\0".as_ptr() as *const i8)
+                }
+                _ => result,
+            };
+            local_map.insert(d.id, value);
+        }
+        result
+    }
+
+    /// a foreign callee's convention goes on both its declaration + the call, they must agree
+    /// or llvm treats the call as ub
+    unsafe fn set_call_conv(&mut self, call: LLVMValueRef, conv: CallConv) {
+        use llvm_sys::LLVMCallConv::*;
+        let cc = match conv.resolve(&TargetInfo::from_triple(&self.target_triple)) {
+            CallConv::Stdcall => LLVMX86StdcallCallConv,
+            CallConv::Fastcall => LLVMX86FastcallCallConv,
+            CallConv::Thiscall => LLVMX86ThisCallCallConv,
+            CallConv::Vectorcall => LLVMX86VectorCallCallConv,
+            CallConv::Win64 => LLVMWin64CallConv,
+            CallConv::Sysv64 => LLVMX8664SysVCallConv,
+            CallConv::C | CallConv::System => return,
+        };
+        LLVMSetFunctionCallConv(LLVMGetCalledValue(call), cc as u32);
+        LLVMSetInstructionCallConv(call, cc as u32);
+    }
+
+    /// C default argument promotions 4 the `...` part of a call: float -> double and
+    /// ints narrower than int -> int, zero extended since byte, ubyte + bool r all unsigned
+    unsafe fn promote_vararg(&mut self, value: LLVMValueRef) -> LLVMValueRef {
+        let context = self.context.get();
+        let ty = LLVMTypeOf(value);
+        match LLVMGetTypeKind(ty) {
+            llvm_sys::LLVMTypeKind::LLVMFloatTypeKind => {
+                LLVMBuildFPExt(self.builder, value, LLVMDoubleTypeInContext(context), b"vararg\0".as_ptr() as *const i8)
+            }
+            llvm_sys::LLVMTypeKind::LLVMIntegerTypeKind if LLVMGetIntTypeWidth(ty) < 32 => {
+                LLVMBuildZExt(self.builder, value, LLVMInt32TypeInContext(context), b"vararg\0".as_ptr() as *const i8)
+            }
+            _ => value,
+        }
+    }
+
+    /// a literal as a `str` value pointing at its interned bytes
+    unsafe fn string_constant(&mut self, s: &str) -> LLVMValueRef {
+        let context = self.context.get();
+        let data = self.intern_string(s);
+        let mut fields = [
+            LLVMConstPointerCast(data, LLVMPointerType(LLVMInt8TypeInContext(context), 0)),
+            LLVMConstInt(LLVMInt64TypeInContext(context), s.len() as u64, 0),
+        ];
+        LLVMConstNamedStruct(string_type(context), fields.as_mut_ptr(), 2)
+    }
+
+    /// `.str` global holding the literal bytes, nul terminated so c callers can take the ptr as is
+    unsafe fn intern_string(&mut self, s: &str) -> LLVMValueRef {
+        if let Some(global) = self.strings.get(s) {
+            return *global;
+        }
+        let init = LLVMConstStringInContext2(self.context.get(), s.as_ptr() as *const i8, s.len(), 0);
+        let global = LLVMAddGlobal(self.module, LLVMTypeOf(init), b".str\0".as_ptr() as *const i8);
+        LLVMSetInitializer(global, init);
+        LLVMSetGlobalConstant(global, 1);
+        LLVMSetLinkage(global, llvm_sys::LLVMLinkage::LLVMPrivateLinkage);
+        LLVMSetUnnamedAddress(global, llvm_sys::LLVMUnnamedAddr::LLVMGlobalUnnamedAddr);
+        self.strings.insert(s.to_string(), global);
+        global
+    }
+
+    /// `[n x i8*]` of the impl fns, one private global per (type, trait)
+    unsafe fn intern_vtable(&mut self, vtable: &Vtable) -> LLVMValueRef {
+        let symbol = vtable.symbol();
+        if let Some(global) = self.vtables.get(&symbol) {
+            return *global;
+        }
+        let byte_ptr = LLVMPointerType(LLVMInt8TypeInContext(self.context.get()), 0);
+        let mut entries: Vec<LLVMValueRef> = vtable
+            .methods
+            .iter()
+            .map(|name| match self.vtable_entry(name) {
+                Some(func) => LLVMConstPointerCast(func, byte_ptr),
+                None => LLVMConstNull(byte_ptr),
+            })
+            .collect();
+        let init = LLVMConstArray2(byte_ptr, entries.as_mut_ptr(), entries.len() as u64);
+        let name = CString::new(symbol.clone()).unwrap();
+        let global = LLVMAddGlobal(self.module, LLVMTypeOf(init), name.as_ptr());
+        LLVMSetInitializer(global, init);
+        LLVMSetGlobalConstant(global, 1);
+        LLVMSetLinkage(global, llvm_sys::LLVMLinkage::LLVMPrivateLinkage);
+        LLVMSetUnnamedAddress(global, llvm_sys::LLVMUnnamedAddr::LLVMGlobalUnnamedAddr);
+        self.vtables.insert(symbol, global);
+        global
+    }
+
+    /// fn a vtable slot points at, impls taking `self` by value get a `.dyn` shim
+    /// that loads it frm the data ptr
+    unsafe fn vtable_entry(&mut self, name: &str) -> Option<LLVMValueRef> {
+        let fn_type = *self.fn_types.get(name)?;
+        let c_name = CString::new(name).unwrap();
+        let mut func = LLVMGetNamedFunction(self.module, c_name.as_ptr());
+        if func.is_null() {
+            func = LLVMAddFunction(self.module, c_name.as_ptr(), fn_type);
+        }
+        let param_count = LLVMCountParamTypes(fn_type) as usize;
+        let mut params = vec![std::ptr::null_mut(); param_count];
+        LLVMGetParamTypes(fn_type, params.as_mut_ptr());
+        let Some(&self_type) = params.first() else { return Some(func) };
+        if LLVMGetTypeKind(self_type) == llvm_sys::LLVMTypeKind::LLVMPointerTypeKind {
+            return Some(func);
+        }
+
+        let context = self.context.get();
+        let shim_name = CString::new(format!("{}.dyn", name)).unwrap();
+        let existing = LLVMGetNamedFunction(self.module, shim_name.as_ptr());
+        if !existing.is_null() {
+            return Some(existing);
+        }
+        let ret_type = LLVMGetReturnType(fn_type);
+        let mut shim_params = params.clone();
+        shim_params[0] = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+        let shim_type = LLVMFunctionType(ret_type, shim_params.as_mut_ptr(), param_count as u32, 0);
+        let shim = LLVMAddFunction(self.module, shim_name.as_ptr(), shim_type);
+        LLVMSetLinkage(shim, llvm_sys::LLVMLinkage::LLVMPrivateLinkage);
+        self.apply_panic_strategy(shim);
+
+        // own builder, the main one is in the middle of a fn
+        let builder = LLVMCreateBuilderInContext(context);
+        let entry = LLVMAppendBasicBlockInContext(context, shim, b"entry\0".as_ptr() as *const i8);
+        LLVMPositionBuilderAtEnd(builder, entry);
+        let data = LLVMBuildBitCast(builder, LLVMGetParam(shim, 0), LLVMPointerType(self_type, 0), b"self\0".as_ptr() as *const i8);
+        let mut args = vec![LLVMBuildLoad2(builder, self_type, data, b"self\0".as_ptr() as *const i8)];
+        args.extend((1..param_count).map(|i| LLVMGetParam(shim, i as u32)));
+        let is_void = LLVMGetTypeKind(ret_type) == llvm_sys::LLVMTypeKind::LLVMVoidTypeKind;
+        let name = if is_void { b"\0".as_ptr() } else { b"ret\0".as_ptr() };
+        let result = LLVMBuildCall2(builder, fn_type, func, args.as_mut_ptr(), param_count as u32, name as *const i8);
+        if is_void {
+            LLVMBuildRetVoid(builder);
+        } else {
+            LLVMBuildRet(builder, result);
+        }
+        LLVMDisposeBuilder(builder);
+        Some(shim)
+    }
+
     /// get LLVM module (for emitter/optimizer)
     pub fn get_module(&self) -> LLVMModuleRef {
         self.module
     }
 }
+
+/// fn level enum attribute like `nounwind`
+unsafe fn add_enum_attribute(context: LLVMContextRef, func: LLVMValueRef, name: &str) {
+    let kind = LLVMGetEnumAttributeKindForName(name.as_ptr() as *const i8, name.len());
+    let attr = LLVMCreateEnumAttribute(context, kind, 0);
+    LLVMAddAttributeAtIndex(func, llvm_sys::LLVMAttributeFunctionIndex, attr);
+}
@@ -7,12 +7,15 @@ use std::sync::Once;
 static LLVM_INIT: Once = Once::new();
 
 /// initialize LLVM (thread-safe, idempotent)
+/// evry built target is registered so --target can cross compile
 pub fn initialize_llvm() {
     LLVM_INIT.call_once(|| {
         unsafe {
-            LLVM_InitializeNativeTarget();
-            LLVM_InitializeNativeAsmPrinter();
-            LLVM_InitializeNativeAsmParser();
+            LLVM_InitializeAllTargetInfos();
+            LLVM_InitializeAllTargets();
+            LLVM_InitializeAllTargetMCs();
+            LLVM_InitializeAllAsmPrinters();
+            LLVM_InitializeAllAsmParsers();
         }
     });
 }
@@ -0,0 +1,538 @@
+use crate::backend::llvm::ffi_compat::insert_dbg_value_before;
+use crate::backend::llvm::types::{mir_type_to_llvm_type, struct_field_indices};
+use crate::backend::ports::codegen::{DebugInfo, DebugInfoLevel};
+use crate::core::mir::MirFunction;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use llvm_sys::core::*;
+use llvm_sys::debuginfo::*;
+use llvm_sys::prelude::*;
+use llvm_sys::target::*;
+use llvm_sys::{LLVMModuleFlagBehavior, LLVMOpcode, LLVMTypeKind};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// llvm's current dwarf version 4 elf/mach-o, codeview is used on msvc targets
+const DWARF_VERSION: u64 = 4;
+
+// DW_ATE_* base type encodings
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x07;
+const DW_ATE_UNSIGNED_CHAR: u32 = 0x08;
+const DW_ATE_UTF: u32 = 0x10;
+const DW_TAG_STRUCTURE_TYPE: u32 = 0x13;
+const DW_OP_DEREF: u64 = 0x06;
+
+/// named param or local of an emitted fn, described 4 the debugger at full debug info
+pub(crate) struct DebugVariable {
+    pub name: String,
+    pub type_: Type,
+    pub value: LLVMValueRef,
+    /// 1 based param position, 0 4 locals
+    pub arg: u32,
+}
+
+/// params + named locals of `mir_func` that made it into llvm values
+/// aggregates held as addresses get a deref so the debugger shows the contents
+pub(crate) unsafe fn collect_variables(
+    mir_func: &MirFunction,
+    function: LLVMValueRef,
+    local_map: &HashMap<usize, LLVMValueRef>,
+) -> Vec<DebugVariable> {
+    let mut variables: Vec<DebugVariable> = mir_func
+        .params
+        .iter()
+        .enumerate()
+        .map(|(idx, param)| DebugVariable {
+            name: param.name.clone(),
+            type_: param.type_.clone(),
+            value: LLVMGetParam(function, idx as u32),
+            arg: idx as u32 + 1,
+        })
+        .collect();
+    for local in &mir_func.locals {
+        let Some(ref name) = local.name else { continue };
+        if mir_func.params.iter().any(|p| p.local.id == local.local.id) {
+            continue;
+        }
+        // constants folded into operands have no value 2 point at
+        let Some(&value) = local_map.get(&local.local.id) else { continue };
+        if LLVMIsAInstruction(value).is_null() && LLVMIsAGlobalVariable(value).is_null() {
+            continue;
+        }
+        variables.push(DebugVariable { name: name.clone(), type_: local.type_.clone(), value, arg: 0 });
+    }
+    variables
+}
+
+/// emerald spelling of a type, its the name the pretty printers match on
+pub fn debug_type_name(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(p) => match p {
+            PrimitiveType::Void => "void",
+            PrimitiveType::Byte => "byte",
+            PrimitiveType::Int => "int",
+            PrimitiveType::Long => "long",
+            PrimitiveType::Size => "size",
+            PrimitiveType::Float => "float",
+            PrimitiveType::Bool => "bool",
+            PrimitiveType::Char => "char",
+            PrimitiveType::UByte => "ubyte",
+            PrimitiveType::UInt => "uint",
+            PrimitiveType::ULong => "ulong",
+        }
+        .to_string(),
+        Type::Pointer(ptr) if ptr.nullable => format!("ref? {}", debug_type_name(&ptr.pointee)),
+        Type::Pointer(ptr) => format!("ref {}", debug_type_name(&ptr.pointee)),
+        Type::Array(arr) => format!("{}[{}]", debug_type_name(&arr.element), arr.size),
+        Type::Struct(s) => s.name.clone(),
+        Type::Enum(e) => e.name.clone(),
+        Type::String => "string".to_string(),
+        Type::TraitObject(t) => format!("dyn {}", t.trait_name),
+        Type::Function(_) => "fn".to_string(),
+        Type::Generic(g) => format!("{:?}", g),
+        Type::Error => "{error}".to_string(),
+    }
+}
+
+/// DIType cache + what it takes 2 size types like the llvm lowering does
+struct DebugTypes {
+    builder: LLVMDIBuilderRef,
+    context: LLVMContextRef,
+    target_data: LLVMTargetDataRef,
+    file: LLVMMetadataRef,
+    scope: LLVMMetadataRef,
+    cache: HashMap<String, LLVMMetadataRef>,
+}
+
+impl DebugTypes {
+    /// (size, align) in bits of the llvm lowering of `ty`
+    unsafe fn layout(&self, ty: &Type) -> (u64, u32) {
+        let llvm_type = mir_type_to_llvm_type(self.context, ty);
+        if LLVMTypeIsSized(llvm_type) == 0 {
+            return (0, 0);
+        }
+        (
+            LLVMABISizeOfType(self.target_data, llvm_type) * 8,
+            LLVMABIAlignmentOfType(self.target_data, llvm_type) * 8,
+        )
+    }
+
+    unsafe fn pointer_to(&mut self, pointee: LLVMMetadataRef) -> LLVMMetadataRef {
+        let bits = LLVMPointerSize(self.target_data) as u64 * 8;
+        LLVMDIBuilderCreatePointerType(self.builder, pointee, bits, bits as u32, 0, "".as_ptr() as *const i8, 0)
+    }
+
+    unsafe fn typedef(&mut self, ty: LLVMMetadataRef, name: &str) -> LLVMMetadataRef {
+        LLVMDIBuilderCreateTypedef(
+            self.builder,
+            ty,
+            name.as_ptr() as *const i8,
+            name.len(),
+            self.file,
+            0,
+            self.scope,
+            0,
+        )
+    }
+
+    unsafe fn basic(&mut self, name: &str, bits: u64, encoding: u32) -> LLVMMetadataRef {
+        LLVMDIBuilderCreateBasicType(self.builder, name.as_ptr() as *const i8, name.len(), bits, encoding, LLVMDIFlagZero)
+    }
+
+    /// DIType 4 `ty`, None 4 void and types w/o a runtime form
+    /// ref? T and dyn Trait r typedefs so the pretty printers can find them by name, string is a named struct
+    unsafe fn get(&mut self, ty: &Type) -> Option<LLVMMetadataRef> {
+        let name = debug_type_name(ty);
+        if let Some(&cached) = self.cache.get(&name) {
+            return Some(cached);
+        }
+        let di_type = match ty {
+            Type::Primitive(PrimitiveType::Void) | Type::Function(_) | Type::Generic(_) | Type::Error => return None,
+            Type::Primitive(p) => {
+                let encoding = match p {
+                    PrimitiveType::Bool => DW_ATE_BOOLEAN,
+                    PrimitiveType::Float => DW_ATE_FLOAT,
+                    PrimitiveType::Char => DW_ATE_UTF,
+                    PrimitiveType::Byte | PrimitiveType::UByte => DW_ATE_UNSIGNED_CHAR,
+                    _ if p.is_signed() => DW_ATE_SIGNED,
+                    _ => DW_ATE_UNSIGNED,
+                };
+                let bits = self.layout(ty).0;
+                self.basic(&name, bits, encoding)
+            }
+            Type::Pointer(ptr) => {
+                let pointee = match self.get(&ptr.pointee) {
+                    Some(pointee) => pointee,
+                    None => self.basic("byte", 8, DW_ATE_UNSIGNED_CHAR),
+                };
+                let pointer = self.pointer_to(pointee);
+                if ptr.nullable { self.typedef(pointer, &name) } else { pointer }
+            }
+            Type::Array(arr) => {
+                let element = self.get(&arr.element)?;
+                let (bits, align) = self.layout(ty);
+                let mut subscripts = [LLVMDIBuilderGetOrCreateSubrange(self.builder, 0, arr.size as i64)];
+                LLVMDIBuilderCreateArrayType(self.builder, bits, align, element, subscripts.as_mut_ptr(), 1)
+            }
+            Type::Struct(s) => {
+                let (bits, align) = self.layout(ty);
+                // self referential fields resolve 2 the placeholder til the real type replaces it
+                let placeholder = LLVMDIBuilderCreateReplaceableCompositeType(
+                    self.builder,
+                    DW_TAG_STRUCTURE_TYPE,
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                    self.scope,
+                    self.file,
+                    0,
+                    0,
+                    bits,
+                    align,
+                    LLVMDIFlagZero,
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                );
+                self.cache.insert(name.clone(), placeholder);
+
+                let llvm_type = mir_type_to_llvm_type(self.context, ty);
+                let sized = LLVMTypeIsSized(llvm_type) != 0;
+                let indices = struct_field_indices(self.context, s);
+                let mut members = Vec::new();
+                for (field, index) in s.fields.iter().zip(indices) {
+                    let Some(field_type) = self.get(&field.type_) else { continue };
+                    let (field_bits, field_align) = self.layout(&field.type_);
+                    let offset = if sized { LLVMOffsetOfElement(self.target_data, llvm_type, index) * 8 } else { 0 };
+                    members.push(LLVMDIBuilderCreateMemberType(
+                        self.builder,
+                        placeholder,
+                        field.name.as_ptr() as *const i8,
+                        field.name.len(),
+                        self.file,
+                        0,
+                        field_bits,
+                        field_align,
+                        offset,
+                        LLVMDIFlagZero,
+                        field_type,
+                    ));
+                }
+                let di_struct = LLVMDIBuilderCreateStructType(
+                    self.builder,
+                    self.scope,
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                    self.file,
+                    0,
+                    bits,
+                    align,
+                    LLVMDIFlagZero,
+                    std::ptr::null_mut(),
+                    members.as_mut_ptr(),
+                    members.len() as u32,
+                    0,
+                    std::ptr::null_mut(),
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                );
+                LLVMMetadataReplaceAllUsesWith(placeholder, di_struct);
+                di_struct
+            }
+            // only the tag is described, payload words depend on it
+            Type::Enum(_) => {
+                let (bits, align) = self.layout(ty);
+                let tag = self.basic("uint", 32, DW_ATE_UNSIGNED);
+                let mut members = [LLVMDIBuilderCreateMemberType(
+                    self.builder, self.scope, "tag".as_ptr() as *const i8, 3, self.file, 0,
+                    32, 32, 0, LLVMDIFlagZero, tag,
+                )];
+                LLVMDIBuilderCreateStructType(
+                    self.builder,
+                    self.scope,
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                    self.file,
+                    0,
+                    bits,
+                    align,
+                    LLVMDIFlagZero,
+                    std::ptr::null_mut(),
+                    members.as_mut_ptr(),
+                    members.len() as u32,
+                    0,
+                    std::ptr::null_mut(),
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                )
+            }
+            // { data, len } like the llvm `str`, the printers read len bytes frm data
+            // { data, vtable } like the llvm `dyn`
+            Type::String | Type::TraitObject(_) => {
+                let (bits, align) = self.layout(ty);
+                let word = LLVMPointerSize(self.target_data) as u64 * 8;
+                let byte = self.basic("byte", 8, DW_ATE_UNSIGNED_CHAR);
+                let data = self.pointer_to(byte);
+                let (second, second_type, second_bits) = match ty {
+                    Type::String => ("len", self.basic("ulong", 64, DW_ATE_UNSIGNED), 64),
+                    _ => ("vtable", self.pointer_to(data), word),
+                };
+                let mut members = [
+                    LLVMDIBuilderCreateMemberType(
+                        self.builder, self.scope, "data".as_ptr() as *const i8, 4, self.file, 0,
+                        word, word as u32, 0, LLVMDIFlagZero, data,
+                    ),
+                    LLVMDIBuilderCreateMemberType(
+                        self.builder, self.scope, second.as_ptr() as *const i8, second.len(), self.file, 0,
+                        second_bits, second_bits as u32, word, LLVMDIFlagZero, second_type,
+                    ),
+                ];
+                LLVMDIBuilderCreateStructType(
+                    self.builder,
+                    self.scope,
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                    self.file,
+                    0,
+                    bits,
+                    align,
+                    LLVMDIFlagZero,
+                    std::ptr::null_mut(),
+                    members.as_mut_ptr(),
+                    members.len() as u32,
+                    0,
+                    std::ptr::null_mut(),
+                    name.as_ptr() as *const i8,
+                    name.len(),
+                )
+            }
+        };
+        self.cache.insert(name, di_type);
+        Some(di_type)
+    }
+}
+
+/// give every defined fn a subprogram + put each instruction on the fn's line
+/// runs after translation so multiversion clones/resolvers get covered 2
+/// at full debug info signatures, params and named locals (`variables`, keyed by llvm fn name) r described 2
+pub(crate) unsafe fn attach_debug_info(
+    llvm_module: LLVMModuleRef,
+    debug_info: &DebugInfo,
+    mir_functions: &[MirFunction],
+    variables: &HashMap<String, Vec<DebugVariable>>,
+    codeview: bool,
+    optimized: bool,
+) {
+    let kind = match debug_info.level {
+        DebugInfoLevel::None => return,
+        DebugInfoLevel::LineTablesOnly => LLVMDWARFEmissionKind::LLVMDWARFEmissionKindLineTablesOnly,
+        DebugInfoLevel::Full => LLVMDWARFEmissionKind::LLVMDWARFEmissionKindFull,
+    };
+    let context = LLVMGetModuleContext(llvm_module);
+    let builder = LLVMCreateDIBuilder(llvm_module);
+
+    let name = debug_info.file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let directory = match debug_info.file.parent() {
+        Some(dir) if dir != Path::new("") => dir.to_string_lossy().to_string(),
+        _ => std::env::current_dir().map(|d| d.to_string_lossy().to_string()).unwrap_or_default(),
+    };
+    let file = LLVMDIBuilderCreateFile(
+        builder,
+        name.as_ptr() as *const i8,
+        name.len(),
+        directory.as_ptr() as *const i8,
+        directory.len(),
+    );
+    let producer = concat!("emc ", env!("CARGO_PKG_VERSION"));
+    let compile_unit = LLVMDIBuilderCreateCompileUnit(
+        builder,
+        // no dwarf language code 4 emerald, debuggers treat it like c
+        LLVMDWARFSourceLanguage::LLVMDWARFSourceLanguageC,
+        file,
+        producer.as_ptr() as *const i8,
+        producer.len(),
+        optimized as LLVMBool,
+        "".as_ptr() as *const i8,
+        0,
+        0,
+        "".as_ptr() as *const i8,
+        0,
+        kind,
+        0,
+        0,
+        0,
+        "".as_ptr() as *const i8,
+        0,
+        "".as_ptr() as *const i8,
+        0,
+    );
+    let mut types = DebugTypes {
+        builder,
+        context,
+        target_data: LLVMGetModuleDataLayout(llvm_module),
+        file,
+        scope: compile_unit,
+        cache: HashMap::new(),
+    };
+    let full = debug_info.level == DebugInfoLevel::Full;
+    let empty_fn_type = LLVMDIBuilderCreateSubroutineType(builder, file, std::ptr::null_mut(), 0, LLVMDIFlagZero);
+
+    let mut function = LLVMGetFirstFunction(llvm_module);
+    while !function.is_null() {
+        if LLVMIsDeclaration(function) == 0 {
+            let mut len = 0;
+            let name_ptr = LLVMGetValueName2(function, &mut len);
+            let fn_name = String::from_utf8_lossy(std::slice::from_raw_parts(name_ptr as *const u8, len)).to_string();
+            // clones r named `f.avx2`, the resolver `f.resolver`
+            let base = fn_name.split('.').next().unwrap_or(&fn_name);
+            let line = debug_info.function_lines.get(base).copied().unwrap_or(0);
+            let fn_type = match mir_functions.iter().find(|f| f.name == base) {
+                Some(mir_func) if full => subroutine_type(&mut types, mir_func),
+                _ => empty_fn_type,
+            };
+
+            let subprogram = LLVMDIBuilderCreateFunction(
+                builder,
+                compile_unit,
+                base.as_ptr() as *const i8,
+                base.len(),
+                fn_name.as_ptr() as *const i8,
+                fn_name.len(),
+                file,
+                line,
+                fn_type,
+                (LLVMGetLinkage(function) != llvm_sys::LLVMLinkage::LLVMExternalLinkage) as LLVMBool,
+                1,
+                line,
+                LLVMDIFlagZero,
+                optimized as LLVMBool,
+            );
+            LLVMSetSubprogram(function, subprogram);
+
+            let location = LLVMDIBuilderCreateDebugLocation(context, line, 0, subprogram, std::ptr::null_mut());
+            let mut block = LLVMGetFirstBasicBlock(function);
+            while !block.is_null() {
+                let mut inst = LLVMGetFirstInstruction(block);
+                while !inst.is_null() {
+                    LLVMInstructionSetDebugLoc(inst, location);
+                    inst = LLVMGetNextInstruction(inst);
+                }
+                block = LLVMGetNextBasicBlock(block);
+            }
+
+            // taken b4 any dbg.value goes in so params keep their order
+            let entry = first_insertion_point(LLVMGetEntryBasicBlock(function));
+            for variable in variables.get(&fn_name).into_iter().flatten() {
+                describe_variable(&mut types, variable, entry, subprogram, line, location);
+            }
+        }
+        function = LLVMGetNextFunction(function);
+    }
+    LLVMDIBuilderFinalize(builder);
+    LLVMDisposeDIBuilder(builder);
+
+    let i32_type = LLVMInt32TypeInContext(context);
+    let flag = |key: &str, value: u64| {
+        let value = LLVMValueAsMetadata(LLVMConstInt(i32_type, value, 0));
+        LLVMAddModuleFlag(
+            llvm_module,
+            LLVMModuleFlagBehavior::LLVMModuleFlagBehaviorWarning,
+            key.as_ptr() as *const i8,
+            key.len(),
+            value,
+        );
+    };
+    flag("Debug Info Version", LLVMDebugMetadataVersion() as u64);
+    if codeview {
+        flag("CodeView", 1);
+    } else {
+        flag("Dwarf Version", DWARF_VERSION);
+    }
+}
+
+/// return type then param types, a void return is a null entry
+unsafe fn subroutine_type(types: &mut DebugTypes, mir_func: &MirFunction) -> LLVMMetadataRef {
+    let mut signature = vec![mir_func.return_type.as_ref().and_then(|t| types.get(t)).unwrap_or(std::ptr::null_mut())];
+    for param in &mir_func.params {
+        signature.push(types.get(&param.type_).unwrap_or(std::ptr::null_mut()));
+    }
+    LLVMDIBuilderCreateSubroutineType(
+        types.builder,
+        types.file,
+        signature.as_mut_ptr(),
+        signature.len() as u32,
+        LLVMDIFlagZero,
+    )
+}
+
+/// variable entry + a dbg.value right after its value is available
+/// params/globals r live from the entry block, instructions from just after they r computed
+unsafe fn describe_variable(
+    types: &mut DebugTypes,
+    variable: &DebugVariable,
+    entry: LLVMValueRef,
+    subprogram: LLVMMetadataRef,
+    line: u32,
+    location: LLVMMetadataRef,
+) {
+    let Some(di_type) = types.get(&variable.type_) else { return };
+    let name = &variable.name;
+    let di_variable = if variable.arg > 0 {
+        LLVMDIBuilderCreateParameterVariable(
+            types.builder,
+            subprogram,
+            name.as_ptr() as *const i8,
+            name.len(),
+            variable.arg,
+            types.file,
+            line,
+            di_type,
+            1,
+            LLVMDIFlagZero,
+        )
+    } else {
+        LLVMDIBuilderCreateAutoVariable(
+            types.builder,
+            subprogram,
+            name.as_ptr() as *const i8,
+            name.len(),
+            types.file,
+            line,
+            di_type,
+            1,
+            LLVMDIFlagZero,
+            0,
+        )
+    };
+
+    // structs/arrays r usually carried by address, the variable is what it points at
+    let by_address = matches!(variable.type_, Type::Struct(_) | Type::Array(_))
+        && LLVMGetTypeKind(LLVMTypeOf(variable.value)) == LLVMTypeKind::LLVMPointerTypeKind;
+    let mut ops = [DW_OP_DEREF];
+    let expression = LLVMDIBuilderCreateExpression(types.builder, ops.as_mut_ptr(), by_address as usize);
+
+    let before = if LLVMIsAInstruction(variable.value).is_null() {
+        entry
+    } else {
+        let next = LLVMGetNextInstruction(variable.value);
+        if !next.is_null() && LLVMGetInstructionOpcode(next) == LLVMOpcode::LLVMPHI {
+            first_insertion_point(LLVMGetInstructionParent(variable.value))
+        } else {
+            next
+        }
+    };
+    if before.is_null() {
+        return;
+    }
+    insert_dbg_value_before(types.builder, variable.value, di_variable, expression, location, before);
+}
+
+/// first non phi instruction of `block`
+unsafe fn first_insertion_point(block: LLVMBasicBlockRef) -> LLVMValueRef {
+    let mut inst = LLVMGetFirstInstruction(block);
+    while !inst.is_null() && LLVMGetInstructionOpcode(inst) == LLVMOpcode::LLVMPHI {
+        inst = LLVMGetNextInstruction(inst);
+    }
+    inst
+}
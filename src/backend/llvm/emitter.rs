@@ -1,19 +1,32 @@
+use crate::backend::elf::ElfFile;
+use crate::backend::link::{self, LinkOptions, LinkerFlavor, LLC};
+use crate::backend::llvm::target::{emit_to_file, module_triple, set_module_target, DEFAULT_WASM_TRIPLE};
 use crate::backend::ports::emitter::{Emitter, EmitError};
+use crate::backend::stack_usage::FrameInfo;
+use crate::core::target::TargetInfo;
 use crate::backend::ports::codegen::Module;
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
-use llvm_sys::target::*;
 use llvm_sys::target_machine::*;
+use llvm_sys::bit_writer::{LLVMWriteBitcodeToFile, LLVMWriteBitcodeToMemoryBuffer};
+use llvm_sys::{LLVMDLLStorageClass, LLVMLinkage, LLVMOpcode, LLVMVisibility};
 use std::ffi::CString;
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// LLVM emitter - emits various output formats
-pub struct LlvmEmitter;
+pub struct LlvmEmitter {
+    link_options: LinkOptions,
+    embed_bitcode: bool,
+}
 
 impl LlvmEmitter {
     pub fn new() -> Self {
-        Self
+        Self {
+            link_options: LinkOptions::default(),
+            embed_bitcode: false,
+        }
     }
 }
 
@@ -25,139 +38,34 @@ impl Default for LlvmEmitter {
 
 impl Emitter for LlvmEmitter {
     fn emit_binary(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
+        let triple = unsafe { module_triple(llvm_module) };
+        // a wasm "executable" is a linked module
+        if TargetInfo::from_triple(&triple).is_wasm() {
+            return self.emit_wasm(module, output);
+        }
         unsafe {
-            let llvm_module = self.get_llvm_module(module)?;
-            
-            // initialize target
-            LLVM_InitializeNativeTarget();
-            LLVM_InitializeNativeAsmPrinter();
-            
-            // get target triple - use default or from module data layout
-            // In LLVM 21, we need to get the triple differently
-            // For now, use the default target triple
-            let triple = "x86_64-unknown-linux-gnu"; // Default, can be overridden
-            let triple_cstr = CString::new(triple).unwrap();
-            
-            // create target machine - LLVMGetTargetFromTriple takes target as out parameter
-            let mut target: LLVMTargetRef = std::ptr::null_mut();
-            let mut error_msg = std::ptr::null_mut();
-            let target_result = LLVMGetTargetFromTriple(triple_cstr.as_ptr(), &mut target, &mut error_msg);
-            if target_result != 0 || target.is_null() {
-                let error = if !error_msg.is_null() {
-                    std::ffi::CStr::from_ptr(error_msg).to_string_lossy().to_string()
-                } else {
-                    format!("Failed to get target for triple: {}", triple)
-                };
-                LLVMDisposeMessage(error_msg);
-                return Err(EmitError::EmissionFailed(error));
-            }
-            
-            // create target machine (use default CPU and features)
-            let cpu_cstr = CString::new("").unwrap();
-            let features_cstr = CString::new("").unwrap();
-            let target_machine = LLVMCreateTargetMachine(
-                target,
-                triple_cstr.as_ptr(),
-                cpu_cstr.as_ptr(),
-                features_cstr.as_ptr(),
-                LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
-                LLVMRelocMode::LLVMRelocDefault,
-                LLVMCodeModel::LLVMCodeModelDefault,
-            );
-            
+            self.prepare_object(llvm_module, &triple, self.link_options.shared);
+
             // emit object file first
-            let obj_path = output.with_extension("o");
-            let obj_path_cstr = CString::new(obj_path.to_string_lossy().as_ref()).unwrap();
-            let mut error_msg = std::ptr::null_mut();
-            
-            if LLVMTargetMachineEmitToFile(
-                target_machine,
-                llvm_module,
-                obj_path_cstr.as_ptr(),
-                LLVMCodeGenFileType::LLVMObjectFile,
-                &mut error_msg,
-            ) != 0 {
-                let error = if !error_msg.is_null() {
-                    std::ffi::CStr::from_ptr(error_msg).to_string_lossy().to_string()
-                } else {
-                    "Unknown error".to_string()
-                };
-                LLVMDisposeMessage(error_msg);
-                return Err(EmitError::EmissionFailed(error));
+            let obj_path = self.object_path(output);
+            // pic since the linker driver produces pie executables by default
+            emit_to_file(llvm_module, LLVMRelocMode::LLVMRelocPIC, LLVMCodeGenFileType::LLVMObjectFile, &obj_path)?;
+
+            // link obj into the executable w/ the system linker driver
+            let linked = link::link(&[obj_path.as_path()], output, &self.link_options);
+            // macos unpacked debug info is read from the object via the debug map
+            if !self.link_options.keeps_objects() {
+                let _ = fs::remove_file(&obj_path);
             }
-            
-            // link object file to binary (simplified - in production would use proper linker)
-            // for now, just copy object file as binary (this is a placeholder)
-            // TODO: use proper linker (lld or system linker)
-            fs::copy(&obj_path, output)?;
-            
-            LLVMDisposeTargetMachine(target_machine);
-            
-            Ok(())
+            linked
         }
     }
 
     fn emit_assembly(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
         unsafe {
-            let llvm_module = self.get_llvm_module(module)?;
-            
-            // initialize target
-            LLVM_InitializeNativeTarget();
-            LLVM_InitializeNativeAsmPrinter();
-            
-            // get target triple - use default
-            let triple = "x86_64-unknown-linux-gnu";
-            let triple_cstr = CString::new(triple).unwrap();
-            
-            // create target machine
-            let mut target: LLVMTargetRef = std::ptr::null_mut();
-            let mut error_msg = std::ptr::null_mut();
-            let target_result = LLVMGetTargetFromTriple(triple_cstr.as_ptr(), &mut target, &mut error_msg);
-            if target_result != 0 || target.is_null() {
-                let error = if !error_msg.is_null() {
-                    std::ffi::CStr::from_ptr(error_msg).to_string_lossy().to_string()
-                } else {
-                    "Failed to get target".to_string()
-                };
-                LLVMDisposeMessage(error_msg);
-                return Err(EmitError::EmissionFailed(error));
-            }
-            
-            let cpu_cstr = CString::new("").unwrap();
-            let features_cstr = CString::new("").unwrap();
-            let target_machine = LLVMCreateTargetMachine(
-                target,
-                triple_cstr.as_ptr(),
-                cpu_cstr.as_ptr(),
-                features_cstr.as_ptr(),
-                LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
-                LLVMRelocMode::LLVMRelocDefault,
-                LLVMCodeModel::LLVMCodeModelDefault,
-            );
-            
-            let output_cstr = CString::new(output.to_string_lossy().as_ref()).unwrap();
-            let mut error_msg = std::ptr::null_mut();
-            
-            if LLVMTargetMachineEmitToFile(
-                target_machine,
-                llvm_module,
-                output_cstr.as_ptr(),
-                LLVMCodeGenFileType::LLVMAssemblyFile,
-                &mut error_msg,
-            ) != 0 {
-                let error = if !error_msg.is_null() {
-                    std::ffi::CStr::from_ptr(error_msg).to_string_lossy().to_string()
-                } else {
-                    "Unknown error".to_string()
-                };
-                LLVMDisposeMessage(error_msg);
-                LLVMDisposeTargetMachine(target_machine);
-                return Err(EmitError::EmissionFailed(error));
-            }
-            
-            LLVMDisposeTargetMachine(target_machine);
-            
-            Ok(())
+            emit_to_file(llvm_module, LLVMRelocMode::LLVMRelocDefault, LLVMCodeGenFileType::LLVMAssemblyFile, output)
         }
     }
 
@@ -180,72 +88,165 @@ impl Emitter for LlvmEmitter {
         }
     }
 
+    fn emit_bitcode(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
+        let path = CString::new(output.to_string_lossy().as_bytes())
+            .map_err(|_| EmitError::InvalidPath(output.display().to_string()))?;
+        if unsafe { LLVMWriteBitcodeToFile(llvm_module, path.as_ptr()) } != 0 {
+            return Err(EmitError::EmissionFailed(format!("Failed to write bitcode to {}", output.display())));
+        }
+        Ok(())
+    }
+
     fn emit_object(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
         unsafe {
-            let llvm_module = self.get_llvm_module(module)?;
-            
-            // initialize target
-            LLVM_InitializeNativeTarget();
-            LLVM_InitializeNativeAsmPrinter();
-            
-            // get target triple - use default
-            let triple = "x86_64-unknown-linux-gnu";
-            let triple_cstr = CString::new(triple).unwrap();
-            
-            // create target machine
-            let mut target: LLVMTargetRef = std::ptr::null_mut();
-            let mut error_msg = std::ptr::null_mut();
-            let target_result = LLVMGetTargetFromTriple(triple_cstr.as_ptr(), &mut target, &mut error_msg);
-            if target_result != 0 || target.is_null() {
-                let error = if !error_msg.is_null() {
-                    std::ffi::CStr::from_ptr(error_msg).to_string_lossy().to_string()
-                } else {
-                    "Failed to get target".to_string()
-                };
-                LLVMDisposeMessage(error_msg);
-                return Err(EmitError::EmissionFailed(error));
-            }
-            
-            let cpu_cstr = CString::new("").unwrap();
-            let features_cstr = CString::new("").unwrap();
-            let target_machine = LLVMCreateTargetMachine(
-                target,
-                triple_cstr.as_ptr(),
-                cpu_cstr.as_ptr(),
-                features_cstr.as_ptr(),
-                LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
-                LLVMRelocMode::LLVMRelocDefault,
-                LLVMCodeModel::LLVMCodeModelDefault,
-            );
-            
-            let output_cstr = CString::new(output.to_string_lossy().as_ref()).unwrap();
-            let mut error_msg = std::ptr::null_mut();
-            
-            if LLVMTargetMachineEmitToFile(
-                target_machine,
-                llvm_module,
-                output_cstr.as_ptr(),
-                LLVMCodeGenFileType::LLVMObjectFile,
-                &mut error_msg,
-            ) != 0 {
-                let error = if !error_msg.is_null() {
-                    std::ffi::CStr::from_ptr(error_msg).to_string_lossy().to_string()
-                } else {
-                    "Unknown error".to_string()
-                };
-                LLVMDisposeMessage(error_msg);
-                LLVMDisposeTargetMachine(target_machine);
-                return Err(EmitError::EmissionFailed(error));
+            let triple = module_triple(llvm_module);
+            self.prepare_object(llvm_module, &triple, self.link_options.shared);
+            emit_to_file(llvm_module, LLVMRelocMode::LLVMRelocDefault, LLVMCodeGenFileType::LLVMObjectFile, output)?;
+        }
+        link::compress_debug_sections(output, &self.link_options)
+    }
+
+    fn emit_static_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
+        let obj_path = self.object_path(output);
+        unsafe {
+            let triple = module_triple(llvm_module);
+            self.prepare_object(llvm_module, &triple, false);
+            // pic so the archive can go in2 pie executables + shared libs
+            emit_to_file(llvm_module, LLVMRelocMode::LLVMRelocPIC, LLVMCodeGenFileType::LLVMObjectFile, &obj_path)?;
+        }
+        let archived = link::archive(&[obj_path.as_path()], output, &self.link_options);
+        let _ = fs::remove_file(&obj_path);
+        archived
+    }
+
+    fn emit_shared_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
+        let obj_path = self.object_path(output);
+        unsafe {
+            let triple = module_triple(llvm_module);
+            self.prepare_object(llvm_module, &triple, true);
+            emit_to_file(llvm_module, LLVMRelocMode::LLVMRelocPIC, LLVMCodeGenFileType::LLVMObjectFile, &obj_path)?;
+        }
+        let options = self.link_options.clone().with_shared(true);
+        let linked = link::link(&[obj_path.as_path()], output, &options);
+        if !options.keeps_objects() {
+            let _ = fs::remove_file(&obj_path);
+        }
+        linked
+    }
+
+    fn emit_wasm(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
+        let mut triple = unsafe { module_triple(llvm_module) };
+        if !TargetInfo::from_triple(&triple).is_wasm() {
+            // --emit wasm w/o a wasm --target, retarget the module
+            triple = DEFAULT_WASM_TRIPLE.to_string();
+            unsafe { set_module_target(llvm_module, &triple) }.map_err(EmitError::EmissionFailed)?;
+        }
+
+        // relocatable object first, wasm-ld turns it into the final module
+        let obj_path = output.with_extension("o");
+        unsafe {
+            emit_to_file(llvm_module, LLVMRelocMode::LLVMRelocDefault, LLVMCodeGenFileType::LLVMObjectFile, &obj_path)?;
+        }
+        let options = self.link_options.clone().with_target(TargetInfo::from_triple(&triple));
+        let linked = link::link(&[obj_path.as_path()], output, &options);
+        let _ = fs::remove_file(&obj_path);
+        linked
+    }
+
+    fn stack_frames(&self, module: &Module) -> Result<Vec<FrameInfo>, EmitError> {
+        let llvm_module = self.get_llvm_module(module)?;
+        unsafe {
+            let mut frames = self.collect_frames(llvm_module);
+            // a fn llvm didnt report has an empty frame
+            let sizes = frame_sizes(llvm_module)?;
+            for frame in &mut frames {
+                frame.frame_size = sizes.iter().find(|(name, _)| *name == frame.name).map_or(0, |(_, size)| *size);
             }
-            
-            LLVMDisposeTargetMachine(target_machine);
-            
-            Ok(())
+            Ok(frames)
         }
     }
+
+    fn set_link_options(&mut self, options: LinkOptions) {
+        self.link_options = options;
+    }
+
+    fn set_embed_bitcode(&mut self, embed: bool) {
+        self.embed_bitcode = embed;
+    }
 }
 
 impl LlvmEmitter {
+    /// dll storage / symbol visibility + embedded bitcode 4 objects that get linked
+    unsafe fn prepare_object(&self, llvm_module: LLVMModuleRef, triple: &str, shared: bool) {
+        if TargetInfo::from_triple(triple).os == "windows" {
+            apply_dll_storage(llvm_module, shared.then_some(&self.link_options));
+        } else {
+            hide_unexported(llvm_module, &self.link_options);
+        }
+        if self.embed_bitcode {
+            embed_bitcode(llvm_module, triple);
+        }
+    }
+
+    /// intermediate object next 2 the final output
+    fn object_path(&self, output: &Path) -> std::path::PathBuf {
+        output.with_extension(if self.link_options.flavor == LinkerFlavor::Msvc { "obj" } else { "o" })
+    }
+
+    /// direct callees of every defined function + whether it allocas a runtime size, the frame
+    /// sizes come frm codegen
+    unsafe fn collect_frames(&self, llvm_module: LLVMModuleRef) -> Vec<FrameInfo> {
+        let mut frames = Vec::new();
+        let mut function = LLVMGetFirstFunction(llvm_module);
+        while !function.is_null() {
+            if LLVMIsDeclaration(function) == 0 {
+                let mut frame = FrameInfo {
+                    name: value_name(function),
+                    frame_size: 0,
+                    dynamic: false,
+                    callees: Vec::new(),
+                };
+                
+                let mut block = LLVMGetFirstBasicBlock(function);
+                while !block.is_null() {
+                    let mut inst = LLVMGetFirstInstruction(block);
+                    while !inst.is_null() {
+                        match LLVMGetInstructionOpcode(inst) {
+                            LLVMOpcode::LLVMAlloca => {
+                                // runtime count or opaque type, no static size
+                                let count = LLVMGetOperand(inst, 0);
+                                if LLVMIsAConstantInt(count).is_null() || LLVMTypeIsSized(LLVMGetAllocatedType(inst)) == 0 {
+                                    frame.dynamic = true;
+                                }
+                            }
+                            LLVMOpcode::LLVMCall => {
+                                let callee = LLVMGetCalledValue(inst);
+                                if !LLVMIsAFunction(callee).is_null() {
+                                    let name = value_name(callee);
+                                    if !frame.callees.contains(&name) {
+                                        frame.callees.push(name);
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                        inst = LLVMGetNextInstruction(inst);
+                    }
+                    block = LLVMGetNextBasicBlock(block);
+                }
+                
+                frames.push(frame);
+            }
+            function = LLVMGetNextFunction(function);
+        }
+        frames
+    }
+
     /// get LLVM module from Module struct
     fn get_llvm_module(&self, module: &Module) -> Result<LLVMModuleRef, EmitError> {
         // get LLVM module from module data
@@ -258,3 +259,120 @@ impl LlvmEmitter {
             ))
     }
 }
+
+/// (fn, bytes) of evry fn as codegen laid out its frame (MachineFrameInfo), spills + callee saved
+/// regs included, read frm the `.stack_sizes` section of the object llc makes of the module. the
+/// c api cant ask 4 that section, + its only written 2 elf so other triples get `-elf` on them
+unsafe fn frame_sizes(llvm_module: LLVMModuleRef) -> Result<Vec<(String, u64)>, EmitError> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let triple = module_triple(llvm_module);
+    let triple = match TargetInfo::from_triple(&triple).os.as_str() {
+        "windows" | "macos" | "ios" => format!("{}-elf", triple),
+        _ => triple,
+    };
+    let stem = std::env::temp_dir().join(format!("emc-stack-{}-{}", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed)));
+    let (bitcode, object) = (stem.with_extension("bc"), stem.with_extension("o"));
+    let bitcode_cstr = CString::new(bitcode.to_string_lossy().as_ref()).unwrap();
+    if LLVMWriteBitcodeToFile(llvm_module, bitcode_cstr.as_ptr()) != 0 {
+        return Err(EmitError::EmissionFailed(format!("Failed to write {}", bitcode.display())));
+    }
+    let arg = |p: &Path| p.to_string_lossy().to_string();
+    let args = [format!("-mtriple={}", triple), "-stack-size-section".to_string(), "-filetype=obj".to_string(), "-o".to_string(), arg(&object), arg(&bitcode)];
+    let compiled = link::run_tool("llc", LLC, &args).and_then(|_| fs::read(&object).map_err(|e| EmitError::EmissionFailed(e.to_string())));
+    let _ = fs::remove_file(&bitcode);
+    let _ = fs::remove_file(&object);
+    ElfFile::parse(&compiled?).and_then(|elf| elf.stack_sizes()).map_err(|e| EmitError::EmissionFailed(e.to_string()))
+}
+
+unsafe fn value_name(value: LLVMValueRef) -> String {
+    let mut len = 0;
+    let name = LLVMGetValueName2(value, &mut len);
+    let bytes = std::slice::from_raw_parts(name as *const u8, len);
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// fn attribute codegen puts on a declaration another object of the same link defines, eg a fn
+/// of another codegen unit or an imported module
+pub(crate) const LINKED_ATTR: &str = "emerald-linked";
+
+/// coff symbol import/export: declarations of library fns come from a dll (dllimport) and
+/// when building a dll every exported definition is dllexport
+unsafe fn apply_dll_storage(llvm_module: LLVMModuleRef, exports: Option<&LinkOptions>) {
+    let mut function = LLVMGetFirstFunction(llvm_module);
+    while !function.is_null() {
+        let intrinsic = value_name(function).starts_with("llvm.");
+        if LLVMIsDeclaration(function) != 0 {
+            let linked = !LLVMGetStringAttributeAtIndex(
+                function,
+                llvm_sys::LLVMAttributeFunctionIndex,
+                LINKED_ATTR.as_ptr() as *const i8,
+                LINKED_ATTR.len() as u32,
+            )
+            .is_null();
+            if !intrinsic && !linked {
+                LLVMSetDLLStorageClass(function, LLVMDLLStorageClass::LLVMDLLImportStorageClass);
+            }
+        } else if LLVMGetLinkage(function) == LLVMLinkage::LLVMExternalLinkage
+            && exports.is_some_and(|o| o.exports_symbol(&value_name(function)))
+        {
+            LLVMSetDLLStorageClass(function, LLVMDLLStorageClass::LLVMDLLExportStorageClass);
+        }
+        function = LLVMGetNextFunction(function);
+    }
+}
+
+/// elf / mach-o: w/ --visibility hidden only the --export'ed definitions stay in the dynamic symbol table
+unsafe fn hide_unexported(llvm_module: LLVMModuleRef, options: &LinkOptions) {
+    let mut function = LLVMGetFirstFunction(llvm_module);
+    while !function.is_null() {
+        if LLVMIsDeclaration(function) == 0
+            && LLVMGetLinkage(function) == LLVMLinkage::LLVMExternalLinkage
+            && !options.exports_symbol(&value_name(function))
+        {
+            LLVMSetVisibility(function, LLVMVisibility::LLVMHiddenVisibility);
+        }
+        function = LLVMGetNextFunction(function);
+    }
+}
+
+/// section clang/lld look in 4 embedded bitcode
+pub fn embedded_bitcode_section(triple: &str) -> &'static str {
+    if TargetInfo::from_triple(triple).os == "macos" {
+        "__LLVM,__bitcode"
+    } else {
+        ".llvmbc"
+    }
+}
+
+/// store the module's bitcode as `llvm.embedded.object` like clang -fembed-bitcode
+/// the global is kept alive thru codegen via llvm.compiler.used
+pub(crate) unsafe fn embed_bitcode(llvm_module: LLVMModuleRef, triple: &str) {
+    // emitting twice must not embed the bitcode of a module w/ bitcode in it
+    if !LLVMGetNamedGlobal(llvm_module, b"llvm.embedded.object\0".as_ptr() as *const i8).is_null() {
+        return;
+    }
+    let context = LLVMGetModuleContext(llvm_module);
+    let buffer = LLVMWriteBitcodeToMemoryBuffer(llvm_module);
+    let data = LLVMConstStringInContext(
+        context,
+        LLVMGetBufferStart(buffer),
+        LLVMGetBufferSize(buffer) as u32,
+        1, // dont null terminate
+    );
+    LLVMDisposeMemoryBuffer(buffer);
+
+    let global = LLVMAddGlobal(llvm_module, LLVMTypeOf(data), b"llvm.embedded.object\0".as_ptr() as *const i8);
+    LLVMSetInitializer(global, data);
+    LLVMSetLinkage(global, LLVMLinkage::LLVMPrivateLinkage);
+    LLVMSetAlignment(global, 1);
+    let section = CString::new(embedded_bitcode_section(triple)).unwrap();
+    LLVMSetSection(global, section.as_ptr());
+
+    let ptr_type = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+    let mut used = [LLVMConstBitCast(global, ptr_type)];
+    let used_array = LLVMConstArray2(ptr_type, used.as_mut_ptr(), 1);
+    let used_global = LLVMAddGlobal(llvm_module, LLVMTypeOf(used_array), b"llvm.compiler.used\0".as_ptr() as *const i8);
+    LLVMSetInitializer(used_global, used_array);
+    LLVMSetLinkage(used_global, LLVMLinkage::LLVMAppendingLinkage);
+    LLVMSetSection(used_global, b"llvm.metadata\0".as_ptr() as *const i8);
+}
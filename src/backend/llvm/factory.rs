@@ -1,11 +1,38 @@
-use crate::backend::factory::{BackendFactory, BackendType, BackendError};
-use crate::backend::ports::{CodeGen, Emitter, Optimizer};
+use crate::backend::factory::{BackendCapabilities, BackendFactory, BackendType, BackendError, TargetSupport};
+use crate::backend::ports::codegen::BackendInputType;
+use crate::backend::ports::{CodeGen, Emitter, ExecutionEngine, Optimizer};
+use crate::backend::llvm::jit::LlvmJit;
 use crate::backend::llvm::codegen::LlvmCodeGen;
+#[cfg(not(feature = "inkwell"))]
 use crate::backend::llvm::optimizer::LlvmOptimizer;
+#[cfg(not(feature = "inkwell"))]
 use crate::backend::llvm::emitter::LlvmEmitter;
+#[cfg(feature = "inkwell")]
+use crate::backend::llvm::safe::{SafeLlvmEmitter as LlvmEmitter, SafeLlvmOptimizer as LlvmOptimizer};
 
 /// LLVM backend factory
-pub struct LlvmBackendFactory;
+#[derive(Debug, Clone, Copy)]
+pub struct LlvmBackendFactory {
+    codegen_units: usize,
+}
+
+impl LlvmBackendFactory {
+    pub fn new() -> Self {
+        Self { codegen_units: 1 }
+    }
+
+    /// each unit gets its own context + module, 0 is treated as 1
+    pub fn with_codegen_units(mut self, units: usize) -> Self {
+        self.codegen_units = units.max(1);
+        self
+    }
+}
+
+impl Default for LlvmBackendFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl BackendFactory for LlvmBackendFactory {
     fn create_codegen(&self) -> Result<Box<dyn CodeGen>, BackendError> {
@@ -23,4 +50,21 @@ impl BackendFactory for LlvmBackendFactory {
     fn backend_type(&self) -> BackendType {
         BackendType::Llvm
     }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        // evry target llvm was built w/ is initialized
+        BackendCapabilities { targets: TargetSupport::Any, debug_info: true, jit: true, preferred_input: BackendInputType::Mir }
+    }
+
+    fn version(&self) -> String {
+        format!("llvm {}", crate::backend::llvm::ffi_compat::LLVM_VERSION_MAJOR)
+    }
+
+    fn create_execution_engine(&self) -> Result<Box<dyn ExecutionEngine>, BackendError> {
+        Ok(Box::new(LlvmJit::new().map_err(|e| BackendError::CreationFailed(e.to_string()))?))
+    }
+
+    fn codegen_units(&self) -> usize {
+        self.codegen_units
+    }
 }
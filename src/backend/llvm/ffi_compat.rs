@@ -0,0 +1,42 @@
+// llvm-c calls whose name or signature differs between the supported LLVM majors
+// evrything else in backend/llvm uses llvm_sys directly, the llvm* cargo feature
+// picks which llvm-sys (and system LLVM) `llvm_sys` is, see lib.rs
+use llvm_sys::debuginfo::*;
+use llvm_sys::prelude::*;
+
+#[cfg(all(feature = "llvm21", feature = "llvm18"))]
+compile_error!("features `llvm21` and `llvm18` r mutually exclusive, use --no-default-features --features llvm18");
+#[cfg(not(any(feature = "llvm21", feature = "llvm18")))]
+compile_error!("the `llvm` feature needs an LLVM version, enable `llvm21` or `llvm18` instead");
+
+/// LLVM major emc was built against
+#[cfg(feature = "llvm21")]
+pub const LLVM_VERSION_MAJOR: u32 = 21;
+#[cfg(all(feature = "llvm18", not(feature = "llvm21")))]
+pub const LLVM_VERSION_MAJOR: u32 = 18;
+
+/// describe `value` as `variable` from `before` on
+/// llvm 18 inserts an llvm.dbg.value call, 19+ attach a debug record 2 `before` instead
+#[cfg(feature = "llvm21")]
+pub(crate) unsafe fn insert_dbg_value_before(
+    builder: LLVMDIBuilderRef,
+    value: LLVMValueRef,
+    variable: LLVMMetadataRef,
+    expression: LLVMMetadataRef,
+    location: LLVMMetadataRef,
+    before: LLVMValueRef,
+) {
+    LLVMDIBuilderInsertDbgValueRecordBefore(builder, value, variable, expression, location, before);
+}
+
+#[cfg(all(feature = "llvm18", not(feature = "llvm21")))]
+pub(crate) unsafe fn insert_dbg_value_before(
+    builder: LLVMDIBuilderRef,
+    value: LLVMValueRef,
+    variable: LLVMMetadataRef,
+    expression: LLVMMetadataRef,
+    location: LLVMMetadataRef,
+    before: LLVMValueRef,
+) {
+    LLVMDIBuilderInsertDbgValueBefore(builder, value, variable, expression, location, before);
+}
@@ -1,6 +1,8 @@
 use crate::core::mir::instruction::Instruction;
 use crate::core::mir::operand::{Operand, Local, Constant};
-use crate::backend::llvm::types::mir_type_to_llvm_type;
+use crate::backend::llvm::types::{mir_type_to_llvm_type, string_type, struct_field_indices, trait_object_type, variant_payload_type};
+use crate::core::types::composite::{EnumType, StructType};
+use crate::core::types::ty::Type;
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
 
@@ -51,57 +53,171 @@ fn constant_to_llvm_value(context: LLVMContextRef, constant: &Constant) -> LLVMV
                 let ty = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
                 LLVMConstNull(ty)
             }
+            Constant::Aggregate(values) => {
+                // no type info here - literal struct of the default-typed values
+                let mut vals: Vec<LLVMValueRef> = values.iter().map(|v| constant_to_llvm_value(context, v)).collect();
+                LLVMConstStructInContext(context, vals.as_mut_ptr(), vals.len() as u32, 0)
+            }
+        }
+    }
+}
+
+/// convert constant 2 LLVM value of the given MIR type
+/// aggregates r built recursively so nested arrays/structs get their declared layout
+pub fn typed_constant(context: LLVMContextRef, constant: &Constant, ty: &Type) -> LLVMValueRef {
+    unsafe {
+        match (constant, ty) {
+            (Constant::Aggregate(values), Type::Array(arr)) => {
+                let elem_ty = mir_type_to_llvm_type(context, &arr.element);
+                let mut vals: Vec<LLVMValueRef> = values.iter().map(|v| typed_constant(context, v, &arr.element)).collect();
+                LLVMConstArray2(elem_ty, vals.as_mut_ptr(), vals.len() as u64)
+            }
+            (Constant::Aggregate(values), Type::Struct(s)) => {
+                let mut vals: Vec<LLVMValueRef> = values.iter().zip(&s.fields)
+                    .map(|(v, field)| typed_constant(context, v, &field.type_))
+                    .collect();
+                // named type so field geps on the constant line up
+                let struct_ty = mir_type_to_llvm_type(context, ty);
+                if LLVMIsOpaqueStruct(struct_ty) == 0 && LLVMCountStructElementTypes(struct_ty) as usize == vals.len() {
+                    LLVMConstNamedStruct(struct_ty, vals.as_mut_ptr(), vals.len() as u32)
+                } else {
+                    LLVMConstStructInContext(context, vals.as_mut_ptr(), vals.len() as u32, 0)
+                }
+            }
+            (Constant::Int(_) | Constant::Char(_) | Constant::Bool(_), Type::Primitive(_)) => {
+                // ints take the width of the declared element eg byte tables stay i8
+                let llvm_ty = mir_type_to_llvm_type(context, ty);
+                let n = match constant {
+                    Constant::Int(n) => *n,
+                    Constant::Char(c) => *c as i64,
+                    Constant::Bool(b) => *b as i64,
+                    _ => 0,
+                };
+                LLVMConstInt(llvm_ty, n as u64, 1)
+            }
+            (Constant::Float(f), Type::Primitive(_)) => {
+                LLVMConstReal(mir_type_to_llvm_type(context, ty), *f)
+            }
+            _ => constant_to_llvm_value(context, constant),
         }
     }
 }
 
+/// both sides of a binary op, int constants take the width of the other side (eg `x / 2` w/ x : ulong)
+unsafe fn binary_operands(
+    context: LLVMContextRef,
+    left: &Operand,
+    right: &Operand,
+    local_map: &std::collections::HashMap<usize, LLVMValueRef>,
+) -> (LLVMValueRef, LLVMValueRef) {
+    let mut left_val = operand_to_llvm_value(context, left, local_map);
+    let mut right_val = operand_to_llvm_value(context, right, local_map);
+    match (left, right) {
+        (Operand::Constant(c @ (Constant::Int(_) | Constant::Char(_))), Operand::Local(_)) => {
+            left_val = retyped_int(c, LLVMTypeOf(right_val)).unwrap_or(left_val);
+        }
+        (Operand::Local(_), Operand::Constant(c @ (Constant::Int(_) | Constant::Char(_)))) => {
+            right_val = retyped_int(c, LLVMTypeOf(left_val)).unwrap_or(right_val);
+        }
+        _ => {}
+    }
+    (left_val, right_val)
+}
+
+unsafe fn retyped_int(constant: &Constant, ty: LLVMTypeRef) -> Option<LLVMValueRef> {
+    if LLVMGetTypeKind(ty) != llvm_sys::LLVMTypeKind::LLVMIntegerTypeKind {
+        return None;
+    }
+    let n = match constant {
+        Constant::Int(n) => *n,
+        Constant::Char(c) => *c as i64,
+        _ => return None,
+    };
+    Some(LLVMConstInt(ty, n as u64, 1))
+}
+
+/// unsigned if the op type or either local operand is, constants take the other side's type
+fn is_unsigned_op(
+    type_: Option<&Type>,
+    operands: [&Operand; 2],
+    local_types: &std::collections::HashMap<usize, Type>,
+) -> bool {
+    let unsigned = |ty: &Type| matches!(ty, Type::Primitive(p) if p.is_unsigned());
+    type_.is_some_and(unsigned)
+        || operands.iter().any(|op| matches!(op, Operand::Local(l) if local_types.get(&l.id).is_some_and(unsigned)))
+}
+
+/// int operand of a float op as a double, MIR has no int 2 float casts so `a + n` w/ n : int lands here
+unsafe fn float_operand(
+    builder: LLVMBuilderRef,
+    context: LLVMContextRef,
+    value: LLVMValueRef,
+    operand: &Operand,
+    local_types: &std::collections::HashMap<usize, Type>,
+) -> LLVMValueRef {
+    if LLVMGetTypeKind(LLVMTypeOf(value)) != llvm_sys::LLVMTypeKind::LLVMIntegerTypeKind {
+        return value;
+    }
+    let double = LLVMDoubleTypeInContext(context);
+    if is_unsigned_op(None, [operand, operand], local_types) {
+        LLVMBuildUIToFP(builder, value, double, b"uitofp\0".as_ptr() as *const i8)
+    } else {
+        // constants fold 2 a double constant
+        LLVMBuildSIToFP(builder, value, double, b"sitofp\0".as_ptr() as *const i8)
+    }
+}
+
+fn is_float_operand(operand: &Operand, local_types: &std::collections::HashMap<usize, Type>) -> bool {
+    match operand {
+        Operand::Constant(c) => matches!(c, Constant::Float(_)),
+        Operand::Local(l) => matches!(local_types.get(&l.id), Some(Type::Primitive(p)) if p.is_float()),
+        Operand::Function(_) => false,
+    }
+}
+
 /// translate arithmetic instruction
 pub fn translate_arithmetic(
     builder: LLVMBuilderRef,
     inst: &Instruction,
     local_map: &mut std::collections::HashMap<usize, LLVMValueRef>,
+    local_types: &std::collections::HashMap<usize, Type>,
     context: LLVMContextRef,
 ) -> Option<LLVMValueRef> {
     unsafe {
-        match inst {
-            Instruction::Add { dest, left, right, type_: _ } => {
-                let left_val = operand_to_llvm_value(context, left, local_map);
-                let right_val = operand_to_llvm_value(context, right, local_map);
-                let result = LLVMBuildAdd(builder, left_val, right_val, b"add\0".as_ptr() as *const i8);
-                local_map.insert(dest.id, result);
-                Some(result)
-            }
-            Instruction::Sub { dest, left, right, type_: _ } => {
-                let left_val = operand_to_llvm_value(context, left, local_map);
-                let right_val = operand_to_llvm_value(context, right, local_map);
-                let result = LLVMBuildSub(builder, left_val, right_val, b"sub\0".as_ptr() as *const i8);
-                local_map.insert(dest.id, result);
-                Some(result)
-            }
-            Instruction::Mul { dest, left, right, type_: _ } => {
-                let left_val = operand_to_llvm_value(context, left, local_map);
-                let right_val = operand_to_llvm_value(context, right, local_map);
-                let result = LLVMBuildMul(builder, left_val, right_val, b"mul\0".as_ptr() as *const i8);
-                local_map.insert(dest.id, result);
-                Some(result)
-            }
-            Instruction::Div { dest, left, right, type_: _ } => {
-                let left_val = operand_to_llvm_value(context, left, local_map);
-                let right_val = operand_to_llvm_value(context, right, local_map);
-                // check if signed or unsigned - default to signed
-                let result = LLVMBuildSDiv(builder, left_val, right_val, b"div\0".as_ptr() as *const i8);
-                local_map.insert(dest.id, result);
-                Some(result)
-            }
-            Instruction::Mod { dest, left, right, type_: _ } => {
-                let left_val = operand_to_llvm_value(context, left, local_map);
-                let right_val = operand_to_llvm_value(context, right, local_map);
-                let result = LLVMBuildSRem(builder, left_val, right_val, b"mod\0".as_ptr() as *const i8);
-                local_map.insert(dest.id, result);
-                Some(result)
-            }
-            _ => None,
+        let (dest, left, right, type_) = match inst {
+            Instruction::Add { dest, left, right, type_ } |
+            Instruction::Sub { dest, left, right, type_ } |
+            Instruction::Mul { dest, left, right, type_ } |
+            Instruction::Div { dest, left, right, type_ } |
+            Instruction::Mod { dest, left, right, type_ } => (dest, left, right, type_),
+            _ => return None,
+        };
+        let (mut left_val, mut right_val) = binary_operands(context, left, right, local_map);
+        let float = matches!(type_, Type::Primitive(p) if p.is_float());
+        if float {
+            left_val = float_operand(builder, context, left_val, left, local_types);
+            right_val = float_operand(builder, context, right_val, right, local_types);
         }
+        let unsigned = !float && is_unsigned_op(Some(type_), [left, right], local_types);
+
+        let result = match (inst, float) {
+            (Instruction::Add { .. }, false) => LLVMBuildAdd(builder, left_val, right_val, b"add\0".as_ptr() as *const i8),
+            (Instruction::Add { .. }, true) => LLVMBuildFAdd(builder, left_val, right_val, b"fadd\0".as_ptr() as *const i8),
+            (Instruction::Sub { .. }, false) => LLVMBuildSub(builder, left_val, right_val, b"sub\0".as_ptr() as *const i8),
+            (Instruction::Sub { .. }, true) => LLVMBuildFSub(builder, left_val, right_val, b"fsub\0".as_ptr() as *const i8),
+            (Instruction::Mul { .. }, false) => LLVMBuildMul(builder, left_val, right_val, b"mul\0".as_ptr() as *const i8),
+            (Instruction::Mul { .. }, true) => LLVMBuildFMul(builder, left_val, right_val, b"fmul\0".as_ptr() as *const i8),
+            (Instruction::Div { .. }, true) => LLVMBuildFDiv(builder, left_val, right_val, b"fdiv\0".as_ptr() as *const i8),
+            (Instruction::Div { .. }, false) if unsigned => {
+                LLVMBuildUDiv(builder, left_val, right_val, b"div\0".as_ptr() as *const i8)
+            }
+            (Instruction::Div { .. }, false) => LLVMBuildSDiv(builder, left_val, right_val, b"div\0".as_ptr() as *const i8),
+            (_, true) => LLVMBuildFRem(builder, left_val, right_val, b"fmod\0".as_ptr() as *const i8),
+            (_, false) if unsigned => LLVMBuildURem(builder, left_val, right_val, b"mod\0".as_ptr() as *const i8),
+            (_, false) => LLVMBuildSRem(builder, left_val, right_val, b"mod\0".as_ptr() as *const i8),
+        };
+        local_map.insert(dest.id, result);
+        Some(result)
     }
 }
 
@@ -110,8 +226,12 @@ pub fn translate_comparison(
     builder: LLVMBuilderRef,
     inst: &Instruction,
     local_map: &mut std::collections::HashMap<usize, LLVMValueRef>,
+    local_types: &std::collections::HashMap<usize, Type>,
     context: LLVMContextRef,
 ) -> Option<LLVMValueRef> {
+    use llvm_sys::LLVMIntPredicate::*;
+    use llvm_sys::LLVMRealPredicate::*;
+
     unsafe {
         let (left, right) = match inst {
             Instruction::Eq { left, right, .. } |
@@ -119,33 +239,38 @@ pub fn translate_comparison(
             Instruction::Lt { left, right, .. } |
             Instruction::Le { left, right, .. } |
             Instruction::Gt { left, right, .. } |
-            Instruction::Ge { left, right, .. } => {
-                (operand_to_llvm_value(context, left, local_map),
-                 operand_to_llvm_value(context, right, local_map))
-            }
+            Instruction::Ge { left, right, .. } => (left, right),
             _ => return None,
         };
+        let (mut left_val, mut right_val) = binary_operands(context, left, right, local_map);
 
-        let result = match inst {
-            Instruction::Eq { .. } => {
-                LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntEQ, left, right, b"eq\0".as_ptr() as *const i8)
-            }
-            Instruction::Ne { .. } => {
-                LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntNE, left, right, b"ne\0".as_ptr() as *const i8)
-            }
-            Instruction::Lt { .. } => {
-                LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntSLT, left, right, b"lt\0".as_ptr() as *const i8)
-            }
-            Instruction::Le { .. } => {
-                LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntSLE, left, right, b"le\0".as_ptr() as *const i8)
-            }
-            Instruction::Gt { .. } => {
-                LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntSGT, left, right, b"gt\0".as_ptr() as *const i8)
-            }
-            Instruction::Ge { .. } => {
-                LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntSGE, left, right, b"ge\0".as_ptr() as *const i8)
-            }
-            _ => return None,
+        let result = if is_float_operand(left, local_types) || is_float_operand(right, local_types) {
+            left_val = float_operand(builder, context, left_val, left, local_types);
+            right_val = float_operand(builder, context, right_val, right, local_types);
+            // ordered, comparisons w/ nan r false, except != which is true like C
+            let (predicate, name) = match inst {
+                Instruction::Eq { .. } => (LLVMRealOEQ, b"eq\0"),
+                Instruction::Ne { .. } => (LLVMRealUNE, b"ne\0"),
+                Instruction::Lt { .. } => (LLVMRealOLT, b"lt\0"),
+                Instruction::Le { .. } => (LLVMRealOLE, b"le\0"),
+                Instruction::Gt { .. } => (LLVMRealOGT, b"gt\0"),
+                _ => (LLVMRealOGE, b"ge\0"),
+            };
+            LLVMBuildFCmp(builder, predicate, left_val, right_val, name.as_ptr() as *const i8)
+        } else {
+            let (predicate, name) = match (inst, is_unsigned_op(None, [left, right], local_types)) {
+                (Instruction::Eq { .. }, _) => (LLVMIntEQ, b"eq\0"),
+                (Instruction::Ne { .. }, _) => (LLVMIntNE, b"ne\0"),
+                (Instruction::Lt { .. }, false) => (LLVMIntSLT, b"lt\0"),
+                (Instruction::Lt { .. }, true) => (LLVMIntULT, b"lt\0"),
+                (Instruction::Le { .. }, false) => (LLVMIntSLE, b"le\0"),
+                (Instruction::Le { .. }, true) => (LLVMIntULE, b"le\0"),
+                (Instruction::Gt { .. }, false) => (LLVMIntSGT, b"gt\0"),
+                (Instruction::Gt { .. }, true) => (LLVMIntUGT, b"gt\0"),
+                (_, false) => (LLVMIntSGE, b"ge\0"),
+                (_, true) => (LLVMIntUGE, b"ge\0"),
+            };
+            LLVMBuildICmp(builder, predicate, left_val, right_val, name.as_ptr() as *const i8)
         };
 
         if let Some(dest) = get_dest_local(inst) {
@@ -171,12 +296,43 @@ pub fn translate_memory(
                 local_map.insert(dest.id, result);
                 Some(result)
             }
-            Instruction::Store { dest, source, type_: _type_ } => {
+            Instruction::Store { dest, source, type_ } => {
                 let ptr = operand_to_llvm_value(context, dest, local_map);
-                let val = operand_to_llvm_value(context, source, local_map);
+                let val = match source {
+                    Operand::Constant(c @ Constant::Aggregate(_)) => typed_constant(context, c, type_),
+                    Operand::Constant(Constant::Null) => LLVMConstNull(mir_type_to_llvm_type(context, type_)),
+                    // an int stored in2 a wider field, eg `{ y: 2 }` as `{ y: long }`
+                    _ if matches!(type_, Type::Primitive(p) if p.is_integer()) => {
+                        fill_value(builder, context, source, mir_type_to_llvm_type(context, type_), local_map)
+                    }
+                    _ => {
+                        let val = operand_to_llvm_value(context, source, local_map);
+                        // struct locals r addresses, copy the value they hold
+                        if type_.is_struct() && is_pointer_value(val) {
+                            LLVMBuildLoad2(builder, mir_type_to_llvm_type(context, type_), val, b"copy\0".as_ptr() as *const i8)
+                        } else {
+                            val
+                        }
+                    }
+                };
                 LLVMBuildStore(builder, val, ptr);
                 None
             }
+            Instruction::VolatileLoad { dest, source, type_ } => {
+                let ptr = operand_to_llvm_value(context, source, local_map);
+                let ty = mir_type_to_llvm_type(context, type_);
+                let result = LLVMBuildLoad2(builder, ty, ptr, b"vload\0".as_ptr() as *const i8);
+                LLVMSetVolatile(result, 1);
+                local_map.insert(dest.id, result);
+                Some(result)
+            }
+            Instruction::VolatileStore { dest, source, type_: _ } => {
+                let ptr = operand_to_llvm_value(context, dest, local_map);
+                let val = operand_to_llvm_value(context, source, local_map);
+                let store = LLVMBuildStore(builder, val, ptr);
+                LLVMSetVolatile(store, 1);
+                None
+            }
             Instruction::Alloca { dest, type_ } => {
                 let ty = mir_type_to_llvm_type(context, type_);
                 let result = LLVMBuildAlloca(builder, ty, b"alloca\0".as_ptr() as *const i8);
@@ -184,7 +340,13 @@ pub fn translate_memory(
                 Some(result)
             }
             Instruction::Gep { dest, base, index, type_ } => {
-                let base_ptr = operand_to_llvm_value(context, base, local_map);
+                let mut base_ptr = operand_to_llvm_value(context, base, local_map);
+                // an array value eg a param is spilled like a by value struct
+                if !is_pointer_value(base_ptr) {
+                    let slot = LLVMBuildAlloca(builder, LLVMTypeOf(base_ptr), b"spill\0".as_ptr() as *const i8);
+                    LLVMBuildStore(builder, base_ptr, slot);
+                    base_ptr = slot;
+                }
                 let idx = operand_to_llvm_value(context, index, local_map);
                 let ty = mir_type_to_llvm_type(context, type_);
                 let mut indices = [idx];
@@ -192,11 +354,411 @@ pub fn translate_memory(
                 local_map.insert(dest.id, result);
                 Some(result)
             }
+            Instruction::PtrOffset { dest, base, offset, type_ } => {
+                // explicit ptr arithmetic - caller promises the result stays in the same object
+                let base_ptr = operand_to_llvm_value(context, base, local_map);
+                let offset_val = operand_to_llvm_value(context, offset, local_map);
+                let ty = mir_type_to_llvm_type(context, type_);
+                let mut indices = [offset_val];
+                let result = LLVMBuildInBoundsGEP2(builder, ty, base_ptr, indices.as_mut_ptr(), indices.len() as u32, b"ptr.offset\0".as_ptr() as *const i8);
+                local_map.insert(dest.id, result);
+                Some(result)
+            }
+            Instruction::Memset { dest, value, count, type_ } => {
+                let ptr = operand_to_llvm_value(context, dest, local_map);
+                let elem_ty = mir_type_to_llvm_type(context, type_);
+                let array_ty = LLVMArrayType2(elem_ty, *count as u64);
+                if is_zero_constant(value) {
+                    // zero fill - store zeroinitializer aggregate
+                    LLVMBuildStore(builder, LLVMConstNull(array_ty), ptr);
+                } else if type_.size_in_bytes() == Some(1) {
+                    // byte fill - llvm.memset
+                    let byte = fill_value(builder, context, value, elem_ty, local_map);
+                    let len = LLVMConstInt(LLVMInt64TypeInContext(context), *count as u64, 0);
+                    LLVMBuildMemSet(builder, ptr, byte, len, 1);
+                } else if let Operand::Constant(_) = value {
+                    // const fill - store splatted constant aggregate
+                    let elem = fill_value(builder, context, value, elem_ty, local_map);
+                    let mut elems = vec![elem; *count];
+                    let splat = LLVMConstArray2(elem_ty, elems.as_mut_ptr(), *count as u64);
+                    LLVMBuildStore(builder, splat, ptr);
+                } else {
+                    // runtime value wider than a byte - store each element
+                    let val = fill_value(builder, context, value, elem_ty, local_map);
+                    let idx_ty = LLVMInt64TypeInContext(context);
+                    for i in 0..*count {
+                        let mut indices = [LLVMConstInt(idx_ty, 0, 0), LLVMConstInt(idx_ty, i as u64, 0)];
+                        let slot = LLVMBuildInBoundsGEP2(builder, array_ty, ptr, indices.as_mut_ptr(), 2, b"fill\0".as_ptr() as *const i8);
+                        LLVMBuildStore(builder, val, slot);
+                    }
+                }
+                None
+            }
             _ => None,
         }
     }
 }
 
+/// struct being indexed when a Gep base is a struct (or ptr 2 struct) local
+pub fn gep_struct_base<'a>(
+    inst: &Instruction,
+    local_types: &'a std::collections::HashMap<usize, Type>,
+) -> Option<&'a StructType> {
+    let base = match inst {
+        Instruction::Gep { base: Operand::Local(base), index: Operand::Constant(Constant::Int(_)), .. } => base,
+        _ => return None,
+    };
+    match local_types.get(&base.id)? {
+        Type::Struct(s) => Some(s),
+        Type::Pointer(p) => match &*p.pointee {
+            Type::Struct(s) => Some(s),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// array being indexed when a Gep base is an array (or ptr 2 array) local. the mir type
+/// says so, opaque llvm ptrs dont
+pub fn gep_array_base<'a>(
+    inst: &Instruction,
+    local_types: &'a std::collections::HashMap<usize, Type>,
+) -> Option<&'a Type> {
+    let base = match inst {
+        Instruction::Gep { base: Operand::Local(base), .. } => base,
+        _ => return None,
+    };
+    match local_types.get(&base.id)? {
+        array @ Type::Array(_) => Some(array),
+        Type::Pointer(p) if matches!(&*p.pointee, Type::Array(_)) => Some(&p.pointee),
+        _ => None,
+    }
+}
+
+/// element address via an array gep, stepping in2 the array frm element 0. by value
+/// arrays eg params r spilled like structs
+pub(crate) unsafe fn translate_element_gep(
+    builder: LLVMBuilderRef,
+    inst: &Instruction,
+    array: &Type,
+    local_map: &mut std::collections::HashMap<usize, LLVMValueRef>,
+    context: LLVMContextRef,
+) -> Option<LLVMValueRef> {
+    let Instruction::Gep { dest, base, index, .. } = inst else { return None };
+    let array_ty = mir_type_to_llvm_type(context, array);
+    let mut ptr = operand_to_llvm_value(context, base, local_map);
+    if !is_pointer_value(ptr) {
+        let slot = LLVMBuildAlloca(builder, array_ty, b"spill\0".as_ptr() as *const i8);
+        LLVMBuildStore(builder, ptr, slot);
+        ptr = slot;
+    }
+    let idx = operand_to_llvm_value(context, index, local_map);
+    let mut indices = [LLVMConstInt(LLVMTypeOf(idx), 0, 0), idx];
+    let result = LLVMBuildGEP2(builder, array_ty, ptr, indices.as_mut_ptr(), indices.len() as u32, b"gep\0".as_ptr() as *const i8);
+    local_map.insert(dest.id, result);
+    Some(result)
+}
+
+/// field address via a struct gep. mir struct locals r usually already addresses
+/// (alloca / const global), by value structs eg params r spilled 2 a stack slot first
+pub fn translate_field_gep(
+    builder: LLVMBuilderRef,
+    inst: &Instruction,
+    s: &StructType,
+    local_map: &mut std::collections::HashMap<usize, LLVMValueRef>,
+    context: LLVMContextRef,
+) -> Option<LLVMValueRef> {
+    let (dest, base, field_idx) = match inst {
+        Instruction::Gep { dest, base, index: Operand::Constant(Constant::Int(idx)), .. } => (dest, base, *idx as usize),
+        _ => return None,
+    };
+    unsafe {
+        let struct_ty = mir_type_to_llvm_type(context, &Type::Struct(s.clone()));
+        let mut ptr = operand_to_llvm_value(context, base, local_map);
+        if !is_pointer_value(ptr) {
+            let slot = LLVMBuildAlloca(builder, struct_ty, b"spill\0".as_ptr() as *const i8);
+            LLVMBuildStore(builder, ptr, slot);
+            ptr = slot;
+        }
+        let element = *struct_field_indices(context, s).get(field_idx)?;
+        let result = LLVMBuildStructGEP2(builder, struct_ty, ptr, element, b"field\0".as_ptr() as *const i8);
+        local_map.insert(dest.id, result);
+        Some(result)
+    }
+}
+
+/// string len / concat on `{ i8*, i64 }` values
+/// concat mallocs a fresh buffer, neither side is modified
+/// eq compares lens then bytes
+pub fn translate_string(
+    builder: LLVMBuilderRef,
+    inst: &Instruction,
+    local_map: &mut std::collections::HashMap<usize, LLVMValueRef>,
+    context: LLVMContextRef,
+) -> Option<LLVMValueRef> {
+    unsafe {
+        let result = match inst {
+            Instruction::StrLen { dest, source } => {
+                let s = string_value(builder, context, source, local_map);
+                let len = LLVMBuildExtractValue(builder, s, 1, b"len\0".as_ptr() as *const i8);
+                (dest, len)
+            }
+            Instruction::StrConcat { dest, left, right } => {
+                let l = string_value(builder, context, left, local_map);
+                let r = string_value(builder, context, right, local_map);
+                let l_ptr = LLVMBuildExtractValue(builder, l, 0, b"lptr\0".as_ptr() as *const i8);
+                let l_len = LLVMBuildExtractValue(builder, l, 1, b"llen\0".as_ptr() as *const i8);
+                let r_ptr = LLVMBuildExtractValue(builder, r, 0, b"rptr\0".as_ptr() as *const i8);
+                let r_len = LLVMBuildExtractValue(builder, r, 1, b"rlen\0".as_ptr() as *const i8);
+                let len = LLVMBuildAdd(builder, l_len, r_len, b"catlen\0".as_ptr() as *const i8);
+
+                let i8_ty = LLVMInt8TypeInContext(context);
+                let buf = build_malloc(builder, context, len);
+                LLVMBuildMemCpy(builder, buf, 1, l_ptr, 1, l_len);
+                let mut offset = [l_len];
+                let tail = LLVMBuildGEP2(builder, i8_ty, buf, offset.as_mut_ptr(), 1, b"cattail\0".as_ptr() as *const i8);
+                LLVMBuildMemCpy(builder, tail, 1, r_ptr, 1, r_len);
+
+                let str_ty = string_type(context);
+                let with_ptr = LLVMBuildInsertValue(builder, LLVMGetUndef(str_ty), buf, 0, b"cat\0".as_ptr() as *const i8);
+                let cat = LLVMBuildInsertValue(builder, with_ptr, len, 1, b"cat\0".as_ptr() as *const i8);
+                (dest, cat)
+            }
+            Instruction::StrEq { dest, left, right } => {
+                let l = string_value(builder, context, left, local_map);
+                let r = string_value(builder, context, right, local_map);
+                let l_ptr = LLVMBuildExtractValue(builder, l, 0, b"lptr\0".as_ptr() as *const i8);
+                let l_len = LLVMBuildExtractValue(builder, l, 1, b"llen\0".as_ptr() as *const i8);
+                let r_ptr = LLVMBuildExtractValue(builder, r, 0, b"rptr\0".as_ptr() as *const i8);
+                let r_len = LLVMBuildExtractValue(builder, r, 1, b"rlen\0".as_ptr() as *const i8);
+                let same_len = LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntEQ, l_len, r_len, b"samelen\0".as_ptr() as *const i8);
+                // memcmp 0 bytes when the lens differ so it never reads past the shorter one
+                let zero = LLVMConstInt(LLVMInt64TypeInContext(context), 0, 0);
+                let count = LLVMBuildSelect(builder, same_len, l_len, zero, b"cmplen\0".as_ptr() as *const i8);
+                let diff = build_memcmp(builder, context, l_ptr, r_ptr, count);
+                let same_bytes = LLVMBuildICmp(
+                    builder,
+                    llvm_sys::LLVMIntPredicate::LLVMIntEQ,
+                    diff,
+                    LLVMConstInt(LLVMInt32TypeInContext(context), 0, 0),
+                    b"samebytes\0".as_ptr() as *const i8,
+                );
+                (dest, LLVMBuildAnd(builder, same_len, same_bytes, b"streq\0".as_ptr() as *const i8))
+            }
+            _ => return None,
+        };
+        let (dest, value) = result;
+        local_map.insert(dest.id, value);
+        Some(value)
+    }
+}
+
+/// call thru slot `slot` of the object's vtable, its data ptr goes in as `self`
+pub fn translate_dyn_call(
+    builder: LLVMBuilderRef,
+    inst: &Instruction,
+    local_map: &mut std::collections::HashMap<usize, LLVMValueRef>,
+    context: LLVMContextRef,
+) -> Option<LLVMValueRef> {
+    let Instruction::DynCall { dest, object, slot, args, return_type } = inst else { return None };
+    unsafe {
+        let mut object = operand_to_llvm_value(context, object, local_map);
+        if is_pointer_value(object) {
+            object = LLVMBuildLoad2(builder, trait_object_type(context), object, b"dyn\0".as_ptr() as *const i8);
+        }
+        let byte_ptr = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+        let data = LLVMBuildExtractValue(builder, object, 0, b"data\0".as_ptr() as *const i8);
+        let vtable = LLVMBuildExtractValue(builder, object, 1, b"vtable\0".as_ptr() as *const i8);
+        let mut index = [LLVMConstInt(LLVMInt64TypeInContext(context), *slot as u64, 0)];
+        let entry = LLVMBuildGEP2(builder, byte_ptr, vtable, index.as_mut_ptr(), 1, b"slot\0".as_ptr() as *const i8);
+        let method = LLVMBuildLoad2(builder, byte_ptr, entry, b"method\0".as_ptr() as *const i8);
+
+        let mut call_args = vec![data];
+        call_args.extend(args.iter().map(|a| operand_to_llvm_value(context, a, local_map)));
+        let mut param_types: Vec<LLVMTypeRef> = call_args.iter().map(|a| LLVMTypeOf(*a)).collect();
+        let ret_type = match return_type {
+            Some(t) if dest.is_some() => mir_type_to_llvm_type(context, t),
+            _ => LLVMVoidTypeInContext(context),
+        };
+        let fn_type = LLVMFunctionType(ret_type, param_types.as_mut_ptr(), param_types.len() as u32, 0);
+        let callee = LLVMBuildBitCast(builder, method, LLVMPointerType(fn_type, 0), b"\0".as_ptr() as *const i8);
+        let name = if dest.is_some() { b"dyncall\0".as_ptr() } else { b"\0".as_ptr() };
+        let result = LLVMBuildCall2(builder, fn_type, callee, call_args.as_mut_ptr(), call_args.len() as u32, name as *const i8);
+        if let Some(d) = dest {
+            local_map.insert(d.id, result);
+        }
+        Some(result)
+    }
+}
+
+/// enum values r `{ i32 tag, [n x i64] payload }` aggregates, a variant writes + reads
+/// its fields thru the payload cast 2 a struct of just that variant's fields
+pub fn translate_enum(
+    builder: LLVMBuilderRef,
+    inst: &Instruction,
+    local_map: &mut std::collections::HashMap<usize, LLVMValueRef>,
+    context: LLVMContextRef,
+) -> Option<LLVMValueRef> {
+    unsafe {
+        let (dest, value) = match inst {
+            Instruction::MakeVariant { dest, type_: Type::Enum(e), tag, fields } => {
+                let ty = mir_type_to_llvm_type(context, &Type::Enum(e.clone()));
+                let slot = LLVMBuildAlloca(builder, ty, b"variant\0".as_ptr() as *const i8);
+                let tag_ptr = LLVMBuildStructGEP2(builder, ty, slot, 0, b"tagptr\0".as_ptr() as *const i8);
+                LLVMBuildStore(builder, LLVMConstInt(LLVMInt32TypeInContext(context), *tag as u64, 0), tag_ptr);
+                if !fields.is_empty() {
+                    let (payload_ty, payload) = variant_payload(builder, context, e, *tag, slot);
+                    for (i, field) in fields.iter().enumerate() {
+                        let field_ty = mir_type_to_llvm_type(context, &e.variants[*tag].fields[i]);
+                        let value = fill_value(builder, context, field, field_ty, local_map);
+                        let field_ptr = LLVMBuildStructGEP2(builder, payload_ty, payload, i as u32, b"fieldptr\0".as_ptr() as *const i8);
+                        LLVMBuildStore(builder, value, field_ptr);
+                    }
+                }
+                (dest, LLVMBuildLoad2(builder, ty, slot, b"variant\0".as_ptr() as *const i8))
+            }
+            Instruction::EnumTag { dest, source } => {
+                let value = operand_to_llvm_value(context, source, local_map);
+                let tag = if is_pointer_value(value) {
+                    let tag_ptr = LLVMBuildBitCast(
+                        builder,
+                        value,
+                        LLVMPointerType(LLVMInt32TypeInContext(context), 0),
+                        b"tagptr\0".as_ptr() as *const i8,
+                    );
+                    LLVMBuildLoad2(builder, LLVMInt32TypeInContext(context), tag_ptr, b"tag\0".as_ptr() as *const i8)
+                } else {
+                    LLVMBuildExtractValue(builder, value, 0, b"tag\0".as_ptr() as *const i8)
+                };
+                (dest, tag)
+            }
+            Instruction::VariantField { dest, source, type_: Type::Enum(e), tag, index } => {
+                let mut slot = operand_to_llvm_value(context, source, local_map);
+                if !is_pointer_value(slot) {
+                    // payload words r only addressable in memory
+                    let value = slot;
+                    slot = LLVMBuildAlloca(builder, LLVMTypeOf(value), b"scrutinee\0".as_ptr() as *const i8);
+                    LLVMBuildStore(builder, value, slot);
+                }
+                let (payload_ty, payload) = variant_payload(builder, context, e, *tag, slot);
+                let field_ptr = LLVMBuildStructGEP2(builder, payload_ty, payload, *index as u32, b"fieldptr\0".as_ptr() as *const i8);
+                let field_ty = mir_type_to_llvm_type(context, &e.variants[*tag].fields[*index]);
+                (dest, LLVMBuildLoad2(builder, field_ty, field_ptr, b"field\0".as_ptr() as *const i8))
+            }
+            _ => return None,
+        };
+        local_map.insert(dest.id, value);
+        Some(value)
+    }
+}
+
+/// ptr 2 the payload of the enum at `slot`, typed as variant `tag`'s fields
+unsafe fn variant_payload(
+    builder: LLVMBuilderRef,
+    context: LLVMContextRef,
+    e: &EnumType,
+    tag: usize,
+    slot: LLVMValueRef,
+) -> (LLVMTypeRef, LLVMValueRef) {
+    let ty = mir_type_to_llvm_type(context, &Type::Enum(e.clone()));
+    let payload_ty = variant_payload_type(context, &e.variants[tag].fields);
+    let words = LLVMBuildStructGEP2(builder, ty, slot, 1, b"payload\0".as_ptr() as *const i8);
+    let payload = LLVMBuildBitCast(builder, words, LLVMPointerType(payload_ty, 0), b"fields\0".as_ptr() as *const i8);
+    (payload_ty, payload)
+}
+
+/// `malloc(i64)` call, LLVMBuildArrayMalloc would truncate the size 2 i32
+unsafe fn build_malloc(builder: LLVMBuilderRef, context: LLVMContextRef, size: LLVMValueRef) -> LLVMValueRef {
+    let module = LLVMGetGlobalParent(LLVMGetBasicBlockParent(LLVMGetInsertBlock(builder)));
+    let byte_ptr = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+    let mut params = [LLVMInt64TypeInContext(context)];
+    let fn_ty = LLVMFunctionType(byte_ptr, params.as_mut_ptr(), 1, 0);
+    let name = b"malloc\0".as_ptr() as *const i8;
+    let mut malloc = LLVMGetNamedFunction(module, name);
+    if malloc.is_null() {
+        malloc = LLVMAddFunction(module, name, fn_ty);
+    }
+    let mut args = [size];
+    LLVMBuildCall2(builder, fn_ty, malloc, args.as_mut_ptr(), 1, b"catbuf\0".as_ptr() as *const i8)
+}
+
+unsafe fn build_memcmp(
+    builder: LLVMBuilderRef,
+    context: LLVMContextRef,
+    left: LLVMValueRef,
+    right: LLVMValueRef,
+    count: LLVMValueRef,
+) -> LLVMValueRef {
+    let module = LLVMGetGlobalParent(LLVMGetBasicBlockParent(LLVMGetInsertBlock(builder)));
+    let byte_ptr = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+    let mut params = [byte_ptr, byte_ptr, LLVMInt64TypeInContext(context)];
+    let fn_ty = LLVMFunctionType(LLVMInt32TypeInContext(context), params.as_mut_ptr(), 3, 0);
+    let name = b"memcmp\0".as_ptr() as *const i8;
+    let mut memcmp = LLVMGetNamedFunction(module, name);
+    if memcmp.is_null() {
+        memcmp = LLVMAddFunction(module, name, fn_ty);
+    }
+    let mut args = [left, right, count];
+    LLVMBuildCall2(builder, fn_ty, memcmp, args.as_mut_ptr(), 3, b"cmp\0".as_ptr() as *const i8)
+}
+
+/// string operand as a `str` value, loaded if the local is an address
+unsafe fn string_value(
+    builder: LLVMBuilderRef,
+    context: LLVMContextRef,
+    operand: &Operand,
+    local_map: &std::collections::HashMap<usize, LLVMValueRef>,
+) -> LLVMValueRef {
+    let value = operand_to_llvm_value(context, operand, local_map);
+    if is_pointer_value(value) {
+        LLVMBuildLoad2(builder, string_type(context), value, b"str\0".as_ptr() as *const i8)
+    } else {
+        value
+    }
+}
+
+pub(crate) fn is_pointer_value(value: LLVMValueRef) -> bool {
+    unsafe { LLVMGetTypeKind(LLVMTypeOf(value)) == llvm_sys::LLVMTypeKind::LLVMPointerTypeKind }
+}
+
+fn is_zero_constant(operand: &Operand) -> bool {
+    matches!(
+        operand,
+        Operand::Constant(Constant::Int(0))
+            | Operand::Constant(Constant::Bool(false))
+            | Operand::Constant(Constant::Char('\0'))
+            | Operand::Constant(Constant::Null)
+    ) || matches!(operand, Operand::Constant(Constant::Float(f)) if *f == 0.0 && f.is_sign_positive())
+}
+
+/// fill value converted 2 the element type of a Memset, also widens int variant fields
+fn fill_value(
+    builder: LLVMBuilderRef,
+    context: LLVMContextRef,
+    value: &Operand,
+    elem_ty: LLVMTypeRef,
+    local_map: &std::collections::HashMap<usize, LLVMValueRef>,
+) -> LLVMValueRef {
+    unsafe {
+        let is_int_elem = LLVMGetTypeKind(elem_ty) == llvm_sys::LLVMTypeKind::LLVMIntegerTypeKind;
+        match value {
+            Operand::Constant(Constant::Int(n)) if is_int_elem => LLVMConstInt(elem_ty, *n as u64, 1),
+            Operand::Constant(Constant::Float(f)) => LLVMConstReal(elem_ty, *f),
+            _ => {
+                let val = operand_to_llvm_value(context, value, local_map);
+                if is_int_elem && LLVMTypeOf(val) != elem_ty {
+                    LLVMBuildIntCast2(builder, val, elem_ty, 1, b"fill.cast\0".as_ptr() as *const i8)
+                } else if LLVMGetTypeKind(elem_ty) == llvm_sys::LLVMTypeKind::LLVMStructTypeKind && is_pointer_value(val) {
+                    // a struct local is its stack slot, the element gets a copy of what it holds
+                    LLVMBuildLoad2(builder, elem_ty, val, b"fill.load\0".as_ptr() as *const i8)
+                } else {
+                    val
+                }
+            }
+        }
+    }
+}
+
 /// translate control flow instruction
 pub fn translate_control_flow(
     builder: LLVMBuilderRef,
@@ -209,7 +771,17 @@ pub fn translate_control_flow(
         match inst {
             Instruction::Ret { value } => {
                 if let Some(val) = value {
-                    let ret_val = operand_to_llvm_value(context, val, local_map);
+                    let mut ret_val = operand_to_llvm_value(context, val, local_map);
+                    // struct results live in a stack slot / const global, return the value
+                    let func = LLVMGetBasicBlockParent(LLVMGetInsertBlock(builder));
+                    let ret_ty = LLVMGetReturnType(LLVMGlobalGetValueType(func));
+                    let int = llvm_sys::LLVMTypeKind::LLVMIntegerTypeKind;
+                    if LLVMGetTypeKind(ret_ty) == llvm_sys::LLVMTypeKind::LLVMStructTypeKind && is_pointer_value(ret_val) {
+                        ret_val = LLVMBuildLoad2(builder, ret_ty, ret_val, b"result\0".as_ptr() as *const i8);
+                    } else if LLVMTypeOf(ret_val) != ret_ty && LLVMGetTypeKind(LLVMTypeOf(ret_val)) == int && LLVMGetTypeKind(ret_ty) == int {
+                        // an int literal returned as a long
+                        ret_val = LLVMBuildIntCast2(builder, ret_val, ret_ty, 1, b"result\0".as_ptr() as *const i8);
+                    }
                     LLVMBuildRet(builder, ret_val);
                 } else {
                     LLVMBuildRetVoid(builder);
@@ -231,6 +803,19 @@ pub fn translate_control_flow(
                 }
                 true // is terminator
             }
+            Instruction::Switch { value, arms, default } => {
+                let val = operand_to_llvm_value(context, value, local_map);
+                if let Some(default_bb) = bb_map.get(default) {
+                    let switch = LLVMBuildSwitch(builder, val, *default_bb, arms.len() as u32);
+                    for (case, target) in arms {
+                        if let Some(target_bb) = bb_map.get(target) {
+                            // cases take the value's width, eg a char or an enum's i32 tag
+                            LLVMAddCase(switch, LLVMConstInt(LLVMTypeOf(val), *case as u64, 1), *target_bb);
+                        }
+                    }
+                }
+                true // is terminator
+            }
             _ => false,
         }
     }
@@ -254,8 +839,10 @@ fn get_dest_local(inst: &Instruction) -> Option<&Local> {
         Instruction::Or { dest, .. } |
         Instruction::Not { dest, .. } |
         Instruction::Load { dest, .. } |
+        Instruction::VolatileLoad { dest, .. } |
         Instruction::Alloca { dest, .. } |
         Instruction::Gep { dest, .. } |
+        Instruction::PtrOffset { dest, .. } |
         Instruction::Call { dest: Some(dest), .. } |
         Instruction::Phi { dest, .. } |
         Instruction::Copy { dest, .. } => Some(dest),
@@ -0,0 +1,177 @@
+use crate::backend::llvm::context::initialize_llvm;
+use crate::backend::ports::codegen::Module;
+use crate::backend::ports::execution::{EntryValue, ExecutionEngine, ExecutionError};
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use llvm_sys::bit_reader::LLVMParseBitcodeInContext2;
+use llvm_sys::core::*;
+use llvm_sys::error::*;
+use llvm_sys::orc2::lljit::*;
+use llvm_sys::orc2::*;
+use llvm_sys::prelude::*;
+use std::ffi::{CStr, CString};
+
+/// in process execution on llvm's orc LLJIT, `emerald run` uses this instead of emitting a binary
+/// libc + the rest of the process r visible 2 jitted code so foreign fns resolve like they wld when linked
+pub struct LlvmJit {
+    jit: LLVMOrcLLJITRef,
+}
+
+impl LlvmJit {
+    pub fn new() -> Result<Self, ExecutionError> {
+        initialize_llvm();
+        unsafe {
+            let mut jit = std::ptr::null_mut();
+            check(LLVMOrcCreateLLJIT(&mut jit, std::ptr::null_mut())).map_err(ExecutionError::SetupFailed)?;
+            let jit = Self { jit };
+            let mut generator = std::ptr::null_mut();
+            let prefix = LLVMOrcLLJITGetGlobalPrefix(jit.jit);
+            check(LLVMOrcCreateDynamicLibrarySearchGeneratorForProcess(&mut generator, prefix, None, std::ptr::null_mut()))
+                .map_err(ExecutionError::SetupFailed)?;
+            LLVMOrcJITDylibAddGenerator(LLVMOrcLLJITGetMainJITDylib(jit.jit), generator);
+            Ok(jit)
+        }
+    }
+
+    /// address of a jitted (or process) symbol, compiles whatever it needs on first lookup
+    pub fn lookup(&mut self, name: &str) -> Result<u64, ExecutionError> {
+        let c_name = CString::new(name).map_err(|_| ExecutionError::SymbolNotFound(name.to_string()))?;
+        let mut address = 0;
+        unsafe { check(LLVMOrcLLJITLookup(self.jit, &mut address, c_name.as_ptr())) }
+            .map_err(|e| ExecutionError::SymbolNotFound(format!("{}: {}", name, e)))?;
+        Ok(address)
+    }
+
+    /// re reads the module in2 a context the jit owns, the codegen's context can go away b4 the code runs.
+    /// returns whether it defines a `main` that returns a value
+    unsafe fn add(&mut self, module: &Module) -> Result<bool, ExecutionError> {
+        let buffer = bitcode_buffer(module)?;
+        let context = LLVMOrcCreateNewThreadSafeContext();
+        let mut llvm_module = std::ptr::null_mut();
+        let failed = LLVMParseBitcodeInContext2(LLVMOrcThreadSafeContextGetContext(context), buffer, &mut llvm_module);
+        LLVMDisposeMemoryBuffer(buffer);
+        if failed != 0 {
+            LLVMOrcDisposeThreadSafeContext(context);
+            return Err(ExecutionError::InvalidModule(format!("'{}' is not valid LLVM bitcode", module.name)));
+        }
+        let main = LLVMGetNamedFunction(llvm_module, b"main\0".as_ptr() as *const i8);
+        let returns = !main.is_null()
+            && LLVMGetTypeKind(LLVMGetReturnType(LLVMGlobalGetValueType(main))) != llvm_sys::LLVMTypeKind::LLVMVoidTypeKind;
+
+        // the module holds its own ref on the context
+        let thread_safe = LLVMOrcCreateNewThreadSafeModule(llvm_module, context);
+        LLVMOrcDisposeThreadSafeContext(context);
+        if let Err(e) = check(LLVMOrcLLJITAddLLVMIRModule(self.jit, LLVMOrcLLJITGetMainJITDylib(self.jit), thread_safe)) {
+            LLVMOrcDisposeThreadSafeModule(thread_safe);
+            return Err(ExecutionError::InvalidModule(e));
+        }
+        Ok(returns)
+    }
+}
+
+impl ExecutionEngine for LlvmJit {
+    fn add_module(&mut self, module: &Module) -> Result<(), ExecutionError> {
+        unsafe { self.add(module) }.map(|_| ())
+    }
+
+    fn run_main(&mut self, module: &Module) -> Result<i32, ExecutionError> {
+        let returns = unsafe { self.add(module) }?;
+        let address = self.lookup("main")?;
+        // `int` is i32, a smaller int result comes back in the low bits of the same register
+        unsafe {
+            if returns {
+                let main: extern "C" fn() -> i32 = std::mem::transmute(address as usize);
+                Ok(main())
+            } else {
+                let main: extern "C" fn() = std::mem::transmute(address as usize);
+                main();
+                Ok(0)
+            }
+        }
+    }
+
+    fn call_entry(&mut self, module: &Module, name: &str, returns: &Type) -> Result<EntryValue, ExecutionError> {
+        unsafe { self.add(module) }?;
+        let address = self.lookup(name)? as usize;
+        // called w/ the C signature the codegen gave the fn's return type, see `mir_type_to_llvm_type`
+        unsafe {
+            Ok(match returns {
+                Type::Primitive(PrimitiveType::Void) => {
+                    std::mem::transmute::<usize, extern "C" fn()>(address)();
+                    EntryValue::Unit
+                }
+                Type::Primitive(PrimitiveType::Bool) => {
+                    EntryValue::Bool(std::mem::transmute::<usize, extern "C" fn() -> u8>(address)() & 1 != 0)
+                }
+                Type::Primitive(PrimitiveType::Float) => {
+                    EntryValue::Float(std::mem::transmute::<usize, extern "C" fn() -> f64>(address)())
+                }
+                Type::Primitive(PrimitiveType::Char) => {
+                    let code = std::mem::transmute::<usize, extern "C" fn() -> u32>(address)();
+                    EntryValue::Char(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+                }
+                Type::Primitive(p) if p.is_integer() => {
+                    let value = match (p.size_in_bytes(), p.is_unsigned()) {
+                        (1, false) => std::mem::transmute::<usize, extern "C" fn() -> i8>(address)() as i64,
+                        (1, true) => std::mem::transmute::<usize, extern "C" fn() -> u8>(address)() as i64,
+                        (4, false) => std::mem::transmute::<usize, extern "C" fn() -> i32>(address)() as i64,
+                        (4, true) => std::mem::transmute::<usize, extern "C" fn() -> u32>(address)() as i64,
+                        _ => std::mem::transmute::<usize, extern "C" fn() -> i64>(address)(),
+                    };
+                    EntryValue::Int(value)
+                }
+                Type::String => {
+                    let s = std::mem::transmute::<usize, extern "C" fn() -> RawStr>(address)();
+                    let bytes = if s.data.is_null() { &[][..] } else { std::slice::from_raw_parts(s.data, s.len as usize) };
+                    EntryValue::Str(String::from_utf8_lossy(bytes).into_owned())
+                }
+                _ => return Err(ExecutionError::InvalidModule(format!("'{}' does not return a primitive or string", name))),
+            })
+        }
+    }
+}
+
+/// the `str` struct a string is returned as
+#[repr(C)]
+struct RawStr {
+    data: *const u8,
+    len: i64,
+}
+
+impl Drop for LlvmJit {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.jit.is_null() {
+                let _ = check(LLVMOrcDisposeLLJIT(self.jit));
+            }
+        }
+    }
+}
+
+/// the module as a bitcode buffer, however the codegen handed it over
+unsafe fn bitcode_buffer(module: &Module) -> Result<LLVMMemoryBufferRef, ExecutionError> {
+    let data = module.data.as_ref();
+    #[cfg(feature = "inkwell")]
+    if let Some(bitcode) = data.and_then(|d| d.downcast_ref::<crate::backend::llvm::safe::BitcodeModule>()) {
+        return Ok(LLVMCreateMemoryBufferWithMemoryRangeCopy(
+            bitcode.bitcode.as_ptr() as *const i8,
+            bitcode.bitcode.len(),
+            b"emerald_module\0".as_ptr() as *const i8,
+        ));
+    }
+    match data.and_then(|d| d.downcast_ref::<crate::backend::llvm::codegen::LlvmModuleWrapper>()) {
+        Some(wrapper) => Ok(llvm_sys::bit_writer::LLVMWriteBitcodeToMemoryBuffer(wrapper.get())),
+        None => Err(ExecutionError::InvalidModule(format!("'{}' does not contain an LLVM module", module.name))),
+    }
+}
+
+/// an LLVMErrorRef as a message, consuming it
+unsafe fn check(error: LLVMErrorRef) -> Result<(), String> {
+    if error.is_null() {
+        return Ok(());
+    }
+    let message = LLVMGetErrorMessage(error);
+    let text = CStr::from_ptr(message).to_string_lossy().to_string();
+    LLVMDisposeErrorMessage(message);
+    Err(text)
+}
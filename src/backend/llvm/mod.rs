@@ -5,6 +5,13 @@ pub mod emitter;
 pub mod types;
 pub mod instructions;
 pub mod context;
+pub mod debuginfo;
+pub mod ffi_compat;
+pub mod jit;
+pub mod runtime;
+#[cfg(feature = "inkwell")]
+pub mod safe;
+pub mod target;
 
 // Export specific types to avoid ambiguous re-exports
 pub use factory::LlvmBackendFactory;
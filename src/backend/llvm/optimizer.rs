@@ -1,19 +1,60 @@
-use crate::backend::ports::optimizer::{Optimizer, OptimizationError};
+use crate::backend::llvm::target::{module_triple, target_machine};
+use crate::backend::ports::optimizer::{Optimizer, OptimizationError, OptimizationPass};
 use crate::backend::ports::codegen::Module;
 use crate::backend::ports::codegen::OptimizationLevel;
-use llvm_sys::core::*;
+use llvm_sys::error::*;
+use llvm_sys::target_machine::*;
+use llvm_sys::transforms::pass_builder::*;
+use std::ffi::{CStr, CString};
 
-/// LLVM optimizer - applies LLVM optimization passes
+/// LLVM optimizer - runs the new pass manager's default pipeline 4 the opt level
 pub struct LlvmOptimizer {
     opt_level: OptimizationLevel,
+    /// added w/ add_pass, run after the default pipeline
+    passes: Vec<OptimizationPass>,
 }
 
 impl LlvmOptimizer {
     pub fn new() -> Self {
         Self {
             opt_level: OptimizationLevel::Default,
+            passes: Vec::new(),
         }
     }
+
+    /// `default<On>` pipeline, the module level ones (O1+) include the inliner, gvn etc
+    pub fn pass_pipeline(level: OptimizationLevel) -> &'static str {
+        match level {
+            OptimizationLevel::None => "default<O0>",
+            OptimizationLevel::Basic => "default<O1>",
+            OptimizationLevel::Default => "default<O2>",
+            OptimizationLevel::Aggressive => "default<O3>",
+            OptimizationLevel::Size => "default<Os>",
+            OptimizationLevel::SizePerformance => "default<Oz>",
+        }
+    }
+
+    /// new pm name of a pass, function/cgscc passes get wrapped in their adaptor by the parser
+    pub fn pass_name(pass: &OptimizationPass) -> &str {
+        match pass {
+            OptimizationPass::Inline => "inline",
+            OptimizationPass::Mem2Reg => "mem2reg",
+            OptimizationPass::DeadCodeElimination => "dce",
+            OptimizationPass::LoopUnroll => "loop-unroll",
+            OptimizationPass::Sccp => "sccp",
+            OptimizationPass::Custom(pipeline) => pipeline,
+        }
+    }
+
+    /// `default<On>` followed by the added passes in order
+    pub fn pipeline(level: OptimizationLevel, passes: &[OptimizationPass]) -> String {
+        let mut pipeline = Self::pass_pipeline(level).to_string();
+        for pass in passes {
+            pipeline.push(',');
+            pipeline.push_str(Self::pass_name(pass));
+        }
+        pipeline
+    }
 }
 
 impl Optimizer for LlvmOptimizer {
@@ -28,30 +69,32 @@ impl Optimizer for LlvmOptimizer {
                     "Module does not contain LLVM module".to_string()
                 ))?;
 
-            // create function pass manager
-            let fpm = LLVMCreateFunctionPassManagerForModule(llvm_module);
-            
-            // Note: In LLVM 21, the pass manager builder API may have changed
-            // For now, we'll use a simplified approach - just initialize and run
-            // TODO: Add proper optimization passes when API is available
-            LLVMInitializeFunctionPassManager(fpm);
-            
-            // run passes on all functions
-            let mut func = LLVMGetFirstFunction(llvm_module);
-            while !func.is_null() {
-                LLVMRunFunctionPassManager(fpm, func);
-                func = LLVMGetNextFunction(func);
-            }
-            
-            LLVMFinalizeFunctionPassManager(fpm);
-            LLVMDisposePassManager(fpm);
+            // the target machine gives the passes the module's cost model + legal types
+            let machine = target_machine(&module_triple(llvm_module), LLVMRelocMode::LLVMRelocDefault)
+                .map_err(OptimizationError::OptimizationFailed)?;
+            let pipeline = CString::new(Self::pipeline(self.opt_level, &self.passes))
+                .map_err(|e| OptimizationError::InvalidPass(e.to_string()))?;
+            let options = LLVMCreatePassBuilderOptions();
+            let error = LLVMRunPasses(llvm_module, pipeline.as_ptr(), machine, options);
+            LLVMDisposePassBuilderOptions(options);
+            LLVMDisposeTargetMachine(machine);
 
+            if !error.is_null() {
+                let msg = LLVMGetErrorMessage(error);
+                let text = CStr::from_ptr(msg).to_string_lossy().to_string();
+                LLVMDisposeErrorMessage(msg);
+                return Err(OptimizationError::InvalidPass(text));
+            }
             Ok(())
         }
     }
 
-    fn add_pass(&mut self, _pass: crate::backend::ports::optimizer::OptimizationPass) {
-        // custom passes can be added here if needed
+    fn add_pass(&mut self, pass: OptimizationPass) {
+        self.passes.push(pass);
+    }
+
+    fn set_optimization_level(&mut self, level: OptimizationLevel) {
+        self.opt_level = level;
     }
 }
 
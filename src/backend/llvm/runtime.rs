@@ -0,0 +1,171 @@
+use crate::core::mir::{ALLOC_FN, FREE_FN, PANIC_FN};
+use crate::core::target::TargetInfo;
+use llvm_sys::core::*;
+use llvm_sys::prelude::*;
+use llvm_sys::LLVMTypeKind;
+use std::ffi::CString;
+
+// the runtime support a program needs, defined in evry module that calls it. weak_odr like
+// `emerald_panic_strategy` so each object can carry a copy + the linker keeps 1, theres no
+// library 2 find at link time + the jit gets it the same way
+
+/// gives `emerald_panic(msg : string, file : string, line : int)` a body if `module` calls it
+/// w/o defining it: `panicked at file:line: msg` on stderr, then abort. pieced 2gether w/ write
+/// like the cranelift copy, msvc's crt has no dprintf + its printfs r inline in its headers
+pub(crate) unsafe fn define_panic(module: LLVMModuleRef, context: LLVMContextRef, target: &TargetInfo) {
+    let name = CString::new(PANIC_FN).unwrap();
+    let func = LLVMGetNamedFunction(module, name.as_ptr());
+    if func.is_null() || LLVMCountBasicBlocks(func) > 0 || LLVMCountParams(func) != 3 {
+        return;
+    }
+    let (message, file, line) = (LLVMGetParam(func, 0), LLVMGetParam(func, 1), LLVMGetParam(func, 2));
+    let is_struct = |v| LLVMGetTypeKind(LLVMTypeOf(v)) == LLVMTypeKind::LLVMStructTypeKind;
+    if !is_struct(message) || !is_struct(file) || LLVMGetTypeKind(LLVMTypeOf(line)) != LLVMTypeKind::LLVMIntegerTypeKind {
+        return;
+    }
+    LLVMSetLinkage(func, llvm_sys::LLVMLinkage::LLVMWeakODRLinkage);
+    for attribute in ["noreturn", "cold", "nounwind"] {
+        let kind = LLVMGetEnumAttributeKindForName(attribute.as_ptr() as *const i8, attribute.len());
+        LLVMAddAttributeAtIndex(func, llvm_sys::LLVMAttributeFunctionIndex, LLVMCreateEnumAttribute(context, kind, 0));
+    }
+
+    // value names, each lives 2 the end of the statement its made in
+    let label = |text: &str| CString::new(text).unwrap();
+    let builder = LLVMCreateBuilderInContext(context);
+    let entry = LLVMAppendBasicBlockInContext(context, func, label("entry").as_ptr());
+    LLVMPositionBuilderAtEnd(builder, entry);
+    let i8_type = LLVMInt8TypeInContext(context);
+    let i32_type = LLVMInt32TypeInContext(context);
+    let i64_type = LLVMInt64TypeInContext(context);
+    let ptr_type = LLVMPointerType(i8_type, 0);
+
+    // write(fd, buf, size_t), windows only has _write(fd, buf, unsigned) w/o oldnames.lib
+    let windows = target.os == "windows";
+    let count_type = if windows { i32_type } else { LLVMIntTypeInContext(context, target.pointer_width) };
+    let mut write_params = [i32_type, ptr_type, count_type];
+    let write_type = LLVMFunctionType(count_type, write_params.as_mut_ptr(), 3, 0);
+    let write = declare(module, if windows { "_write" } else { "write" }, write_type);
+    let put = |data: LLVMValueRef, len: LLVMValueRef| {
+        let len = LLVMBuildIntCast2(builder, len, count_type, 0, label("len").as_ptr());
+        let mut args = [LLVMConstInt(i32_type, 2, 0), LLVMBuildBitCast(builder, data, ptr_type, label("").as_ptr()), len];
+        LLVMBuildCall2(builder, write_type, write, args.as_mut_ptr(), 3, label("").as_ptr());
+    };
+    // a string is { data, len }, it needs no nul
+    let put_str = |string: LLVMValueRef| {
+        let data = LLVMBuildExtractValue(builder, string, 0, label("data").as_ptr());
+        put(data, LLVMBuildExtractValue(builder, string, 1, label("len").as_ptr()));
+    };
+    let put_text = |text: &str| {
+        let bytes = CString::new(text).unwrap();
+        let global = LLVMBuildGlobalStringPtr(builder, bytes.as_ptr(), label("panic.text").as_ptr());
+        put(global, LLVMConstInt(i64_type, text.len() as u64, 0));
+    };
+
+    put_text("panicked at ");
+    put_str(file);
+    put_text(":");
+
+    // line digits written backwards frm the end of a slot, 20 fit any u64
+    let slot_type = LLVMArrayType(i8_type, 24);
+    let slot = LLVMBuildAlloca(builder, slot_type, label("digits").as_ptr());
+    let mut indices = [LLVMConstInt(i32_type, 0, 0), LLVMConstInt(i32_type, 24, 0)];
+    let end = LLVMBuildInBoundsGEP2(builder, slot_type, slot, indices.as_mut_ptr(), 2, label("end").as_ptr());
+    let line = LLVMBuildIntCast2(builder, line, i64_type, 0, label("line").as_ptr());
+    let from = LLVMGetInsertBlock(builder);
+    let digit = LLVMAppendBasicBlockInContext(context, func, label("digit").as_ptr());
+    let done = LLVMAppendBasicBlockInContext(context, func, label("done").as_ptr());
+    LLVMBuildBr(builder, digit);
+
+    LLVMPositionBuilderAtEnd(builder, digit);
+    let n = LLVMBuildPhi(builder, i64_type, label("n").as_ptr());
+    let at = LLVMBuildPhi(builder, ptr_type, label("at").as_ptr());
+    let mut back = [LLVMConstInt(i64_type, u64::MAX, 1)];
+    let next = LLVMBuildInBoundsGEP2(builder, i8_type, at, back.as_mut_ptr(), 1, label("next").as_ptr());
+    let ten = LLVMConstInt(i64_type, 10, 0);
+    let rem = LLVMBuildURem(builder, n, ten, label("rem").as_ptr());
+    let ascii = LLVMBuildAdd(builder, rem, LLVMConstInt(i64_type, b'0' as u64, 0), label("ascii").as_ptr());
+    LLVMBuildStore(builder, LLVMBuildTrunc(builder, ascii, i8_type, label("ascii").as_ptr()), next);
+    let rest = LLVMBuildUDiv(builder, n, ten, label("rest").as_ptr());
+    let more = LLVMBuildICmp(builder, llvm_sys::LLVMIntPredicate::LLVMIntNE, rest, LLVMConstInt(i64_type, 0, 0), label("more").as_ptr());
+    LLVMBuildCondBr(builder, more, digit, done);
+    let (mut n_values, mut n_blocks) = ([line, rest], [from, digit]);
+    LLVMAddIncoming(n, n_values.as_mut_ptr(), n_blocks.as_mut_ptr(), 2);
+    let (mut at_values, mut at_blocks) = ([end, next], [from, digit]);
+    LLVMAddIncoming(at, at_values.as_mut_ptr(), at_blocks.as_mut_ptr(), 2);
+
+    LLVMPositionBuilderAtEnd(builder, done);
+    let address = |p: LLVMValueRef| LLVMBuildPtrToInt(builder, p, i64_type, label("address").as_ptr());
+    put(next, LLVMBuildSub(builder, address(end), address(next), label("len").as_ptr()));
+    put_text(": ");
+    put_str(message);
+    put_text("\n");
+
+    let abort_type = LLVMFunctionType(LLVMVoidTypeInContext(context), std::ptr::null_mut(), 0, 0);
+    let abort = declare(module, "abort", abort_type);
+    LLVMBuildCall2(builder, abort_type, abort, std::ptr::null_mut(), 0, label("").as_ptr());
+    LLVMBuildUnreachable(builder);
+    LLVMDisposeBuilder(builder);
+}
+
+/// gives `emerald_alloc(size)` + `emerald_free(p)` bodies on malloc + free if `module` calls
+/// them w/o defining them. weak not weak_odr: another definition, eg a custom allocator in an
+/// object linked in, replaces these + no call is inlined past it
+pub(crate) unsafe fn define_allocator(module: LLVMModuleRef, context: LLVMContextRef) {
+    let ptr_type = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+    let size_type = LLVMInt64TypeInContext(context);
+    let builder = LLVMCreateBuilderInContext(context);
+
+    if let Some(alloc) = undefined(module, ALLOC_FN, 1) {
+        let size = LLVMGetParam(alloc, 0);
+        if LLVMGetTypeKind(LLVMTypeOf(size)) == LLVMTypeKind::LLVMIntegerTypeKind {
+            LLVMSetLinkage(alloc, llvm_sys::LLVMLinkage::LLVMWeakAnyLinkage);
+            let entry = LLVMAppendBasicBlockInContext(context, alloc, b"entry\0".as_ptr() as *const i8);
+            LLVMPositionBuilderAtEnd(builder, entry);
+            let mut malloc_params = [size_type];
+            let malloc = declare(module, "malloc", LLVMFunctionType(ptr_type, malloc_params.as_mut_ptr(), 1, 0));
+            let malloc_type = LLVMGlobalGetValueType(malloc);
+            let mut params = [std::ptr::null_mut()];
+            LLVMGetParamTypes(malloc_type, params.as_mut_ptr());
+            let mut args = [LLVMBuildIntCast2(builder, size, params[0], 0, b"size\0".as_ptr() as *const i8)];
+            let memory = LLVMBuildCall2(builder, malloc_type, malloc, args.as_mut_ptr(), 1, b"memory\0".as_ptr() as *const i8);
+            let ret = LLVMGetReturnType(LLVMGlobalGetValueType(alloc));
+            LLVMBuildRet(builder, LLVMBuildBitCast(builder, memory, ret, b"memory\0".as_ptr() as *const i8));
+        }
+    }
+
+    if let Some(free) = undefined(module, FREE_FN, 1) {
+        let pointer = LLVMGetParam(free, 0);
+        if LLVMGetTypeKind(LLVMTypeOf(pointer)) == LLVMTypeKind::LLVMPointerTypeKind {
+            LLVMSetLinkage(free, llvm_sys::LLVMLinkage::LLVMWeakAnyLinkage);
+            let entry = LLVMAppendBasicBlockInContext(context, free, b"entry\0".as_ptr() as *const i8);
+            LLVMPositionBuilderAtEnd(builder, entry);
+            let mut libc_params = [ptr_type];
+            let libc_free = declare(module, "free", LLVMFunctionType(LLVMVoidTypeInContext(context), libc_params.as_mut_ptr(), 1, 0));
+            let libc_type = LLVMGlobalGetValueType(libc_free);
+            let mut params = [std::ptr::null_mut()];
+            LLVMGetParamTypes(libc_type, params.as_mut_ptr());
+            let mut args = [LLVMBuildBitCast(builder, pointer, params[0], b"p\0".as_ptr() as *const i8)];
+            LLVMBuildCall2(builder, libc_type, libc_free, args.as_mut_ptr(), 1, b"\0".as_ptr() as *const i8);
+            LLVMBuildRetVoid(builder);
+        }
+    }
+    LLVMDisposeBuilder(builder);
+}
+
+/// `name` if `module` calls it w/ `params` args but has no body 4 it
+unsafe fn undefined(module: LLVMModuleRef, name: &str, params: u32) -> Option<LLVMValueRef> {
+    let name = CString::new(name).unwrap();
+    let func = LLVMGetNamedFunction(module, name.as_ptr());
+    (!func.is_null() && LLVMCountBasicBlocks(func) == 0 && LLVMCountParams(func) == params).then_some(func)
+}
+
+/// `name` as the module already declares it, or declared as `ty`
+unsafe fn declare(module: LLVMModuleRef, name: &str, ty: LLVMTypeRef) -> LLVMValueRef {
+    let name = CString::new(name).unwrap();
+    let existing = LLVMGetNamedFunction(module, name.as_ptr());
+    if existing.is_null() {
+        LLVMAddFunction(module, name.as_ptr(), ty)
+    } else {
+        existing
+    }
+}
@@ -0,0 +1,310 @@
+use crate::backend::link::{self, LinkOptions, LinkerFlavor};
+use crate::backend::llvm::emitter::{embed_bitcode, LINKED_ATTR};
+use crate::backend::llvm::optimizer::LlvmOptimizer;
+use crate::backend::llvm::target::DEFAULT_WASM_TRIPLE;
+use crate::backend::ports::codegen::{Module, OptimizationLevel};
+use crate::backend::ports::emitter::{EmitError, Emitter};
+use crate::backend::ports::optimizer::{OptimizationError, OptimizationPass, Optimizer};
+use crate::backend::stack_usage::FrameInfo;
+use crate::core::target::TargetInfo;
+use inkwell::context::Context;
+use inkwell::memory_buffer::MemoryBuffer;
+use inkwell::module::{Linkage, Module as InkwellModule};
+use inkwell::passes::PassBuilderOptions;
+use inkwell::targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple};
+use inkwell::values::{BasicValueEnum, InstructionOpcode};
+use inkwell::attributes::AttributeLoc;
+use inkwell::{DLLStorageClass, GlobalVisibility};
+use std::fs;
+use std::path::Path;
+
+/// llvm module handed from codegen 2 the optimizer/emitter as bitcode
+/// each stage parses it into a context it owns, so nothing outlives the context it came from
+pub(crate) struct BitcodeModule {
+    pub bitcode: Vec<u8>,
+}
+
+fn bitcode(module: &Module) -> Result<&BitcodeModule, String> {
+    module.data.as_ref()
+        .and_then(|d| d.downcast_ref::<BitcodeModule>())
+        .ok_or_else(|| "Module does not contain LLVM bitcode".to_string())
+}
+
+fn parse<'ctx>(data: &BitcodeModule, context: &'ctx Context) -> Result<InkwellModule<'ctx>, String> {
+    let buffer = MemoryBuffer::create_from_memory_range_copy(&data.bitcode, "emerald_module");
+    InkwellModule::parse_bitcode_from_buffer(&buffer, context).map_err(|e| e.to_string())
+}
+
+/// machine 4 the module's triple, cpu "generic" 4 wasm like target::target_machine
+fn target_machine(triple: &TargetTriple, reloc: RelocMode) -> Result<TargetMachine, String> {
+    Target::initialize_all(&InitializationConfig::default());
+    let name = triple.as_str().to_string_lossy().to_string();
+    let target = Target::from_triple(triple).map_err(|e| format!("'{}': {}", name, e))?;
+    let cpu = if TargetInfo::from_triple(&name).is_wasm() { "generic" } else { "" };
+    target
+        .create_target_machine(triple, cpu, "", inkwell::OptimizationLevel::Default, reloc, CodeModel::Default)
+        .ok_or_else(|| format!("'{}': cannot create a target machine", name))
+}
+
+fn triple_name(module: &InkwellModule) -> String {
+    let triple = module.get_triple().as_str().to_string_lossy().to_string();
+    if triple.is_empty() { TargetInfo::host().triple } else { triple }
+}
+
+fn write(module: &InkwellModule, reloc: RelocMode, file_type: FileType, output: &Path) -> Result<(), EmitError> {
+    let machine = target_machine(&module.get_triple(), reloc).map_err(EmitError::EmissionFailed)?;
+    machine
+        .write_to_file(module, file_type, output)
+        .map_err(|e| EmitError::EmissionFailed(e.to_string()))
+}
+
+/// `LlvmOptimizer` on inkwell, same pipelines
+pub struct SafeLlvmOptimizer {
+    opt_level: OptimizationLevel,
+    passes: Vec<OptimizationPass>,
+}
+
+impl SafeLlvmOptimizer {
+    pub fn new() -> Self {
+        Self {
+            opt_level: OptimizationLevel::Default,
+            passes: Vec::new(),
+        }
+    }
+}
+
+impl Default for SafeLlvmOptimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Optimizer for SafeLlvmOptimizer {
+    fn optimize(&mut self, module: &mut Module) -> Result<(), OptimizationError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module)
+            .and_then(|data| parse(data, &context))
+            .map_err(OptimizationError::OptimizationFailed)?;
+        let machine = target_machine(&llvm_module.get_triple(), RelocMode::Default)
+            .map_err(OptimizationError::OptimizationFailed)?;
+        llvm_module
+            .run_passes(&LlvmOptimizer::pipeline(self.opt_level, &self.passes), &machine, PassBuilderOptions::create())
+            .map_err(|e| OptimizationError::InvalidPass(e.to_string()))?;
+
+        let optimized = llvm_module.write_bitcode_to_memory().as_slice().to_vec();
+        module.data = Some(Box::new(BitcodeModule { bitcode: optimized }));
+        Ok(())
+    }
+
+    fn add_pass(&mut self, pass: OptimizationPass) {
+        self.passes.push(pass);
+    }
+
+    fn set_optimization_level(&mut self, level: OptimizationLevel) {
+        self.opt_level = level;
+    }
+}
+
+/// `LlvmEmitter` on inkwell
+pub struct SafeLlvmEmitter {
+    link_options: LinkOptions,
+    embed_bitcode: bool,
+}
+
+impl SafeLlvmEmitter {
+    pub fn new() -> Self {
+        Self {
+            link_options: LinkOptions::default(),
+            embed_bitcode: false,
+        }
+    }
+
+    /// dll storage / visibility + embedded bitcode 4 objects that get linked, see LlvmEmitter::prepare_object
+    fn prepare_object(&self, module: &InkwellModule, shared: bool) {
+        let triple = triple_name(module);
+        let windows = TargetInfo::from_triple(&triple).os == "windows";
+        for function in module.get_functions() {
+            let global = function.as_global_value();
+            let name = function.get_name().to_string_lossy();
+            if function.count_basic_blocks() == 0 {
+                let linked = function.get_string_attribute(AttributeLoc::Function, LINKED_ATTR).is_some();
+                if windows && !name.starts_with("llvm.") && !linked {
+                    global.set_dll_storage_class(DLLStorageClass::Import);
+                }
+            } else if function.get_linkage() == Linkage::External {
+                let exported = self.link_options.exports_symbol(&name);
+                if windows && shared && exported {
+                    global.set_dll_storage_class(DLLStorageClass::Export);
+                } else if !windows && !exported {
+                    global.set_visibility(GlobalVisibility::Hidden);
+                }
+            }
+        }
+        if self.embed_bitcode {
+            // inkwell has no const string from raw bytes w/o a trailing nul, reuse the llvm-sys version
+            unsafe { embed_bitcode(module.as_mut_ptr(), &triple) }
+        }
+    }
+
+    fn object_path(&self, output: &Path) -> std::path::PathBuf {
+        output.with_extension(if self.link_options.flavor == LinkerFlavor::Msvc { "obj" } else { "o" })
+    }
+}
+
+impl Default for SafeLlvmEmitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Emitter for SafeLlvmEmitter {
+    fn emit_binary(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        if TargetInfo::from_triple(&triple_name(&llvm_module)).is_wasm() {
+            return self.emit_wasm(module, output);
+        }
+        self.prepare_object(&llvm_module, self.link_options.shared);
+
+        let obj_path = self.object_path(output);
+        // pic since the linker driver produces pie executables by default
+        write(&llvm_module, RelocMode::PIC, FileType::Object, &obj_path)?;
+        let linked = link::link(&[obj_path.as_path()], output, &self.link_options);
+        if !self.link_options.keeps_objects() {
+            let _ = fs::remove_file(&obj_path);
+        }
+        linked
+    }
+
+    fn emit_assembly(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        write(&llvm_module, RelocMode::Default, FileType::Assembly, output)
+    }
+
+    fn emit_llvm_ir(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        fs::write(output, llvm_module.print_to_string().to_string())?;
+        Ok(())
+    }
+
+    /// the module already is bitcode
+    fn emit_bitcode(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        fs::write(output, &bitcode(module).map_err(EmitError::EmissionFailed)?.bitcode)?;
+        Ok(())
+    }
+
+    fn emit_object(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        self.prepare_object(&llvm_module, self.link_options.shared);
+        write(&llvm_module, RelocMode::Default, FileType::Object, output)?;
+        link::compress_debug_sections(output, &self.link_options)
+    }
+
+    fn emit_static_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        self.prepare_object(&llvm_module, false);
+        let obj_path = self.object_path(output);
+        write(&llvm_module, RelocMode::PIC, FileType::Object, &obj_path)?;
+        let archived = link::archive(&[obj_path.as_path()], output, &self.link_options);
+        let _ = fs::remove_file(&obj_path);
+        archived
+    }
+
+    fn emit_shared_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        self.prepare_object(&llvm_module, true);
+        let obj_path = self.object_path(output);
+        write(&llvm_module, RelocMode::PIC, FileType::Object, &obj_path)?;
+        let options = self.link_options.clone().with_shared(true);
+        let linked = link::link(&[obj_path.as_path()], output, &options);
+        if !options.keeps_objects() {
+            let _ = fs::remove_file(&obj_path);
+        }
+        linked
+    }
+
+    fn emit_wasm(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        let mut triple = triple_name(&llvm_module);
+        if !TargetInfo::from_triple(&triple).is_wasm() {
+            // --emit wasm w/o a wasm --target, retarget the module
+            triple = DEFAULT_WASM_TRIPLE.to_string();
+            let wasm = TargetTriple::create(&triple);
+            let machine = target_machine(&wasm, RelocMode::Default).map_err(EmitError::EmissionFailed)?;
+            llvm_module.set_triple(&wasm);
+            llvm_module.set_data_layout(&machine.get_target_data().get_data_layout());
+        }
+
+        let obj_path = output.with_extension("o");
+        write(&llvm_module, RelocMode::Default, FileType::Object, &obj_path)?;
+        let options = self.link_options.clone().with_target(TargetInfo::from_triple(&triple));
+        let linked = link::link(&[obj_path.as_path()], output, &options);
+        let _ = fs::remove_file(&obj_path);
+        linked
+    }
+
+    fn stack_frames(&self, module: &Module) -> Result<Vec<FrameInfo>, EmitError> {
+        let context = Context::create();
+        let llvm_module = bitcode(module).and_then(|d| parse(d, &context)).map_err(EmitError::EmissionFailed)?;
+        let machine = target_machine(&llvm_module.get_triple(), RelocMode::Default).map_err(EmitError::EmissionFailed)?;
+        let target_data = machine.get_target_data();
+
+        let mut frames = Vec::new();
+        for function in llvm_module.get_functions().filter(|f| f.count_basic_blocks() > 0) {
+            let mut frame = FrameInfo {
+                name: function.get_name().to_string_lossy().to_string(),
+                frame_size: 0,
+                dynamic: false,
+                callees: Vec::new(),
+            };
+            for block in function.get_basic_block_iter() {
+                for inst in block.get_instructions() {
+                    match inst.get_opcode() {
+                        InstructionOpcode::Alloca => {
+                            let count = match inst.get_operand(0).and_then(|op| op.left()) {
+                                Some(BasicValueEnum::IntValue(count)) => count.get_zero_extended_constant(),
+                                _ => None,
+                            };
+                            match (count, inst.get_allocated_type()) {
+                                (Some(count), Ok(allocated)) if allocated.is_sized() => {
+                                    let size = target_data.get_abi_size(&allocated) * count;
+                                    let align = (inst.get_alignment().unwrap_or(1) as u64).max(1);
+                                    frame.frame_size = frame.frame_size.div_ceil(align) * align + size;
+                                }
+                                // runtime count or opaque type, no static size
+                                _ => frame.dynamic = true,
+                            }
+                        }
+                        InstructionOpcode::Call => {
+                            // callee is the last operand, only direct calls 2 fns in the module count
+                            let callee = inst.get_num_operands().checked_sub(1).and_then(|i| inst.get_operand(i));
+                            if let Some(BasicValueEnum::PointerValue(callee)) = callee.and_then(|op| op.left()) {
+                                let name = callee.get_name().to_string_lossy().to_string();
+                                if llvm_module.get_function(&name).is_some() && !frame.callees.contains(&name) {
+                                    frame.callees.push(name);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    fn set_link_options(&mut self, options: LinkOptions) {
+        self.link_options = options;
+    }
+
+    fn set_embed_bitcode(&mut self, embed: bool) {
+        self.embed_bitcode = embed;
+    }
+}
@@ -0,0 +1,92 @@
+use crate::backend::llvm::context::initialize_llvm;
+use crate::backend::ports::emitter::EmitError;
+use crate::core::target::TargetInfo;
+use llvm_sys::core::*;
+use llvm_sys::prelude::*;
+use llvm_sys::target::*;
+use llvm_sys::target_machine::*;
+use std::ffi::{CStr, CString};
+use std::path::Path;
+
+/// triple used when wasm output is asked 4 w/o a wasm --target
+pub const DEFAULT_WASM_TRIPLE: &str = "wasm32-unknown-unknown";
+
+/// target machine 4 `triple`, caller disposes it
+/// the error names the triple so a typo in --target is obvious
+pub(crate) unsafe fn target_machine(triple: &str, reloc: LLVMRelocMode) -> Result<LLVMTargetMachineRef, String> {
+    initialize_llvm();
+    let triple_cstr = CString::new(triple).map_err(|_| format!("'{}' contains a nul byte", triple))?;
+    let mut target: LLVMTargetRef = std::ptr::null_mut();
+    let mut error_msg = std::ptr::null_mut();
+    if LLVMGetTargetFromTriple(triple_cstr.as_ptr(), &mut target, &mut error_msg) != 0 || target.is_null() {
+        let error = if !error_msg.is_null() {
+            let msg = CStr::from_ptr(error_msg).to_string_lossy().to_string();
+            LLVMDisposeMessage(error_msg);
+            msg
+        } else {
+            "no LLVM target for it".to_string()
+        };
+        return Err(format!("'{}': {}", triple, error));
+    }
+
+    // wasm has no "host" cpu, generic is what clang picks
+    let cpu = if TargetInfo::from_triple(triple).is_wasm() { "generic" } else { "" };
+    let cpu_cstr = CString::new(cpu).unwrap();
+    let features_cstr = CString::new("").unwrap();
+    Ok(LLVMCreateTargetMachine(
+        target,
+        triple_cstr.as_ptr(),
+        cpu_cstr.as_ptr(),
+        features_cstr.as_ptr(),
+        LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+        reloc,
+        LLVMCodeModel::LLVMCodeModelDefault,
+    ))
+}
+
+/// stamp the module w/ the triple + its data layout so allocas/constants r sized 4 the target
+pub(crate) unsafe fn set_module_target(llvm_module: LLVMModuleRef, triple: &str) -> Result<(), String> {
+    let machine = target_machine(triple, LLVMRelocMode::LLVMRelocDefault)?;
+    let triple_cstr = CString::new(triple).unwrap();
+    LLVMSetTarget(llvm_module, triple_cstr.as_ptr());
+    let layout = LLVMCreateTargetDataLayout(machine);
+    LLVMSetModuleDataLayout(llvm_module, layout);
+    LLVMDisposeTargetData(layout);
+    LLVMDisposeTargetMachine(machine);
+    Ok(())
+}
+
+/// triple codegen stored on the module, host if it was never set
+pub(crate) unsafe fn module_triple(llvm_module: LLVMModuleRef) -> String {
+    let triple = CStr::from_ptr(LLVMGetTarget(llvm_module)).to_string_lossy().to_string();
+    if triple.is_empty() {
+        TargetInfo::host().triple
+    } else {
+        triple
+    }
+}
+
+/// run the module's target machine over it, writing an object or assembly file
+pub(crate) unsafe fn emit_to_file(
+    llvm_module: LLVMModuleRef,
+    reloc: LLVMRelocMode,
+    file_type: LLVMCodeGenFileType,
+    output: &Path,
+) -> Result<(), EmitError> {
+    let machine = target_machine(&module_triple(llvm_module), reloc).map_err(EmitError::EmissionFailed)?;
+    let output_cstr = CString::new(output.to_string_lossy().as_ref()).unwrap();
+    let mut error_msg = std::ptr::null_mut();
+    let failed = LLVMTargetMachineEmitToFile(machine, llvm_module, output_cstr.as_ptr(), file_type, &mut error_msg) != 0;
+    LLVMDisposeTargetMachine(machine);
+    if failed {
+        let error = if !error_msg.is_null() {
+            let msg = CStr::from_ptr(error_msg).to_string_lossy().to_string();
+            LLVMDisposeMessage(error_msg);
+            msg
+        } else {
+            "Unknown error".to_string()
+        };
+        return Err(EmitError::EmissionFailed(error));
+    }
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use crate::core::types::ty::Type;
+use crate::core::types::composite::{EnumType, StructType};
 use crate::core::types::primitive::PrimitiveType;
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
@@ -6,29 +7,30 @@ use llvm_sys::LLVMTypeKind;
 
 /// convert MIR type to LLVM type
 pub fn mir_type_to_llvm_type(context: LLVMContextRef, ty: &Type) -> LLVMTypeRef {
+    lower_type(context, ty, &mut Vec::new())
+}
+
+/// `defining` holds the structs whose body is being built so self referential
+/// fields (eg `next : ptr Node`) reuse the named type instead of recursing
+fn lower_type(context: LLVMContextRef, ty: &Type, defining: &mut Vec<String>) -> LLVMTypeRef {
     unsafe {
         match ty {
             Type::Primitive(p) => primitive_to_llvm_type(context, p),
             Type::Pointer(ptr) => {
-                let pointee = mir_type_to_llvm_type(context, &ptr.pointee);
+                let pointee = lower_type(context, &ptr.pointee, defining);
                 LLVMPointerType(pointee, 0) // addr space 0
             }
             Type::Array(arr) => {
-                let element = mir_type_to_llvm_type(context, &arr.element);
+                let element = lower_type(context, &arr.element, defining);
                 LLVMArrayType2(element, arr.size as u64)
             }
-            Type::Struct(s) => {
-                // create struct type - for now use opaque struct
-                // TODO: properly handle struct fields
-                let name = format!("struct.{}", s.name);
-                let name_cstr = std::ffi::CString::new(name).unwrap();
-                LLVMStructCreateNamed(context, name_cstr.as_ptr())
-            }
+            Type::Struct(s) => struct_to_llvm_type(context, s, defining),
+            Type::Enum(e) => enum_to_llvm_type(context, e),
             Type::Function(func) => {
-                let ret_type = mir_type_to_llvm_type(context, &func.return_type);
+                let ret_type = lower_type(context, &func.return_type, defining);
                 
                 let mut param_types: Vec<LLVMTypeRef> = func.params.iter()
-                    .map(|p| mir_type_to_llvm_type(context, p))
+                    .map(|p| lower_type(context, p, defining))
                     .collect();
                 
                 if param_types.is_empty() {
@@ -47,16 +49,10 @@ pub fn mir_type_to_llvm_type(context: LLVMContextRef, ty: &Type) -> LLVMTypeRef
                     )
                 }
             }
-            Type::String => {
-                // string is (ptr, len) - for now just use i8*
-                LLVMPointerType(LLVMInt8TypeInContext(context), 0)
-            }
-            Type::TraitObject(_) => {
-                // trait object is (data_ptr, vtable_ptr) - use i8* for now
-                LLVMPointerType(LLVMInt8TypeInContext(context), 0)
-            }
-            Type::Generic(_) => {
-                // generic types should be monomorphized before reaching backend
+            Type::String => string_type(context),
+            Type::TraitObject(_) => trait_object_type(context),
+            Type::Generic(_) | Type::Error => {
+                // generic types should be monomorphized before reaching backend, errors stop b4 it
                 // use i8* as fallback
                 LLVMPointerType(LLVMInt8TypeInContext(context), 0)
             }
@@ -64,14 +60,135 @@ pub fn mir_type_to_llvm_type(context: LLVMContextRef, ty: &Type) -> LLVMTypeRef
     }
 }
 
+/// `str = { i8*, i64 }` - data ptr + byte len, literals point at a private global
+pub fn string_type(context: LLVMContextRef) -> LLVMTypeRef {
+    unsafe {
+        let name = b"str\0";
+        let ty = LLVMGetTypeByName2(context, name.as_ptr() as *const i8);
+        if !ty.is_null() {
+            return ty;
+        }
+        let ty = LLVMStructCreateNamed(context, name.as_ptr() as *const i8);
+        let mut elements = [LLVMPointerType(LLVMInt8TypeInContext(context), 0), LLVMInt64TypeInContext(context)];
+        LLVMStructSetBody(ty, elements.as_mut_ptr(), 2, 0);
+        ty
+    }
+}
+
+/// `dyn = { i8*, i8** }` - data ptr + vtable, a const array of fn ptrs in trait method order
+pub fn trait_object_type(context: LLVMContextRef) -> LLVMTypeRef {
+    unsafe {
+        let name = b"dyn\0";
+        let ty = LLVMGetTypeByName2(context, name.as_ptr() as *const i8);
+        if !ty.is_null() {
+            return ty;
+        }
+        let ty = LLVMStructCreateNamed(context, name.as_ptr() as *const i8);
+        let byte_ptr = LLVMPointerType(LLVMInt8TypeInContext(context), 0);
+        let mut elements = [byte_ptr, LLVMPointerType(byte_ptr, 0)];
+        LLVMStructSetBody(ty, elements.as_mut_ptr(), 2, 0);
+        ty
+    }
+}
+
+/// named `enum.Name = { i32, [n x i64] }`, tag + payload words. variants read their
+/// fields thru a bitcast of the payload 2 `variant_payload_type`
+pub fn enum_to_llvm_type(context: LLVMContextRef, e: &EnumType) -> LLVMTypeRef {
+    unsafe {
+        let name = std::ffi::CString::new(format!("enum.{}", e.name)).unwrap();
+        let ty = LLVMGetTypeByName2(context, name.as_ptr());
+        if !ty.is_null() {
+            return ty;
+        }
+        let ty = LLVMStructCreateNamed(context, name.as_ptr());
+        let words = e.payload_words();
+        let mut elements = vec![LLVMInt32TypeInContext(context)];
+        if words > 0 {
+            elements.push(LLVMArrayType2(LLVMInt64TypeInContext(context), words as u64));
+        }
+        LLVMStructSetBody(ty, elements.as_mut_ptr(), elements.len() as u32, 0);
+        ty
+    }
+}
+
+/// literal struct of one variant's fields, how the payload words r viewed 4 that variant
+pub fn variant_payload_type(context: LLVMContextRef, fields: &[Type]) -> LLVMTypeRef {
+    unsafe {
+        let mut elements: Vec<LLVMTypeRef> = fields.iter().map(|f| mir_type_to_llvm_type(context, f)).collect();
+        LLVMStructTypeInContext(context, elements.as_mut_ptr(), elements.len() as u32, 0)
+    }
+}
+
+/// named `struct.Name` type, the context doubles as the type cache so every
+/// use of a struct shares one llvm type. body is set the first time the fields r known
+unsafe fn struct_to_llvm_type(context: LLVMContextRef, s: &StructType, defining: &mut Vec<String>) -> LLVMTypeRef {
+    let name = std::ffi::CString::new(format!("struct.{}", s.name)).unwrap();
+    let mut ty = LLVMGetTypeByName2(context, name.as_ptr());
+    if ty.is_null() {
+        ty = LLVMStructCreateNamed(context, name.as_ptr());
+    }
+    if LLVMIsOpaqueStruct(ty) == 0 || s.fields.is_empty() || defining.contains(&s.name) {
+        return ty;
+    }
+
+    defining.push(s.name.clone());
+    let (mut elements, _) = struct_body(context, s, defining);
+    defining.pop();
+    LLVMStructSetBody(ty, elements.as_mut_ptr(), elements.len() as u32, 0);
+    ty
+}
+
+/// llvm element types + the element index of each declared field
+/// fields w/ a layout offset past their natural position get an explicit `[n x i8]`
+/// pad b4 them, and a declared size larger than the fields gets tail padding
+unsafe fn struct_body(context: LLVMContextRef, s: &StructType, defining: &mut Vec<String>) -> (Vec<LLVMTypeRef>, Vec<u32>) {
+    let byte = LLVMInt8TypeInContext(context);
+    let mut elements = Vec::new();
+    let mut indices = Vec::new();
+    // end of the last field, None once a field of unknown size is seen
+    let mut cursor = Some(0usize);
+
+    for field in &s.fields {
+        let align = field.type_.align().max(1);
+        if let (Some(offset), Some(at)) = (field.offset, cursor) {
+            if offset > align_to(at, align) {
+                elements.push(LLVMArrayType2(byte, (offset - at) as u64));
+            }
+            cursor = Some(offset.max(at));
+        }
+        indices.push(elements.len() as u32);
+        elements.push(lower_type(context, &field.type_, defining));
+        cursor = match (cursor, field.type_.size_in_bytes()) {
+            (Some(at), Some(size)) => Some(align_to(at, align) + size),
+            _ => None,
+        };
+    }
+
+    if let (Some(size), Some(at)) = (s.size, cursor) {
+        if size > align_to(at, s.align.unwrap_or(1).max(1)) {
+            elements.push(LLVMArrayType2(byte, (size - at) as u64));
+        }
+    }
+    (elements, indices)
+}
+
+/// llvm struct element index of each declared field (differs when padding is inserted)
+pub fn struct_field_indices(context: LLVMContextRef, s: &StructType) -> Vec<u32> {
+    unsafe { struct_body(context, s, &mut vec![s.name.clone()]).1 }
+}
+
+fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
 /// convert primitive type to LLVM type
 fn primitive_to_llvm_type(context: LLVMContextRef, p: &PrimitiveType) -> LLVMTypeRef {
     unsafe {
         match p {
-            PrimitiveType::Void => LLVMVoidType(),
-            PrimitiveType::Byte => LLVMInt8TypeInContext(context),
-            PrimitiveType::Int => LLVMInt32TypeInContext(context),
-            PrimitiveType::Long => LLVMInt64TypeInContext(context),
+            PrimitiveType::Void => LLVMVoidTypeInContext(context),
+            PrimitiveType::Byte | PrimitiveType::UByte => LLVMInt8TypeInContext(context),
+            PrimitiveType::Int | PrimitiveType::UInt => LLVMInt32TypeInContext(context),
+            PrimitiveType::Long | PrimitiveType::ULong => LLVMInt64TypeInContext(context),
             PrimitiveType::Size => {
                 // size_t is platform-dependent, use u64 for 64-bit
                 LLVMInt64TypeInContext(context)
@@ -2,11 +2,27 @@ pub mod ports;
 pub mod factory;
 pub mod bridge;
 pub mod null;
+pub mod elf;
+pub mod link;
+pub mod objcopy;
+pub mod multiversion;
+pub mod stack_usage;
+pub mod codegen_units;
+pub mod debugger;
+pub mod interp;
+#[cfg(feature = "llvm")]
 pub mod llvm;
+#[cfg(feature = "cranelift")]
+pub mod cranelift;
 
 pub use ports::*;
 pub use factory::*;
 pub use bridge::*;
 pub use null::*;
+pub use link::LinkOptions;
+pub use interp::{InterpBackendFactory, Interpreter};
 // Export LLVM types explicitly to avoid conflicts with ports module
-pub use llvm::{LlvmBackendFactory, LlvmCodeGen, LlvmOptimizer, LlvmEmitter};
\ No newline at end of file
+#[cfg(feature = "llvm")]
+pub use llvm::{LlvmBackendFactory, LlvmCodeGen, LlvmOptimizer, LlvmEmitter};
+#[cfg(feature = "cranelift")]
+pub use cranelift::{CraneliftBackendFactory, CraneliftCodeGen, CraneliftOptimizer, CraneliftEmitter};
\ No newline at end of file
@@ -0,0 +1,91 @@
+use crate::backend::ports::codegen::CodeGenError;
+use crate::core::target::{x86_cpu_feature, TargetInfo};
+
+/// one clone of a `@target_feature` fn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionVersion {
+    pub symbol: String,
+    /// None 4 the baseline version every cpu can run
+    pub feature: Option<String>,
+    /// bit in `__cpu_model.__cpu_features[0]` the resolver tests
+    pub cpu_bit: Option<u32>,
+}
+
+impl FunctionVersion {
+    /// value 4 the llvm "target-features" fn attribute
+    pub fn llvm_target_features(&self) -> Option<String> {
+        self.feature.as_ref().map(|f| format!("+{}", f))
+    }
+}
+
+/// symbol of the clone built 4 `feature`
+pub fn clone_name(function: &str, feature: &str) -> String {
+    format!("{}.{}", function, feature)
+}
+
+pub fn default_name(function: &str) -> String {
+    format!("{}.default", function)
+}
+
+pub fn resolver_name(function: &str) -> String {
+    format!("{}.resolver", function)
+}
+
+/// global a dispatch thunk keeps the resolved clone in
+pub fn target_name(function: &str) -> String {
+    format!("{}.target", function)
+}
+
+/// clones in the order the resolver tries them: newest isa first, baseline last
+pub fn versions(function: &str, features: &[String]) -> Result<Vec<FunctionVersion>, CodeGenError> {
+    let mut ranked = Vec::new();
+    for feature in features {
+        let (priority, bit) = x86_cpu_feature(feature).ok_or_else(|| {
+            CodeGenError::UnsupportedFeature(format!("unknown target feature '{}' on '{}'", feature, function))
+        })?;
+        ranked.push((priority, bit, feature));
+    }
+    ranked.sort_by_key(|r| std::cmp::Reverse(r.0));
+    ranked.dedup_by_key(|r| r.0);
+
+    let mut versions: Vec<FunctionVersion> = ranked
+        .into_iter()
+        .map(|(_, bit, feature)| FunctionVersion {
+            symbol: clone_name(function, feature),
+            feature: Some(feature.clone()),
+            cpu_bit: Some(bit),
+        })
+        .collect();
+    versions.push(FunctionVersion { symbol: default_name(function), feature: None, cpu_bit: None });
+    Ok(versions)
+}
+
+/// how the public symbol picks a clone at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchKind {
+    /// elf ifunc, the dynamic loader runs the resolver once at relocation time
+    IFunc,
+    /// plain fn that resolves on its 1st call, caches the clone + forwards its args (mach-o / coff
+    /// have no ifuncs)
+    Thunk,
+}
+
+/// runtime dispatch relies on libgcc/compiler-rt's x86 cpu model, which msvc's runtime doesnt have
+pub fn dispatch_kind(target: &TargetInfo) -> Result<DispatchKind, CodeGenError> {
+    if !target.is_x86() {
+        return Err(CodeGenError::UnsupportedFeature(format!(
+            "@target_feature dispatch is only supported on x86 targets, not {}",
+            target.triple
+        )));
+    }
+    if target.os == "windows" && target.triple.ends_with("msvc") {
+        return Err(CodeGenError::UnsupportedFeature(format!(
+            "@target_feature dispatch needs the __cpu_model of libgcc or compiler-rt, which {} doesn't link, use a windows-gnu target",
+            target.triple
+        )));
+    }
+    match target.os.as_str() {
+        "macos" | "windows" => Ok(DispatchKind::Thunk),
+        _ => Ok(DispatchKind::IFunc),
+    }
+}
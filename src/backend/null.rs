@@ -1,8 +1,9 @@
-use crate::backend::factory::{BackendFactory, BackendType, BackendError};
+use crate::backend::factory::{BackendCapabilities, BackendFactory, BackendType, BackendError, TargetSupport};
 use crate::backend::ports::{CodeGen, Emitter, Optimizer};
 use crate::backend::ports::codegen::{CodeGenError, Module, OptimizationLevel, BackendInputType};
 use crate::backend::ports::optimizer::OptimizationError;
 use crate::backend::ports::emitter::EmitError;
+use crate::backend::stack_usage::FrameInfo;
 use crate::core::mir::MirFunction;
 use std::path::Path;
 
@@ -25,6 +26,11 @@ impl BackendFactory for NullBackendFactory {
     fn backend_type(&self) -> BackendType {
         BackendType::Null
     }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        // the triple only ends up in the text's header
+        BackendCapabilities { targets: TargetSupport::Any, debug_info: false, jit: false, preferred_input: BackendInputType::Mir }
+    }
 }
 
 /// null ocde generator
@@ -42,10 +48,19 @@ impl NullCodeGen {
     }
 }
 
+/// what a null module holds, the text its emitter writes
+pub struct NullModule {
+    pub text: String,
+}
+
 impl CodeGen for NullCodeGen {
-    fn generate_from_mir(&mut self, _mir: &[MirFunction]) -> Result<Module, CodeGenError> {
-        // no op: just ret a plchldr module
-        Ok(Module::new("null_module".to_string()))
+    fn generate_from_mir(&mut self, mir: &[MirFunction]) -> Result<Module, CodeGenError> {
+        let mut text = format!("; target {}, opt level {:?}\n", self.target_triple, self.opt_level);
+        for func in mir {
+            text.push('\n');
+            text.push_str(&func.to_string());
+        }
+        Ok(Module::with_data("null_module".to_string(), Box::new(NullModule { text })))
     }
     
     fn set_optimization_level(&mut self, level: OptimizationLevel) {
@@ -75,31 +90,56 @@ impl Optimizer for NullOptimizer {
     }
 }
 
-/// null emttr
+/// null emttr, evry kind of output is the textual MIR so frontend changes can b diffed w/o llvm
 struct NullEmitter;
 
+impl NullEmitter {
+    fn write(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        let module = module
+            .data
+            .as_ref()
+            .and_then(|d| d.downcast_ref::<NullModule>())
+            .ok_or_else(|| EmitError::EmissionFailed("Not a null backend module".to_string()))?;
+        Ok(std::fs::write(output, &module.text)?)
+    }
+}
+
 impl Emitter for NullEmitter {
-    fn emit_binary(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
-        Err(EmitError::EmissionFailed(
-            "Null backend does not support binary emission".to_string()
-        ))
+    fn emit_binary(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
     }
-    
-    fn emit_assembly(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
-        Err(EmitError::EmissionFailed(
-            "Null backend does not support assembly emission".to_string()
-        ))
+
+    fn emit_assembly(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
     }
-    
-    fn emit_llvm_ir(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
-        Err(EmitError::EmissionFailed(
-            "Null backend does not support LLVM IR emission".to_string()
-        ))
+
+    fn emit_llvm_ir(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
     }
-    
-    fn emit_object(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+
+    fn emit_object(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
+    }
+
+    fn emit_bitcode(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
+    }
+
+    fn emit_static_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
+    }
+
+    fn emit_shared_lib(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
+    }
+
+    fn emit_wasm(&self, module: &Module, output: &Path) -> Result<(), EmitError> {
+        self.write(module, output)
+    }
+
+    fn stack_frames(&self, _module: &Module) -> Result<Vec<FrameInfo>, EmitError> {
         Err(EmitError::EmissionFailed(
-            "Null backend does not support object file emission".to_string()
+            "Null backend does not support stack usage analysis".to_string()
         ))
     }
 }
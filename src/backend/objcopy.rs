@@ -0,0 +1,112 @@
+use crate::backend::elf::{ElfError, ElfFile, PT_LOAD};
+use crate::backend::ports::emitter::EmitError;
+
+/// bytes of a PT_LOAD segment placed at its load (physical) address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub address: u64,
+    pub data: Vec<u8>,
+}
+
+/// loadable image of a linked elf executable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadImage {
+    pub entry: u64,
+    pub segments: Vec<Segment>,
+}
+
+impl LoadImage {
+    /// extract PT_LOAD segments from a 32 or 64 bit elf
+    /// segments r sorted by load address, bss (memsz only) is skipped like objcopy does
+    pub fn from_elf(bytes: &[u8]) -> Result<Self, EmitError> {
+        let invalid = |e: ElfError| EmitError::EmissionFailed(e.to_string());
+        let elf = ElfFile::parse(bytes).map_err(invalid)?;
+
+        let mut segments = Vec::new();
+        for ph in elf.program_headers().map_err(invalid)? {
+            if ph.p_type != PT_LOAD || ph.filesz == 0 {
+                continue;
+            }
+            let data = elf.data(ph.offset, ph.filesz).map_err(invalid)?;
+            segments.push(Segment { address: ph.paddr, data: data.to_vec() });
+        }
+
+        if segments.is_empty() {
+            return Err(EmitError::EmissionFailed(
+                "ELF file has no loadable segments (was it linked?)".to_string(),
+            ));
+        }
+        segments.sort_by_key(|s| s.address);
+        Ok(Self { entry: elf.entry().map_err(invalid)?, segments })
+    }
+
+    /// flat memory image from the lowest load address, gaps r zero filled
+    pub fn to_raw_binary(&self) -> Vec<u8> {
+        let start = self.segments.first().map(|s| s.address).unwrap_or(0);
+        let end = self
+            .segments
+            .iter()
+            .map(|s| s.address + s.data.len() as u64)
+            .max()
+            .unwrap_or(start);
+        let mut image = vec![0u8; (end - start) as usize];
+        for segment in &self.segments {
+            let at = (segment.address - start) as usize;
+            image[at..at + segment.data.len()].copy_from_slice(&segment.data);
+        }
+        image
+    }
+
+    /// intel hex w/ 16 byte data records and extended linear address records
+    pub fn to_ihex(&self) -> Result<String, EmitError> {
+        let mut out = String::new();
+        let mut upper: Option<u16> = None;
+
+        for segment in &self.segments {
+            if segment.address + segment.data.len() as u64 > 0x1_0000_0000 {
+                return Err(EmitError::EmissionFailed(format!(
+                    "segment at {:#x} does not fit in the 32 bit Intel HEX address space",
+                    segment.address
+                )));
+            }
+            let mut address = segment.address;
+            let mut rest = segment.data.as_slice();
+            while !rest.is_empty() {
+                let hi = (address >> 16) as u16;
+                if upper != Some(hi) {
+                    out.push_str(&ihex_record(0x04, 0, &hi.to_be_bytes()));
+                    upper = Some(hi);
+                }
+                // records never cross a 64k boundary
+                let room = 0x1_0000 - (address & 0xffff);
+                let len = rest.len().min(16).min(room as usize);
+                out.push_str(&ihex_record(0x00, address as u16, &rest[..len]));
+                address += len as u64;
+                rest = &rest[len..];
+            }
+        }
+
+        if self.entry != 0 && self.entry <= u32::MAX as u64 {
+            out.push_str(&ihex_record(0x05, 0, &(self.entry as u32).to_be_bytes()));
+        }
+        out.push_str(&ihex_record(0x01, 0, &[]));
+        Ok(out)
+    }
+}
+
+/// one `:LLAAAATT<data>CC` record, checksum is the 2s complement of the byte sum
+pub fn ihex_record(record_type: u8, address: u16, data: &[u8]) -> String {
+    let mut bytes = vec![data.len() as u8];
+    bytes.extend_from_slice(&address.to_be_bytes());
+    bytes.push(record_type);
+    bytes.extend_from_slice(data);
+    let sum = bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+    bytes.push(sum.wrapping_neg());
+
+    let mut record = String::from(":");
+    for b in bytes {
+        record.push_str(&format!("{:02X}", b));
+    }
+    record.push('\n');
+    record
+}
@@ -1,5 +1,7 @@
 use crate::core::mir::MirFunction;
 use crate::core::hir::Hir;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// represents a compiled module
@@ -87,6 +89,15 @@ pub trait CodeGen {
     
     /// get preferred input type (HIR or MIR)
     fn preferred_input(&self) -> BackendInputType;
+    
+    /// emit dwarf/codeview 4 the source (-g / -C debuginfo)
+    fn set_debug_info(&mut self, _debug_info: DebugInfo) {}
+
+    /// unwind w/ landing pads or abort on panic (-C panic)
+    fn set_panic_strategy(&mut self, _strategy: PanicStrategy) {}
+
+    /// only define these fns, the rest of the input is just declared so calls into other units link
+    fn set_codegen_unit(&mut self, _functions: Vec<String>) {}
 }
 
 /// backend input type preference
@@ -96,6 +107,15 @@ pub enum BackendInputType {
     Mir,
 }
 
+impl BackendInputType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackendInputType::Hir => "hir",
+            BackendInputType::Mir => "mir",
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum CodeGenError {
     #[error("Code generation failed: {0}")]
@@ -131,3 +151,61 @@ impl OptimizationLevel {
         }
     }
 }
+
+/// how much debug info 2 emit (-C debuginfo=0|1|2)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DebugInfoLevel {
+    #[default]
+    None,
+    /// fn names + line tables, enough 4 backtraces and breakpoints
+    LineTablesOnly,
+    Full,
+}
+
+impl DebugInfoLevel {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "0" | "none" => Some(Self::None),
+            "1" | "line-tables-only" => Some(Self::LineTablesOnly),
+            "2" | "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+}
+
+/// what a panic does (-C panic=unwind|abort)
+/// unwind would run landing pads so defer/destructors still fire, nothing raises or catches yet
+/// so its refused + evry panic aborts w/o unwind tables
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PanicStrategy {
+    Unwind,
+    #[default]
+    Abort,
+}
+
+impl PanicStrategy {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "unwind" => Some(Self::Unwind),
+            "abort" => Some(Self::Abort),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unwind => "unwind",
+            Self::Abort => "abort",
+        }
+    }
+}
+
+/// source info a backend needs 2 emit debug info
+/// MIR has no spans so fn lines r collected frm the ast b4 lowering
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugInfo {
+    pub level: DebugInfoLevel,
+    pub file: PathBuf,
+    /// 1 based line of each fn definition
+    pub function_lines: HashMap<String, u32>,
+}
@@ -1,5 +1,7 @@
+use crate::backend::link::LinkOptions;
 use crate::backend::ports::codegen::Module;
-use std::path::Path;
+use crate::backend::stack_usage::FrameInfo;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 /// trait 4 emitting cmpld output
@@ -15,6 +17,43 @@ pub trait Emitter {
     
     /// emit an object flie
     fn emit_object(&self, module: &Module, output: &Path) -> Result<(), EmitError>;
+
+    /// emit llvm bitcode (.bc) 4 llvm tools + lto
+    fn emit_bitcode(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(EmitError::EmissionFailed(
+            "This backend cannot emit LLVM bitcode".to_string(),
+        ))
+    }
+    
+    /// emit a static library (.a / .lib) C code can link against
+    fn emit_static_lib(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(EmitError::EmissionFailed(
+            "This backend cannot emit static libraries".to_string(),
+        ))
+    }
+
+    /// emit a shared library (.so / .dylib / .dll), only exported fns r visible 2 its users
+    fn emit_shared_lib(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(EmitError::EmissionFailed(
+            "This backend cannot emit shared libraries".to_string(),
+        ))
+    }
+    
+    /// emit a .wasm module 4 wasm32-unknown-unknown / wasm32-wasi
+    fn emit_wasm(&self, _module: &Module, _output: &Path) -> Result<(), EmitError> {
+        Err(EmitError::EmissionFailed(
+            "This backend cannot emit WebAssembly".to_string(),
+        ))
+    }
+    
+    /// frame size of every defined function 4 stack usage reports
+    fn stack_frames(&self, module: &Module) -> Result<Vec<FrameInfo>, EmitError>;
+
+    /// set options 4 the linker step of emit_binary
+    fn set_link_options(&mut self, _options: LinkOptions) {}
+    
+    /// embed the module's bitcode in emitted objects (-C embed-bitcode)
+    fn set_embed_bitcode(&mut self, _embed: bool) {}
 }
 
 #[derive(Debug, Error)]
@@ -34,7 +73,18 @@ pub enum EmitType {
     Binary,
     Assembly,
     LlvmIr,
+    Bitcode,
     Object,
+    RawBinary,
+    Ihex,
+    StackUsage,
+    Wasm,
+    StaticLib,
+    SharedLib,
+    /// optimized MIR as text, written by the driver b4 any backend runs
+    Mir,
+    /// each MIR fn's CFG as graphviz DOT, also written b4 any backend runs
+    CfgDot,
 }
 
 impl EmitType {
@@ -43,8 +93,56 @@ impl EmitType {
             "binary" | "bin" | "exe" => Some(Self::Binary),
             "asm" | "assembly" => Some(Self::Assembly),
             "llvm-ir" | "llvm" | "ir" => Some(Self::LlvmIr),
+            "llvm-bc" | "bitcode" | "bc" => Some(Self::Bitcode),
             "obj" | "object" => Some(Self::Object),
+            "raw-bin" | "raw" => Some(Self::RawBinary),
+            "ihex" | "hex" => Some(Self::Ihex),
+            "stack-usage" => Some(Self::StackUsage),
+            "wasm" => Some(Self::Wasm),
+            "staticlib" | "static-lib" => Some(Self::StaticLib),
+            "dylib" | "cdylib" | "shared-lib" => Some(Self::SharedLib),
+            "mir" => Some(Self::Mir),
+            "cfg-dot" => Some(Self::CfgDot),
             _ => None,
         }
     }
+
+    /// the --emit spelling `from_str` reads back
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Binary => "binary",
+            Self::Assembly => "asm",
+            Self::LlvmIr => "llvm-ir",
+            Self::Bitcode => "llvm-bc",
+            Self::Object => "obj",
+            Self::RawBinary => "raw-bin",
+            Self::Ihex => "ihex",
+            Self::StackUsage => "stack-usage",
+            Self::Wasm => "wasm",
+            Self::StaticLib => "staticlib",
+            Self::SharedLib => "dylib",
+            Self::Mir => "mir",
+            Self::CfgDot => "cfg-dot",
+        }
+    }
+
+    /// where `input` is written w/o -o, in the working dir named after the input
+    pub fn default_output(&self, input: &Path) -> PathBuf {
+        let stem = input.file_stem().map_or_else(|| "out".into(), |s| s.to_string_lossy());
+        PathBuf::from(match self {
+            Self::Binary => stem.into_owned(),
+            Self::Assembly => format!("{}.s", stem),
+            Self::LlvmIr => format!("{}.ll", stem),
+            Self::Bitcode => format!("{}.bc", stem),
+            Self::Object => format!("{}.o", stem),
+            Self::RawBinary => format!("{}.bin", stem),
+            Self::Ihex => format!("{}.hex", stem),
+            Self::StackUsage => format!("{}.su", stem),
+            Self::Wasm => format!("{}.wasm", stem),
+            Self::StaticLib => format!("lib{}.a", stem),
+            Self::SharedLib => format!("lib{}.so", stem),
+            Self::Mir => format!("{}.mir", stem),
+            Self::CfgDot => format!("{}.dot", stem),
+        })
+    }
 }
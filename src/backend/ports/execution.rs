@@ -0,0 +1,55 @@
+use crate::backend::ports::codegen::Module;
+use crate::core::types::ty::Type;
+use thiserror::Error;
+
+/// trait 4 running cmpld modules in process, no binary is written
+pub trait ExecutionEngine {
+    /// make a module's fns callable frm the ones added after it, eg an imported module's
+    fn add_module(&mut self, module: &Module) -> Result<(), ExecutionError>;
+
+    /// add `module` + call its `main`, what main returns is the exit code (0 4 a void main)
+    fn run_main(&mut self, module: &Module) -> Result<i32, ExecutionError>;
+
+    /// add `module` + call its fn `name`, which takes nothing + returns `returns`. only a primitive or a
+    /// string comes back 2 the host, the repl runs each entry this way
+    fn call_entry(&mut self, module: &Module, name: &str, returns: &Type) -> Result<EntryValue, ExecutionError>;
+}
+
+/// what an entry fn returned, copied out of the engine
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryValue {
+    Unit,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+}
+
+impl std::fmt::Display for EntryValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryValue::Unit => write!(f, "()"),
+            EntryValue::Int(i) => write!(f, "{}", i),
+            EntryValue::Float(x) => write!(f, "{:?}", x),
+            EntryValue::Bool(b) => write!(f, "{}", b),
+            EntryValue::Char(c) => write!(f, "{:?}", c),
+            EntryValue::Str(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ExecutionError {
+    #[error("JIT setup failed: {0}")]
+    SetupFailed(String),
+
+    #[error("Invalid module: {0}")]
+    InvalidModule(String),
+
+    #[error("Symbol not found: {0}")]
+    SymbolNotFound(String),
+
+    #[error("Execution trapped: {0}")]
+    Trapped(String),
+}
@@ -1,7 +1,9 @@
 pub mod codegen;
 pub mod optimizer;
 pub mod emitter;
+pub mod execution;
 
 pub use codegen::*;
 pub use optimizer::*;
 pub use emitter::*;
+pub use execution::*;
@@ -1,4 +1,4 @@
-use crate::backend::ports::codegen::Module;
+use crate::backend::ports::codegen::{Module, OptimizationLevel};
 use thiserror::Error;
 
 /// trai 4 mdl optimization
@@ -8,6 +8,9 @@ pub trait Optimizer {
     
     /// add a cstm optmztn pass
     fn add_pass(&mut self, pass: OptimizationPass);
+
+    /// pipeline 2 run, backends that optimize during codegen ignore it
+    fn set_optimization_level(&mut self, _level: OptimizationLevel) {}
 }
 
 #[derive(Debug, Error)]
@@ -20,14 +23,51 @@ pub enum OptimizationError {
 }
 
 /// represents an optimization pass
-#[derive(Debug, Clone)]
-pub struct OptimizationPass {
-    pub name: String,
-    // pass speicfic data will be added here
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptimizationPass {
+    /// inline calls
+    Inline,
+    /// promote stack slots 2 ssa values
+    Mem2Reg,
+    /// dead code elimination
+    DeadCodeElimination,
+    LoopUnroll,
+    /// sparse conditional constant propagation
+    Sccp,
+    /// textual pipeline in the backend's own syntax, passed thru as is
+    Custom(String),
 }
 
 impl OptimizationPass {
-    pub fn new(name: String) -> Self {
-        Self { name }
+    /// the pipeline fragments `--passes` knows by name
+    pub const NAMES: [&'static str; 12] = [
+        "inline", "mem2reg", "dce", "loop-unroll", "sccp", "sroa", "gvn", "instcombine", "simplifycfg", "licm", "adce",
+        "tailcallelim",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "inline" => OptimizationPass::Inline,
+            "mem2reg" => OptimizationPass::Mem2Reg,
+            "dce" => OptimizationPass::DeadCodeElimination,
+            "loop-unroll" => OptimizationPass::LoopUnroll,
+            "sccp" => OptimizationPass::Sccp,
+            // a loop pass, it needs memory ssa 2 hoist loads
+            "licm" => OptimizationPass::Custom("function(loop-mssa(licm))".to_string()),
+            name if Self::NAMES.contains(&name) => OptimizationPass::Custom(name.to_string()),
+            _ => return None,
+        })
+    }
+
+    /// the name `from_name` takes, or a custom pipeline's text
+    pub fn name(&self) -> &str {
+        match self {
+            OptimizationPass::Inline => "inline",
+            OptimizationPass::Mem2Reg => "mem2reg",
+            OptimizationPass::DeadCodeElimination => "dce",
+            OptimizationPass::LoopUnroll => "loop-unroll",
+            OptimizationPass::Sccp => "sccp",
+            OptimizationPass::Custom(pipeline) => pipeline,
+        }
     }
 }
@@ -0,0 +1,198 @@
+use crate::core::mir::{Instruction, MirFunction, Operand};
+use std::collections::{HashMap, HashSet};
+
+/// direct call edges of mir functions keyed by caller
+pub fn mir_call_graph(functions: &[MirFunction]) -> HashMap<String, Vec<String>> {
+    let mut graph = HashMap::new();
+    for function in functions {
+        let mut callees: Vec<String> = Vec::new();
+        for block in &function.basic_blocks {
+            for inst in &block.instructions {
+                if let Instruction::Call { func: Operand::Function(f), .. } = inst {
+                    if !callees.contains(&f.name) {
+                        callees.push(f.name.clone());
+                    }
+                }
+            }
+        }
+        graph.insert(function.name.clone(), callees);
+    }
+    graph
+}
+
+/// stack frame of one defined function as reported by a backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameInfo {
+    pub name: String,
+    /// bytes of fixed size stack slots
+    pub frame_size: u64,
+    /// has a stack allocation whose size is only known at runtime
+    pub dynamic: bool,
+    /// direct callees, externals included
+    pub callees: Vec<String>,
+}
+
+/// per function frame sizes + call graph 4 bounding stack consumption
+#[derive(Debug, Clone, Default)]
+pub struct StackUsage {
+    frames: Vec<FrameInfo>,
+}
+
+impl StackUsage {
+    pub fn new(frames: Vec<FrameInfo>) -> Self {
+        Self { frames }
+    }
+
+    /// merge call edges the backend cant see (eg frm mir)
+    pub fn with_calls(mut self, calls: &HashMap<String, Vec<String>>) -> Self {
+        for frame in &mut self.frames {
+            for callee in calls.get(&frame.name).into_iter().flatten() {
+                if !frame.callees.contains(callee) {
+                    frame.callees.push(callee.clone());
+                }
+            }
+        }
+        self
+    }
+
+    pub fn frames(&self) -> &[FrameInfo] {
+        &self.frames
+    }
+
+    fn frame(&self, name: &str) -> Option<&FrameInfo> {
+        self.frames.iter().find(|f| f.name == name)
+    }
+
+    /// functions on a call cycle (self recursion or mutual recursion)
+    pub fn recursive_functions(&self) -> HashSet<String> {
+        // tarjan scc over defined functions
+        struct Tarjan<'a> {
+            usage: &'a StackUsage,
+            index: HashMap<&'a str, usize>,
+            lowlink: HashMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            on_stack: HashSet<&'a str>,
+            recursive: HashSet<String>,
+        }
+
+        impl<'a> Tarjan<'a> {
+            fn visit(&mut self, name: &'a str) {
+                let idx = self.index.len();
+                self.index.insert(name, idx);
+                self.lowlink.insert(name, idx);
+                self.stack.push(name);
+                self.on_stack.insert(name);
+
+                let callees = self.usage.frame(name).map(|f| f.callees.as_slice()).unwrap_or(&[]);
+                for callee in callees {
+                    if self.usage.frame(callee).is_none() {
+                        continue;
+                    }
+                    if !self.index.contains_key(callee.as_str()) {
+                        self.visit(callee);
+                        let low = self.lowlink[name].min(self.lowlink[callee.as_str()]);
+                        self.lowlink.insert(name, low);
+                    } else if self.on_stack.contains(callee.as_str()) {
+                        let low = self.lowlink[name].min(self.index[callee.as_str()]);
+                        self.lowlink.insert(name, low);
+                    }
+                }
+
+                if self.lowlink[name] == self.index[name] {
+                    let mut component = Vec::new();
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack.remove(member);
+                        component.push(member);
+                        if member == name {
+                            break;
+                        }
+                    }
+                    let self_call = callees.iter().any(|c| c == name);
+                    if component.len() > 1 || self_call {
+                        self.recursive.extend(component.iter().map(|m| m.to_string()));
+                    }
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            usage: self,
+            index: HashMap::new(),
+            lowlink: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            recursive: HashSet::new(),
+        };
+        for frame in &self.frames {
+            if !tarjan.index.contains_key(frame.name.as_str()) {
+                tarjan.visit(&frame.name);
+            }
+        }
+        tarjan.recursive
+    }
+
+    /// deepest stack reachable from `root`
+    /// none when the bound is unknown (recursion or dynamic allocas reachable)
+    /// external callees r not counted
+    pub fn worst_case(&self, root: &str) -> Option<u64> {
+        let recursive = self.recursive_functions();
+        let mut memo = HashMap::new();
+        self.worst_case_inner(root, &recursive, &mut memo)
+    }
+
+    fn worst_case_inner(
+        &self,
+        name: &str,
+        recursive: &HashSet<String>,
+        memo: &mut HashMap<String, Option<u64>>,
+    ) -> Option<u64> {
+        if let Some(cached) = memo.get(name) {
+            return *cached;
+        }
+        let frame = self.frame(name)?;
+        let result = if frame.dynamic || recursive.contains(name) {
+            None
+        } else {
+            let mut deepest = Some(0u64);
+            for callee in &frame.callees {
+                if self.frame(callee).is_none() {
+                    continue;
+                }
+                deepest = match (deepest, self.worst_case_inner(callee, recursive, memo)) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                };
+            }
+            deepest.map(|d| d + frame.frame_size)
+        };
+        memo.insert(name.to_string(), result);
+        result
+    }
+
+    /// human readable table, one line per function
+    pub fn report(&self) -> String {
+        let recursive = self.recursive_functions();
+        let width = self.frames.iter().map(|f| f.name.len()).max().unwrap_or(0).max(8);
+
+        let mut out = format!("{:<width$}  {:>8}  {:>9}  notes\n", "function", "frame", "worst");
+        for frame in &self.frames {
+            let worst = match self.worst_case(&frame.name) {
+                Some(bytes) => bytes.to_string(),
+                None => "unbounded".to_string(),
+            };
+            let mut notes = Vec::new();
+            if recursive.contains(&frame.name) {
+                notes.push("recursive");
+            }
+            if frame.dynamic {
+                notes.push("dynamic");
+            }
+            out.push_str(
+                format!("{:<width$}  {:>8}  {:>9}  {}", frame.name, frame.frame_size, worst, notes.join(", "))
+                    .trim_end(),
+            );
+            out.push('\n');
+        }
+        out
+    }
+}
@@ -1,24 +1,47 @@
 use clap::Parser;
-use emc::cli::args::{Cli, Commands};
-use emc::cli::compiler::{display_results, Compiler};
+use emc::cli::args::{Cli, Commands, HighlightFormat};
+use emc::cli::compiler::{display_results, strict_summary, Compiler};
+use emc::cli::daemon;
+use emc::cli::manifest::Manifest;
+use emc::cli::passes::PassPlan;
+use emc::cli::error_display::display_diagnostics;
 use emc::cli::output::Output;
+use emc::cli::remote::JobRunner;
+use emc::cli::repl::{Repl, ReplError};
 use emc::backend::factory::BackendType;
+use emc::backend::link::{SplitDebugInfo, Strip, SymbolVisibility};
+use emc::backend::ports::codegen::{DebugInfoLevel, PanicStrategy};
+use emc::backend::ports::emitter::EmitType;
+use emc::core::edition::Edition;
+use emc::core::limits::Limits;
+use emc::core::mir::passes::DEFAULT_INLINE_THRESHOLD;
+use emc::error::columns::DEFAULT_TAB_WIDTH;
+use codespan_reporting::term::termcolor::ColorChoice;
+use std::io::{BufRead, Write};
 use std::process;
 
 fn main() {
     let cli = Cli::parse();
 
+    // builds + checks go 2 the daemon when 1 is running
+    if cli.daemon && matches!(cli.command, None | Some(Commands::Check { .. })) {
+        forward_to_daemon();
+    }
+
     // handle subcommands
     if let Some(command) = &cli.command {
         match command {
             Commands::Build { input, output } => {
-                handle_build(input.as_ref().or(cli.input.as_ref()), output.as_ref().or(cli.output.as_ref()));
+                handle_build(input.as_ref().or(cli.inputs.first()), output.as_ref().or(cli.output.as_ref()));
             }
             Commands::Run { input } => {
-                handle_run(input.as_ref().or(cli.input.as_ref()));
+                handle_run(input.as_ref().or(cli.inputs.first()), cli.interp);
+            }
+            Commands::Repl => {
+                handle_repl(cli.interp);
             }
             Commands::Check { input } => {
-                handle_check(input.as_ref().or(cli.input.as_ref()));
+                handle_check(input.as_ref().or(cli.inputs.first()));
             }
             Commands::Test { test: _ } => {
                 Output::info("Test command not yet implemented");
@@ -28,35 +51,80 @@ fn main() {
                 Output::info("Format command not yet implemented");
                 process::exit(1);
             }
+            Commands::Size { input } => {
+                handle_size(input.as_ref().or(cli.inputs.first()));
+            }
+            Commands::Backends { json } => {
+                let registry = emc::backend::factory::BackendRegistry::new();
+                match json {
+                    true => println!("{}", emc::cli::backends::describe_json(&registry)),
+                    false => println!("{}", emc::cli::backends::describe(&registry)),
+                }
+            }
+            Commands::Clean { dir, backend, target, opt_level, stale } => {
+                handle_clean(dir, backend.as_deref(), target.clone(), opt_level.clone(), *stale);
+            }
+            Commands::Daemon { listen, stop } => {
+                handle_daemon(listen, *stop);
+            }
+            Commands::Serve { listen, cache, token_file } => {
+                handle_serve(listen, cache.clone(), token_file.clone());
+            }
+            Commands::Worker => {
+                let runner = JobRunner::new(emc::backend::factory::BackendRegistry::new());
+                if let Err(e) = runner.serve_stream(&mut std::io::stdin().lock(), &mut std::io::stdout().lock()) {
+                    Output::error(&format!("Worker failed: {}", e));
+                    process::exit(1);
+                }
+            }
             Commands::Doc { input: _ } => {
                 Output::info("Documentation generation not yet implemented");
                 process::exit(1);
             }
+            Commands::HighlightDefs { format, output } => {
+                let defs = match format {
+                    HighlightFormat::Textmate => emc::cli::highlight::textmate(),
+                    HighlightFormat::TreeSitter => emc::cli::highlight::tree_sitter(),
+                };
+                write_output(&defs, output.as_ref());
+            }
         }
         return;
     }
 
-    // default: compile the input file
-    match emc::cli::args::CompileConfig::from_cli(&cli) {
-        Ok(config) => {
-            let mut compiler = Compiler::new(config.clone());
-            match compiler.compile() {
-                Ok(result) => {
-                    display_results(&result, &config);
-                    if !result.success {
-                        process::exit(1);
-                    }
-                }
-                Err(e) => {
-                    Output::error(&format!("Compilation failed: {}", e));
-                    process::exit(1);
-                }
-            }
-        }
+    // the grammar doesnt depend on an input
+    if cli.emit == "grammar" {
+        handle_grammar(cli.edition.as_deref(), cli.output.as_ref());
+        return;
+    }
+
+    // default: compile each input file, all of them r tried b4 failing
+    let configs = match emc::cli::args::CompileConfig::for_inputs(&cli) {
+        Ok(configs) => configs,
         Err(e) => {
             Output::error(&e);
             process::exit(1);
         }
+    };
+    let mut failed = false;
+    for config in configs {
+        let mut compiler = Compiler::new(config.clone());
+        match compiler.compile() {
+            Ok(result) => {
+                display_results(&result, &config);
+                failed |= !result.success;
+            }
+            Err(e) => {
+                if config.strict {
+                    println!("{}", strict_summary(&config, Err(&e)));
+                }
+                Output::error(&format!("{}: compilation failed: {}", config.input.display(), e));
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
     }
 }
 
@@ -69,19 +137,51 @@ fn handle_build(input: Option<&std::path::PathBuf>, output: Option<&std::path::P
         }
     };
 
+    let edition = package_edition(&input);
+    let passes = package_passes(&input);
+    let output = output.cloned().unwrap_or_else(|| EmitType::Binary.default_output(&input));
     let config = emc::cli::args::CompileConfig {
         input,
-        output: output.cloned(),
+        output: Some(output),
         target: None,
         opt_level: "2".to_string(),
         emit: "binary".to_string(),
         library_paths: vec![],
         link_libs: vec![],
+        linker_script: None,
+        embed_bitcode: false,
+        codegen_units: 1,
+        debuginfo: DebugInfoLevel::None,
+        split_debuginfo: SplitDebugInfo::Off,
+        strip: Strip::None,
+        debuginfo_compression: None,
+        panic: PanicStrategy::Abort,
+        inline_threshold: DEFAULT_INLINE_THRESHOLD,
+        profile_use: None,
+        null_checks: true,
+        incremental: None,
+        builders: Vec::new(),
+        remote_token: None,
         crate_type: None,
+        visibility: SymbolVisibility::Default,
+        exports: Vec::new(),
+        no_prelude: false,
+        edition,
+        lints: Vec::new(),
         verbose: false,
         quiet: false,
         color: emc::cli::args::ColorWhen::Auto,
+        error_format: emc::cli::args::ErrorFormat::Human,
+        message_format: emc::cli::args::MessageFormat::Human,
+        tab_width: DEFAULT_TAB_WIDTH,
+        strict: false,
+        limits: Limits::default(),
+        dump_mir: None,
+        dump_mir_dir: std::path::PathBuf::from("mir_dump"),
+        print_pass_stats: false,
+        passes,
         backend: BackendType::Llvm, // default 2 llvm
+        explicit_backend: false,
     };
 
     let mut compiler = Compiler::new(config.clone());
@@ -99,12 +199,299 @@ fn handle_build(input: Option<&std::path::PathBuf>, output: Option<&std::path::P
     }
 }
 
-fn handle_run(input: Option<&std::path::PathBuf>) {
-    Output::info("Run command not yet implemented (backend codegen required)");
-    if let Some(input) = input {
-        Output::info(&format!("Would run: {}", input.display()));
+fn handle_run(input: Option<&std::path::PathBuf>, interp: bool) {
+    let input = match input {
+        Some(i) => i.clone(),
+        None => {
+            Output::error("No input file specified for run command");
+            process::exit(1);
+        }
+    };
+
+    // nothing is written, the program is jitted (or interpreted) + run in this process
+    let edition = package_edition(&input);
+    let passes = package_passes(&input);
+    let config = emc::cli::args::CompileConfig {
+        input,
+        output: None,
+        target: None,
+        opt_level: "0".to_string(),
+        emit: "binary".to_string(),
+        library_paths: vec![],
+        link_libs: vec![],
+        linker_script: None,
+        embed_bitcode: false,
+        codegen_units: 1,
+        debuginfo: DebugInfoLevel::None,
+        split_debuginfo: SplitDebugInfo::Off,
+        strip: Strip::None,
+        debuginfo_compression: None,
+        panic: PanicStrategy::Abort,
+        inline_threshold: DEFAULT_INLINE_THRESHOLD,
+        profile_use: None,
+        null_checks: true,
+        incremental: None,
+        builders: Vec::new(),
+        remote_token: None,
+        crate_type: None,
+        visibility: SymbolVisibility::Default,
+        exports: Vec::new(),
+        no_prelude: false,
+        edition,
+        lints: Vec::new(),
+        verbose: false,
+        quiet: true,
+        color: emc::cli::args::ColorWhen::Auto,
+        error_format: emc::cli::args::ErrorFormat::Human,
+        message_format: emc::cli::args::MessageFormat::Human,
+        tab_width: DEFAULT_TAB_WIDTH,
+        strict: false,
+        limits: Limits::default(),
+        dump_mir: None,
+        dump_mir_dir: std::path::PathBuf::from("mir_dump"),
+        print_pass_stats: false,
+        passes,
+        backend: if interp { BackendType::Interp } else { BackendType::Llvm },
+        explicit_backend: interp,
+    };
+
+    let mut compiler = Compiler::new(config.clone());
+    let result = match compiler.compile() {
+        Ok(result) => result,
+        Err(e) => {
+            Output::error(&format!("Compilation failed: {}", e));
+            process::exit(1);
+        }
+    };
+    if !result.success {
+        display_results(&result, &config);
+        process::exit(1);
+    }
+    match compiler.run_jit(&result) {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            Output::error(&format!("Run failed: {}", e));
+            process::exit(1);
+        }
+    }
+}
+
+fn handle_repl(interp: bool) {
+    // an emerald.toml in the working dir picks the edition
+    let input = std::env::current_dir().unwrap_or_default().join("<repl>");
+    let edition = package_edition(&input);
+    let passes = package_passes(&input);
+    let config = emc::cli::args::CompileConfig {
+        input,
+        output: None,
+        target: None,
+        opt_level: "0".to_string(),
+        emit: "binary".to_string(),
+        library_paths: vec![],
+        link_libs: vec![],
+        linker_script: None,
+        embed_bitcode: false,
+        codegen_units: 1,
+        debuginfo: DebugInfoLevel::None,
+        split_debuginfo: SplitDebugInfo::Off,
+        strip: Strip::None,
+        debuginfo_compression: None,
+        panic: PanicStrategy::Abort,
+        inline_threshold: DEFAULT_INLINE_THRESHOLD,
+        profile_use: None,
+        null_checks: true,
+        incremental: None,
+        builders: Vec::new(),
+        remote_token: None,
+        crate_type: None,
+        visibility: SymbolVisibility::Default,
+        exports: Vec::new(),
+        no_prelude: false,
+        edition,
+        lints: Vec::new(),
+        verbose: false,
+        quiet: true,
+        color: emc::cli::args::ColorWhen::Auto,
+        error_format: emc::cli::args::ErrorFormat::Human,
+        message_format: emc::cli::args::MessageFormat::Human,
+        tab_width: DEFAULT_TAB_WIDTH,
+        strict: false,
+        limits: Limits::default(),
+        dump_mir: None,
+        dump_mir_dir: std::path::PathBuf::from("mir_dump"),
+        print_pass_stats: false,
+        passes,
+        backend: if interp { BackendType::Interp } else { BackendType::Llvm },
+        explicit_backend: interp,
+    };
+
+    let mut repl = match Repl::new(config) {
+        Ok(repl) => repl,
+        Err(e) => {
+            Output::error(&e);
+            process::exit(1);
+        }
+    };
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        // a block is read til its `end`
+        let mut entry = String::new();
+        loop {
+            print!("{}", if entry.is_empty() { ">> " } else { ".. " });
+            let _ = std::io::stdout().flush();
+            let Some(Ok(line)) = lines.next() else {
+                return;
+            };
+            if entry.is_empty() && line.trim() == ":quit" {
+                return;
+            }
+            entry.push_str(&line);
+            entry.push('\n');
+            if !repl.is_incomplete(&entry) {
+                break;
+            }
+        }
+        if entry.trim().is_empty() {
+            continue;
+        }
+
+        let result = repl.eval(entry.trim_end());
+        display_diagnostics(repl.reporter(), ColorChoice::Auto, DEFAULT_TAB_WIDTH);
+        match result {
+            Ok(Some(value)) => println!("=> {}", value),
+            Ok(None) | Err(ReplError::Invalid(_)) => {}
+            Err(e) => Output::error(&e.to_string()),
+        }
+    }
+}
+
+fn handle_size(input: Option<&std::path::PathBuf>) {
+    let input = match input {
+        Some(i) => i.clone(),
+        None => {
+            Output::error("No object or binary specified for size command");
+            process::exit(1);
+        }
+    };
+
+    let bytes = match std::fs::read(&input) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            Output::error(&format!("Failed to read {}: {}", input.display(), e));
+            process::exit(1);
+        }
+    };
+
+    match emc::cli::size::SizeReport::from_elf(&bytes) {
+        Ok(report) => print!("{}", report.render()),
+        Err(e) => {
+            Output::error(&format!("{}: {}", input.display(), e));
+            process::exit(1);
+        }
+    }
+}
+
+fn handle_clean(dir: &std::path::Path, backend: Option<&str>, target: Option<String>, opt_level: Option<String>, stale: bool) {
+    let backend = match backend.map(|name| BackendType::from_str(name).ok_or(name)).transpose() {
+        Ok(backend) => backend,
+        Err(name) => {
+            let names: Vec<&str> = BackendType::ALL.iter().map(|b| b.as_str()).collect();
+            Output::error(&format!("Unknown backend '{}', expected one of: {}", name, names.join(", ")));
+            process::exit(1);
+        }
+    };
+    let filter = emc::cli::cache::CleanFilter { backend, target, opt_level, stale };
+    let registry = emc::backend::factory::BackendRegistry::new();
+    match emc::cli::cache::clean(dir, &filter, &registry) {
+        Ok(removed) => Output::info(&format!("Removed {} cached object(s) from {}", removed, dir.display())),
+        Err(e) => {
+            Output::error(&e);
+            process::exit(1);
+        }
+    }
+}
+
+fn handle_daemon(listen: &str, stop: bool) {
+    if stop {
+        match daemon::running().map(|endpoint| daemon::send(&endpoint, "STOP", &[])) {
+            Some(Ok(_)) => Output::info("Stopped the daemon"),
+            Some(Err(e)) => {
+                Output::error(&format!("Failed to stop the daemon: {}", e));
+                process::exit(1);
+            }
+            None => Output::info("No daemon is running"),
+        }
+        return;
+    }
+    if let Some(endpoint) = daemon::running() {
+        Output::error(&format!("A daemon is already running on {}", endpoint.address));
+        process::exit(1);
+    }
+    let listener = match std::net::TcpListener::bind(listen) {
+        Ok(listener) => listener,
+        Err(e) => {
+            Output::error(&format!("Failed to listen on {}: {}", listen, e));
+            process::exit(1);
+        }
+    };
+    if let Ok(address) = listener.local_addr() {
+        Output::info(&format!("Daemon listening on {}", address));
+    }
+    if let Err(e) = daemon::run(listener) {
+        Output::error(&e);
+        process::exit(1);
+    }
+}
+
+/// run this command line on the daemon + print what it did, returns if theres no daemon
+fn forward_to_daemon() {
+    let Some(endpoint) = daemon::running() else {
+        Output::warning("No daemon is running, building here. Start one with 'emerald daemon'");
+        return;
+    };
+    let request = daemon::DaemonRequest {
+        cwd: std::env::current_dir().unwrap_or_default(),
+        args: std::env::args().skip(1).filter(|arg| arg != "--daemon").collect(),
+        color: atty::is(atty::Stream::Stderr),
+    };
+    match daemon::send(&endpoint, "RUN", &request.encode()) {
+        Ok(reply) => {
+            eprint!("{}", reply.stderr);
+            print!("{}", reply.stdout);
+            process::exit(reply.code);
+        }
+        Err(e) => Output::warning(&format!("The daemon on {} failed, building here: {}", endpoint.address, e)),
+    }
+}
+
+fn handle_serve(listen: &str, cache: Option<std::path::PathBuf>, token_file: Option<std::path::PathBuf>) {
+    let token_file = token_file.unwrap_or_else(emc::cli::remote::token_file);
+    let token = match emc::cli::remote::load_or_create_token(&token_file) {
+        Ok(token) => token,
+        Err(e) => {
+            Output::error(&e);
+            process::exit(1);
+        }
+    };
+    let listener = match std::net::TcpListener::bind(listen) {
+        Ok(listener) => listener,
+        Err(e) => {
+            Output::error(&format!("Failed to listen on {}: {}", listen, e));
+            process::exit(1);
+        }
+    };
+    let mut runner = JobRunner::new(emc::backend::factory::BackendRegistry::new()).with_token(token);
+    if let Some(dir) = cache {
+        runner = runner.with_cache(dir);
+    }
+    Output::info(&format!("Serving codegen jobs on {}", listen));
+    Output::info(&format!("Clients need the token in {}, pass it with -C remote-token=FILE", token_file.display()));
+    if let Err(e) = std::sync::Arc::new(runner).serve(listener) {
+        Output::error(&format!("Server failed: {}", e));
+        process::exit(1);
     }
-    process::exit(1);
 }
 
 fn handle_check(input: Option<&std::path::PathBuf>) {
@@ -116,6 +503,8 @@ fn handle_check(input: Option<&std::path::PathBuf>) {
         }
     };
 
+    let edition = package_edition(&input);
+    let passes = package_passes(&input);
     let config = emc::cli::args::CompileConfig {
         input,
         output: None,
@@ -124,11 +513,40 @@ fn handle_check(input: Option<&std::path::PathBuf>) {
         emit: "binary".to_string(),
         library_paths: vec![],
         link_libs: vec![],
+        linker_script: None,
+        embed_bitcode: false,
+        codegen_units: 1,
+        debuginfo: DebugInfoLevel::None,
+        split_debuginfo: SplitDebugInfo::Off,
+        strip: Strip::None,
+        debuginfo_compression: None,
+        panic: PanicStrategy::Abort,
+        inline_threshold: DEFAULT_INLINE_THRESHOLD,
+        profile_use: None,
+        null_checks: true,
+        incremental: None,
+        builders: Vec::new(),
+        remote_token: None,
         crate_type: None,
+        visibility: SymbolVisibility::Default,
+        exports: Vec::new(),
+        no_prelude: false,
+        edition,
+        lints: Vec::new(),
         verbose: false,
         quiet: false,
         color: emc::cli::args::ColorWhen::Auto,
+        error_format: emc::cli::args::ErrorFormat::Human,
+        message_format: emc::cli::args::MessageFormat::Human,
+        tab_width: DEFAULT_TAB_WIDTH,
+        strict: false,
+        limits: Limits::default(),
+        dump_mir: None,
+        dump_mir_dir: std::path::PathBuf::from("mir_dump"),
+        print_pass_stats: false,
+        passes,
         backend: BackendType::Llvm, // dflt 2 llvm
+        explicit_backend: false,
     };
 
     let mut compiler = Compiler::new(config.clone());
@@ -147,3 +565,50 @@ fn handle_check(input: Option<&std::path::PathBuf>) {
         }
     }
 }
+
+/// --emit=grammar, the ebnf of the latest edition unless --edition picks one
+fn handle_grammar(edition: Option<&str>, output: Option<&std::path::PathBuf>) {
+    let edition = match edition {
+        Some(year) => Edition::from_str(year).unwrap_or_else(|| {
+            Output::error(&format!("Invalid edition '{}', expected 2025 or 2026", year));
+            process::exit(1);
+        }),
+        None => Edition::LATEST,
+    };
+    write_output(&emc::frontend::parser::grammar::ebnf(edition), output);
+}
+
+/// generated text 2 `-o` or stdout
+fn write_output(text: &str, output: Option<&std::path::PathBuf>) {
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, text) {
+                Output::error(&format!("Failed to write {}: {}", path.display(), e));
+                process::exit(1);
+            }
+        }
+        None => print!("{}", text),
+    }
+}
+
+/// the edition frm the input's emerald.toml, a broken manifest stops the command
+fn package_edition(input: &std::path::Path) -> Edition {
+    match Manifest::edition_for(input) {
+        Ok(edition) => edition,
+        Err(e) => {
+            Output::error(&e);
+            process::exit(1);
+        }
+    }
+}
+
+/// `[build] passes` of the package `input` is in
+fn package_passes(input: &std::path::Path) -> Option<PassPlan> {
+    match Manifest::passes_for(input).and_then(|spec| spec.as_deref().map(PassPlan::parse).transpose()) {
+        Ok(plan) => plan,
+        Err(e) => {
+            Output::error(&e);
+            process::exit(1);
+        }
+    }
+}
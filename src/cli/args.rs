@@ -1,15 +1,29 @@
+use clap::builder::TypedValueParser;
 use clap::{Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::backend::factory::BackendType;
+use crate::backend::link::{DebugCompression, SplitDebugInfo, Strip, SymbolVisibility};
+use crate::backend::ports::codegen::{DebugInfoLevel, PanicStrategy};
+use crate::backend::ports::emitter::EmitType;
+use crate::cli::manifest::Manifest;
+use crate::cli::passes::PassPlan;
+use crate::cli::remote::Builder;
+use crate::core::edition::Edition;
+use crate::core::mir::dump::DumpFilter;
+use crate::core::mir::passes::DEFAULT_INLINE_THRESHOLD;
+use crate::core::limits::Limits;
+use crate::core::optimizations::MirOptimizer;
+use crate::error::columns::DEFAULT_TAB_WIDTH;
+use crate::error::{Lint, LintLevel};
 
 #[derive(Parser, Debug)]
 #[command(name = "emerald")]
 #[command(about = "Emerald compiler - A systems programming language", long_about = None)]
 #[command(version)]
 pub struct Cli {
-    /// input suorce file
+    /// input source files, each one is compiled on its own
     #[arg(value_name = "INPUT")]
-    pub input: Option<PathBuf>,
+    pub inputs: Vec<PathBuf>,
 
     /// output file path
     #[arg(short, long, value_name = "FILE")]
@@ -43,6 +57,18 @@ pub struct Cli {
     #[arg(long)]
     pub native: bool,
 
+    /// use cranelift bcknd 4 fast debug builds
+    #[arg(long)]
+    pub cranelift: bool,
+
+    /// run on the MIR interpreter instead of a jit (`emerald run`)
+    #[arg(long)]
+    pub interp: bool,
+
+    /// use the null bcknd, the output is the MIR as text
+    #[arg(long)]
+    pub null: bool,
+
     /// lbrry search path
     #[arg(short = 'L', long, value_name = "PATH")]
     pub library_path: Vec<PathBuf>,
@@ -51,10 +77,71 @@ pub struct Cli {
     #[arg(short = 'l', long, value_name = "LIB")]
     pub link: Vec<String>,
 
+    /// linker script passed 2 the linker driver (-T)
+    #[arg(long, value_name = "FILE")]
+    pub linker_script: Option<PathBuf>,
+
+    /// emit debug info, same as -C debuginfo=2
+    #[arg(short = 'g')]
+    pub debug: bool,
+
+    /// strip debuginfo or symbols frm the linked output
+    #[arg(long, value_name = "WHAT")]
+    pub strip: Option<String>,
+
+    /// split codegen in2 N units compiled in parallel, only 4 linked binaries
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub codegen_units: usize,
+
+    /// codegen option eg -C embed-bitcode
+    #[arg(short = 'C', long = "codegen", value_name = "OPT")]
+    pub codegen: Vec<String>,
+
+    /// unstable option eg -Z strict, -Z limits=nesting=256, -Z dump-mir=dce
+    #[arg(short = 'Z', value_name = "OPT")]
+    pub unstable: Vec<String>,
+
     /// crate ytpe
     #[arg(long, value_name = "TYPE")]
     pub crate_type: Option<String>,
 
+    /// symbol visibility of libs: default exports evry fn, hidden only the --export'ed ones
+    #[arg(long, value_name = "VISIBILITY")]
+    pub visibility: Option<String>,
+
+    /// fn that stays visible w/ --visibility hidden
+    #[arg(long = "export", value_name = "SYMBOL")]
+    pub exports: Vec<String>,
+
+    /// leave out the prelude (Option, Result, List, assert) 4 freestanding builds
+    #[arg(long)]
+    pub no_prelude: bool,
+
+    /// after MIR optimization print what each pass changed
+    #[arg(long)]
+    pub print_pass_stats: bool,
+
+    /// MIR passes in the order they run + `llvm:` ones 4 the backend, eg `default,llvm:gvn`.
+    /// overrides `[build] passes` in emerald.toml
+    #[arg(long, value_name = "PASSES")]
+    pub passes: Option<String>,
+
+    /// turn a lint off eg -A deprecated
+    #[arg(short = 'A', long = "allow", value_name = "LINT")]
+    pub allow: Vec<String>,
+
+    /// report a lint as a warning
+    #[arg(short = 'W', long = "warn", value_name = "LINT")]
+    pub warn: Vec<String>,
+
+    /// make a lint an error
+    #[arg(short = 'D', long = "deny", value_name = "LINT")]
+    pub deny: Vec<String>,
+
+    /// language edition, overrides the one in emerald.toml
+    #[arg(long, value_name = "YEAR")]
+    pub edition: Option<String>,
+
     /// verbose output
     #[arg(short, long)]
     pub verbose: bool,
@@ -67,6 +154,22 @@ pub struct Cli {
     #[arg(long, value_enum, default_value = "auto")]
     pub color: ColorWhen,
 
+    /// how diagnostics r printed, json is one object per line 4 editors + build tools
+    #[arg(long, value_enum, default_value = "human")]
+    pub error_format: ErrorFormat,
+
+    /// json streams the build as JSON lines on stdout: phases, artifacts + diagnostics as they happen
+    #[arg(long, value_enum, default_value = "human")]
+    pub message_format: MessageFormat,
+
+    /// columns between tab stops when diagnostics point in2 a line
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_TAB_WIDTH, value_parser = clap::value_parser!(u8).range(1..).map(usize::from))]
+    pub tab_width: usize,
+
+    /// hand the build or check 2 the running `emerald daemon`, built here if none is running
+    #[arg(long)]
+    pub daemon: bool,
+
     /// sbcmmnd
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -92,6 +195,9 @@ pub enum Commands {
         input: Option<PathBuf>,
     },
 
+    /// interactive session on the jit, entries r read frm stdin
+    Repl,
+
     /// type chk w/o cdgn
     Check {
         /// input source file
@@ -113,12 +219,90 @@ pub enum Commands {
         input: Option<PathBuf>,
     },
 
+    /// size breakdown of an emitted object or binary
+    Size {
+        /// object file or executable
+        #[arg(value_name = "FILE")]
+        input: Option<PathBuf>,
+    },
+
+    /// list the backends + what each supports
+    Backends {
+        /// machine readable, an array w/ an object per backend
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// remove objects cached w/ -C incremental, all of them unless a filter is given
+    Clean {
+        /// the -C incremental dir
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+
+        /// only objects frm this backend
+        #[arg(long, value_name = "BACKEND")]
+        backend: Option<String>,
+
+        /// only objects 4 this target triple
+        #[arg(long, value_name = "TRIPLE")]
+        target: Option<String>,
+
+        /// only objects built at this opt lvl
+        #[arg(long, value_name = "LEVEL")]
+        opt_level: Option<String>,
+
+        /// only objects frm another emc or backend version, no build can reuse them
+        #[arg(long)]
+        stale: bool,
+    },
+
+    /// keep running + build 4 `--daemon` clients, reusing builds nothing changed since
+    Daemon {
+        /// address 2 listen on, a free local port by default
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:0")]
+        listen: String,
+
+        /// stop the running daemon
+        #[arg(long)]
+        stop: bool,
+    },
+
+    /// build codegen units sent by -C remote clients
+    Serve {
+        /// address 2 listen on, eg 0.0.0.0:7070
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7070")]
+        listen: String,
+
+        /// keep built objects here + answer repeated jobs frm it
+        #[arg(long, value_name = "DIR")]
+        cache: Option<PathBuf>,
+
+        /// the token clients must send, made if missing. copy it 2 clients + pass it w/
+        /// -C remote-token=FILE [default: emerald-serve-<user>.token in the temp dir]
+        #[arg(long, value_name = "FILE")]
+        token_file: Option<PathBuf>,
+    },
+
+    /// build codegen units read frm stdin, what -C workers spawns
+    #[command(hide = true)]
+    Worker,
+
     /// gen documentation
     Doc {
         /// input source file or driectory
         #[arg(value_name = "INPUT")]
         input: Option<PathBuf>,
     },
+
+    /// editor syntax highlighting defs frm the lexer's tokens
+    HighlightDefs {
+        #[arg(long, value_enum, default_value = "textmate")]
+        format: HighlightFormat,
+
+        /// output fil path, stdout if omitted
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -138,6 +322,26 @@ impl ColorWhen {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HighlightFormat {
+    /// `.tmLanguage.json` grammar
+    Textmate,
+    /// `highlights.scm` query
+    TreeSitter,
+}
+
 /// compilation configuration drvd from cli arguments
 #[derive(Debug, Clone)]
 pub struct CompileConfig {
@@ -148,25 +352,74 @@ pub struct CompileConfig {
     pub emit: String,
     pub library_paths: Vec<PathBuf>,
     pub link_libs: Vec<String>,
+    pub linker_script: Option<PathBuf>,
+    pub embed_bitcode: bool,
+    pub codegen_units: usize,
+    pub debuginfo: DebugInfoLevel,
+    pub split_debuginfo: SplitDebugInfo,
+    pub strip: Strip,
+    pub debuginfo_compression: Option<DebugCompression>,
+    pub panic: PanicStrategy,
+    /// -C inline-threshold: the most instructions a fn inlined at MIR level may have, 0 is off
+    pub inline_threshold: usize,
+    /// -C profile-use: call counts frm a training run 4 the inliner
+    pub profile_use: Option<PathBuf>,
+    /// -C null-checks: `.value` on a `ref?` panics on null at runtime
+    pub null_checks: bool,
+    /// -C incremental: dir objects r cached in, keyed by the backend's fingerprint
+    pub incremental: Option<PathBuf>,
+    /// -C workers / -C remote: where codegen units r built, in this process when empty
+    pub builders: Vec<Builder>,
+    /// -C remote-token: the file w/ the token the -C remote servers want, `remote::token_file` if unset
+    pub remote_token: Option<PathBuf>,
     pub crate_type: Option<String>,
+    pub visibility: SymbolVisibility,
+    pub exports: Vec<String>,
+    pub no_prelude: bool,
+    pub edition: Edition,
+    /// -A / -W / -D levels in that order, so -D beats -A
+    pub lints: Vec<(Lint, LintLevel)>,
     pub verbose: bool,
     pub quiet: bool,
     pub color: ColorWhen,
+    pub error_format: ErrorFormat,
+    pub message_format: MessageFormat,
+    pub tab_width: usize,
+    /// -Z strict: warnings, ICEs + output that differs between 2 lowerings all fail the build
+    pub strict: bool,
+    /// -Z limits: caps on literal sizes + nesting
+    pub limits: Limits,
+    /// -Z dump-mir: the passes whose MIR is written b4 + after, in2 `dump_mir_dir`
+    pub dump_mir: Option<DumpFilter>,
+    /// -Z dump-mir-dir, `mir_dump` if not given
+    pub dump_mir_dir: PathBuf,
+    pub print_pass_stats: bool,
+    /// --passes, the default pipelines when None
+    pub passes: Option<PassPlan>,
     pub backend: BackendType,
+    /// backend picked w/ a flag, no fallback if its not built in
+    pub explicit_backend: bool,
 }
 
 impl CompileConfig {
     pub fn from_cli(cli: &Cli) -> Result<Self, String> {
         let input = cli
-            .input
-            .clone()
+            .inputs
+            .first()
+            .cloned()
             .ok_or_else(|| "No input file specified".to_string())?;
 
         // determine backend: explicit flags take precedence dflt 2 llvm
         let backend = if cli.native {
             BackendType::Native
+        } else if cli.cranelift {
+            BackendType::Cranelift
         } else if cli.llvm {
             BackendType::Llvm
+        } else if cli.interp {
+            BackendType::Interp
+        } else if cli.null {
+            BackendType::Null
         } else {
             // deault 2 llvm
             BackendType::Llvm
@@ -181,19 +434,246 @@ impl CompileConfig {
             cli.emit.clone()
         };
 
-        Ok(CompileConfig {
+        // -C key[=value] codegen options
+        let mut embed_bitcode = false;
+        let mut debuginfo = if cli.debug { DebugInfoLevel::Full } else { DebugInfoLevel::None };
+        let mut split_debuginfo = SplitDebugInfo::Off;
+        let mut debuginfo_compression = None;
+        let mut panic = PanicStrategy::Abort;
+        let mut inline_threshold = DEFAULT_INLINE_THRESHOLD;
+        let mut null_checks = true;
+        let mut profile_use = None;
+        let mut incremental = None;
+        let mut builders = Vec::new();
+        let mut remote_token = None;
+        for opt in &cli.codegen {
+            if let Some(level) = opt.strip_prefix("debuginfo=") {
+                debuginfo = DebugInfoLevel::from_str(level)
+                    .ok_or_else(|| format!("Invalid debuginfo level '{}', expected 0, 1 or 2", level))?;
+                continue;
+            }
+            if let Some(kind) = opt.strip_prefix("split-debuginfo=") {
+                split_debuginfo = SplitDebugInfo::from_str(kind)
+                    .ok_or_else(|| format!("Invalid split-debuginfo '{}', expected off, packed or unpacked", kind))?;
+                continue;
+            }
+            if let Some(kind) = opt.strip_prefix("debuginfo-compression=") {
+                debuginfo_compression = match kind {
+                    "none" => None,
+                    _ => Some(DebugCompression::from_str(kind).ok_or_else(|| {
+                        format!("Invalid debuginfo-compression '{}', expected none, zlib or zstd", kind)
+                    })?),
+                };
+                continue;
+            }
+            if let Some(n) = opt.strip_prefix("inline-threshold=") {
+                inline_threshold = parse_inline_threshold(n)?;
+                continue;
+            }
+            if let Some(path) = opt.strip_prefix("profile-use=") {
+                profile_use = Some(PathBuf::from(path));
+                continue;
+            }
+            if let Some(dir) = opt.strip_prefix("incremental=") {
+                incremental = Some(PathBuf::from(dir));
+                continue;
+            }
+            if let Some(n) = opt.strip_prefix("workers=") {
+                let n: usize = n.parse().map_err(|_| format!("Invalid workers '{}', expected a number", n))?;
+                builders.extend(std::iter::repeat_n(Builder::Worker, n));
+                continue;
+            }
+            if let Some(file) = opt.strip_prefix("remote-token=") {
+                remote_token = Some(PathBuf::from(file));
+                continue;
+            }
+            if let Some(addresses) = opt.strip_prefix("remote=") {
+                builders.extend(addresses.split(',').filter(|a| !a.is_empty()).map(|a| Builder::Remote(a.to_string())));
+                continue;
+            }
+            if let Some(strategy) = opt.strip_prefix("panic=") {
+                panic = match PanicStrategy::from_str(strategy) {
+                    Some(PanicStrategy::Unwind) => return Err("-C panic=unwind is not supported yet, panics always abort".to_string()),
+                    Some(strategy) => strategy,
+                    None => return Err(format!("Invalid panic strategy '{}', expected unwind or abort", strategy)),
+                };
+                continue;
+            }
+            match opt.as_str() {
+                "embed-bitcode" | "embed-bitcode=yes" | "embed-bitcode=y" | "embed-bitcode=on" => embed_bitcode = true,
+                "embed-bitcode=no" | "embed-bitcode=n" | "embed-bitcode=off" => embed_bitcode = false,
+                "null-checks" | "null-checks=yes" | "null-checks=y" | "null-checks=on" => null_checks = true,
+                "null-checks=no" | "null-checks=n" | "null-checks=off" => null_checks = false,
+                other => return Err(format!("Unknown codegen option: {}", other)),
+            }
+        }
+
+        let mut strict = false;
+        let mut limits = Limits::default();
+        let mut dump_mir = None;
+        let mut dump_mir_dir = PathBuf::from("mir_dump");
+        for opt in &cli.unstable {
+            match opt.as_str() {
+                "strict" => strict = true,
+                other => {
+                    if let Some(spec) = other.strip_prefix("limits=") {
+                        limits = Limits::parse(spec)?;
+                    } else if let Some(n) = other.strip_prefix("inline-threshold=") {
+                        inline_threshold = parse_inline_threshold(n)?;
+                    } else if let Some(dir) = other.strip_prefix("dump-mir-dir=") {
+                        dump_mir_dir = PathBuf::from(dir);
+                    } else if let Some(pass) = other.strip_prefix("dump-mir=") {
+                        dump_mir = Some(parse_dump_filter(pass)?);
+                    } else {
+                        return Err(format!("Unknown -Z option: {}", other));
+                    }
+                }
+            }
+        }
+
+        let strip = match cli.strip {
+            Some(ref what) => Strip::from_str(what)
+                .ok_or_else(|| format!("Invalid strip '{}', expected none, debuginfo or symbols", what))?,
+            None => Strip::None,
+        };
+
+        let visibility = match cli.visibility {
+            Some(ref v) => SymbolVisibility::from_str(v)
+                .ok_or_else(|| format!("Invalid visibility '{}', expected default or hidden", v))?,
+            None => SymbolVisibility::Default,
+        };
+
+        let edition = match cli.edition {
+            Some(ref year) => Edition::from_str(year)
+                .ok_or_else(|| format!("Invalid edition '{}', expected 2025 or 2026", year))?,
+            None => Manifest::edition_for(&input)?,
+        };
+        let passes = pass_plan(cli, &input)?;
+
+        let mut lints = Vec::new();
+        for (names, level) in [(&cli.allow, LintLevel::Allow), (&cli.warn, LintLevel::Warn), (&cli.deny, LintLevel::Deny)] {
+            for name in names {
+                let lint = Lint::from_str(name).ok_or_else(|| format!("Unknown lint '{}'", name))?;
+                lints.push((lint, level));
+            }
+        }
+
+        let mut config = CompileConfig {
             input,
-            output: cli.output.clone(),
+            output: None,
             target: cli.target.clone(),
             opt_level: cli.opt_level.clone(),
             emit,
             library_paths: cli.library_path.clone(),
             link_libs: cli.link.clone(),
+            linker_script: cli.linker_script.clone(),
+            embed_bitcode,
+            codegen_units: cli.codegen_units,
+            debuginfo,
+            split_debuginfo,
+            strip,
+            debuginfo_compression,
+            panic,
+            inline_threshold,
+            profile_use,
+            null_checks,
+            incremental,
+            builders,
+            remote_token,
             crate_type: cli.crate_type.clone(),
+            visibility,
+            exports: cli.exports.clone(),
             verbose: cli.verbose,
+            no_prelude: cli.no_prelude,
+            edition,
+            lints,
             quiet: cli.quiet,
             color: cli.color,
+            error_format: cli.error_format,
+            message_format: cli.message_format,
+            tab_width: cli.tab_width,
+            strict,
+            limits,
+            dump_mir,
+            dump_mir_dir,
+            print_pass_stats: cli.print_pass_stats,
+            passes,
             backend,
+            explicit_backend: cli.native || cli.cranelift || cli.llvm || cli.interp || cli.null,
+        };
+        let emit_type = config.emit_type()?;
+        config.output = Some(cli.output.clone().unwrap_or_else(|| emit_type.default_output(&config.input)));
+        Ok(config)
+    }
+
+    /// a config per input file, like from_cli 4 each. -o names 1 file so it only goes w/ 1 input
+    pub fn for_inputs(cli: &Cli) -> Result<Vec<Self>, String> {
+        let config = Self::from_cli(cli)?;
+        if cli.inputs.len() > 1 && cli.output.is_some() {
+            return Err("Cannot use -o with multiple input files".to_string());
+        }
+        let emit_type = config.emit_type()?;
+        cli.inputs
+            .iter()
+            .map(|input| {
+                let edition = match cli.edition {
+                    Some(_) => config.edition,
+                    None => Manifest::edition_for(input)?,
+                };
+                Ok(CompileConfig {
+                    input: input.clone(),
+                    output: Some(cli.output.clone().unwrap_or_else(|| emit_type.default_output(input))),
+                    edition,
+                    passes: pass_plan(cli, input)?,
+                    ..config.clone()
+                })
+            })
+            .collect()
+    }
+
+    /// w/ relative paths taken frm `dir` instead of the cwd, 4 a build run on a client's behalf
+    pub fn rooted(mut self, dir: &Path) -> Self {
+        self.input = dir.join(&self.input);
+        self.output = self.output.map(|output| dir.join(output));
+        self.library_paths = self.library_paths.iter().map(|path| dir.join(path)).collect();
+        self.linker_script = self.linker_script.map(|script| dir.join(script));
+        self.profile_use = self.profile_use.map(|profile| dir.join(profile));
+        self.incremental = self.incremental.map(|cache| dir.join(cache));
+        self.remote_token = self.remote_token.map(|file| dir.join(file));
+        self.dump_mir_dir = dir.join(&self.dump_mir_dir);
+        self
+    }
+
+    /// what --emit asks 4, a library --crate-type turns a linked binary in2 that library
+    pub fn emit_type(&self) -> Result<EmitType, String> {
+        let emit_type = EmitType::from_str(&self.emit).ok_or_else(|| format!("Unknown emit type: {}", self.emit))?;
+        Ok(match (emit_type, self.crate_type.as_deref()) {
+            (EmitType::Binary, Some("staticlib")) => EmitType::StaticLib,
+            (EmitType::Binary, Some("dylib" | "cdylib")) => EmitType::SharedLib,
+            (emit_type, _) => emit_type,
         })
     }
 }
+
+/// -C / -Z inline-threshold=N
+fn parse_inline_threshold(n: &str) -> Result<usize, String> {
+    n.parse().map_err(|_| format!("Invalid inline-threshold '{}', expected a number of instructions", n))
+}
+
+/// --passes, or the `[build] passes` of `input`'s package w/o it
+fn pass_plan(cli: &Cli, input: &Path) -> Result<Option<PassPlan>, String> {
+    let spec = match &cli.passes {
+        Some(spec) => Some(spec.clone()),
+        None => Manifest::passes_for(input)?,
+    };
+    spec.as_deref().map(PassPlan::parse).transpose()
+}
+
+/// -Z dump-mir=all or the name of 1 MIR pass
+fn parse_dump_filter(pass: &str) -> Result<DumpFilter, String> {
+    let names = MirOptimizer::new().pass_names();
+    if pass != "all" && !names.contains(&pass) {
+        return Err(format!("Unknown MIR pass '{}' for dump-mir, expected all or one of: {}", pass, names.join(", ")));
+    }
+    Ok(DumpFilter::parse(pass))
+}
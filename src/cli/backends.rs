@@ -0,0 +1,66 @@
+use crate::backend::factory::{BackendCapabilities, BackendRegistry, BackendType, TargetSupport};
+use crate::cli::error_display::json_string;
+use crate::core::target::TargetInfo;
+
+/// `emerald backends`, 1 line per backend, the ones not built in say how 2 get them
+pub fn describe(registry: &BackendRegistry) -> String {
+    let default = registry.default_factory().backend_type();
+    let mut lines = vec![format!("{:<12}{:<16}{:<12}{:<6}{}", "BACKEND", "TARGETS", "DEBUG INFO", "JIT", "INPUT")];
+    for backend in BackendType::ALL {
+        let name = match backend == default {
+            true => format!("{} *", backend.as_str()),
+            false => backend.as_str().to_string(),
+        };
+        let line = match registry.get_factory(backend) {
+            Some(factory) => {
+                let caps = factory.capabilities();
+                let targets = match caps.targets {
+                    TargetSupport::Host => format!("host ({})", TargetInfo::host().arch),
+                    TargetSupport::Any => caps.targets.as_str().to_string(),
+                };
+                format!("{:<12}{:<16}{:<12}{:<6}{}", name, targets, yes_no(caps.debug_info), yes_no(caps.jit), caps.preferred_input.as_str())
+            }
+            None => format!("{:<12}{}", name, unavailable(backend)),
+        };
+        lines.push(line.trim_end().to_string());
+    }
+    lines.push("* the default".to_string());
+    lines.join("\n")
+}
+
+/// `emerald backends --json` 4 tooling, an array w/ an object per backend
+pub fn describe_json(registry: &BackendRegistry) -> String {
+    let default = registry.default_factory().backend_type();
+    let entries: Vec<String> = BackendType::ALL
+        .iter()
+        .map(|&backend| {
+            let fields = match registry.get_factory(backend) {
+                Some(factory) => capabilities_json(&factory.capabilities()),
+                None => format!("\"available\":false,\"reason\":{}", json_string(&unavailable(backend))),
+            };
+            format!("{{\"name\":{},\"default\":{},{}}}", json_string(backend.as_str()), backend == default, fields)
+        })
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+fn capabilities_json(caps: &BackendCapabilities) -> String {
+    format!(
+        "\"available\":true,\"targets\":{},\"debug_info\":{},\"jit\":{},\"preferred_input\":{}",
+        json_string(caps.targets.as_str()),
+        caps.debug_info,
+        caps.jit,
+        json_string(caps.preferred_input.as_str())
+    )
+}
+
+fn unavailable(backend: BackendType) -> String {
+    match backend.feature() {
+        Some(feature) => format!("not built in, rebuild emc with --features {}", feature),
+        None => "not implemented yet".to_string(),
+    }
+}
+
+fn yes_no(b: bool) -> &'static str {
+    if b { "yes" } else { "no" }
+}
@@ -0,0 +1,236 @@
+use crate::backend::factory::{BackendRegistry, BackendType};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// evrything besides the MIR that decides what a backend emits, objects built under 1 fingerprint
+/// r never handed out under another so switching backends or flags cant serve a stale object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendFingerprint {
+    /// emc's own version, MIR lowering + codegen change between releases
+    pub compiler: String,
+    pub backend: BackendType,
+    /// `BackendFactory::version`, eg the LLVM major
+    pub version: String,
+    pub target: String,
+    pub opt_level: String,
+    /// the rest of the codegen flags as `key`, `value`, eg panic + llvm passes
+    pub settings: Vec<(String, String)>,
+}
+
+impl BackendFingerprint {
+    pub const FILE_NAME: &'static str = "fingerprint.toml";
+
+    pub fn new(backend: BackendType, version: String, target: String, opt_level: String) -> Self {
+        Self { compiler: env!("CARGO_PKG_VERSION").to_string(), backend, version, target, opt_level, settings: Vec::new() }
+    }
+
+    pub fn with_setting(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.settings.push((key.to_string(), value.into()));
+        self
+    }
+
+    /// the dir its objects live in, readable names 1st so `ls` shows what each one is 4
+    pub fn dir_name(&self) -> String {
+        format!("{}-{}-O{}-{:016x}", self.backend.as_str(), self.target, self.opt_level, stable_hash(self.to_toml().as_bytes()))
+    }
+
+    /// `key = "value"` lines like the manifest, `emerald clean` reads them back 2 pick what 2 remove
+    pub fn to_toml(&self) -> String {
+        let mut lines = vec![
+            format!("compiler = \"{}\"", self.compiler),
+            format!("backend = \"{}\"", self.backend.as_str()),
+            format!("version = \"{}\"", self.version),
+            format!("target = \"{}\"", self.target),
+            format!("opt-level = \"{}\"", self.opt_level),
+        ];
+        lines.extend(self.settings.iter().map(|(key, value)| format!("{} = \"{}\"", key, value)));
+        lines.join("\n") + "\n"
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut fingerprint = Self::new(BackendType::Null, String::new(), String::new(), String::new());
+        let mut backend = None;
+        for line in text.lines() {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim().strip_prefix('"')?.strip_suffix('"')?.to_string();
+            match key.trim() {
+                "compiler" => fingerprint.compiler = value,
+                "backend" => backend = BackendType::from_str(&value),
+                "version" => fingerprint.version = value,
+                "target" => fingerprint.target = value,
+                "opt-level" => fingerprint.opt_level = value,
+                key => fingerprint.settings.push((key.to_string(), value)),
+            }
+        }
+        fingerprint.backend = backend?;
+        Some(fingerprint)
+    }
+
+    /// built by another emc or a backend version this one doesnt have, nothing will reuse it
+    pub fn is_stale(&self, registry: &BackendRegistry) -> bool {
+        self.compiler != env!("CARGO_PKG_VERSION")
+            || registry.get_factory(self.backend).is_none_or(|factory| factory.version() != self.version)
+    }
+}
+
+/// objects compiled under 1 fingerprint, each named by a digest of what went in2 it
+pub struct ArtifactCache {
+    dir: PathBuf,
+}
+
+impl ArtifactCache {
+    /// the fingerprint's dir under `root` (-C incremental), created on 1st use
+    pub fn open(root: &Path, fingerprint: &BackendFingerprint) -> Result<Self, String> {
+        let dir = root.join(fingerprint.dir_name());
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create cache dir {}: {}", dir.display(), e))?;
+        let file = dir.join(BackendFingerprint::FILE_NAME);
+        if !file.is_file() {
+            fs::write(&file, fingerprint.to_toml()).map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+        }
+        Ok(Self { dir })
+    }
+
+    /// key 4 an object frm the text of its inputs, eg the MIR + debug info
+    pub fn key(inputs: &[&str]) -> Digest {
+        let mut bytes = Vec::new();
+        for input in inputs {
+            bytes.extend_from_slice(input.as_bytes());
+            bytes.push(0);
+        }
+        sha256(&bytes)
+    }
+
+    pub fn object(&self, key: Digest) -> PathBuf {
+        self.dir.join(format!("{}.o", key))
+    }
+
+    /// copy the cached object 2 `dest`, false if theres none
+    pub fn fetch(&self, key: Digest, dest: &Path) -> bool {
+        let object = self.object(key);
+        object.is_file() && fs::copy(&object, dest).is_ok()
+    }
+
+    /// keep a copy of `object`, written aside + renamed so a build reading it never sees half of it
+    pub fn store(&self, key: Digest, object: &Path) -> Result<(), String> {
+        let cached = self.object(key);
+        let partial = cached.with_extension(format!("o.{}.part", std::process::id()));
+        fs::copy(object, &partial)
+            .and_then(|_| fs::rename(&partial, &cached))
+            .map_err(|e| format!("Failed to cache {}: {}", object.display(), e))
+    }
+}
+
+/// what `emerald clean` removes, evry fingerprint when nothing is set
+#[derive(Debug, Clone, Default)]
+pub struct CleanFilter {
+    pub backend: Option<BackendType>,
+    pub target: Option<String>,
+    pub opt_level: Option<String>,
+    /// only fingerprints no build w/ this emc can hit
+    pub stale: bool,
+}
+
+impl CleanFilter {
+    pub fn matches(&self, fingerprint: &BackendFingerprint, registry: &BackendRegistry) -> bool {
+        self.backend.is_none_or(|backend| backend == fingerprint.backend)
+            && self.target.as_ref().is_none_or(|target| *target == fingerprint.target)
+            && self.opt_level.as_ref().is_none_or(|level| *level == fingerprint.opt_level)
+            && (!self.stale || fingerprint.is_stale(registry))
+    }
+}
+
+/// remove the fingerprint dirs under `root` `filter` matches, returns the objects that went w/ them.
+/// a dir w/o a readable fingerprint isnt the cache's, its never touched, not even w/o a filter
+pub fn clean(root: &Path, filter: &CleanFilter, registry: &BackendRegistry) -> Result<usize, String> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read {}: {}", root.display(), e)),
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        let fingerprint = fs::read_to_string(dir.join(BackendFingerprint::FILE_NAME))
+            .ok()
+            .and_then(|text| BackendFingerprint::parse(&text));
+        if !fingerprint.is_some_and(|fingerprint| filter.matches(&fingerprint, registry)) {
+            continue;
+        }
+        let objects = fs::read_dir(&dir)
+            .map(|entries| entries.flatten().filter(|e| e.path().extension().is_some_and(|ext| ext == "o")).count())
+            .unwrap_or(0);
+        fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+        removed += objects;
+    }
+    Ok(removed)
+}
+
+/// FNV-1a, the same on evry run + platform unlike std's hasher
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// a SHA-256, what cached objects r named by. unlike `stable_hash` no 1 can craft a job that
+/// collides w/ another 2 get their object handed out in its place
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Digest(pub [u8; 32]);
+
+impl std::fmt::Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// FIPS 180-4 SHA-256
+pub fn sha256(bytes: &[u8]) -> Digest {
+    let mut state: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+    // 0x80, zeros up 2 56 mod 64, then the length in bits big endian
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((bytes.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(SHA256_K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (out, word) in digest.chunks_exact_mut(4).zip(state) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    Digest(digest)
+}
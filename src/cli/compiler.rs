@@ -1,32 +1,68 @@
-use crate::cli::args::CompileConfig;
-use crate::cli::error_display::{count_diagnostics, display_diagnostics};
+use crate::cli::args::{CompileConfig, ErrorFormat, MessageFormat};
+use crate::cli::cache::{stable_hash, ArtifactCache, BackendFingerprint, Digest};
+use crate::cli::error_display::{count_diagnostics, diagnostic_json, display_diagnostics, display_diagnostics_json, json_string};
+use crate::cli::events::{BuildEvent, EventStream};
+use crate::cli::ice::{self, IceReport};
 use crate::cli::output::Output;
 use crate::cli::progress::{CompilePhase, ProgressTracker};
+use crate::cli::remote::{read_token, token_file, Builder, Dispatcher, RemoteJob};
+use crate::core::ast::{Ast, Item};
 use crate::core::hir::Hir;
-use crate::core::mir::MirFunction;
+use crate::core::mir::dump::MirDump;
+use crate::core::mir::passes::PassStats;
+use crate::core::mir::profile::CallProfile;
+use crate::core::mir::{MirFunction, MirModule};
 use crate::core::optimizations::{HirOptimizer, MirOptimizer};
+use crate::core::target::TargetInfo;
 use crate::error::Reporter;
 use crate::frontend::lexer::Lexer;
 use crate::frontend::parser::Parser;
-use crate::frontend::semantic::SemanticAnalyzer;
+use crate::frontend::semantic::symbol_table::SymbolTable;
+use crate::frontend::semantic::{Prelude, SemanticAnalyzer};
 use crate::middle::{HirLowerer, MirLowerer};
-use crate::backend::{BackendBridge, BackendRegistry, BackendType};
-use crate::backend::ports::codegen::OptimizationLevel;
+use crate::backend::{debugger, BackendBridge, BackendFactory, BackendRegistry, BackendType};
+use crate::backend::codegen_units::partition;
+use crate::backend::ports::codegen::{BackendInput, BackendInputType, DebugInfo, DebugInfoLevel, OptimizationLevel};
+use crate::backend::link::{LinkOptions, LinkerFlavor, SplitDebugInfo, Strip};
 use crate::backend::ports::emitter::EmitType;
-use codespan::Files;
+use codespan::{FileId, Files};
 use codespan_reporting::term::termcolor::ColorChoice;
+use std::collections::HashMap;
+use rayon::prelude::*;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+/// one object compiled b4 the output is linked
+struct ObjectJob<'a> {
+    /// 4 errors, eg "module 'util'"
+    what: String,
+    functions: &'a [MirFunction],
+    /// fns 2 define, none = all of them
+    unit: Option<Vec<String>>,
+    object: PathBuf,
+}
+
 /// cmpltn result - provides access to both HIR and MIR
 /// some backends use HIR (higher level) others use MIR (lower level)
 #[derive(Debug)]
 pub struct CompileResult {
     pub mir_functions: Vec<MirFunction>,
+    /// one unit per imported module, compiled separately
+    pub modules: Vec<MirModule>,
     pub hir: Option<Hir>,
     pub reporter: Reporter,
     pub success: bool,
     pub ast: Option<crate::core::ast::Ast>,
+    /// fns whose MIR came out different when the program was lowered again, only checked w/ -Z strict
+    pub nondeterministic: Vec<String>,
+    /// what the MIR passes did over the program's fns, printed w/ --print-pass-stats
+    pub pass_stats: Vec<PassStats>,
+    /// what the MIR passes did 2 particular fns, printed w/ --verbose
+    pub remarks: Vec<String>,
+    /// the input + evry file it imports, what the output is built frm
+    pub sources: Vec<PathBuf>,
 }
 
 impl CompileResult {
@@ -51,106 +87,484 @@ impl CompileResult {
     }
 }
 
+/// what lexing, parsing + analysis made of 1 input
+#[derive(Clone)]
+struct FrontEnd {
+    /// `Compiler::front_end_key` of the build that made it
+    key: u64,
+    /// the input + evry file it imports w/ a hash of what it held
+    sources: Vec<(PathBuf, u64)>,
+    /// the files read + the diagnostics so far
+    reporter: Reporter,
+    file_id: FileId,
+    /// the program w/ the prelude in
+    ast: Ast,
+    prelude_assert: bool,
+    symbol_table: SymbolTable,
+    /// each imported module's name, ast + symbols
+    imports: Vec<(String, Ast, SymbolTable)>,
+}
+
+/// front end results by input, the daemon keeps 1 across its builds so an unchanged input isnt
+/// lexed, parsed + analyzed again. lowering + codegen still run evry build
+#[derive(Clone, Default)]
+pub struct FrontEndCache(Arc<Mutex<HashMap<PathBuf, FrontEnd>>>);
+
+impl FrontEndCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// how many inputs it holds
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `input`'s results if they were made w/ `key` + none of its sources changed since
+    fn get(&self, input: &Path, key: u64) -> Option<FrontEnd> {
+        let unchanged = |(path, hash): &(PathBuf, u64)| fs::read(path).is_ok_and(|bytes| stable_hash(&bytes) == *hash);
+        let cache = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(input).filter(|front_end| front_end.key == key && front_end.sources.iter().all(unchanged)).cloned()
+    }
+
+    fn insert(&self, input: &Path, front_end: FrontEnd) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).insert(input.to_path_buf(), front_end);
+    }
+}
+
 /// compiler orchestrator
 pub struct Compiler {
     config: CompileConfig,
     progress: ProgressTracker,
+    /// -C profile-use, read once the compile starts
+    profile: Option<Arc<CallProfile>>,
+    /// --message-format=json
+    events: Option<EventStream>,
+    /// diagnostics already sent as events
+    reported: usize,
+    /// results of earlier builds 2 reuse, set by the daemon
+    front_ends: Option<FrontEndCache>,
 }
 
 impl Compiler {
     pub fn new(config: CompileConfig) -> Self {
         let verbose = config.verbose;
-        Self {
+        let events = (config.message_format == MessageFormat::Json).then(|| EventStream::stdout(&config.input));
+        let compiler = Self {
             config,
             progress: ProgressTracker::new(verbose),
+            profile: None,
+            events: None,
+            reported: 0,
+            front_ends: None,
+        };
+        match events {
+            Some(events) => compiler.with_events(events),
+            None => compiler,
         }
     }
 
-    /// compile the input file
+    /// send the build's events 2 `events` instead of stdout
+    pub fn with_events(mut self, events: EventStream) -> Self {
+        self.progress = ProgressTracker::new(self.config.verbose).with_events(events.clone());
+        self.events = Some(events);
+        self
+    }
+
+    /// reuse the front end results in `cache` + keep this build's there
+    pub fn with_front_ends(mut self, cache: FrontEndCache) -> Self {
+        self.front_ends = Some(cache);
+        self
+    }
+
+    /// compile the input file, a panic in any phase comes back as `CompileError::Internal`
     pub fn compile(&mut self) -> Result<CompileResult, CompileError> {
+        self.emit(BuildEvent::BuildStarted);
+        let result = ice::catch(|| self.compile_phases()).unwrap_or_else(|panic| Err(self.internal_error(panic)));
+        let error = result.as_ref().err().map(|e| e.to_string());
+        let success = result.as_ref().is_ok_and(|r| r.success);
+        self.emit(BuildEvent::BuildFinished { success, error: error.as_deref() });
+        result
+    }
+
+    fn emit(&self, event: BuildEvent) {
+        if let Some(events) = &self.events {
+            events.emit(event);
+        }
+    }
+
+    /// start `phase`, the diagnostics the last 1 reported r streamed 1st
+    fn advance(&mut self, phase: CompilePhase, reporter: &Reporter) {
+        if let Some(events) = &self.events {
+            for diag in &reporter.diagnostics()[self.reported..] {
+                events.emit(BuildEvent::Diagnostic(diagnostic_json(diag, reporter.files(), self.config.tab_width)));
+            }
+            self.reported = reporter.diagnostics().len();
+        }
+        self.progress.set_phase(phase);
+    }
+
+    /// `path` was written, `cached` if it was copied frm -C incremental's cache
+    fn artifact(&self, kind: &str, path: &std::path::Path, cached: bool) {
+        self.emit(BuildEvent::Artifact { kind, path, cached });
+    }
+
+    /// the ICE report 4 `panic`, written 2 the temp dir
+    fn internal_error(&self, panic: ice::Panic) -> CompileError {
+        let mut report = IceReport::new(self.progress.current_phase(), &self.config.input, panic);
+        // the report itself says so when it couldnt be written
+        let _ = report.write_to(&std::env::temp_dir());
+        CompileError::Internal(Box::new(report))
+    }
+
+    fn compile_phases(&mut self) -> Result<CompileResult, CompileError> {
         let start_time = Instant::now();
+        self.check_backend()?;
 
         // load source file
         self.progress.set_phase(CompilePhase::Loading);
         let source = self.load_source()?;
+        self.profile = self.load_profile()?;
 
         if self.config.verbose {
             Output::processing_file(self.config.input.to_string_lossy().as_ref());
         }
 
+        let front_end = match self.front_ends.as_ref().and_then(|cache| cache.get(&self.config.input, self.front_end_key())) {
+            Some(front_end) => front_end,
+            None => {
+                let front_end = self.front_end(&source);
+                if let Some(cache) = &self.front_ends {
+                    cache.insert(&self.config.input, front_end.clone());
+                }
+                front_end
+            }
+        };
+        let FrontEnd { reporter, file_id, ast, prelude_assert, symbol_table, imports, sources, .. } = front_end;
+        let target = self.target_info();
+        let modules: Vec<MirModule> = imports.iter().map(|(name, module_ast, symbols)| self.lower_module(name, module_ast, symbols, &target)).collect();
+        let sources: Vec<PathBuf> = sources.into_iter().map(|(path, _)| path).collect();
+
+        // -Z strict lowers the program a 2nd time 2 compare, the lowerers consume these
+        let rerun = (self.config.strict && !reporter.has_errors()).then(|| (symbol_table.clone(), target.clone()));
+
+        // hir lowering
+        self.advance(CompilePhase::HirLowering, &reporter);
+        let mut hir_lowerer = HirLowerer::new(symbol_table).with_target(target);
+        let mut hir = hir_lowerer.lower(&ast);
+
+        // hir optmztn
+        self.advance(CompilePhase::HirOptimization, &reporter);
+        let mut hir_optimizer = HirOptimizer::new();
+        hir_optimizer.optimize(&mut hir);
+
+        // mir lwrng
+        self.advance(CompilePhase::MirLowering, &reporter);
+        // the lowerer is made again 4 -Z strict, w/o holding on2 self
+        let (input, null_checks) = (self.config.input.to_string_lossy().to_string(), self.config.null_checks);
+        let mir_lowerer = || {
+            let lowerer = MirLowerer::new()
+                .with_source(input.as_str(), &source)
+                .with_null_checks(null_checks);
+            if prelude_assert {
+                lowerer.with_prelude_assert()
+            } else {
+                lowerer
+            }
+        };
+        let mut mir_functions = mir_lowerer().lower(&hir);
+
+        // mir optimization
+        self.advance(CompilePhase::MirOptimization, &reporter);
+        let mut mir_optimizer = self.mir_optimizer();
+        if let Some(filter) = &self.config.dump_mir {
+            mir_optimizer = mir_optimizer.with_dump(MirDump::new(&self.config.dump_mir_dir, filter.clone()));
+        }
+        mir_optimizer.optimize_all(&mut mir_functions);
+        if let Some(error) = mir_optimizer.dump_error() {
+            return Err(CompileError::IoError(error.to_string()));
+        }
+        let pass_stats = mir_optimizer.pass_stats();
+        let remarks = mir_optimizer.remarks();
+        let nondeterministic = rerun
+            .map(|(symbols, target)| Self::nondeterministic(&mir_functions, &Self::lower_to_mir(&ast, &symbols, &target, mir_lowerer(), self.mir_optimizer())))
+            .unwrap_or_default();
+
+        // backend code generation, theres nothing 2 generate frm a program w/ errors
+        if self.should_run_backend() && !reporter.has_errors() {
+            if self.emits_mir() {
+                self.write_mir(&mir_functions, &modules).map_err(CompileError::IoError)?;
+            } else {
+                self.advance(CompilePhase::CodeGeneration, &reporter);
+                let debug_info = self.debug_info(&ast, &reporter, file_id);
+                self.run_backend(Some(&hir), &mir_functions, &modules, debug_info)
+                    .map_err(CompileError::BackendFailed)?;
+            }
+        }
+
+        let _elapsed = start_time.elapsed().as_millis() as u64;
+        self.advance(CompilePhase::Complete, &reporter);
+
+        let (_, warnings) = count_diagnostics(&reporter);
+        let strict_failed = self.config.strict && (warnings > 0 || !nondeterministic.is_empty());
+        let success = !reporter.has_errors() && !strict_failed;
+
+        Ok(CompileResult {
+            mir_functions,
+            modules,
+            hir: Some(hir),
+            reporter,
+            success,
+            ast: Some(ast),
+            nondeterministic,
+            pass_stats,
+            remarks,
+            sources,
+        })
+    }
+
+    /// lex, parse + analyze `source`, the input's text
+    fn front_end(&mut self, source: &str) -> FrontEnd {
         // initialize rprtr and files
         let mut files = Files::new();
         let file_id = files.add(
             self.config.input.to_string_lossy().to_string(),
-            source.clone(),
+            source.to_string(),
         );
         let mut reporter = Reporter::new();
         *reporter.files_mut() = files;
+        for &(lint, level) in &self.config.lints {
+            reporter.set_lint_level(lint, level);
+        }
 
         // lxcl anlyss
-        self.progress.set_phase(CompilePhase::Lexing);
-        let mut lexer = Lexer::new(&source, file_id, &mut reporter);
+        self.advance(CompilePhase::Lexing, &reporter);
+        let mut lexer = Lexer::new(source, file_id, &mut reporter).with_edition(self.config.edition).with_limits(self.config.limits);
         let tokens = lexer.tokenize();
 
         // parsing
-        self.progress.set_phase(CompilePhase::Parsing);
-        let mut parser = Parser::new(tokens, file_id, &mut reporter);
-        let ast = parser.parse();
+        self.advance(CompilePhase::Parsing, &reporter);
+        let mut parser = Parser::new(tokens, file_id, &mut reporter).with_edition(self.config.edition).with_limits(self.config.limits);
+        let mut ast = parser.parse();
+
+        // the prelude goes in b4 any pass sees the program so its items lower like the program's own
+        let prelude = (!self.config.no_prelude).then(|| Prelude::load(&mut reporter));
+        let prelude_assert = prelude.as_ref().is_some_and(|p| p.provides(&ast, "assert"));
+        if let Some(prelude) = &prelude {
+            ast = prelude.inject(&ast, true);
+        }
 
         // smntc analysis
-        let symbol_table = if !reporter.has_errors() {
-            self.progress.set_phase(CompilePhase::SemanticAnalysis);
-            let mut analyzer = SemanticAnalyzer::new(&mut reporter, file_id);
-            analyzer.analyze(&ast)
+        let mut sources = vec![self.config.input.clone()];
+        let (symbol_table, imports) = if !reporter.has_errors() {
+            self.advance(CompilePhase::SemanticAnalysis, &reporter);
+            let mut analyzer = SemanticAnalyzer::new(&mut reporter, file_id)
+                .with_target(self.target_info())
+                .with_edition(self.config.edition);
+            if let Some(prelude) = prelude {
+                analyzer = analyzer.with_prelude(prelude);
+            }
+            let symbol_table = analyzer.analyze(&ast);
+            let imports = analyzer
+                .imported_modules()
+                .into_iter()
+                .map(|(name, module_ast, symbols)| (name.to_string(), module_ast.clone(), symbols.clone()))
+                .collect();
+            sources.extend(analyzer.imported_files());
+            (symbol_table, imports)
         } else {
-            crate::frontend::semantic::symbol_table::SymbolTable::new()
+            (SymbolTable::new(), Vec::new())
         };
 
-        // hir lowering
-        self.progress.set_phase(CompilePhase::HirLowering);
-        let mut hir_lowerer = HirLowerer::new(symbol_table);
-        let mut hir = hir_lowerer.lower(&ast);
+        let sources = sources.into_iter().map(|path| {
+            let hash = fs::read(&path).map(|bytes| stable_hash(&bytes)).unwrap_or_default();
+            (path, hash)
+        }).collect();
+        FrontEnd { key: self.front_end_key(), sources, reporter, file_id, ast, prelude_assert, symbol_table, imports }
+    }
 
-        // hir optmztn
-        self.progress.set_phase(CompilePhase::HirOptimization);
-        let mut hir_optimizer = HirOptimizer::new();
-        hir_optimizer.optimize(&mut hir);
+    /// hash of the options lexing, parsing + analysis read
+    fn front_end_key(&self) -> u64 {
+        let c = &self.config;
+        stable_hash(format!("{:?}", (c.edition, c.limits, c.no_prelude, &c.target, &c.lints)).as_bytes())
+    }
 
-        // mir lwrng
-        self.progress.set_phase(CompilePhase::MirLowering);
-        let mut mir_lowerer = MirLowerer::new();
-        let mut mir_functions = mir_lowerer.lower(&hir);
+    /// names of the fns whose MIR text differs between 2 lowerings of the same program
+    fn nondeterministic(first: &[MirFunction], second: &[MirFunction]) -> Vec<String> {
+        let mut names: Vec<String> = first
+            .iter()
+            .filter(|f| second.iter().find(|g| g.name == f.name).is_none_or(|g| g.to_string() != f.to_string()))
+            .map(|f| f.name.clone())
+            .collect();
+        names.extend(second.iter().filter(|g| !first.iter().any(|f| f.name == g.name)).map(|g| g.name.clone()));
+        names
+    }
 
-        // mir optimization
-        self.progress.set_phase(CompilePhase::MirOptimization);
-        let mut mir_optimizer = MirOptimizer::new();
-        for func in &mut mir_functions {
-            mir_optimizer.optimize(func);
+    /// an imported module thru the same hir + mir passes as the program
+    pub(crate) fn lower_module(&self, name: &str, ast: &Ast, symbols: &SymbolTable, target: &TargetInfo) -> MirModule {
+        let lowerer = MirLowerer::new().with_null_checks(self.config.null_checks);
+        MirModule::new(name.to_string(), Self::lower_to_mir(ast, symbols, target, lowerer, self.mir_optimizer()))
+    }
+
+    /// hir lowering thru mir optimization in 1 go
+    fn lower_to_mir(ast: &Ast, symbols: &SymbolTable, target: &TargetInfo, mut lowerer: MirLowerer, mut optimizer: MirOptimizer) -> Vec<MirFunction> {
+        let mut hir = HirLowerer::new(symbols.clone()).with_target(target.clone()).lower(ast);
+        HirOptimizer::new().optimize(&mut hir);
+        let mut functions = lowerer.lower(&hir);
+        optimizer.optimize_all(&mut functions);
+        functions
+    }
+
+    /// the MIR optimizer as the -C options + --passes ask 4
+    pub(crate) fn mir_optimizer(&self) -> MirOptimizer {
+        let mut optimizer = MirOptimizer::new().with_inline_threshold(self.config.inline_threshold);
+        if let Some(plan) = &self.config.passes {
+            optimizer = optimizer.with_passes(plan.mir_passes());
+        }
+        match &self.profile {
+            Some(profile) => optimizer.with_profile(profile.clone()),
+            None => optimizer,
         }
+    }
 
-        // backend code generation
-        if self.should_run_backend() {
-            self.progress.set_phase(CompilePhase::CodeGeneration);
-            if let Err(e) = self.run_backend(Some(&hir), &mir_functions) {
-                // bakcend errrs dont fail the cmltn just warn
-                if self.config.verbose {
-                    Output::warning(&format!("Backend codegen failed: {}", e));
+    fn load_profile(&self) -> Result<Option<Arc<CallProfile>>, CompileError> {
+        let Some(path) = &self.config.profile_use else { return Ok(None) };
+        let profile = CallProfile::load(path).map_err(|e| CompileError::IoError(e.to_string()))?;
+        Ok(Some(Arc::new(profile)))
+    }
+
+    /// codegen a compiled program in memory + run its `main` on the backend's jit, imported
+    /// modules r added first so main's calls in2 them resolve. returns main's result.
+    /// w/o the default backend built in the MIR interpreter runs it
+    pub fn run_jit(&self, result: &CompileResult) -> Result<i32, String> {
+        let registry = BackendRegistry::new();
+        let factory = self.jit_factory(&registry)?;
+        let mut engine = factory.create_execution_engine().map_err(|e| e.to_string())?;
+        // a module lives in its codegen's context, the bridge has 2 outlive it until the jit read it
+        let compile = |functions: &[MirFunction], what: &str| {
+            let mut bridge = self.bridge(factory)?;
+            let mut module = bridge.compile_from_mir(functions).map_err(|e| format!("{}: {}", what, e))?;
+            bridge.optimize(&mut module).map_err(|e| format!("{}: {}", what, e))?;
+            Ok::<_, String>((bridge, module))
+        };
+        for m in &result.modules {
+            let (_bridge, module) = compile(&m.functions, &format!("module '{}'", m.name))?;
+            engine.add_module(&module).map_err(|e| e.to_string())?;
+        }
+        let (_bridge, module) = compile(&result.mir_functions, "main")?;
+        engine.run_main(&module).map_err(|e| e.to_string())
+    }
+
+    /// the backend that runs code in process, the interpreter when the default one isnt built in
+    /// or cant
+    pub(crate) fn jit_factory<'r>(&self, registry: &'r BackendRegistry) -> Result<&'r dyn BackendFactory, String> {
+        match registry.get_factory(self.config.backend) {
+            Some(factory) if factory.capabilities().jit => Ok(factory),
+            _ if !self.config.explicit_backend => registry
+                .get_factory(BackendType::Interp)
+                .ok_or_else(|| "No backend can run the program".to_string()),
+            Some(_) => Err(format!("{} backend cannot run code in process, use --interp", self.config.backend.as_str())),
+            None => Err(format!("Backend '{}' not available", self.config.backend.as_str())),
+        }
+    }
+
+    /// target from --target or the host 4 `target.*` constants
+    pub(crate) fn target_info(&self) -> TargetInfo {
+        match &self.config.target {
+            Some(triple) => TargetInfo::from_triple(triple),
+            None => TargetInfo::host(),
+        }
+    }
+
+    /// fn definition lines 4 -g, None when no debug info was asked 4
+    fn debug_info(&self, ast: &Ast, reporter: &Reporter, file_id: FileId) -> Option<DebugInfo> {
+        if self.config.debuginfo == DebugInfoLevel::None {
+            return None;
+        }
+        let mut function_lines = HashMap::new();
+        for item in &ast.items {
+            if let Item::Function(func) = item {
+                if let Ok(location) = reporter.files().location(file_id, func.span.start()) {
+                    function_lines.insert(func.name.clone(), location.line.to_usize() as u32 + 1);
                 }
             }
         }
+        Some(DebugInfo {
+            level: self.config.debuginfo,
+            file: self.config.input.clone(),
+            function_lines,
+        })
+    }
 
-        let _elapsed = start_time.elapsed().as_millis() as u64;
-        self.progress.set_phase(CompilePhase::Complete);
+    /// a feature gated backend asked 4 by flag has 2 be built in, only the default one falls back.
+    /// one that is built in has 2 support the target + -g
+    fn check_backend(&self) -> Result<(), CompileError> {
+        let backend = self.config.backend;
+        if !self.config.explicit_backend || self.emits_mir() {
+            return Ok(());
+        }
+        let registry = BackendRegistry::new();
+        let Some(factory) = registry.get_factory(backend) else {
+            return match backend.feature() {
+                Some(feature) => Err(CompileError::BackendUnavailable(format!(
+                    "{} backend not available, rebuild emc with --features {}",
+                    backend.as_str(),
+                    feature
+                ))),
+                None => Ok(()),
+            };
+        };
+        let capabilities = factory.capabilities();
+        if let Some(triple) = &self.config.target {
+            if !capabilities.targets.supports(triple) {
+                return Err(CompileError::Unsupported(format!(
+                    "{} backend cannot generate code for '{}', it only targets the host ({})",
+                    backend.as_str(),
+                    triple,
+                    TargetInfo::host().arch
+                )));
+            }
+        }
+        if self.config.debuginfo != DebugInfoLevel::None && !capabilities.debug_info {
+            return Err(CompileError::Unsupported(format!(
+                "{} backend cannot emit debug info, drop -g or use --llvm",
+                backend.as_str()
+            )));
+        }
+        Ok(())
+    }
 
-        let success = !reporter.has_errors();
+    /// --emit=mir + --emit=cfg-dot stop b4 codegen, no backend is needed
+    fn emits_mir(&self) -> bool {
+        matches!(self.config.emit_type(), Ok(EmitType::Mir | EmitType::CfgDot))
+    }
 
-        Ok(CompileResult {
-            mir_functions,
-            hir: Some(hir),
-            reporter,
-            success,
-            ast: Some(ast),
-        })
+    /// --emit=mir or cfg-dot, the program's fns then each imported module's
+    fn write_mir(&self, functions: &[MirFunction], modules: &[MirModule]) -> Result<(), String> {
+        let output = self.config.output.as_ref()
+            .ok_or_else(|| "No output file specified".to_string())?;
+        let text = if self.config.emit_type() == Ok(EmitType::CfgDot) {
+            let mut graphs: Vec<String> = functions.iter().map(|f| f.to_dot(&f.name)).collect();
+            for module in modules {
+                graphs.extend(module.functions.iter().map(|f| f.to_dot(&format!("{}::{}", module.name, f.name))));
+            }
+            graphs
+        } else {
+            let mut text: Vec<String> = functions.iter().map(|f| f.to_string()).collect();
+            for module in modules {
+                text.push(format!("; module '{}'", module.name));
+                text.extend(module.functions.iter().map(|f| f.to_string()));
+            }
+            text
+        };
+        fs::write(output, text.join("\n"))
+            .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        self.artifact(if self.config.emit_type() == Ok(EmitType::CfgDot) { "cfg-dot" } else { "mir" }, output, false);
+        Ok(())
     }
 
     /// chk if backend codegen shld be run
@@ -160,12 +574,18 @@ impl Compiler {
     }
 
     /// run bcknd code generation
-    fn run_backend(&self, hir: Option<&Hir>, mir_functions: &[MirFunction]) -> Result<(), String> {
+    fn run_backend(
+        &self,
+        hir: Option<&Hir>,
+        mir_functions: &[MirFunction],
+        modules: &[MirModule],
+        debug_info: Option<DebugInfo>,
+    ) -> Result<(), String> {
         // get backend type from config
         let mut backend_type = self.config.backend;
 
         // crt backend registry
-        let registry = BackendRegistry::new();
+        let registry = BackendRegistry::new().with_codegen_units(self.config.codegen_units);
         
         // try 2 get the requested bcknd fall back if not available
         let factory = if let Some(factory) = registry.get_factory(backend_type) {
@@ -194,32 +614,92 @@ impl Compiler {
                 backend_type = BackendType::Null;
                 registry.get_factory(BackendType::Null)
                     .ok_or_else(|| "No backend available".to_string())?
+            } else if backend_type == BackendType::Cranelift {
+                // no silent fallback, --cranelift is an explicit request 4 a fast build
+                return Err("Cranelift backend not available, rebuild emc with --features cranelift".to_string());
             } else {
                 return Err(format!("Backend '{}' not available", backend_type.as_str()));
             }
         };
 
+        // get otpt path
+        let output = self.config.output.as_ref()
+            .ok_or_else(|| "No output file specified".to_string())?;
+
+        // get emi type
+        let emit_type = self.config.emit_type()?;
+
         // create bcknd brdg
-        let mut bridge = BackendBridge::from_factory(factory)
-            .map_err(|e| format!("Failed to create backend: {}", e))?;
+        let mut bridge = self.bridge(factory)?;
 
-        // set optimization level
-        if let Some(opt_level) = OptimizationLevel::from_str(&self.config.opt_level) {
-            bridge.set_optimization_level(opt_level);
+        // imported modules get their own objects next 2 the output, a binary links them in
+        let stem = output.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let mut jobs: Vec<ObjectJob> = modules
+            .iter()
+            .map(|m| ObjectJob {
+                what: format!("module '{}'", m.name),
+                functions: &m.functions,
+                unit: None,
+                object: output.with_file_name(format!("{}.{}.o", stem, m.name)),
+            })
+            .collect();
+        // codegen units only split a linked binary, the output's own module then defines nothing.
+        // w/ builders theres at least 1 unit each, even w/ a single 1 the output isnt built here
+        let builders = &self.config.builders;
+        let units = factory.codegen_units().max(builders.len());
+        let split_units = (units > 1 || !builders.is_empty()) && emit_type == EmitType::Binary && bridge.preferred_input_type() == BackendInputType::Mir;
+        if split_units {
+            for (i, unit) in partition(mir_functions, units).into_iter().enumerate() {
+                jobs.push(ObjectJob {
+                    what: format!("codegen unit {}", i),
+                    functions: mir_functions,
+                    unit: Some(unit),
+                    object: output.with_file_name(format!("{}.cgu{}.o", stem, i)),
+                });
+            }
         }
-
-        // set trgt triple if spcfd
-        if let Some(ref target) = self.config.target {
-            bridge.set_target_triple(target.clone());
+        let cache = self.artifact_cache(factory)?;
+        let dispatcher = match builders.is_empty() {
+            true => None,
+            false => Some(Dispatcher::new(builders).with_token(self.remote_token()?)),
+        };
+        let objects = self.compile_objects(factory, jobs, debug_info.clone(), units, cache.as_ref(), dispatcher.as_ref())?;
+        if split_units {
+            bridge.set_codegen_unit(Vec::new());
         }
 
-        // get emi type
-        let emit_type = EmitType::from_str(&self.config.emit)
-            .ok_or_else(|| format!("Unknown emit type: {}", self.config.emit))?;
+        // split-debuginfo has nothing 2 split w/o debug info or once its stripped
+        let split_debuginfo = match &debug_info {
+            Some(debug_info) => {
+                bridge.set_debug_info(debug_info.clone());
+                if self.config.strip == Strip::None { self.config.split_debuginfo } else { SplitDebugInfo::Off }
+            }
+            None => SplitDebugInfo::Off,
+        };
 
-        // get otpt path
-        let output = self.config.output.as_ref()
-            .ok_or_else(|| "No output file specified".to_string())?;
+        // linker script/libs only matter 4 binary style outputs
+        let shared = emit_type == EmitType::SharedLib;
+        let mut link_options = LinkOptions::new()
+            .with_target(self.target_info())
+            .with_library_paths(self.config.library_paths.clone())
+            .with_libs(self.config.link_libs.clone())
+            .with_shared(shared)
+            .with_split_debuginfo(split_debuginfo)
+            .with_strip(self.config.strip)
+            .with_visibility(self.config.visibility)
+            .with_exports(self.config.exports.clone())
+            .with_objects(objects);
+        if let Some(compression) = self.config.debuginfo_compression {
+            link_options = link_options.with_debug_compression(compression);
+        }
+        if let Some(ref script) = self.config.linker_script {
+            link_options = link_options.with_script(script.clone());
+        }
+        if link_options.flavor == LinkerFlavor::Darwin {
+            link_options = link_options.with_apple_sdk_from_env();
+        }
+        bridge.set_link_options(link_options);
+        bridge.set_embed_bitcode(self.config.embed_bitcode);
 
         // compile and emit - use backend's preferred input type
         let preferred = bridge.preferred_input_type();
@@ -243,12 +723,183 @@ impl Compiler {
             }
         };
         
-        bridge.compile_and_emit(input, emit_type, output)
-            .map_err(|e| format!("Backend compilation failed: {}", e))?;
+        // a lone object is cached like the ones linked in2 a binary, a linked output isnt
+        let key = match (&cache, &input) {
+            (Some(_), BackendInput::Mir(functions)) if emit_type == EmitType::Object => {
+                Some(ArtifactCache::key(&[&mir_text(functions), &format!("{:?}", debug_info)]))
+            }
+            _ => None,
+        };
+        let hit = cache.as_ref().zip(key).is_some_and(|(cache, key)| cache.fetch(key, output));
+        if !hit {
+            bridge.compile_and_emit(input, emit_type, output)
+                .map_err(|e| format!("Backend compilation failed: {}", e))?;
+            if let Some((cache, key)) = cache.as_ref().zip(key) {
+                self.store_object(cache, key, output);
+            }
+        } else if self.config.verbose {
+            Output::info(&format!("Reusing cached object for {}", output.display()));
+        }
+        self.artifact(emit_type.as_str(), output, hit);
+
+        // printers only help when the binary still has types 4 them 2 match
+        let described = self.config.debuginfo == DebugInfoLevel::Full && self.config.strip == Strip::None;
+        if described && emit_type == EmitType::Binary && !self.target_info().is_wasm() {
+            let scripts = debugger::write_pretty_printers(output)
+                .map_err(|e| format!("Failed to write debugger scripts: {}", e))?;
+            for script in scripts {
+                if self.config.verbose {
+                    Output::info(&format!("Debugger script: {}", script.display()));
+                }
+                self.artifact("debugger-script", &script, false);
+            }
+        }
 
         Ok(())
     }
 
+    /// compile each job 2 its own object on a pool of `threads`, each thread w/ its own bridge
+    /// or handing its jobs 2 the dispatcher's builders
+    fn compile_objects(
+        &self,
+        factory: &dyn crate::backend::BackendFactory,
+        jobs: Vec<ObjectJob>,
+        debug_info: Option<DebugInfo>,
+        threads: usize,
+        cache: Option<&ArtifactCache>,
+        dispatcher: Option<&Dispatcher>,
+    ) -> Result<Vec<PathBuf>, String> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| format!("Failed to start codegen threads: {}", e))?;
+        pool.install(|| {
+            jobs.into_par_iter()
+                .map(|job| {
+                    // only units get debug info, so its only part of their key
+                    let unit_debug_info = job.unit.as_ref().and(debug_info.as_ref());
+                    let key = cache.map(|_| {
+                        ArtifactCache::key(&[&mir_text(job.functions), &format!("{:?}", job.unit), &format!("{:?}", unit_debug_info)])
+                    });
+                    if let Some((cache, key)) = cache.zip(key) {
+                        if cache.fetch(key, &job.object) {
+                            if self.config.verbose {
+                                Output::info(&format!("Reusing cached object for {}", job.what));
+                            }
+                            self.artifact("obj", &job.object, true);
+                            return Ok(job.object);
+                        }
+                    }
+                    let built = match dispatcher {
+                        Some(dispatcher) => dispatcher
+                            .compile(&self.remote_job(factory, &job, unit_debug_info), &job.object)
+                            .map_err(|e| format!("Backend compilation of {} failed: {}", job.what, e))?,
+                        None => false,
+                    };
+                    if built {
+                        if self.config.verbose {
+                            Output::info(&format!("Built {} on a remote builder", job.what));
+                        }
+                        if let Some((cache, key)) = cache.zip(key) {
+                            self.store_object(cache, key, &job.object);
+                        }
+                        self.artifact("obj", &job.object, false);
+                        return Ok(job.object);
+                    }
+                    let mut bridge = self.bridge(factory)?;
+                    if let Some(unit) = job.unit {
+                        bridge.set_codegen_unit(unit);
+                        if let Some(debug_info) = unit_debug_info {
+                            bridge.set_debug_info(debug_info.clone());
+                        }
+                    }
+                    bridge.compile_and_emit(BackendInput::Mir(job.functions.to_vec()), EmitType::Object, &job.object)
+                        .map_err(|e| format!("Backend compilation of {} failed: {}", job.what, e))?;
+                    if let Some((cache, key)) = cache.zip(key) {
+                        self.store_object(cache, key, &job.object);
+                    }
+                    self.artifact("obj", &job.object, false);
+                    Ok(job.object)
+                })
+                .collect()
+        })
+    }
+
+    /// `job` w/ the settings `bridge` would give it, 4 a builder
+    fn remote_job(&self, factory: &dyn BackendFactory, job: &ObjectJob, debug_info: Option<&DebugInfo>) -> RemoteJob {
+        RemoteJob {
+            backend: factory.backend_type(),
+            opt_level: self.config.opt_level.clone(),
+            target: self.config.target.clone(),
+            panic: self.config.panic,
+            passes: self.config.passes.iter().flat_map(|plan| plan.llvm.clone()).collect(),
+            unit: job.unit.clone(),
+            debug_info: debug_info.cloned(),
+            functions: job.functions.to_vec(),
+        }
+    }
+
+    /// -C incremental's cache 4 `factory`, keyed by evry setting the bridge passes on
+    fn artifact_cache(&self, factory: &dyn BackendFactory) -> Result<Option<ArtifactCache>, String> {
+        let Some(root) = &self.config.incremental else {
+            return Ok(None);
+        };
+        let passes: Vec<&str> = self.config.passes.iter().flat_map(|plan| &plan.llvm).map(|pass| pass.name()).collect();
+        let fingerprint = BackendFingerprint::new(
+            factory.backend_type(),
+            factory.version(),
+            self.target_info().triple,
+            self.config.opt_level.clone(),
+        )
+        .with_setting("panic", self.config.panic.as_str())
+        .with_setting("debuginfo", format!("{:?}", self.config.debuginfo))
+        .with_setting("embed-bitcode", self.config.embed_bitcode.to_string())
+        .with_setting("passes", passes.join(","))
+        // the emitter hides unexported fns in the object itself
+        .with_setting("visibility", format!("{:?}", self.config.visibility))
+        .with_setting("exports", self.config.exports.join(","))
+        // a dll's objects dllexport what it exports on windows
+        .with_setting("shared", (self.config.emit_type()? == EmitType::SharedLib).to_string());
+        ArtifactCache::open(root, &fingerprint).map(Some)
+    }
+
+    /// the token -C remote servers want, workers need none
+    fn remote_token(&self) -> Result<String, String> {
+        if !self.config.builders.iter().any(|builder| matches!(builder, Builder::Remote(_))) {
+            return Ok(String::new());
+        }
+        read_token(&self.config.remote_token.clone().unwrap_or_else(token_file))
+    }
+
+    /// a cache that cant be written only costs the next build time, so it doesnt fail this 1
+    fn store_object(&self, cache: &ArtifactCache, key: Digest, object: &std::path::Path) {
+        if let Err(e) = cache.store(key, object) {
+            Output::warning(&e);
+        }
+    }
+
+    /// bcknd brdg w/ the configured opt level, target + panic strategy
+    pub(crate) fn bridge(&self, factory: &dyn BackendFactory) -> Result<BackendBridge, String> {
+        let mut bridge = BackendBridge::from_factory(factory)
+            .map_err(|e| format!("Failed to create backend: {}", e))?;
+
+        // set optimization level
+        if let Some(opt_level) = OptimizationLevel::from_str(&self.config.opt_level) {
+            bridge.set_optimization_level(opt_level);
+        }
+        for pass in self.config.passes.iter().flat_map(|plan| &plan.llvm) {
+            bridge.add_pass(pass.clone());
+        }
+
+        // set trgt triple if spcfd
+        if let Some(ref target) = self.config.target {
+            bridge.set_target_triple(target.clone());
+        }
+
+        bridge.set_panic_strategy(self.config.panic);
+        Ok(bridge)
+    }
+
     /// load source file rfom disk
     fn load_source(&self) -> Result<String, CompileError> {
         fs::read_to_string(&self.config.input)
@@ -261,6 +912,11 @@ impl Compiler {
     }
 }
 
+/// the text of `functions`, what a cached object's key is made frm
+fn mir_text(functions: &[MirFunction]) -> String {
+    functions.iter().map(|f| f.to_string()).collect::<Vec<_>>().join("\n")
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum CompileError {
     #[error("IO error: {0}")]
@@ -268,6 +924,19 @@ pub enum CompileError {
 
     #[error("Compilation failed with errors")]
     CompilationFailed,
+
+    #[error("{0}")]
+    BackendUnavailable(String),
+
+    #[error("{0}")]
+    BackendFailed(String),
+
+    /// a flag the chosen backend cant honour
+    #[error("{0}")]
+    Unsupported(String),
+
+    #[error("{0}")]
+    Internal(Box<IceReport>),
 }
 
 /// display compilation rslts
@@ -280,8 +949,39 @@ pub fn display_results(result: &CompileResult, config: &CompileConfig) {
 
     let (_errors, _warnings) = count_diagnostics(&result.reporter);
 
+    if config.strict {
+        println!("{}", strict_summary(config, Ok(result)));
+    }
+
+    // the events already carried the diagnostics + how the build ended
+    if config.message_format == MessageFormat::Json {
+        return;
+    }
+
+    // json is read by tools, the build summary would only get in their way
+    if config.error_format == ErrorFormat::Json {
+        display_diagnostics_json(&result.reporter, config.tab_width);
+        return;
+    }
+
+    for name in &result.nondeterministic {
+        Output::error(&format!("the MIR of '{}' changed when the program was lowered again", name));
+    }
+
+    if config.print_pass_stats {
+        for stats in &result.pass_stats {
+            Output::info(&stats.to_string());
+        }
+    }
+
+    if config.verbose {
+        for remark in &result.remarks {
+            Output::note(remark);
+        }
+    }
+
     if !result.reporter.diagnostics().is_empty() {
-        display_diagnostics(&result.reporter, color_choice);
+        display_diagnostics(&result.reporter, color_choice, config.tab_width);
     }
 
     if !config.quiet {
@@ -296,3 +996,35 @@ pub fn display_results(result: &CompileResult, config: &CompileConfig) {
         }
     }
 }
+
+/// the 1 line of json -Z strict prints on stdout 4 CI, `{"strict","input","passed","errors",
+/// "warnings","nondeterministic","ice","failure"}`. an ICE is `{"phase","message","report"}`,
+/// `failure` is why the compiler stopped w/o a result (eg the input couldnt be read)
+pub fn strict_summary(config: &CompileConfig, result: Result<&CompileResult, &CompileError>) -> String {
+    let null = || "null".to_string();
+    let ((errors, warnings), nondeterministic, ice, failure) = match result {
+        Ok(result) => (count_diagnostics(&result.reporter), result.nondeterministic.as_slice(), null(), null()),
+        Err(CompileError::Internal(report)) => {
+            let ice = format!(
+                "{{\"phase\":{},\"message\":{},\"report\":{}}}",
+                report.phase.map_or_else(null, |p| json_string(p.as_str())),
+                json_string(&report.panic.message),
+                report.path.as_ref().map_or_else(null, |p| json_string(&p.to_string_lossy()))
+            );
+            ((0, 0), &[][..], ice, null())
+        }
+        Err(e) => ((0, 0), &[][..], null(), json_string(&e.to_string())),
+    };
+    let passed = matches!(result, Ok(r) if r.success);
+    let names: Vec<String> = nondeterministic.iter().map(|n| json_string(n)).collect();
+    format!(
+        "{{\"strict\":true,\"input\":{},\"passed\":{},\"errors\":{},\"warnings\":{},\"nondeterministic\":[{}],\"ice\":{},\"failure\":{}}}",
+        json_string(&config.input.to_string_lossy()),
+        passed,
+        errors,
+        warnings,
+        names.join(","),
+        ice,
+        failure
+    )
+}
@@ -0,0 +1,407 @@
+use crate::cli::args::{Cli, ColorWhen, Commands, CompileConfig, ErrorFormat, MessageFormat};
+use crate::cli::cache::stable_hash;
+use crate::cli::compiler::{strict_summary, Compiler, FrontEndCache};
+use crate::cli::error_display::{diagnostic_json, render_diagnostics};
+use crate::cli::events::EventStream;
+use crate::cli::remote::{read_frame, write_frame};
+use crate::core::mir::wire::{Reader, WireError, Writer};
+use clap::Parser;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// where this user's daemon keeps the address it listens on + its token, `--daemon` clients
+/// look it up there
+pub fn address_file() -> PathBuf {
+    user_file("emerald-daemon", "addr")
+}
+
+/// `<stem>-<user>.<extension>` in the temp dir, so each user gets their own
+pub(crate) fn user_file(stem: &str, extension: &str) -> PathBuf {
+    let name = match std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+        Ok(user) if !user.is_empty() => format!("{}-{}.{}", stem, user, extension),
+        _ => format!("{}.{}", stem, extension),
+    };
+    std::env::temp_dir().join(name)
+}
+
+/// a daemon's address + the token evry request 2 it carries. only who can read the address
+/// file knows the token, so another local user cant run builds as the daemon's
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub address: String,
+    pub token: String,
+}
+
+/// the running daemon, None when theres none
+pub fn running() -> Option<Endpoint> {
+    let endpoint = read_address(&address_file())?;
+    TcpStream::connect(&endpoint.address).is_ok().then_some(endpoint)
+}
+
+/// writes `endpoint` 2 `file` readable by this user alone. a file an earlier daemon left is
+/// replaced, 1 another user made cant b removed + fails the write
+pub fn write_address(file: &Path, endpoint: &Endpoint) -> io::Result<()> {
+    write_private(file, &format!("{}\n{}\n", endpoint.address, endpoint.token))
+}
+
+/// the endpoint in `file`, None unless it's there + no other user can read or write it
+pub fn read_address(file: &Path) -> Option<Endpoint> {
+    let contents = read_private(file)?;
+    let mut lines = contents.lines();
+    let (address, token) = (lines.next()?.trim(), lines.next()?.trim());
+    (!token.is_empty()).then(|| Endpoint { address: address.to_string(), token: token.to_string() })
+}
+
+/// (re)creates `file` w/ `contents`, readable + writable by this user alone
+pub(crate) fn write_private(file: &Path, contents: &str) -> io::Result<()> {
+    match fs::remove_file(file) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // the temp dir is per user on windows already
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(file)?.write_all(contents.as_bytes())
+}
+
+/// what `file` holds, None unless it's there + no other user can read or write it
+pub(crate) fn read_private(file: &Path) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if fs::metadata(file).ok()?.permissions().mode() & 0o077 != 0 {
+            return None;
+        }
+    }
+    fs::read_to_string(file).ok()
+}
+
+/// 128 random bits as hex, frm the keys std seeds its hashers w/ frm the os
+pub(crate) fn random_token() -> String {
+    let nanos = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    (0..2u8)
+        .map(|half| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(nanos);
+            hasher.write_u32(std::process::id());
+            hasher.write_u8(half);
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// `a == b` w/o stopping at the 1st byte that differs, so the time taken doesnt leak the token
+pub(crate) fn same_token(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// an `emerald` command line 2 run as if it was run in `cwd`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaemonRequest {
+    pub cwd: PathBuf,
+    /// the args after `emerald`
+    pub args: Vec<String>,
+    /// whether the client's terminal shows colors, 4 `--color auto`
+    pub color: bool,
+}
+
+/// what the run printed + its exit code, the client prints + exits w/ them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DaemonReply {
+    pub code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl DaemonRequest {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::new();
+        w.put(&self.cwd.to_string_lossy().to_string());
+        w.put(&self.args);
+        w.put(&self.color);
+        w.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut r = Reader::new(bytes);
+        let cwd: String = r.get()?;
+        Ok(Self { cwd: PathBuf::from(cwd), args: r.get()?, color: r.get()? })
+    }
+}
+
+impl DaemonReply {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::new();
+        w.put(&(self.code as i64));
+        w.put(&self.stdout);
+        w.put(&self.stderr);
+        w.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut r = Reader::new(bytes);
+        let code: i64 = r.get()?;
+        Ok(Self { code: code as i32, stdout: r.get()?, stderr: r.get()? })
+    }
+
+    fn fail(code: i32, message: &str) -> Self {
+        Self { code, stdout: String::new(), stderr: format!("error: {}\n", message) }
+    }
+
+    /// `other` after this 1, the worse exit code wins
+    fn append(&mut self, other: &DaemonReply) {
+        self.code = self.code.max(other.code);
+        self.stdout.push_str(&other.stdout);
+        self.stderr.push_str(&other.stderr);
+    }
+}
+
+/// the last run 4 an input, reused while its config, sources + output stay the same
+struct Build {
+    /// hash of the config it ran w/
+    config: u64,
+    /// evry file it read w/ a hash of what it held
+    sources: Vec<(PathBuf, u64)>,
+    /// the output + when it was written, a build whose output was removed or touched runs again
+    output: Option<(PathBuf, SystemTime)>,
+    reply: DaemonReply,
+}
+
+impl Build {
+    fn is_fresh(&self, config: u64) -> bool {
+        let unchanged = |(path, hash): &(PathBuf, u64)| fs::read(path).is_ok_and(|bytes| stable_hash(&bytes) == *hash);
+        let output_kept = |(path, written): &(PathBuf, SystemTime)| fs::metadata(path).and_then(|m| m.modified()).is_ok_and(|m| m == *written);
+        self.config == config && self.sources.iter().all(unchanged) && self.output.as_ref().is_none_or(output_kept)
+    }
+}
+
+/// `emerald daemon`, runs builds + checks 4 `--daemon` clients + answers 1 again w/o compiling
+/// while nothing it depends on changed, so an editor chking on evry save only pays 4 real edits
+pub struct Daemon {
+    builds: Mutex<HashMap<PathBuf, Build>>,
+    /// what lexing, parsing + analysis made of each input, reused when only the options after
+    /// them or the output changed
+    front_ends: FrontEndCache,
+    /// what a request has 2 carry 2 b run
+    token: String,
+    /// how long it waits on a client 2 send or take a frame
+    client_timeout: Duration,
+}
+
+impl Default for Daemon {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Daemon {
+    pub fn new() -> Self {
+        Self { builds: Mutex::new(HashMap::new()), front_ends: FrontEndCache::new(), token: random_token(), client_timeout: Duration::from_secs(10) }
+    }
+
+    /// drop a client that goes quiet 4 `timeout` mid request
+    pub fn with_client_timeout(mut self, timeout: Duration) -> Self {
+        self.client_timeout = timeout;
+        self
+    }
+
+    /// the front end results kept frm earlier builds
+    pub fn front_ends(&self) -> &FrontEndCache {
+        &self.front_ends
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// run 1 request, only builds + `check` r taken
+    pub fn handle(&self, request: &DaemonRequest) -> DaemonReply {
+        let argv = std::iter::once("emerald".to_string()).chain(request.args.iter().cloned());
+        let mut cli = match Cli::try_parse_from(argv) {
+            Ok(cli) => cli,
+            Err(e) => return DaemonReply { code: 2, stdout: String::new(), stderr: e.to_string() },
+        };
+        let check = match &cli.command {
+            None => false,
+            Some(Commands::Check { input }) => {
+                cli.inputs.extend(input.iter().cloned());
+                true
+            }
+            Some(_) => return DaemonReply::fail(2, "The daemon only runs builds and 'check'"),
+        };
+        // the manifest next 2 each input picks its edition, so they r found frm the client's dir
+        cli.inputs = cli.inputs.iter().map(|input| request.cwd.join(input)).collect();
+        let configs = match CompileConfig::for_inputs(&cli) {
+            Ok(configs) => configs,
+            Err(e) => return DaemonReply::fail(1, &e),
+        };
+        let mut reply = DaemonReply::default();
+        for config in configs {
+            let mut config = config.rooted(&request.cwd);
+            if check {
+                config.output = None;
+                config.opt_level = "0".to_string();
+            }
+            reply.append(&self.build(config, request.color));
+        }
+        reply
+    }
+
+    fn build(&self, config: CompileConfig, color: bool) -> DaemonReply {
+        let key = stable_hash(format!("{:?}", config).as_bytes());
+        if let Some(build) = self.builds.lock().unwrap_or_else(|e| e.into_inner()).get(&config.input) {
+            if build.is_fresh(key) {
+                let mut reply = build.reply.clone();
+                if config.verbose {
+                    reply.stdout.insert_str(0, &format!("info: {} is unchanged, reusing its last build\n", config.input.display()));
+                }
+                return reply;
+            }
+        }
+
+        let mut reply = DaemonReply::default();
+        // events go back 2 the client, not 2 the daemon's own stdout
+        let events = (config.message_format == MessageFormat::Json).then(|| EventStream::buffered(&config.input));
+        let mut compiler = Compiler::new(config.clone()).with_front_ends(self.front_ends.clone());
+        if let Some(events) = &events {
+            compiler = compiler.with_events(events.clone());
+        }
+        let compiled = compiler.compile();
+        if let Some(events) = &events {
+            reply.stdout = events.take();
+        }
+        let result = match compiled {
+            Ok(result) => result,
+            Err(e) => {
+                if config.strict {
+                    reply.stdout = format!("{}\n", strict_summary(&config, Err(&e)));
+                }
+                reply.append(&DaemonReply::fail(1, &format!("{}: compilation failed: {}", config.input.display(), e)));
+                return reply;
+            }
+        };
+        let color = match config.color {
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+            ColorWhen::Auto => color,
+        };
+        reply.code = if result.success { 0 } else { 1 };
+        if config.strict {
+            reply.stdout.push_str(&format!("{}\n", strict_summary(&config, Ok(&result))));
+        }
+        // the events already carry the diagnostics + the outcome
+        match config.error_format {
+            _ if events.is_some() => {}
+            ErrorFormat::Json => {
+                for diag in result.reporter.diagnostics() {
+                    reply.stderr.push_str(&format!("{}\n", diagnostic_json(diag, result.reporter.files(), config.tab_width)));
+                }
+            }
+            ErrorFormat::Human => reply.stderr.push_str(&render_diagnostics(&result.reporter, color, config.tab_width)),
+        }
+        if !config.quiet && config.error_format == ErrorFormat::Human && events.is_none() {
+            reply.stdout.push_str(&match (&config.output, result.success) {
+                (Some(output), true) => format!("\n✓ Build successful!\n  Output: {}\n", output.display()),
+                (None, true) => "\n✓ Checked, no errors\n".to_string(),
+                (_, false) => "\n✗ Build failed!\n".to_string(),
+            });
+        }
+
+        let sources = result.sources.iter().filter_map(|path| fs::read(path).ok().map(|bytes| (path.clone(), stable_hash(&bytes)))).collect();
+        let output = match &config.output {
+            Some(path) => match fs::metadata(path).and_then(|m| m.modified()) {
+                Ok(written) => Some((path.clone(), written)),
+                // nothing was written, eg the build failed, so theres nothing 2 reuse
+                Err(_) => return reply,
+            },
+            None => None,
+        };
+        let build = Build { config: key, sources, output, reply: reply.clone() };
+        self.builds.lock().unwrap_or_else(|e| e.into_inner()).insert(config.input.clone(), build);
+        reply
+    }
+
+    /// answer clients 1 at a time until 1 sends `STOP`, 1 that goes quiet is dropped after the
+    /// client timeout so it cant hold up the rest
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let mut stream = stream?;
+            stream.set_read_timeout(Some(self.client_timeout))?;
+            stream.set_write_timeout(Some(self.client_timeout))?;
+            if let Ok(false) = self.serve_client(&mut stream) {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// evry request on `stream`, false once its asked 2 stop
+    fn serve_client(&self, stream: &mut TcpStream) -> io::Result<bool> {
+        let mut input = BufReader::new(stream.try_clone()?);
+        while let Some((kind, frame)) = read_frame(&mut input)? {
+            let mut r = Reader::new(&frame);
+            let payload = match (r.get::<String>(), r.get::<Vec<u8>>()) {
+                (Ok(token), Ok(payload)) if same_token(&token, &self.token) => payload,
+                _ => {
+                    write_frame(stream, "DONE", &DaemonReply::fail(2, "The daemon refused the request, its token doesn't match").encode())?;
+                    continue;
+                }
+            };
+            let reply = match kind.as_str() {
+                "RUN" => match DaemonRequest::decode(&payload) {
+                    Ok(request) => self.handle(&request),
+                    Err(e) => DaemonReply::fail(2, &format!("Invalid request: {}", e)),
+                },
+                "STOP" => {
+                    write_frame(stream, "DONE", &DaemonReply::default().encode())?;
+                    return Ok(false);
+                }
+                other => DaemonReply::fail(2, &format!("Unknown request '{}'", other)),
+            };
+            write_frame(stream, "DONE", &reply.encode())?;
+        }
+        Ok(true)
+    }
+}
+
+/// send `kind` (`RUN` or `STOP`) 2 the daemon at `endpoint`
+pub fn send(endpoint: &Endpoint, kind: &str, payload: &[u8]) -> io::Result<DaemonReply> {
+    let mut stream = TcpStream::connect(&endpoint.address)?;
+    let mut w = Writer::new();
+    w.put(&endpoint.token);
+    w.put(&payload.to_vec());
+    write_frame(&mut stream, kind, &w.into_bytes())?;
+    match read_frame(&mut BufReader::new(&stream))? {
+        Some((reply, bytes)) if reply == "DONE" => {
+            DaemonReply::decode(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "The daemon sent no reply")),
+    }
+}
+
+/// serve on `listener` until stopped, its address + token r in `address_file` meanwhile
+pub fn run(listener: TcpListener) -> Result<(), String> {
+    let daemon = Daemon::new();
+    let endpoint = Endpoint { address: listener.local_addr().map_err(|e| e.to_string())?.to_string(), token: daemon.token.clone() };
+    let file = address_file();
+    write_address(&file, &endpoint).map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+    let served = daemon.serve(listener);
+    remove_address(&file, &endpoint);
+    served.map_err(|e| format!("Daemon failed: {}", e))
+}
+
+/// a daemon started since may have written its own address
+fn remove_address(file: &Path, endpoint: &Endpoint) {
+    if read_address(file).as_ref() == Some(endpoint) {
+        let _ = fs::remove_file(file);
+    }
+}
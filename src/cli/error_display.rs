@@ -1,9 +1,10 @@
 use crate::error::{Diagnostic, DiagnosticKind, Reporter, Severity};
 use codespan_reporting::diagnostic::{Diagnostic as CodespanDiagnostic, Label, Severity as CodespanSeverity};
-use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use codespan_reporting::term::termcolor::{Buffer, ColorChoice, StandardStream, WriteColor};
 use codespan_reporting::term::{self, Config};
 
-use codespan::FileId;
+use crate::error::columns::display_column;
+use codespan::{ByteIndex, FileId, Files, Span};
 
 /// convert emerald diagnostic 2 codespan rprtng dgnstc
 pub fn convert_diagnostic(diag: &Diagnostic) -> CodespanDiagnostic<FileId> {
@@ -13,23 +14,21 @@ pub fn convert_diagnostic(diag: &Diagnostic) -> CodespanDiagnostic<FileId> {
         Severity::Note => CodespanSeverity::Note,
     };
 
-    let code = match &diag.kind {
-        DiagnosticKind::LexicalError => Some("E0001"),
-        DiagnosticKind::SyntaxError => Some("E0002"),
-        DiagnosticKind::TypeError => Some("E0003"),
-        DiagnosticKind::NameResolutionError => Some("E0004"),
-        DiagnosticKind::BorrowCheckerError => Some("E0005"),
-        DiagnosticKind::SemanticError => Some("E0006"),
-    };
+
+    // secondary labels carry their own file, codespan prints each file's name + snippet
+    let mut labels = vec![Label::primary(
+        diag.file_id,
+        usize::from(diag.span.start())..usize::from(diag.span.end()),
+    )
+    .with_message(get_label_message(&diag.kind))];
+    labels.extend(diag.labels.iter().map(|l| {
+        Label::secondary(l.file_id, usize::from(l.span.start())..usize::from(l.span.end())).with_message(&l.message)
+    }));
 
     let mut codespan_diag = CodespanDiagnostic::new(severity)
-        .with_code(code.unwrap_or("E0000"))
+        .with_code(error_code(&diag.kind))
         .with_message(&diag.message)
-        .with_labels(vec![Label::primary(
-            diag.file_id,
-            usize::from(diag.span.start())..usize::from(diag.span.end()),
-        )
-        .with_message(get_label_message(&diag.kind))]);
+        .with_labels(labels);
 
     // add notes if prsnt
     if !diag.notes.is_empty() {
@@ -39,6 +38,17 @@ pub fn convert_diagnostic(diag: &Diagnostic) -> CodespanDiagnostic<FileId> {
     codespan_diag
 }
 
+fn error_code(kind: &DiagnosticKind) -> &'static str {
+    match kind {
+        DiagnosticKind::LexicalError => "E0001",
+        DiagnosticKind::SyntaxError => "E0002",
+        DiagnosticKind::TypeError => "E0003",
+        DiagnosticKind::NameResolutionError => "E0004",
+        DiagnosticKind::BorrowCheckerError => "E0005",
+        DiagnosticKind::SemanticError => "E0006",
+    }
+}
+
 fn get_label_message(kind: &DiagnosticKind) -> String {
     match kind {
         DiagnosticKind::LexicalError => "lexical error occurred here",
@@ -51,21 +61,28 @@ fn get_label_message(kind: &DiagnosticKind) -> String {
     .to_string()
 }
 
-/// display all dgnstcs from a reporter
-pub fn display_diagnostics(reporter: &Reporter, color_choice: ColorChoice) {
-    let files = reporter.files();
-    let diagnostics = reporter.diagnostics();
-
-    if diagnostics.is_empty() {
+/// display all dgnstcs from a reporter, carets account 4 tabs every `tab_width` columns
+pub fn display_diagnostics(reporter: &Reporter, color_choice: ColorChoice, tab_width: usize) {
+    if reporter.diagnostics().is_empty() {
         return;
     }
 
     let writer = StandardStream::stderr(color_choice);
-    let config = Config::default();
+    emit_diagnostics(&mut writer.lock(), reporter, tab_width);
+}
+
+/// the diagnostics as `display_diagnostics` prints them, 4 showing them in another process
+pub fn render_diagnostics(reporter: &Reporter, color: bool, tab_width: usize) -> String {
+    let mut buffer = if color { Buffer::ansi() } else { Buffer::no_color() };
+    emit_diagnostics(&mut buffer, reporter, tab_width);
+    String::from_utf8_lossy(buffer.as_slice()).to_string()
+}
 
-    for diag in diagnostics {
+fn emit_diagnostics(writer: &mut impl WriteColor, reporter: &Reporter, tab_width: usize) {
+    let config = Config { tab_width, ..Config::default() };
+    for diag in reporter.diagnostics() {
         let codespan_diag = convert_diagnostic(diag);
-        term::emit(&mut writer.lock(), &config, files, &codespan_diag)
+        term::emit(writer, &config, reporter.files(), &codespan_diag)
             .expect("Failed to emit diagnostic");
     }
 }
@@ -85,3 +102,81 @@ pub fn count_diagnostics(reporter: &Reporter) -> (usize, usize) {
 
     (errors, warnings)
 }
+
+/// evry diagnostic as 1 line of json on stderr, `--error-format=json`
+pub fn display_diagnostics_json(reporter: &Reporter, tab_width: usize) {
+    for diag in reporter.diagnostics() {
+        eprintln!("{}", diagnostic_json(diag, reporter.files(), tab_width));
+    }
+}
+
+/// `{"severity","code","message","lint","file","line","column","end_line","end_column",
+/// "display_column","end_display_column","notes","labels"}`, lines + columns count frm 1.
+/// `column` counts chars, `display_column` terminal cells (see error::columns)
+pub fn diagnostic_json(diag: &Diagnostic, files: &Files<String>, tab_width: usize) -> String {
+    let severity = match diag.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+    };
+    let lint = diag.lint.map_or("null".to_string(), |lint| json_string(lint.name()));
+    let notes: Vec<String> = diag.notes.iter().map(|n| json_string(n)).collect();
+    let labels: Vec<String> = diag
+        .labels
+        .iter()
+        .map(|l| format!("{{{},\"message\":{}}}", json_location(files, l.file_id, l.span, tab_width), json_string(&l.message)))
+        .collect();
+    format!(
+        "{{\"severity\":\"{}\",\"code\":\"{}\",\"message\":{},\"lint\":{},{},\"notes\":[{}],\"labels\":[{}]}}",
+        severity,
+        error_code(&diag.kind),
+        json_string(&diag.message),
+        lint,
+        json_location(files, diag.file_id, diag.span, tab_width),
+        notes.join(","),
+        labels.join(",")
+    )
+}
+
+fn json_location(files: &Files<String>, file_id: FileId, span: Span, tab_width: usize) -> String {
+    let position = |index: ByteIndex| {
+        let Ok(location) = files.location(file_id, index) else {
+            return (0, 0, 0);
+        };
+        let display = files.line_span(file_id, location.line).map_or(0, |line| {
+            let text = &files.source(file_id)[line.start().to_usize()..line.end().to_usize()];
+            display_column(text, index.to_usize() - line.start().to_usize(), tab_width)
+        });
+        (location.line.to_usize() + 1, location.column.to_usize() + 1, display)
+    };
+    let (line, column, display_column) = position(span.start());
+    let (end_line, end_column, end_display_column) = position(span.end());
+    format!(
+        "\"file\":{},\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{},\"display_column\":{},\"end_display_column\":{}",
+        json_string(&files.name(file_id).to_string_lossy()),
+        line,
+        column,
+        end_line,
+        end_column,
+        display_column,
+        end_display_column
+    )
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,94 @@
+use crate::cli::error_display::json_string;
+use crate::cli::progress::CompilePhase;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 1 step of a build as `--message-format=json` reports it
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildEvent<'a> {
+    BuildStarted,
+    PhaseStarted(CompilePhase),
+    PhaseFinished(CompilePhase, Duration),
+    /// a file the build wrote, `cached` when it came frm -C incremental's cache
+    Artifact { kind: &'a str, path: &'a Path, cached: bool },
+    /// a diagnostic as `--error-format=json` prints it
+    Diagnostic(String),
+    /// `error` is why the build stopped early, eg a backend failing
+    BuildFinished { success: bool, error: Option<&'a str> },
+}
+
+enum Sink {
+    Stdout,
+    /// kept 4 `take`, the daemon sends them back 2 its client
+    Buffer(Vec<u8>),
+}
+
+/// JSON lines on stdout as a build goes, so IDEs + build tools can show its progress +
+/// diagnostics b4 it ends. evry event names the input it belongs 2, several builds can share
+/// stdout. clones write 2 the same stream, codegen threads each report their own objects
+#[derive(Clone)]
+pub struct EventStream {
+    sink: Arc<Mutex<Sink>>,
+    input: String,
+    start: Instant,
+}
+
+impl EventStream {
+    pub fn stdout(input: &Path) -> Self {
+        Self::with_sink(Sink::Stdout, input)
+    }
+
+    /// events kept in memory instead of printed
+    pub fn buffered(input: &Path) -> Self {
+        Self::with_sink(Sink::Buffer(Vec::new()), input)
+    }
+
+    fn with_sink(sink: Sink, input: &Path) -> Self {
+        Self { sink: Arc::new(Mutex::new(sink)), input: input.to_string_lossy().to_string(), start: Instant::now() }
+    }
+
+    pub fn emit(&self, event: BuildEvent) {
+        let line = self.to_json(&event);
+        match &mut *self.sink.lock().unwrap_or_else(|e| e.into_inner()) {
+            Sink::Stdout => {
+                let mut out = std::io::stdout().lock();
+                // a closed stdout only loses the progress, not the build
+                let _ = writeln!(out, "{}", line).and_then(|_| out.flush());
+            }
+            Sink::Buffer(bytes) => {
+                bytes.extend_from_slice(line.as_bytes());
+                bytes.push(b'\n');
+            }
+        }
+    }
+
+    /// the lines buffered so far, empty 4 a stream on stdout
+    pub fn take(&self) -> String {
+        match &mut *self.sink.lock().unwrap_or_else(|e| e.into_inner()) {
+            Sink::Buffer(bytes) => String::from_utf8_lossy(&std::mem::take(bytes)).to_string(),
+            Sink::Stdout => String::new(),
+        }
+    }
+
+    fn to_json(&self, event: &BuildEvent) -> String {
+        let (name, fields) = match event {
+            BuildEvent::BuildStarted => ("build-started", String::new()),
+            BuildEvent::PhaseStarted(phase) => ("phase-started", format!(",\"phase\":\"{}\"", phase.name())),
+            BuildEvent::PhaseFinished(phase, elapsed) => {
+                ("phase-finished", format!(",\"phase\":\"{}\",\"ms\":{}", phase.name(), elapsed.as_millis()))
+            }
+            BuildEvent::Artifact { kind, path, cached } => (
+                "artifact",
+                format!(",\"kind\":{},\"path\":{},\"cached\":{}", json_string(kind), json_string(&path.to_string_lossy()), cached),
+            ),
+            BuildEvent::Diagnostic(json) => ("diagnostic", format!(",\"diagnostic\":{}", json)),
+            BuildEvent::BuildFinished { success, error } => {
+                let error = error.map_or_else(|| "null".to_string(), json_string);
+                ("build-finished", format!(",\"success\":{},\"error\":{},\"ms\":{}", success, error, self.start.elapsed().as_millis()))
+            }
+        };
+        format!("{{\"event\":\"{}\",\"input\":{}{}}}", name, json_string(&self.input), fields)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::cli::error_display::json_string;
+use crate::frontend::lexer::token::{TokenClass, TokenKind, EDITION_KEYWORDS, KEYWORDS, SYMBOLS};
+
+// editor highlighting built frm the lexer's own tables so a new keyword or operator shows up
+// w/o touching the grammar files. comments, strings, chars + numbers mirror the lexer by hand
+
+/// classes in the order editors shld try them, a symbol that's a prefix of another (`ref` /
+/// `ref?`, `.` / `..`) is always in a later class or sorted after the longer one
+const ORDER: &[TokenClass] = &[
+    TokenClass::Constant,
+    TokenClass::Type,
+    TokenClass::Control,
+    TokenClass::Keyword,
+    TokenClass::Operator,
+    TokenClass::Punctuation,
+];
+
+/// words + symbols of one class, symbols longest first. editors get the latest edition's keywords
+pub fn spellings(class: TokenClass) -> (Vec<&'static str>, Vec<&'static str>) {
+    let words = KEYWORDS
+        .iter()
+        .map(|(word, kind)| (word, kind))
+        .chain(EDITION_KEYWORDS.iter().map(|(word, kind, _)| (word, kind)))
+        .filter(|(_, kind)| kind.class() == class)
+        .map(|(word, _)| *word)
+        .collect();
+    let mut symbols: Vec<_> = SYMBOLS.iter().filter(|kind| kind.class() == class).filter_map(TokenKind::symbol).collect();
+    symbols.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    (words, symbols)
+}
+
+fn textmate_scope(class: TokenClass) -> &'static str {
+    match class {
+        TokenClass::Constant => "constant.language",
+        TokenClass::Type => "storage.type",
+        TokenClass::Control => "keyword.control",
+        TokenClass::Keyword => "keyword.other",
+        TokenClass::Operator => "keyword.operator",
+        TokenClass::Punctuation => "punctuation.separator",
+        _ => "source",
+    }
+}
+
+fn tree_sitter_capture(class: TokenClass) -> &'static str {
+    match class {
+        TokenClass::Constant => "@constant.builtin",
+        TokenClass::Type => "@type.builtin",
+        TokenClass::Control => "@keyword.control",
+        TokenClass::Keyword => "@keyword",
+        TokenClass::Operator => "@operator",
+        TokenClass::Punctuation => "@punctuation.delimiter",
+        _ => "@none",
+    }
+}
+
+fn regex_escape(s: &str) -> String {
+    s.chars().fold(String::new(), |mut out, c| {
+        if "\\.^$|?*+()[]{}".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+        out
+    })
+}
+
+/// `match` regex 4 a class, symbols b4 words so `ref?` wins over `ref`
+fn class_regex(class: TokenClass) -> String {
+    let (words, symbols) = spellings(class);
+    let mut alternatives: Vec<_> = symbols.iter().map(|s| regex_escape(s)).collect();
+    if !words.is_empty() {
+        alternatives.push(format!("\\b(?:{})\\b", words.join("|")));
+    }
+    alternatives.join("|")
+}
+
+fn pattern(name: &str, regex: &str) -> String {
+    format!("{{\"name\": {}, \"match\": {}}}", json_string(&format!("{}.emerald", name)), json_string(regex))
+}
+
+/// a TextMate grammar (`.tmLanguage.json`) 4 VS Code, Sublime, etc.
+pub fn textmate() -> String {
+    let mut patterns = vec![
+        pattern("comment.line.double-slash", "//.*$"),
+        pattern("comment.line.number-sign", "#.*$"),
+        // strings cant span lines, the lexer reports them as unterminated
+        format!(
+            "{{\"name\": \"string.quoted.double.emerald\", \"begin\": \"\\\"\", \"end\": \"\\\"|$\", \"patterns\": [{}]}}",
+            pattern("constant.character.escape", "\\\\[ntr\\\\\"]")
+        ),
+        pattern("string.quoted.single", "'(?:\\\\[ntr\\\\']|[^\\\\'])'"),
+        pattern("constant.numeric", "\\b[0-9]+(?:\\.[0-9]+)?\\b"),
+    ];
+    patterns.extend(ORDER.iter().map(|&class| pattern(textmate_scope(class), &class_regex(class))));
+    patterns.push(pattern("variable.other", "\\b[A-Za-z_][A-Za-z0-9_]*\\b"));
+
+    let mut out = String::from("{\n");
+    out.push_str("  \"name\": \"Emerald\",\n");
+    out.push_str("  \"scopeName\": \"source.emerald\",\n");
+    out.push_str("  \"fileTypes\": [\"em\"],\n");
+    out.push_str("  \"patterns\": [\n");
+    out.push_str(&patterns.iter().map(|p| format!("    {}", p)).collect::<Vec<_>>().join(",\n"));
+    out.push_str("\n  ]\n}\n");
+    out
+}
+
+/// a tree-sitter `highlights.scm`, keywords + symbols r anonymous nodes spelled like the
+/// source, the named nodes r `comment`, `string`, `char`, `number` + `identifier`
+pub fn tree_sitter() -> String {
+    let mut out = String::from("; generated by `emerald highlight-defs --format tree-sitter`\n\n");
+    for line in ["(comment) @comment", "(string) @string", "(char) @character", "(number) @number", "(identifier) @variable"] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    for &class in ORDER {
+        let (words, symbols) = spellings(class);
+        let nodes: Vec<_> = words.iter().chain(&symbols).map(|s| json_string(s)).collect();
+        out.push_str(&format!("\n[\n  {}\n] {}\n", nodes.join("\n  "), tree_sitter_capture(class)));
+    }
+    out
+}
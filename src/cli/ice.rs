@@ -0,0 +1,163 @@
+use crate::cli::progress::CompilePhase;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+// a panic in the compiler is an internal compiler error (ICE). the driver runs its phases inside
+// `catch`, the panic is turned in2 a report written 2 a file instead of rust's panic dump
+
+/// where 2 report ICEs
+pub const ISSUE_URL: &str = "https://github.com/emerald-language/emerald/issues/new";
+
+/// lines of source kept in a report, enough 2 see what the input looked like w/o attaching it all
+pub const SNIPPET_LINES: usize = 40;
+
+/// what the panic hook saw
+#[derive(Debug, Clone)]
+pub struct Panic {
+    pub message: String,
+    /// `file:line:col` in the compiler
+    pub location: Option<String>,
+    /// None when the panic happened on another thread (eg a rayon worker) + only its payload got here
+    pub backtrace: Option<String>,
+}
+
+thread_local! {
+    /// set while this thread is inside `catch`, panics elsewhere keep the previous hook
+    static ARMED: Cell<bool> = const { Cell::new(false) };
+    static CAUGHT: RefCell<Option<Panic>> = const { RefCell::new(None) };
+}
+
+static INSTALL: Once = Once::new();
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "the compiler panicked without a message".to_string())
+}
+
+fn install_hook() {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info: &PanicHookInfo<'_>| {
+            if !ARMED.get() {
+                return previous(info);
+            }
+            let caught = Panic {
+                message: payload_message(info.payload()),
+                location: info.location().map(|l| l.to_string()),
+                backtrace: Some(Backtrace::force_capture().to_string()),
+            };
+            CAUGHT.set(Some(caught));
+        }));
+    });
+}
+
+/// runs `f`, a panic in it comes back as the `Panic` instead of unwinding further
+pub fn catch<T>(f: impl FnOnce() -> T) -> Result<T, Panic> {
+    install_hook();
+    let outer = ARMED.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    ARMED.set(outer);
+    result.map_err(|payload| {
+        CAUGHT.take().unwrap_or_else(|| Panic { message: payload_message(&*payload), location: None, backtrace: None })
+    })
+}
+
+/// an ICE w/ what's needed 2 reproduce it
+#[derive(Debug, Clone)]
+pub struct IceReport {
+    pub version: String,
+    /// None b4 the first phase started
+    pub phase: Option<CompilePhase>,
+    pub input: PathBuf,
+    pub panic: Panic,
+    /// the first `SNIPPET_LINES` of the input
+    pub snippet: Option<String>,
+    /// the file the report was written 2, None if writing it failed
+    pub path: Option<PathBuf>,
+}
+
+impl IceReport {
+    pub fn new(phase: Option<CompilePhase>, input: &Path, panic: Panic) -> Self {
+        let snippet = std::fs::read_to_string(input).ok().map(|source| snippet(&source));
+        Self {
+            version: format!("emc {}", env!("CARGO_PKG_VERSION")),
+            phase,
+            input: input.to_path_buf(),
+            panic,
+            snippet,
+            path: None,
+        }
+    }
+
+    fn phase_name(&self) -> &'static str {
+        self.phase.map_or("startup", |p| p.as_str())
+    }
+
+    /// the full report, what goes in the file
+    pub fn render(&self) -> String {
+        let mut text = format!(
+            "internal compiler error\nversion: {}\nphase: {}\ninput: {}\n\npanicked",
+            self.version,
+            self.phase_name(),
+            self.input.display()
+        );
+        if let Some(location) = &self.panic.location {
+            text.push_str(&format!(" at {}", location));
+        }
+        text.push_str(&format!(":\n{}\n", self.panic.message));
+        if let Some(snippet) = &self.snippet {
+            text.push_str(&format!("\nsource:\n{}", snippet));
+        }
+        match &self.panic.backtrace {
+            Some(backtrace) => text.push_str(&format!("\nbacktrace:\n{}\n", backtrace)),
+            None => text.push_str("\nbacktrace: not captured, the panic happened on another thread\n"),
+        }
+        text
+    }
+
+    /// writes the report in2 `dir` as `emerald-ice-<pid>-<n>.txt`, the path is kept in `path`
+    pub fn write_to(&mut self, dir: &Path) -> std::io::Result<PathBuf> {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let path = dir.join(format!("emerald-ice-{}-{}.txt", std::process::id(), stamp));
+        std::fs::write(&path, self.render())?;
+        self.path = Some(path.clone());
+        Ok(path)
+    }
+}
+
+/// numbered lines, cut at `SNIPPET_LINES`
+fn snippet(source: &str) -> String {
+    let total = source.lines().count();
+    let mut text: String = source
+        .lines()
+        .take(SNIPPET_LINES)
+        .enumerate()
+        .map(|(i, line)| format!("{:>4} | {}\n", i + 1, line))
+        .collect();
+    if total > SNIPPET_LINES {
+        text.push_str(&format!("     ({} more lines)\n", total - SNIPPET_LINES));
+    }
+    text
+}
+
+/// the short form the driver prints, the details r in the file
+impl fmt::Display for IceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "internal compiler error ({}): {}", self.phase_name(), self.panic.message)?;
+        writeln!(f, "note: the compiler crashed, this is a bug in emerald and not in your code")?;
+        match &self.path {
+            Some(path) => writeln!(f, "note: a report was written to {}", path.display())?,
+            None => writeln!(f, "note: the report could not be written, the details follow\n{}", self.render())?,
+        }
+        write!(f, "note: please file an issue at {} and attach the report", ISSUE_URL)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::core::edition::Edition;
+use std::path::{Path, PathBuf};
+
+/// a package's `emerald.toml`, in the input's dir or one above it. only the `[package]` + `[build]`
+/// keys the compiler uses r read, anything else is left 4 other tools
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    pub path: PathBuf,
+    pub name: Option<String>,
+    pub edition: Option<Edition>,
+    /// `[build] passes`, the same list `--passes` takes
+    pub passes: Option<String>,
+}
+
+impl Manifest {
+    pub const FILE_NAME: &'static str = "emerald.toml";
+
+    /// the closest manifest above `input`, None if the file isnt in a package
+    pub fn find(input: &Path) -> Result<Option<Self>, String> {
+        let start = std::fs::canonicalize(input).unwrap_or_else(|_| input.to_path_buf());
+        for dir in start.ancestors().skip(1) {
+            let path = dir.join(Self::FILE_NAME);
+            if path.is_file() {
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                return Self::parse(&path, &text).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// `key = "value"` lines under `[package]` + `[build]`, `#` starts a comment
+    pub fn parse(path: &Path, text: &str) -> Result<Self, String> {
+        let mut manifest = Manifest { path: path.to_path_buf(), ..Default::default() };
+        let mut section = String::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| format!("{}:{}: {}", path.display(), i + 1, message);
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(format!("expected `key = value`, found '{}'", line)));
+            };
+            if section != "package" && section != "build" {
+                continue;
+            }
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .ok_or_else(|| error(format!("'{}' must be a string", key.trim())))?;
+            match (section.as_str(), key.trim()) {
+                ("package", "name") => manifest.name = Some(value.to_string()),
+                ("build", "passes") => manifest.passes = Some(value.to_string()),
+                ("package", "edition") => {
+                    manifest.edition = Some(Edition::from_str(value).ok_or_else(|| {
+                        error(format!("unknown edition '{}', expected 2025 or 2026", value))
+                    })?);
+                }
+                _ => {}
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// edition `input` is compiled w/, the default one outside a package
+    pub fn edition_for(input: &Path) -> Result<Edition, String> {
+        Ok(Self::find(input)?.and_then(|m| m.edition).unwrap_or_default())
+    }
+
+    /// the `[build] passes` of `input`'s package, if it sets any
+    pub fn passes_for(input: &Path) -> Result<Option<String>, String> {
+        Ok(Self::find(input)?.and_then(|m| m.passes))
+    }
+}
@@ -1,13 +1,25 @@
 pub mod args;
+pub mod backends;
+pub mod cache;
 pub mod compiler;
+pub mod daemon;
 pub mod error_display;
+pub mod events;
+pub mod ice;
+pub mod manifest;
 pub mod output;
+pub mod passes;
 pub mod progress;
+pub mod remote;
+pub mod repl;
 pub mod build_system;
+pub mod size;
+pub mod highlight;
 
 pub use args::*;
 pub use compiler::*;
 pub use error_display::*;
+pub use manifest::Manifest;
 pub use output::*;
 pub use progress::*;
 pub use build_system::*;
\ No newline at end of file
@@ -0,0 +1,60 @@
+use crate::backend::ports::optimizer::OptimizationPass;
+use crate::core::mir::passes::{PassManager, PASS_NAMES};
+use crate::frontend::semantic::type_checker::closest_name;
+
+/// marks a pass in `--passes` as 1 4 the LLVM optimizer, eg `llvm:gvn`
+pub const LLVM_PREFIX: &str = "llvm:";
+
+/// what `--passes` or the manifest's `[build] passes` asks 4: the MIR passes in the order they run
+/// after copy prop, + the LLVM fragments added after its `default<On>` pipeline. inlining + the
+/// cleanup steps around the MIR passes always run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PassPlan {
+    pub mir: Vec<String>,
+    pub llvm: Vec<OptimizationPass>,
+}
+
+impl PassPlan {
+    /// a comma separated list, `default` stands 4 the MIR passes run w/o the option
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut plan = Self::default();
+        for name in spec.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if name == "default" {
+                plan.mir.extend(PASS_NAMES.iter().map(|n| n.to_string()));
+            } else if let Some(pass) = name.strip_prefix(LLVM_PREFIX).and_then(OptimizationPass::from_name) {
+                plan.llvm.push(pass);
+            } else if PassManager::create(name).is_some() {
+                plan.mir.push(name.to_string());
+            } else {
+                return Err(unknown_pass(name));
+            }
+        }
+        Ok(plan)
+    }
+
+    /// the MIR passes, ready 2 schedule
+    pub fn mir_passes(&self) -> PassManager {
+        PassManager::from_names(&self.mir).expect("parse only keeps registered passes")
+    }
+}
+
+/// evry name `--passes` takes
+pub fn pass_names() -> Vec<String> {
+    let mut names = vec!["default".to_string()];
+    names.extend(PASS_NAMES.iter().map(|n| n.to_string()));
+    names.extend(OptimizationPass::NAMES.iter().map(|n| format!("{}{}", LLVM_PREFIX, n)));
+    names
+}
+
+fn unknown_pass(name: &str) -> String {
+    let names = pass_names();
+    // an LLVM pass w/o its prefix is the likeliest slip
+    let suggestion = match OptimizationPass::from_name(name) {
+        Some(_) => Some(format!("{}{}", LLVM_PREFIX, name)),
+        None => closest_name(name, &names).map(str::to_string),
+    };
+    match suggestion {
+        Some(suggestion) => format!("Unknown optimization pass '{}', did you mean '{}'?", name, suggestion),
+        None => format!("Unknown optimization pass '{}', expected one of: {}", name, names.join(", ")),
+    }
+}
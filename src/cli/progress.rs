@@ -1,4 +1,6 @@
+use crate::cli::events::{BuildEvent, EventStream};
 use owo_colors::OwoColorize;
+use std::time::Instant;
 
 /// compilation phase tracking
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -33,6 +35,23 @@ impl CompilePhase {
         }
     }
 
+    /// how `--message-format=json` names it
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompilePhase::Loading => "loading",
+            CompilePhase::Lexing => "lexing",
+            CompilePhase::Parsing => "parsing",
+            CompilePhase::SemanticAnalysis => "semantic-analysis",
+            CompilePhase::HirLowering => "hir-lowering",
+            CompilePhase::HirOptimization => "hir-optimization",
+            CompilePhase::MirLowering => "mir-lowering",
+            CompilePhase::MirOptimization => "mir-optimization",
+            CompilePhase::CodeGeneration => "codegen",
+            CompilePhase::Linking => "linking",
+            CompilePhase::Complete => "complete",
+        }
+    }
+
     pub fn display(&self) {
         println!("{} {}", "→".bright_blue(), self.as_str().bright_white());
     }
@@ -42,6 +61,9 @@ impl CompilePhase {
 pub struct ProgressTracker {
     current_phase: Option<CompilePhase>,
     verbose: bool,
+    /// --message-format=json, each phase is reported as it starts + ends instead of displayed
+    events: Option<EventStream>,
+    phase_start: Option<Instant>,
 }
 
 impl ProgressTracker {
@@ -49,12 +71,29 @@ impl ProgressTracker {
         Self {
             current_phase: None,
             verbose,
+            events: None,
+            phase_start: None,
         }
     }
 
+    pub fn with_events(mut self, events: EventStream) -> Self {
+        self.events = Some(events);
+        self
+    }
+
     pub fn set_phase(&mut self, phase: CompilePhase) {
-        if self.verbose {
-            phase.display();
+        match &self.events {
+            Some(events) => {
+                if let (Some(current), Some(start)) = (self.current_phase, self.phase_start) {
+                    events.emit(BuildEvent::PhaseFinished(current, start.elapsed()));
+                }
+                if phase != CompilePhase::Complete {
+                    events.emit(BuildEvent::PhaseStarted(phase));
+                }
+                self.phase_start = Some(Instant::now());
+            }
+            None if self.verbose => phase.display(),
+            None => {}
         }
         self.current_phase = Some(phase);
     }
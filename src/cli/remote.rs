@@ -0,0 +1,430 @@
+use crate::backend::factory::{BackendRegistry, BackendType};
+use crate::backend::ports::codegen::{BackendInput, DebugInfo, DebugInfoLevel, OptimizationLevel, PanicStrategy};
+use crate::backend::ports::emitter::EmitType;
+use crate::backend::ports::optimizer::OptimizationPass;
+use crate::backend::BackendBridge;
+use crate::cli::cache::{sha256, ArtifactCache, BackendFingerprint, Digest};
+use crate::cli::daemon::{random_token, read_private, same_token, user_file, write_private};
+use crate::cli::output::Output;
+use crate::core::mir::wire::{decode_functions, encode_functions, Reader, WireError, Writer};
+use crate::core::mir::MirFunction;
+use crate::core::target::TargetInfo;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// where -C workers + -C remote send codegen units instead of compiling them in this process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Builder {
+    /// an `emerald worker` child of this emc, fed over its stdin + stdout
+    Worker,
+    /// an `emerald serve` at host:port
+    Remote(String),
+}
+
+impl fmt::Display for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Builder::Worker => write!(f, "worker"),
+            Builder::Remote(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+/// 1 object 2 build: a codegen unit's MIR + evry setting the bridge would get, so a builder needs
+/// nothing frm the client's config or filesystem
+#[derive(Debug, Clone)]
+pub struct RemoteJob {
+    pub backend: BackendType,
+    pub opt_level: String,
+    pub target: Option<String>,
+    pub panic: PanicStrategy,
+    pub passes: Vec<OptimizationPass>,
+    /// fns 2 define, none = all of them
+    pub unit: Option<Vec<String>>,
+    pub debug_info: Option<DebugInfo>,
+    pub functions: Vec<MirFunction>,
+}
+
+const DEBUG_LEVELS: [DebugInfoLevel; 3] = [DebugInfoLevel::None, DebugInfoLevel::LineTablesOnly, DebugInfoLevel::Full];
+
+impl RemoteJob {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::new();
+        w.put(&self.backend.as_str().to_string());
+        w.put(&self.opt_level);
+        w.put(&self.target);
+        w.put(&self.panic.as_str().to_string());
+        w.put(&self.passes.iter().map(|pass| pass.name().to_string()).collect::<Vec<_>>());
+        w.put(&self.unit);
+        match &self.debug_info {
+            Some(debug_info) => {
+                w.u8(1);
+                w.u8(DEBUG_LEVELS.iter().position(|level| *level == debug_info.level).unwrap() as u8);
+                w.put(&debug_info.file.to_string_lossy().to_string());
+                // sorted so the same job always encodes 2 the same bytes + hits the same cache entry
+                let mut lines: Vec<(String, u32)> = debug_info.function_lines.iter().map(|(f, l)| (f.clone(), *l)).collect();
+                lines.sort();
+                w.put(&lines);
+            }
+            None => w.u8(0),
+        }
+        encode_functions(&self.functions, &mut w);
+        w.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let invalid = |e: WireError| format!("Invalid job: {}", e);
+        let mut r = Reader::new(bytes);
+        let backend: String = r.get().map_err(invalid)?;
+        let backend = BackendType::from_str(&backend).ok_or_else(|| format!("Invalid job: unknown backend '{}'", backend))?;
+        let opt_level = r.get().map_err(invalid)?;
+        let target = r.get().map_err(invalid)?;
+        let panic: String = r.get().map_err(invalid)?;
+        let panic = PanicStrategy::from_str(&panic).ok_or_else(|| format!("Invalid job: unknown panic strategy '{}'", panic))?;
+        let passes: Vec<String> = r.get().map_err(invalid)?;
+        let passes = passes.into_iter().map(|name| OptimizationPass::from_name(&name).unwrap_or(OptimizationPass::Custom(name))).collect();
+        let unit = r.get().map_err(invalid)?;
+        let debug_info = match r.get::<bool>().map_err(invalid)? {
+            true => {
+                let level = DEBUG_LEVELS.get(r.u8().map_err(invalid)? as usize).copied().ok_or("Invalid job: unknown debuginfo level")?;
+                let file: String = r.get().map_err(invalid)?;
+                let lines: Vec<(String, u32)> = r.get().map_err(invalid)?;
+                Some(DebugInfo { level, file: PathBuf::from(file), function_lines: lines.into_iter().collect() })
+            }
+            false => None,
+        };
+        let functions = decode_functions(&mut r).map_err(invalid)?;
+        if !r.is_done() {
+            return Err("Invalid job: trailing bytes after the MIR".to_string());
+        }
+        Ok(Self { backend, opt_level, target, panic, passes, unit, debug_info, functions })
+    }
+
+    /// what a builder caches the object under, the same job frm any client is the same key
+    pub fn key(encoded: &[u8]) -> Digest {
+        sha256(encoded)
+    }
+
+    /// compile 2 an object at `object` w/ a backend frm `registry`
+    pub fn run(self, registry: &BackendRegistry, object: &Path) -> Result<(), String> {
+        let factory = registry
+            .get_factory(self.backend)
+            .ok_or_else(|| format!("Backend '{}' not available on this builder", self.backend.as_str()))?;
+        let mut bridge = BackendBridge::from_factory(factory).map_err(|e| format!("Failed to create backend: {}", e))?;
+        if let Some(opt_level) = OptimizationLevel::from_str(&self.opt_level) {
+            bridge.set_optimization_level(opt_level);
+        }
+        for pass in self.passes {
+            bridge.add_pass(pass);
+        }
+        if let Some(target) = self.target {
+            bridge.set_target_triple(target);
+        }
+        bridge.set_panic_strategy(self.panic);
+        if let Some(unit) = self.unit {
+            bridge.set_codegen_unit(unit);
+        }
+        if let Some(debug_info) = self.debug_info {
+            bridge.set_debug_info(debug_info);
+        }
+        bridge
+            .compile_and_emit(BackendInput::Mir(self.functions), EmitType::Object, object)
+            .map_err(|e| format!("Backend compilation failed: {}", e))
+    }
+}
+
+/// where `emerald serve` keeps its token by default, a client on another machine needs a copy
+pub fn token_file() -> PathBuf {
+    user_file("emerald-serve", "token")
+}
+
+/// the token in `file`, which only its owner may read or write
+pub fn read_token(file: &Path) -> Result<String, String> {
+    read_private(file)
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| format!("No builder token in {}, it must hold one + be readable by its owner alone", file.display()))
+}
+
+/// `file`'s token, a new 1 is made if theres none yet
+pub fn load_or_create_token(file: &Path) -> Result<String, String> {
+    if file.exists() {
+        return read_token(file);
+    }
+    let token = random_token();
+    write_private(file, &format!("{}\n", token)).map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+    Ok(token)
+}
+
+/// a `JOB` frame's payload, the token every job 2 a server carries + the encoded job
+pub fn job_payload(token: &str, job: &[u8]) -> Vec<u8> {
+    let mut w = Writer::new();
+    w.put(&token.to_string());
+    w.put(&job.to_vec());
+    w.into_bytes()
+}
+
+/// the biggest frame a reader takes, a peer cant make it buffer more than this
+pub const MAX_FRAME: usize = 256 << 20;
+
+/// `<kind> <len>\n` + len bytes. a client sends `JOB`s, a builder answers each w/ `OBJ` + the
+/// object or `ERR` + a message, in order on the same stream
+pub fn write_frame(out: &mut impl Write, kind: &str, payload: &[u8]) -> io::Result<()> {
+    writeln!(out, "{} {}", kind, payload.len())?;
+    out.write_all(payload)?;
+    out.flush()
+}
+
+/// the next frame, None when the stream ends b4 1 starts
+pub fn read_frame(input: &mut impl BufRead) -> io::Result<Option<(String, Vec<u8>)>> {
+    let mut header = String::new();
+    // a header is a short line, a peer that never ends 1 is cut off
+    if input.by_ref().take(64).read_line(&mut header)? == 0 {
+        return Ok(None);
+    }
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("Invalid frame header '{}'", header.trim_end()));
+    if !header.ends_with('\n') {
+        return Err(invalid());
+    }
+    let (kind, len) = header.trim_end().split_once(' ').ok_or_else(invalid)?;
+    let len: usize = len.parse().map_err(|_| invalid())?;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Frame of {} bytes is over the {} byte limit", len, MAX_FRAME)));
+    }
+    let mut payload = Vec::new();
+    input.take(len as u64).read_to_end(&mut payload)?;
+    if payload.len() != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Frame ended early"));
+    }
+    Ok(Some((kind.to_string(), payload)))
+}
+
+/// builds jobs 4 `emerald worker` + `emerald serve`, the objects a cache dir is given r kept
+/// under the job's fingerprint like -C incremental keeps them
+pub struct JobRunner {
+    registry: BackendRegistry,
+    cache: Option<PathBuf>,
+    /// what evry job must carry, none 4 a worker whose only client is the emc that spawned it
+    token: Option<String>,
+    /// 4 naming scratch objects, several connections build at once
+    next: AtomicUsize,
+}
+
+impl JobRunner {
+    pub fn new(registry: BackendRegistry) -> Self {
+        Self { registry, cache: None, token: None, next: AtomicUsize::new(0) }
+    }
+
+    pub fn with_cache(mut self, dir: PathBuf) -> Self {
+        self.cache = Some(dir);
+        self
+    }
+
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// the object 4 an encoded job
+    pub fn build(&self, encoded: &[u8]) -> Result<Vec<u8>, String> {
+        let job = RemoteJob::decode(encoded)?;
+        let key = RemoteJob::key(encoded);
+        let cache = match &self.cache {
+            Some(root) => Some(ArtifactCache::open(root, &self.fingerprint(&job))?),
+            None => None,
+        };
+        if let Some(bytes) = cache.as_ref().and_then(|cache| fs::read(cache.object(key)).ok()) {
+            return Ok(bytes);
+        }
+        let n = self.next.fetch_add(1, Ordering::Relaxed);
+        let object = std::env::temp_dir().join(format!("emc-job-{}-{}.o", std::process::id(), n));
+        let built = job.run(&self.registry, &object).and_then(|_| {
+            if let Some(cache) = &cache {
+                if let Err(e) = cache.store(key, &object) {
+                    Output::warning(&e);
+                }
+            }
+            fs::read(&object).map_err(|e| format!("Failed to read {}: {}", object.display(), e))
+        });
+        let _ = fs::remove_file(&object);
+        built
+    }
+
+    /// the key already covers evry setting, the fingerprint only sorts the cache 4 `emerald clean`
+    fn fingerprint(&self, job: &RemoteJob) -> BackendFingerprint {
+        let version = self.registry.get_factory(job.backend).map(|factory| factory.version()).unwrap_or_default();
+        let target = job.target.clone().unwrap_or_else(|| TargetInfo::host().triple);
+        BackendFingerprint::new(job.backend, version, target, job.opt_level.clone()).with_setting("panic", job.panic.as_str())
+    }
+
+    /// answer evry job on `input` until it ends, a job that fails 2 build doesnt end the stream.
+    /// 1 w/ the wrong token does, the client isnt 1 this runner builds 4
+    pub fn serve_stream(&self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        while let Some((kind, payload)) = read_frame(input)? {
+            let reply = match kind.as_str() {
+                "JOB" => {
+                    let mut r = Reader::new(&payload);
+                    let (token, job) = match (r.get::<String>(), r.get::<Vec<u8>>()) {
+                        (Ok(token), Ok(job)) => (token, job),
+                        _ => {
+                            write_frame(output, "ERR", b"Invalid job frame")?;
+                            continue;
+                        }
+                    };
+                    if self.token.as_ref().is_some_and(|expected| !same_token(&token, expected)) {
+                        return write_frame(output, "ERR", b"The builder refused the job, its token doesn't match");
+                    }
+                    self.build(&job)
+                }
+                other => Err(format!("Unknown request '{}'", other)),
+            };
+            match reply {
+                Ok(object) => write_frame(output, "OBJ", &object)?,
+                Err(message) => write_frame(output, "ERR", message.as_bytes())?,
+            }
+        }
+        Ok(())
+    }
+
+    /// `emerald serve`, a thread per client. its reachable by the network so it wont run w/o a token
+    pub fn serve(self: Arc<Self>, listener: TcpListener) -> io::Result<()> {
+        if self.token.is_none() {
+            return Err(io::Error::other("A builder that serves over the network needs a token"));
+        }
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let runner = Arc::clone(&self);
+            std::thread::spawn(move || {
+                let peer = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+                let served = stream.try_clone().and_then(|input| runner.serve_stream(&mut BufReader::new(input), &mut &stream));
+                if let Err(e) = served {
+                    Output::warning(&format!("Connection from {} failed: {}", peer, e));
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// an open stream 2 a builder
+struct Connection {
+    /// dropped 1st so a worker sees its stdin close + exits b4 its waited on
+    output: Option<Box<dyn Write + Send>>,
+    input: Box<dyn BufRead + Send>,
+    child: Option<Child>,
+}
+
+impl Connection {
+    fn open(builder: &Builder) -> io::Result<Self> {
+        match builder {
+            Builder::Worker => {
+                let mut child = Command::new(std::env::current_exe()?)
+                    .arg("worker")
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+                let output = child.stdin.take().map(|stdin| Box::new(stdin) as Box<dyn Write + Send>);
+                let stdout = child.stdout.take().ok_or_else(|| io::Error::other("Worker has no stdout"))?;
+                Ok(Self { output, input: Box::new(BufReader::new(stdout)), child: Some(child) })
+            }
+            Builder::Remote(address) => {
+                let stream = TcpStream::connect(address)?;
+                let input = BufReader::new(stream.try_clone()?);
+                Ok(Self { output: Some(Box::new(stream)), input: Box::new(input), child: None })
+            }
+        }
+    }
+
+    /// send a job, the builder's object or its error message
+    fn exchange(&mut self, token: &str, job: &[u8]) -> io::Result<Result<Vec<u8>, String>> {
+        let output = self.output.as_mut().ok_or_else(|| io::Error::other("Connection closed"))?;
+        write_frame(output, "JOB", &job_payload(token, job))?;
+        match read_frame(&mut self.input)? {
+            Some((kind, object)) if kind == "OBJ" => Ok(Ok(object)),
+            Some((kind, message)) if kind == "ERR" => Ok(Err(String::from_utf8_lossy(&message).to_string())),
+            Some((kind, _)) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unexpected reply '{}'", kind))),
+            None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Builder closed the connection")),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.output = None;
+        if let Some(child) = &mut self.child {
+            let _ = child.wait();
+        }
+    }
+}
+
+struct Slot {
+    builder: Builder,
+    connection: Mutex<Option<Connection>>,
+    /// couldnt be reached, its jobs r built locally 4 the rest of the build
+    down: AtomicBool,
+}
+
+/// hands jobs 2 builders round robin, each builder takes 1 at a time over a connection thats
+/// opened on its 1st job + kept 4 the rest
+pub struct Dispatcher {
+    slots: Vec<Slot>,
+    next: AtomicUsize,
+    /// the servers' token, workers take any
+    token: String,
+}
+
+impl Dispatcher {
+    pub fn new(builders: &[Builder]) -> Self {
+        let slots = builders
+            .iter()
+            .map(|builder| Slot { builder: builder.clone(), connection: Mutex::new(None), down: AtomicBool::new(false) })
+            .collect();
+        Self { slots, next: AtomicUsize::new(0), token: String::new() }
+    }
+
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// build `job` on a builder + write the object 2 `object`. false when no builder could be
+    /// reached, the caller builds it itself then, an error is the build failing
+    pub fn compile(&self, job: &RemoteJob, object: &Path) -> Result<bool, String> {
+        let encoded = job.encode();
+        while let Some(slot) = self.pick() {
+            let mut connection = slot.connection.lock().unwrap_or_else(|e| e.into_inner());
+            let reply = match connection.as_mut() {
+                Some(open) => open.exchange(&self.token, &encoded),
+                None => Connection::open(&slot.builder).and_then(|open| connection.insert(open).exchange(&self.token, &encoded)),
+            };
+            match reply {
+                Ok(Ok(bytes)) => {
+                    fs::write(object, bytes).map_err(|e| format!("Failed to write {}: {}", object.display(), e))?;
+                    return Ok(true);
+                }
+                Ok(Err(message)) => return Err(message),
+                Err(e) => {
+                    *connection = None;
+                    if !slot.down.swap(true, Ordering::Relaxed) {
+                        Output::warning(&format!("Builder {} failed, building its jobs locally: {}", slot.builder, e));
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn pick(&self) -> Option<&Slot> {
+        let up: Vec<&Slot> = self.slots.iter().filter(|slot| !slot.down.load(Ordering::Relaxed)).collect();
+        if up.is_empty() {
+            return None;
+        }
+        Some(up[self.next.fetch_add(1, Ordering::Relaxed) % up.len()])
+    }
+}
@@ -0,0 +1,304 @@
+use crate::backend::ports::execution::{EntryValue, ExecutionEngine, ExecutionError};
+use crate::backend::{BackendBridge, BackendRegistry, CompileError, Module};
+use crate::cli::args::CompileConfig;
+use crate::cli::compiler::Compiler;
+use crate::core::ast::expr::Expr;
+use crate::core::ast::item::Function;
+use crate::core::ast::stmt::Stmt;
+use crate::core::ast::{Ast, Item};
+use crate::core::hir::{Hir, HirExpr, HirItem, HirReturnStmt, HirStmt};
+use crate::core::mir::MirFunction;
+use crate::core::optimizations::HirOptimizer;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::{Lexer, Token, TokenKind};
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::symbol_table::SymbolTable;
+use crate::frontend::semantic::{Prelude, SemanticAnalyzer};
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan::{ByteIndex, FileId, Span};
+use std::collections::HashSet;
+
+/// an interactive session, each entry is checked against evrything entered b4 it + run on one
+/// persistent jit (or interpreter)
+///
+/// all entries r appended 2 one source file so spans frm earlier entries stay valid + diagnostics
+/// point at the line just typed. a declaration (`def`, `struct`, ...) is kept as an item, anything
+/// else becomes the body of a fresh entry fn `__repl_N`. locals dont outlive a fn so `let`s +
+/// assignments of earlier entries r replayed in front of the new statements, a bare expression or
+/// control flow runs once. only fns the engine hasnt seen yet r compiled
+pub struct Repl {
+    compiler: Compiler,
+    registry: BackendRegistry,
+    engine: Box<dyn ExecutionEngine>,
+    /// modules r read by the jit lazily, their bridges have 2 stay alive
+    bridges: Vec<BackendBridge>,
+    reporter: Reporter,
+    file_id: FileId,
+    source: String,
+    prelude: Option<Prelude>,
+    items: Vec<Item>,
+    history: Vec<Stmt>,
+    symbols: SymbolTable,
+    /// fns + imported modules already in the engine
+    compiled: HashSet<String>,
+    modules: HashSet<String>,
+    entries: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplError {
+    #[error("{0} error(s) in entry")]
+    Invalid(usize),
+
+    #[error("{0}")]
+    Backend(String),
+
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+}
+
+impl Repl {
+    pub fn new(config: CompileConfig) -> Result<Self, String> {
+        let compiler = Compiler::new(config);
+        let registry = BackendRegistry::new();
+        let engine = compiler.jit_factory(&registry)?.create_execution_engine().map_err(|e| e.to_string())?;
+        let mut reporter = Reporter::new();
+        for &(lint, level) in &compiler.config().lints {
+            reporter.set_lint_level(lint, level);
+        }
+        let file_id = reporter.add_file("<repl>".to_string(), String::new());
+        let prelude = (!compiler.config().no_prelude).then(|| Prelude::load(&mut reporter));
+        Ok(Self {
+            compiler,
+            registry,
+            engine,
+            bridges: Vec::new(),
+            reporter,
+            file_id,
+            source: String::new(),
+            prelude,
+            items: Vec::new(),
+            history: Vec::new(),
+            symbols: SymbolTable::new(),
+            compiled: HashSet::new(),
+            modules: HashSet::new(),
+            entries: 0,
+        })
+    }
+
+    /// check + run one entry, the value of a trailing expression comes back. a rejected entry
+    /// leaves the session as it was, its diagnostics r in `reporter()` until the next entry
+    pub fn eval(&mut self, input: &str) -> Result<Option<EntryValue>, ReplError> {
+        self.reporter.take_diagnostics();
+        let offset = self.source.len();
+        self.source.push_str(input);
+        self.source.push('\n');
+        self.reporter.files_mut().update(self.file_id, self.source.clone());
+        self.entries += 1;
+
+        let edition = self.compiler.config().edition;
+        let tokens = Lexer::new(&self.source, self.file_id, &mut self.reporter)
+            .with_edition(edition)
+            .with_offset(offset)
+            .tokenize();
+        let mut parser = Parser::new(tokens.clone(), self.file_id, &mut self.reporter).with_edition(edition);
+        let (new_items, stmts) = if is_declaration(&tokens) {
+            (parser.parse().items, Vec::new())
+        } else {
+            (Vec::new(), parser.parse_statements())
+        };
+        self.reject_if_errors()?;
+
+        let name = format!("__repl_{}", self.entries);
+        let end = ByteIndex(self.source.len() as u32);
+        let mut body = self.history.clone();
+        body.extend(stmts.iter().cloned());
+        let mut items = self.items.clone();
+        items.extend(new_items.iter().cloned());
+        items.push(Item::Function(Function {
+            name: name.clone(),
+            generics: Vec::new(),
+            params: Vec::new(),
+            return_type: None,
+            body: Some(body),
+            uses: Vec::new(),
+            target_features: Vec::new(),
+            deprecated: None,
+            span: Span::new(ByteIndex(offset as u32), end),
+        }));
+        let mut ast = Ast { items, span: Span::new(ByteIndex(0), end) };
+        if let Some(prelude) = &self.prelude {
+            ast = prelude.inject(&ast, true);
+        }
+
+        let target = self.compiler.target_info();
+        let null_checks = self.compiler.config().null_checks;
+        let mut analyzer = SemanticAnalyzer::new(&mut self.reporter, self.file_id)
+            .with_target(target.clone())
+            .with_edition(edition);
+        if let Some(prelude) = &self.prelude {
+            analyzer = analyzer.with_prelude(prelude.clone());
+        }
+        let symbols = analyzer.analyze(&ast);
+        let modules: Vec<_> = analyzer
+            .imported_modules()
+            .into_iter()
+            .filter(|(module, _, _)| !self.modules.contains(*module))
+            .map(|(module, module_ast, module_symbols)| self.compiler.lower_module(module, module_ast, module_symbols, &target))
+            .collect();
+        // earlier entries r checked again, only their errors r worth repeating
+        for diagnostic in self.reporter.take_diagnostics() {
+            let earlier = diagnostic.file_id == self.file_id && diagnostic.span.start().to_usize() < offset;
+            if !earlier || matches!(diagnostic.severity, Severity::Error) {
+                self.reporter.add_diagnostic(diagnostic);
+            }
+        }
+        self.reject_if_errors()?;
+
+        let mut hir = HirLowerer::new(symbols.clone()).with_target(target).lower(&ast);
+        let returns = return_last_expr(&mut hir, &name);
+        HirOptimizer::new().optimize(&mut hir);
+        let mut functions = MirLowerer::new().with_null_checks(null_checks).lower(&hir);
+        functions.retain(|f| f.name == name || !self.compiled.contains(&f.name));
+        self.compiler.mir_optimizer().optimize_all(&mut functions);
+
+        for module in modules {
+            let compiled = self.compile(&module.functions, &format!("module '{}'", module.name))?;
+            self.engine.add_module(&compiled)?;
+            self.modules.insert(module.name);
+        }
+        let module = self.compile(&functions, "entry")?;
+        let value = self.engine.call_entry(&module, &name, &returns);
+        // fns that made it in2 the engine cant be added again, even if the entry then failed
+        if !matches!(value, Err(ExecutionError::InvalidModule(_))) {
+            self.compiled.extend(functions.iter().filter(|f| f.name != name).map(|f| f.name.clone()));
+        }
+        let value = value?;
+
+        self.items.extend(new_items);
+        self.history.extend(stmts.into_iter().filter(is_replayed));
+        self.symbols = symbols;
+        Ok((returns != Type::Primitive(PrimitiveType::Void)).then_some(value))
+    }
+
+    /// whether `text` stops in the middle of a block or expression, the caller reads another line
+    pub fn is_incomplete(&self, text: &str) -> bool {
+        let mut reporter = Reporter::new();
+        let file_id = reporter.add_file("<repl>".to_string(), text.to_string());
+        let edition = self.compiler.config().edition;
+        let tokens = Lexer::new(text, file_id, &mut reporter).with_edition(edition).tokenize();
+        let eof = tokens.last().map_or(0, |t| t.span.start().to_usize());
+        let declaration = is_declaration(&tokens);
+        let mut parser = Parser::new(tokens, file_id, &mut reporter).with_edition(edition);
+        if declaration {
+            // a `def` header at the end of input parses as a decl w/o a body
+            let items = parser.parse().items;
+            if matches!(items.last(), Some(Item::Function(f)) if f.body.is_none()) {
+                return true;
+            }
+        } else {
+            parser.parse_statements();
+        }
+        reporter
+            .diagnostics()
+            .iter()
+            .any(|d| matches!(d.severity, Severity::Error) && d.span.start().to_usize() >= eof)
+    }
+
+    /// diagnostics of the last entry, spans r in the session's `<repl>` file
+    pub fn reporter(&self) -> &Reporter {
+        &self.reporter
+    }
+
+    /// symbols of evrything entered so far
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    fn reject_if_errors(&self) -> Result<(), ReplError> {
+        let errors = self
+            .reporter
+            .diagnostics()
+            .iter()
+            .filter(|d| matches!(d.severity, Severity::Error))
+            .count();
+        if errors > 0 {
+            return Err(ReplError::Invalid(errors));
+        }
+        Ok(())
+    }
+
+    fn compile(&mut self, functions: &[MirFunction], what: &str) -> Result<Module, ReplError> {
+        let factory = self.compiler.jit_factory(&self.registry).map_err(ReplError::Backend)?;
+        let mut bridge = self.compiler.bridge(factory).map_err(ReplError::Backend)?;
+        let error = |e: CompileError| ReplError::Backend(format!("{}: {}", what, e));
+        let mut module = bridge.compile_from_mir(functions).map_err(error)?;
+        bridge.optimize(&mut module).map_err(error)?;
+        self.bridges.push(bridge);
+        Ok(module)
+    }
+}
+
+/// entries starting w/ these r items, not statements
+fn is_declaration(tokens: &[Token]) -> bool {
+    tokens.first().is_some_and(|t| {
+        matches!(
+            t.kind,
+            TokenKind::Def
+                | TokenKind::Struct
+                | TokenKind::Enum
+                | TokenKind::Trait
+                | TokenKind::Implement
+                | TokenKind::Foreign
+                | TokenKind::Import
+                | TokenKind::Require
+                | TokenKind::Module
+                | TokenKind::Use
+                | TokenKind::Declare
+                | TokenKind::At
+        )
+    })
+}
+
+/// statements that bind or change a local run again in later entries
+fn is_replayed(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Let(_) => true,
+        Stmt::Expr(e) => matches!(e.expr, Expr::Assignment(_)),
+        _ => false,
+    }
+}
+
+/// the entry fn returns its last statement's value if its a primitive or a string, the type
+/// that comes back
+fn return_last_expr(hir: &mut Hir, name: &str) -> Type {
+    let void = Type::Primitive(PrimitiveType::Void);
+    let Some(func) = hir.items.iter_mut().find_map(|item| match item {
+        HirItem::Function(f) if f.name == name => Some(f),
+        _ => None,
+    }) else {
+        return void;
+    };
+    let Some(body) = func.body.as_mut() else {
+        return void;
+    };
+    let ty = match body.last() {
+        Some(HirStmt::Expr(last)) if !matches!(last.expr, HirExpr::Assignment(_)) => last.expr.type_().clone(),
+        _ => return void,
+    };
+    let returnable = match &ty {
+        Type::Primitive(p) => *p != PrimitiveType::Void,
+        Type::String => true,
+        _ => false,
+    };
+    if !returnable {
+        return void;
+    }
+    if let Some(HirStmt::Expr(last)) = body.pop() {
+        body.push(HirStmt::Return(HirReturnStmt { value: Some(last.expr), span: last.span }));
+    }
+    func.return_type = Some(ty.clone());
+    ty
+}
@@ -0,0 +1,123 @@
+use crate::backend::elf::{ElfFile, STT_FUNC, STT_OBJECT};
+use crate::core::mir::SPECIALIZATION_MARKER;
+use std::collections::HashMap;
+
+/// emerald symbol name split back into its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemangledName {
+    /// `math` in `math::sqrt`, trait impls keep `Trait::Type`
+    pub module: Option<String>,
+    /// generic fn the symbol was instantiated from, or the fn itself
+    pub origin: String,
+    /// what a specialization is 4, eg `int` in `max$int`, several r comma separated
+    pub specialization: Option<String>,
+}
+
+/// undo `module::member` paths and the `name$type1$type2` a specialization is named w/, only
+/// the marker splits so `read_file_int` is a fn of its own
+pub fn demangle(symbol: &str) -> DemangledName {
+    let (path, specialization) = match symbol.split_once(SPECIALIZATION_MARKER) {
+        Some((path, rest)) => (path, Some(rest.split(SPECIALIZATION_MARKER).collect::<Vec<_>>().join(", "))),
+        None => (symbol, None),
+    };
+    let (module, origin) = match path.rfind("::") {
+        Some(idx) => (Some(path[..idx].to_string()), &path[idx + 2..]),
+        None => (None, path),
+    };
+    DemangledName { module, origin: origin.to_string(), specialization }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSize {
+    pub symbol: String,
+    pub size: u64,
+    pub is_function: bool,
+    pub name: DemangledName,
+}
+
+/// size breakdown of the defined fns and data in an object or binary
+#[derive(Debug, Clone, Default)]
+pub struct SizeReport {
+    pub symbols: Vec<SymbolSize>,
+}
+
+impl SizeReport {
+    pub fn from_elf(bytes: &[u8]) -> Result<Self, String> {
+        let elf = ElfFile::parse(bytes).map_err(|e| e.to_string())?;
+        let mut symbols: Vec<SymbolSize> = elf
+            .symbols()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|s| s.section != 0 && s.size > 0 && (s.kind == STT_FUNC || s.kind == STT_OBJECT))
+            .filter(|s| !s.name.is_empty())
+            .map(|s| SymbolSize {
+                name: demangle(&s.name),
+                size: s.size,
+                is_function: s.kind == STT_FUNC,
+                symbol: s.name,
+            })
+            .collect();
+        if symbols.is_empty() {
+            return Err("no sized symbols found (is the file stripped?)".to_string());
+        }
+        symbols.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.symbol.cmp(&b.symbol)));
+        Ok(Self { symbols })
+    }
+
+    pub fn total(&self) -> u64 {
+        self.symbols.iter().map(|s| s.size).sum()
+    }
+
+    /// (module, bytes) largest first, top level items under `<root>`
+    pub fn by_module(&self) -> Vec<(String, u64)> {
+        let mut modules: HashMap<String, u64> = HashMap::new();
+        for s in &self.symbols {
+            let module = s.name.module.clone().unwrap_or_else(|| "<root>".to_string());
+            *modules.entry(module).or_insert(0) += s.size;
+        }
+        let mut rows: Vec<(String, u64)> = modules.into_iter().collect();
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        rows
+    }
+
+    /// (generic fn, instances, bytes) 4 fns w/ at least one specialization
+    pub fn by_generic(&self) -> Vec<(String, usize, u64)> {
+        let mut generics: HashMap<String, (usize, u64)> = HashMap::new();
+        for s in self.symbols.iter().filter(|s| s.is_function && s.name.specialization.is_some()) {
+            let origin = match &s.name.module {
+                Some(module) => format!("{}::{}", module, s.name.origin),
+                None => s.name.origin.clone(),
+            };
+            let entry = generics.entry(origin).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += s.size;
+        }
+        let mut rows: Vec<(String, usize, u64)> =
+            generics.into_iter().map(|(name, (count, size))| (name, count, size)).collect();
+        rows.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        rows
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = format!("{:>10}  symbol\n", "bytes");
+        for s in &self.symbols {
+            let kind = if s.is_function { "" } else { " (data)" };
+            out.push_str(&format!("{:>10}  {}{}\n", s.size, s.symbol, kind));
+        }
+        out.push_str(&format!("{:>10}  total\n", self.total()));
+
+        out.push_str(&format!("\n{:>10}  module\n", "bytes"));
+        for (module, size) in self.by_module() {
+            out.push_str(&format!("{:>10}  {}\n", size, module));
+        }
+
+        let generics = self.by_generic();
+        if !generics.is_empty() {
+            out.push_str(&format!("\n{:>10}  {:>9}  generic function\n", "bytes", "instances"));
+            for (origin, count, size) in generics {
+                out.push_str(&format!("{:>10}  {:>9}  {}\n", size, count, origin));
+            }
+        }
+        out
+    }
+}
@@ -22,6 +22,10 @@ pub enum Expr {
     ArrayLiteral(ArrayLiteralExpr),
     ModuleAccess(ModuleAccessExpr),
     StructLiteral(StructLiteralExpr),
+    New(NewExpr),
+    Delete(DeleteExpr),
+    Try(TryExpr),
+    Tuple(TupleExpr),
     Null,
 }
 
@@ -171,6 +175,7 @@ pub struct ComptimeExpr {
 #[derive(Debug, Clone)]
 pub struct ArrayLiteralExpr {
     pub elements: Vec<Expr>,
+    pub repeat: Option<usize>, // [value; count] - elements holds the single value
     pub span: Span,
 }
 
@@ -188,6 +193,52 @@ pub struct StructLiteralExpr {
     pub span: Span,
 }
 
+/// `new value`, a heap copy of `value` frm the runtime allocator
+#[derive(Debug, Clone)]
+pub struct NewExpr {
+    pub value: Box<Expr>,
+    pub span: Span,
+}
+
+/// `delete p`, hands the memory `new` gave back 2 the allocator
+#[derive(Debug, Clone)]
+pub struct DeleteExpr {
+    pub pointer: Box<Expr>,
+    pub span: Span,
+}
+
+/// `expr?` or `try expr`, the value of a `value or error` + returns the error 2 the caller
+#[derive(Debug, Clone)]
+pub struct TryExpr {
+    pub expr: Box<Expr>,
+    pub span: Span,
+}
+
+/// `(1, 2.5)`, or the targets of `a, b = pair`
+#[derive(Debug, Clone)]
+pub struct TupleExpr {
+    pub elements: Vec<Expr>,
+    pub span: Span,
+}
+
+impl StructLiteralExpr {
+    /// `{ x: 1 }` w/o a name is an anonymous record
+    pub fn is_record(&self) -> bool {
+        self.struct_name.is_empty()
+    }
+
+    /// `f { x: 1, y: 2 }` when f is a fn - named args, passed as 1 record
+    pub fn as_record_call(&self) -> CallExpr {
+        let record = StructLiteralExpr { struct_name: String::new(), fields: self.fields.clone(), span: self.span };
+        CallExpr {
+            callee: Box::new(Expr::Variable(VariableExpr { name: self.struct_name.clone(), span: self.span })),
+            args: vec![Expr::StructLiteral(record)],
+            generic_args: None,
+            span: self.span,
+        }
+    }
+}
+
 impl Expr {
     pub fn span(&self) -> Span {
         match self {
@@ -210,6 +261,10 @@ impl Expr {
             Expr::ArrayLiteral(e) => e.span,
             Expr::ModuleAccess(e) => e.span,
             Expr::StructLiteral(e) => e.span,
+            Expr::New(e) => e.span,
+            Expr::Delete(e) => e.span,
+            Expr::Try(e) => e.span,
+            Expr::Tuple(e) => e.span,
             Expr::Null => Span::new(ByteIndex(0), ByteIndex(0)),
         }
     }
@@ -7,11 +7,13 @@ use codespan::Span;
 pub enum Item {
     Function(Function),
     Struct(Struct),
+    Enum(Enum),
     Trait(Trait),
     TraitImpl(TraitImpl),
     Module(Module),
     Foreign(Foreign),
     Require(Require),
+    Import(Import),
     Use(Use),
     Global(Global),
     ForwardDecl(ForwardDecl),
@@ -32,6 +34,9 @@ pub struct Function {
     pub return_type: Option<Type>,
     pub body: Option<Vec<Stmt>>,
     pub uses: Vec<String>,
+    /// `@target_feature("avx2")` isas 2 clone the fn 4, dispatched at runtime
+    pub target_features: Vec<String>,
+    pub deprecated: Option<Deprecation>,
     pub span: Span,
 }
 
@@ -39,6 +44,8 @@ pub struct Function {
 pub struct Param {
     pub name: String,
     pub type_: Type,
+    /// `comptime n : int` - arg must be known at cmpl time, the fn is specialized per value
+    pub comptime: bool,
     pub span: Span,
 }
 
@@ -54,6 +61,25 @@ pub struct Struct {
     pub name: String,
     pub generics: Vec<GenericParam>,
     pub fields: Vec<Field>,
+    /// `@derive(Eq, Hash)` traits whose impls r synthesized after parsing
+    pub derives: Vec<String>,
+    pub deprecated: Option<Deprecation>,
+    pub span: Span,
+}
+
+/// `enum Name` w/ one variant per line, `Circle(float)` or a bare `Empty`
+#[derive(Debug, Clone)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    pub deprecated: Option<Deprecation>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: String,
+    pub fields: Vec<Type>,
     pub span: Span,
 }
 
@@ -61,6 +87,14 @@ pub struct Struct {
 pub struct Field {
     pub name: String,
     pub type_: Type,
+    pub deprecated: Option<Deprecation>,
+    pub span: Span,
+}
+
+/// `@deprecated("reason")` b4 a fn, struct, enum or field, evry use of it warns
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    pub reason: Option<String>,
     pub span: Span,
 }
 
@@ -69,6 +103,16 @@ pub struct Trait {
     pub name: String,
     pub generics: Vec<GenericParam>,
     pub methods: Vec<TraitMethod>,
+    pub consts: Vec<AssocConst>, // value is the default
+    pub span: Span,
+}
+
+/// `const MAX : int = 10` in a trait or impl, read as `Type::MAX` at cmpl time
+#[derive(Debug, Clone)]
+pub struct AssocConst {
+    pub name: String,
+    pub type_: Type,
+    pub value: Option<Expr>,
     pub span: Span,
 }
 
@@ -86,6 +130,7 @@ pub struct TraitImpl {
     pub type_name: String,
     pub generics: Vec<GenericParam>,
     pub methods: Vec<Function>,
+    pub consts: Vec<AssocConst>,
     pub span: Span,
 }
 
@@ -120,6 +165,35 @@ pub struct Require {
     pub span: Span,
 }
 
+/// `import util::strings` - a module file found by its path, members r `strings::name`
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub path: Vec<String>,
+    /// `import m (a, b)` only brings in the listed members
+    pub members: Option<Vec<String>>,
+    /// `import m as n`, members r `n::name` here
+    pub alias: Option<String>,
+    /// `pub import`, whoever imports this file gets the members as its own
+    pub public: bool,
+    pub span: Span,
+}
+
+impl Import {
+    /// the module's own name, the path's last segment. its fns r `name::fn` in its object
+    pub fn name(&self) -> &str {
+        self.path.last().map(String::as_str).unwrap_or_default()
+    }
+
+    /// the qualifier members r accessed thru, the alias if there is one
+    pub fn qualifier(&self) -> &str {
+        self.alias.as_deref().unwrap_or(self.name())
+    }
+
+    pub fn exposes(&self, member: &str) -> bool {
+        self.members.as_ref().is_none_or(|m| m.iter().any(|n| n == member))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Use {
     pub path: Vec<String>,
@@ -1,11 +1,13 @@
 pub mod expr;
 pub mod item;
+pub mod pattern;
 pub mod stmt;
 pub mod types;
 pub mod visitor;
 
 pub use expr::*;
 pub use item::*;
+pub use pattern::*;
 pub use stmt::*;
 pub use types::*;
 pub use visitor::*;
@@ -0,0 +1,36 @@
+use crate::core::ast::expr::LiteralExpr;
+use codespan::Span;
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Literal(LiteralExpr),
+    Range(RangePattern),
+    Variant(VariantPattern),
+}
+
+/// `start .. end`, both ends inclusive
+#[derive(Debug, Clone)]
+pub struct RangePattern {
+    pub start: i64,
+    pub end: i64,
+    pub span: Span,
+}
+
+/// `Shape::Circle(r)` or just `Circle(r)`, `_` skips a field
+#[derive(Debug, Clone)]
+pub struct VariantPattern {
+    pub enum_name: Option<String>,
+    pub variant: String,
+    pub bindings: Vec<String>,
+    pub span: Span,
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Literal(l) => l.span,
+            Pattern::Range(r) => r.span,
+            Pattern::Variant(v) => v.span,
+        }
+    }
+}
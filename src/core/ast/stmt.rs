@@ -1,4 +1,5 @@
 use crate::core::ast::expr::Expr;
+use crate::core::ast::pattern::Pattern;
 use codespan::Span;
 
 #[derive(Debug, Clone)]
@@ -11,6 +12,9 @@ pub enum Stmt {
     For(ForStmt),
     Break(BreakStmt),
     Continue(ContinueStmt),
+    Match(MatchStmt),
+    Guard(GuardStmt),
+    Defer(DeferStmt),
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +63,37 @@ pub struct ForStmt {
     pub span: Span,
 }
 
+/// runs `else_branch` when `condition` is false, which must diverge
+#[derive(Debug, Clone)]
+pub struct GuardStmt {
+    pub condition: Expr,
+    pub else_branch: Vec<Stmt>,
+    pub span: Span,
+}
+
+/// runs `body` when the enclosing scope exits, at its end or a `return`, the last deferred first
+#[derive(Debug, Clone)]
+pub struct DeferStmt {
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchStmt {
+    pub scrutinee: Expr,
+    pub arms: Vec<MatchArm>,
+    pub else_branch: Option<Vec<Stmt>>,
+    pub span: Span,
+}
+
+/// `when a, b` - runs `body` if any of the patterns match
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub patterns: Vec<Pattern>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone)]
 pub struct BreakStmt {
     pub span: Span,
@@ -7,6 +7,10 @@ pub enum Type {
     Named(NamedType),
     Generic(GenericType),
     Function(FunctionType),
+    TraitObject(String), // dyn Trait
+    Record(Vec<(String, Type)>), // { x: int, y: int }
+    Tuple(Vec<Type>), // (int, float)
+    Fallible(FallibleType), // int or Error
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,6 +23,9 @@ pub enum PrimitiveType {
     Float,
     Bool,
     Char,
+    UByte,
+    UInt,
+    ULong,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -33,6 +40,13 @@ pub struct PointerType {
     pub nullable: bool,
 }
 
+/// `value or error`, what a fn that can fail returns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallibleType {
+    pub value: Box<Type>,
+    pub error: Box<Type>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NamedType {
     pub name: String,
@@ -26,6 +26,10 @@ pub trait AstVisitor {
             Expr::ArrayLiteral(e) => self.visit_array_literal(e),
             Expr::ModuleAccess(e) => self.visit_module_access(e),
             Expr::StructLiteral(e) => self.visit_struct_literal(e),
+            Expr::New(e) => self.visit_new(e),
+            Expr::Delete(e) => self.visit_delete(e),
+            Expr::Try(e) => self.visit_try(e),
+            Expr::Tuple(e) => self.visit_tuple(e),
             Expr::Null => self.visit_null(),
         }
     }
@@ -146,6 +150,28 @@ pub trait AstVisitor {
         unimplemented!()
     }
 
+    fn visit_new(&mut self, expr: &crate::core::ast::expr::NewExpr) -> Self::Result {
+        self.visit_expr(&expr.value);
+        unimplemented!()
+    }
+
+    fn visit_delete(&mut self, expr: &crate::core::ast::expr::DeleteExpr) -> Self::Result {
+        self.visit_expr(&expr.pointer);
+        unimplemented!()
+    }
+
+    fn visit_try(&mut self, expr: &crate::core::ast::expr::TryExpr) -> Self::Result {
+        self.visit_expr(&expr.expr);
+        unimplemented!()
+    }
+
+    fn visit_tuple(&mut self, expr: &crate::core::ast::expr::TupleExpr) -> Self::Result {
+        for element in &expr.elements {
+            self.visit_expr(element);
+        }
+        unimplemented!()
+    }
+
     fn visit_null(&mut self) -> Self::Result {
         unimplemented!()
     }
@@ -160,6 +186,9 @@ pub trait AstVisitor {
             Stmt::For(s) => self.visit_for(s),
             Stmt::Break(s) => self.visit_break(s),
             Stmt::Continue(s) => self.visit_continue(s),
+            Stmt::Match(s) => self.visit_match(s),
+            Stmt::Guard(s) => self.visit_guard(s),
+            Stmt::Defer(s) => self.visit_defer(s),
         }
     }
 
@@ -219,6 +248,36 @@ pub trait AstVisitor {
         unimplemented!()
     }
 
+    fn visit_guard(&mut self, stmt: &crate::core::ast::stmt::GuardStmt) -> Self::Result {
+        self.visit_expr(&stmt.condition);
+        for s in &stmt.else_branch {
+            self.visit_stmt(s);
+        }
+        unimplemented!()
+    }
+
+    fn visit_defer(&mut self, stmt: &crate::core::ast::stmt::DeferStmt) -> Self::Result {
+        for s in &stmt.body {
+            self.visit_stmt(s);
+        }
+        unimplemented!()
+    }
+
+    fn visit_match(&mut self, stmt: &crate::core::ast::stmt::MatchStmt) -> Self::Result {
+        self.visit_expr(&stmt.scrutinee);
+        for arm in &stmt.arms {
+            for s in &arm.body {
+                self.visit_stmt(s);
+            }
+        }
+        if let Some(stmts) = &stmt.else_branch {
+            for s in stmts {
+                self.visit_stmt(s);
+            }
+        }
+        unimplemented!()
+    }
+
     fn visit_break(&mut self, _stmt: &crate::core::ast::stmt::BreakStmt) -> Self::Result {
         unimplemented!()
     }
@@ -231,11 +290,13 @@ pub trait AstVisitor {
         match item {
             Item::Function(f) => self.visit_function(f),
             Item::Struct(s) => self.visit_struct(s),
+            Item::Enum(e) => self.visit_enum(e),
             Item::Trait(t) => self.visit_trait(t),
             Item::TraitImpl(ti) => self.visit_trait_impl(ti),
             Item::Module(m) => self.visit_module(m),
             Item::Foreign(f) => self.visit_foreign(f),
             Item::Require(r) => self.visit_require(r),
+            Item::Import(i) => self.visit_import(i),
             Item::Use(u) => self.visit_use(u),
                     Item::Global(g) => self.visit_global(g),
                     Item::ForwardDecl(f) => self.visit_forward_decl(f),
@@ -255,6 +316,10 @@ pub trait AstVisitor {
         unimplemented!()
     }
 
+    fn visit_enum(&mut self, _e: &crate::core::ast::item::Enum) -> Self::Result {
+        unimplemented!()
+    }
+
     fn visit_trait(&mut self, _t: &crate::core::ast::item::Trait) -> Self::Result {
         unimplemented!()
     }
@@ -281,6 +346,10 @@ pub trait AstVisitor {
         unimplemented!()
     }
 
+    fn visit_import(&mut self, _i: &crate::core::ast::item::Import) -> Self::Result {
+        unimplemented!()
+    }
+
     fn visit_use(&mut self, _u: &crate::core::ast::item::Use) -> Self::Result {
         unimplemented!()
     }
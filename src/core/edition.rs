@@ -0,0 +1,67 @@
+/// language edition a package is written 4, `edition` in emerald.toml or --edition.
+/// a newer edition can reserve words + drop old syntax, code written 4 an older one keeps compiling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Edition {
+    /// the language as it was b4 editions, what a package w/o a manifest gets
+    #[default]
+    E2025,
+    E2026,
+}
+
+/// a change that only applies frm some edition on, the lexer / parser / analyzer ask 4 these
+/// instead of comparing editions themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditionFeature {
+    /// `async`, `await` + `yield` r kept 4 future syntax, not identifiers
+    ReservedKeywords,
+    /// `try expr`, the prefix spelling of `expr?`. `try` is an identifier b4
+    TryKeyword,
+    /// `require "path"` is gone, `import` replaced it
+    ImportOnly,
+}
+
+/// words `ReservedKeywords` takes away frm identifiers
+pub const RESERVED: &[&str] = &["async", "await", "yield"];
+
+impl EditionFeature {
+    /// 1st edition w/ the change
+    pub fn since(&self) -> Edition {
+        match self {
+            EditionFeature::ReservedKeywords | EditionFeature::TryKeyword | EditionFeature::ImportOnly => Edition::E2026,
+        }
+    }
+}
+
+impl Edition {
+    pub const LATEST: Edition = Edition::E2026;
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "2025" => Some(Self::E2025),
+            "2026" => Some(Self::E2026),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Edition::E2025 => "2025",
+            Edition::E2026 => "2026",
+        }
+    }
+
+    pub fn has(&self, feature: EditionFeature) -> bool {
+        *self >= feature.since()
+    }
+
+    /// `word` is a keyword in this edition but the grammar doesnt use it yet
+    pub fn is_reserved(&self, word: &str) -> bool {
+        self.has(EditionFeature::ReservedKeywords) && RESERVED.contains(&word)
+    }
+}
+
+impl std::fmt::Display for Edition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
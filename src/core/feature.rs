@@ -0,0 +1,28 @@
+/// unstable syntax, off til a file opts in w/ `@feature("name")` at its top. lets a construct
+/// ship + change b4 evry program can rely on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// `macro square(x) = x * x`, expanded where `square!(3)` is used
+    Macros,
+}
+
+impl Feature {
+    pub const ALL: &'static [Feature] = &[Feature::Macros];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Feature::Macros => "macros",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|feature| feature.name() == s)
+    }
+
+    /// the error 4 using the construct w/o the gate
+    pub fn gate_message(&self) -> &'static str {
+        match self {
+            Feature::Macros => "Macros are experimental",
+        }
+    }
+}
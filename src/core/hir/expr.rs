@@ -22,6 +22,11 @@ pub enum HirExpr {
     Closure(HirClosureExpr),
     Comptime(HirComptimeExpr),
     ArrayLiteral(HirArrayLiteralExpr),
+    StructLiteral(HirStructLiteralExpr),
+    Variant(HirVariantExpr),
+    New(HirNewExpr),
+    Delete(HirDeleteExpr),
+    Try(HirTryExpr),
     Null,
 }
 
@@ -162,6 +167,29 @@ pub struct HirAtExpr {
     pub span: Span,
 }
 
+/// `type_` is the ref 2 the value's type
+#[derive(Debug, Clone)]
+pub struct HirNewExpr {
+    pub value: Box<HirExpr>,
+    pub type_: Type,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct HirDeleteExpr {
+    pub pointer: Box<HirExpr>,
+    pub type_: Type,
+    pub span: Span,
+}
+
+/// `expr?`, `type_` is the value `expr` holds when it didnt fail
+#[derive(Debug, Clone)]
+pub struct HirTryExpr {
+    pub expr: Box<HirExpr>,
+    pub type_: Type,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone)]
 pub struct HirExistsExpr {
     pub expr: Box<HirExpr>,
@@ -203,6 +231,25 @@ pub struct HirComptimeExpr {
 #[derive(Debug, Clone)]
 pub struct HirArrayLiteralExpr {
     pub elements: Vec<HirExpr>,
+    pub repeat: Option<usize>, // [value; count] - elements holds the single value
+    pub type_: Type,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct HirStructLiteralExpr {
+    pub struct_name: String,
+    pub fields: Vec<(String, HirExpr)>, // in declaration order not source order
+    pub type_: Type,
+    pub span: Span,
+}
+
+/// `Enum::Variant(fields..)`, `tag` is the variant's index in the enum decl
+#[derive(Debug, Clone)]
+pub struct HirVariantExpr {
+    pub variant: String,
+    pub tag: usize,
+    pub fields: Vec<HirExpr>,
     pub type_: Type,
     pub span: Span,
 }
@@ -227,6 +274,11 @@ impl HirExpr {
             HirExpr::Closure(e) => e.span,
             HirExpr::Comptime(e) => e.span,
             HirExpr::ArrayLiteral(e) => e.span,
+            HirExpr::StructLiteral(e) => e.span,
+            HirExpr::Variant(e) => e.span,
+            HirExpr::New(e) => e.span,
+            HirExpr::Delete(e) => e.span,
+            HirExpr::Try(e) => e.span,
             HirExpr::Null => Span::new(ByteIndex(0), ByteIndex(0)),
         }
     }
@@ -250,6 +302,11 @@ impl HirExpr {
             HirExpr::Closure(e) => &e.type_,
             HirExpr::Comptime(e) => &e.type_,
             HirExpr::ArrayLiteral(e) => &e.type_,
+            HirExpr::StructLiteral(e) => &e.type_,
+            HirExpr::Variant(e) => &e.type_,
+            HirExpr::New(e) => &e.type_,
+            HirExpr::Delete(e) => &e.type_,
+            HirExpr::Try(e) => &e.type_,
             HirExpr::Null => {
                 // ret a sttc ref 4 null
                 static NULL_TYPE: once_cell::sync::Lazy<Type> = once_cell::sync::Lazy::new(|| {
@@ -31,6 +31,9 @@ pub struct HirFunction {
     pub return_type: Option<Type>,
     pub body: Option<Vec<HirStmt>>,
     pub uses: Vec<String>,
+    pub target_features: Vec<String>,
+    /// its `ref` result is an allocation the caller owns + frees
+    pub owned_result: bool,
     pub span: Span,
 }
 
@@ -101,6 +104,8 @@ pub struct HirForeignFunction {
     pub name: String,
     pub params: Vec<HirParam>,
     pub return_type: Option<Type>,
+    /// `...` after the params, extra args get the C default promotions
+    pub variadic: bool,
     pub abi: Option<String>,
     pub span: Span,
 }
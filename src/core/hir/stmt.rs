@@ -1,4 +1,4 @@
-use crate::core::hir::expr::HirExpr;
+use crate::core::hir::expr::{HirExpr, HirLiteralExpr};
 use crate::core::types::ty::Type;
 use codespan::Span;
 
@@ -12,6 +12,9 @@ pub enum HirStmt {
     For(HirForStmt),
     Break(HirBreakStmt),
     Continue(HirContinueStmt),
+    Match(HirMatchStmt),
+    Guard(HirGuardStmt),
+    Defer(HirDeferStmt),
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +62,44 @@ pub struct HirForStmt {
     pub span: Span,
 }
 
+#[derive(Debug, Clone)]
+pub struct HirGuardStmt {
+    pub condition: HirExpr,
+    pub else_branch: Vec<HirStmt>,
+    pub span: Span,
+}
+
+/// MIR lowering copies `body` in2 evry exit of the enclosing scope
+#[derive(Debug, Clone)]
+pub struct HirDeferStmt {
+    pub body: Vec<HirStmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct HirMatchStmt {
+    pub scrutinee: HirExpr,
+    pub arms: Vec<HirMatchArm>,
+    pub else_branch: Option<Vec<HirStmt>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct HirMatchArm {
+    pub patterns: Vec<HirPattern>,
+    pub body: Vec<HirStmt>,
+    pub span: Span,
+}
+
+/// literals r typed as the scrutinee, ranges r inclusive
+/// variants bind (field index, name, type) 4 each non `_` binding
+#[derive(Debug, Clone)]
+pub enum HirPattern {
+    Literal(HirLiteralExpr),
+    Range { start: i64, end: i64 },
+    Variant { tag: usize, bindings: Vec<(usize, String, Type)> },
+}
+
 #[derive(Debug, Clone)]
 pub struct HirBreakStmt {
     pub span: Span,
@@ -0,0 +1,53 @@
+/// caps on pathological inputs so they end in a diagnostic instead of the compiler running out of
+/// memory or stack. `-Z limits=key=value,..` raises or lowers them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// bytes in 1 string literal after escapes
+    pub string_literal: usize,
+    /// elements of 1 array literal, `[[0; 10]; 10]` counts 100
+    pub array_elements: usize,
+    /// how deep expressions + blocks nest inside each other
+    pub nesting: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self { string_literal: 1 << 20, array_elements: 1 << 20, nesting: 4096 }
+    }
+}
+
+impl Limits {
+    pub const KEYS: &'static [&'static str] = &["string-literal", "array-elements", "nesting"];
+
+    /// `key=value,..` over the defaults, values take a k, m or g suffix (powers of 1024)
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut limits = Self::default();
+        for pair in spec.split(',').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or_else(|| format!("Expected key=value in -Z limits, got '{}'", pair))?;
+            let value = parse_size(value).ok_or_else(|| format!("Invalid value '{}' for limit '{}'", value, key))?;
+            match key {
+                "string-literal" => limits.string_literal = value,
+                "array-elements" => limits.array_elements = value,
+                "nesting" => limits.nesting = value,
+                _ => return Err(format!("Unknown limit '{}', expected one of: {}", key, Self::KEYS.join(", "))),
+            }
+        }
+        Ok(limits)
+    }
+
+    /// the note on a limit's diagnostic
+    pub fn how_to_raise(key: &str) -> String {
+        format!("`-Z limits={}=<n>` raises the limit", key)
+    }
+}
+
+fn parse_size(value: &str) -> Option<usize> {
+    let lower = value.to_ascii_lowercase();
+    let (digits, shift) = match lower.as_bytes().last()? {
+        b'k' => (&lower[..lower.len() - 1], 10),
+        b'm' => (&lower[..lower.len() - 1], 20),
+        b'g' => (&lower[..lower.len() - 1], 30),
+        _ => (lower.as_str(), 0),
+    };
+    digits.parse::<usize>().ok()?.checked_mul(1 << shift).filter(|n| *n > 0)
+}
@@ -0,0 +1,293 @@
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Constant, Operand};
+use crate::core::mir::{is_heap_alloc, is_heap_free};
+use crate::core::types::ty::Type;
+use std::collections::{HashMap, HashSet};
+
+// which pointers in a fn can point 2 the same memory, 4 the passes that move or remove loads +
+// stores. MIR knows things llvm has 2 rediscover or cant: an alloca, a heap allocation or an
+// aggregate local is memory only this fn can name til its address escapes, + a gep w/ a constant
+// index picks 1 field or element of it
+
+/// what a pointer points in2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Base {
+    /// memory the fn owns, an `alloca`, a heap allocation or a struct/array local, by the local
+    /// that holds it
+    Object(usize),
+    /// wherever a pointer the fn didnt make points, a param or a loaded or returned pointer
+    Pointer(usize),
+    /// a pointer assigned more than once, or a constant
+    Unknown,
+}
+
+/// a pointer as a base + the constant gep indices taken frm it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Place {
+    pub base: Base,
+    /// None once a non-constant index or a byte offset was taken
+    pub path: Option<Vec<i64>>,
+}
+
+impl Place {
+    fn unknown() -> Self {
+        Self { base: Base::Unknown, path: None }
+    }
+
+    fn at(base: Base) -> Self {
+        Self { base, path: Some(Vec::new()) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasResult {
+    /// never the same memory
+    No,
+    May,
+    /// always the same address
+    Must,
+}
+
+/// the places + escaped objects of 1 fn, computed once + queried by the passes. it describes the
+/// fn as it was, a pass that rewrites pointers has 2 build a new 1
+pub struct AliasAnalysis {
+    places: HashMap<usize, Place>,
+    /// aggregate locals, the local is the object rather than pointing 2 1
+    values: HashSet<usize>,
+    escaped: HashSet<usize>,
+}
+
+impl AliasAnalysis {
+    pub fn new(func: &MirFunction) -> Self {
+        let mut defs: HashMap<usize, Vec<&Instruction>> = HashMap::new();
+        for inst in func.basic_blocks.iter().flat_map(|bb| &bb.instructions) {
+            if let Some(dest) = inst.dest() {
+                defs.entry(dest.id).or_default().push(inst);
+            }
+        }
+        let types: HashMap<usize, &Type> = func
+            .locals
+            .iter()
+            .map(|l| (l.local.id, &l.type_))
+            .chain(func.params.iter().map(|p| (p.local.id, &p.type_)))
+            .collect();
+        let params = func.params.iter().map(|p| p.local.id).collect();
+        let mut resolver = Resolver { defs: &defs, types: &types, params, places: HashMap::new() };
+        for &id in types.keys() {
+            resolver.resolve(id);
+        }
+        let places = resolver.places;
+        let values = places
+            .iter()
+            .filter(|(id, p)| p.base == Base::Object(**id) && p.path.as_deref() == Some(&[]) && !allocates(&defs, **id))
+            .map(|(id, _)| *id)
+            .collect();
+        let mut analysis = Self { places, values, escaped: HashSet::new() };
+        analysis.escaped = func.basic_blocks.iter().flat_map(|bb| &bb.instructions).flat_map(|inst| analysis.escapes_in(inst)).collect();
+        analysis
+    }
+
+    /// what `pointer` points in2
+    pub fn place(&self, pointer: &Operand) -> Place {
+        match pointer {
+            Operand::Local(l) => self.places.get(&l.id).cloned().unwrap_or_else(Place::unknown),
+            Operand::Constant(_) | Operand::Function(_) => Place::unknown(),
+        }
+    }
+
+    /// whether the address of `object` (an alloca, heap allocation or aggregate local) leaves the
+    /// fn's sight, passed 2 a call, stored, returned or put somewhere the analysis cant follow.
+    /// freeing it doesnt count
+    pub fn escapes(&self, object: usize) -> bool {
+        self.escaped.contains(&object)
+    }
+
+    /// whether `pointer` is in2 memory nothing outside the fn can reach
+    pub fn is_local(&self, pointer: &Operand) -> bool {
+        matches!(self.place(pointer).base, Base::Object(o) if !self.escapes(o))
+    }
+
+    pub fn alias(&self, a: &Operand, b: &Operand) -> AliasResult {
+        if matches!((a, b), (Operand::Constant(Constant::Null), _) | (_, Operand::Constant(Constant::Null))) {
+            return AliasResult::No;
+        }
+        let (a, b) = (self.place(a), self.place(b));
+        match (a.base, b.base) {
+            (Base::Unknown, _) | (_, Base::Unknown) => AliasResult::May,
+            (Base::Object(x), Base::Object(y)) if x != y => AliasResult::No,
+            (Base::Object(o), Base::Pointer(_)) | (Base::Pointer(_), Base::Object(o)) => {
+                if self.escapes(o) {
+                    AliasResult::May
+                } else {
+                    AliasResult::No
+                }
+            }
+            // 2 params can point 2 the same thing
+            (Base::Pointer(x), Base::Pointer(y)) if x != y => AliasResult::May,
+            _ => match (a.path, b.path) {
+                (Some(a), Some(b)) if a == b => AliasResult::Must,
+                // a field + the struct its in overlap, 2 different fields dont
+                (Some(a), Some(b)) if !a.starts_with(&b) && !b.starts_with(&a) => AliasResult::No,
+                _ => AliasResult::May,
+            },
+        }
+    }
+
+    /// whether running `inst` can change what `pointer` points 2
+    pub fn may_write(&self, inst: &Instruction, pointer: &Operand) -> bool {
+        match inst {
+            Instruction::Store { dest, .. } | Instruction::VolatileStore { dest, .. } | Instruction::Memset { dest, .. } => {
+                self.alias(dest, pointer) != AliasResult::No
+            }
+            Instruction::Call { .. } | Instruction::DynCall { .. } => !self.is_local(pointer),
+            // assigning an aggregate local overwrites the object
+            _ => inst.dest().is_some_and(|d| self.values.contains(&d.id) && self.place(pointer).base == Base::Object(d.id)),
+        }
+    }
+
+    /// whether running `inst` can read what `pointer` points 2
+    pub fn may_read(&self, inst: &Instruction, pointer: &Operand) -> bool {
+        let object = self.place(pointer).base;
+        // an aggregate local used as a value reads all of it
+        let reads_value = || {
+            inst.operands().into_iter().any(|op| matches!(op, Operand::Local(l) if self.values.contains(&l.id) && object == Base::Object(l.id)))
+        };
+        match inst {
+            Instruction::Load { source, .. } | Instruction::VolatileLoad { source, .. } => self.alias(source, pointer) != AliasResult::No,
+            // the caller can read whatever isnt the fn's own once it returns
+            Instruction::Call { .. } | Instruction::DynCall { .. } | Instruction::Ret { .. } => !self.is_local(pointer) || reads_value(),
+            // taking an address isnt reading
+            Instruction::Gep { .. } | Instruction::PtrOffset { .. } => false,
+            _ => reads_value(),
+        }
+    }
+
+    /// objects whose address `inst` lets out of sight
+    fn escapes_in(&self, inst: &Instruction) -> Vec<usize> {
+        let tracked = match inst {
+            // uses that only read, write or compare thru the pointer
+            Instruction::Load { .. }
+            | Instruction::VolatileLoad { .. }
+            | Instruction::Gep { .. }
+            | Instruction::PtrOffset { .. }
+            | Instruction::Eq { .. }
+            | Instruction::Ne { .. }
+            | Instruction::Lt { .. }
+            | Instruction::Le { .. }
+            | Instruction::Gt { .. }
+            | Instruction::Ge { .. } => return Vec::new(),
+            Instruction::Store { source, .. } | Instruction::VolatileStore { source, .. } => vec![source],
+            Instruction::Memset { .. } => return Vec::new(),
+            Instruction::Call { func: Operand::Function(f), .. } if is_heap_free(&f.name) => return Vec::new(),
+            // a pointer copied in2 a local that stays the same place is still followed
+            Instruction::Copy { dest, .. } | Instruction::Phi { dest, .. } => {
+                let into = self.place(&Operand::Local(*dest)).base;
+                return inst
+                    .operands()
+                    .into_iter()
+                    .filter_map(|op| self.pointer_into(op))
+                    .filter(|o| into != Base::Object(*o))
+                    .collect();
+            }
+            _ => inst.operands(),
+        };
+        tracked.into_iter().filter_map(|op| self.pointer_into(op)).collect()
+    }
+
+    /// the object `operand` is a pointer in2, an aggregate local used as a value isnt 1
+    fn pointer_into(&self, operand: &Operand) -> Option<usize> {
+        let Operand::Local(l) = operand else { return None };
+        match self.place(operand).base {
+            Base::Object(o) if !self.values.contains(&l.id) => Some(o),
+            _ => None,
+        }
+    }
+}
+
+/// whether `id` holds new memory, not an aggregate value
+fn allocates(defs: &HashMap<usize, Vec<&Instruction>>, id: usize) -> bool {
+    match defs.get(&id).map(Vec::as_slice) {
+        Some([Instruction::Alloca { .. }]) => true,
+        Some([Instruction::Call { func: Operand::Function(f), .. }]) => is_heap_alloc(&f.name),
+        _ => false,
+    }
+}
+
+fn is_aggregate(ty: Option<&&Type>) -> bool {
+    matches!(ty, Some(Type::Struct(_) | Type::Array(_)))
+}
+
+/// follows each local back 2 where its pointer came frm, memoized
+struct Resolver<'a> {
+    defs: &'a HashMap<usize, Vec<&'a Instruction>>,
+    types: &'a HashMap<usize, &'a Type>,
+    params: HashSet<usize>,
+    places: HashMap<usize, Place>,
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, id: usize) -> Place {
+        if let Some(place) = self.places.get(&id) {
+            return place.clone();
+        }
+        // a cycle thru phis + copies stays unknown
+        self.places.insert(id, Place::unknown());
+        let place = self.place_of(id);
+        self.places.insert(id, place.clone());
+        place
+    }
+
+    fn operand(&mut self, operand: &Operand) -> Place {
+        match operand {
+            Operand::Local(l) => self.resolve(l.id),
+            _ => Place::unknown(),
+        }
+    }
+
+    /// whether `operand` is an aggregate local itself, not a pointer in2 1
+    fn is_value(&mut self, operand: &Operand) -> bool {
+        let Operand::Local(l) = operand else { return false };
+        let place = self.resolve(l.id);
+        place.base == Base::Object(l.id) && place.path.as_deref() == Some(&[]) && !allocates(self.defs, l.id)
+    }
+
+    fn place_of(&mut self, id: usize) -> Place {
+        let aggregate = is_aggregate(self.types.get(&id));
+        let defs = self.defs.get(&id).map(Vec::as_slice).unwrap_or_default();
+        let opaque = if aggregate { Place::at(Base::Object(id)) } else { Place::at(Base::Pointer(id)) };
+        if self.params.contains(&id) {
+            return if defs.is_empty() { opaque } else if aggregate { Place::at(Base::Object(id)) } else { Place::unknown() };
+        }
+        let [def] = defs else {
+            // an aggregate local keeps its storage however often its assigned
+            return if aggregate && !defs.is_empty() { opaque } else { Place::unknown() };
+        };
+        match def {
+            Instruction::Alloca { .. } => Place::at(Base::Object(id)),
+            Instruction::Call { func: Operand::Function(f), .. } if is_heap_alloc(&f.name) => Place::at(Base::Object(id)),
+            Instruction::Gep { base, index, .. } => {
+                let mut place = self.operand(base);
+                match (&mut place.path, index) {
+                    (Some(path), Operand::Constant(Constant::Int(i))) => path.push(*i),
+                    _ => place.path = None,
+                }
+                place
+            }
+            Instruction::PtrOffset { base, .. } => Place { path: None, ..self.operand(base) },
+            // copying an aggregate local makes a new object, copying a pointer 2 1 doesnt
+            Instruction::Copy { source, .. } if self.is_value(source) => Place::at(Base::Object(id)),
+            Instruction::Copy { source: source @ Operand::Local(_), .. } => self.operand(source),
+            Instruction::Phi { incoming, .. } if incoming.iter().any(|(op, _)| self.is_value(op)) => opaque,
+            Instruction::Phi { incoming, .. } => {
+                let places: Vec<Place> = incoming.iter().map(|(op, _)| self.operand(op)).collect();
+                match places.split_first() {
+                    Some((first, rest)) if rest.iter().all(|p| p == first) && first.base != Base::Unknown => first.clone(),
+                    _ if aggregate => opaque,
+                    _ => Place::unknown(),
+                }
+            }
+            _ => opaque,
+        }
+    }
+}
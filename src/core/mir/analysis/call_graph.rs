@@ -0,0 +1,178 @@
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::Operand;
+use std::collections::HashMap;
+
+/// who calls whom directly in a program, fns by their index in the slice it was built frm. a call
+/// 2 a fn outside the slice (libc, another module) isnt an edge, nor is a call thru a pointer or
+/// a vtable: a fn whose address is taken is only marked as such
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallGraph {
+    index: HashMap<String, usize>,
+    callees: Vec<Vec<usize>>,
+    callers: Vec<Vec<usize>>,
+    address_taken: Vec<bool>,
+    /// the strongly connected components, each after evry component it calls
+    sccs: Vec<Vec<usize>>,
+    scc_of: Vec<usize>,
+}
+
+impl CallGraph {
+    pub fn new(functions: &[MirFunction]) -> Self {
+        let index: HashMap<String, usize> = functions.iter().enumerate().map(|(i, f)| (f.name.clone(), i)).collect();
+        let mut callees = vec![Vec::new(); functions.len()];
+        let mut callers = vec![Vec::new(); functions.len()];
+        let mut address_taken = vec![false; functions.len()];
+        for (caller, func) in functions.iter().enumerate() {
+            for inst in func.basic_blocks.iter().flat_map(|bb| &bb.instructions) {
+                let called = match inst {
+                    Instruction::Call { func: Operand::Function(f), .. } => index.get(&f.name).copied(),
+                    _ => None,
+                };
+                if let Some(callee) = called {
+                    if !callees[caller].contains(&callee) {
+                        callees[caller].push(callee);
+                        callers[callee].push(caller);
+                    }
+                }
+                // a fn used as a value rather than called, eg passed as a callback
+                let values = inst.operands().into_iter().skip(usize::from(called.is_some()));
+                for operand in values {
+                    if let Operand::Function(f) = operand {
+                        if let Some(&f) = index.get(&f.name) {
+                            address_taken[f] = true;
+                        }
+                    }
+                }
+            }
+        }
+        let sccs = strongly_connected(&callees);
+        let mut scc_of = vec![0; functions.len()];
+        for (i, scc) in sccs.iter().enumerate() {
+            for &f in scc {
+                scc_of[f] = i;
+            }
+        }
+        Self { index, callees, callers, address_taken, sccs, scc_of }
+    }
+
+    /// the fn called `name`, if the program defines it
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.callees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.callees.is_empty()
+    }
+
+    /// the fns `f` calls directly, each once
+    pub fn callees(&self, f: usize) -> &[usize] {
+        self.callees.get(f).map_or(&[], Vec::as_slice)
+    }
+
+    /// the fns that call `f` directly, each once
+    pub fn callers(&self, f: usize) -> &[usize] {
+        self.callers.get(f).map_or(&[], Vec::as_slice)
+    }
+
+    /// whether `f` is used as a value, so it can be called in ways the graph doesnt see
+    pub fn is_address_taken(&self, f: usize) -> bool {
+        self.address_taken.get(f).copied().unwrap_or(false)
+    }
+
+    /// the strongly connected components bottom-up: a component comes after evry 1 it calls, so
+    /// a pass that wants callees done 1st can walk them in order. fns in a component call each
+    /// other in a cycle
+    pub fn sccs(&self) -> &[Vec<usize>] {
+        &self.sccs
+    }
+
+    /// the component `f` is in, as an index in2 `sccs`
+    pub fn scc_of(&self, f: usize) -> usize {
+        self.scc_of[f]
+    }
+
+    /// whether `f` can call itself again b4 it returns, directly or thru others
+    pub fn is_recursive(&self, f: usize) -> bool {
+        self.sccs[self.scc_of[f]].len() > 1 || self.callees(f).contains(&f)
+    }
+
+    /// evry fn after the ones it calls, except around a cycle
+    pub fn bottom_up(&self) -> Vec<usize> {
+        self.sccs.iter().flatten().copied().collect()
+    }
+
+    /// which fns `roots` can end up calling, roots included. what survives dead fn elimination
+    /// once the roots have main, exports + anything whose address is taken
+    pub fn reachable_from(&self, roots: impl IntoIterator<Item = usize>) -> Vec<bool> {
+        let mut reachable = vec![false; self.len()];
+        let mut worklist: Vec<usize> = roots.into_iter().filter(|f| *f < self.len()).collect();
+        while let Some(f) = worklist.pop() {
+            if !std::mem::replace(&mut reachable[f], true) {
+                worklist.extend(self.callees(f).iter().copied());
+            }
+        }
+        reachable
+    }
+}
+
+/// tarjan's algorithm w/o recursion, so a long call chain cant overflow the stack. components come
+/// out callees 1st, in the order fns r first reached frm index 0 up
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let mut order = vec![UNVISITED; edges.len()];
+    let mut low = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = Vec::new();
+    let mut sccs = Vec::new();
+    let mut next = 0;
+    for root in 0..edges.len() {
+        if order[root] != UNVISITED {
+            continue;
+        }
+        // each fn being visited w/ how many of its edges were followed
+        let mut visiting = vec![(root, 0)];
+        order[root] = next;
+        low[root] = next;
+        next += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&mut (f, ref mut edge)) = visiting.last_mut() {
+            if let Some(&to) = edges[f].get(*edge) {
+                *edge += 1;
+                if order[to] == UNVISITED {
+                    order[to] = next;
+                    low[to] = next;
+                    next += 1;
+                    stack.push(to);
+                    on_stack[to] = true;
+                    visiting.push((to, 0));
+                } else if on_stack[to] {
+                    low[f] = low[f].min(order[to]);
+                }
+                continue;
+            }
+            visiting.pop();
+            if let Some(&(parent, _)) = visiting.last() {
+                low[parent] = low[parent].min(low[f]);
+            }
+            if low[f] == order[f] {
+                let mut scc = Vec::new();
+                while let Some(g) = stack.pop() {
+                    on_stack[g] = false;
+                    scc.push(g);
+                    if g == f {
+                        break;
+                    }
+                }
+                scc.sort_unstable();
+                sccs.push(scc);
+            }
+        }
+    }
+    sccs
+}
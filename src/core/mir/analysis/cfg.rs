@@ -0,0 +1,135 @@
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+
+/// a fn's control flow as its terminators say, the blocks' own `predecessors`/`successors` lists
+/// arent kept up 2 date by evry pass. a block is its id, which is its index in the fn
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg {
+    entry: usize,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl Cfg {
+    pub fn new(func: &MirFunction) -> Self {
+        // a branch 2 a block that doesnt exist is the verifier's 2 report, not an edge
+        let successors: Vec<Vec<usize>> = func
+            .basic_blocks
+            .iter()
+            .map(|bb| bb.instructions.last().map(Instruction::successors).unwrap_or_default())
+            .collect();
+        Self::from_edges(func.entry_block, successors)
+    }
+
+    /// the graph w/ `successors[b]` as the edges out of block `b`, an edge given twice counts once
+    /// + 1 2 a block past the end is dropped
+    pub fn from_edges(entry: usize, mut successors: Vec<Vec<usize>>) -> Self {
+        let len = successors.len();
+        let mut predecessors = vec![Vec::new(); len];
+        for (b, succs) in successors.iter_mut().enumerate() {
+            let mut kept = Vec::with_capacity(succs.len());
+            for &s in succs.iter() {
+                if s < len && !kept.contains(&s) {
+                    kept.push(s);
+                    predecessors[s].push(b);
+                }
+            }
+            *succs = kept;
+        }
+        Self { entry, successors, predecessors }
+    }
+
+    pub fn entry(&self) -> usize {
+        self.entry
+    }
+
+    /// how many blocks, reachable or not
+    pub fn len(&self) -> usize {
+        self.successors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.successors.is_empty()
+    }
+
+    pub fn successors(&self, block: usize) -> &[usize] {
+        self.successors.get(block).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn predecessors(&self, block: usize) -> &[usize] {
+        self.predecessors.get(block).map_or(&[], Vec::as_slice)
+    }
+
+    /// blocks that leave the fn, a `ret` or anything else w/o a successor
+    pub fn exits(&self) -> Vec<usize> {
+        (0..self.len()).filter(|b| self.successors[*b].is_empty()).collect()
+    }
+
+    /// the blocks reachable frm the entry, each after all its successors except along back edges
+    pub fn postorder(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.len());
+        if self.entry >= self.len() {
+            return order;
+        }
+        let mut visited = vec![false; self.len()];
+        // each block w/ how many of its successors were pushed, so deep CFGs dont recurse
+        let mut stack = vec![(self.entry, 0)];
+        visited[self.entry] = true;
+        while let Some((block, next)) = stack.last_mut() {
+            match self.successors[*block].get(*next).copied() {
+                Some(succ) => {
+                    *next += 1;
+                    if !visited[succ] {
+                        visited[succ] = true;
+                        stack.push((succ, 0));
+                    }
+                }
+                None => {
+                    order.push(*block);
+                    stack.pop();
+                }
+            }
+        }
+        order
+    }
+
+    /// the reachable blocks, each b4 its successors except along back edges. the order forward
+    /// dataflow converges fastest in
+    pub fn reverse_postorder(&self) -> Vec<usize> {
+        let mut order = self.postorder();
+        order.reverse();
+        order
+    }
+
+    /// which blocks the entry reaches, by block
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.len()];
+        for b in self.postorder() {
+            reachable[b] = true;
+        }
+        reachable
+    }
+
+    /// whether control can get back 2 `block` after leaving it, ie it's in a loop
+    pub fn on_cycle(&self, block: usize) -> bool {
+        let mut seen = vec![false; self.len()];
+        let mut worklist = self.successors(block).to_vec();
+        while let Some(b) = worklist.pop() {
+            if b == block {
+                return true;
+            }
+            if !std::mem::replace(&mut seen[b], true) {
+                worklist.extend_from_slice(self.successors(b));
+            }
+        }
+        false
+    }
+
+    /// the same blocks w/ evry edge turned around + a virtual exit block at `len()` as the entry,
+    /// w/ an edge 2 each of `exits()`. what post-dominance is dominance in
+    pub fn reversed(&self) -> Self {
+        let mut successors = self.predecessors.clone();
+        successors.push(self.exits());
+        Self::from_edges(self.len(), successors)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::core::mir::analysis::cfg::Cfg;
+use crate::core::mir::function::MirFunction;
+
+/// the dominator tree of a fn, or its post-dominator tree. a block dominates another when evry
+/// path frm the entry 2 it goes thru the 1st, it post-dominates it when evry path frm it 2 an
+/// exit does. built w/ Cooper, Harvey + Kennedy's iterative algorithm over reverse postorder
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dominators {
+    /// the immediate dominator of each block, None 4 the root + blocks the tree doesnt reach
+    idom: Vec<Option<usize>>,
+    /// blocks in the tree, the entry reaches them or, 4 post-dominance, they reach an exit
+    in_tree: Vec<bool>,
+    /// the edges the tree was built over, a block's predecessors in the direction it was built
+    preds: Vec<Vec<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl Dominators {
+    /// who dominates whom in `func`
+    pub fn new(func: &MirFunction) -> Self {
+        Self::of(&Cfg::new(func))
+    }
+
+    /// who post-dominates whom in `func`. blocks w/ several exits have no immediate post-dominator,
+    /// nor does a block that never reaches an exit eg an endless loop
+    pub fn post(func: &MirFunction) -> Self {
+        Self::post_of(&Cfg::new(func))
+    }
+
+    pub fn of(cfg: &Cfg) -> Self {
+        let preds = (0..cfg.len()).map(|b| cfg.predecessors(b).to_vec()).collect();
+        Self::build(cfg, preds, cfg.len())
+    }
+
+    pub fn post_of(cfg: &Cfg) -> Self {
+        let reversed = cfg.reversed();
+        // in the reversed graph a block's predecessors r its successors, the virtual exit isnt 1
+        let preds = (0..cfg.len()).map(|b| cfg.successors(b).to_vec()).collect();
+        Self::build(&reversed, preds, cfg.len())
+    }
+
+    /// the tree of `graph` rooted at its entry, kept 4 its 1st `len` blocks. anything immediately
+    /// dominated by a root past them (the virtual exit) has no immediate dominator
+    fn build(graph: &Cfg, preds: Vec<Vec<usize>>, len: usize) -> Self {
+        let order = graph.reverse_postorder();
+        let mut position = vec![usize::MAX; graph.len()];
+        for (i, b) in order.iter().enumerate() {
+            position[*b] = i;
+        }
+        let mut idom: Vec<Option<usize>> = vec![None; graph.len()];
+        let root = graph.entry();
+        if root < graph.len() {
+            idom[root] = Some(root);
+        }
+
+        // walks the 2 blocks up the tree so far til they meet, the later in the order goes 1st
+        let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+            while a != b {
+                while position[a] > position[b] {
+                    a = idom[a].unwrap();
+                }
+                while position[b] > position[a] {
+                    b = idom[b].unwrap();
+                }
+            }
+            a
+        };
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &b in order.iter().skip(1) {
+                let mut new = None;
+                for &p in graph.predecessors(b) {
+                    if idom[p].is_some() {
+                        new = Some(new.map_or(p, |n| intersect(&idom, p, n)));
+                    }
+                }
+                if idom[b] != new {
+                    idom[b] = new;
+                    changed = true;
+                }
+            }
+        }
+
+        let in_tree: Vec<bool> = (0..len).map(|b| position[b] != usize::MAX).collect();
+        let idom: Vec<Option<usize>> = (0..len).map(|b| idom[b].filter(|d| *d != b && *d < len)).collect();
+        let mut children = vec![Vec::new(); len];
+        for (b, d) in idom.iter().enumerate() {
+            if let Some(d) = d {
+                children[*d].push(b);
+            }
+        }
+        Self { idom, in_tree, preds, children }
+    }
+
+    /// the closest block that strictly dominates `block`, None 4 the root, a block right under the
+    /// virtual exit + blocks outside the tree
+    pub fn idom(&self, block: usize) -> Option<usize> {
+        self.idom.get(block).copied().flatten()
+    }
+
+    /// the blocks `block` immediately dominates
+    pub fn children(&self, block: usize) -> &[usize] {
+        self.children.get(block).map_or(&[], Vec::as_slice)
+    }
+
+    /// whether `block` is in the tree, the entry reaches it or it reaches an exit
+    pub fn contains(&self, block: usize) -> bool {
+        self.in_tree.get(block).copied().unwrap_or(false)
+    }
+
+    /// whether evry path thru `b` goes thru `a` 1st (or after, 4 post-dominance). a block
+    /// dominates itself, a block outside the tree is dominated by nothing
+    pub fn dominates(&self, a: usize, b: usize) -> bool {
+        if !self.contains(a) || !self.contains(b) {
+            return false;
+        }
+        let mut block = Some(b);
+        while let Some(current) = block {
+            if current == a {
+                return true;
+            }
+            block = self.idom(current);
+        }
+        false
+    }
+
+    pub fn strictly_dominates(&self, a: usize, b: usize) -> bool {
+        a != b && self.dominates(a, b)
+    }
+
+    /// the dominance frontier of each block: the blocks it doesnt strictly dominate but dominates
+    /// a predecessor of, where mem2reg puts its phis. 4 post-dominance its the blocks each 1 is
+    /// control dependent on
+    pub fn frontiers(&self) -> Vec<Vec<usize>> {
+        let mut frontiers = vec![Vec::new(); self.idom.len()];
+        for (b, preds) in self.preds.iter().enumerate() {
+            if preds.len() < 2 || !self.contains(b) {
+                continue;
+            }
+            for &p in preds.iter().filter(|p| self.contains(**p)) {
+                let mut runner = Some(p);
+                while let Some(r) = runner.filter(|r| Some(*r) != self.idom(b)) {
+                    if !frontiers[r].contains(&b) {
+                        frontiers[r].push(b);
+                    }
+                    runner = self.idom(r);
+                }
+            }
+        }
+        frontiers
+    }
+}
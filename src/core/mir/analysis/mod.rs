@@ -0,0 +1,10 @@
+pub mod call_graph;
+pub mod cfg;
+pub mod dominators;
+
+pub use call_graph::CallGraph;
+pub use cfg::Cfg;
+pub use dominators::Dominators;
+
+// facts about control flow that passes + the verifier share instead of each working them out
+// again: a fn's CFG frm its terminators, block orders + (post-)dominance, + a program's calls
@@ -36,8 +36,6 @@ impl BasicBlock {
 
     /// chk if this block has a trmntr instrctn
     pub fn has_terminator(&self) -> bool {
-        self.instructions.last().map_or(false, |inst| {
-            matches!(inst, Instruction::Ret { .. } | Instruction::Jump { .. } | Instruction::Br { .. })
-        })
+        self.instructions.last().is_some_and(Instruction::is_terminator)
     }
 }
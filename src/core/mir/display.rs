@@ -0,0 +1,170 @@
+use crate::core::mir::basic_block::BasicBlock;
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Constant, FunctionRef, Local, Operand};
+use crate::core::target::CallConv;
+use crate::core::types::composite::spelling;
+use crate::core::types::ty::Type;
+use std::fmt;
+
+// textual MIR, evrything is printed in the order it's stored so the same input always gives the
+// same text. blocks print their id, locals r `%N` + fns `@name`
+
+impl fmt::Display for Local {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}", self.id)
+    }
+}
+
+impl fmt::Display for FunctionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", self.name)
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::Int(i) => write!(f, "{}", i),
+            // debug keeps the `.0` so a float never reads as an int
+            Constant::Float(x) => write!(f, "{:?}", x),
+            Constant::Bool(b) => write!(f, "{}", b),
+            Constant::Char(c) => write!(f, "'{}'", c.escape_default()),
+            Constant::String(s) => write!(f, "{:?}", s),
+            Constant::Null => write!(f, "null"),
+            Constant::Aggregate(elements) => write!(f, "[{}]", list(elements)),
+        }
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Constant(c) => c.fmt(f),
+            Operand::Local(l) => l.fmt(f),
+            Operand::Function(func) => func.fmt(f),
+        }
+    }
+}
+
+fn list<T: fmt::Display>(items: &[T]) -> String {
+    items.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+fn returns(t: &Option<Type>) -> String {
+    t.as_ref().map_or_else(|| "void".to_string(), spelling)
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Instruction::*;
+        match self {
+            Add { dest, left, right, type_ } => write!(f, "{} = add {} {}, {}", dest, spelling(type_), left, right),
+            Sub { dest, left, right, type_ } => write!(f, "{} = sub {} {}, {}", dest, spelling(type_), left, right),
+            Mul { dest, left, right, type_ } => write!(f, "{} = mul {} {}, {}", dest, spelling(type_), left, right),
+            Div { dest, left, right, type_ } => write!(f, "{} = div {} {}, {}", dest, spelling(type_), left, right),
+            Mod { dest, left, right, type_ } => write!(f, "{} = mod {} {}, {}", dest, spelling(type_), left, right),
+            Eq { dest, left, right } => write!(f, "{} = eq {}, {}", dest, left, right),
+            Ne { dest, left, right } => write!(f, "{} = ne {}, {}", dest, left, right),
+            Lt { dest, left, right } => write!(f, "{} = lt {}, {}", dest, left, right),
+            Le { dest, left, right } => write!(f, "{} = le {}, {}", dest, left, right),
+            Gt { dest, left, right } => write!(f, "{} = gt {}, {}", dest, left, right),
+            Ge { dest, left, right } => write!(f, "{} = ge {}, {}", dest, left, right),
+            And { dest, left, right } => write!(f, "{} = and {}, {}", dest, left, right),
+            Or { dest, left, right } => write!(f, "{} = or {}, {}", dest, left, right),
+            Not { dest, operand } => write!(f, "{} = not {}", dest, operand),
+            Load { dest, source, type_ } => write!(f, "{} = load {} {}", dest, spelling(type_), source),
+            Store { dest, source, type_ } => write!(f, "store {} {}, {}", spelling(type_), source, dest),
+            VolatileLoad { dest, source, type_ } => write!(f, "{} = load volatile {} {}", dest, spelling(type_), source),
+            VolatileStore { dest, source, type_ } => write!(f, "store volatile {} {}, {}", spelling(type_), source, dest),
+            Alloca { dest, type_ } => write!(f, "{} = alloca {}", dest, spelling(type_)),
+            Gep { dest, base, index, type_ } => write!(f, "{} = gep {} {}, {}", dest, spelling(type_), base, index),
+            PtrOffset { dest, base, offset, type_ } => write!(f, "{} = offset {} {}, {}", dest, spelling(type_), base, offset),
+            Memset { dest, value, count, type_ } => write!(f, "memset {} {}, {} x {}", spelling(type_), dest, value, count),
+            Call { dest, func, args, return_type, fixed_args, conv } => {
+                if let Some(dest) = dest {
+                    write!(f, "{} = ", dest)?;
+                }
+                write!(f, "call ")?;
+                if *conv != CallConv::C {
+                    write!(f, "{:?} ", conv)?;
+                }
+                write!(f, "{} {}({})", returns(return_type), func, list(args))?;
+                if let Some(fixed) = fixed_args {
+                    write!(f, " variadic after {}", fixed)?;
+                }
+                Ok(())
+            }
+            Ret { value: Some(value) } => write!(f, "ret {}", value),
+            Ret { value: None } => write!(f, "ret"),
+            Br { condition, then_bb, else_bb } => write!(f, "br {}, bb{}, bb{}", condition, then_bb, else_bb),
+            Jump { target } => write!(f, "jump bb{}", target),
+            Switch { value, arms, default } => {
+                let arms: Vec<_> = arms.iter().map(|(v, bb)| format!("{} => bb{}", v, bb)).collect();
+                write!(f, "switch {} [{}], bb{}", value, arms.join(", "), default)
+            }
+            StrLen { dest, source } => write!(f, "{} = strlen {}", dest, source),
+            StrConcat { dest, left, right } => write!(f, "{} = strconcat {}, {}", dest, left, right),
+            StrEq { dest, left, right } => write!(f, "{} = streq {}, {}", dest, left, right),
+            MakeDyn { dest, data, vtable } => {
+                write!(f, "{} = dyn {}, {} [{}]", dest, data, vtable.symbol(), vtable.methods.join(", "))
+            }
+            DynCall { dest, object, slot, args, return_type } => {
+                if let Some(dest) = dest {
+                    write!(f, "{} = ", dest)?;
+                }
+                write!(f, "dyncall {} {}.{}({})", returns(return_type), object, slot, list(args))
+            }
+            MakeVariant { dest, type_, tag, fields } => {
+                write!(f, "{} = variant {} #{}({})", dest, spelling(type_), tag, list(fields))
+            }
+            EnumTag { dest, source } => write!(f, "{} = tag {}", dest, source),
+            VariantField { dest, source, type_, tag, index } => {
+                write!(f, "{} = field {} {}#{}.{}", dest, spelling(type_), source, tag, index)
+            }
+            Phi { dest, type_, incoming } => {
+                let incoming: Vec<_> = incoming.iter().map(|(v, bb)| format!("[{}, bb{}]", v, bb)).collect();
+                write!(f, "{} = phi {} {}", dest, spelling(type_), incoming.join(", "))
+            }
+            Copy { dest, source, type_ } => write!(f, "{} = copy {} {}", dest, spelling(type_), source),
+        }
+    }
+}
+
+impl fmt::Display for BasicBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "bb{}:", self.id)?;
+        for inst in &self.instructions {
+            writeln!(f, "    {}", inst)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for MirFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params: Vec<_> = self.params.iter().map(|p| format!("{}: {}", p.local, spelling(&p.type_))).collect();
+        write!(f, "fn @{}({}) -> {}", self.name, params.join(", "), returns(&self.return_type))?;
+        if !self.target_features.is_empty() {
+            write!(f, " target_features({})", self.target_features.join(", "))?;
+        }
+        writeln!(f, " {{")?;
+        for local in &self.locals {
+            write!(f, "    let {}: {}", local.local, spelling(&local.type_))?;
+            match &local.name {
+                Some(name) => writeln!(f, "  ; {}", name)?,
+                None => writeln!(f)?,
+            }
+        }
+        for (i, block) in self.basic_blocks.iter().enumerate() {
+            if i > 0 || !self.locals.is_empty() {
+                writeln!(f)?;
+            }
+            if block.id == self.entry_block && self.entry_block != 0 {
+                writeln!(f, "  ; entry")?;
+            }
+            write!(f, "  {}", block)?;
+        }
+        writeln!(f, "}}")
+    }
+}
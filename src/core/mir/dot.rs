@@ -0,0 +1,56 @@
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+
+// graphviz DOT 4 a fn's control flow, what --emit=cfg-dot writes. 1 `digraph` per fn, each
+// block a box w/ its textual MIR, each edge labelled w/ the branch that takes it.
+// `dot -Tsvg -O out.dot` renders evry graph in the file 2 its own svg
+
+impl MirFunction {
+    /// the fn's CFG as a DOT `digraph`, `name` is what the graph is called
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph {} {{\n", quote(name));
+        dot.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+        for block in &self.basic_blocks {
+            let mut label = format!("bb{}:\\l", block.id);
+            for inst in &block.instructions {
+                label.push_str(&format!("  {}\\l", escape(&inst.to_string())));
+            }
+            let entry = if block.id == self.entry_block { ", penwidth=2" } else { "" };
+            dot.push_str(&format!("  bb{} [label=\"{}\"{}];\n", block.id, label, entry));
+        }
+        for block in &self.basic_blocks {
+            for (target, label) in block.instructions.last().map(edges).unwrap_or_default() {
+                match label {
+                    Some(label) => dot.push_str(&format!("  bb{} -> bb{} [label={}];\n", block.id, target, quote(&label))),
+                    None => dot.push_str(&format!("  bb{} -> bb{};\n", block.id, target)),
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// a terminator's targets w/ what the edge is labelled, a plain jump has no label
+fn edges(terminator: &Instruction) -> Vec<(usize, Option<String>)> {
+    match terminator {
+        Instruction::Jump { target } => vec![(*target, None)],
+        Instruction::Br { then_bb, else_bb, .. } => {
+            vec![(*then_bb, Some("true".to_string())), (*else_bb, Some("false".to_string()))]
+        }
+        Instruction::Switch { arms, default, .. } => arms
+            .iter()
+            .map(|(value, bb)| (*bb, Some(value.to_string())))
+            .chain(std::iter::once((*default, Some("default".to_string()))))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
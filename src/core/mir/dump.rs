@@ -0,0 +1,95 @@
+use crate::core::mir::function::MirFunction;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// which passes -Z dump-mir snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpFilter {
+    All,
+    Pass(String),
+}
+
+impl DumpFilter {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "all" => DumpFilter::All,
+            pass => DumpFilter::Pass(pass.to_string()),
+        }
+    }
+
+    pub fn wants(&self, pass: &str) -> bool {
+        match self {
+            DumpFilter::All => true,
+            DumpFilter::Pass(name) => name == pass,
+        }
+    }
+}
+
+/// writes a fn's MIR b4 + after each pass the filter picks as `<fn>.<step>.<pass>.before.mir` +
+/// `.after.mir`, so `diff` on a pair shows what the pass did. steps count evry pass run on the
+/// fn, picked or not, so the files sort in pipeline order
+#[derive(Debug)]
+pub struct MirDump {
+    dir: PathBuf,
+    filter: DumpFilter,
+    steps: HashMap<String, usize>,
+    /// the 1st write that failed, the optimizer has no way 2 stop on it
+    error: Option<String>,
+}
+
+impl MirDump {
+    pub fn new(dir: impl Into<PathBuf>, filter: DumpFilter) -> Self {
+        Self { dir: dir.into(), filter, steps: HashMap::new(), error: None }
+    }
+
+    /// runs `pass` over `func`, snapshotting it either side if the filter picks the pass
+    pub fn step<R>(&mut self, pass: &str, func: &mut MirFunction, run: impl FnOnce(&mut MirFunction) -> R) -> R {
+        let step = self.steps.entry(func.name.clone()).or_insert(0);
+        let index = *step;
+        *step += 1;
+        if !self.filter.wants(pass) {
+            return run(func);
+        }
+        let before = func.to_string();
+        let result = run(func);
+        self.write(&func.name, index, pass, "before", &before);
+        self.write(&func.name, index, pass, "after", &func.to_string());
+        result
+    }
+
+    /// `step` 4 a pass over the whole program, eg the inliner
+    pub fn step_all<R>(&mut self, pass: &str, functions: &mut [MirFunction], run: impl FnOnce(&mut [MirFunction]) -> R) -> R {
+        let wanted = self.filter.wants(pass);
+        let before: Vec<String> = functions.iter().filter(|_| wanted).map(|f| f.to_string()).collect();
+        let result = run(functions);
+        for (i, func) in functions.iter().enumerate() {
+            let step = self.steps.entry(func.name.clone()).or_insert(0);
+            let index = *step;
+            *step += 1;
+            if wanted {
+                self.write(&func.name, index, pass, "before", &before[i]);
+                self.write(&func.name, index, pass, "after", &func.to_string());
+            }
+        }
+        result
+    }
+
+    fn write(&mut self, name: &str, index: usize, pass: &str, stage: &str, mir: &str) {
+        if self.error.is_some() {
+            return;
+        }
+        let path = self.dir.join(format!("{}.{:02}.{}.{}.mir", file_name(name), index, pass, stage));
+        if let Err(e) = std::fs::create_dir_all(&self.dir).and_then(|_| std::fs::write(&path, mir)) {
+            self.error = Some(format!("Failed to write '{}': {}", path.display(), e));
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// `name` w/ anything a path cant hold, eg a module's `::`, as `_`
+fn file_name(name: &str) -> String {
+    name.chars().map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect()
+}
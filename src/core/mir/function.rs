@@ -1,4 +1,5 @@
 use crate::core::mir::basic_block::BasicBlock;
+use crate::core::mir::instruction::Instruction;
 use crate::core::mir::operand::Local;
 use crate::core::types::ty::Type;
 
@@ -11,6 +12,8 @@ pub struct MirFunction {
     pub entry_block: usize,
     pub locals: Vec<LocalInfo>,
     pub next_local_id: usize,
+    /// isas 2 emit clones 4 (empty = single version)
+    pub target_features: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +41,7 @@ impl MirFunction {
             entry_block: 0,
             locals: Vec::new(),
             next_local_id: 0,
+            target_features: Vec::new(),
         }
     }
 
@@ -71,4 +75,23 @@ impl MirFunction {
     pub fn block_has_terminator(&self, id: usize) -> bool {
         self.basic_blocks.get(id).map_or(false, |bb| bb.has_terminator())
     }
+
+    /// successors + predecessors frm the terminators
+    pub fn relink(&mut self) {
+        let edges: Vec<(usize, Vec<usize>)> = self
+            .basic_blocks
+            .iter()
+            .map(|bb| (bb.id, bb.instructions.last().map(Instruction::successors).unwrap_or_default()))
+            .collect();
+        for bb in &mut self.basic_blocks {
+            bb.successors.clear();
+            bb.predecessors.clear();
+        }
+        for (from, successors) in edges {
+            for to in successors {
+                self.basic_blocks[from].add_successor(to);
+                self.basic_blocks[to].add_predecessor(from);
+            }
+        }
+    }
 }
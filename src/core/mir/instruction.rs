@@ -1,4 +1,5 @@
 use crate::core::mir::operand::{Operand, Local};
+use crate::core::target::CallConv;
 use crate::core::types::ty::Type;
 
 #[derive(Debug, Clone)]
@@ -26,16 +27,232 @@ pub enum Instruction {
     // memory
     Load { dest: Local, source: Operand, type_: Type },
     Store { dest: Operand, source: Operand, type_: Type },
+    VolatileLoad { dest: Local, source: Operand, type_: Type }, // never elided or reordered w/ other volatiles
+    VolatileStore { dest: Operand, source: Operand, type_: Type },
     Alloca { dest: Local, type_: Type },
     Gep { dest: Local, base: Operand, index: Operand, type_: Type }, // get element ptr
+    PtrOffset { dest: Local, base: Operand, offset: Operand, type_: Type }, // inbounds ptr + offset elements of type_
+    Memset { dest: Operand, value: Operand, count: usize, type_: Type }, // fill count elements of type_ w/ value
 
     // control flow
-    Call { dest: Option<Local>, func: Operand, args: Vec<Operand>, return_type: Option<Type> },
+    Call { dest: Option<Local>, func: Operand, args: Vec<Operand>, return_type: Option<Type>, fixed_args: Option<usize>, conv: CallConv }, // fixed_args: param count of a C variadic callee, conv: a foreign callee's convention
     Ret { value: Option<Operand> },
     Br { condition: Operand, then_bb: usize, else_bb: usize },
     Jump { target: usize },
+    Switch { value: Operand, arms: Vec<(i64, usize)>, default: usize }, // multi way branch on an int, case values r unique
+
+    // strings, (ptr, len) values
+    StrLen { dest: Local, source: Operand },
+    StrConcat { dest: Local, left: Operand, right: Operand }, // fresh heap buffer
+    StrEq { dest: Local, left: Operand, right: Operand }, // same len + same bytes
+
+    // trait objects, (data ptr, vtable ptr) values
+    MakeDyn { dest: Local, data: Operand, vtable: Vtable },
+    DynCall { dest: Option<Local>, object: Operand, slot: usize, args: Vec<Operand>, return_type: Option<Type> }, // data ptr is passed as self
+
+    // enums, `{ i32 tag, [n x i64] payload }` values of type_
+    MakeVariant { dest: Local, type_: Type, tag: usize, fields: Vec<Operand> },
+    EnumTag { dest: Local, source: Operand },
+    VariantField { dest: Local, source: Operand, type_: Type, tag: usize, index: usize }, // field `index` of variant `tag`
 
     // other
     Phi { dest: Local, type_: Type, incoming: Vec<(Operand, usize)> },
     Copy { dest: Local, source: Operand, type_: Type },
 }
+
+impl Instruction {
+    /// the local this instruction writes, if any
+    pub fn dest(&self) -> Option<Local> {
+        match self {
+            Instruction::Add { dest, .. }
+            | Instruction::Sub { dest, .. }
+            | Instruction::Mul { dest, .. }
+            | Instruction::Div { dest, .. }
+            | Instruction::Mod { dest, .. }
+            | Instruction::Eq { dest, .. }
+            | Instruction::Ne { dest, .. }
+            | Instruction::Lt { dest, .. }
+            | Instruction::Le { dest, .. }
+            | Instruction::Gt { dest, .. }
+            | Instruction::Ge { dest, .. }
+            | Instruction::And { dest, .. }
+            | Instruction::Or { dest, .. }
+            | Instruction::Not { dest, .. }
+            | Instruction::StrLen { dest, .. }
+            | Instruction::MakeDyn { dest, .. }
+            | Instruction::StrConcat { dest, .. }
+            | Instruction::StrEq { dest, .. }
+            | Instruction::MakeVariant { dest, .. }
+            | Instruction::EnumTag { dest, .. }
+            | Instruction::VariantField { dest, .. }
+            | Instruction::Load { dest, .. }
+            | Instruction::VolatileLoad { dest, .. }
+            | Instruction::Alloca { dest, .. }
+            | Instruction::Gep { dest, .. }
+            | Instruction::PtrOffset { dest, .. }
+            | Instruction::Phi { dest, .. }
+            | Instruction::Copy { dest, .. } => Some(*dest),
+            Instruction::Call { dest, .. } | Instruction::DynCall { dest, .. } => *dest,
+            _ => None,
+        }
+    }
+
+    /// `dest` 4 rewriting it in place
+    pub fn dest_mut(&mut self) -> Option<&mut Local> {
+        match self {
+            Instruction::Add { dest, .. }
+            | Instruction::Sub { dest, .. }
+            | Instruction::Mul { dest, .. }
+            | Instruction::Div { dest, .. }
+            | Instruction::Mod { dest, .. }
+            | Instruction::Eq { dest, .. }
+            | Instruction::Ne { dest, .. }
+            | Instruction::Lt { dest, .. }
+            | Instruction::Le { dest, .. }
+            | Instruction::Gt { dest, .. }
+            | Instruction::Ge { dest, .. }
+            | Instruction::And { dest, .. }
+            | Instruction::Or { dest, .. }
+            | Instruction::Not { dest, .. }
+            | Instruction::StrLen { dest, .. }
+            | Instruction::MakeDyn { dest, .. }
+            | Instruction::StrConcat { dest, .. }
+            | Instruction::StrEq { dest, .. }
+            | Instruction::MakeVariant { dest, .. }
+            | Instruction::EnumTag { dest, .. }
+            | Instruction::VariantField { dest, .. }
+            | Instruction::Load { dest, .. }
+            | Instruction::VolatileLoad { dest, .. }
+            | Instruction::Alloca { dest, .. }
+            | Instruction::Gep { dest, .. }
+            | Instruction::PtrOffset { dest, .. }
+            | Instruction::Phi { dest, .. }
+            | Instruction::Copy { dest, .. } => Some(dest),
+            Instruction::Call { dest, .. } | Instruction::DynCall { dest, .. } => dest.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// evry operand the instruction reads, a store's address included
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Instruction::Add { left, right, .. }
+            | Instruction::Sub { left, right, .. }
+            | Instruction::Mul { left, right, .. }
+            | Instruction::Div { left, right, .. }
+            | Instruction::Mod { left, right, .. }
+            | Instruction::Eq { left, right, .. }
+            | Instruction::Ne { left, right, .. }
+            | Instruction::Lt { left, right, .. }
+            | Instruction::Le { left, right, .. }
+            | Instruction::Gt { left, right, .. }
+            | Instruction::Ge { left, right, .. }
+            | Instruction::And { left, right, .. }
+            | Instruction::Or { left, right, .. }
+            | Instruction::StrConcat { left, right, .. }
+            | Instruction::StrEq { left, right, .. } => vec![left, right],
+            Instruction::Not { operand, .. }
+            | Instruction::StrLen { source: operand, .. }
+            | Instruction::MakeDyn { data: operand, .. }
+            | Instruction::EnumTag { source: operand, .. }
+            | Instruction::VariantField { source: operand, .. }
+            | Instruction::Load { source: operand, .. }
+            | Instruction::VolatileLoad { source: operand, .. }
+            | Instruction::Copy { source: operand, .. }
+            | Instruction::Br { condition: operand, .. }
+            | Instruction::Switch { value: operand, .. } => vec![operand],
+            Instruction::Store { dest, source, .. } | Instruction::VolatileStore { dest, source, .. } => vec![dest, source],
+            Instruction::Gep { base, index, .. } | Instruction::PtrOffset { base, offset: index, .. } => vec![base, index],
+            Instruction::Memset { dest, value, .. } => vec![dest, value],
+            Instruction::Call { func, args, .. } | Instruction::DynCall { object: func, args, .. } => {
+                std::iter::once(func).chain(args).collect()
+            }
+            Instruction::MakeVariant { fields, .. } => fields.iter().collect(),
+            Instruction::Ret { value } => value.iter().collect(),
+            Instruction::Phi { incoming, .. } => incoming.iter().map(|(op, _)| op).collect(),
+            Instruction::Alloca { .. } | Instruction::Jump { .. } => Vec::new(),
+        }
+    }
+
+    /// `operands` 4 rewriting them in place
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Instruction::Add { left, right, .. }
+            | Instruction::Sub { left, right, .. }
+            | Instruction::Mul { left, right, .. }
+            | Instruction::Div { left, right, .. }
+            | Instruction::Mod { left, right, .. }
+            | Instruction::Eq { left, right, .. }
+            | Instruction::Ne { left, right, .. }
+            | Instruction::Lt { left, right, .. }
+            | Instruction::Le { left, right, .. }
+            | Instruction::Gt { left, right, .. }
+            | Instruction::Ge { left, right, .. }
+            | Instruction::And { left, right, .. }
+            | Instruction::Or { left, right, .. }
+            | Instruction::StrConcat { left, right, .. }
+            | Instruction::StrEq { left, right, .. } => vec![left, right],
+            Instruction::Not { operand, .. }
+            | Instruction::StrLen { source: operand, .. }
+            | Instruction::MakeDyn { data: operand, .. }
+            | Instruction::EnumTag { source: operand, .. }
+            | Instruction::VariantField { source: operand, .. }
+            | Instruction::Load { source: operand, .. }
+            | Instruction::VolatileLoad { source: operand, .. }
+            | Instruction::Copy { source: operand, .. }
+            | Instruction::Br { condition: operand, .. }
+            | Instruction::Switch { value: operand, .. } => vec![operand],
+            Instruction::Store { dest, source, .. } | Instruction::VolatileStore { dest, source, .. } => vec![dest, source],
+            Instruction::Gep { base, index, .. } | Instruction::PtrOffset { base, offset: index, .. } => vec![base, index],
+            Instruction::Memset { dest, value, .. } => vec![dest, value],
+            Instruction::Call { func, args, .. } | Instruction::DynCall { object: func, args, .. } => {
+                std::iter::once(func).chain(args).collect()
+            }
+            Instruction::MakeVariant { fields, .. } => fields.iter_mut().collect(),
+            Instruction::Ret { value } => value.iter_mut().collect(),
+            Instruction::Phi { incoming, .. } => incoming.iter_mut().map(|(op, _)| op).collect(),
+            Instruction::Alloca { .. } | Instruction::Jump { .. } => Vec::new(),
+        }
+    }
+
+    pub fn is_terminator(&self) -> bool {
+        matches!(self, Instruction::Ret { .. } | Instruction::Jump { .. } | Instruction::Br { .. } | Instruction::Switch { .. })
+    }
+
+    /// evry block id the instruction names, a terminator's targets + a phi's predecessors, 4
+    /// renumbering blocks
+    pub fn blocks_mut(&mut self) -> Vec<&mut usize> {
+        match self {
+            Instruction::Jump { target } => vec![target],
+            Instruction::Br { then_bb, else_bb, .. } => vec![then_bb, else_bb],
+            Instruction::Switch { arms, default, .. } => arms.iter_mut().map(|(_, bb)| bb).chain(std::iter::once(default)).collect(),
+            Instruction::Phi { incoming, .. } => incoming.iter_mut().map(|(_, bb)| bb).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// blocks a terminator can go 2, in the order it names them
+    pub fn successors(&self) -> Vec<usize> {
+        match self {
+            Instruction::Jump { target } => vec![*target],
+            Instruction::Br { then_bb, else_bb, .. } => vec![*then_bb, *else_bb],
+            Instruction::Switch { arms, default, .. } => arms.iter().map(|(_, bb)| *bb).chain(std::iter::once(*default)).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// fn ptrs 4 one (type, trait) pair, `methods` r the impl fns in trait declaration order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vtable {
+    pub trait_name: String,
+    pub type_name: String,
+    pub methods: Vec<String>,
+}
+
+impl Vtable {
+    /// global the backend emits it as, shared by evry trait object of this pair
+    pub fn symbol(&self) -> String {
+        format!("vtable.{}.{}", self.type_name, self.trait_name)
+    }
+}
@@ -1,9 +1,51 @@
+pub mod alias;
+pub mod analysis;
 pub mod basic_block;
+pub mod display;
+pub mod dump;
+pub mod dot;
 pub mod function;
 pub mod instruction;
+pub mod module;
 pub mod operand;
+pub mod passes;
+pub mod profile;
+pub mod verify;
+pub mod wire;
 
 pub use basic_block::*;
 pub use function::*;
 pub use instruction::*;
+pub use module::*;
 pub use operand::*;
+
+/// the runtime fn a failed chk or `panic(msg)` calls w/ (message, file, line), it reports them on
+/// stderr + aborts
+pub const PANIC_FN: &str = "emerald_panic";
+
+/// the runtime allocator `new value` calls w/ the size in bytes + `delete p` hands the memory
+/// back 2. the backends give them weak bodies on malloc + free, a program or an object linked
+/// in w/ its own definitions replaces them
+pub const ALLOC_FN: &str = "emerald_alloc";
+pub const FREE_FN: &str = "emerald_free";
+
+/// sits between a generic fn's name + each thing its specialized 4, eg `max$int` or `scale$ct0`.
+/// no identifier can hold it, so a specialization never clashes w/ a fn the program defines
+pub const SPECIALIZATION_MARKER: char = '$';
+
+/// `name` specialized 4 `parts`
+pub fn specialized_name(name: &str, parts: &[String]) -> String {
+    parts.iter().fold(name.to_string(), |name, part| format!("{}{}{}", name, SPECIALIZATION_MARKER, part))
+}
+
+/// fns that return fresh heap memory the size of their 1st arg in bytes, each w/ the fn that
+/// frees it. what escape analysis + stack promotion know as allocations
+pub const HEAP_ALLOCATORS: [(&str, &str); 2] = [("malloc", "free"), (ALLOC_FN, FREE_FN)];
+
+pub fn is_heap_alloc(name: &str) -> bool {
+    HEAP_ALLOCATORS.iter().any(|(alloc, _)| *alloc == name)
+}
+
+pub fn is_heap_free(name: &str) -> bool {
+    HEAP_ALLOCATORS.iter().any(|(_, free)| *free == name)
+}
@@ -0,0 +1,35 @@
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::Operand;
+
+/// the MIR of one imported module, compiled 2 its own object + linked w/ the program
+/// its fns r named `name::fn` so they cant clash w/ the importer's
+#[derive(Debug, Clone)]
+pub struct MirModule {
+    pub name: String,
+    pub functions: Vec<MirFunction>,
+}
+
+impl MirModule {
+    /// qualifies the fns + evry reference 2 them inside the module
+    pub fn new(name: String, mut functions: Vec<MirFunction>) -> Self {
+        let defined: Vec<String> = functions.iter().map(|f| f.name.clone()).collect();
+        let qualify = |n: &mut String| {
+            if defined.contains(n) {
+                *n = format!("{}::{}", name, n);
+            }
+        };
+        for func in &mut functions {
+            qualify(&mut func.name);
+            for inst in func.basic_blocks.iter_mut().flat_map(|bb| &mut bb.instructions) {
+                match inst {
+                    Instruction::Call { func: Operand::Function(f), .. }
+                    | Instruction::Copy { source: Operand::Function(f), .. } => qualify(&mut f.name),
+                    Instruction::MakeDyn { vtable, .. } => vtable.methods.iter_mut().for_each(&qualify),
+                    _ => {}
+                }
+            }
+        }
+        Self { name, functions }
+    }
+}
@@ -34,6 +34,10 @@ impl std::hash::Hash for Constant {
             Constant::Null => {
                 state.write_u8(5);
             }
+            Constant::Aggregate(elements) => {
+                state.write_u8(6);
+                elements.hash(state);
+            }
         }
     }
 }
@@ -46,6 +50,7 @@ pub enum Constant {
     Char(char),
     String(String),
     Null,
+    Aggregate(Vec<Constant>), // array elements or struct fields in declaration order
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
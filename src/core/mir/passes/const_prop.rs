@@ -0,0 +1,210 @@
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Constant, Operand};
+use crate::core::mir::passes::MirPass;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use std::collections::HashMap;
+
+/// folds arithmetic, comparisons + logic on constants in2 a `copy` of the result, + replaces
+/// uses of a local w/ its value when the local is only ever assigned that constant. repeats
+/// til nothing changes so `a = 2; b = a * 3; c = b + 1` ends as `c = 7`. the copies it leaves
+/// behind r dead code 4 a later pass
+pub struct ConstProp;
+
+impl MirPass for ConstProp {
+    fn name(&self) -> &'static str {
+        "const-prop"
+    }
+
+    fn run(&mut self, func: &mut MirFunction) -> bool {
+        let mut changed = false;
+        loop {
+            let mut round = false;
+            for inst in func.basic_blocks.iter_mut().flat_map(|bb| &mut bb.instructions) {
+                round |= fold(inst);
+            }
+            let known = known_constants(func);
+            for inst in func.basic_blocks.iter_mut().flat_map(|bb| &mut bb.instructions) {
+                for operand in inst.operands_mut() {
+                    if let Operand::Local(l) = operand {
+                        if let Some(c) = known.get(&l.id) {
+                            *operand = Operand::Constant(c.clone());
+                            round = true;
+                        }
+                    }
+                }
+            }
+            if !round {
+                return changed;
+            }
+            changed = true;
+        }
+    }
+}
+
+/// locals w/ a single assignment, a copy of a scalar constant of the local's own type. MIR isnt
+/// SSA, a local assigned twice (a loop counter, a reassigned var) or a param is never constant
+fn known_constants(func: &MirFunction) -> HashMap<usize, Constant> {
+    let mut assignments: HashMap<usize, usize> = HashMap::new();
+    for inst in func.basic_blocks.iter().flat_map(|bb| &bb.instructions) {
+        if let Some(dest) = inst.dest() {
+            *assignments.entry(dest.id).or_default() += 1;
+        }
+    }
+    let types: HashMap<usize, &Type> = func.locals.iter().map(|l| (l.local.id, &l.type_)).collect();
+    let mut known = HashMap::new();
+    for inst in func.basic_blocks.iter().flat_map(|bb| &bb.instructions) {
+        let Instruction::Copy { dest, source: Operand::Constant(c), .. } = inst else { continue };
+        let is_param = func.params.iter().any(|p| p.local.id == dest.id);
+        let fits = types.get(&dest.id).is_some_and(|ty| fits(c, ty));
+        if assignments[&dest.id] == 1 && !is_param && fits {
+            known.insert(dest.id, c.clone());
+        }
+    }
+    known
+}
+
+/// whether `c` can stand in 4 a local of type `ty`. strings + aggregates r addressed thru
+/// the local so they stay where they r
+fn fits(c: &Constant, ty: &Type) -> bool {
+    match (c, ty) {
+        (Constant::Int(_), Type::Primitive(p)) => p.is_integer(),
+        (Constant::Float(_), Type::Primitive(PrimitiveType::Float)) => true,
+        (Constant::Bool(_), Type::Primitive(PrimitiveType::Bool)) => true,
+        (Constant::Char(_), Type::Primitive(PrimitiveType::Char)) => true,
+        (Constant::Null, Type::Pointer(_)) => true,
+        _ => false,
+    }
+}
+
+/// `value` wrapped 2 the width of `p`, unsigned types keep their bits in the i64
+fn wrap(value: i128, p: PrimitiveType) -> i64 {
+    let bits = p.size_in_bytes() as u32 * 8;
+    if bits >= 64 {
+        return value as i64;
+    }
+    let truncated = value & ((1i128 << bits) - 1);
+    if p.is_signed() && truncated >> (bits - 1) == 1 {
+        (truncated - (1i128 << bits)) as i64
+    } else {
+        truncated as i64
+    }
+}
+
+/// an int operand as the value it stands 4, a ulong above i64::MAX is stored negative
+fn widen(value: i64, p: PrimitiveType) -> i128 {
+    if p.is_signed() {
+        value as i128
+    } else {
+        value as u64 as i128
+    }
+}
+
+fn arithmetic(inst: &Instruction) -> Option<Constant> {
+    let (left, right, type_) = match inst {
+        Instruction::Add { left, right, type_, .. }
+        | Instruction::Sub { left, right, type_, .. }
+        | Instruction::Mul { left, right, type_, .. }
+        | Instruction::Div { left, right, type_, .. }
+        | Instruction::Mod { left, right, type_, .. } => (left, right, type_),
+        _ => return None,
+    };
+    match (left, right, type_) {
+        (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r)), Type::Primitive(p)) if p.is_integer() => {
+            let (l, r) = (widen(*l, *p), widen(*r, *p));
+            let value = match inst {
+                Instruction::Add { .. } => l.wrapping_add(r),
+                Instruction::Sub { .. } => l.wrapping_sub(r),
+                Instruction::Mul { .. } => l.wrapping_mul(r),
+                // division by zero is left 2 trap at runtime
+                Instruction::Div { .. } if r != 0 => l / r,
+                Instruction::Mod { .. } if r != 0 => l % r,
+                _ => return None,
+            };
+            Some(Constant::Int(wrap(value, *p)))
+        }
+        (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r)), _) => Some(Constant::Float(match inst {
+            Instruction::Add { .. } => l + r,
+            Instruction::Sub { .. } => l - r,
+            Instruction::Mul { .. } => l * r,
+            Instruction::Div { .. } => l / r,
+            _ => l % r,
+        })),
+        _ => None,
+    }
+}
+
+fn comparison(inst: &Instruction) -> Option<Constant> {
+    use std::cmp::Ordering;
+    let (left, right) = match inst {
+        Instruction::Eq { left, right, .. }
+        | Instruction::Ne { left, right, .. }
+        | Instruction::Lt { left, right, .. }
+        | Instruction::Le { left, right, .. }
+        | Instruction::Gt { left, right, .. }
+        | Instruction::Ge { left, right, .. } => (left, right),
+        _ => return None,
+    };
+    let (Operand::Constant(l), Operand::Constant(r)) = (left, right) else { return None };
+    let order = match (l, r) {
+        // the operands' type isnt kept, signed + unsigned only order the same when the signs do
+        (Constant::Int(a), Constant::Int(b)) if (*a < 0) != (*b < 0) => {
+            return match inst {
+                Instruction::Eq { .. } => Some(Constant::Bool(false)),
+                Instruction::Ne { .. } => Some(Constant::Bool(true)),
+                _ => None,
+            };
+        }
+        (Constant::Int(a), Constant::Int(b)) => a.partial_cmp(b),
+        (Constant::Float(a), Constant::Float(b)) => a.partial_cmp(b),
+        (Constant::Char(a), Constant::Char(b)) => a.partial_cmp(b),
+        (Constant::Bool(a), Constant::Bool(b)) => a.partial_cmp(b),
+        (Constant::Null, Constant::Null) => Some(Ordering::Equal),
+        _ => return None,
+    };
+    // NaN is unordered, only `ne` is true
+    let result = match (inst, order) {
+        (Instruction::Eq { .. }, order) => order == Some(Ordering::Equal),
+        (Instruction::Ne { .. }, order) => order != Some(Ordering::Equal),
+        (_, None) => false,
+        (Instruction::Lt { .. }, Some(o)) => o == Ordering::Less,
+        (Instruction::Le { .. }, Some(o)) => o != Ordering::Greater,
+        (Instruction::Gt { .. }, Some(o)) => o == Ordering::Greater,
+        (_, Some(o)) => o != Ordering::Less,
+    };
+    Some(Constant::Bool(result))
+}
+
+fn logic(inst: &Instruction) -> Option<Constant> {
+    match inst {
+        Instruction::And { left: Operand::Constant(Constant::Bool(l)), right: Operand::Constant(Constant::Bool(r)), .. } => {
+            Some(Constant::Bool(*l && *r))
+        }
+        Instruction::Or { left: Operand::Constant(Constant::Bool(l)), right: Operand::Constant(Constant::Bool(r)), .. } => {
+            Some(Constant::Bool(*l || *r))
+        }
+        Instruction::Not { operand: Operand::Constant(Constant::Bool(b)), .. } => Some(Constant::Bool(!b)),
+        _ => None,
+    }
+}
+
+/// `inst` as a copy of its result when its operands r all constant
+fn fold(inst: &mut Instruction) -> bool {
+    let Some(dest) = inst.dest() else { return false };
+    let (value, type_) = match &*inst {
+        Instruction::Add { type_, .. }
+        | Instruction::Sub { type_, .. }
+        | Instruction::Mul { type_, .. }
+        | Instruction::Div { type_, .. }
+        | Instruction::Mod { type_, .. } => (arithmetic(inst), type_.clone()),
+        _ => (comparison(inst).or_else(|| logic(inst)), Type::Primitive(PrimitiveType::Bool)),
+    };
+    match value {
+        Some(value) => {
+            *inst = Instruction::Copy { dest, source: Operand::Constant(value), type_ };
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,146 @@
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Constant, Local, Operand};
+use crate::core::mir::passes::MirPass;
+use std::collections::{HashMap, HashSet};
+
+/// removes the blocks the entry cant reach + the instructions whose result nothing reads and
+/// that do nothing else, repeating til a whole chain of dead values is gone. stores in2 an
+/// `alloca` nobody reads r dead along w/ the alloca
+#[derive(Default)]
+pub struct Dce {
+    instructions: usize,
+    blocks: usize,
+}
+
+impl MirPass for Dce {
+    fn name(&self) -> &'static str {
+        "dce"
+    }
+
+    fn run(&mut self, func: &mut MirFunction) -> bool {
+        let blocks = remove_unreachable_blocks(func);
+        let mut instructions = 0;
+        loop {
+            match remove_dead_instructions(func) {
+                0 => break,
+                n => instructions += n,
+            }
+        }
+        self.blocks += blocks;
+        self.instructions += instructions;
+        blocks + instructions > 0
+    }
+
+    fn counters(&self) -> Vec<(&'static str, usize)> {
+        vec![("instructions removed", self.instructions), ("blocks removed", self.blocks)]
+    }
+}
+
+/// drops blocks w/o a path frm the entry + renumbers the rest in order, returns how many went
+fn remove_unreachable_blocks(func: &mut MirFunction) -> usize {
+    let mut reachable = vec![false; func.basic_blocks.len()];
+    let mut worklist = vec![func.entry_block];
+    while let Some(id) = worklist.pop() {
+        if reachable.get(id) != Some(&false) {
+            continue;
+        }
+        reachable[id] = true;
+        let bb = &func.basic_blocks[id];
+        // the recorded successors as well as the terminator's, whichever is more
+        worklist.extend(bb.successors.iter().copied());
+        worklist.extend(bb.instructions.last().map(Instruction::successors).unwrap_or_default());
+    }
+    let removed = reachable.iter().filter(|r| !**r).count();
+    if removed == 0 {
+        return 0;
+    }
+
+    let mut renumbered = Vec::with_capacity(reachable.len());
+    let mut next = 0;
+    for r in &reachable {
+        renumbered.push(r.then(|| {
+            next += 1;
+            next - 1
+        }));
+    }
+    let new_id = |old: &usize| renumbered.get(*old).copied().flatten();
+    let blocks = std::mem::take(&mut func.basic_blocks);
+    for mut bb in blocks.into_iter().filter(|bb| reachable[bb.id]) {
+        bb.id = new_id(&bb.id).unwrap_or(bb.id);
+        bb.successors = bb.successors.iter().filter_map(new_id).collect();
+        bb.predecessors = bb.predecessors.iter().filter_map(new_id).collect();
+        for inst in &mut bb.instructions {
+            // a phi forgets the values that came frm removed blocks
+            if let Instruction::Phi { incoming, .. } = inst {
+                incoming.retain(|(_, pred)| new_id(pred).is_some());
+            }
+            for block in inst.blocks_mut() {
+                *block = new_id(block).unwrap_or(*block);
+            }
+        }
+        func.basic_blocks.push(bb);
+    }
+    func.entry_block = new_id(&func.entry_block).unwrap_or(0);
+    removed
+}
+
+/// 1 round of removing dead instructions, returns how many went
+fn remove_dead_instructions(func: &mut MirFunction) -> usize {
+    let instructions = || func.basic_blocks.iter().flat_map(|bb| &bb.instructions);
+    let allocas: HashSet<usize> = instructions()
+        .filter_map(|inst| match inst {
+            Instruction::Alloca { dest, .. } => Some(dest.id),
+            _ => None,
+        })
+        .collect();
+    // reads of each local, writing thru an alloca's address isnt 1
+    let mut reads: HashMap<usize, usize> = HashMap::new();
+    for inst in instructions() {
+        for operand in inst.operands() {
+            let Operand::Local(l) = operand else { continue };
+            if written_alloca(inst, &allocas) != Some(*l) {
+                *reads.entry(l.id).or_default() += 1;
+            }
+        }
+    }
+
+    let mut removed = 0;
+    for bb in &mut func.basic_blocks {
+        let before = bb.instructions.len();
+        bb.instructions.retain(|inst| !is_dead(inst, &reads, &allocas));
+        removed += before - bb.instructions.len();
+    }
+    removed
+}
+
+/// the alloca a store or memset writes in2
+fn written_alloca(inst: &Instruction, allocas: &HashSet<usize>) -> Option<Local> {
+    match inst {
+        Instruction::Store { dest: Operand::Local(l), .. } | Instruction::Memset { dest: Operand::Local(l), .. } => {
+            allocas.contains(&l.id).then_some(*l)
+        }
+        _ => None,
+    }
+}
+
+fn is_dead(inst: &Instruction, reads: &HashMap<usize, usize>, allocas: &HashSet<usize>) -> bool {
+    let unread = |l: Local| !reads.contains_key(&l.id);
+    if let Some(slot) = written_alloca(inst, allocas) {
+        return unread(slot);
+    }
+    match inst {
+        // effects beyond the result
+        Instruction::Call { .. }
+        | Instruction::DynCall { .. }
+        | Instruction::VolatileLoad { .. }
+        | Instruction::VolatileStore { .. }
+        | Instruction::Store { .. }
+        | Instruction::Memset { .. } => false,
+        // an int division by 0 or by an unknown divisor can trap, that has 2 stay
+        Instruction::Div { dest, right, .. } | Instruction::Mod { dest, right, .. } => {
+            matches!(right, Operand::Constant(c) if *c != Constant::Int(0)) && unread(*dest)
+        }
+        _ => inst.dest().is_some_and(unread),
+    }
+}
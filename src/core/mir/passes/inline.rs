@@ -0,0 +1,214 @@
+use crate::core::mir::analysis::CallGraph;
+use crate::core::mir::basic_block::BasicBlock;
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Local, Operand};
+use crate::core::mir::passes::PassStats;
+use crate::core::mir::profile::{CallProfile, Hotness};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// instructions a callee can have and still be inlined when nothing else is asked 4
+pub const DEFAULT_INLINE_THRESHOLD: usize = 20;
+
+/// how many times the threshold a callee at a hot call site may be
+const HOT_THRESHOLD_FACTOR: usize = 4;
+
+/// the most instructions a callee at a cold call site may have, about what the call costs anyway
+const COLD_THRESHOLD: usize = 3;
+
+/// replaces direct calls 2 small fns w/ a copy of the callee's blocks, so backends w/o an inliner
+/// of their own (the interpreter, cranelift, debug builds) dont pay a call 4 evry getter. works
+/// on the whole program not 1 fn, so it isnt a `MirPass`. callees r done b4 their callers, a
+/// small fn calling another small fn is inlined w/ that call already gone. w/ a profile the
+/// threshold is raised at hot call sites + lowered at cold 1s
+pub struct Inliner {
+    /// the most instructions a callee may have, 0 turns inlining off
+    threshold: usize,
+    profile: Option<Arc<CallProfile>>,
+    functions: usize,
+    changed: usize,
+    inlined: usize,
+    hot: usize,
+}
+
+impl Inliner {
+    pub fn new(threshold: usize) -> Self {
+        Self { threshold, profile: None, functions: 0, changed: 0, inlined: 0, hot: 0 }
+    }
+
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_profile(mut self, profile: Arc<CallProfile>) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// inlines evry call it can in `functions`, true if any was
+    pub fn run(&mut self, functions: &mut [MirFunction]) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+        let graph = CallGraph::new(functions);
+        let mut any = false;
+        for caller in graph.bottom_up() {
+            self.functions += 1;
+            let mut count = 0;
+            // an inlined body can bring calls of its own, they go 2 fns further down the call graph
+            // so this ends
+            while let Some((block, at, callee)) = self.next_site(&functions[caller], &graph, functions) {
+                let callee = functions[callee].clone();
+                if self.hotness(&functions[caller], &callee) == Hotness::Hot {
+                    self.hot += 1;
+                }
+                inline_call(&mut functions[caller], block, at, &callee);
+                count += 1;
+            }
+            if count > 0 {
+                self.changed += 1;
+                self.inlined += count;
+                any = true;
+            }
+        }
+        any
+    }
+
+    /// the first call in `caller` worth inlining, as (block, instruction, callee)
+    fn next_site(
+        &self,
+        caller: &MirFunction,
+        graph: &CallGraph,
+        functions: &[MirFunction],
+    ) -> Option<(usize, usize, usize)> {
+        for (b, bb) in caller.basic_blocks.iter().enumerate() {
+            for (i, inst) in bb.instructions.iter().enumerate() {
+                let Instruction::Call { func: Operand::Function(f), args, fixed_args: None, .. } = inst else { continue };
+                let Some(callee) = graph.index_of(&f.name) else { continue };
+                let target = &functions[callee];
+                if target.name != caller.name
+                    && !graph.is_recursive(callee)
+                    && args.len() == target.params.len()
+                    && self.inlinable(target, self.limit(caller, target))
+                {
+                    return Some((b, i, callee));
+                }
+            }
+        }
+        None
+    }
+
+    fn hotness(&self, caller: &MirFunction, callee: &MirFunction) -> Hotness {
+        self.profile.as_ref().map_or(Hotness::Unknown, |p| p.hotness(&caller.name, &callee.name))
+    }
+
+    /// the most instructions `callee` may have 2 be inlined in2 `caller`. a site the profile
+    /// doesnt know goes by size alone. an inlined body keeps the name of the fn it was inlined
+    /// in2, so its calls r looked up as that fn's
+    fn limit(&self, caller: &MirFunction, callee: &MirFunction) -> usize {
+        match self.hotness(caller, callee) {
+            Hotness::Hot => self.threshold.saturating_mul(HOT_THRESHOLD_FACTOR),
+            Hotness::Cold => self.threshold.min(COLD_THRESHOLD),
+            Hotness::Unknown => self.threshold,
+        }
+    }
+
+    /// small enough + nothing in it that would change meaning once it's part of another fn
+    fn inlinable(&self, callee: &MirFunction, limit: usize) -> bool {
+        let instructions = callee.basic_blocks.iter().flat_map(|bb| &bb.instructions);
+        let size = instructions.clone().count();
+        // an alloca inlined in2 a loop would grow the stack evry iteration, multiversioned fns
+        // pick their clone at the call
+        size > 0
+            && size <= limit
+            && callee.target_features.is_empty()
+            && callee.basic_blocks.iter().all(BasicBlock::has_terminator)
+            && !instructions.clone().any(|i| matches!(i, Instruction::Alloca { .. }))
+    }
+
+    pub fn stats(&self) -> PassStats {
+        let mut counters = vec![("calls inlined", self.inlined)];
+        if self.profile.is_some() {
+            counters.push(("hot calls inlined", self.hot));
+        }
+        PassStats { name: "inline", runs: self.functions, changed: self.changed, counters }
+    }
+}
+
+/// replaces the call at `caller.basic_blocks[block].instructions[at]` w/ `callee`'s body. the
+/// block is split after the call, args r copied in2 the callee's params + each `ret` copies its
+/// value in2 the call's dest + jumps 2 the 2nd half
+fn inline_call(caller: &mut MirFunction, block: usize, at: usize, callee: &MirFunction) {
+    let Instruction::Call { dest, args, .. } = caller.basic_blocks[block].instructions[at].clone() else { return };
+
+    let mut locals: HashMap<usize, Local> =
+        callee.locals.iter().map(|l| (l.local.id, caller.new_local(l.type_.clone(), l.name.clone()))).collect();
+    for param in &callee.params {
+        locals.entry(param.local.id).or_insert_with(|| caller.new_local(param.type_.clone(), Some(param.name.clone())));
+    }
+    let first = caller.basic_blocks.len();
+    let blocks: HashMap<usize, usize> = callee.basic_blocks.iter().enumerate().map(|(i, bb)| (bb.id, first + i)).collect();
+    let rest = first + callee.basic_blocks.len();
+
+    // the call's block keeps what came b4 it, the copies in2 the params + a jump in2 the callee
+    let mut after = caller.basic_blocks[block].instructions.split_off(at);
+    after.remove(0);
+    let head = &mut caller.basic_blocks[block].instructions;
+    for (param, arg) in callee.params.iter().zip(args) {
+        head.push(Instruction::Copy { dest: locals[&param.local.id], source: arg, type_: param.type_.clone() });
+    }
+    head.push(Instruction::Jump { target: blocks[&callee.entry_block] });
+
+    for bb in &callee.basic_blocks {
+        let mut copy = BasicBlock::new(blocks[&bb.id]);
+        for inst in &bb.instructions {
+            match inst {
+                Instruction::Ret { value } => {
+                    if let (Some(dest), Some(value), Some(type_)) = (dest, value, &callee.return_type) {
+                        let source = rename(value.clone(), &locals);
+                        copy.instructions.push(Instruction::Copy { dest, source, type_: type_.clone() });
+                    }
+                    copy.instructions.push(Instruction::Jump { target: rest });
+                }
+                _ => {
+                    let mut inst = inst.clone();
+                    if let Some(d) = inst.dest_mut() {
+                        *d = locals.get(&d.id).copied().unwrap_or(*d);
+                    }
+                    for operand in inst.operands_mut() {
+                        *operand = rename(operand.clone(), &locals);
+                    }
+                    for target in inst.blocks_mut() {
+                        *target = blocks[&*target];
+                    }
+                    copy.instructions.push(inst);
+                }
+            }
+        }
+        caller.basic_blocks.push(copy);
+    }
+
+    // phis after the call now come frm the 2nd half
+    let mut tail = BasicBlock::new(rest);
+    tail.instructions = after;
+    for succ in tail.instructions.last().map(Instruction::successors).unwrap_or_default() {
+        for inst in &mut caller.basic_blocks[succ].instructions {
+            if let Instruction::Phi { incoming, .. } = inst {
+                for (_, pred) in incoming.iter_mut().filter(|(_, pred)| *pred == block) {
+                    *pred = rest;
+                }
+            }
+        }
+    }
+    caller.basic_blocks.push(tail);
+    caller.relink();
+}
+
+fn rename(operand: Operand, locals: &HashMap<usize, Local>) -> Operand {
+    match operand {
+        Operand::Local(l) => Operand::Local(locals.get(&l.id).copied().unwrap_or(l)),
+        other => other,
+    }
+}
@@ -0,0 +1,167 @@
+use crate::core::mir::alias::{AliasAnalysis, AliasResult, Base};
+use crate::core::mir::analysis::Cfg;
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Local, Operand};
+use crate::core::mir::passes::MirPass;
+use crate::core::types::ty::Type;
+use std::collections::HashSet;
+
+/// removes loads of a value the fn just stored or loaded, + stores nothing reads afterwards: ones
+/// overwritten b4 a read, + ones in2 the fn's own slots after their last read. a block w/ 1
+/// predecessor carries on frm what that predecessor knew. the interpreter + the C backend get no
+/// llvm 2 do this 4 them
+#[derive(Default)]
+pub struct LoadStore {
+    loads: usize,
+    stores: usize,
+}
+
+impl MirPass for LoadStore {
+    fn name(&self) -> &'static str {
+        "load-store"
+    }
+
+    fn run(&mut self, func: &mut MirFunction) -> bool {
+        let loads = forward_loads(func);
+        let stores = remove_dead_stores(func);
+        self.loads += loads;
+        self.stores += stores;
+        loads + stores > 0
+    }
+
+    fn counters(&self) -> Vec<(&'static str, usize)> {
+        vec![("loads removed", self.loads), ("stores removed", self.stores)]
+    }
+}
+
+/// memory whose contents r known at a point, `value` is what loading `pointer` as `type_` gives
+#[derive(Clone)]
+struct Known {
+    pointer: Operand,
+    value: Operand,
+    type_: Type,
+}
+
+/// turns loads of known memory in2 copies, returns how many
+fn forward_loads(func: &mut MirFunction) -> usize {
+    let aa = AliasAnalysis::new(func);
+    let cfg = Cfg::new(func);
+    let mut known_at_end: Vec<Option<Vec<Known>>> = vec![None; func.basic_blocks.len()];
+    let mut forwarded = 0;
+    for b in 0..func.basic_blocks.len() {
+        // only a block's sole predecessor that came earlier has run, anything else starts empty
+        let mut known = match cfg.predecessors(b) {
+            [p] if b != func.entry_block => known_at_end[*p].clone().unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        for inst in &mut func.basic_blocks[b].instructions {
+            if let Instruction::Load { dest, source, type_ } = inst {
+                let found = known.iter().rev().find(|k| k.type_ == *type_ && aa.alias(&k.pointer, source) == AliasResult::Must);
+                if let Some(k) = found {
+                    *inst = Instruction::Copy { dest: *dest, source: k.value.clone(), type_: type_.clone() };
+                    forwarded += 1;
+                }
+            }
+            // MIR isnt SSA, a reassigned local no longer holds what was known. a struct or array
+            // value is memory itself, writing in2 it changes what a copy of it gives
+            let redefined = inst.dest();
+            known.retain(|k| {
+                let aggregate = matches!(k.type_, Type::Struct(_) | Type::Array(_));
+                let clobbered = aa.may_write(inst, &k.pointer) || (aggregate && aa.may_write(inst, &k.value));
+                !clobbered && !redefined.is_some_and(|d| k.value == Operand::Local(d) || k.pointer == Operand::Local(d))
+            });
+            match inst {
+                Instruction::Store { dest, source, type_ } => {
+                    known.push(Known { pointer: dest.clone(), value: source.clone(), type_: type_.clone() });
+                }
+                // a load that reads its own pointer's local isnt worth keeping
+                Instruction::Load { dest, source, type_ } if *source != Operand::Local(*dest) => {
+                    known.push(Known { pointer: source.clone(), value: Operand::Local(*dest), type_: type_.clone() });
+                }
+                _ => {}
+            }
+        }
+        known_at_end[b] = Some(known);
+    }
+    forwarded
+}
+
+/// removes stores + memsets nothing reads b4 they r overwritten or the slot they write dies w/
+/// the fn, returns how many
+fn remove_dead_stores(func: &mut MirFunction) -> usize {
+    let aa = AliasAnalysis::new(func);
+    // the fn's own memory, nothing outside it reads these
+    let objects: Vec<Local> = func
+        .locals
+        .iter()
+        .map(|l| l.local)
+        .filter(|l| aa.place(&Operand::Local(*l)).base == Base::Object(l.id) && !aa.escapes(l.id))
+        .collect();
+    let object_of = |pointer: &Operand| match aa.place(pointer).base {
+        Base::Object(o) => objects.iter().find(|l| l.id == o).copied(),
+        _ => None,
+    };
+    let reads = |inst: &Instruction, live: &mut HashSet<usize>| {
+        for o in &objects {
+            if aa.may_read(inst, &Operand::Local(*o)) {
+                live.insert(o.id);
+            }
+        }
+    };
+
+    // objects that may still be read on entry 2 each block, til nothing changes
+    let successors: Vec<Vec<usize>> =
+        func.basic_blocks.iter().map(|bb| bb.instructions.last().map(Instruction::successors).unwrap_or_default()).collect();
+    let mut live_in: Vec<HashSet<usize>> = vec![HashSet::new(); func.basic_blocks.len()];
+    let live_out = |live_in: &[HashSet<usize>], b: usize| -> HashSet<usize> {
+        successors[b].iter().filter_map(|s| live_in.get(*s)).flatten().copied().collect()
+    };
+    loop {
+        let mut changed = false;
+        for b in (0..func.basic_blocks.len()).rev() {
+            let mut live = live_out(&live_in, b);
+            for inst in func.basic_blocks[b].instructions.iter().rev() {
+                reads(inst, &mut live);
+            }
+            if live != live_in[b] {
+                live_in[b] = live;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut removed = 0;
+    for b in 0..func.basic_blocks.len() {
+        let mut live = live_out(&live_in, b);
+        // pointers a later store in the block overwrites b4 anything reads them
+        let mut overwritten: Vec<Operand> = Vec::new();
+        let mut dead = HashSet::new();
+        for (i, inst) in func.basic_blocks[b].instructions.iter().enumerate().rev() {
+            if let Instruction::Store { dest, .. } | Instruction::Memset { dest, .. } = inst {
+                let unread = object_of(dest).is_some_and(|o| !live.contains(&o.id));
+                let covered =
+                    matches!(inst, Instruction::Store { .. }) && overwritten.iter().any(|p| aa.alias(p, dest) == AliasResult::Must);
+                if unread || covered {
+                    dead.insert(i);
+                    continue;
+                }
+            }
+            reads(inst, &mut live);
+            overwritten.retain(|p| !aa.may_read(inst, p));
+            if let Instruction::Store { dest, .. } = inst {
+                overwritten.push(dest.clone());
+            }
+        }
+        let mut i = 0;
+        func.basic_blocks[b].instructions.retain(|_| {
+            i += 1;
+            !dead.contains(&(i - 1))
+        });
+        removed += dead.len();
+    }
+    removed
+}
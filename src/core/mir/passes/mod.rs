@@ -0,0 +1,151 @@
+pub mod const_prop;
+pub mod dce;
+pub mod inline;
+pub mod load_store;
+pub mod stack_promote;
+
+pub use const_prop::ConstProp;
+pub use dce::Dce;
+pub use inline::{Inliner, DEFAULT_INLINE_THRESHOLD};
+pub use load_store::LoadStore;
+pub use stack_promote::StackPromote;
+
+use crate::core::mir::dump::MirDump;
+use crate::core::mir::function::MirFunction;
+use std::fmt;
+
+// optimizer passes over MIR, each 1 a `MirPass` the `PassManager` runs in order over a fn
+
+/// a transformation of 1 fn's MIR
+pub trait MirPass {
+    /// short name, what the pass is called in dumps + errors
+    fn name(&self) -> &'static str;
+
+    /// rewrites `func`, true if anything changed
+    fn run(&mut self, func: &mut MirFunction) -> bool;
+
+    /// totals the pass kept over all its runs 4 `--print-pass-stats`, eg instructions removed
+    fn counters(&self) -> Vec<(&'static str, usize)> {
+        Vec::new()
+    }
+
+    /// what the pass did 2 particular code, 1 line each, shown w/ --verbose
+    fn remarks(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// what 1 pass did over evry fn it ran on
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassStats {
+    pub name: &'static str,
+    pub runs: usize,
+    /// runs that changed the fn
+    pub changed: usize,
+    pub counters: Vec<(&'static str, usize)>,
+}
+
+/// `dce: changed 2 of 5 functions, 7 instructions removed, 1 blocks removed`
+impl fmt::Display for PassStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: changed {} of {} functions", self.name, self.changed, self.runs)?;
+        for (what, count) in &self.counters {
+            write!(f, ", {} {}", count, what)?;
+        }
+        Ok(())
+    }
+}
+
+/// a pass + how often it ran + changed something
+struct Scheduled {
+    pass: Box<dyn MirPass>,
+    runs: usize,
+    changed: usize,
+}
+
+/// evry pass a `PassManager` can schedule by name, in the default pipeline's order
+pub const PASS_NAMES: [&str; 4] = ["const-prop", "stack-promote", "load-store", "dce"];
+
+/// passes in the order they run
+#[derive(Default)]
+pub struct PassManager {
+    passes: Vec<Scheduled>,
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the passes the optimizer runs when nothing else is asked 4
+    pub fn default_pipeline() -> Self {
+        Self::from_names(&PASS_NAMES).expect("every default pass is registered")
+    }
+
+    /// a fresh pass by its `name()`, None if no pass goes by it
+    pub fn create(name: &str) -> Option<Box<dyn MirPass>> {
+        Some(match name {
+            "const-prop" => Box::new(ConstProp),
+            "stack-promote" => Box::new(StackPromote::default()),
+            "load-store" => Box::new(LoadStore::default()),
+            "dce" => Box::new(Dce::default()),
+            _ => return None,
+        })
+    }
+
+    /// the named passes in that order, a name can come more than once. Err is the 1st unknown name
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Result<Self, String> {
+        let mut manager = Self::new();
+        for name in names {
+            let pass = Self::create(name.as_ref()).ok_or_else(|| name.as_ref().to_string())?;
+            manager.passes.push(Scheduled { pass, runs: 0, changed: 0 });
+        }
+        Ok(manager)
+    }
+
+    pub fn with_pass(mut self, pass: impl MirPass + 'static) -> Self {
+        self.passes.push(Scheduled { pass: Box::new(pass), runs: 0, changed: 0 });
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.passes.iter().map(|s| s.pass.name()).collect()
+    }
+
+    /// each pass once over `func`, returns the names of the ones that changed it
+    pub fn run(&mut self, func: &mut MirFunction) -> Vec<&'static str> {
+        self.run_dumped(func, None)
+    }
+
+    /// `run` w/ each pass thru `dump` when there is 1
+    pub fn run_dumped(&mut self, func: &mut MirFunction, mut dump: Option<&mut MirDump>) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        for scheduled in &mut self.passes {
+            scheduled.runs += 1;
+            let pass = &mut scheduled.pass;
+            let name = pass.name();
+            let did = match dump.as_deref_mut() {
+                Some(dump) => dump.step(name, func, |func| pass.run(func)),
+                None => pass.run(func),
+            };
+            if did {
+                scheduled.changed += 1;
+                changed.push(scheduled.pass.name());
+            }
+        }
+        changed
+    }
+
+    /// evry pass's remarks so far, in pipeline order
+    pub fn remarks(&self) -> Vec<String> {
+        self.passes.iter().flat_map(|s| s.pass.remarks()).collect()
+    }
+
+    /// what each pass did so far, in pipeline order
+    pub fn stats(&self) -> Vec<PassStats> {
+        self.passes
+            .iter()
+            .map(|s| PassStats { name: s.pass.name(), runs: s.runs, changed: s.changed, counters: s.pass.counters() })
+            .collect()
+    }
+}
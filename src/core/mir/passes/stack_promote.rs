@@ -0,0 +1,79 @@
+use crate::core::mir::alias::{AliasAnalysis, Base};
+use crate::core::mir::analysis::Cfg;
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Constant, Operand};
+use crate::core::mir::passes::MirPass;
+use crate::core::mir::{is_heap_alloc, is_heap_free};
+use crate::core::types::ty::Type;
+
+/// the biggest heap allocation moved 2 the stack, so a promoted buffer cant blow a small stack
+pub const MAX_PROMOTED_BYTES: usize = 1024;
+
+/// turns a heap allocation whose address never escapes the fn in2 an `alloca` + drops the frees
+/// of it, so a short lived object costs no allocator calls. only 1 of a constant size that
+/// matches the type it's used as, outside any loop so the stack doesnt grow per iteration
+#[derive(Default)]
+pub struct StackPromote {
+    promoted: usize,
+    bytes: usize,
+    remarks: Vec<String>,
+}
+
+impl MirPass for StackPromote {
+    fn name(&self) -> &'static str {
+        "stack-promote"
+    }
+
+    fn run(&mut self, func: &mut MirFunction) -> bool {
+        let aa = AliasAnalysis::new(func);
+        let cfg = Cfg::new(func);
+        let mut promoted = Vec::new();
+        for (b, bb) in func.basic_blocks.iter().enumerate() {
+            for (i, inst) in bb.instructions.iter().enumerate() {
+                let Instruction::Call { dest: Some(dest), func: Operand::Function(f), args, .. } = inst else { continue };
+                let [Operand::Constant(Constant::Int(size))] = args.as_slice() else { continue };
+                let Some(Type::Pointer(pointer)) = func.locals.iter().find(|l| l.local == *dest).map(|l| &l.type_) else { continue };
+                let pointee = (*pointer.pointee).clone();
+                let fits = usize::try_from(*size).is_ok_and(|size| size <= MAX_PROMOTED_BYTES && pointee.size_in_bytes() == Some(size));
+                if is_heap_alloc(&f.name)
+                    && fits
+                    && aa.place(&Operand::Local(*dest)).base == Base::Object(dest.id)
+                    && !aa.escapes(dest.id)
+                    && !cfg.on_cycle(b)
+                {
+                    promoted.push((b, i, *dest, pointee, *size as usize));
+                }
+            }
+        }
+        if promoted.is_empty() {
+            return false;
+        }
+
+        for (b, i, dest, type_, size) in &promoted {
+            func.basic_blocks[*b].instructions[*i] = Instruction::Alloca { dest: *dest, type_: type_.clone() };
+            self.promoted += 1;
+            self.bytes += size;
+            self.remarks.push(format!("promoted a {} byte heap allocation in '{}' to the stack", size, func.name));
+        }
+        // the memory goes w/ the frame now
+        let objects: Vec<Base> = promoted.iter().map(|(_, _, dest, _, _)| Base::Object(dest.id)).collect();
+        for bb in &mut func.basic_blocks {
+            bb.instructions.retain(|inst| match inst {
+                Instruction::Call { func: Operand::Function(f), args, .. } if is_heap_free(&f.name) => {
+                    !args.first().is_some_and(|p| objects.contains(&aa.place(p).base))
+                }
+                _ => true,
+            });
+        }
+        true
+    }
+
+    fn counters(&self) -> Vec<(&'static str, usize)> {
+        vec![("allocations promoted", self.promoted), ("bytes promoted", self.bytes)]
+    }
+
+    fn remarks(&self) -> Vec<String> {
+        self.remarks.clone()
+    }
+}
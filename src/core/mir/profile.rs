@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use thiserror::Error;
+
+/// the share of all calls in a profile the hot call sites make up between them
+const HOT_FRACTION: f64 = 0.9;
+
+/// how often each caller called each callee in a training run, read frm `-C profile-use`. the
+/// file has 1 `caller callee count` per line, `#` starts a comment + a pair given twice adds up,
+/// so the profiles of several runs can just be concatenated
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallProfile {
+    counts: HashMap<(String, String), u64>,
+    /// fns that show up in the profile at all, as caller or callee
+    seen: HashSet<String>,
+    /// the smallest count still hot
+    hot_count: u64,
+}
+
+/// what a profile says about 1 call site
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hotness {
+    /// 1 of the sites that make up most of the calls in the run
+    Hot,
+    /// the caller ran but made this call rarely or never
+    Cold,
+    /// the caller isnt in the profile, eg it was written after the run
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ProfileError {
+    #[error("Cannot read profile '{path}': {message}")]
+    Io { path: String, message: String },
+
+    #[error("Invalid profile line {line}: expected 'caller callee count', found '{text}'")]
+    Malformed { line: usize, text: String },
+}
+
+impl CallProfile {
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ProfileError::Io { path: path.display().to_string(), message: e.to_string() })?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ProfileError> {
+        let mut profile = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let content = line.split('#').next().unwrap_or_default();
+            let fields: Vec<&str> = content.split_whitespace().collect();
+            let entry = match fields[..] {
+                [] => continue,
+                [caller, callee, count] => count.parse::<u64>().ok().map(|count| (caller, callee, count)),
+                _ => None,
+            };
+            let Some((caller, callee, count)) = entry else {
+                return Err(ProfileError::Malformed { line: i + 1, text: line.trim().to_string() });
+            };
+            profile.add(caller, callee, count);
+        }
+        profile.hot_count = profile.hot_cutoff();
+        Ok(profile)
+    }
+
+    fn add(&mut self, caller: &str, callee: &str, count: u64) {
+        let total = self.counts.entry((caller.to_string(), callee.to_string())).or_insert(0);
+        *total = total.saturating_add(count);
+        self.seen.insert(caller.to_string());
+        self.seen.insert(callee.to_string());
+    }
+
+    /// the count of the least called site among the busiest that together make `HOT_FRACTION`
+    /// of all calls
+    fn hot_cutoff(&self) -> u64 {
+        let mut counts: Vec<u64> = self.counts.values().copied().filter(|c| *c > 0).collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        let total: f64 = counts.iter().map(|c| *c as f64).sum();
+        let mut covered = 0.0;
+        for count in &counts {
+            covered += *count as f64;
+            if covered >= total * HOT_FRACTION {
+                return *count;
+            }
+        }
+        // no calls at all, nothing is hot
+        u64::MAX
+    }
+
+    /// how often `caller` called `callee`, None when the profile doesnt say
+    pub fn count(&self, caller: &str, callee: &str) -> Option<u64> {
+        self.counts.get(&(caller.to_string(), callee.to_string())).copied()
+    }
+
+    pub fn hotness(&self, caller: &str, callee: &str) -> Hotness {
+        if !self.seen.contains(caller) {
+            return Hotness::Unknown;
+        }
+        match self.count(caller, callee) {
+            Some(count) if count >= self.hot_count => Hotness::Hot,
+            _ => Hotness::Cold,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
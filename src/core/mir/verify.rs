@@ -0,0 +1,234 @@
+use crate::core::mir::analysis::Cfg;
+use crate::core::mir::function::MirFunction;
+use crate::core::mir::instruction::Instruction;
+use crate::core::mir::operand::{Constant, Local, Operand};
+use crate::core::types::composite::spelling;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+// structural checks on MIR b4 it reaches a backend, a lowering or optimizer bug shows up as an
+// error naming the fn + block instead of an llvm assertion. types r only compared where MIR
+// fixes them (arithmetic, conditions, strings, copies + returns), loads/stores/calls r
+// left 2 the backend
+
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("MIR of '{function}' is invalid in bb{block}: {kind}")]
+pub struct VerifyError {
+    pub function: String,
+    pub block: usize,
+    pub kind: VerifyErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum VerifyErrorKind {
+    #[error("the block doesn't end with a terminator")]
+    MissingTerminator,
+
+    #[error("instruction {0} is a terminator but isn't last")]
+    EarlyTerminator(usize),
+
+    #[error("branch to bb{0}, which doesn't exist")]
+    UnknownBlock(usize),
+
+    #[error("{0} is used but never assigned")]
+    UndefinedLocal(Local),
+
+    #[error("phi takes a value from bb{0}, which doesn't branch here")]
+    PhiFromNonPredecessor(usize),
+
+    #[error("{what} is {found}, expected {expected}")]
+    TypeMismatch { what: String, expected: String, found: String },
+}
+
+/// what MIR can tell apart, `Other` matches anything
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Class {
+    Int,
+    Float,
+    Bool,
+    Char,
+    Str,
+    Ptr,
+    Other,
+}
+
+impl Class {
+    fn of(ty: &Type) -> Self {
+        match ty {
+            Type::Primitive(PrimitiveType::Float) => Class::Float,
+            Type::Primitive(PrimitiveType::Bool) => Class::Bool,
+            Type::Primitive(PrimitiveType::Char) => Class::Char,
+            Type::Primitive(PrimitiveType::Void) => Class::Other,
+            Type::Primitive(_) => Class::Int,
+            Type::String => Class::Str,
+            Type::Pointer(_) => Class::Ptr,
+            _ => Class::Other,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Class::Int => "an integer",
+            Class::Float => "a float",
+            Class::Bool => "a bool",
+            Class::Char => "a char",
+            Class::Str => "a string",
+            Class::Ptr => "a pointer",
+            Class::Other => "a value",
+        }
+    }
+
+    fn fits(self, expected: Class) -> bool {
+        self == expected || self == Class::Other || expected == Class::Other
+    }
+}
+
+struct Verifier<'a> {
+    func: &'a MirFunction,
+    types: HashMap<usize, &'a Type>,
+    errors: Vec<VerifyError>,
+    block: usize,
+}
+
+/// evry problem in `func`, empty when it's fine 2 hand 2 a backend
+pub fn verify(func: &MirFunction) -> Result<(), Vec<VerifyError>> {
+    let mut verifier = Verifier {
+        func,
+        types: func.locals.iter().map(|l| (l.local.id, &l.type_)).collect(),
+        errors: Vec::new(),
+        block: 0,
+    };
+    verifier.run();
+    if verifier.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(verifier.errors)
+    }
+}
+
+/// `verify` over a whole program, errors of all fns together
+pub fn verify_all(functions: &[MirFunction]) -> Result<(), Vec<VerifyError>> {
+    let errors: Vec<_> = functions.iter().filter_map(|f| verify(f).err()).flatten().collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl Verifier<'_> {
+    fn error(&mut self, kind: VerifyErrorKind) {
+        self.errors.push(VerifyError { function: self.func.name.clone(), block: self.block, kind });
+    }
+
+    fn run(&mut self) {
+        let func = self.func;
+        let mut defined: HashSet<usize> = func.params.iter().map(|p| p.local.id).collect();
+        defined.extend(func.basic_blocks.iter().flat_map(|bb| &bb.instructions).filter_map(|i| i.dest()).map(|l| l.id));
+
+        let cfg = Cfg::new(func);
+
+        for block in &func.basic_blocks {
+            self.block = block.id;
+            match block.instructions.iter().position(Instruction::is_terminator) {
+                None => self.error(VerifyErrorKind::MissingTerminator),
+                Some(i) if i + 1 != block.instructions.len() => self.error(VerifyErrorKind::EarlyTerminator(i)),
+                Some(_) => {}
+            }
+            for inst in &block.instructions {
+                for succ in inst.successors() {
+                    if func.get_block(succ).is_none() {
+                        self.error(VerifyErrorKind::UnknownBlock(succ));
+                    }
+                }
+                for operand in inst.operands() {
+                    if let Operand::Local(l) = operand {
+                        if !defined.contains(&l.id) {
+                            self.error(VerifyErrorKind::UndefinedLocal(*l));
+                        }
+                    }
+                }
+                if let Instruction::Phi { incoming, .. } = inst {
+                    for (_, from) in incoming {
+                        if !cfg.predecessors(block.id).contains(from) {
+                            self.error(VerifyErrorKind::PhiFromNonPredecessor(*from));
+                        }
+                    }
+                }
+                self.check_types(inst);
+            }
+        }
+    }
+
+    fn class(&self, operand: &Operand) -> Class {
+        match operand {
+            Operand::Local(l) => self.types.get(&l.id).map_or(Class::Other, |t| Class::of(t)),
+            Operand::Constant(Constant::Int(_)) => Class::Int,
+            Operand::Constant(Constant::Float(_)) => Class::Float,
+            Operand::Constant(Constant::Bool(_)) => Class::Bool,
+            Operand::Constant(Constant::Char(_)) => Class::Char,
+            Operand::Constant(Constant::String(_)) => Class::Str,
+            Operand::Constant(Constant::Null) => Class::Ptr,
+            Operand::Constant(Constant::Aggregate(_)) | Operand::Function(_) => Class::Other,
+        }
+    }
+
+    fn mismatch(&mut self, what: &str, operand: &Operand, expected: String, found: Class) {
+        let what = format!("{} {}", what, operand);
+        self.error(VerifyErrorKind::TypeMismatch { what, expected, found: found.name().to_string() });
+    }
+
+    fn expect_type(&mut self, what: &str, operand: &Operand, ty: &Type) {
+        let found = self.class(operand);
+        if !found.fits(Class::of(ty)) {
+            self.mismatch(what, operand, spelling(ty), found);
+        }
+    }
+
+    fn expect_class(&mut self, what: &str, operand: &Operand, expected: Class) {
+        let found = self.class(operand);
+        if !found.fits(expected) {
+            self.mismatch(what, operand, expected.name().to_string(), found);
+        }
+    }
+
+    fn check_types(&mut self, inst: &Instruction) {
+        match inst {
+            Instruction::Add { left, right, type_, .. }
+            | Instruction::Sub { left, right, type_, .. }
+            | Instruction::Mul { left, right, type_, .. }
+            | Instruction::Div { left, right, type_, .. }
+            | Instruction::Mod { left, right, type_, .. } => {
+                self.expect_type("operand", left, type_);
+                self.expect_type("operand", right, type_);
+            }
+            Instruction::Eq { left, right, .. }
+            | Instruction::Ne { left, right, .. }
+            | Instruction::Lt { left, right, .. }
+            | Instruction::Le { left, right, .. }
+            | Instruction::Gt { left, right, .. }
+            | Instruction::Ge { left, right, .. } => {
+                let expected = self.class(left);
+                self.expect_class("compared operand", right, expected);
+            }
+            Instruction::Br { condition, .. } => self.expect_class("condition", condition, Class::Bool),
+            Instruction::StrLen { source, .. } => self.expect_class("operand", source, Class::Str),
+            Instruction::StrConcat { left, right, .. } | Instruction::StrEq { left, right, .. } => {
+                self.expect_class("operand", left, Class::Str);
+                self.expect_class("operand", right, Class::Str);
+            }
+            Instruction::Copy { dest, source, type_ } => {
+                self.expect_type("copied value", source, type_);
+                self.expect_type("copy destination", &Operand::Local(*dest), type_);
+            }
+            Instruction::Ret { value: Some(value) } => {
+                if let Some(ty) = &self.func.return_type {
+                    self.expect_type("returned value", value, ty);
+                }
+            }
+            _ => {}
+        }
+    }
+}
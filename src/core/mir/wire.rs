@@ -0,0 +1,764 @@
+use crate::core::mir::basic_block::BasicBlock;
+use crate::core::mir::function::{LocalInfo, MirFunction, Param};
+use crate::core::mir::instruction::{Instruction, Vtable};
+use crate::core::mir::operand::{Constant, FunctionRef, Local, Operand};
+use crate::core::target::CallConv;
+use crate::core::types::composite::{ArrayType, EnumType, Field, FunctionType, StructType, Variant};
+use crate::core::types::generic::GenericType;
+use crate::core::types::pointer::PointerType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::{TraitObjectType, Type};
+use thiserror::Error;
+
+/// bumped whenever the encoding changes, a reader only takes its own version
+pub const WIRE_VERSION: u32 = 1;
+
+/// MIR as bytes 4 handing codegen units 2 another process or machine. numbers r little endian,
+/// lengths + enum tags come b4 what they describe, nothing is shared so a fn decodes on its own
+pub trait Wire: Sized {
+    fn encode(&self, w: &mut Writer);
+    fn decode(r: &mut Reader) -> Result<Self, WireError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum WireError {
+    #[error("Unexpected end of input at byte {0}")]
+    Truncated(usize),
+
+    #[error("Invalid {what} tag {tag} at byte {at}")]
+    BadTag { what: &'static str, tag: u8, at: usize },
+
+    #[error("Invalid UTF-8 at byte {0}")]
+    BadText(usize),
+
+    #[error("Unsupported wire version {0}, expected {WIRE_VERSION}")]
+    Version(u32),
+}
+
+#[derive(Debug, Default)]
+pub struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+
+    pub fn u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, v: u64) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn bytes(&mut self, v: &[u8]) {
+        self.u64(v.len() as u64);
+        self.bytes.extend_from_slice(v);
+    }
+
+    pub fn put<T: Wire>(&mut self, v: &T) {
+        v.encode(self);
+    }
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], WireError> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.bytes.len()).ok_or(WireError::Truncated(self.pos))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, WireError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, WireError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, WireError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], WireError> {
+        let len = self.u64()?;
+        self.take(usize::try_from(len).map_err(|_| WireError::Truncated(self.pos))?)
+    }
+
+    pub fn get<T: Wire>(&mut self) -> Result<T, WireError> {
+        T::decode(self)
+    }
+
+    /// a tag byte, w/ what it tags 4 the error when its out of range
+    fn tag(&mut self, what: &'static str, count: u8) -> Result<u8, WireError> {
+        let at = self.pos;
+        let tag = self.u8()?;
+        if tag < count { Ok(tag) } else { Err(WireError::BadTag { what, tag, at }) }
+    }
+}
+
+impl Wire for u8 {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(*self);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        r.u8()
+    }
+}
+
+impl Wire for u32 {
+    fn encode(&self, w: &mut Writer) {
+        w.u32(*self);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        r.u32()
+    }
+}
+
+impl Wire for usize {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(*self as u64);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        let at = r.pos;
+        usize::try_from(r.u64()?).map_err(|_| WireError::Truncated(at))
+    }
+}
+
+impl Wire for i64 {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(*self as u64);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(r.u64()? as i64)
+    }
+}
+
+impl Wire for bool {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(*self as u8);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(r.tag("bool", 2)? == 1)
+    }
+}
+
+impl Wire for String {
+    fn encode(&self, w: &mut Writer) {
+        w.bytes(self.as_bytes());
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        let at = r.pos;
+        String::from_utf8(r.bytes()?.to_vec()).map_err(|_| WireError::BadText(at))
+    }
+}
+
+impl<T: Wire> Wire for Box<T> {
+    fn encode(&self, w: &mut Writer) {
+        (**self).encode(w);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        T::decode(r).map(Box::new)
+    }
+}
+
+impl<T: Wire> Wire for Option<T> {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Some(v) => {
+                w.u8(1);
+                v.encode(w);
+            }
+            None => w.u8(0),
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        match r.tag("option", 2)? {
+            1 => T::decode(r).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<T: Wire> Wire for Vec<T> {
+    fn encode(&self, w: &mut Writer) {
+        w.u64(self.len() as u64);
+        for v in self {
+            v.encode(w);
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        let len: usize = r.get()?;
+        // a bad length runs out of input instead of allocating it all up front
+        let mut items = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            items.push(T::decode(r)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<A: Wire, B: Wire> Wire for (A, B) {
+    fn encode(&self, w: &mut Writer) {
+        self.0.encode(w);
+        self.1.encode(w);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok((r.get()?, r.get()?))
+    }
+}
+
+const PRIMITIVES: [PrimitiveType; 11] = [
+    PrimitiveType::Void,
+    PrimitiveType::Byte,
+    PrimitiveType::Int,
+    PrimitiveType::Long,
+    PrimitiveType::Size,
+    PrimitiveType::Float,
+    PrimitiveType::Bool,
+    PrimitiveType::Char,
+    PrimitiveType::UByte,
+    PrimitiveType::UInt,
+    PrimitiveType::ULong,
+];
+
+impl Wire for PrimitiveType {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(PRIMITIVES.iter().position(|p| p == self).unwrap() as u8);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(PRIMITIVES[r.tag("primitive type", PRIMITIVES.len() as u8)? as usize])
+    }
+}
+
+impl Wire for Type {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Type::Primitive(p) => {
+                w.u8(0);
+                w.put(p);
+            }
+            Type::Struct(s) => {
+                w.u8(1);
+                w.put(&s.name);
+                w.put(&s.fields);
+                w.put(&s.size);
+                w.put(&s.align);
+            }
+            Type::Enum(e) => {
+                w.u8(2);
+                w.put(&e.name);
+                w.put(&e.variants);
+            }
+            Type::Array(a) => {
+                w.u8(3);
+                w.put(&a.element);
+                w.put(&a.size);
+            }
+            Type::Pointer(p) => {
+                w.u8(4);
+                w.put(&p.pointee);
+                w.put(&p.nullable);
+            }
+            Type::Generic(g) => {
+                w.u8(5);
+                w.put(&g.name);
+                w.put(&g.constraints);
+            }
+            Type::Function(f) => {
+                w.u8(6);
+                w.put(&f.params);
+                w.put(&f.return_type);
+            }
+            Type::TraitObject(t) => {
+                w.u8(7);
+                w.put(&t.trait_name);
+                w.put(&t.constraints);
+            }
+            Type::String => w.u8(8),
+            Type::Error => w.u8(9),
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(match r.tag("type", 10)? {
+            0 => Type::Primitive(r.get()?),
+            1 => Type::Struct(StructType { name: r.get()?, fields: r.get()?, size: r.get()?, align: r.get()? }),
+            2 => Type::Enum(EnumType { name: r.get()?, variants: r.get()? }),
+            3 => Type::Array(ArrayType { element: r.get()?, size: r.get()? }),
+            4 => Type::Pointer(PointerType { pointee: r.get()?, nullable: r.get()? }),
+            5 => Type::Generic(GenericType { name: r.get()?, constraints: r.get()? }),
+            6 => Type::Function(FunctionType { params: r.get()?, return_type: r.get()? }),
+            7 => Type::TraitObject(TraitObjectType { trait_name: r.get()?, constraints: r.get()? }),
+            8 => Type::String,
+            _ => Type::Error,
+        })
+    }
+}
+
+impl Wire for Field {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.name);
+        w.put(&self.type_);
+        w.put(&self.offset);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(Field { name: r.get()?, type_: r.get()?, offset: r.get()? })
+    }
+}
+
+impl Wire for Variant {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.name);
+        w.put(&self.fields);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(Variant { name: r.get()?, fields: r.get()? })
+    }
+}
+
+impl Wire for Local {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.id);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(Local::new(r.get()?))
+    }
+}
+
+impl Wire for Constant {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Constant::Int(i) => {
+                w.u8(0);
+                w.put(i);
+            }
+            Constant::Float(f) => {
+                w.u8(1);
+                w.u64(f.to_bits());
+            }
+            Constant::Bool(b) => {
+                w.u8(2);
+                w.put(b);
+            }
+            Constant::Char(c) => {
+                w.u8(3);
+                w.u32(*c as u32);
+            }
+            Constant::String(s) => {
+                w.u8(4);
+                w.put(s);
+            }
+            Constant::Null => w.u8(5),
+            Constant::Aggregate(elements) => {
+                w.u8(6);
+                w.put(elements);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(match r.tag("constant", 7)? {
+            0 => Constant::Int(r.get()?),
+            1 => Constant::Float(f64::from_bits(r.u64()?)),
+            2 => Constant::Bool(r.get()?),
+            3 => {
+                let at = r.pos;
+                Constant::Char(char::from_u32(r.u32()?).ok_or(WireError::BadText(at))?)
+            }
+            4 => Constant::String(r.get()?),
+            5 => Constant::Null,
+            _ => Constant::Aggregate(r.get()?),
+        })
+    }
+}
+
+impl Wire for Operand {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Operand::Constant(c) => {
+                w.u8(0);
+                w.put(c);
+            }
+            Operand::Local(l) => {
+                w.u8(1);
+                w.put(l);
+            }
+            Operand::Function(f) => {
+                w.u8(2);
+                w.put(&f.name);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(match r.tag("operand", 3)? {
+            0 => Operand::Constant(r.get()?),
+            1 => Operand::Local(r.get()?),
+            _ => Operand::Function(FunctionRef { name: r.get()? }),
+        })
+    }
+}
+
+const CALL_CONVS: [CallConv; 8] = [
+    CallConv::C,
+    CallConv::Stdcall,
+    CallConv::Fastcall,
+    CallConv::Thiscall,
+    CallConv::Vectorcall,
+    CallConv::Win64,
+    CallConv::Sysv64,
+    CallConv::System,
+];
+
+impl Wire for CallConv {
+    fn encode(&self, w: &mut Writer) {
+        w.u8(CALL_CONVS.iter().position(|c| c == self).unwrap() as u8);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(CALL_CONVS[r.tag("calling convention", CALL_CONVS.len() as u8)? as usize])
+    }
+}
+
+impl Wire for Vtable {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.trait_name);
+        w.put(&self.type_name);
+        w.put(&self.methods);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(Vtable { trait_name: r.get()?, type_name: r.get()?, methods: r.get()? })
+    }
+}
+
+/// tags r the variants in declaration order, the binary ops share a layout per group
+impl Wire for Instruction {
+    fn encode(&self, w: &mut Writer) {
+        match self {
+            Instruction::Add { dest, left, right, type_ }
+            | Instruction::Sub { dest, left, right, type_ }
+            | Instruction::Mul { dest, left, right, type_ }
+            | Instruction::Div { dest, left, right, type_ }
+            | Instruction::Mod { dest, left, right, type_ } => {
+                let tag = match self {
+                    Instruction::Add { .. } => 0,
+                    Instruction::Sub { .. } => 1,
+                    Instruction::Mul { .. } => 2,
+                    Instruction::Div { .. } => 3,
+                    _ => 4,
+                };
+                w.u8(tag);
+                w.put(dest);
+                w.put(left);
+                w.put(right);
+                w.put(type_);
+            }
+            Instruction::Eq { dest, left, right }
+            | Instruction::Ne { dest, left, right }
+            | Instruction::Lt { dest, left, right }
+            | Instruction::Le { dest, left, right }
+            | Instruction::Gt { dest, left, right }
+            | Instruction::Ge { dest, left, right }
+            | Instruction::And { dest, left, right }
+            | Instruction::Or { dest, left, right }
+            | Instruction::StrConcat { dest, left, right }
+            | Instruction::StrEq { dest, left, right } => {
+                let tag = match self {
+                    Instruction::Eq { .. } => 5,
+                    Instruction::Ne { .. } => 6,
+                    Instruction::Lt { .. } => 7,
+                    Instruction::Le { .. } => 8,
+                    Instruction::Gt { .. } => 9,
+                    Instruction::Ge { .. } => 10,
+                    Instruction::And { .. } => 11,
+                    Instruction::Or { .. } => 12,
+                    Instruction::StrConcat { .. } => 28,
+                    _ => 29,
+                };
+                w.u8(tag);
+                w.put(dest);
+                w.put(left);
+                w.put(right);
+            }
+            Instruction::Not { dest, operand } => {
+                w.u8(13);
+                w.put(dest);
+                w.put(operand);
+            }
+            Instruction::Load { dest, source, type_ } | Instruction::VolatileLoad { dest, source, type_ } | Instruction::Copy { dest, source, type_ } => {
+                let tag = match self {
+                    Instruction::Load { .. } => 14,
+                    Instruction::VolatileLoad { .. } => 16,
+                    _ => 36,
+                };
+                w.u8(tag);
+                w.put(dest);
+                w.put(source);
+                w.put(type_);
+            }
+            Instruction::Store { dest, source, type_ } | Instruction::VolatileStore { dest, source, type_ } => {
+                w.u8(if matches!(self, Instruction::Store { .. }) { 15 } else { 17 });
+                w.put(dest);
+                w.put(source);
+                w.put(type_);
+            }
+            Instruction::Alloca { dest, type_ } => {
+                w.u8(18);
+                w.put(dest);
+                w.put(type_);
+            }
+            Instruction::Gep { dest, base, index, type_ } | Instruction::PtrOffset { dest, base, offset: index, type_ } => {
+                w.u8(if matches!(self, Instruction::Gep { .. }) { 19 } else { 20 });
+                w.put(dest);
+                w.put(base);
+                w.put(index);
+                w.put(type_);
+            }
+            Instruction::Memset { dest, value, count, type_ } => {
+                w.u8(21);
+                w.put(dest);
+                w.put(value);
+                w.put(count);
+                w.put(type_);
+            }
+            Instruction::Call { dest, func, args, return_type, fixed_args, conv } => {
+                w.u8(22);
+                w.put(dest);
+                w.put(func);
+                w.put(args);
+                w.put(return_type);
+                w.put(fixed_args);
+                w.put(conv);
+            }
+            Instruction::Ret { value } => {
+                w.u8(23);
+                w.put(value);
+            }
+            Instruction::Br { condition, then_bb, else_bb } => {
+                w.u8(24);
+                w.put(condition);
+                w.put(then_bb);
+                w.put(else_bb);
+            }
+            Instruction::Jump { target } => {
+                w.u8(25);
+                w.put(target);
+            }
+            Instruction::Switch { value, arms, default } => {
+                w.u8(26);
+                w.put(value);
+                w.put(arms);
+                w.put(default);
+            }
+            Instruction::StrLen { dest, source } | Instruction::EnumTag { dest, source } => {
+                w.u8(if matches!(self, Instruction::StrLen { .. }) { 27 } else { 33 });
+                w.put(dest);
+                w.put(source);
+            }
+            Instruction::MakeDyn { dest, data, vtable } => {
+                w.u8(30);
+                w.put(dest);
+                w.put(data);
+                w.put(vtable);
+            }
+            Instruction::DynCall { dest, object, slot, args, return_type } => {
+                w.u8(31);
+                w.put(dest);
+                w.put(object);
+                w.put(slot);
+                w.put(args);
+                w.put(return_type);
+            }
+            Instruction::MakeVariant { dest, type_, tag, fields } => {
+                w.u8(32);
+                w.put(dest);
+                w.put(type_);
+                w.put(tag);
+                w.put(fields);
+            }
+            Instruction::VariantField { dest, source, type_, tag, index } => {
+                w.u8(34);
+                w.put(dest);
+                w.put(source);
+                w.put(type_);
+                w.put(tag);
+                w.put(index);
+            }
+            Instruction::Phi { dest, type_, incoming } => {
+                w.u8(35);
+                w.put(dest);
+                w.put(type_);
+                w.put(incoming);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(match r.tag("instruction", 37)? {
+            0 => Instruction::Add { dest: r.get()?, left: r.get()?, right: r.get()?, type_: r.get()? },
+            1 => Instruction::Sub { dest: r.get()?, left: r.get()?, right: r.get()?, type_: r.get()? },
+            2 => Instruction::Mul { dest: r.get()?, left: r.get()?, right: r.get()?, type_: r.get()? },
+            3 => Instruction::Div { dest: r.get()?, left: r.get()?, right: r.get()?, type_: r.get()? },
+            4 => Instruction::Mod { dest: r.get()?, left: r.get()?, right: r.get()?, type_: r.get()? },
+            5 => Instruction::Eq { dest: r.get()?, left: r.get()?, right: r.get()? },
+            6 => Instruction::Ne { dest: r.get()?, left: r.get()?, right: r.get()? },
+            7 => Instruction::Lt { dest: r.get()?, left: r.get()?, right: r.get()? },
+            8 => Instruction::Le { dest: r.get()?, left: r.get()?, right: r.get()? },
+            9 => Instruction::Gt { dest: r.get()?, left: r.get()?, right: r.get()? },
+            10 => Instruction::Ge { dest: r.get()?, left: r.get()?, right: r.get()? },
+            11 => Instruction::And { dest: r.get()?, left: r.get()?, right: r.get()? },
+            12 => Instruction::Or { dest: r.get()?, left: r.get()?, right: r.get()? },
+            13 => Instruction::Not { dest: r.get()?, operand: r.get()? },
+            14 => Instruction::Load { dest: r.get()?, source: r.get()?, type_: r.get()? },
+            15 => Instruction::Store { dest: r.get()?, source: r.get()?, type_: r.get()? },
+            16 => Instruction::VolatileLoad { dest: r.get()?, source: r.get()?, type_: r.get()? },
+            17 => Instruction::VolatileStore { dest: r.get()?, source: r.get()?, type_: r.get()? },
+            18 => Instruction::Alloca { dest: r.get()?, type_: r.get()? },
+            19 => Instruction::Gep { dest: r.get()?, base: r.get()?, index: r.get()?, type_: r.get()? },
+            20 => Instruction::PtrOffset { dest: r.get()?, base: r.get()?, offset: r.get()?, type_: r.get()? },
+            21 => Instruction::Memset { dest: r.get()?, value: r.get()?, count: r.get()?, type_: r.get()? },
+            22 => Instruction::Call {
+                dest: r.get()?,
+                func: r.get()?,
+                args: r.get()?,
+                return_type: r.get()?,
+                fixed_args: r.get()?,
+                conv: r.get()?,
+            },
+            23 => Instruction::Ret { value: r.get()? },
+            24 => Instruction::Br { condition: r.get()?, then_bb: r.get()?, else_bb: r.get()? },
+            25 => Instruction::Jump { target: r.get()? },
+            26 => Instruction::Switch { value: r.get()?, arms: r.get()?, default: r.get()? },
+            27 => Instruction::StrLen { dest: r.get()?, source: r.get()? },
+            28 => Instruction::StrConcat { dest: r.get()?, left: r.get()?, right: r.get()? },
+            29 => Instruction::StrEq { dest: r.get()?, left: r.get()?, right: r.get()? },
+            30 => Instruction::MakeDyn { dest: r.get()?, data: r.get()?, vtable: r.get()? },
+            31 => Instruction::DynCall { dest: r.get()?, object: r.get()?, slot: r.get()?, args: r.get()?, return_type: r.get()? },
+            32 => Instruction::MakeVariant { dest: r.get()?, type_: r.get()?, tag: r.get()?, fields: r.get()? },
+            33 => Instruction::EnumTag { dest: r.get()?, source: r.get()? },
+            34 => Instruction::VariantField { dest: r.get()?, source: r.get()?, type_: r.get()?, tag: r.get()?, index: r.get()? },
+            35 => Instruction::Phi { dest: r.get()?, type_: r.get()?, incoming: r.get()? },
+            _ => Instruction::Copy { dest: r.get()?, source: r.get()?, type_: r.get()? },
+        })
+    }
+}
+
+impl Wire for BasicBlock {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.id);
+        w.put(&self.instructions);
+        w.put(&self.predecessors);
+        w.put(&self.successors);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(BasicBlock { id: r.get()?, instructions: r.get()?, predecessors: r.get()?, successors: r.get()? })
+    }
+}
+
+impl Wire for Param {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.name);
+        w.put(&self.type_);
+        w.put(&self.local);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(Param { name: r.get()?, type_: r.get()?, local: r.get()? })
+    }
+}
+
+impl Wire for LocalInfo {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.local);
+        w.put(&self.type_);
+        w.put(&self.name);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(LocalInfo { local: r.get()?, type_: r.get()?, name: r.get()? })
+    }
+}
+
+impl Wire for MirFunction {
+    fn encode(&self, w: &mut Writer) {
+        w.put(&self.name);
+        w.put(&self.params);
+        w.put(&self.return_type);
+        w.put(&self.basic_blocks);
+        w.put(&self.entry_block);
+        w.put(&self.locals);
+        w.put(&self.next_local_id);
+        w.put(&self.target_features);
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self, WireError> {
+        Ok(MirFunction {
+            name: r.get()?,
+            params: r.get()?,
+            return_type: r.get()?,
+            basic_blocks: r.get()?,
+            entry_block: r.get()?,
+            locals: r.get()?,
+            next_local_id: r.get()?,
+            target_features: r.get()?,
+        })
+    }
+}
+
+/// `functions` behind the wire version, what a codegen unit's MIR travels as
+pub fn encode_functions(functions: &[MirFunction], w: &mut Writer) {
+    w.u32(WIRE_VERSION);
+    w.u64(functions.len() as u64);
+    for function in functions {
+        function.encode(w);
+    }
+}
+
+pub fn decode_functions(r: &mut Reader) -> Result<Vec<MirFunction>, WireError> {
+    let version = r.u32()?;
+    if version != WIRE_VERSION {
+        return Err(WireError::Version(version));
+    }
+    r.get()
+}
@@ -1,5 +1,10 @@
 pub mod ast;
+pub mod edition;
+pub mod feature;
 pub mod hir;
+pub mod limits;
 pub mod mir;
 pub mod optimizations;
+pub mod stack;
+pub mod target;
 pub mod types;
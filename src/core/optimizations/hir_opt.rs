@@ -1,5 +1,6 @@
 use crate::core::hir::*;
 use crate::core::hir::symbol::HirSymbol;
+use crate::core::stack;
 use std::collections::HashMap;
 
 pub struct HirOptimizer {
@@ -39,6 +40,10 @@ impl HirOptimizer {
     }
 
     fn constant_fold_stmts(&mut self, stmts: &mut Vec<HirStmt>) {
+        stack::grow(|| self.constant_fold_stmts_inner(stmts))
+    }
+
+    fn constant_fold_stmts_inner(&mut self, stmts: &mut Vec<HirStmt>) {
         // track const vars 4 propagation
         let mut const_vars: HashMap<String, HirLiteralKind> = HashMap::new();
         
@@ -53,8 +58,8 @@ impl HirOptimizer {
                     if let Some(e) = &mut s.value {
                         self.constant_fold_expr(e);
                         self.propagate_constants_expr(e, &const_vars);
-                        // if value is const track it
-                        if let HirExpr::Literal(lit) = e {
+                        // if value is const track it, a mut var may be reassigned anywhere after
+                        if let (HirExpr::Literal(lit), false) = (&*e, s.mutable) {
                             const_vars.insert(s.name.clone(), lit.kind.clone());
                         }
                     }
@@ -78,6 +83,25 @@ impl HirOptimizer {
                     self.propagate_constants_expr(&mut s.condition, &const_vars);
                     self.constant_fold_stmts(&mut s.body);
                 }
+                HirStmt::Guard(s) => {
+                    self.constant_fold_expr(&mut s.condition);
+                    self.propagate_constants_expr(&mut s.condition, &const_vars);
+                    self.constant_fold_stmts(&mut s.else_branch);
+                }
+                // runs at the scope's exit, whats constant here may not b by then
+                HirStmt::Defer(s) => {
+                    self.constant_fold_stmts(&mut s.body);
+                }
+                HirStmt::Match(s) => {
+                    self.constant_fold_expr(&mut s.scrutinee);
+                    self.propagate_constants_expr(&mut s.scrutinee, &const_vars);
+                    for arm in &mut s.arms {
+                        self.constant_fold_stmts(&mut arm.body);
+                    }
+                    if let Some(else_stmts) = &mut s.else_branch {
+                        self.constant_fold_stmts(else_stmts);
+                    }
+                }
                 _ => {}
             }
         }
@@ -85,6 +109,10 @@ impl HirOptimizer {
 
     // propagate known const vars into exprs
     fn propagate_constants_expr(&mut self, expr: &mut HirExpr, const_vars: &HashMap<String, HirLiteralKind>) {
+        stack::grow(|| self.propagate_constants_expr_inner(expr, const_vars))
+    }
+
+    fn propagate_constants_expr_inner(&mut self, expr: &mut HirExpr, const_vars: &HashMap<String, HirLiteralKind>) {
         match expr {
             HirExpr::Variable(v) => {
                 // if var is known const replace w/ literal
@@ -103,12 +131,21 @@ impl HirOptimizer {
             HirExpr::Unary(u) => {
                 self.propagate_constants_expr(&mut u.expr, const_vars);
             }
+            HirExpr::Try(t) => {
+                self.propagate_constants_expr(&mut t.expr, const_vars);
+            }
             HirExpr::Call(c) => {
                 self.propagate_constants_expr(&mut c.callee, const_vars);
                 for arg in &mut c.args {
                     self.propagate_constants_expr(arg, const_vars);
                 }
             }
+            HirExpr::MethodCall(m) => {
+                self.propagate_constants_expr(&mut m.receiver, const_vars);
+                for arg in &mut m.args {
+                    self.propagate_constants_expr(arg, const_vars);
+                }
+            }
             HirExpr::FieldAccess(f) => {
                 self.propagate_constants_expr(&mut f.object, const_vars);
             }
@@ -135,6 +172,10 @@ impl HirOptimizer {
     }
 
     fn constant_fold_expr(&mut self, expr: &mut HirExpr) {
+        stack::grow(|| self.constant_fold_expr_inner(expr))
+    }
+
+    fn constant_fold_expr_inner(&mut self, expr: &mut HirExpr) {
         match expr {
             HirExpr::Binary(b) => {
                 self.constant_fold_expr(&mut b.left);
@@ -163,6 +204,9 @@ impl HirOptimizer {
                     }
                 }
             }
+            HirExpr::Try(t) => {
+                self.constant_fold_expr(&mut t.expr);
+            }
             HirExpr::Call(c) => {
                 self.constant_fold_expr(&mut c.callee);
                 for arg in &mut c.args {
@@ -295,6 +339,10 @@ impl HirOptimizer {
     }
 
     fn remove_unreachable_code(&mut self, stmts: &mut Vec<HirStmt>) {
+        stack::grow(|| self.remove_unreachable_code_inner(stmts))
+    }
+
+    fn remove_unreachable_code_inner(&mut self, stmts: &mut Vec<HirStmt>) {
         let mut i = 0;
         while i < stmts.len() {
             if matches!(stmts[i], HirStmt::Return(_) | HirStmt::Break(_)) {
@@ -303,18 +351,20 @@ impl HirOptimizer {
                 break;
             }
             // also remove dead branches in if stmts
-            if let HirStmt::If(s) = &stmts[i] {
-                if let HirExpr::Literal(lit) = &s.condition {
+            // the branch is moved out not cloned, cloning a deeply nested branch recurses as deep.
+            // 1 w/ a defer stays a scope of its own, spliced in the deferred code would run later
+            if let HirStmt::If(s) = &mut stmts[i] {
+                let defers = |branch: &[HirStmt]| branch.iter().any(|st| matches!(st, HirStmt::Defer(_)));
+                if let (HirExpr::Literal(lit), false) = (&s.condition, defers(&s.then_branch) || s.else_branch.as_deref().is_some_and(defers)) {
                     if let HirLiteralKind::Bool(true) = lit.kind {
                         // condition always true replace w/ then branch
-                        let then_branch = s.then_branch.clone();
+                        let then_branch = std::mem::take(&mut s.then_branch);
                         stmts.remove(i);
                         stmts.splice(i..i, then_branch);
                         continue;
                     } else if let HirLiteralKind::Bool(false) = lit.kind {
                         // condition always false use else branch or remove
-                        if let Some(else_branch) = &s.else_branch {
-                            let else_branch = else_branch.clone();
+                        if let Some(else_branch) = s.else_branch.take() {
                             stmts.remove(i);
                             stmts.splice(i..i, else_branch);
                             continue;
@@ -327,8 +377,9 @@ impl HirOptimizer {
                 }
             }
             // remove unused let assignments (simple case)
+            // a `?` in the value can return, the let stays 4 that
             if let HirStmt::Let(s) = &stmts[i] {
-                if s.value.is_some() {
+                if s.value.as_ref().is_some_and(|v| !propagates(v)) {
                     // chk if var is used later (simple check)
                     let var_name = &s.name;
                     let mut used = false;
@@ -350,6 +401,10 @@ impl HirOptimizer {
     }
 
     fn var_used_in_stmt(&self, var_name: &str, stmt: &HirStmt) -> bool {
+        stack::grow(|| self.var_used_in_stmt_inner(var_name, stmt))
+    }
+
+    fn var_used_in_stmt_inner(&self, var_name: &str, stmt: &HirStmt) -> bool {
         match stmt {
             HirStmt::Expr(s) => self.var_used_in_expr(var_name, &s.expr),
             HirStmt::Let(s) => {
@@ -380,11 +435,25 @@ impl HirOptimizer {
                 self.var_used_in_expr(var_name, &s.condition) ||
                 s.body.iter().any(|st| self.var_used_in_stmt(var_name, st))
             }
+            HirStmt::Guard(s) => {
+                self.var_used_in_expr(var_name, &s.condition) ||
+                s.else_branch.iter().any(|st| self.var_used_in_stmt(var_name, st))
+            }
+            HirStmt::Defer(s) => s.body.iter().any(|st| self.var_used_in_stmt(var_name, st)),
+            HirStmt::Match(s) => {
+                self.var_used_in_expr(var_name, &s.scrutinee) ||
+                s.arms.iter().flat_map(|a| &a.body).chain(s.else_branch.iter().flatten())
+                    .any(|st| self.var_used_in_stmt(var_name, st))
+            }
             _ => false,
         }
     }
 
     fn var_used_in_expr(&self, var_name: &str, expr: &HirExpr) -> bool {
+        stack::grow(|| self.var_used_in_expr_inner(var_name, expr))
+    }
+
+    fn var_used_in_expr_inner(&self, var_name: &str, expr: &HirExpr) -> bool {
         match expr {
             HirExpr::Variable(v) => v.name == var_name,
             HirExpr::Binary(b) => {
@@ -396,6 +465,10 @@ impl HirOptimizer {
                 self.var_used_in_expr(var_name, &c.callee) ||
                 c.args.iter().any(|a| self.var_used_in_expr(var_name, a))
             }
+            HirExpr::MethodCall(m) => {
+                self.var_used_in_expr(var_name, &m.receiver) ||
+                m.args.iter().any(|a| self.var_used_in_expr(var_name, a))
+            }
             HirExpr::FieldAccess(f) => self.var_used_in_expr(var_name, &f.object),
             HirExpr::Index(i) => {
                 self.var_used_in_expr(var_name, &i.array) ||
@@ -414,6 +487,14 @@ impl HirOptimizer {
                 self.var_used_in_expr(var_name, &a.target) ||
                 self.var_used_in_expr(var_name, &a.value)
             }
+            HirExpr::Ref(r) => self.var_used_in_expr(var_name, &r.expr),
+            HirExpr::At(a) => self.var_used_in_expr(var_name, &a.expr),
+            HirExpr::New(n) => self.var_used_in_expr(var_name, &n.value),
+            HirExpr::Delete(d) => self.var_used_in_expr(var_name, &d.pointer),
+            HirExpr::Try(t) => self.var_used_in_expr(var_name, &t.expr),
+            HirExpr::ArrayLiteral(a) => a.elements.iter().any(|e| self.var_used_in_expr(var_name, e)),
+            HirExpr::StructLiteral(s) => s.fields.iter().any(|(_, e)| self.var_used_in_expr(var_name, e)),
+            HirExpr::Variant(v) => v.fields.iter().any(|e| self.var_used_in_expr(var_name, e)),
             _ => false,
         }
     }
@@ -430,6 +511,10 @@ impl HirOptimizer {
     }
 
     fn desugar_stmts(&mut self, stmts: &mut Vec<HirStmt>) {
+        stack::grow(|| self.desugar_stmts_inner(stmts))
+    }
+
+    fn desugar_stmts_inner(&mut self, stmts: &mut Vec<HirStmt>) {
         for stmt in stmts {
             match stmt {
                 HirStmt::Expr(s) => {
@@ -456,6 +541,22 @@ impl HirOptimizer {
                     self.desugar_expr(&mut s.condition);
                     self.desugar_stmts(&mut s.body);
                 }
+                HirStmt::Guard(s) => {
+                    self.desugar_expr(&mut s.condition);
+                    self.desugar_stmts(&mut s.else_branch);
+                }
+                HirStmt::Defer(s) => {
+                    self.desugar_stmts(&mut s.body);
+                }
+                HirStmt::Match(s) => {
+                    self.desugar_expr(&mut s.scrutinee);
+                    for arm in &mut s.arms {
+                        self.desugar_stmts(&mut arm.body);
+                    }
+                    if let Some(else_stmts) = &mut s.else_branch {
+                        self.desugar_stmts(else_stmts);
+                    }
+                }
                 HirStmt::For(s) => {
                     // desugar for loops 2 while loops
                     if let Some(_init) = &mut s.init {
@@ -475,6 +576,10 @@ impl HirOptimizer {
     }
 
     fn desugar_expr(&mut self, expr: &mut HirExpr) {
+        stack::grow(|| self.desugar_expr_inner(expr))
+    }
+
+    fn desugar_expr_inner(&mut self, expr: &mut HirExpr) {
         match expr {
             HirExpr::MethodCall(m) => {
                 // desugar method calls 2 regular fn calls
@@ -511,6 +616,9 @@ impl HirOptimizer {
             HirExpr::Unary(u) => {
                 self.desugar_expr(&mut u.expr);
             }
+            HirExpr::Try(t) => {
+                self.desugar_expr(&mut t.expr);
+            }
             HirExpr::If(i) => {
                 self.desugar_expr(&mut i.condition);
                 self.desugar_expr(&mut i.then_branch);
@@ -574,6 +682,10 @@ impl HirOptimizer {
     }
 
     fn cse_stmts(&mut self, stmts: &mut Vec<HirStmt>) {
+        stack::grow(|| self.cse_stmts_inner(stmts))
+    }
+
+    fn cse_stmts_inner(&mut self, stmts: &mut Vec<HirStmt>) {
         // map expr 2 var names that hold the result
         let mut expr_cache: HashMap<String, String> = HashMap::new();
         
@@ -627,12 +739,32 @@ impl HirOptimizer {
                     self.cse_expr(&mut s.condition, &mut expr_cache);
                     self.cse_stmts(&mut s.body);
                 }
+                HirStmt::Guard(s) => {
+                    self.cse_expr(&mut s.condition, &mut expr_cache);
+                    self.cse_stmts(&mut s.else_branch);
+                }
+                HirStmt::Defer(s) => {
+                    self.cse_stmts(&mut s.body);
+                }
+                HirStmt::Match(s) => {
+                    self.cse_expr(&mut s.scrutinee, &mut expr_cache);
+                    for arm in &mut s.arms {
+                        self.cse_stmts(&mut arm.body);
+                    }
+                    if let Some(else_stmts) = &mut s.else_branch {
+                        self.cse_stmts(else_stmts);
+                    }
+                }
                 _ => {}
             }
         }
     }
 
     fn cse_expr(&mut self, expr: &mut HirExpr, cache: &mut HashMap<String, String>) {
+        stack::grow(|| self.cse_expr_inner(expr, cache))
+    }
+
+    fn cse_expr_inner(&mut self, expr: &mut HirExpr, cache: &mut HashMap<String, String>) {
         match expr {
             HirExpr::Binary(b) => {
                 self.cse_expr(&mut b.left, cache);
@@ -672,6 +804,10 @@ impl HirOptimizer {
     }
 
     fn expr_key(&self, expr: &HirExpr) -> String {
+        stack::grow(|| self.expr_key_inner(expr))
+    }
+
+    fn expr_key_inner(&self, expr: &HirExpr) -> String {
         // gen a key 4 expr 2 detect duplicates
         match expr {
             HirExpr::Binary(b) => format!("{:?}:{:?}:{:?}", b.op, self.expr_key(&b.left), self.expr_key(&b.right)),
@@ -706,6 +842,10 @@ impl HirOptimizer {
     }
 
     fn optimize_loops_stmts(&mut self, stmts: &mut Vec<HirStmt>) {
+        stack::grow(|| self.optimize_loops_stmts_inner(stmts))
+    }
+
+    fn optimize_loops_stmts_inner(&mut self, stmts: &mut Vec<HirStmt>) {
         for stmt in stmts {
             match stmt {
                 HirStmt::While(s) => {
@@ -746,6 +886,20 @@ impl HirOptimizer {
                         self.optimize_loops_stmts(else_stmts);
                     }
                 }
+                HirStmt::Guard(s) => {
+                    self.optimize_loops_stmts(&mut s.else_branch);
+                }
+                HirStmt::Defer(s) => {
+                    self.optimize_loops_stmts(&mut s.body);
+                }
+                HirStmt::Match(s) => {
+                    for arm in &mut s.arms {
+                        self.optimize_loops_stmts(&mut arm.body);
+                    }
+                    if let Some(else_stmts) = &mut s.else_branch {
+                        self.optimize_loops_stmts(else_stmts);
+                    }
+                }
                 _ => {}
             }
         }
@@ -757,3 +911,21 @@ impl Default for HirOptimizer {
         Self::new()
     }
 }
+
+/// whether `expr` has a `?` that returns the error it gets
+fn propagates(expr: &HirExpr) -> bool {
+    match expr {
+        HirExpr::Try(_) => true,
+        HirExpr::Binary(b) => propagates(&b.left) || propagates(&b.right),
+        HirExpr::Unary(u) => propagates(&u.expr),
+        HirExpr::Call(c) => propagates(&c.callee) || c.args.iter().any(propagates),
+        HirExpr::MethodCall(m) => propagates(&m.receiver) || m.args.iter().any(propagates),
+        HirExpr::FieldAccess(f) => propagates(&f.object),
+        HirExpr::Index(i) => propagates(&i.array) || propagates(&i.index),
+        HirExpr::New(n) => propagates(&n.value),
+        HirExpr::ArrayLiteral(a) => a.elements.iter().any(propagates),
+        HirExpr::StructLiteral(s) => s.fields.iter().any(|(_, e)| propagates(e)),
+        HirExpr::Variant(v) => v.fields.iter().any(propagates),
+        _ => false,
+    }
+}
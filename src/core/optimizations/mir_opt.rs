@@ -1,271 +1,110 @@
+use crate::core::mir::dump::MirDump;
+use crate::core::mir::passes::{Inliner, PassManager, PassStats, DEFAULT_INLINE_THRESHOLD};
+use crate::core::mir::profile::CallProfile;
 use crate::core::mir::*;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
-pub struct MirOptimizer;
+/// the steps b4 + after `passes` in `optimize`, by the names -Z dump-mir knows them by
+const EARLY_STEPS: [&str; 2] = ["inst-combine", "copy-prop"];
+const LATE_STEPS: [&str; 4] = ["dead-locals", "renumber-locals", "phi-opt", "simplify-blocks"];
+
+pub struct MirOptimizer {
+    /// passes ported 2 `core::mir::passes`, they run after copy prop
+    passes: PassManager,
+    /// runs over the whole program b4 the per fn passes, only thru `optimize_all`
+    inliner: Inliner,
+    /// -Z dump-mir
+    dump: Option<MirDump>,
+}
 
 impl MirOptimizer {
     pub fn new() -> Self {
-        Self
+        Self { passes: PassManager::default_pipeline(), inliner: Inliner::new(DEFAULT_INLINE_THRESHOLD), dump: None }
     }
 
-    pub fn optimize(&mut self, func: &mut MirFunction) {
-        // optmzation order: const fold -> inst combine -> copy prop -> dead code -> store-load elim -> store opt -> dead local -> local renumber -> phi opt -> block simplify
-        self.constant_fold(func);
-        self.instruction_combining(func);
-        self.copy_propagation(func);
-        self.dead_code_elimination(func);
-        self.store_load_elimination(func);
-        self.store_optimization(func);
-        self.dead_local_elimination(func);
-        self.local_renumbering(func);
-        self.phi_optimization(func);
-        self.block_simplification(func);
+    /// snapshots evry fn around the passes `dump` picks
+    pub fn with_dump(mut self, dump: MirDump) -> Self {
+        self.dump = Some(dump);
+        self
     }
 
-    fn dead_code_elimination(&mut self, func: &mut MirFunction) {
-        use std::collections::{HashSet, VecDeque};
-        
-        // build def use chains: track where each local is defined and used
-        let mut defs: HashMap<Local, Vec<(usize, usize)>> = HashMap::new(); // local > []
-        let mut uses: HashMap<Local, Vec<(usize, usize)>> = HashMap::new(); // local > []
-        let mut instruction_info: Vec<(usize, usize, Instruction)> = Vec::new(); // 
-        
-        // frst pss: cllct defs uses and store all instrutions
-        for (bb_id, bb) in func.basic_blocks.iter().enumerate() {
-            for (inst_idx, inst) in bb.instructions.iter().enumerate() {
-                instruction_info.push((bb_id, inst_idx, inst.clone()));
-                
-                // find dfntns
-                if let Some(dest_local) = self.get_dest_local(inst) {
-                    defs.entry(dest_local).or_insert_with(Vec::new).push((bb_id, inst_idx));
-                }
-                
-                // find uses
-                self.collect_uses(inst, |local| {
-                    uses.entry(local).or_insert_with(Vec::new).push((bb_id, inst_idx));
-                });
-            }
-        }
-        
-        // mark locals as live if thyr used in:
-        // ret instructions
-        // br conditions
-        // store operations
-        // call arguments
-        // phi nds
-        let mut live_locals: HashSet<Local> = HashSet::new();
-        let mut worklist: VecDeque<Local> = VecDeque::new();
-        
-        // initialize wrklst w/ lcls uesd in side effectful or control flow instructions
-        for (_bb_id, _inst_idx, inst) in &instruction_info {
-            match inst {
-                Instruction::Ret { value } => {
-                    if let Some(Operand::Local(l)) = value {
-                        if !live_locals.contains(l) {
-                            live_locals.insert(*l);
-                            worklist.push_back(*l);
-                        }
-                    }
-                }
-                Instruction::Br { condition, .. } => {
-                    if let Operand::Local(l) = condition {
-                        if !live_locals.contains(l) {
-                            live_locals.insert(*l);
-                            worklist.push_back(*l);
-                        }
-                    }
-                }
-                Instruction::Store { dest, source, .. } => {
-                    // store has side effects mark both dest and source as live
-                    if let Operand::Local(l) = dest {
-                        if !live_locals.contains(l) {
-                            live_locals.insert(*l);
-                            worklist.push_back(*l);
-                        }
-                    }
-                    if let Operand::Local(l) = source {
-                        if !live_locals.contains(l) {
-                            live_locals.insert(*l);
-                            worklist.push_back(*l);
-                        }
-                    }
-                }
-                Instruction::Call { func, args, .. } => {
-                    // call has side effects mark fn and arguments as live
-                    if let Operand::Local(l) = func {
-                        if !live_locals.contains(l) {
-                            live_locals.insert(*l);
-                            worklist.push_back(*l);
-                        }
-                    }
-                    for arg in args {
-                        if let Operand::Local(l) = arg {
-                            if !live_locals.contains(l) {
-                                live_locals.insert(*l);
-                                worklist.push_back(*l);
-                            }
-                        }
-                    }
-                }
-                Instruction::Phi { incoming, .. } => {
-                    // phi nodes require all incoming values 2 be live
-                    for (op, _) in incoming {
-                        if let Operand::Local(l) = op {
-                            if !live_locals.contains(l) {
-                                live_locals.insert(*l);
-                                worklist.push_back(*l);
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        
-        // propagate lvnss bckwrds: if a local is live amrk its dfntn as needed
-        // create a map 4 quick lookup of instructions by
-        let mut inst_map: HashMap<(usize, usize), &Instruction> = HashMap::new();
-        for (bb_id, inst_idx, inst) in &instruction_info {
-            inst_map.insert((*bb_id, *inst_idx), inst);
+    /// runs `passes` after copy prop instead of the default pipeline
+    pub fn with_passes(mut self, passes: PassManager) -> Self {
+        self.passes = passes;
+        self
+    }
+
+    /// evry pass in the order they run
+    pub fn pass_names(&self) -> Vec<&'static str> {
+        let mut names = vec!["inline"];
+        names.extend(EARLY_STEPS);
+        names.extend(self.passes.names());
+        names.extend(LATE_STEPS);
+        names
+    }
+
+    /// what the passes did 2 particular fns, 4 --verbose
+    pub fn remarks(&self) -> Vec<String> {
+        self.passes.remarks()
+    }
+
+    /// the 1st snapshot -Z dump-mir couldnt write
+    pub fn dump_error(&self) -> Option<&str> {
+        self.dump.as_ref().and_then(MirDump::error)
+    }
+
+    /// -C inline-threshold, the most instructions an inlined callee may have. 0 turns it off
+    pub fn with_inline_threshold(mut self, threshold: usize) -> Self {
+        self.inliner = self.inliner.with_threshold(threshold);
+        self
+    }
+
+    /// -C profile-use, call counts that weigh the inliner's threshold at each call site
+    pub fn with_profile(mut self, profile: Arc<CallProfile>) -> Self {
+        self.inliner = self.inliner.with_profile(profile);
+        self
+    }
+
+    /// inlines across `functions` then optimizes each
+    pub fn optimize_all(&mut self, functions: &mut [MirFunction]) {
+        match &mut self.dump {
+            Some(dump) => dump.step_all("inline", functions, |functions| self.inliner.run(functions)),
+            None => self.inliner.run(functions),
+        };
+        for func in functions {
+            self.optimize(func);
         }
-        
-        while let Some(local) = worklist.pop_front() {
-            // find all definitions of this lcl
-            if let Some(def_list) = defs.get(&local) {
-                for (def_bb_id, def_inst_idx) in def_list {
-                    // get the instruction that defines this local from our strd info
-                    if let Some(def_inst) = inst_map.get(&(*def_bb_id, *def_inst_idx)) {
-                        // mark all oprnds used in this definition as live
-                        self.collect_uses(def_inst, |used_local| {
-                            if !live_locals.contains(&used_local) {
-                                live_locals.insert(used_local);
-                                worklist.push_back(used_local);
-                            }
-                        });
-                    }
-                }
-            }
+    }
+
+    pub fn optimize(&mut self, func: &mut MirFunction) {
+        // optmzation order: inst combine -> copy prop -> passes (const prop, stack promote, load-store, dce) -> dead local -> local renumber -> phi opt -> block simplify
+        let mut dump = self.dump.take();
+        let early: [fn(&mut Self, &mut MirFunction); 2] = [Self::instruction_combining, Self::copy_propagation];
+        for (name, step) in EARLY_STEPS.into_iter().zip(early) {
+            self.step(&mut dump, name, func, step);
         }
-        
-        // track which locals r actually read (not just written)
-        let mut read_locals: HashSet<Local> = HashSet::new();
-        for (_bb_id, _inst_idx, inst) in &instruction_info {
-            match inst {
-                Instruction::Ret { value } => {
-                    if let Some(Operand::Local(l)) = value {
-                        read_locals.insert(*l);
-                    }
-                }
-                Instruction::Br { condition, .. } => {
-                    if let Operand::Local(l) = condition {
-                        read_locals.insert(*l);
-                    }
-                }
-                Instruction::Load { source, .. } => {
-                    if let Operand::Local(l) = source {
-                        read_locals.insert(*l);
-                    }
-                }
-                Instruction::Call { func, args, .. } => {
-                    if let Operand::Local(l) = func {
-                        read_locals.insert(*l);
-                    }
-                    for arg in args {
-                        if let Operand::Local(l) = arg {
-                            read_locals.insert(*l);
-                        }
-                    }
-                }
-                Instruction::Phi { incoming, .. } => {
-                    for (op, _) in incoming {
-                        if let Operand::Local(l) = op {
-                            read_locals.insert(*l);
-                        }
-                    }
-                }
-                // chk if any arithmetic/comparison ops use locals
-                Instruction::Add { left, right, .. }
-                | Instruction::Sub { left, right, .. }
-                | Instruction::Mul { left, right, .. }
-                | Instruction::Div { left, right, .. }
-                | Instruction::Mod { left, right, .. }
-                | Instruction::Eq { left, right, .. }
-                | Instruction::Ne { left, right, .. }
-                | Instruction::Lt { left, right, .. }
-                | Instruction::Le { left, right, .. }
-                | Instruction::Gt { left, right, .. }
-                | Instruction::Ge { left, right, .. }
-                | Instruction::And { left, right, .. }
-                | Instruction::Or { left, right, .. } => {
-                    if let Operand::Local(l) = left {
-                        read_locals.insert(*l);
-                    }
-                    if let Operand::Local(l) = right {
-                        read_locals.insert(*l);
-                    }
-                }
-                Instruction::Not { operand, .. } => {
-                    if let Operand::Local(l) = operand {
-                        read_locals.insert(*l);
-                    }
-                }
-                Instruction::Gep { base, index, .. } => {
-                    if let Operand::Local(l) = base {
-                        read_locals.insert(*l);
-                    }
-                    if let Operand::Local(l) = index {
-                        read_locals.insert(*l);
-                    }
-                }
-                    Instruction::Store { dest: _dest, source, .. } => {
-                        // store reads source
-                        if let Operand::Local(l) = source {
-                            read_locals.insert(*l);
-                        }
-                        // store writes dest - chk if dest is ever read
-                    }
-                Instruction::Copy { source, .. } => {
-                    if let Operand::Local(l) = source {
-                        read_locals.insert(*l);
-                    }
-                }
-                _ => {}
-            }
+        self.passes.run_dumped(func, dump.as_mut());
+        let late: [fn(&mut Self, &mut MirFunction); 4] =
+            [Self::dead_local_elimination, Self::local_renumbering, Self::phi_optimization, Self::block_simplification];
+        for (name, step) in LATE_STEPS.into_iter().zip(late) {
+            self.step(&mut dump, name, func, step);
         }
+        self.dump = dump;
+    }
 
-        // remove instructions whose dest is not live
-        // also remove stores 2 locals that r never read
-        for (_bb_id, bb) in func.basic_blocks.iter_mut().enumerate() {
-            bb.instructions.retain(|inst| {
-                match inst {
-                    // always keep control flow instructions
-                    Instruction::Ret { .. } | Instruction::Br { .. } | Instruction::Jump { .. } => true,
-                    // always keep phi nodes
-                    Instruction::Phi { .. } => true,
-                    // chk store: remove if dest local is never read
-                    Instruction::Store { dest, .. } => {
-                        if let Operand::Local(dest_local) = dest {
-                            read_locals.contains(dest_local)
-                        } else {
-                            true // keep non-local stores
-                        }
-                    }
-                    // always keep call (side effects)
-                    Instruction::Call { .. } => true,
-                    // 4 other instructions chk if dest is live
-                    _ => {
-                        if let Some(dest_local) = self.get_dest_local(inst) {
-                            live_locals.contains(&dest_local)
-                        } else {
-                            // instrctns w/o dest
-                            true
-                        }
-                    }
-                }
-            });
+    fn step(&mut self, dump: &mut Option<MirDump>, name: &str, func: &mut MirFunction, step: fn(&mut Self, &mut MirFunction)) {
+        match dump {
+            Some(dump) => dump.step(name, func, |func| step(self, func)),
+            None => step(self, func),
         }
     }
 
+    /// what the inliner + the ported passes did over evry fn optimized so far
+    pub fn pass_stats(&self) -> Vec<PassStats> {
+        std::iter::once(self.inliner.stats()).chain(self.passes.stats()).collect()
+    }
+
     fn copy_propagation(&mut self, func: &mut MirFunction) {
         // build def use chains: track whr each lcl is defined and used
         let mut defs: HashMap<Local, Vec<(usize, usize)>> = HashMap::new(); // local > []
@@ -297,12 +136,17 @@ impl MirOptimizer {
         for (bb_id, inst_idx, dest, source) in &copy_instructions {
             // chk if source is a constant or single use
             let can_propagate = match source {
-                Operand::Constant(_) => true, // cnstnts can always be propagated
+                // aggregates + strings r emitted as static data and addressed thru the local
+                Operand::Constant(Constant::Aggregate(_) | Constant::String(_)) => false,
+                // constants r const-prop's, it checks the local isnt assigned again
+                Operand::Constant(_) => false,
                 Operand::Local(src_local) => {
                     // chk if source local has only one dfntn and one use
                     let src_defs = defs.get(src_local).map(|v| v.len()).unwrap_or(0);
                     let src_uses = uses.get(src_local).map(|v| v.len()).unwrap_or(0);
-                    src_defs == 1 && src_uses <= 1 // single def at most one use
+                    // dest assigned again elsewhere (an inlined callee's returns) keeps its copies
+                    let dest_defs = defs.get(dest).map(|v| v.len()).unwrap_or(0);
+                    src_defs == 1 && src_uses <= 1 && dest_defs == 1 // single def at most one use
                 }
                 _ => false,
             };
@@ -338,115 +182,17 @@ impl MirOptimizer {
     }
 
     fn get_dest_local(&self, inst: &Instruction) -> Option<Local> {
-        match inst {
-            Instruction::Add { dest, .. }
-            | Instruction::Sub { dest, .. }
-            | Instruction::Mul { dest, .. }
-            | Instruction::Div { dest, .. }
-            | Instruction::Mod { dest, .. }
-            | Instruction::Eq { dest, .. }
-            | Instruction::Ne { dest, .. }
-            | Instruction::Lt { dest, .. }
-            | Instruction::Le { dest, .. }
-            | Instruction::Gt { dest, .. }
-            | Instruction::Ge { dest, .. }
-            | Instruction::And { dest, .. }
-            | Instruction::Or { dest, .. }
-            | Instruction::Not { dest, .. }
-            | Instruction::Load { dest, .. }
-            | Instruction::Alloca { dest, .. }
-            | Instruction::Gep { dest, .. }
-            | Instruction::Phi { dest, .. }
-            | Instruction::Copy { dest, .. } => Some(*dest),
-            Instruction::Call { dest, .. } => *dest,
-            _ => None,
-        }
+        inst.dest()
     }
 
     fn collect_uses<F>(&self, inst: &Instruction, mut f: F)
     where
         F: FnMut(Local),
     {
-        match inst {
-            Instruction::Add { left, right, .. }
-            | Instruction::Sub { left, right, .. }
-            | Instruction::Mul { left, right, .. }
-            | Instruction::Div { left, right, .. }
-            | Instruction::Mod { left, right, .. }
-            | Instruction::Eq { left, right, .. }
-            | Instruction::Ne { left, right, .. }
-            | Instruction::Lt { left, right, .. }
-            | Instruction::Le { left, right, .. }
-            | Instruction::Gt { left, right, .. }
-            | Instruction::Ge { left, right, .. }
-            | Instruction::And { left, right, .. }
-            | Instruction::Or { left, right, .. } => {
-                if let Operand::Local(l) = left {
-                    f(*l);
-                }
-                if let Operand::Local(l) = right {
-                    f(*l);
-                }
-            }
-            Instruction::Not { operand, .. } => {
-                if let Operand::Local(l) = operand {
-                    f(*l);
-                }
-            }
-            Instruction::Load { source, .. } => {
-                if let Operand::Local(l) = source {
-                    f(*l);
-                }
-            }
-            Instruction::Store { dest, source, .. } => {
-                if let Operand::Local(l) = dest {
-                    f(*l);
-                }
-                if let Operand::Local(l) = source {
-                    f(*l);
-                }
-            }
-            Instruction::Gep { base, index, .. } => {
-                if let Operand::Local(l) = base {
-                    f(*l);
-                }
-                if let Operand::Local(l) = index {
-                    f(*l);
-                }
-            }
-            Instruction::Call { func, args, .. } => {
-                if let Operand::Local(l) = func {
-                    f(*l);
-                }
-                for arg in args {
-                    if let Operand::Local(l) = arg {
-                        f(*l);
-                    }
-                }
-            }
-            Instruction::Ret { value } => {
-                if let Some(Operand::Local(l)) = value {
-                    f(*l);
-                }
-            }
-            Instruction::Br { condition, .. } => {
-                if let Operand::Local(l) = condition {
-                    f(*l);
-                }
-            }
-            Instruction::Phi { incoming, .. } => {
-                for (op, _) in incoming {
-                    if let Operand::Local(l) = op {
-                        f(*l);
-                    }
-                }
-            }
-            Instruction::Copy { source, .. } => {
-                if let Operand::Local(l) = source {
-                    f(*l);
-                }
+        for operand in inst.operands() {
+            if let Operand::Local(l) = operand {
+                f(*l);
             }
-            _ => {}
         }
     }
 
@@ -464,7 +210,9 @@ impl MirOptimizer {
             | Instruction::Gt { left, right, .. }
             | Instruction::Ge { left, right, .. }
             | Instruction::And { left, right, .. }
-            | Instruction::Or { left, right, .. } => {
+            | Instruction::Or { left, right, .. }
+            | Instruction::StrConcat { left, right, .. }
+            | Instruction::StrEq { left, right, .. } => {
                 if *left == old {
                     *left = new.clone();
                 }
@@ -472,17 +220,21 @@ impl MirOptimizer {
                     *right = new;
                 }
             }
-            Instruction::Not { operand, .. } => {
+            Instruction::Not { operand, .. }
+            | Instruction::StrLen { source: operand, .. }
+            | Instruction::MakeDyn { data: operand, .. }
+            | Instruction::EnumTag { source: operand, .. }
+            | Instruction::VariantField { source: operand, .. } => {
                 if *operand == old {
                     *operand = new;
                 }
             }
-            Instruction::Load { source, .. } => {
+            Instruction::Load { source, .. } | Instruction::VolatileLoad { source, .. } => {
                 if *source == old {
                     *source = new;
                 }
             }
-            Instruction::Store { dest, source, .. } => {
+            Instruction::Store { dest, source, .. } | Instruction::VolatileStore { dest, source, .. } => {
                 if *dest == old {
                     *dest = new.clone();
                 }
@@ -490,7 +242,7 @@ impl MirOptimizer {
                     *source = new;
                 }
             }
-            Instruction::Gep { base, index, .. } => {
+            Instruction::Gep { base, index, .. } | Instruction::PtrOffset { base, offset: index, .. } => {
                 if *base == old {
                     *base = new.clone();
                 }
@@ -498,7 +250,15 @@ impl MirOptimizer {
                     *index = new;
                 }
             }
-            Instruction::Call { func, args, .. } => {
+            Instruction::Memset { dest, value, .. } => {
+                if *dest == old {
+                    *dest = new.clone();
+                }
+                if *value == old {
+                    *value = new;
+                }
+            }
+            Instruction::Call { func, args, .. } | Instruction::DynCall { object: func, args, .. } => {
                 if *func == old {
                     *func = new.clone();
                 }
@@ -508,6 +268,13 @@ impl MirOptimizer {
                     }
                 }
             }
+            Instruction::MakeVariant { fields, .. } => {
+                for field in fields {
+                    if *field == old {
+                        *field = new.clone();
+                    }
+                }
+            }
             Instruction::Ret { value } => {
                 if let Some(v) = value {
                     if *v == old {
@@ -515,7 +282,7 @@ impl MirOptimizer {
                     }
                 }
             }
-            Instruction::Br { condition, .. } => {
+            Instruction::Br { condition, .. } | Instruction::Switch { value: condition, .. } => {
                 if *condition == old {
                     *condition = new;
                 }
@@ -531,15 +298,6 @@ impl MirOptimizer {
         }
     }
 
-    // constant folding at mir level
-    fn constant_fold(&mut self, func: &mut MirFunction) {
-        for bb in &mut func.basic_blocks {
-            for inst in &mut bb.instructions {
-                self.fold_instruction(inst);
-            }
-        }
-    }
-
     // instruction combining: simplify ops w/ identity/zero values
     fn instruction_combining(&mut self, func: &mut MirFunction) {
         for bb in &mut func.basic_blocks {
@@ -682,352 +440,6 @@ impl MirOptimizer {
         }
     }
 
-    fn fold_instruction(&mut self, inst: &mut Instruction) {
-        match inst {
-            Instruction::Add { dest, left, right, type_ } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Int(*l + *r)),
-                            type_: type_.clone(),
-                        };
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Float(*l + *r)),
-                            type_: type_.clone(),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Sub { dest, left, right, type_ } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Int(*l - *r)),
-                            type_: type_.clone(),
-                        };
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Float(*l - *r)),
-                            type_: type_.clone(),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Mul { dest, left, right, type_ } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Int(*l * *r)),
-                            type_: type_.clone(),
-                        };
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Float(*l * *r)),
-                            type_: type_.clone(),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Div { dest, left, right, type_ } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        if *r != 0 {
-                            *inst = Instruction::Copy {
-                                dest: *dest,
-                                source: Operand::Constant(Constant::Int(*l / *r)),
-                                type_: type_.clone(),
-                            };
-                        }
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        if *r != 0.0 {
-                            *inst = Instruction::Copy {
-                                dest: *dest,
-                                source: Operand::Constant(Constant::Float(*l / *r)),
-                                type_: type_.clone(),
-                            };
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Mod { dest, left, right, type_ } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        if *r != 0 {
-                            *inst = Instruction::Copy {
-                                dest: *dest,
-                                source: Operand::Constant(Constant::Int(*l % *r)),
-                                type_: type_.clone(),
-                            };
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Eq { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(l), Operand::Constant(r)) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(if l == r { Constant::Bool(true) } else { Constant::Bool(false) }),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Ne { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(l), Operand::Constant(r)) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(if l != r { Constant::Bool(true) } else { Constant::Bool(false) }),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Lt { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l < *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l < *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Le { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l <= *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l <= *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Gt { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l > *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l > *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Ge { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Int(l)), Operand::Constant(Constant::Int(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l >= *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    (Operand::Constant(Constant::Float(l)), Operand::Constant(Constant::Float(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l >= *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::And { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Bool(l)), Operand::Constant(Constant::Bool(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l && *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Or { dest, left, right } => {
-                match (left, right) {
-                    (Operand::Constant(Constant::Bool(l)), Operand::Constant(Constant::Bool(r))) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(*l || *r)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            Instruction::Not { dest, operand } => {
-                match operand {
-                    Operand::Constant(Constant::Bool(b)) => {
-                        *inst = Instruction::Copy {
-                            dest: *dest,
-                            source: Operand::Constant(Constant::Bool(!*b)),
-                            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
-                        };
-                    }
-                    _ => {}
-                }
-            }
-            _ => {}
-        }
-    }
-
-    // store-load elimination: remove redundant load after store
-    fn store_load_elimination(&mut self, func: &mut MirFunction) {
-        use std::collections::HashMap;
-        
-        // track last store 2 each local
-        let mut last_store: HashMap<Local, (usize, usize, Operand)> = HashMap::new();
-        
-        for (bb_id, bb) in func.basic_blocks.iter_mut().enumerate() {
-            for (inst_idx, inst) in bb.instructions.iter_mut().enumerate() {
-                match inst {
-                    Instruction::Store { dest, source, .. } => {
-                        if let Operand::Local(dest_local) = dest {
-                            // track this store
-                            last_store.insert(*dest_local, (bb_id, inst_idx, source.clone()));
-                        }
-                    }
-                    Instruction::Load { dest, source, type_ } => {
-                        if let Operand::Local(src_local) = source {
-                            // chk if we recently stored 2 this local
-                            if let Some((store_bb, store_idx, stored_value)) = last_store.get(src_local) {
-                                // if store is in same block b4 this load we can replace load w/ stored value
-                                if *store_bb == bb_id && *store_idx < inst_idx {
-                                    // replace load w/ copy of stored value
-                                    *inst = Instruction::Copy {
-                                        dest: *dest,
-                                        source: stored_value.clone(),
-                                        type_: type_.clone(),
-                                    };
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
-    }
-
-    // store optimization: remove redundant stores
-    fn store_optimization(&mut self, func: &mut MirFunction) {
-        use std::collections::HashMap;
-        
-        for bb in &mut func.basic_blocks {
-            // track stores 2 each local in this block
-            let mut local_stores: HashMap<Local, Vec<usize>> = HashMap::new();
-            
-            // find all stores
-            for (inst_idx, inst) in bb.instructions.iter().enumerate() {
-                if let Instruction::Store { dest, .. } = inst {
-                    if let Operand::Local(dest_local) = dest {
-                        local_stores.entry(*dest_local).or_insert_with(Vec::new).push(inst_idx);
-                    }
-                }
-            }
-            
-            // 4 each local w/ multiple stores remove all but the last
-            for (local, store_indices) in local_stores {
-                if store_indices.len() > 1 {
-                    // keep only the last store remove others
-                    let _last_store_idx = store_indices[store_indices.len() - 1];
-                    for &store_idx in &store_indices[..store_indices.len() - 1] {
-                        // mark for removal (we'll do it in a 2nd pass)
-                        if let Some(inst) = bb.instructions.get_mut(store_idx) {
-                            if let Instruction::Store { dest, .. } = inst {
-                                if let Operand::Local(dest_local) = dest {
-                                    if *dest_local == local {
-                                        // replace w/ nop (we'll remove nops later)
-                                        // actually just remove it by marking
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            
-            // remove redundant stores (stores that r immediately overwritten)
-            let mut to_remove = Vec::new();
-            for (inst_idx, inst) in bb.instructions.iter().enumerate() {
-                if let Instruction::Store { dest, .. } = inst {
-                    if let Operand::Local(dest_local) = dest {
-                        // chk if there's another store 2 this local later
-                        for (_later_idx, later_inst) in bb.instructions.iter().enumerate().skip(inst_idx + 1) {
-                            if let Instruction::Store { dest: later_dest, .. } = later_inst {
-                                if let Operand::Local(later_dest_local) = later_dest {
-                                    if later_dest_local == dest_local {
-                                        // this store is overwritten remove it
-                                        to_remove.push(inst_idx);
-                                        break;
-                                    }
-                                }
-                            }
-                            // if we hit a terminator or side effect stop
-                            if matches!(later_inst, Instruction::Ret { .. } | Instruction::Br { .. } | Instruction::Jump { .. } | Instruction::Call { .. }) {
-                                break;
-                            }
-                        }
-                    }
-                }
-            }
-            
-            // remove marked stores (in reverse order 2 maintain indices)
-            to_remove.sort();
-            to_remove.reverse();
-            for idx in to_remove {
-                bb.instructions.remove(idx);
-            }
-        }
-    }
-
     // local renumbering: compact local ids after dead local elimination
     fn local_renumbering(&mut self, func: &mut MirFunction) {
         use std::collections::HashMap;
@@ -1112,7 +524,9 @@ impl MirOptimizer {
             | Instruction::Gt { left, right, dest, .. }
             | Instruction::Ge { left, right, dest, .. }
             | Instruction::And { left, right, dest, .. }
-            | Instruction::Or { left, right, dest, .. } => {
+            | Instruction::Or { left, right, dest, .. }
+            | Instruction::StrConcat { left, right, dest }
+            | Instruction::StrEq { left, right, dest } => {
                 if let Operand::Local(l) = left {
                     if let Some(new_id) = old_to_new.get(&l.id) {
                         *left = Operand::Local(Local::new(*new_id));
@@ -1127,7 +541,11 @@ impl MirOptimizer {
                     *dest = Local::new(*new_id);
                 }
             }
-            Instruction::Not { operand, dest, .. } => {
+            Instruction::Not { operand, dest, .. }
+            | Instruction::StrLen { source: operand, dest }
+            | Instruction::MakeDyn { data: operand, dest, .. }
+            | Instruction::EnumTag { source: operand, dest }
+            | Instruction::VariantField { source: operand, dest, .. } => {
                 if let Operand::Local(l) = operand {
                     if let Some(new_id) = old_to_new.get(&l.id) {
                         *operand = Operand::Local(Local::new(*new_id));
@@ -1137,7 +555,7 @@ impl MirOptimizer {
                     *dest = Local::new(*new_id);
                 }
             }
-            Instruction::Load { dest, source, .. } => {
+            Instruction::Load { dest, source, .. } | Instruction::VolatileLoad { dest, source, .. } => {
                 if let Operand::Local(l) = source {
                     if let Some(new_id) = old_to_new.get(&l.id) {
                         *source = Operand::Local(Local::new(*new_id));
@@ -1147,7 +565,7 @@ impl MirOptimizer {
                     *dest = Local::new(*new_id);
                 }
             }
-            Instruction::Store { dest, source, .. } => {
+            Instruction::Store { dest, source, .. } | Instruction::VolatileStore { dest, source, .. } => {
                 if let Operand::Local(l) = dest {
                     if let Some(new_id) = old_to_new.get(&l.id) {
                         *dest = Operand::Local(Local::new(*new_id));
@@ -1159,7 +577,7 @@ impl MirOptimizer {
                     }
                 }
             }
-            Instruction::Gep { dest, base, index, .. } => {
+            Instruction::Gep { dest, base, index, .. } | Instruction::PtrOffset { dest, base, offset: index, .. } => {
                 if let Operand::Local(l) = base {
                     if let Some(new_id) = old_to_new.get(&l.id) {
                         *base = Operand::Local(Local::new(*new_id));
@@ -1174,7 +592,7 @@ impl MirOptimizer {
                     *dest = Local::new(*new_id);
                 }
             }
-            Instruction::Call { dest, func, args, .. } => {
+            Instruction::Call { dest, func, args, .. } | Instruction::DynCall { dest, object: func, args, .. } => {
                 if let Some(d) = dest {
                     if let Some(new_id) = old_to_new.get(&d.id) {
                         *dest = Some(Local::new(*new_id));
@@ -1193,6 +611,18 @@ impl MirOptimizer {
                     }
                 }
             }
+            Instruction::MakeVariant { dest, fields, .. } => {
+                if let Some(new_id) = old_to_new.get(&dest.id) {
+                    *dest = Local::new(*new_id);
+                }
+                for field in fields {
+                    if let Operand::Local(l) = field {
+                        if let Some(new_id) = old_to_new.get(&l.id) {
+                            *field = Operand::Local(Local::new(*new_id));
+                        }
+                    }
+                }
+            }
             Instruction::Ret { value } => {
                 if let Some(Operand::Local(l)) = value {
                     if let Some(new_id) = old_to_new.get(&l.id) {
@@ -1200,7 +630,7 @@ impl MirOptimizer {
                     }
                 }
             }
-            Instruction::Br { condition, .. } => {
+            Instruction::Br { condition, .. } | Instruction::Switch { value: condition, .. } => {
                 if let Operand::Local(l) = condition {
                     if let Some(new_id) = old_to_new.get(&l.id) {
                         *condition = Operand::Local(Local::new(*new_id));
@@ -1234,6 +664,18 @@ impl MirOptimizer {
                     *dest = Local::new(*new_id);
                 }
             }
+            Instruction::Memset { dest, value, .. } => {
+                if let Operand::Local(l) = dest {
+                    if let Some(new_id) = old_to_new.get(&l.id) {
+                        *dest = Operand::Local(Local::new(*new_id));
+                    }
+                }
+                if let Operand::Local(l) = value {
+                    if let Some(new_id) = old_to_new.get(&l.id) {
+                        *value = Operand::Local(Local::new(*new_id));
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -1258,7 +700,7 @@ impl MirOptimizer {
                             }
                         }
                     }
-                    Instruction::Br { condition, .. } => {
+                    Instruction::Br { condition, .. } | Instruction::Switch { value: condition, .. } => {
                         if let Operand::Local(l) = condition {
                             if !used_locals.contains(l) {
                                 used_locals.insert(*l);
@@ -1266,7 +708,7 @@ impl MirOptimizer {
                             }
                         }
                     }
-                    Instruction::Store { dest, source, .. } => {
+                    Instruction::Store { dest, source, .. } | Instruction::VolatileStore { dest, source, .. } => {
                         if let Operand::Local(l) = dest {
                             if !used_locals.contains(l) {
                                 used_locals.insert(*l);
@@ -1280,7 +722,21 @@ impl MirOptimizer {
                             }
                         }
                     }
-                    Instruction::Call { func: func_op, args, .. } => {
+                    Instruction::Memset { dest, value, .. } => {
+                        if let Operand::Local(l) = dest {
+                            if !used_locals.contains(l) {
+                                used_locals.insert(*l);
+                                worklist.push_back(*l);
+                            }
+                        }
+                        if let Operand::Local(l) = value {
+                            if !used_locals.contains(l) {
+                                used_locals.insert(*l);
+                                worklist.push_back(*l);
+                            }
+                        }
+                    }
+                    Instruction::Call { func: func_op, args, .. } | Instruction::DynCall { object: func_op, args, .. } => {
                         if let Operand::Local(l) = func_op {
                             if !used_locals.contains(l) {
                                 used_locals.insert(*l);
@@ -1418,6 +874,13 @@ impl MirOptimizer {
                                 *else_bb = *new_else;
                             }
                         }
+                        Instruction::Switch { arms, default, .. } => {
+                            for target in arms.iter_mut().map(|(_, bb)| bb).chain(std::iter::once(default)) {
+                                if let Some(new_target) = old_to_new.get(target) {
+                                    *target = *new_target;
+                                }
+                            }
+                        }
                         Instruction::Phi { incoming, .. } => {
                             for (_, bb_id) in incoming {
                                 if let Some(new_bb_id) = old_to_new.get(bb_id) {
@@ -1591,6 +1054,13 @@ impl MirOptimizer {
                                     *else_bb = *new_else;
                                 }
                             }
+                            Instruction::Switch { arms, default, .. } => {
+                                for target in arms.iter_mut().map(|(_, bb)| bb).chain(std::iter::once(default)) {
+                                    if let Some(new_target) = old_to_new.get(target) {
+                                        *target = *new_target;
+                                    }
+                                }
+                            }
                             Instruction::Phi { incoming, .. } => {
                                 for (_, bb_id) in incoming {
                                     if let Some(new_bb_id) = old_to_new.get(bb_id) {
@@ -0,0 +1,15 @@
+// nested source recurses thru the parser, the semantic passes + lowering as deep as it nests.
+// machine-generated code nests deeper than any thread's stack, so the recursive entry points
+// run under `grow`, which continues on a fresh heap-allocated segment when the stack runs low
+
+/// below this much stack left a new segment is started, it has 2 hold the frames between 2
+/// calls 2 `grow` in a debug build
+pub const RED_ZONE: usize = 256 * 1024;
+
+/// size of each new segment
+pub const SEGMENT: usize = 4 * 1024 * 1024;
+
+/// runs `f`, on a new stack segment if this one is nearly used up
+pub fn grow<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(RED_ZONE, SEGMENT, f)
+}
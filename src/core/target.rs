@@ -0,0 +1,189 @@
+/// byte order of the compilation target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Endian::Little => "little",
+            Endian::Big => "big",
+        }
+    }
+}
+
+/// x86 isa extensions usable in `@target_feature`, oldest first so later
+/// entries win runtime dispatch. the number is the bit in libgcc/compiler-rt's
+/// `__cpu_model.__cpu_features[0]` that `__builtin_cpu_supports` tests
+pub const X86_CPU_FEATURES: &[(&str, u32)] = &[
+    ("cmov", 0),
+    ("mmx", 1),
+    ("sse", 3),
+    ("sse2", 4),
+    ("sse3", 5),
+    ("ssse3", 6),
+    ("sse4a", 11),
+    ("sse4.1", 7),
+    ("sse4.2", 8),
+    ("popcnt", 2),
+    ("aes", 18),
+    ("pclmul", 19),
+    ("avx", 9),
+    ("fma4", 12),
+    ("xop", 13),
+    ("fma", 14),
+    ("bmi", 16),
+    ("bmi2", 17),
+    ("avx2", 10),
+    ("avx512f", 15),
+    ("avx512cd", 23),
+    ("avx512vl", 20),
+    ("avx512bw", 21),
+    ("avx512dq", 22),
+    ("avx512ifma", 27),
+    ("avx512vbmi", 26),
+    ("avx512vbmi2", 31),
+    ("avx512vpopcntdq", 30),
+];
+
+/// (dispatch priority, cpu feature bit) of an x86 feature name
+pub fn x86_cpu_feature(name: &str) -> Option<(usize, u32)> {
+    X86_CPU_FEATURES
+        .iter()
+        .position(|(feature, _)| *feature == name)
+        .map(|idx| (idx, X86_CPU_FEATURES[idx].1))
+}
+
+/// platform info drvd from a target triple
+/// exposed 2 programs as the comptime `target.*` constants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetInfo {
+    pub triple: String,
+    pub arch: String,
+    pub os: String,
+    pub endian: Endian,
+    pub pointer_width: u32,
+}
+
+impl TargetInfo {
+    /// parse arch-vendor-os[-env] triple
+    /// unknown arch falls back 2 little endian 64 bit
+    pub fn from_triple(triple: &str) -> Self {
+        let parts: Vec<&str> = triple.split('-').collect();
+        let arch = parts.first().copied().unwrap_or("unknown").to_string();
+        // os is the 3rd component (2nd when vendor is omitted eg wasm32-wasi
+        // or thumbv7em-none-eabihf)
+        let os_part = match parts.len() {
+            0 | 1 => "unknown",
+            2 => parts[1],
+            _ if parts[1] == "none" => "none",
+            _ => parts[2],
+        };
+        let os = match os_part {
+            os if os.starts_with("darwin") || os.starts_with("macos") => "macos",
+            os if os.starts_with("windows") => "windows",
+            os if os.starts_with("linux") => "linux",
+            os if os.starts_with("freebsd") => "freebsd",
+            os if os.starts_with("wasi") => "wasi",
+            "none" | "elf" | "eabi" => "none",
+            other => other,
+        }
+        .to_string();
+
+        let (endian, pointer_width) = Self::arch_layout(&arch);
+        Self {
+            triple: triple.to_string(),
+            arch,
+            os,
+            endian,
+            pointer_width,
+        }
+    }
+
+    pub fn is_x86(&self) -> bool {
+        matches!(self.arch.as_str(), "x86_64" | "i386" | "i586" | "i686" | "x86")
+    }
+
+    /// wasm32/wasm64 incl wasi
+    pub fn is_wasm(&self) -> bool {
+        self.arch.starts_with("wasm")
+    }
+
+    /// target the compiler itself runs on
+    pub fn host() -> Self {
+        let os = if cfg!(target_os = "macos") {
+            "apple-darwin"
+        } else if cfg!(target_os = "windows") {
+            "pc-windows-msvc"
+        } else {
+            "unknown-linux-gnu"
+        };
+        Self::from_triple(&format!("{}-{}", std::env::consts::ARCH, os))
+    }
+
+    fn arch_layout(arch: &str) -> (Endian, u32) {
+        match arch {
+            "x86_64" | "aarch64" | "arm64" | "riscv64" | "riscv64gc" | "wasm64" => (Endian::Little, 64),
+            "powerpc64le" => (Endian::Little, 64),
+            "powerpc64" | "s390x" | "sparc64" | "aarch64_be" => (Endian::Big, 64),
+            "i386" | "i586" | "i686" | "x86" | "wasm32" | "riscv32" | "mipsel" => (Endian::Little, 32),
+            "mips" | "powerpc" | "sparc" | "armeb" | "thumbeb" => (Endian::Big, 32),
+            a if a.starts_with("arm") || a.starts_with("thumb") => (Endian::Little, 32),
+            "avr" | "msp430" => (Endian::Little, 16),
+            _ => (Endian::Little, 64),
+        }
+    }
+}
+
+impl Default for TargetInfo {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+/// calling convention of a foreign fn, frm `foreign "stdcall" name` or `with abi = "..."`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CallConv {
+    #[default]
+    C,
+    Stdcall,
+    Fastcall,
+    Thiscall,
+    Vectorcall,
+    Win64,
+    Sysv64,
+    /// the platform's system api convention, stdcall on 32 bit windows + C evrywhere else
+    System,
+}
+
+impl CallConv {
+    pub fn from_abi(abi: &str) -> Option<Self> {
+        match abi {
+            "C" | "c" | "cdecl" => Some(Self::C),
+            "stdcall" => Some(Self::Stdcall),
+            "fastcall" => Some(Self::Fastcall),
+            "thiscall" => Some(Self::Thiscall),
+            "vectorcall" => Some(Self::Vectorcall),
+            "win64" => Some(Self::Win64),
+            "sysv64" => Some(Self::Sysv64),
+            "system" => Some(Self::System),
+            _ => None,
+        }
+    }
+
+    /// the convention actually used on `target`, the 32 bit x86 ones collapse 2 C elsewhere
+    /// like msvc + gcc do so the same foreign block builds 4 evry target
+    pub fn resolve(self, target: &TargetInfo) -> Self {
+        let x86_32 = target.is_x86() && target.pointer_width == 32;
+        match self {
+            Self::System if x86_32 && target.os == "windows" => Self::Stdcall,
+            Self::System => Self::C,
+            Self::Stdcall | Self::Fastcall | Self::Thiscall if !x86_32 => Self::C,
+            Self::Vectorcall | Self::Win64 | Self::Sysv64 if !target.is_x86() => Self::C,
+            Self::Win64 | Self::Sysv64 if x86_32 => Self::C,
+            conv => conv,
+        }
+    }
+}
@@ -8,6 +8,48 @@ pub struct StructType {
     pub align: Option<usize>, // alignment rqrmnt
 }
 
+impl StructType {
+    /// anonymous `{ x: int, y: long }` - fields sorted by name + the name spells them out,
+    /// so 2 records w/ the same fields r the same struct no matter the order they were written in
+    pub fn record(mut fields: Vec<(String, Type)>) -> Self {
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        let spelled: Vec<String> = fields.iter().map(|(name, ty)| format!("{}: {}", name, spelling(ty))).collect();
+        let types: Vec<Type> = fields.iter().map(|(_, ty)| ty.clone()).collect();
+        StructType {
+            name: format!("{{ {} }}", spelled.join(", ")),
+            fields: fields.into_iter().map(|(name, type_)| Field { name, type_, offset: None }).collect(),
+            size: Some(fields_size(&types)),
+            align: Some(types.iter().map(|t| t.align().max(1)).max().unwrap_or(1)),
+        }
+    }
+
+    pub fn is_record(&self) -> bool {
+        self.name.starts_with('{')
+    }
+
+    /// `(int, float)` - fields `0`, `1`, .. in order, named like records by what they hold
+    pub fn tuple(elements: Vec<Type>) -> Self {
+        let spelled: Vec<String> = elements.iter().map(spelling).collect();
+        // (int,) 4 1 element, (int) reads like a parenthesized int
+        let trailing = if spelled.len() == 1 { "," } else { "" };
+        StructType {
+            name: format!("({}{})", spelled.join(", "), trailing),
+            size: Some(fields_size(&elements)),
+            align: Some(elements.iter().map(|t| t.align().max(1)).max().unwrap_or(1)),
+            fields: elements.into_iter().enumerate().map(|(i, type_)| Field { name: i.to_string(), type_, offset: None }).collect(),
+        }
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        self.name.starts_with('(')
+    }
+
+    /// records + tuples r typed by their fields, not by a decl
+    pub fn is_structural(&self) -> bool {
+        self.is_record() || self.is_tuple()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Field {
     pub name: String,
@@ -26,3 +68,86 @@ pub struct FunctionType {
     pub params: Vec<Type>,
     pub return_type: Box<Type>,
 }
+
+/// tagged union, `{ i32 tag, [n x i64] payload }` - n words fit the largest variant
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Variant {
+    pub name: String,
+    pub fields: Vec<Type>,
+}
+
+impl EnumType {
+    pub const OK: &'static str = "Ok";
+    pub const ERR: &'static str = "Err";
+
+    /// `value or error` - `Ok(value)` + `Err(error)`, named by what it holds like a record so
+    /// evry fn that can fail w/ the same types returns the same enum
+    pub fn fallible(value: Type, error: Type) -> Self {
+        EnumType {
+            name: format!("{} or {}", spelling(&value), spelling(&error)),
+            variants: vec![
+                Variant { name: Self::OK.to_string(), fields: vec![value] },
+                Variant { name: Self::ERR.to_string(), fields: vec![error] },
+            ],
+        }
+    }
+
+    /// the value + error types of a `value or error`, a declared enum's name never has a space
+    pub fn fallible_parts(&self) -> Option<(&Type, &Type)> {
+        let [ok, err] = self.variants.as_slice() else { return None };
+        match (ok.fields.as_slice(), err.fields.as_slice()) {
+            ([value], [error]) if self.name == format!("{} or {}", spelling(value), spelling(error)) => Some((value, error)),
+            _ => None,
+        }
+    }
+
+    /// tag of `variant`, its index in declaration order
+    pub fn tag(&self, variant: &str) -> Option<usize> {
+        self.variants.iter().position(|v| v.name == variant)
+    }
+
+    /// i64 words the payload needs, variant fields r laid out like struct fields
+    pub fn payload_words(&self) -> usize {
+        let largest = self.variants.iter().map(|v| fields_size(&v.fields)).max().unwrap_or(0);
+        largest.div_ceil(8)
+    }
+}
+
+fn fields_size(fields: &[Type]) -> usize {
+    let mut size = 0usize;
+    let mut max_align = 1usize;
+    for field in fields {
+        let align = field.align().max(1);
+        size = size.div_ceil(align) * align + layout_size(field);
+        max_align = max_align.max(align);
+    }
+    size.div_ceil(max_align) * max_align
+}
+
+/// structs whose layout wasnt computed yet r sized frm their fields
+pub fn layout_size(ty: &Type) -> usize {
+    match ty {
+        Type::Struct(s) if s.size.is_none() => fields_size(&s.fields.iter().map(|f| f.type_.clone()).collect::<Vec<_>>()),
+        _ => ty.size_in_bytes().unwrap_or(8),
+    }
+}
+
+/// source spelling of a type, 4 record names + textual MIR
+pub fn spelling(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(p) => format!("{:?}", p).to_lowercase(),
+        Type::String => "string".to_string(),
+        Type::Struct(s) => s.name.clone(),
+        Type::Enum(e) => e.name.clone(),
+        Type::Pointer(p) if p.nullable => format!("ref? {}", spelling(&p.pointee)),
+        Type::Pointer(p) => format!("ref {}", spelling(&p.pointee)),
+        Type::Array(a) => format!("{}[{}]", spelling(&a.element), a.size),
+        _ => format!("{:?}", ty),
+    }
+}
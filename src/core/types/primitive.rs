@@ -8,6 +8,9 @@ pub enum PrimitiveType {
     Float,  // double
     Bool,   // bool
     Char,   // chr32_t
+    UByte,  // uint8_t
+    UInt,   // uint32_t
+    ULong,  // uint64_t
 }
 
 impl PrimitiveType {
@@ -21,6 +24,9 @@ impl PrimitiveType {
             PrimitiveType::Float => 8,
             PrimitiveType::Bool => 1,
             PrimitiveType::Char => 4,
+            PrimitiveType::UByte => 1,
+            PrimitiveType::UInt => 4,
+            PrimitiveType::ULong => 8,
         }
     }
 
@@ -39,9 +45,17 @@ impl PrimitiveType {
                 | PrimitiveType::Long
                 | PrimitiveType::Size
                 | PrimitiveType::Char
+                | PrimitiveType::UByte
+                | PrimitiveType::UInt
+                | PrimitiveType::ULong
         )
     }
 
+    /// picks udiv/urem, unsigned compares and zero extension, byte + size count as unsigned
+    pub fn is_unsigned(&self) -> bool {
+        self.is_integer() && !self.is_signed()
+    }
+
     pub fn is_float(&self) -> bool {
         matches!(self, PrimitiveType::Float)
     }
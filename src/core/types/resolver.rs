@@ -1,8 +1,8 @@
 use crate::core::ast::types::Type as AstType;
-use crate::core::types::ty::Type;
+use crate::core::types::ty::{TraitObjectType, Type};
 use crate::core::types::primitive::PrimitiveType;
 use crate::core::types::pointer::PointerType;
-use crate::core::types::composite::{ArrayType, StructType, FunctionType};
+use crate::core::types::composite::{ArrayType, EnumType, StructType, FunctionType};
 use crate::core::types::generic::GenericType;
 use std::collections::HashSet;
 
@@ -21,6 +21,9 @@ pub fn resolve_ast_type_with_context(ast_type: &AstType, generic_params: &HashSe
             crate::core::ast::types::PrimitiveType::Float => PrimitiveType::Float,
             crate::core::ast::types::PrimitiveType::Bool => PrimitiveType::Bool,
             crate::core::ast::types::PrimitiveType::Char => PrimitiveType::Char,
+            crate::core::ast::types::PrimitiveType::UByte => PrimitiveType::UByte,
+            crate::core::ast::types::PrimitiveType::UInt => PrimitiveType::UInt,
+            crate::core::ast::types::PrimitiveType::ULong => PrimitiveType::ULong,
         }),
         AstType::Array(a) => Type::Array(ArrayType {
             element: Box::new(resolve_ast_type_with_context(&a.element, generic_params)),
@@ -52,6 +55,20 @@ pub fn resolve_ast_type_with_context(ast_type: &AstType, generic_params: &HashSe
             name: g.name.clone(),
             constraints: Vec::new(),
         }),
+        AstType::TraitObject(name) => Type::TraitObject(TraitObjectType {
+            trait_name: name.clone(),
+            constraints: Vec::new(),
+        }),
+        AstType::Record(fields) => Type::Struct(StructType::record(
+            fields.iter().map(|(name, t)| (name.clone(), resolve_ast_type_with_context(t, generic_params))).collect(),
+        )),
+        AstType::Fallible(f) => Type::Enum(EnumType::fallible(
+            resolve_ast_type_with_context(&f.value, generic_params),
+            resolve_ast_type_with_context(&f.error, generic_params),
+        )),
+        AstType::Tuple(elements) => Type::Struct(StructType::tuple(
+            elements.iter().map(|t| resolve_ast_type_with_context(t, generic_params)).collect(),
+        )),
         AstType::Function(f) => Type::Function(FunctionType {
             params: f.params.iter().map(|p| resolve_ast_type_with_context(p, generic_params)).collect(),
             return_type: Box::new(resolve_ast_type_with_context(&f.return_type, generic_params)),
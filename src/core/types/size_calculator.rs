@@ -60,6 +60,7 @@ impl SizeCalculator {
         match type_ {
             Type::Primitive(p) => Ok(p.size_in_bytes()),
             Type::Struct(s) => self.calculate_size(s),
+            Type::Enum(_) => Ok(type_.size_in_bytes().unwrap_or(4)),
             Type::Array(a) => {
                 let element_size = self.type_size(&a.element)?;
                 Ok(element_size * a.size)
@@ -69,6 +70,7 @@ impl SizeCalculator {
             Type::Function(_) => Err("Functions don't have a size".to_string()),
             Type::TraitObject(_) => Ok(std::mem::size_of::<usize>() * 2), // data ptr + vtable ptr
             Type::String => Ok(std::mem::size_of::<usize>() * 2), // ptr + length
+            Type::Error => Err("Cannot calculate size of an erroneous type".to_string()),
         }
     }
 
@@ -1,4 +1,4 @@
-use crate::core::types::composite::{ArrayType, StructType, FunctionType};
+use crate::core::types::composite::{ArrayType, EnumType, StructType, FunctionType};
 use crate::core::types::generic::GenericType;
 use crate::core::types::pointer::PointerType;
 use crate::core::types::primitive::PrimitiveType;
@@ -7,12 +7,15 @@ use crate::core::types::primitive::PrimitiveType;
 pub enum Type {
     Primitive(PrimitiveType),
     Struct(StructType),
+    Enum(EnumType),
     Array(ArrayType),
     Pointer(PointerType),
     Generic(GenericType),
     Function(FunctionType),
     TraitObject(TraitObjectType),
     String, // first clss str type
+    /// type of an expr that failed 2 check, unifies w/ anything so its users still get checked
+    Error,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -26,12 +29,14 @@ impl Type {
         match self {
             Type::Primitive(p) => Some(p.size_in_bytes()),
             Type::Struct(s) => s.size,
+            Type::Enum(e) => Some(if e.payload_words() == 0 { 4 } else { 8 + 8 * e.payload_words() }),
             Type::Array(a) => Some(a.element.size_in_bytes()? * a.size),
             Type::Pointer(_) => Some(std::mem::size_of::<usize>()), // ptr size
             Type::Generic(_) => None, // unknown until monomorphization
             Type::Function(_) => None, // functions dont have a size
             Type::TraitObject(_) => Some(std::mem::size_of::<usize>() * 2), // data ptr + vtable ptr
             Type::String => Some(std::mem::size_of::<usize>() * 2), // ptr + length
+            Type::Error => None,
         }
     }
 
@@ -39,12 +44,14 @@ impl Type {
         match self {
             Type::Primitive(p) => p.size_in_bytes(),
             Type::Struct(s) => s.align.unwrap_or(1),
+            Type::Enum(e) => if e.payload_words() == 0 { 4 } else { 8 },
             Type::Array(a) => a.element.align(),
             Type::Pointer(_) => std::mem::size_of::<usize>(),
             Type::Generic(_) => 1, // unknwn
             Type::Function(_) => 1,
             Type::TraitObject(_) => std::mem::size_of::<usize>(),
             Type::String => std::mem::size_of::<usize>(),
+            Type::Error => 1,
         }
     }
 
@@ -59,4 +66,8 @@ impl Type {
     pub fn is_array(&self) -> bool {
         matches!(self, Type::Array(_))
     }
+
+    pub fn is_error(&self) -> bool {
+        matches!(self, Type::Error)
+    }
 }
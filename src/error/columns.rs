@@ -0,0 +1,26 @@
+use unicode_width::UnicodeWidthStr;
+
+// where a terminal or editor draws a char, not where it is in the source: wide CJK + emoji take
+// 2 cells, combining marks none + a tab runs 2 the next stop
+
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// 1-based display column of byte `offset` in `line`, an offset inside a char counts as its start
+pub fn display_column(line: &str, offset: usize, tab_width: usize) -> usize {
+    let mut end = offset.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    let tab_width = tab_width.max(1);
+    let mut column = 0;
+    for segment in line[..end].split_inclusive('\t') {
+        match segment.strip_suffix('\t') {
+            Some(text) => {
+                column += text.width();
+                column += tab_width - column % tab_width;
+            }
+            None => column += segment.width(),
+        }
+    }
+    column + 1
+}
@@ -1,3 +1,4 @@
+use crate::error::Lint;
 use codespan::{FileId, Span};
 use thiserror::Error;
 
@@ -16,6 +17,18 @@ pub struct Diagnostic {
     pub file_id: FileId,
     pub message: String,
     pub notes: Vec<String>,
+    /// extra spans shown under the primary one, each in its own file eg "defined here"
+    pub labels: Vec<SecondaryLabel>,
+    /// the lint that raised it, its level decided the severity
+    pub lint: Option<Lint>,
+}
+
+/// a span in any loaded file w/ a short message
+#[derive(Debug, Clone)]
+pub struct SecondaryLabel {
+    pub file_id: FileId,
+    pub span: Span,
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Error)]
@@ -49,6 +62,8 @@ impl Diagnostic {
             file_id,
             message,
             notes: Vec::new(),
+            labels: Vec::new(),
+            lint: None,
         }
     }
 
@@ -57,6 +72,11 @@ impl Diagnostic {
         self
     }
 
+    pub fn with_label(mut self, file_id: FileId, span: Span, message: String) -> Self {
+        self.labels.push(SecondaryLabel { file_id, span, message });
+        self
+    }
+
     pub fn error(kind: DiagnosticKind, span: Span, file_id: FileId, message: String) -> Self {
         Self::new(Severity::Error, kind, span, file_id, message)
     }
@@ -0,0 +1,39 @@
+/// warnings w/ a name, `-A` / `-W` / `-D <lint>` pick whether they r dropped, shown or fail the build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// use of a `@deprecated` fn, type or field
+    Deprecated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Lint {
+    pub const ALL: &'static [Lint] = &[Lint::Deprecated];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::Deprecated => "deprecated",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|lint| lint.name() == s)
+    }
+
+    pub fn default_level(&self) -> LintLevel {
+        match self {
+            Lint::Deprecated => LintLevel::Warn,
+        }
+    }
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -1,5 +1,10 @@
+pub mod columns;
 pub mod diagnostic;
+pub mod lint;
 pub mod reporter;
+pub mod source_map;
 
-pub use diagnostic::{Diagnostic, DiagnosticKind, Severity};
+pub use diagnostic::{Diagnostic, DiagnosticKind, SecondaryLabel, Severity};
+pub use lint::{Lint, LintLevel};
 pub use reporter::Reporter;
+pub use source_map::{Desugaring, SourceMap};
@@ -1,10 +1,15 @@
-use crate::error::Diagnostic;
+use crate::error::{Diagnostic, Lint, LintLevel, Severity, SourceMap};
 use codespan::{FileId, Files};
+use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Reporter {
     files: Files<String>,
     diagnostics: Vec<Diagnostic>,
+    /// levels set on the command line, the rest keep their default
+    lint_levels: HashMap<Lint, LintLevel>,
+    /// what desugared constructs were written as, diagnostics on them r explained thru it
+    source_map: SourceMap,
 }
 
 impl Reporter {
@@ -12,6 +17,8 @@ impl Reporter {
         Self {
             files: Files::new(),
             diagnostics: Vec::new(),
+            lint_levels: HashMap::new(),
+            source_map: SourceMap::default(),
         }
     }
 
@@ -20,7 +27,26 @@ impl Reporter {
     }
 
     pub fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
-        self.diagnostics.push(diagnostic);
+        self.diagnostics.push(self.source_map.explain(diagnostic));
+    }
+
+    pub fn set_lint_level(&mut self, lint: Lint, level: LintLevel) {
+        self.lint_levels.insert(lint, level);
+    }
+
+    pub fn lint_level(&self, lint: Lint) -> LintLevel {
+        self.lint_levels.get(&lint).copied().unwrap_or_else(|| lint.default_level())
+    }
+
+    /// `diagnostic` as a warning or error depending on `lint`'s level, nothing if its allowed
+    pub fn add_lint(&mut self, lint: Lint, diagnostic: Diagnostic) {
+        let (severity, note) = match self.lint_level(lint) {
+            LintLevel::Allow => return,
+            LintLevel::Warn => (Severity::Warning, format!("`-A {}` silences this warning", lint)),
+            LintLevel::Deny => (Severity::Error, format!("`-D {}` turned this warning into an error", lint)),
+        };
+        let diagnostic = Diagnostic { severity, lint: Some(lint), ..diagnostic };
+        self.diagnostics.push(self.source_map.explain(diagnostic.with_note(note)));
     }
 
     pub fn has_errors(&self) -> bool {
@@ -29,6 +55,11 @@ impl Reporter {
             .any(|d| matches!(d.severity, crate::error::Severity::Error))
     }
 
+    /// the diagnostics so far, the reporter starts over empty w/ the same files
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
     pub fn diagnostics(&self) -> &[Diagnostic] {
         &self.diagnostics
     }
@@ -40,6 +71,14 @@ impl Reporter {
     pub fn files_mut(&mut self) -> &mut Files<String> {
         &mut self.files
     }
+
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    pub fn source_map_mut(&mut self) -> &mut SourceMap {
+        &mut self.source_map
+    }
 }
 
 impl Default for Reporter {
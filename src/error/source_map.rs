@@ -0,0 +1,74 @@
+use crate::error::Diagnostic;
+use codespan::{FileId, Span};
+
+// the parser rewrites some syntax in2 other constructs (if let -> match, macro calls -> their
+// body), the source map remembers what each generated construct was written as so diagnostics
+// on it can talk about the user's code instead of the lowered form
+
+/// the syntax a generated construct was desugared frm
+#[derive(Debug, Clone, PartialEq)]
+pub enum Desugaring {
+    /// `if let P = v`, a match w/ 1 arm
+    IfLet,
+    /// `while let P = v`, `while true` around a match that breaks
+    WhileLet,
+    /// `name!(..)`, the body of the macro defined at `definition`
+    Macro { name: String, definition: Span },
+}
+
+/// `span` in `file_id` is the user syntax the construct came frm
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub file_id: FileId,
+    pub span: Span,
+    pub desugaring: Desugaring,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    expansions: Vec<Expansion>,
+}
+
+fn contains(outer: Span, inner: Span) -> bool {
+    outer.start() <= inner.start() && inner.end() <= outer.end()
+}
+
+impl SourceMap {
+    pub fn record(&mut self, file_id: FileId, span: Span, desugaring: Desugaring) {
+        self.expansions.push(Expansion { file_id, span, desugaring });
+    }
+
+    pub fn expansions(&self) -> &[Expansion] {
+        &self.expansions
+    }
+
+    /// what the construct at exactly `span` was desugared frm
+    pub fn origin(&self, file_id: FileId, span: Span) -> Option<&Desugaring> {
+        self.expansions.iter().rev().find(|e| e.file_id == file_id && e.span == span).map(|e| &e.desugaring)
+    }
+
+    /// the innermost macro call `span` is part of, nested calls share the outer call's span so
+    /// the last one recorded wins a tie
+    fn macro_call(&self, file_id: FileId, span: Span) -> Option<&Expansion> {
+        self.expansions
+            .iter()
+            .filter(|e| e.file_id == file_id && matches!(e.desugaring, Desugaring::Macro { .. }) && contains(e.span, span))
+            .fold(None, |best: Option<&Expansion>, e| match best {
+                Some(b) if contains(e.span, b.span) && e.span != b.span => Some(b),
+                _ => Some(e),
+            })
+    }
+
+    /// `diagnostic` pointing in2 a macro's expansion also names the macro + where it's defined
+    pub fn explain(&self, diagnostic: Diagnostic) -> Diagnostic {
+        match self.macro_call(diagnostic.file_id, diagnostic.span) {
+            Some(Expansion { file_id, desugaring: Desugaring::Macro { name, definition }, .. }) => {
+                let (file_id, definition) = (*file_id, *definition);
+                diagnostic
+                    .with_note(format!("this happens in the expansion of macro '{}'", name))
+                    .with_label(file_id, definition, format!("'{}' is defined here", name))
+            }
+            _ => diagnostic,
+        }
+    }
+}
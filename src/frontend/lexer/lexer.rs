@@ -1,4 +1,7 @@
+use crate::core::edition::Edition;
+use crate::core::limits::Limits;
 use crate::error::{Diagnostic, DiagnosticKind, Reporter};
+use crate::frontend::lexer::stream::{SourceToken, Trivia, TriviaKind};
 use crate::frontend::lexer::token::{Token, TokenKind};
 use codespan::{ByteIndex, FileId, Span};
 
@@ -8,6 +11,11 @@ pub struct Lexer<'a> {
     reporter: &'a mut Reporter,
     current: usize,
     start: usize,
+    edition: Edition,
+    limits: Limits,
+    /// skipped whitespace + comments since the last token, only kept by `tokenize_with_trivia`
+    trivia: Vec<Trivia>,
+    keep_trivia: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -18,9 +26,33 @@ impl<'a> Lexer<'a> {
             reporter,
             current: 0,
             start: 0,
+            edition: Edition::default(),
+            limits: Limits::default(),
+            trivia: Vec::new(),
+            keep_trivia: false,
         }
     }
 
+    /// words the edition reserves r errors as identifiers
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// a string literal longer than `limits.string_literal` is an error
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// lex only what comes after `offset`, spans stay relative 2 the whole source. the repl appends
+    /// each entry 2 one growing source this way
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.current = offset;
+        self.start = offset;
+        self
+    }
+
     pub fn tokenize(&mut self) -> Vec<Token> {
         let mut tokens = Vec::new();
 
@@ -37,10 +69,31 @@ impl<'a> Lexer<'a> {
         tokens
     }
 
+    /// `tokenize` keeping the whitespace + comments in front of each token, eof carries whatever
+    /// trails the last one
+    pub fn tokenize_with_trivia(&mut self) -> Vec<SourceToken> {
+        self.keep_trivia = true;
+        let mut tokens = Vec::new();
+
+        loop {
+            self.start = self.current;
+            let token = self.next_token();
+            let eof = matches!(token.kind, TokenKind::Eof);
+            tokens.push(SourceToken { kind: token.kind, span: token.span, leading_trivia: std::mem::take(&mut self.trivia) });
+
+            if eof {
+                break;
+            }
+        }
+
+        tokens
+    }
+
     fn next_token(&mut self) -> Token {
         self.skip_whitespace();
 
         if self.is_at_end() {
+            self.start = self.current;
             return self.make_token(TokenKind::Eof);
         }
 
@@ -79,6 +132,7 @@ impl<'a> Lexer<'a> {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    self.push_trivia(TriviaKind::Comment, self.start);
                     self.next_token()
                 } else {
                     self.make_token(TokenKind::Slash)
@@ -89,6 +143,7 @@ impl<'a> Lexer<'a> {
                 while self.peek() != '\n' && !self.is_at_end() {
                     self.advance();
                 }
+                self.push_trivia(TriviaKind::Comment, self.start);
                 self.next_token()
             }
             '%' => self.make_token(TokenKind::Percent),
@@ -133,6 +188,8 @@ impl<'a> Lexer<'a> {
                     self.advance(); // consume second .
                     self.advance(); // consume third .
                     self.make_token(TokenKind::Ellipsis)
+                } else if self.match_char('.') {
+                    self.make_token(TokenKind::DotDot)
                 } else {
                     self.make_token(TokenKind::Dot)
                 }
@@ -212,6 +269,15 @@ impl<'a> Lexer<'a> {
         }
 
         self.advance(); // cnsm closing "
+        if value.len() > self.limits.string_literal {
+            let message = format!("String literal is {} bytes, over the limit of {}", value.len(), self.limits.string_literal);
+            let span = Span::new(ByteIndex(self.start as u32), ByteIndex(self.current as u32));
+            let diagnostic = Diagnostic::error(DiagnosticKind::LexicalError, span, self.file_id, message)
+                .with_note(Limits::how_to_raise("string-literal"));
+            self.reporter.add_diagnostic(diagnostic);
+            // an empty literal keeps the rest of the file parsing w/o carrying the bytes along
+            value = String::new();
+        }
         self.make_token(TokenKind::StringLiteral(value))
     }
 
@@ -262,8 +328,11 @@ impl<'a> Lexer<'a> {
             self.advance();
         }
 
+        // `pair.0.1` is 2 tuple fields, not `0.1`. a range's `..` still takes a float after it
+        let before = &self.source[..self.start];
+        let field = before.ends_with('.') && !before.ends_with("..");
         // look 4 fractional part
-        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+        if !field && self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance(); // cnsm
             while self.peek().is_ascii_digit() {
                 self.advance();
@@ -298,13 +367,26 @@ impl<'a> Lexer<'a> {
             _ => {}
         }
         
-        match TokenKind::keyword_from_str(text) {
+        match TokenKind::keyword_in(text, self.edition) {
             Some(kind) => self.make_token(kind),
-            None => self.make_token(TokenKind::Identifier(text.to_string())),
+            None => {
+                // still an identifier so the parser doesnt trip over it as well
+                if self.edition.is_reserved(text) {
+                    let span = Span::new(ByteIndex(self.start as u32), ByteIndex(self.current as u32));
+                    self.reporter.add_diagnostic(Diagnostic::error(
+                        DiagnosticKind::LexicalError,
+                        span,
+                        self.file_id,
+                        format!("'{}' is a reserved keyword in edition {}", text, self.edition),
+                    ));
+                }
+                self.make_token(TokenKind::Identifier(text.to_string()))
+            }
         }
     }
 
     fn skip_whitespace(&mut self) {
+        let start = self.current;
         loop {
             match self.peek() {
                 ' ' | '\r' | '\t' | '\n' => {
@@ -313,29 +395,34 @@ impl<'a> Lexer<'a> {
                 _ => break,
             }
         }
+        if self.current > start {
+            self.push_trivia(TriviaKind::Whitespace, start);
+        }
     }
 
+    fn push_trivia(&mut self, kind: TriviaKind, start: usize) {
+        if self.keep_trivia {
+            let span = Span::new(ByteIndex(start as u32), ByteIndex(self.current as u32));
+            self.trivia.push(Trivia { kind, span });
+        }
+    }
+
+    // `current` + `start` r byte offsets, spans + slices of the source must never split a char
+
     fn advance(&mut self) -> char {
-        if self.is_at_end() {
-            return '\0';
+        let c = self.peek();
+        if !self.is_at_end() {
+            self.current += c.len_utf8();
         }
-        let c = self.source.chars().nth(self.current).unwrap();
-        self.current += 1;
         c
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        }
-        self.source.chars().nth(self.current).unwrap_or('\0')
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-        self.source.chars().nth(self.current + 1).unwrap_or('\0')
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
     fn match_char(&mut self, expected: char) -> bool {
@@ -345,7 +432,7 @@ impl<'a> Lexer<'a> {
         if self.peek() != expected {
             return false;
         }
-        self.current += 1;
+        self.current += expected.len_utf8();
         true
     }
 
@@ -1,5 +1,7 @@
 pub mod lexer;
+pub mod stream;
 pub mod token;
 
 pub use lexer::Lexer;
+pub use stream::{token_stream, SourceToken, TokenStream, Trivia, TriviaKind};
 pub use token::{Token, TokenKind};
@@ -0,0 +1,73 @@
+use crate::core::edition::Edition;
+use crate::error::Reporter;
+use crate::frontend::lexer::lexer::Lexer;
+use crate::frontend::lexer::token::TokenKind;
+use codespan::Span;
+
+// the real lexer's output 4 tools outside the compiler (linters, metrics, formatters). each
+// token keeps the whitespace + comments in front of it, so the trivia + text of evry token in
+// order is the source again. spans r offsets in2 the source string
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// spaces, tabs + newlines
+    Whitespace,
+    /// `// ...` or `# ...` up 2 the end of the line
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceToken {
+    pub kind: TokenKind,
+    pub span: Span,
+    pub leading_trivia: Vec<Trivia>,
+}
+
+/// an error the lexer reported, the token it belongs 2 is still in the stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub message: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenStream {
+    /// always ends w/ `TokenKind::Eof`, which holds the trailing trivia
+    pub tokens: Vec<SourceToken>,
+    pub errors: Vec<LexError>,
+}
+
+/// lex `source` as `edition` does
+pub fn token_stream(source: &str, edition: Edition) -> TokenStream {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file(String::new(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).with_edition(edition).tokenize_with_trivia();
+    let errors = reporter
+        .diagnostics()
+        .iter()
+        .map(|d| LexError { message: d.message.clone(), span: d.span })
+        .collect();
+    TokenStream { tokens, errors }
+}
+
+impl SourceToken {
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        slice(source, self.span)
+    }
+}
+
+impl Trivia {
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        slice(source, self.span)
+    }
+}
+
+fn slice(source: &str, span: Span) -> &str {
+    &source[span.start().to_usize()..span.end().to_usize()]
+}
@@ -1,3 +1,4 @@
+use crate::core::edition::{Edition, EditionFeature};
 use codespan::Span;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,14 +29,23 @@ pub enum TokenKind {
     For,
     Break,
     Continue,
+    Match,
+    When,
+    Guard,
+    Defer,
+    Try,
+    Let,
     Struct,
+    Enum,
     Trait,
     Implement,
     Module,
     Require,
+    Import,
     Use,
     Foreign,
     Comptime,
+    Const,
     Declare,
     End,
     Uses,
@@ -43,6 +53,8 @@ pub enum TokenKind {
     Do,
     Mut,
     At,
+    New,
+    Delete,
     Ref,
     RefNullable,
 
@@ -55,6 +67,9 @@ pub enum TokenKind {
     Float,
     Bool,
     Char,
+    UByte,
+    UInt,
+    ULong,
     String,
 
     // operators
@@ -81,6 +96,7 @@ pub enum TokenKind {
     Question,       // ?
     Exists,         // exisst?
     Ellipsis,       // ...
+    DotDot,         // ..
 
     // dlmtrs
     LeftParen,      // (
@@ -96,58 +112,191 @@ pub enum TokenKind {
     Error(String),
 }
 
+/// evry word the lexer turns in2 a keyword token, `true` / `false` r literals
+pub const KEYWORDS: &[(&str, TokenKind)] = &[
+    ("def", TokenKind::Def),
+    ("return", TokenKind::Return),
+    ("if", TokenKind::If),
+    ("else", TokenKind::Else),
+    ("while", TokenKind::While),
+    ("for", TokenKind::For),
+    ("break", TokenKind::Break),
+    ("continue", TokenKind::Continue),
+    ("match", TokenKind::Match),
+    ("when", TokenKind::When),
+    ("guard", TokenKind::Guard),
+    ("defer", TokenKind::Defer),
+    ("let", TokenKind::Let),
+    ("struct", TokenKind::Struct),
+    ("enum", TokenKind::Enum),
+    ("trait", TokenKind::Trait),
+    ("implement", TokenKind::Implement),
+    ("module", TokenKind::Module),
+    ("require", TokenKind::Require),
+    ("import", TokenKind::Import),
+    ("use", TokenKind::Use),
+    ("foreign", TokenKind::Foreign),
+    ("comptime", TokenKind::Comptime),
+    ("const", TokenKind::Const),
+    ("declare", TokenKind::Declare),
+    ("end", TokenKind::End),
+    ("uses", TokenKind::Uses),
+    ("returns", TokenKind::Returns),
+    ("do", TokenKind::Do),
+    ("mut", TokenKind::Mut),
+    ("at", TokenKind::At),
+    ("new", TokenKind::New),
+    ("delete", TokenKind::Delete),
+    ("ref", TokenKind::Ref),
+    ("null", TokenKind::Null),
+    ("not", TokenKind::Not),
+    ("void", TokenKind::Void),
+    ("byte", TokenKind::Byte),
+    ("int", TokenKind::Int),
+    ("long", TokenKind::Long),
+    ("size", TokenKind::Size),
+    ("float", TokenKind::Float),
+    ("bool", TokenKind::Bool),
+    ("char", TokenKind::Char),
+    ("string", TokenKind::String),
+    ("ubyte", TokenKind::UByte),
+    ("uint", TokenKind::UInt),
+    ("ulong", TokenKind::ULong),
+    ("true", TokenKind::BoolLiteral(true)),
+    ("false", TokenKind::BoolLiteral(false)),
+];
+
+/// keywords only frm the edition w/ the feature on, an identifier b4 it
+pub const EDITION_KEYWORDS: &[(&str, TokenKind, EditionFeature)] = &[("try", TokenKind::Try, EditionFeature::TryKeyword)];
+
+/// evry token w/ a fixed spelling that isnt a word, see `TokenKind::symbol`
+pub const SYMBOLS: &[TokenKind] = &[
+    TokenKind::Plus,
+    TokenKind::Minus,
+    TokenKind::Star,
+    TokenKind::Slash,
+    TokenKind::Percent,
+    TokenKind::Equal,
+    TokenKind::EqualEqual,
+    TokenKind::NotEqual,
+    TokenKind::Less,
+    TokenKind::LessEqual,
+    TokenKind::Greater,
+    TokenKind::GreaterEqual,
+    TokenKind::And,
+    TokenKind::Or,
+    TokenKind::Not,
+    TokenKind::Dot,
+    TokenKind::Comma,
+    TokenKind::Colon,
+    TokenKind::ColonColon,
+    TokenKind::Semicolon,
+    TokenKind::Question,
+    TokenKind::Exists,
+    TokenKind::Ellipsis,
+    TokenKind::DotDot,
+    TokenKind::LeftParen,
+    TokenKind::RightParen,
+    TokenKind::LeftBrace,
+    TokenKind::RightBrace,
+    TokenKind::LeftBracket,
+    TokenKind::RightBracket,
+    TokenKind::Pipe,
+    TokenKind::At,
+    TokenKind::RefNullable,
+];
+
+/// wht a token is 4 an editor, used by the highlighting defs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenClass {
+    /// branches, loops + returns
+    Control,
+    /// declarations + evry other keyword
+    Keyword,
+    /// builtin typs + `ref`
+    Type,
+    /// `true`, `false` + `null`
+    Constant,
+    Operator,
+    Punctuation,
+    Literal,
+    Identifier,
+    /// eof + errors
+    Other,
+}
+
 impl TokenKind {
     pub fn is_keyword(s: &str) -> bool {
-        matches!(
-            s,
-            "def" | "return" | "if" | "else" | "while" | "for" | "break" | "continue"
-                | "struct" | "trait" | "implement" | "module" | "require" | "use"
-                | "foreign" | "comptime" | "declare" | "end" | "uses" | "returns"
-                | "do" | "mut" | "at" | "ref" | "null" | "not" | "void" | "byte" | "int"
-                | "long" | "size" | "float" | "bool" | "char" | "string"
-        )
+        KEYWORDS.iter().any(|(word, _)| *word == s)
     }
 
     pub fn keyword_from_str(s: &str) -> Option<TokenKind> {
-        match s {
-            "def" => Some(TokenKind::Def),
-            "return" => Some(TokenKind::Return),
-            "if" => Some(TokenKind::If),
-            "else" => Some(TokenKind::Else),
-            "while" => Some(TokenKind::While),
-            "for" => Some(TokenKind::For),
-            "break" => Some(TokenKind::Break),
-            "continue" => Some(TokenKind::Continue),
-            "struct" => Some(TokenKind::Struct),
-            "trait" => Some(TokenKind::Trait),
-            "implement" => Some(TokenKind::Implement),
-            "module" => Some(TokenKind::Module),
-            "require" => Some(TokenKind::Require),
-            "use" => Some(TokenKind::Use),
-            "foreign" => Some(TokenKind::Foreign),
-            "comptime" => Some(TokenKind::Comptime),
-            "declare" => Some(TokenKind::Declare),
-            "end" => Some(TokenKind::End),
-            "uses" => Some(TokenKind::Uses),
-            "returns" => Some(TokenKind::Returns),
-            "do" => Some(TokenKind::Do),
-            "mut" => Some(TokenKind::Mut),
-            "at" => Some(TokenKind::At),
-            "ref" => Some(TokenKind::Ref),
-            "null" => Some(TokenKind::Null),
-            "not" => Some(TokenKind::Not),
-            "void" => Some(TokenKind::Void),
-            "byte" => Some(TokenKind::Byte),
-            "int" => Some(TokenKind::Int),
-            "long" => Some(TokenKind::Long),
-            "size" => Some(TokenKind::Size),
-            "float" => Some(TokenKind::Float),
-            "bool" => Some(TokenKind::Bool),
-            "char" => Some(TokenKind::Char),
-            "string" => Some(TokenKind::String),
-            "true" => Some(TokenKind::BoolLiteral(true)),
-            "false" => Some(TokenKind::BoolLiteral(false)),
-            _ => None,
+        KEYWORDS.iter().find(|(word, _)| *word == s).map(|(_, kind)| kind.clone())
+    }
+
+    /// like `keyword_from_str` + the `EDITION_KEYWORDS` `edition` has
+    pub fn keyword_in(s: &str, edition: Edition) -> Option<TokenKind> {
+        Self::keyword_from_str(s).or_else(|| {
+            EDITION_KEYWORDS.iter().find(|(word, _, feature)| *word == s && edition.has(*feature)).map(|(_, kind, _)| kind.clone())
+        })
+    }
+
+    /// how an operator or delimiter is written, `None` 4 keywords, literals + identifiers
+    pub fn symbol(&self) -> Option<&'static str> {
+        Some(match self {
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Star => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
+            TokenKind::Equal => "=",
+            TokenKind::EqualEqual => "==",
+            TokenKind::NotEqual => "!=",
+            TokenKind::Less => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::And => "&&",
+            TokenKind::Or => "||",
+            TokenKind::Not => "!",
+            TokenKind::Dot => ".",
+            TokenKind::Comma => ",",
+            TokenKind::Colon => ":",
+            TokenKind::ColonColon => "::",
+            TokenKind::Semicolon => ";",
+            TokenKind::Question => "?",
+            TokenKind::Exists => "???exists",
+            TokenKind::Ellipsis => "...",
+            TokenKind::DotDot => "..",
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::LeftBrace => "{",
+            TokenKind::RightBrace => "}",
+            TokenKind::LeftBracket => "[",
+            TokenKind::RightBracket => "]",
+            TokenKind::Pipe => "|",
+            TokenKind::At => "@",
+            TokenKind::RefNullable => "ref?",
+            _ => return None,
+        })
+    }
+
+    pub fn class(&self) -> TokenClass {
+        use TokenKind::*;
+        match self {
+            Return | If | Else | While | For | Break | Continue | Match | When | Guard | Defer | Try => TokenClass::Control,
+            Def | Let | Struct | Enum | Trait | Implement | Module | Require | Import | Use | Foreign | Comptime
+            | Const | Declare | End | Uses | Returns | Do | Mut | At | New | Delete => TokenClass::Keyword,
+            Void | Byte | Int | Long | Size | Float | Bool | Char | UByte | UInt | ULong | String | Ref
+            | RefNullable => TokenClass::Type,
+            BoolLiteral(_) | Null => TokenClass::Constant,
+            Plus | Minus | Star | Slash | Percent | Equal | EqualEqual | NotEqual | Less | LessEqual | Greater
+            | GreaterEqual | And | Or | Not | Question | Exists | Ellipsis | DotDot => TokenClass::Operator,
+            Dot | Comma | Colon | ColonColon | Semicolon | LeftParen | RightParen | LeftBrace | RightBrace
+            | LeftBracket | RightBracket | Pipe => TokenClass::Punctuation,
+            IntLiteral(_) | FloatLiteral(_) | CharLiteral(_) | StringLiteral(_) => TokenClass::Literal,
+            Identifier(_) => TokenClass::Identifier,
+            Eof | Error(_) => TokenClass::Other,
         }
     }
 }
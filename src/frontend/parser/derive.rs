@@ -0,0 +1,238 @@
+use crate::core::ast::expr::*;
+use crate::core::ast::item::*;
+use crate::core::ast::stmt::*;
+use crate::core::ast::types::*;
+use codespan::Span;
+
+/// traits `@derive` can synthesize an impl 4
+pub const DERIVABLE: [&str; 3] = ["Display", "Eq", "Hash"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeriveError {
+    pub span: Span,
+    pub message: String,
+}
+
+/// `@derive(..)` structs -> `implement Trait for Struct` items built frm the field list
+/// derived traits the file doesnt declare get a declaration, Display prints thru C `printf`
+pub fn expand_derives(items: &mut Vec<Item>) -> Vec<DeriveError> {
+    let mut errors = Vec::new();
+    let mut impls = Vec::new();
+    let mut derived = Vec::new();
+    for item in items.iter() {
+        let Item::Struct(s) = item else { continue };
+        for name in &s.derives {
+            if !s.generics.is_empty() {
+                errors.push(DeriveError { span: s.span, message: format!("Cannot derive '{}' for generic struct '{}'", name, s.name) });
+                continue;
+            }
+            let method = match name.as_str() {
+                "Display" => derive_display(s),
+                "Eq" => derive_eq(s),
+                "Hash" => derive_hash(s),
+                _ => Err(DeriveError {
+                    span: s.span,
+                    message: format!("Cannot derive '{}', only {} can be derived", name, DERIVABLE.join(", ")),
+                }),
+            };
+            match method {
+                Ok(method) => {
+                    impls.push(Item::TraitImpl(TraitImpl {
+                        trait_name: name.clone(),
+                        type_name: s.name.clone(),
+                        generics: Vec::new(),
+                        methods: vec![method],
+                        consts: Vec::new(),
+                        span: s.span,
+                    }));
+                    if !derived.contains(name) {
+                        derived.push(name.clone());
+                    }
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    for name in &derived {
+        if !items.iter().any(|i| matches!(i, Item::Trait(t) if t.name == *name)) {
+            items.push(Item::Trait(declaration(name)));
+        }
+    }
+    if derived.iter().any(|n| n == "Display") && !declares_printf(items) {
+        items.push(Item::Foreign(printf_declaration()));
+    }
+    items.extend(impls);
+    errors
+}
+
+/// `fmt(self) returns int` - printf of `Name { a: .., b: .. }`, the format string is fixed at cmpl time
+fn derive_display(s: &Struct) -> Result<Function, DeriveError> {
+    let mut parts = Vec::new();
+    let mut values = Vec::new();
+    for field in &s.fields {
+        let Some(spec) = display_spec(&field.type_) else {
+            return Err(DeriveError {
+                span: field.span,
+                message: format!("Cannot derive 'Display' for '{}': field '{}' has no printf format", s.name, field.name),
+            });
+        };
+        parts.push(format!("{}: {}", field.name, spec));
+        values.push(self_field(&field.name, s.span));
+    }
+    let format = if parts.is_empty() { s.name.clone() } else { format!("{} {{ {} }}", s.name, parts.join(", ")) };
+    let mut args = vec![Expr::Literal(LiteralExpr { kind: LiteralKind::String(format), span: s.span })];
+    args.extend(values);
+    let call = Expr::Call(CallExpr {
+        callee: Box::new(Expr::Variable(VariableExpr { name: "printf".to_string(), span: s.span })),
+        args,
+        generic_args: None,
+        span: s.span,
+    });
+    Ok(method(s, "fmt", Vec::new(), Type::int(), call))
+}
+
+/// `eq(self, other) returns bool` - field by field `==`
+fn derive_eq(s: &Struct) -> Result<Function, DeriveError> {
+    let mut result = None;
+    for field in &s.fields {
+        // strings + nested structs r aggregates, `==` only compares scalars
+        if !matches!(&field.type_, Type::Primitive(p) if *p != PrimitiveType::Void) && !matches!(field.type_, Type::Pointer(_)) {
+            return Err(DeriveError {
+                span: field.span,
+                message: format!("Cannot derive 'Eq' for '{}': field '{}' can't be compared with ==", s.name, field.name),
+            });
+        }
+        let other = Expr::FieldAccess(FieldAccessExpr {
+            object: Box::new(Expr::Variable(VariableExpr { name: "other".to_string(), span: s.span })),
+            field: field.name.clone(),
+            span: s.span,
+        });
+        let eq = binary(self_field(&field.name, s.span), BinaryOp::Eq, other, s.span);
+        result = Some(match result {
+            Some(prev) => binary(prev, BinaryOp::And, eq, s.span),
+            None => eq,
+        });
+    }
+    let body = result.unwrap_or(Expr::Literal(LiteralExpr { kind: LiteralKind::Bool(true), span: s.span }));
+    let other = Param { name: "other".to_string(), type_: self_type(s), comptime: false, span: s.span };
+    Ok(method(s, "eq", vec![other], Type::bool(), body))
+}
+
+/// `hash(self) returns long` - `h * 31 + field` over the fields, strings hash their len
+fn derive_hash(s: &Struct) -> Result<Function, DeriveError> {
+    let mut hash = Expr::Literal(LiteralExpr { kind: LiteralKind::Int(17), span: s.span });
+    for field in &s.fields {
+        let value = match &field.type_ {
+            Type::Primitive(p) if is_integer(p) => self_field(&field.name, s.span),
+            Type::Named(n) if n.name == "string" => Expr::MethodCall(MethodCallExpr {
+                receiver: Box::new(self_field(&field.name, s.span)),
+                method: "len".to_string(),
+                args: Vec::new(),
+                span: s.span,
+            }),
+            _ => {
+                return Err(DeriveError {
+                    span: field.span,
+                    message: format!("Cannot derive 'Hash' for '{}': field '{}' can't be hashed", s.name, field.name),
+                })
+            }
+        };
+        let mixed = binary(hash, BinaryOp::Mul, Expr::Literal(LiteralExpr { kind: LiteralKind::Int(31), span: s.span }), s.span);
+        hash = binary(mixed, BinaryOp::Add, value, s.span);
+    }
+    Ok(method(s, "hash", Vec::new(), Type::Primitive(PrimitiveType::Long), hash))
+}
+
+fn display_spec(ty: &Type) -> Option<&'static str> {
+    match ty {
+        Type::Primitive(p) => match p {
+            PrimitiveType::Byte | PrimitiveType::Int => Some("%d"),
+            PrimitiveType::UByte | PrimitiveType::UInt => Some("%u"),
+            PrimitiveType::Long => Some("%ld"),
+            PrimitiveType::ULong => Some("%lu"),
+            PrimitiveType::Size => Some("%zu"),
+            PrimitiveType::Float => Some("%f"),
+            PrimitiveType::Char => Some("%c"),
+            PrimitiveType::Bool | PrimitiveType::Void => None,
+        },
+        Type::Pointer(_) => Some("%p"),
+        _ => None,
+    }
+}
+
+fn is_integer(p: &PrimitiveType) -> bool {
+    !matches!(p, PrimitiveType::Void | PrimitiveType::Float | PrimitiveType::Bool | PrimitiveType::Char)
+}
+
+fn declaration(name: &str) -> Trait {
+    let span = Span::new(0, 0);
+    // self + eq's other r untyped, void is the checker's placeholder 4 the impl type
+    let param = |name: &str| Param { name: name.to_string(), type_: Type::void(), comptime: false, span };
+    let (method, params, return_type) = match name {
+        "Display" => ("fmt", vec![param("self")], Type::int()),
+        "Eq" => ("eq", vec![param("self"), param("other")], Type::bool()),
+        _ => ("hash", vec![param("self")], Type::Primitive(PrimitiveType::Long)),
+    };
+    Trait {
+        name: name.to_string(),
+        generics: Vec::new(),
+        methods: vec![TraitMethod { name: method.to_string(), params, return_type: Some(return_type), span }],
+        consts: Vec::new(),
+        span,
+    }
+}
+
+fn declares_printf(items: &[Item]) -> bool {
+    items.iter().any(|i| matches!(i, Item::Foreign(f) if f.functions.iter().any(|func| func.name == "printf")))
+}
+
+fn printf_declaration() -> Foreign {
+    let span = Span::new(0, 0);
+    Foreign {
+        abi: "C".to_string(),
+        name: "stdio".to_string(),
+        functions: vec![ForeignFunction {
+            name: "printf".to_string(),
+            params: vec![Param { name: "format".to_string(), type_: Type::ref_(Type::Primitive(PrimitiveType::Char)), comptime: false, span }],
+            return_type: Some(Type::int()),
+            abi: None,
+            variadic: true,
+            span,
+        }],
+        span,
+    }
+}
+
+fn self_type(s: &Struct) -> Type {
+    Type::ref_(Type::Named(NamedType { name: s.name.clone(), generics: Vec::new() }))
+}
+
+fn self_field(field: &str, span: Span) -> Expr {
+    Expr::FieldAccess(FieldAccessExpr {
+        object: Box::new(Expr::Variable(VariableExpr { name: "self".to_string(), span })),
+        field: field.to_string(),
+        span,
+    })
+}
+
+fn binary(left: Expr, op: BinaryOp, right: Expr, span: Span) -> Expr {
+    Expr::Binary(BinaryExpr { left: Box::new(left), op, right: Box::new(right), span })
+}
+
+/// `def name(self : ref S, params..) returns ret` whose body returns `value`
+fn method(s: &Struct, name: &str, params: Vec<Param>, return_type: Type, value: Expr) -> Function {
+    let mut all = vec![Param { name: "self".to_string(), type_: self_type(s), comptime: false, span: s.span }];
+    all.extend(params);
+    Function {
+        name: name.to_string(),
+        generics: Vec::new(),
+        params: all,
+        return_type: Some(return_type),
+        body: Some(vec![Stmt::Return(ReturnStmt { value: Some(value), span: s.span })]),
+        uses: Vec::new(),
+        target_features: Vec::new(),
+        deprecated: None,
+        span: s.span,
+    }
+}
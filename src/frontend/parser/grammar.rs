@@ -1,5 +1,264 @@
-// grammar dcmnttn and helper functions 4 the prsr
-// this module serves as dcmnttn 4 the emrld gramamr
+// the grammar the prsr accepts, exported as ebnf by --emit=grammar 4 editor grammars + tools
+//
+// the grammar is prsd usg a pratt prsr 4 expressions and rcrsv dscnt 4 statements and items.
+// keywords + evry operator lvl r generated frm the tables the lexer + prsr run on, the rest r
+// hand-written rules whose examples the tests parse so a rule that stops matching is caught
 
-// the grammar is prsd usg a pratt prsr 4 expressions
-// and rcrsv dscnt 4 statements and items
+use crate::core::edition::{Edition, EditionFeature, RESERVED};
+use crate::frontend::lexer::token::{TokenKind, EDITION_KEYWORDS, KEYWORDS};
+use crate::frontend::parser::precedence::{Precedence, BINARY_OPERATORS, UNARY_OPERATORS};
+
+/// a hand-written production
+pub struct Rule {
+    pub name: &'static str,
+    pub body: &'static str,
+    /// one of `item`s alternatives
+    pub item: bool,
+    /// the edition change that takes the rule out of the language
+    pub removed_by: Option<EditionFeature>,
+    /// a whole program that uses the rule
+    pub example: &'static str,
+}
+
+const fn rule(name: &'static str, body: &'static str, example: &'static str) -> Rule {
+    Rule { name, body, item: false, removed_by: None, example }
+}
+
+const fn item(name: &'static str, body: &'static str, example: &'static str) -> Rule {
+    Rule { name, body, item: true, removed_by: None, example }
+}
+
+pub const RULES: &[Rule] = &[
+    rule(
+        "program",
+        r#"( feature_gate | macro_def | item )*"#,
+        "def main() returns int\n  return 0\nend\n",
+    ),
+    rule(
+        "feature_gate",
+        r#""@" "feature" "(" STRING ( "," STRING )* ")""#,
+        "@feature(\"macros\")\n",
+    ),
+    rule(
+        "macro_def",
+        r#""macro" IDENT "(" ( IDENT ( "," IDENT )* )? ")" "=" expression"#,
+        "@feature(\"macros\")\nmacro sq(x) = x * x\n",
+    ),
+    rule(
+        "attribute",
+        r#""@" ( "derive" "(" IDENT ( "," IDENT )* ")" | "target_feature" "(" STRING ( "," STRING )* ")" | "deprecated" ( "(" STRING ")" )? )"#,
+        "@derive(Eq)\n@deprecated(\"old\")\nstruct P\n  x : int\nend\n",
+    ),
+    item(
+        "function",
+        r#""def" IDENT generics? ( "(" ( param ( "," param )* )? ")" )? ( "returns" type )? ( "uses" IDENT ( "," IDENT )* )? function_body?"#,
+        "def add[Type T for Num](a : T, comptime b : int) returns T uses io\n  return a\nend\n",
+    ),
+    rule(
+        "function_body",
+        r#""{" stmt* "}" | "=" expression | stmt* "end""#,
+        "def one() returns int = 1\ndef two() returns int { return 2 }\n",
+    ),
+    rule("param", r#""comptime"? IDENT ":" type"#, "def f(x : int)\nend\n"),
+    rule(
+        "generics",
+        r#""[" ( "Type" IDENT ( "for" IDENT )? ( "," "Type" IDENT ( "for" IDENT )? )* )? "]""#,
+        "struct Pair[Type A, Type B for Eq]\n  a : A\nend\n",
+    ),
+    item(
+        "struct",
+        r#""struct" IDENT generics? ( ( "@" "deprecated" ( "(" STRING ")" )? )? IDENT ":" type )* "end""#,
+        "struct Point\n  x : int\n  @deprecated\n  y : int\nend\n",
+    ),
+    item(
+        "enum",
+        r#""enum" IDENT ( IDENT ( "(" ( type ( "," type )* )? ")" )? )* "end""#,
+        "enum Shape\n  Circle(float)\n  Rect(float, float)\n  Empty\nend\n",
+    ),
+    item(
+        "trait",
+        r#""trait" IDENT generics? ( "def" IDENT ( "(" ( ( "self" | param ) ( "," param )* )? ")" )? ( "returns" type )? | assoc_const )* "end""#,
+        "trait Area\n  def area(self) returns float\n  const SIDES : int\nend\n",
+    ),
+    rule(
+        "assoc_const",
+        r#""const" IDENT ":" type ( "=" expression )?"#,
+        "trait T\n  const N : int = 1\nend\n",
+    ),
+    item(
+        "impl",
+        r#""implement" IDENT "for" IDENT generics? ( function | assoc_const )* "end""#,
+        "implement Area for Square\n  const SIDES : int = 4\n  def area(self : Square) returns float\n    return 1.0\n  end\nend\n",
+    ),
+    item("module", r#""module" IDENT item* "end""#, "module math\n  def one() returns int = 1\nend\n"),
+    item(
+        "foreign",
+        r#""foreign" STRING? IDENT ( "def" IDENT ( "(" ( ( param | "..." ) ( "," ( param | "..." ) )* )? ")" )? ( "returns" type )? ( "with" "abi" "=" STRING )? )* "end""#,
+        "foreign \"C\" libc\n  def printf(fmt : ref byte, ...) returns int\n  def f() with abi = \"C\"\nend\n",
+    ),
+    item(
+        "import",
+        r#""pub"? "import" IDENT ( "::" IDENT )* ( "(" IDENT ( "," IDENT )* ")" )? ( "as" IDENT )?"#,
+        "import std::io (print, read) as sio\npub import math\n",
+    ),
+    Rule {
+        name: "require",
+        body: r#""require" STRING"#,
+        item: true,
+        removed_by: Some(EditionFeature::ImportOnly),
+        example: "require \"lib.em\"\n",
+    },
+    item("use", r#""use" IDENT ( ( "." | "::" ) IDENT )*"#, "use std.io\nuse a::b\n"),
+    item("declare", r#""declare" "struct" IDENT generics?"#, "declare struct Node[Type T]\n"),
+    item("global", r#""mut"? IDENT ":" type ( "=" expression )?"#, "mut count : int = 0\nlimit : int\n"),
+    rule(
+        "type",
+        r#"( primitive_type | "ref" type | "ref?" type | "dyn" IDENT | IDENT ( "[" ( INT | type ( "," type )* ) "]" )? | "{" ( IDENT ":" type ( "," IDENT ":" type )* )? "}" ) ( "[" INT? "]" )* ( "or" type )?"#,
+        "grid : int[3][4]\np : ref? Node\nr : { x : int, y : int }\nl : List[int]\n",
+    ),
+    rule(
+        "primitive_type",
+        r#""void" | "byte" | "int" | "long" | "size" | "float" | "bool" | "char" | "ubyte" | "uint" | "ulong" | "string""#,
+        "a : ulong\nb : string\n",
+    ),
+    rule(
+        "stmt",
+        r#"let_stmt | "return" expression? | if_stmt | while_stmt | for_stmt | match_stmt | "guard" expression "else" stmt* "end" | "defer" ("do" stmt* "end" | expression) | "break" | "continue" | expression"#,
+        "def f(x : int) returns int\n  guard x > 0 else\n    return 0\n  end\n  while true\n    break\n  end\n  return x\nend\n",
+    ),
+    rule(
+        "let_stmt",
+        r#""comptime"? "mut"? IDENT ( ":" type )? ( "=" expression )?"#,
+        "def f()\n  mut x : int = 1\n  x = x + 1\nend\n",
+    ),
+    rule(
+        "if_stmt",
+        r#""if" ( "let" pattern "=" )? expression ( "{" stmt* "}" ( "else" "{" stmt* "}" )? | stmt* ( "else" stmt* )? "end" )"#,
+        "def f(x : int) returns int\n  if x > 1\n    return 1\n  else\n    return 2\n  end\n  if let 0 = x\n    return 3\n  end\n  return 4\nend\n",
+    ),
+    rule(
+        "while_stmt",
+        r#""while" ( "let" pattern "=" )? expression ( "{" stmt* "}" | stmt* "end" )"#,
+        "def f()\n  mut i : int = 0\n  while i < 3 { i = i + 1 }\nend\n",
+    ),
+    rule(
+        "for_stmt",
+        r#""for" "(" stmt? ";" expression? ";" expression? ")" "{" stmt* "}""#,
+        "def f()\n  for (i : int = 0; i < 3; i = i + 1) {\n  }\nend\n",
+    ),
+    rule(
+        "match_stmt",
+        r#""match" expression ( "when" pattern ( "," pattern )* stmt* )+ ( "else" stmt* )? "end""#,
+        "def f(x : int) returns int\n  match x\n    when 0, 1\n      return 1\n    when 2 .. 9\n      return 2\n    else\n      return 3\n  end\nend\n",
+    ),
+    rule(
+        "pattern",
+        r#""-"? INT ( ".." "-"? INT )? | "true" | "false" | CHAR | STRING | IDENT ( "::" IDENT )? ( "(" ( IDENT ( "," IDENT )* )? ")" )?"#,
+        "enum E\n  A(int)\nend\ndef f(e : E) returns int\n  match e\n    when E::A(v)\n      return v\n  end\n  return 0\nend\n",
+    ),
+    rule(
+        "postfix",
+        r#""(" arguments? ")" | "[" expression "]" | "{" ( IDENT ":" expression ( "," IDENT ":" expression )* )? "}" | "." IDENT ( "(" arguments? ")" )? | "::" IDENT | "?""#,
+        "def f(a : int[2]) returns int or Error\n  return a[0] + g(1, 2)? + m::k\nend\n",
+    ),
+    rule("arguments", r#"expression ( "," expression )*"#, "def f()\n  g(1, 2 + 3)\nend\n"),
+    rule(
+        "primary",
+        r#"INT | FLOAT | STRING | CHAR | "true" | "false" | "null" | IDENT ( "!" "(" arguments? ")" )? | primitive_type | "(" expression ")" | "[" ( expression ( ";" INT | ( "," expression )* ) )? "]" | "{" stmt* "}" | "if" expression expression ( "else" expression )? | "@" unary | "new" call | "delete" unary | "comptime" expression | "do" ( "|" ( IDENT ( ":" type )? ( "," IDENT ( ":" type )? )* )? "|" )? stmt* "end""#,
+        "def f() returns int\n  xs : int[4] = [0; 4]\n  c : int = comptime 2 * 3\n  return (xs[0] + 1)\nend\n",
+    ),
+];
+
+/// rule name of an operator lvl
+fn level_name(precedence: Precedence) -> &'static str {
+    match precedence {
+        Precedence::None | Precedence::Assignment => "assignment",
+        Precedence::Or => "or_expr",
+        Precedence::And => "and_expr",
+        Precedence::Equality => "equality",
+        Precedence::Comparison => "comparison",
+        Precedence::Term => "term",
+        Precedence::Factor => "factor",
+        Precedence::Unary => "unary",
+        Precedence::Call | Precedence::Primary => "call",
+    }
+}
+
+fn quoted(s: &str) -> String {
+    format!("\"{}\"", s)
+}
+
+/// spellings of a token, its symbol + any keyword the lexer maps 2 it (`!` + `not`)
+fn spellings(kind: &TokenKind) -> Vec<String> {
+    kind.symbol()
+        .into_iter()
+        .chain(KEYWORDS.iter().filter(|(_, k)| k == kind).map(|(word, _)| *word))
+        .map(quoted)
+        .collect()
+}
+
+fn alternatives(kinds: &[&TokenKind]) -> String {
+    let spelled: Vec<_> = kinds.iter().flat_map(|k| spellings(k)).collect();
+    if spelled.len() == 1 {
+        spelled[0].clone()
+    } else {
+        format!("( {} )", spelled.join(" | "))
+    }
+}
+
+/// the binary lvls frm loosest 2 tightest, w/ their operators
+fn binary_levels() -> Vec<(Precedence, Vec<&'static TokenKind>)> {
+    let mut levels: Vec<(Precedence, Vec<&TokenKind>)> = Vec::new();
+    for (token, _, precedence) in BINARY_OPERATORS {
+        match levels.iter_mut().find(|(p, _)| p == precedence) {
+            Some((_, tokens)) => tokens.push(token),
+            None => levels.push((*precedence, vec![token])),
+        }
+    }
+    levels.sort_by_key(|(precedence, _)| *precedence);
+    levels
+}
+
+/// the whole grammar in w3c ebnf (`name ::= ...`), 4 `edition`
+pub fn ebnf(edition: Edition) -> String {
+    let available = |r: &&Rule| r.removed_by.is_none_or(|f| !edition.has(f));
+    let mut lines = vec![
+        format!("/* emerald grammar, edition {} */", edition),
+        "/* terminals: IDENT, INT, FLOAT, STRING and CHAR; a keyword is never an IDENT */".to_string(),
+        String::new(),
+    ];
+    let rule_line = |name: &str, body: &str| format!("{} ::= {}", name, body);
+
+    for r in RULES.iter().filter(available) {
+        lines.push(rule_line(r.name, r.body));
+        if r.name == "program" {
+            let items: Vec<_> = RULES.iter().filter(available).filter(|r| r.item).map(|r| r.name).collect();
+            lines.push(rule_line("item", &format!("attribute* ( {} )", items.join(" | "))));
+        }
+    }
+
+    lines.push(String::new());
+    lines.push(rule_line("expression", "assignment"));
+    let levels = binary_levels();
+    let first = levels.first().map_or(Precedence::Unary, |(p, _)| *p);
+    lines.push(rule_line("assignment", &format!("{} ( {} assignment )?", level_name(first), alternatives(&[&TokenKind::Equal]))));
+    for (precedence, tokens) in &levels {
+        let operand = level_name(precedence.next());
+        lines.push(rule_line(level_name(*precedence), &format!("{} ( {} {} )*", operand, alternatives(tokens), operand)));
+    }
+    let unary: Vec<_> = UNARY_OPERATORS.iter().map(|(token, _)| token).collect();
+    let try_prefix = if edition.has(EditionFeature::TryKeyword) { r#" | "try" unary"# } else { "" };
+    lines.push(rule_line("unary", &format!("{} unary{} | call", alternatives(&unary), try_prefix)));
+    lines.push(rule_line("call", "primary postfix*"));
+
+    lines.push(String::new());
+    let added = EDITION_KEYWORDS.iter().filter(|(_, _, feature)| edition.has(*feature)).map(|(word, _, _)| word);
+    let keywords: Vec<_> = KEYWORDS.iter().map(|(word, _)| word).chain(added).map(|word| quoted(word)).collect();
+    let keywords: Vec<_> = keywords.chunks(10).map(|chunk| chunk.join(" | ")).collect();
+    lines.push(rule_line("keyword", &keywords.join("\n    | ")));
+    let reserved: Vec<_> = RESERVED.iter().filter(|w| edition.is_reserved(w)).map(|w| quoted(w)).collect();
+    if !reserved.is_empty() {
+        lines.push(format!("/* reserved for future syntax */\nreserved ::= {}", reserved.join(" | ")));
+    }
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,39 @@
+use crate::frontend::lexer::token::{Token, TokenKind};
+use codespan::Span;
+
+/// a `macro name(params) = expr`, kept as tokens + expanded by the parser where its invoked
+#[derive(Debug, Clone)]
+pub struct Macro {
+    pub params: Vec<String>,
+    pub body: Vec<Token>,
+    pub span: Span,
+}
+
+/// how deep `a!(..)` can expand in2 `b!(..)`, a macro that invokes itself stops here
+pub const MAX_EXPANSION_DEPTH: usize = 64;
+
+impl Macro {
+    /// the body w/ each param replaced by its argument. the arguments + the whole body get parens
+    /// so `square!(1 + 2)` is `((1 + 2) * (1 + 2))`, not `1 + 2 * 1 + 2`. body tokens take the
+    /// call's span, errors in them point at the call + not at evry use of the definition
+    pub fn expand(&self, args: &[Vec<Token>], span: Span) -> Vec<Token> {
+        let paren = |kind| Token { kind, span };
+        let mut tokens = vec![paren(TokenKind::LeftParen)];
+        for token in &self.body {
+            let param = match &token.kind {
+                TokenKind::Identifier(name) => self.params.iter().position(|p| p == name),
+                _ => None,
+            };
+            match param {
+                Some(i) => {
+                    tokens.push(paren(TokenKind::LeftParen));
+                    tokens.extend(args[i].iter().cloned());
+                    tokens.push(paren(TokenKind::RightParen));
+                }
+                None => tokens.push(Token { kind: token.kind.clone(), span }),
+            }
+        }
+        tokens.push(paren(TokenKind::RightParen));
+        tokens
+    }
+}
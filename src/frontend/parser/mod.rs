@@ -1,4 +1,6 @@
+pub mod derive;
 pub mod grammar;
+pub mod macros;
 pub mod precedence;
 pub mod pratt;
 
@@ -1,18 +1,37 @@
 use crate::core::ast::expr::*;
 use crate::core::ast::item::*;
+use crate::core::ast::pattern::*;
 use crate::core::ast::stmt::*;
 use crate::core::ast::types::*;
 use crate::core::ast::Ast;
-use crate::error::{Diagnostic, DiagnosticKind, Reporter};
+use crate::core::edition::{Edition, EditionFeature};
+use crate::core::feature::Feature;
+use crate::core::limits::Limits;
+use crate::core::stack;
+use crate::error::{Desugaring, Diagnostic, DiagnosticKind, Reporter};
 use crate::frontend::lexer::token::{Token, TokenKind};
-use crate::frontend::parser::precedence::Precedence;
+use crate::frontend::parser::macros::{Macro, MAX_EXPANSION_DEPTH};
+use crate::frontend::parser::precedence::{binary_operator, unary_operator, Precedence, POSTFIX_OPERATORS};
 use codespan::{FileId, Span};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 pub struct Parser<'a> {
     tokens: Vec<Token>,
     current: usize,
     file_id: FileId,
     reporter: &'a mut Reporter,
+    edition: Edition,
+    /// `@feature(..)` gates the file turned on so far
+    features: HashSet<Feature>,
+    macros: HashMap<String, Macro>,
+    macro_depth: usize,
+    limits: Limits,
+    /// expressions + statements being parsed inside each other, checked against `limits.nesting`
+    depth: Rc<Cell<usize>>,
+    /// the nesting error is reported once, not again by evry level unwinding
+    too_deep: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -22,21 +41,63 @@ impl<'a> Parser<'a> {
             current: 0,
             file_id,
             reporter,
+            edition: Edition::default(),
+            features: HashSet::new(),
+            macros: HashMap::new(),
+            macro_depth: 0,
+            limits: Limits::default(),
+            depth: Rc::new(Cell::new(0)),
+            too_deep: false,
         }
     }
 
+    /// nesting + array literals past `limits` r errors
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// 1 level deeper til the guard drops, an error past the nesting limit
+    fn enter(&mut self) -> Result<Nesting, ()> {
+        if self.depth.get() >= self.limits.nesting {
+            if !self.too_deep {
+                self.too_deep = true;
+                let message = format!("Nesting is deeper than {} levels", self.limits.nesting);
+                let diagnostic = Diagnostic::error(DiagnosticKind::SyntaxError, self.peek().span, self.file_id, message)
+                    .with_note(Limits::how_to_raise("nesting"));
+                self.reporter.add_diagnostic(diagnostic);
+            }
+            return Err(());
+        }
+        self.depth.set(self.depth.get() + 1);
+        Ok(Nesting(self.depth.clone()))
+    }
+
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
     pub fn parse(&mut self) -> Ast {
         let mut items = Vec::new();
         let start_span = self.peek().span;
 
         while !self.is_at_end() {
-            match self.parse_item() {
-                Ok(item) => items.push(item),
-                Err(_) => {
-                    self.synchronize();
-                }
+            // gates + macros change how the rest of the file parses, neither is an item
+            let parsed = if self.check(&TokenKind::At) && self.peek_attribute_name() == "feature" {
+                self.parse_feature_gate()
+            } else if self.check_macro_definition() {
+                self.parse_macro_definition()
+            } else {
+                self.parse_item().map(|item| items.push(item))
+            };
+            if parsed.is_err() {
+                self.synchronize();
             }
         }
+        for e in crate::frontend::parser::derive::expand_derives(&mut items) {
+            self.reporter.add_diagnostic(Diagnostic::error(DiagnosticKind::SemanticError, e.span, self.file_id, e.message));
+        }
 
         let span = if items.is_empty() {
             start_span
@@ -50,12 +111,19 @@ impl<'a> Parser<'a> {
     fn parse_item(&mut self) -> Result<Item, ()> {
         match self.peek().kind {
             TokenKind::Def => self.parse_function().map(Item::Function),
+            TokenKind::At => self.parse_attributed_item(),
             TokenKind::Struct => self.parse_struct().map(Item::Struct),
+            TokenKind::Enum => self.parse_enum().map(Item::Enum),
             TokenKind::Trait => self.parse_trait().map(Item::Trait),
             TokenKind::Implement => self.parse_trait_impl().map(Item::TraitImpl),
             TokenKind::Module => self.parse_module().map(Item::Module),
             TokenKind::Foreign => self.parse_foreign().map(Item::Foreign),
             TokenKind::Require => self.parse_require().map(Item::Require),
+            TokenKind::Import => self.parse_import().map(Item::Import),
+            TokenKind::Identifier(_) if self.check_ahead_pub_import() => {
+                self.advance(); // pub
+                self.parse_import().map(|i| Item::Import(Import { public: true, ..i }))
+            }
             TokenKind::Use => self.parse_use().map(Item::Use),
             TokenKind::Declare => self.parse_declare(),
             _ => {
@@ -70,6 +138,269 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// `pub` is only a keyword in front of `import`
+    fn check_ahead_pub_import(&self) -> bool {
+        matches!(&self.peek().kind, TokenKind::Identifier(name) if name == "pub")
+            && matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::Import))
+    }
+
+    /// `@derive(Display, Eq)`, `@target_feature("avx2", "fma")` + `@deprecated("reason")` lines b4 an item
+    fn parse_attributed_item(&mut self) -> Result<Item, ()> {
+        let start_span = self.peek().span;
+        let mut derives = Vec::new();
+        let mut target_features = Vec::new();
+        let mut deprecated = None;
+        while self.check(&TokenKind::At) {
+            let name = self.peek_attribute_name();
+            match name.as_str() {
+                "derive" => {
+                    self.advance(); // @
+                    self.advance(); // derive
+                    self.expect(&TokenKind::LeftParen)?;
+                    loop {
+                        derives.push(self.expect_identifier()?);
+                        if !self.check(&TokenKind::Comma) {
+                            break;
+                        }
+                        self.advance();
+                    }
+                    self.expect(&TokenKind::RightParen)?;
+                }
+                "target_feature" => {
+                    self.advance(); // @
+                    self.advance(); // target_feature
+                    self.expect(&TokenKind::LeftParen)?;
+                    loop {
+                        match &self.peek().kind {
+                            TokenKind::StringLiteral(feature) => {
+                                target_features.push(feature.clone());
+                                self.advance();
+                            }
+                            _ => {
+                                self.error("Expected target feature string");
+                                return Err(());
+                            }
+                        }
+                        if !self.check(&TokenKind::Comma) {
+                            break;
+                        }
+                        self.advance();
+                    }
+                    self.expect(&TokenKind::RightParen)?;
+                }
+                "deprecated" => deprecated = Some(self.parse_deprecated()?),
+                _ => {
+                    self.advance(); // @
+                    self.error(&format!("Unknown attribute '@{}'", name));
+                    return Err(());
+                }
+            }
+        }
+
+        match self.peek().kind {
+            TokenKind::Def if derives.is_empty() => {
+                let mut function = self.parse_function()?;
+                function.target_features = target_features;
+                function.deprecated = deprecated;
+                function.span = Span::new(start_span.start(), function.span.end());
+                Ok(Item::Function(function))
+            }
+            TokenKind::Struct if target_features.is_empty() => {
+                let mut s = self.parse_struct()?;
+                s.derives = derives;
+                s.deprecated = deprecated;
+                s.span = Span::new(start_span.start(), s.span.end());
+                Ok(Item::Struct(s))
+            }
+            TokenKind::Enum if target_features.is_empty() && derives.is_empty() => {
+                let mut e = self.parse_enum()?;
+                e.deprecated = deprecated;
+                e.span = Span::new(start_span.start(), e.span.end());
+                Ok(Item::Enum(e))
+            }
+            _ => {
+                if !derives.is_empty() {
+                    self.error("Expected struct after derive attribute");
+                } else if !target_features.is_empty() {
+                    self.error("Expected function after attribute");
+                } else {
+                    self.error("Expected function, struct or enum after deprecated attribute");
+                }
+                Err(())
+            }
+        }
+    }
+
+    /// name after the `@` at the current token, empty if its not an identifier
+    fn peek_attribute_name(&self) -> String {
+        match self.tokens.get(self.current + 1).map(|t| &t.kind) {
+            Some(TokenKind::Identifier(name)) => name.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// `@feature("macros")`, the gates stay on 4 the rest of the file
+    fn parse_feature_gate(&mut self) -> Result<(), ()> {
+        self.advance(); // @
+        self.advance(); // feature
+        self.expect(&TokenKind::LeftParen)?;
+        loop {
+            match self.peek().kind.clone() {
+                TokenKind::StringLiteral(name) => {
+                    match Feature::from_str(&name) {
+                        Some(feature) => {
+                            self.features.insert(feature);
+                        }
+                        None => {
+                            let known: Vec<_> = Feature::ALL.iter().map(|f| f.name()).collect();
+                            self.error(&format!("Unknown feature '{}', expected one of: {}", name, known.join(", ")));
+                        }
+                    }
+                    self.advance();
+                }
+                _ => {
+                    self.error("Expected feature name string");
+                    return Err(());
+                }
+            }
+            if !self.check(&TokenKind::Comma) {
+                break;
+            }
+            self.advance();
+        }
+        self.expect(&TokenKind::RightParen)?;
+        Ok(())
+    }
+
+    /// error unless the file turned `feature` on, the note says how
+    fn require_feature(&mut self, feature: Feature, span: Span) {
+        if self.features.contains(&feature) {
+            return;
+        }
+        let diagnostic = Diagnostic::error(DiagnosticKind::SyntaxError, span, self.file_id, feature.gate_message().to_string())
+            .with_note(format!("add `@feature(\"{}\")` at the top of the file to enable it", feature.name()));
+        self.reporter.add_diagnostic(diagnostic);
+    }
+
+    /// `macro` is only a keyword in front of `name(`
+    fn check_macro_definition(&self) -> bool {
+        let kind = |n: usize| self.tokens.get(self.current + n).map(|t| &t.kind);
+        matches!(kind(0), Some(TokenKind::Identifier(m)) if m == "macro")
+            && matches!(kind(1), Some(TokenKind::Identifier(_)))
+            && matches!(kind(2), Some(TokenKind::LeftParen))
+    }
+
+    /// `macro square(x) = x * x`, the body is kept as tokens 4 `square!(..)` 2 expand
+    fn parse_macro_definition(&mut self) -> Result<(), ()> {
+        let start_span = self.advance().span; // macro
+        self.require_feature(Feature::Macros, start_span);
+        let name_span = self.peek().span;
+        let name = self.expect_identifier()?;
+        self.expect(&TokenKind::LeftParen)?;
+        let mut params = Vec::new();
+        if !self.check(&TokenKind::RightParen) {
+            loop {
+                params.push(self.expect_identifier()?);
+                if !self.check(&TokenKind::Comma) {
+                    break;
+                }
+                self.advance();
+            }
+        }
+        self.expect(&TokenKind::RightParen)?;
+        self.expect(&TokenKind::Equal)?;
+        let from = self.current;
+        self.parse_expression()?;
+        let body = self.tokens[from..self.current].to_vec();
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        if self.macros.insert(name.clone(), Macro { params, body, span }).is_some() {
+            let message = format!("Macro '{}' is already defined", name);
+            self.reporter.add_diagnostic(Diagnostic::error(DiagnosticKind::SyntaxError, name_span, self.file_id, message));
+        }
+        Ok(())
+    }
+
+    /// `name!(args)` is swapped 4 the macro's expansion in the token stream + parsed in its place
+    fn parse_macro_invocation(&mut self, name: &str, start_span: Span) -> Result<Expr, ()> {
+        let start = self.current - 1;
+        self.advance(); // !
+        self.expect(&TokenKind::LeftParen)?;
+        let mut args = Vec::new();
+        if !self.check(&TokenKind::RightParen) {
+            loop {
+                let from = self.current;
+                self.parse_expression()?;
+                args.push(self.tokens[from..self.current].to_vec());
+                if !self.check(&TokenKind::Comma) {
+                    break;
+                }
+                self.advance();
+            }
+        }
+        self.expect(&TokenKind::RightParen)?;
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        let expected = self.macros[name].params.len();
+        let message = if args.len() != expected {
+            format!("Macro '{}' takes {} argument(s) but {} were given", name, expected, args.len())
+        } else if self.macro_depth >= MAX_EXPANSION_DEPTH {
+            format!("Macro '{}' expands more than {} levels deep", name, MAX_EXPANSION_DEPTH)
+        } else {
+            let expansion = self.macros[name].expand(&args, span);
+            self.record_expansion(name, span);
+            self.tokens.splice(start..self.current, expansion);
+            self.current = start;
+            self.macro_depth += 1;
+            let expr = self.parse_prefix();
+            self.macro_depth -= 1;
+            return expr;
+        };
+        self.reporter.add_diagnostic(Diagnostic::error(DiagnosticKind::SyntaxError, span, self.file_id, message));
+        Err(())
+    }
+
+    /// kept out of `parse_prefix` like `record_expansion`, a macro recursing in2 itself puts a
+    /// `parse_prefix` frame on the stack per level
+    fn array_literal(&mut self, literal: ArrayLiteralExpr) -> Result<Expr, ()> {
+        let count = array_elements(&literal);
+        if count > self.limits.array_elements {
+            let message = format!("Array literal has {} elements, over the limit of {}", count, self.limits.array_elements);
+            let diagnostic = Diagnostic::error(DiagnosticKind::SyntaxError, literal.span, self.file_id, message)
+                .with_note(Limits::how_to_raise("array-elements"));
+            self.reporter.add_diagnostic(diagnostic);
+            return Err(());
+        }
+        Ok(Expr::ArrayLiteral(literal))
+    }
+
+    /// kept out of `parse_macro_invocation`, its frame is on the stack once per nested expansion
+    fn record_expansion(&mut self, name: &str, span: Span) {
+        let desugaring = Desugaring::Macro { name: name.to_string(), definition: self.macros[name].span };
+        self.reporter.source_map_mut().record(self.file_id, span, desugaring);
+    }
+
+    /// `@deprecated` or `@deprecated("use x instead")`
+    fn parse_deprecated(&mut self) -> Result<Deprecation, ()> {
+        let start_span = self.advance().span; // @
+        self.advance(); // deprecated
+        let mut reason = None;
+        if self.check(&TokenKind::LeftParen) {
+            self.advance(); // (
+            match &self.peek().kind {
+                TokenKind::StringLiteral(r) => {
+                    reason = Some(r.clone());
+                    self.advance();
+                }
+                _ => {
+                    self.error("Expected deprecation reason string");
+                    return Err(());
+                }
+            }
+            self.expect(&TokenKind::RightParen)?;
+        }
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        Ok(Deprecation { reason, span })
+    }
+
     fn parse_function(&mut self) -> Result<Function, ()> {
         let start_span = self.advance().span; // def
         let name = self.expect_identifier_or_keyword()?;
@@ -116,6 +447,8 @@ impl<'a> Parser<'a> {
             return_type,
             body,
             uses,
+            target_features: Vec::new(),
+            deprecated: None,
             span,
         })
     }
@@ -133,6 +466,7 @@ impl<'a> Parser<'a> {
                         params.push(Param {
                             name,
                             type_: Type::Primitive(crate::core::ast::types::PrimitiveType::Void),
+                            comptime: false,
                             span: self.previous().span,
                         });
                         if !self.check(&TokenKind::Comma) {
@@ -151,6 +485,7 @@ impl<'a> Parser<'a> {
                     params.push(Param {
                         name,
                         type_,
+                        comptime: false,
                         span,
                     });
                     if !self.check(&TokenKind::Comma) {
@@ -186,6 +521,10 @@ impl<'a> Parser<'a> {
                         break; // Exit loop, ellipsis handled
                     }
                     
+                    let comptime = self.check(&TokenKind::Comptime);
+                    if comptime {
+                        self.advance(); // comptime
+                    }
                     let name = self.expect_identifier_or_keyword()?;
                     // require explicit type annotation for all parameters
                     if !self.check(&TokenKind::Colon) {
@@ -198,6 +537,7 @@ impl<'a> Parser<'a> {
                     params.push(Param {
                         name,
                         type_,
+                        comptime,
                         span,
                     });
 
@@ -326,6 +666,7 @@ impl<'a> Parser<'a> {
                 params.push(Param {
                     name,
                     type_,
+                    comptime: false,
                     span,
                 });
 
@@ -352,7 +693,7 @@ impl<'a> Parser<'a> {
             }
             self.expect(&TokenKind::Identifier("Type".to_string()))?; // type kywrd
             let name = self.expect_identifier_or_keyword()?;
-            let constraint = if self.check(&TokenKind::Identifier("for".to_string())) {
+            let constraint = if self.check(&TokenKind::For) {
                 self.advance();
                 Some(self.expect_identifier()?)
             } else {
@@ -381,13 +722,20 @@ impl<'a> Parser<'a> {
         let mut fields = Vec::new();
 
         while !self.check(&TokenKind::End) && !self.is_at_end() {
+            let deprecated = if self.check(&TokenKind::At) && self.peek_attribute_name() == "deprecated" {
+                Some(self.parse_deprecated()?)
+            } else {
+                None
+            };
+            let field_start = self.peek().span;
             let field_name = self.expect_identifier_or_keyword()?;
             self.expect(&TokenKind::Colon)?;
             let type_ = self.parse_type()?;
-            let span = self.previous().span;
+            let span = Span::new(field_start.start(), self.previous().span.end());
             fields.push(Field {
                 name: field_name,
                 type_,
+                deprecated,
                 span,
             });
         }
@@ -398,15 +746,47 @@ impl<'a> Parser<'a> {
             name,
             generics,
             fields,
+            derives: Vec::new(),
+            deprecated: None,
             span,
         })
     }
 
+    fn parse_enum(&mut self) -> Result<Enum, ()> {
+        let start_span = self.advance().span; // enum
+        let name = self.expect_identifier_or_keyword()?;
+        let mut variants = Vec::new();
+
+        while !self.check(&TokenKind::End) && !self.is_at_end() {
+            let variant_start = self.peek().span;
+            let variant_name = self.expect_identifier()?;
+            let mut fields = Vec::new();
+            if self.check(&TokenKind::LeftParen) {
+                self.advance();
+                while !self.check(&TokenKind::RightParen) {
+                    fields.push(self.parse_type()?);
+                    if !self.check(&TokenKind::Comma) {
+                        break;
+                    }
+                    self.advance();
+                }
+                self.expect(&TokenKind::RightParen)?;
+            }
+            let span = Span::new(variant_start.start(), self.previous().span.end());
+            variants.push(EnumVariant { name: variant_name, fields, span });
+        }
+
+        self.expect(&TokenKind::End)?;
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        Ok(Enum { name, variants, deprecated: None, span })
+    }
+
     fn parse_trait(&mut self) -> Result<Trait, ()> {
         let start_span = self.advance().span; // trait
         let name = self.expect_identifier_or_keyword()?;
         let generics = self.parse_generics()?;
         let mut methods = Vec::new();
+        let mut consts = Vec::new();
 
         // parse methods until we hit end
         loop {
@@ -416,6 +796,8 @@ impl<'a> Parser<'a> {
             if self.check(&TokenKind::Def) {
                 let method = self.parse_trait_method()?;
                 methods.push(method);
+            } else if self.check(&TokenKind::Const) {
+                consts.push(self.parse_assoc_const()?);
             } else {
                 // unxpctd token break and let expect handle the err
                 break;
@@ -428,10 +810,27 @@ impl<'a> Parser<'a> {
             name,
             generics,
             methods,
+            consts,
             span,
         })
     }
 
+    /// `const NAME : Type` w/ an optional `= value`
+    fn parse_assoc_const(&mut self) -> Result<AssocConst, ()> {
+        let start_span = self.advance().span; // const
+        let name = self.expect_identifier_or_keyword()?;
+        self.expect(&TokenKind::Colon)?;
+        let type_ = self.parse_type()?;
+        let value = if self.check(&TokenKind::Equal) {
+            self.advance();
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        Ok(AssocConst { name, type_, value, span })
+    }
+
     fn parse_trait_method(&mut self) -> Result<TraitMethod, ()> {
         self.advance(); // def
         let name = self.expect_identifier_or_keyword()?;
@@ -458,11 +857,14 @@ impl<'a> Parser<'a> {
         let type_name = self.expect_identifier()?;
         let generics = self.parse_generics()?;
         let mut methods = Vec::new();
+        let mut consts = Vec::new();
 
         while !self.check(&TokenKind::End) && !self.is_at_end() {
             if self.check(&TokenKind::Def) {
                 let method = self.parse_function()?;
                 methods.push(method);
+            } else if self.check(&TokenKind::Const) {
+                consts.push(self.parse_assoc_const()?);
             } else {
                 self.advance();
             }
@@ -475,6 +877,7 @@ impl<'a> Parser<'a> {
             type_name,
             generics,
             methods,
+            consts,
             span,
         })
     }
@@ -573,9 +976,44 @@ impl<'a> Parser<'a> {
             return Err(());
         };
         let span = Span::new(start_span.start(), self.previous().span.end());
+        // still parsed so the rest of the file gets checked
+        if self.edition.has(EditionFeature::ImportOnly) {
+            let message = format!("'require' was replaced by 'import' in edition {}", self.edition);
+            self.reporter.add_diagnostic(Diagnostic::error(DiagnosticKind::SyntaxError, span, self.file_id, message));
+        }
         Ok(Require { path, span })
     }
 
+    /// `import name` or `import dir::name`
+    fn parse_import(&mut self) -> Result<Import, ()> {
+        let start_span = self.advance().span; // import
+        let mut path = vec![self.expect_identifier_or_keyword()?];
+        while self.check(&TokenKind::ColonColon) {
+            self.advance(); // ::
+            path.push(self.expect_identifier_or_keyword()?);
+        }
+        let members = if self.check(&TokenKind::LeftParen) {
+            self.advance(); // (
+            let mut members = vec![self.expect_identifier_or_keyword()?];
+            while self.check(&TokenKind::Comma) {
+                self.advance(); // ,
+                members.push(self.expect_identifier_or_keyword()?);
+            }
+            self.expect(&TokenKind::RightParen)?;
+            Some(members)
+        } else {
+            None
+        };
+        let alias = if matches!(&self.peek().kind, TokenKind::Identifier(a) if a == "as") {
+            self.advance(); // as
+            Some(self.expect_identifier()?)
+        } else {
+            None
+        };
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        Ok(Import { path, members, alias, public: false, span })
+    }
+
     fn parse_use(&mut self) -> Result<Use, ()> {
         let start_span = self.advance().span; // use
         let mut path = Vec::new();
@@ -671,6 +1109,18 @@ impl<'a> Parser<'a> {
                 self.advance();
                 Type::Primitive(PrimitiveType::Char)
             }
+            TokenKind::UByte => {
+                self.advance();
+                Type::Primitive(PrimitiveType::UByte)
+            }
+            TokenKind::UInt => {
+                self.advance();
+                Type::Primitive(PrimitiveType::UInt)
+            }
+            TokenKind::ULong => {
+                self.advance();
+                Type::Primitive(PrimitiveType::ULong)
+            }
             TokenKind::String => {
                 self.advance();
                 Type::Named(NamedType { name: "string".to_string(), generics: Vec::new() })
@@ -727,6 +1177,43 @@ impl<'a> Parser<'a> {
                 self.error("Unexpected [ in type position");
                 return Err(());
             }
+            // anonymous record: { x: int, y: int }
+            TokenKind::LeftBrace => {
+                self.advance(); // {
+                let mut fields = Vec::new();
+                while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+                    let name = self.expect_identifier()?;
+                    self.expect(&TokenKind::Colon)?;
+                    fields.push((name, self.parse_type()?));
+                    if !self.check(&TokenKind::Comma) {
+                        break;
+                    }
+                    self.advance(); // ,
+                }
+                self.expect(&TokenKind::RightBrace)?;
+                Type::Record(fields)
+            }
+            // tuple: (int, float), (int,) 4 1 element
+            TokenKind::LeftParen => {
+                self.advance(); // (
+                let mut elements = Vec::new();
+                while !self.check(&TokenKind::RightParen) && !self.is_at_end() {
+                    elements.push(self.parse_type()?);
+                    if !self.check(&TokenKind::Comma) {
+                        break;
+                    }
+                    self.advance(); // ,
+                }
+                self.expect(&TokenKind::RightParen)?;
+                Type::Tuple(elements)
+            }
+            // `dyn` is only a keyword in front of a trait name
+            TokenKind::Identifier(ref d)
+                if d == "dyn" && matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::Identifier(_))) =>
+            {
+                self.advance(); // dyn
+                Type::TraitObject(self.expect_identifier()?)
+            }
             TokenKind::Identifier(_) => {
                 let name = if let TokenKind::Identifier(n) = self.advance().kind.clone() {
                     n
@@ -799,8 +1286,9 @@ impl<'a> Parser<'a> {
             }
         };
         
-        // chk if array type follows the base type: int[10], string[5], etc
-        let final_type = if self.check(&TokenKind::LeftBracket) {
+        // chk if array dims follow the base type: int[10], string[5], int[10][20], etc
+        let mut dims = Vec::new();
+        while self.check(&TokenKind::LeftBracket) {
             self.advance(); // [
             let size = if matches!(self.peek().kind, TokenKind::IntLiteral(_)) {
                 if let TokenKind::IntLiteral(n) = self.advance().kind.clone() {
@@ -812,15 +1300,33 @@ impl<'a> Parser<'a> {
                 None
             };
             self.expect(&TokenKind::RightBracket)?;
-            Type::Array(ArrayType {
-                element: Box::new(base_type),
-                size,
-            })
-        } else {
-            base_type
-        };
+            dims.push(size);
+        }
         
-        Ok(final_type)
+        let type_ = Self::nest_array_dims(base_type, &dims);
+        // `or` is only a word here, the value type of a fn that can fail
+        if matches!(&self.peek().kind, TokenKind::Identifier(word) if word == "or") {
+            self.advance(); // or
+            let error = self.parse_type()?;
+            return Ok(Type::Fallible(FallibleType { value: Box::new(type_), error: Box::new(error) }));
+        }
+        Ok(type_)
+    }
+
+    // row-major: int[10][20] is 10 rows of int[20] so later dims nest innermost
+    fn nest_array_dims(base: Type, dims: &[Option<usize>]) -> Type {
+        match base {
+            Type::Array(a) if !dims.is_empty() => Type::Array(ArrayType {
+                element: Box::new(Self::nest_array_dims(*a.element, dims)),
+                size: a.size,
+            }),
+            base => dims.iter().rev().fold(base, |element, size| {
+                Type::Array(ArrayType {
+                    element: Box::new(element),
+                    size: *size,
+                })
+            }),
+        }
     }
 
     fn parse_block_stmts(&mut self) -> Result<Vec<Stmt>, ()> {
@@ -833,6 +1339,18 @@ impl<'a> Parser<'a> {
         Ok(stmts)
     }
 
+    /// statements up 2 the end of input, a repl entry thats not a declaration
+    pub fn parse_statements(&mut self) -> Vec<Stmt> {
+        let mut stmts = Vec::new();
+        while !self.is_at_end() {
+            match self.parse_stmt() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(_) => self.synchronize(),
+            }
+        }
+        stmts
+    }
+
     fn parse_stmts_until_end(&mut self) -> Result<Vec<Stmt>, ()> {
         let mut stmts = Vec::new();
         while !self.check(&TokenKind::End) && !self.is_at_end() {
@@ -855,11 +1373,21 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_stmt(&mut self) -> Result<Stmt, ()> {
+        stack::grow(|| self.parse_stmt_inner())
+    }
+
+    fn parse_stmt_inner(&mut self) -> Result<Stmt, ()> {
+        let _nesting = self.enter()?;
         match self.peek().kind {
             TokenKind::Return => self.parse_return().map(Stmt::Return),
+            TokenKind::If if self.check_ahead_let() => self.parse_if_let().map(Stmt::Match),
             TokenKind::If => self.parse_if_stmt().map(Stmt::If),
+            TokenKind::While if self.check_ahead_let() => self.parse_while_let().map(Stmt::While),
             TokenKind::While => self.parse_while().map(Stmt::While),
             TokenKind::For => self.parse_for().map(Stmt::For),
+            TokenKind::Match => self.parse_match().map(Stmt::Match),
+            TokenKind::Guard => self.parse_guard().map(Stmt::Guard),
+            TokenKind::Defer => self.parse_defer().map(Stmt::Defer),
             TokenKind::Break => {
                 let span = self.advance().span;
                 Ok(Stmt::Break(BreakStmt { span }))
@@ -873,8 +1401,9 @@ impl<'a> Parser<'a> {
                 if self.check(&TokenKind::Mut) || self.check_ahead_identifier_colon() {
                     self.parse_let().map(Stmt::Let)
                 } else {
-                    self.parse_expression()
-                        .map(|e| Stmt::Expr(ExprStmt { expr: e, span: self.previous().span }))
+                    let expr = self.parse_expression()?;
+                    let expr = if self.check(&TokenKind::Comma) { self.parse_destructure(expr)? } else { expr };
+                    Ok(Stmt::Expr(ExprStmt { expr, span: self.previous().span }))
                 }
             }
             _ => self
@@ -883,6 +1412,53 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// `a, b = pair` or `a, b = b, a`, an assignment 2 a tuple of targets. `first` is the
+    /// target b4 the 1st comma
+    fn parse_destructure(&mut self, first: Expr) -> Result<Expr, ()> {
+        let start = first.span();
+        let mut targets = vec![first];
+        while self.check(&TokenKind::Comma) {
+            self.advance(); // ,
+            targets.push(self.parse_precedence(Precedence::Or)?);
+        }
+        let target_span = Span::new(start.start(), self.previous().span.end());
+        self.expect(&TokenKind::Equal)?;
+        let value = self.parse_expression()?;
+        let value = if self.check(&TokenKind::Comma) {
+            let value_start = value.span();
+            let mut elements = vec![value];
+            while self.check(&TokenKind::Comma) {
+                self.advance(); // ,
+                elements.push(self.parse_expression()?);
+            }
+            let span = Span::new(value_start.start(), self.previous().span.end());
+            Expr::Tuple(TupleExpr { elements, span })
+        } else {
+            value
+        };
+        let span = Span::new(start.start(), value.span().end());
+        Ok(Expr::Assignment(AssignmentExpr {
+            target: Box::new(Expr::Tuple(TupleExpr { elements: targets, span: target_span })),
+            value: Box::new(value),
+            span,
+        }))
+    }
+
+    /// `a, b =` starts the next stmt, its names arent args of a call w/o parens
+    fn check_ahead_destructure(&self) -> bool {
+        let mut at = self.current;
+        let mut targets = 0;
+        while matches!(self.tokens.get(at).map(|t| &t.kind), Some(TokenKind::Identifier(_))) {
+            targets += 1;
+            match self.tokens.get(at + 1).map(|t| &t.kind) {
+                Some(TokenKind::Comma) => at += 2,
+                Some(TokenKind::Equal) => return targets > 1,
+                _ => return false,
+            }
+        }
+        false
+    }
+
     fn check_ahead_identifier_colon(&self) -> bool {
         if let Some(token) = self.tokens.get(self.current) {
             if matches!(token.kind, TokenKind::Identifier(_)) {
@@ -979,6 +1555,216 @@ impl<'a> Parser<'a> {
         })
     }
 
+    fn check_ahead_let(&self) -> bool {
+        matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::Let))
+    }
+
+    /// `let P = value` after `if` / `while`, the pattern + the value it's matched against
+    fn parse_let_binding(&mut self) -> Result<(Pattern, Expr), ()> {
+        self.advance(); // let
+        let pattern = self.parse_pattern()?;
+        self.expect(&TokenKind::Equal)?;
+        let value = self.parse_expression()?;
+        Ok((pattern, value))
+    }
+
+    /// `if let P = value .. else .. end` is a match w/ 1 arm, w/o an else the else arm is empty
+    fn parse_if_let(&mut self) -> Result<MatchStmt, ()> {
+        let start_span = self.advance().span; // if
+        let (pattern, scrutinee) = self.parse_let_binding()?;
+        let arm_start = self.peek().span;
+        let mut body = Vec::new();
+        while !self.check(&TokenKind::Else) && !self.check(&TokenKind::End) && !self.is_at_end() {
+            body.push(self.parse_stmt()?);
+        }
+        let arm_span = Span::new(arm_start.start(), self.previous().span.end());
+        let mut else_branch = Vec::new();
+        if self.check(&TokenKind::Else) {
+            self.advance();
+            while !self.check(&TokenKind::End) && !self.is_at_end() {
+                else_branch.push(self.parse_stmt()?);
+            }
+        }
+        self.expect(&TokenKind::End)?;
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        self.reporter.source_map_mut().record(self.file_id, span, Desugaring::IfLet);
+        Ok(MatchStmt {
+            scrutinee,
+            arms: vec![MatchArm { patterns: vec![pattern], body, span: arm_span }],
+            else_branch: Some(else_branch),
+            span,
+        })
+    }
+
+    /// `while let P = value .. end` -> `while true` around a match that breaks once P stops matching,
+    /// value is re-evaluated evry iteration
+    fn parse_while_let(&mut self) -> Result<WhileStmt, ()> {
+        let start_span = self.advance().span; // whl
+        let (pattern, scrutinee) = self.parse_let_binding()?;
+        let arm_start = self.peek().span;
+        let body = self.parse_stmts_until_end()?;
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        self.reporter.source_map_mut().record(self.file_id, span, Desugaring::WhileLet);
+        let matched = Stmt::Match(MatchStmt {
+            scrutinee,
+            arms: vec![MatchArm { patterns: vec![pattern], body, span: Span::new(arm_start.start(), span.end()) }],
+            else_branch: Some(vec![Stmt::Break(BreakStmt { span })]),
+            span,
+        });
+        Ok(WhileStmt {
+            condition: Expr::Literal(LiteralExpr { kind: LiteralKind::Bool(true), span: start_span }),
+            body: vec![matched],
+            span,
+        })
+    }
+
+    /// `guard cond else .. end`, the else block has 2 leave the enclosing fn or loop
+    fn parse_guard(&mut self) -> Result<GuardStmt, ()> {
+        let start_span = self.advance().span; // guard
+        let condition = self.parse_expression()?;
+        self.expect(&TokenKind::Else)?;
+        let mut else_branch = Vec::new();
+        while !self.check(&TokenKind::End) && !self.is_at_end() {
+            else_branch.push(self.parse_stmt()?);
+        }
+        self.expect(&TokenKind::End)?;
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        Ok(GuardStmt { condition, else_branch, span })
+    }
+
+    /// `defer expr` or `defer do .. end`, the body runs when the enclosing scope exits. a `do`
+    /// w/o params is a block here, a closure that's never called would b pointless
+    fn parse_defer(&mut self) -> Result<DeferStmt, ()> {
+        let start_span = self.advance().span; // defer
+        let body = if self.check(&TokenKind::Do) && !matches!(self.tokens.get(self.current + 1).map(|t| &t.kind), Some(TokenKind::Pipe)) {
+            self.advance(); // do
+            let mut body = Vec::new();
+            while !self.check(&TokenKind::End) && !self.is_at_end() {
+                body.push(self.parse_stmt()?);
+            }
+            self.expect(&TokenKind::End)?;
+            body
+        } else {
+            let expr = self.parse_expression()?;
+            vec![Stmt::Expr(ExprStmt { span: expr.span(), expr })]
+        };
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        Ok(DeferStmt { body, span })
+    }
+
+    fn parse_match(&mut self) -> Result<MatchStmt, ()> {
+        let start_span = self.advance().span; // match
+        let scrutinee = self.parse_expression()?;
+        let mut arms = Vec::new();
+        while self.check(&TokenKind::When) {
+            let arm_start = self.advance().span;
+            let mut patterns = vec![self.parse_pattern()?];
+            while self.check(&TokenKind::Comma) {
+                self.advance();
+                patterns.push(self.parse_pattern()?);
+            }
+            let mut body = Vec::new();
+            while !self.check(&TokenKind::When) && !self.check(&TokenKind::Else) && !self.check(&TokenKind::End) && !self.is_at_end() {
+                body.push(self.parse_stmt()?);
+            }
+            let span = Span::new(arm_start.start(), self.previous().span.end());
+            arms.push(MatchArm { patterns, body, span });
+        }
+        if arms.is_empty() {
+            self.error("Expected 'when' after match scrutinee");
+            return Err(());
+        }
+        let else_branch = if self.check(&TokenKind::Else) {
+            self.advance();
+            let mut stmts = Vec::new();
+            while !self.check(&TokenKind::End) && !self.is_at_end() {
+                stmts.push(self.parse_stmt()?);
+            }
+            Some(stmts)
+        } else {
+            None
+        };
+        self.expect(&TokenKind::End)?;
+        let span = Span::new(start_span.start(), self.previous().span.end());
+        Ok(MatchStmt { scrutinee, arms, else_branch, span })
+    }
+
+    /// literal or `lo .. hi` integer range, ints can b negative
+    fn parse_pattern(&mut self) -> Result<Pattern, ()> {
+        let start = self.peek().span;
+        if matches!(self.peek().kind, TokenKind::Identifier(_)) {
+            return self.parse_variant_pattern().map(Pattern::Variant);
+        }
+        let kind = match self.peek().kind.clone() {
+            TokenKind::Minus => {
+                self.advance();
+                match self.peek().kind {
+                    TokenKind::IntLiteral(v) => LiteralKind::Int(-v),
+                    _ => {
+                        self.error("Expected integer after '-' in pattern");
+                        return Err(());
+                    }
+                }
+            }
+            TokenKind::IntLiteral(v) => LiteralKind::Int(v),
+            TokenKind::BoolLiteral(b) => LiteralKind::Bool(b),
+            TokenKind::CharLiteral(c) => LiteralKind::Char(c),
+            TokenKind::StringLiteral(s) => LiteralKind::String(s),
+            _ => {
+                self.error("Expected literal or range pattern");
+                return Err(());
+            }
+        };
+        self.advance();
+        let literal = LiteralExpr { kind, span: Span::new(start.start(), self.previous().span.end()) };
+        if !self.check(&TokenKind::DotDot) {
+            return Ok(Pattern::Literal(literal));
+        }
+        let LiteralKind::Int(lo) = literal.kind else {
+            self.error("Range patterns need integer bounds");
+            return Err(());
+        };
+        self.advance(); // ..
+        let negative = self.check(&TokenKind::Minus);
+        if negative {
+            self.advance();
+        }
+        let TokenKind::IntLiteral(hi) = self.peek().kind else {
+            self.error("Expected integer upper bound in range pattern");
+            return Err(());
+        };
+        self.advance();
+        Ok(Pattern::Range(RangePattern {
+            start: lo,
+            end: if negative { -hi } else { hi },
+            span: Span::new(start.start(), self.previous().span.end()),
+        }))
+    }
+
+    fn parse_variant_pattern(&mut self) -> Result<VariantPattern, ()> {
+        let start = self.peek().span;
+        let mut enum_name = None;
+        let mut variant = self.expect_identifier()?;
+        if self.check(&TokenKind::ColonColon) {
+            self.advance();
+            enum_name = Some(variant);
+            variant = self.expect_identifier()?;
+        }
+        let mut bindings = Vec::new();
+        if self.check(&TokenKind::LeftParen) {
+            self.advance();
+            while !self.check(&TokenKind::RightParen) {
+                bindings.push(self.expect_identifier()?);
+                if !self.check(&TokenKind::Comma) {
+                    break;
+                }
+                self.advance();
+            }
+            self.expect(&TokenKind::RightParen)?;
+        }
+        Ok(VariantPattern { enum_name, variant, bindings, span: Span::new(start.start(), self.previous().span.end()) })
+    }
+
     fn parse_while(&mut self) -> Result<WhileStmt, ()> {
         let start_span = self.advance().span; // whl
         let condition = self.parse_expression()?;
@@ -1032,6 +1818,11 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_precedence(&mut self, precedence: Precedence) -> Result<Expr, ()> {
+        stack::grow(|| self.parse_operators(precedence))
+    }
+
+    fn parse_operators(&mut self, precedence: Precedence) -> Result<Expr, ()> {
+        let _nesting = self.enter()?;
         let mut expr = self.parse_prefix()?;
         
         if (precedence == Precedence::Call || precedence == Precedence::Assignment)
@@ -1092,20 +1883,23 @@ impl<'a> Parser<'a> {
 
     fn parse_prefix(&mut self) -> Result<Expr, ()> {
         let kind = self.peek().kind.clone();
+        if let Some(op) = unary_operator(&kind) {
+            self.advance();
+            let expr = self.parse_precedence(Precedence::Unary)?;
+            let span = self.previous().span;
+            return Ok(Expr::Unary(UnaryExpr {
+                op,
+                expr: Box::new(expr),
+                span,
+            }));
+        }
         match kind {
-            TokenKind::Minus | TokenKind::Not => {
-                let op = match self.advance().kind {
-                    TokenKind::Minus => UnaryOp::Neg,
-                    TokenKind::Not => UnaryOp::Not,
-                    _ => return Err(()),
-                };
+            // binds like `-` so `try f() + 1` adds 2 the value
+            TokenKind::Try => {
+                let start = self.advance().span;
                 let expr = self.parse_precedence(Precedence::Unary)?;
-                let span = self.previous().span;
-                Ok(Expr::Unary(UnaryExpr {
-                    op,
-                    expr: Box::new(expr),
-                    span,
-                }))
+                let span = Span::new(start.start(), expr.span().end());
+                Ok(Expr::Try(TryExpr { expr: Box::new(expr), span }))
             }
             TokenKind::IntLiteral(n) => {
                 let span = self.advance().span;
@@ -1153,6 +1947,9 @@ impl<'a> Parser<'a> {
                     return Err(());
                 };
                 let start_span = self.previous().span;
+                if self.check(&TokenKind::Not) && self.macros.contains_key(&name) {
+                    return self.parse_macro_invocation(&name, start_span);
+                }
                 // chk 4 module access: Utils::helper
                 if self.check(&TokenKind::ColonColon) {
                     self.advance(); // ::
@@ -1170,7 +1967,7 @@ impl<'a> Parser<'a> {
             }
             TokenKind::Size | TokenKind::Int | TokenKind::Float | TokenKind::Bool 
             | TokenKind::Char | TokenKind::String | TokenKind::Void | TokenKind::Byte 
-            | TokenKind::Long => {
+            | TokenKind::Long | TokenKind::UByte | TokenKind::UInt | TokenKind::ULong => {
                 // Type keywords can be used as identifiers in expressions
                 let name = match self.advance().kind {
                     TokenKind::Size => "size".to_string(),
@@ -1182,24 +1979,47 @@ impl<'a> Parser<'a> {
                     TokenKind::Void => "void".to_string(),
                     TokenKind::Byte => "byte".to_string(),
                     TokenKind::Long => "long".to_string(),
+                    TokenKind::UByte => "ubyte".to_string(),
+                    TokenKind::UInt => "uint".to_string(),
+                    TokenKind::ULong => "ulong".to_string(),
                     _ => return Err(()),
                 };
                 let span = self.previous().span;
                 Ok(Expr::Variable(VariableExpr { name, span }))
             }
             TokenKind::LeftParen => {
-                self.advance(); // (
+                let start_span = self.advance().span; // (
                 let expr = self.parse_expression()?;
+                if !self.check(&TokenKind::Comma) {
+                    self.expect(&TokenKind::RightParen)?;
+                    return Ok(expr);
+                }
+                // a comma makes it a tuple, `(x,)` has 1 element
+                let mut elements = vec![expr];
+                while self.check(&TokenKind::Comma) {
+                    self.advance(); // ,
+                    if self.check(&TokenKind::RightParen) {
+                        break;
+                    }
+                    elements.push(self.parse_expression()?);
+                }
                 self.expect(&TokenKind::RightParen)?;
-                Ok(expr)
+                let span = Span::new(start_span.start(), self.previous().span.end());
+                Ok(Expr::Tuple(TupleExpr { elements, span }))
             }
             TokenKind::LeftBracket => {
-                // array literal: [expr1, expr2, ...]
+                // array literal: [expr1, expr2, ...] or repeat initializer [expr; count]
                 let start_span = self.advance().span; // [
                 let mut elements = Vec::new();
+                let mut repeat = None;
                 if !self.check(&TokenKind::RightBracket) {
                     loop {
                         elements.push(self.parse_expression()?);
+                        if elements.len() == 1 && self.check(&TokenKind::Semicolon) {
+                            self.advance(); // ;
+                            repeat = Some(self.parse_repeat_count()?);
+                            break;
+                        }
                         if !self.check(&TokenKind::Comma) {
                             break;
                         }
@@ -1208,10 +2028,7 @@ impl<'a> Parser<'a> {
                 }
                 self.expect(&TokenKind::RightBracket)?;
                 let span = Span::new(start_span.start(), self.previous().span.end());
-                Ok(Expr::ArrayLiteral(ArrayLiteralExpr {
-                    elements,
-                    span,
-                }))
+                self.array_literal(ArrayLiteralExpr { elements, repeat, span })
             }
             TokenKind::LeftBrace => {
                 let start_span = self.advance().span; // {
@@ -1316,6 +2133,19 @@ impl<'a> Parser<'a> {
                     span,
                 }))
             }
+            TokenKind::New => {
+                let start_span = self.advance().span; // new
+                // at Call so `new Point { x: 1 }` is the struct literal
+                let value = self.parse_precedence(Precedence::Call)?;
+                let span = Span::new(start_span.start(), self.previous().span.end());
+                Ok(Expr::New(NewExpr { value: Box::new(value), span }))
+            }
+            TokenKind::Delete => {
+                let start_span = self.advance().span; // delete
+                let pointer = self.parse_precedence(Precedence::Unary)?;
+                let span = Span::new(start_span.start(), self.previous().span.end());
+                Ok(Expr::Delete(DeleteExpr { pointer: Box::new(pointer), span }))
+            }
             TokenKind::Comptime => {
                 let start_span = self.advance().span; // cmptm
                 let expr = self.parse_expression()?;
@@ -1372,47 +2202,36 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // repeat count in [value; count] must be a non-negative int literal
+    fn parse_repeat_count(&mut self) -> Result<usize, ()> {
+        match self.peek().kind {
+            TokenKind::IntLiteral(n) if n >= 0 => {
+                self.advance();
+                Ok(n as usize)
+            }
+            _ => {
+                self.error("Expected non-negative integer literal as array repeat count");
+                Err(())
+            }
+        }
+    }
+
     fn parse_infix(&mut self, left: Expr, current_precedence: Precedence) -> Result<Expr, ()> {
+        if let Some((op, precedence)) = binary_operator(&self.peek().kind) {
+            self.advance();
+            let right = self.parse_precedence(precedence.next())?;
+            let span = Span::new(left.span().start(), right.span().end());
+            return Ok(Expr::Binary(BinaryExpr {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+                span,
+            }));
+        }
         match self.peek().kind {
-            TokenKind::Plus
-            | TokenKind::Minus
-            | TokenKind::Star
-            | TokenKind::Slash
-            | TokenKind::Percent
-            | TokenKind::EqualEqual
-            | TokenKind::NotEqual
-            | TokenKind::Less
-            | TokenKind::LessEqual
-            | TokenKind::Greater
-            | TokenKind::GreaterEqual
-            | TokenKind::And
-            | TokenKind::Or => {
-                // get precedence bfr advncng so we use the operators precedence not the nxt tokens
-                let precedence = self.get_precedence();
-                let op = match self.advance().kind {
-                    TokenKind::Plus => BinaryOp::Add,
-                    TokenKind::Minus => BinaryOp::Sub,
-                    TokenKind::Star => BinaryOp::Mul,
-                    TokenKind::Slash => BinaryOp::Div,
-                    TokenKind::Percent => BinaryOp::Mod,
-                    TokenKind::EqualEqual => BinaryOp::Eq,
-                    TokenKind::NotEqual => BinaryOp::Ne,
-                    TokenKind::Less => BinaryOp::Lt,
-                    TokenKind::LessEqual => BinaryOp::Le,
-                    TokenKind::Greater => BinaryOp::Gt,
-                    TokenKind::GreaterEqual => BinaryOp::Ge,
-                    TokenKind::And => BinaryOp::And,
-                    TokenKind::Or => BinaryOp::Or,
-                    _ => return Err(()),
-                };
-                let right = self.parse_precedence(precedence.next())?;
-                let span = Span::new(left.span().start(), right.span().end());
-                Ok(Expr::Binary(BinaryExpr {
-                    left: Box::new(left),
-                    op,
-                    right: Box::new(right),
-                    span,
-                }))
+            TokenKind::Question => {
+                let span = Span::new(left.span().start(), self.advance().span.end());
+                Ok(Expr::Try(TryExpr { expr: Box::new(left), span }))
             }
             TokenKind::Equal => {
                 let start = left.span();
@@ -1474,6 +2293,10 @@ impl<'a> Parser<'a> {
                 let field = if self.check(&TokenKind::Exists) {
                     self.advance(); // exists?
                     "exists?".to_string()
+                } else if let TokenKind::IntLiteral(n) = self.peek().kind {
+                    // tuple element, `pair.0`
+                    self.advance();
+                    n.to_string()
                 } else {
                     let field_name = self.expect_identifier()?;
                     // chk if fllwd by ?
@@ -1580,7 +2403,7 @@ impl<'a> Parser<'a> {
     }
 
     fn can_parse_call_without_parens(&self) -> bool {
-        if self.is_at_end() {
+        if self.is_at_end() || self.check_ahead_destructure() {
             return false;
         }
         match self.peek().kind {
@@ -1593,13 +2416,14 @@ impl<'a> Parser<'a> {
             | TokenKind::Dot | TokenKind::Exists | TokenKind::Semicolon
             | TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace
             | TokenKind::Comma | TokenKind::Colon | TokenKind::End | TokenKind::Eof
-            | TokenKind::Returns | TokenKind::Uses
+            | TokenKind::Returns | TokenKind::Uses | TokenKind::DotDot
             // can't be statement keywords
             | TokenKind::Return | TokenKind::If | TokenKind::Else | TokenKind::While
             | TokenKind::For | TokenKind::Break | TokenKind::Continue
-            | TokenKind::Def | TokenKind::Struct | TokenKind::Trait | TokenKind::Implement
-            | TokenKind::Module | TokenKind::Foreign | TokenKind::Require | TokenKind::Use
-            | TokenKind::Declare => false,
+            | TokenKind::Match | TokenKind::When | TokenKind::Guard | TokenKind::Defer
+            | TokenKind::Def | TokenKind::Struct | TokenKind::Enum | TokenKind::Trait | TokenKind::Implement
+            | TokenKind::Module | TokenKind::Foreign | TokenKind::Require | TokenKind::Import | TokenKind::Use
+            | TokenKind::Declare | TokenKind::Delete => false,
             // can be: identifier, literal, do (closure), or other expression starters
             _ => true,
         }
@@ -1612,6 +2436,16 @@ impl<'a> Parser<'a> {
         // parse first arg (required for calls w/o parens)
         // Parse expression that stops at comma, end, semicolon, etc.
         let first_arg = self.parse_argument_expression()?;
+        // Name { field: value } - the brace expr names the struct, not an arg
+        if let (Expr::Variable(v), Expr::StructLiteral(s)) = (&callee, &first_arg) {
+            if s.struct_name.is_empty() && !self.check(&TokenKind::Comma) {
+                return Ok(Expr::StructLiteral(StructLiteralExpr {
+                    struct_name: v.name.clone(),
+                    fields: s.fields.clone(),
+                    span: Span::new(start.start(), s.span.end()),
+                }));
+            }
+        }
         args.push(first_arg);
         
         // parse more args if comma separated
@@ -1651,17 +2485,13 @@ impl<'a> Parser<'a> {
     }
 
     fn get_precedence(&self) -> Precedence {
-        match self.peek().kind {
+        let kind = &self.peek().kind;
+        if let Some((_, precedence)) = binary_operator(kind) {
+            return precedence;
+        }
+        match kind {
             TokenKind::Equal => Precedence::Assignment,
-            TokenKind::Or => Precedence::Or,
-            TokenKind::And => Precedence::And,
-            TokenKind::EqualEqual | TokenKind::NotEqual => Precedence::Equality,
-            TokenKind::Less | TokenKind::LessEqual | TokenKind::Greater | TokenKind::GreaterEqual => {
-                Precedence::Comparison
-            }
-            TokenKind::Plus | TokenKind::Minus => Precedence::Term,
-            TokenKind::Star | TokenKind::Slash | TokenKind::Percent => Precedence::Factor,
-            TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace | TokenKind::Dot | TokenKind::ColonColon => Precedence::Call,
+            kind if POSTFIX_OPERATORS.contains(kind) => Precedence::Call,
             _ => Precedence::None,
         }
     }
@@ -1804,6 +2634,7 @@ impl<'a> Parser<'a> {
             match self.peek().kind {
                 TokenKind::Def
                 | TokenKind::Struct
+                | TokenKind::Enum
                 | TokenKind::Trait
                 | TokenKind::Implement
                 | TokenKind::Module
@@ -1820,3 +2651,25 @@ impl<'a> Parser<'a> {
 
     
 }
+
+/// a level of `Parser::depth`, given back when dropped so every early return out of a level
+/// leaves it. a guard instead of a wrapper fn keeps deep nesting at 1 frame per level
+struct Nesting(Rc<Cell<usize>>);
+
+impl Drop for Nesting {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+/// elements `a` holds once nested literals r expanded, `[[0; 10]; 10]` is 100
+fn array_elements(a: &ArrayLiteralExpr) -> usize {
+    let count = |e: &Expr| match e {
+        Expr::ArrayLiteral(inner) => array_elements(inner),
+        _ => 1,
+    };
+    match a.repeat {
+        Some(n) => a.elements.first().map_or(0, count).saturating_mul(n),
+        None => a.elements.iter().map(count).fold(0, usize::saturating_add),
+    }
+}
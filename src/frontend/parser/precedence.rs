@@ -1,3 +1,6 @@
+use crate::core::ast::expr::{BinaryOp, UnaryOp};
+use crate::frontend::lexer::token::TokenKind;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Precedence {
     None = 0,
@@ -30,3 +33,46 @@ impl Precedence {
         }
     }
 }
+
+/// binary operators + the lvl they bind at, all left-associative. the parser + the exported
+/// grammar both read this so they cant drift apart
+pub const BINARY_OPERATORS: &[(TokenKind, BinaryOp, Precedence)] = &[
+    (TokenKind::Or, BinaryOp::Or, Precedence::Or),
+    (TokenKind::And, BinaryOp::And, Precedence::And),
+    (TokenKind::EqualEqual, BinaryOp::Eq, Precedence::Equality),
+    (TokenKind::NotEqual, BinaryOp::Ne, Precedence::Equality),
+    (TokenKind::Less, BinaryOp::Lt, Precedence::Comparison),
+    (TokenKind::LessEqual, BinaryOp::Le, Precedence::Comparison),
+    (TokenKind::Greater, BinaryOp::Gt, Precedence::Comparison),
+    (TokenKind::GreaterEqual, BinaryOp::Ge, Precedence::Comparison),
+    (TokenKind::Plus, BinaryOp::Add, Precedence::Term),
+    (TokenKind::Minus, BinaryOp::Sub, Precedence::Term),
+    (TokenKind::Star, BinaryOp::Mul, Precedence::Factor),
+    (TokenKind::Slash, BinaryOp::Div, Precedence::Factor),
+    (TokenKind::Percent, BinaryOp::Mod, Precedence::Factor),
+];
+
+/// prefix operators, their operand is parsed at `Precedence::Unary`
+pub const UNARY_OPERATORS: &[(TokenKind, UnaryOp)] = &[
+    (TokenKind::Minus, UnaryOp::Neg),
+    (TokenKind::Not, UnaryOp::Not),
+];
+
+/// tokens that continue an expression at `Precedence::Call`: calls, indexing, struct literals,
+/// field access, paths + `?`
+pub const POSTFIX_OPERATORS: &[TokenKind] = &[
+    TokenKind::LeftParen,
+    TokenKind::LeftBracket,
+    TokenKind::LeftBrace,
+    TokenKind::Dot,
+    TokenKind::ColonColon,
+    TokenKind::Question,
+];
+
+pub fn binary_operator(kind: &TokenKind) -> Option<(BinaryOp, Precedence)> {
+    BINARY_OPERATORS.iter().find(|(token, _, _)| token == kind).map(|(_, op, precedence)| (op.clone(), *precedence))
+}
+
+pub fn unary_operator(kind: &TokenKind) -> Option<UnaryOp> {
+    UNARY_OPERATORS.iter().find(|(token, _)| token == kind).map(|(_, op)| op.clone())
+}
@@ -1,24 +1,52 @@
+use crate::core::ast::item::Import;
 use crate::core::ast::{Ast, Item, Stmt};
+use crate::core::edition::Edition;
+use crate::core::stack;
+use crate::core::target::TargetInfo;
 use crate::core::types::module::ModuleDependencyGraph;
 use crate::error::Reporter;
 use crate::frontend::semantic::borrow_checker::BorrowChecker;
 use crate::frontend::semantic::collector::SymbolCollector;
+use crate::frontend::semantic::definite_init::DefiniteInit;
+use crate::frontend::semantic::deprecation::{decl_line, Deprecated, Deprecations};
 use crate::frontend::semantic::ffi::FfiChecker;
 use crate::frontend::semantic::module_registry::ModuleRegistry;
 use crate::frontend::semantic::module_resolver::ModuleResolver;
-use crate::frontend::semantic::symbol_table::SymbolTable;
+use crate::frontend::semantic::ownership::OwnershipChecker;
+use crate::frontend::semantic::prelude::Prelude;
+use crate::frontend::semantic::symbol_table::{Symbol, SymbolKind, SymbolTable};
 use crate::frontend::semantic::trait_checker::TraitChecker;
-use crate::frontend::semantic::type_checker::TypeChecker;
+use crate::frontend::semantic::type_checker::{Hole, TypeChecker};
 use crate::frontend::semantic::type_resolver::TypeResolver;
-use codespan::FileId;
+use codespan::{ByteIndex, FileId, Span};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+/// (member, symbol, `def` line, `@deprecated`) of a fn a module exports
+type Export = (String, Symbol, (FileId, Span), Option<Deprecated>);
+
 pub struct SemanticAnalyzer<'a> {
     reporter: &'a mut Reporter,
     file_id: FileId,
     module_registry: ModuleRegistry,
     dependency_graph: ModuleDependencyGraph,
     analyzing_modules: Arc<Mutex<std::collections::HashSet<String>>>, // shared state to track modules currently being analyzed across all instances
+    target: TargetInfo,
+    /// (name, registry path) of evry imported module, the ones they import too
+    imported: Vec<(String, String)>,
+    /// `name::fn` symbols of the fns imported modules export
+    imported_symbols: Vec<(String, Symbol)>,
+    /// file + `def` line of each imported fn, 4 "defined here" labels in the importer's errors
+    origins: HashMap<String, (FileId, Span)>,
+    /// imported fns marked `@deprecated`, by their `name::fn`
+    deprecations: Deprecations,
+    /// exprs that failed 2 type check, 4 editor tooling
+    holes: Vec<Hole>,
+    /// put in2 evry imported module's scope, the program's own ast already has it
+    prelude: Option<Prelude>,
+    /// the package's, imported modules r lexed + parsed w/ it
+    edition: Edition,
 }
 
 impl<'a> SemanticAnalyzer<'a> {
@@ -29,9 +57,34 @@ impl<'a> SemanticAnalyzer<'a> {
             module_registry: ModuleRegistry::new(),
             dependency_graph: ModuleDependencyGraph::new(),
             analyzing_modules: Arc::new(Mutex::new(std::collections::HashSet::new())),
+            target: TargetInfo::host(),
+            imported: Vec::new(),
+            imported_symbols: Vec::new(),
+            origins: HashMap::new(),
+            deprecations: Deprecations::default(),
+            holes: Vec::new(),
+            prelude: None,
+            edition: Edition::default(),
         }
     }
 
+    /// analyze 4 the given target instead of the host
+    pub fn with_target(mut self, target: TargetInfo) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// give imported modules the prelude too, see `Prelude::inject`
+    pub fn with_prelude(mut self, prelude: Prelude) -> Self {
+        self.prelude = Some(prelude);
+        self
+    }
+
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
     pub fn analyze(&mut self, ast: &Ast) -> SymbolTable {
         // pass 0: resolve and load modules
         self.resolve_modules(ast);
@@ -39,14 +92,21 @@ impl<'a> SemanticAnalyzer<'a> {
         // pass 1: collect symbls
         let mut collector = SymbolCollector::new(self.reporter, self.file_id);
         let mut symbol_table = collector.collect_symbols(ast);
+        for (name, symbol) in &self.imported_symbols {
+            let _ = symbol_table.define(name.clone(), symbol.clone());
+        }
 
         // pass 2: resolve types
         let mut type_resolver = TypeResolver::new(self.reporter, self.file_id);
         type_resolver.resolve_types(ast, &mut symbol_table);
 
         // pass 3: resolve bds and type chk expressions
-        let mut type_checker = TypeChecker::new(symbol_table.clone(), self.reporter, self.file_id);
+        let mut type_checker = TypeChecker::new(symbol_table.clone(), self.reporter, self.file_id)
+            .with_target(self.target.clone())
+            .with_origins(self.origins.clone())
+            .with_deprecations(self.deprecations.clone());
         type_checker.check(ast);
+        self.holes = type_checker.take_holes();
 
         // pass 4: check trait implementations
         let mut trait_checker = TraitChecker::new(&symbol_table, ast, self.reporter, self.file_id);
@@ -64,6 +124,14 @@ impl<'a> SemanticAnalyzer<'a> {
         let mut borrow_checker = BorrowChecker::new(self.reporter, self.file_id);
         borrow_checker.check(ast);
 
+        // struct fields r set b4 the struct is used
+        let mut definite_init = DefiniteInit::new(self.reporter, self.file_id);
+        definite_init.check(ast);
+
+        // a `new` allocation isnt used after it's moved or deleted
+        let mut ownership = OwnershipChecker::new(self.reporter, self.file_id);
+        ownership.check(ast);
+
         // lifetime checking
         let mut lifetime_checker = crate::frontend::semantic::lifetime_checker::LifetimeChecker::new(self.reporter, self.file_id);
         lifetime_checker.check(ast);
@@ -182,6 +250,22 @@ impl<'a> SemanticAnalyzer<'a> {
                     }
                     Self::track_instantiations_in_stmts(&s.body, specializer, symbol_table);
                 }
+                Stmt::Guard(s) => {
+                    Self::track_instantiations_in_expr(&s.condition, specializer, symbol_table);
+                    Self::track_instantiations_in_stmts(&s.else_branch, specializer, symbol_table);
+                }
+                Stmt::Defer(s) => {
+                    Self::track_instantiations_in_stmts(&s.body, specializer, symbol_table);
+                }
+                Stmt::Match(s) => {
+                    Self::track_instantiations_in_expr(&s.scrutinee, specializer, symbol_table);
+                    for arm in &s.arms {
+                        Self::track_instantiations_in_stmts(&arm.body, specializer, symbol_table);
+                    }
+                    if let Some(else_branch) = &s.else_branch {
+                        Self::track_instantiations_in_stmts(else_branch, specializer, symbol_table);
+                    }
+                }
                 Stmt::Break(_) | Stmt::Continue(_) => {}
             }
         }
@@ -191,6 +275,14 @@ impl<'a> SemanticAnalyzer<'a> {
         expr: &crate::core::ast::expr::Expr,
         specializer: &mut crate::frontend::semantic::specializer::Specializer,
         symbol_table: &SymbolTable,
+    ) {
+        stack::grow(|| Self::track_instantiations_in_expr_inner(expr, specializer, symbol_table))
+    }
+
+    fn track_instantiations_in_expr_inner(
+        expr: &crate::core::ast::expr::Expr,
+        specializer: &mut crate::frontend::semantic::specializer::Specializer,
+        symbol_table: &SymbolTable,
     ) {
         use crate::core::ast::expr::Expr;
         match expr {
@@ -229,6 +321,11 @@ impl<'a> SemanticAnalyzer<'a> {
                     Self::track_instantiations_in_expr(elem, specializer, symbol_table);
                 }
             }
+            Expr::Tuple(t) => {
+                for elem in &t.elements {
+                    Self::track_instantiations_in_expr(elem, specializer, symbol_table);
+                }
+            }
             Expr::Block(b) => {
                 Self::track_instantiations_in_stmts(&b.stmts, specializer, symbol_table);
                 if let Some(expr) = &b.expr {
@@ -257,6 +354,15 @@ impl<'a> SemanticAnalyzer<'a> {
             Expr::Ref(r) => {
                 Self::track_instantiations_in_expr(&r.expr, specializer, symbol_table);
             }
+            Expr::New(n) => {
+                Self::track_instantiations_in_expr(&n.value, specializer, symbol_table);
+            }
+            Expr::Delete(d) => {
+                Self::track_instantiations_in_expr(&d.pointer, specializer, symbol_table);
+            }
+            Expr::Try(t) => {
+                Self::track_instantiations_in_expr(&t.expr, specializer, symbol_table);
+            }
             Expr::ModuleAccess(_) => {
                 // module access doesnt need tracking
             }
@@ -314,9 +420,10 @@ impl<'a> SemanticAnalyzer<'a> {
         // collect all require statements first
         let mut requires = Vec::new();
         self.collect_requires(ast, &mut requires);
+        let imports = self.collect_imports(ast);
 
         // if no requires, skip module resolution
-        if requires.is_empty() {
+        if requires.is_empty() && imports.is_empty() {
             return;
         }
 
@@ -324,8 +431,13 @@ impl<'a> SemanticAnalyzer<'a> {
         let current_path = self.reporter.files().name(self.file_id).to_string_lossy().to_string();
         self.dependency_graph.add_module(current_path.clone());
 
-        // resolve each require
-        for require_path in &requires {
+        // resolve each require, imports r keyed by their `a::b` path + found next 2 this file
+        let modules = requires
+            .iter()
+            .map(|r| (r.clone(), r.clone()))
+            .chain(imports.iter().map(|i| (i.path.join("::"), ModuleResolver::import_path(&i.path))));
+        for (require_path, file_path) in modules {
+            let require_path = &require_path;
             self.dependency_graph.add_dependency(current_path.clone(), require_path.clone());
             
             // check for cycle before analyzing (early detection)
@@ -356,7 +468,7 @@ impl<'a> SemanticAnalyzer<'a> {
             }
             
             // create resolver 4 this module
-            let mut resolver = ModuleResolver::new(self.reporter);
+            let mut resolver = ModuleResolver::new(self.reporter).with_edition(self.edition);
             
             // add standard library path if it exists
             let std_path = std::path::PathBuf::from("std");
@@ -364,7 +476,11 @@ impl<'a> SemanticAnalyzer<'a> {
                 resolver.add_search_path(std_path);
             }
             
-            if let Some((module_ast, module_file_id)) = resolver.resolve_module(require_path, self.file_id) {
+            if let Some((module_ast, module_file_id)) = resolver.resolve_module(&file_path, self.file_id) {
+                let module_ast = match &self.prelude {
+                    Some(prelude) => prelude.inject(&module_ast, false),
+                    None => module_ast,
+                };
                 // mark as analyzing to prevent cycles (before recursive call)
                 {
                     let mut analyzing = self.analyzing_modules.lock().unwrap();
@@ -378,10 +494,19 @@ impl<'a> SemanticAnalyzer<'a> {
                 let mut module_analyzer = SemanticAnalyzer::new(
                     self.reporter,
                     module_file_id,
-                );
+                ).with_target(self.target.clone()).with_edition(self.edition);
+                module_analyzer.prelude = self.prelude.clone();
                 // share the Arc (clone the Arc, not the HashSet)
                 module_analyzer.analyzing_modules = Arc::clone(&self.analyzing_modules);
                 let module_symbol_table = module_analyzer.analyze(&module_ast);
+                let nested = std::mem::take(&mut module_analyzer.module_registry);
+                let nested_imports = std::mem::take(&mut module_analyzer.imported);
+                self.module_registry.absorb(nested);
+                for import in nested_imports {
+                    if !self.imported.contains(&import) {
+                        self.imported.push(import);
+                    }
+                }
                 
                 // unmark after analysis completes (even on error)
                 {
@@ -403,6 +528,8 @@ impl<'a> SemanticAnalyzer<'a> {
             }
         }
 
+        self.import_members(&imports);
+
         // check 4 circular dependencies
         if let Some(cycle) = self.dependency_graph.detect_cycles() {
             let cycle_str = cycle.join(" -> ");
@@ -432,6 +559,131 @@ impl<'a> SemanticAnalyzer<'a> {
         }
     }
 
+    /// top level imports, a name can only be imported once
+    fn collect_imports(&mut self, ast: &Ast) -> Vec<Import> {
+        let mut imports: Vec<Import> = Vec::new();
+        for item in &ast.items {
+            let Item::Import(i) = item else { continue };
+            if imports.iter().any(|other| other.qualifier() == i.qualifier()) {
+                let diagnostic = crate::error::Diagnostic::error(
+                    crate::error::DiagnosticKind::SemanticError,
+                    i.span,
+                    self.file_id,
+                    format!("Module '{}' is imported more than once", i.qualifier()),
+                );
+                self.reporter.add_diagnostic(diagnostic);
+                continue;
+            }
+            imports.push(i.clone());
+        }
+        imports
+    }
+
+    /// evry fn an imported module exports becomes `qualifier::fn` in this one, the symbol keeps
+    /// the name its object defines it under so an alias doesnt change what gets linked
+    fn import_members(&mut self, imports: &[Import]) {
+        for import in imports {
+            let path = import.path.join("::");
+            if self.module_registry.get_module(&path).is_none() {
+                continue;
+            }
+            let exports = self.module_exports(&path, &mut Vec::new());
+            for member in import.members.iter().flatten() {
+                if !exports.iter().any(|(name, ..)| name == member) {
+                    let diagnostic = crate::error::Diagnostic::error(
+                        crate::error::DiagnosticKind::SemanticError,
+                        import.span,
+                        self.file_id,
+                        format!("Module '{}' has no function '{}'", import.name(), member),
+                    );
+                    self.reporter.add_diagnostic(diagnostic);
+                }
+            }
+            for (member, symbol, origin, deprecated) in exports.into_iter().filter(|(member, ..)| import.exposes(member)) {
+                let name = format!("{}::{}", import.qualifier(), member);
+                if let Some(deprecated) = deprecated {
+                    self.deprecations.add_function(name.clone(), deprecated);
+                }
+                self.origins.insert(name.clone(), origin);
+                self.imported_symbols.push((name, symbol));
+            }
+            let entry = (import.name().to_string(), path);
+            if !self.imported.contains(&entry) {
+                self.imported.push(entry);
+            }
+        }
+    }
+
+    /// (member, symbol named as its object defines it, `def` line, `@deprecated`) 4 the non generic
+    /// fns of the module at `path` + whatever its `pub import`s pass on
+    fn module_exports(&self, path: &str, seen: &mut Vec<String>) -> Vec<Export> {
+        let (Some(ast), Some(symbols), Some(file_id)) = (
+            self.module_registry.get_module_ast(path),
+            self.module_registry.get_module_symbols(path),
+            self.module_registry.get_module_file_id(path),
+        ) else {
+            return Vec::new();
+        };
+        // 2 modules re exporting each other
+        if seen.iter().any(|p| p == path) {
+            return Vec::new();
+        }
+        seen.push(path.to_string());
+        let module_name = path.rsplit("::").next().unwrap_or(path);
+        let source = self.reporter.files().source(file_id);
+        let mut exports = Vec::new();
+        for item in &ast.items {
+            match item {
+                Item::Function(f) if f.generics.is_empty() => {
+                    let Some(symbol) = symbols.resolve(&f.name) else { continue };
+                    if !matches!(symbol.kind, SymbolKind::Function { .. }) {
+                        continue;
+                    }
+                    let origin = (file_id, decl_line(source, f.span));
+                    let deprecated = f.deprecated.as_ref().map(|d| Deprecated { reason: d.reason.clone(), file_id, span: f.span });
+                    let name = format!("{}::{}", module_name, f.name);
+                    exports.push((f.name.clone(), Symbol { name, ..symbol.clone() }, origin, deprecated));
+                }
+                Item::Import(i) if i.public => {
+                    let inner = self.module_exports(&i.path.join("::"), seen);
+                    exports.extend(inner.into_iter().filter(|(member, ..)| i.exposes(member)));
+                }
+                _ => {}
+            }
+        }
+        exports
+    }
+
+    /// imported modules as (name, ast, symbols), transitive ones too, each file once
+    pub fn imported_modules(&self) -> Vec<(&str, &Ast, &SymbolTable)> {
+        self.imports().into_iter().map(|(_, name, ast, symbols)| (name, ast, symbols)).collect()
+    }
+
+    /// the files `imported_modules` were read frm, a build is out of date once 1 of them changes
+    pub fn imported_files(&self) -> Vec<PathBuf> {
+        self.imports().into_iter().map(|(file, ..)| file).collect()
+    }
+
+    fn imports(&self) -> Vec<(PathBuf, &str, &Ast, &SymbolTable)> {
+        let mut modules: Vec<(PathBuf, &str, &Ast, &SymbolTable)> = Vec::new();
+        for (name, path) in &self.imported {
+            let (Some(file_id), Some(ast), Some(symbols)) = (
+                self.module_registry.get_module_file_id(path),
+                self.module_registry.get_module_ast(path),
+                self.module_registry.get_module_symbols(path),
+            ) else {
+                continue;
+            };
+            // the same file reached thru 2 relative paths (`util::m` here, `m` frm util/lib) is read twice
+            let file = self.reporter.files().name(file_id).to_os_string();
+            let file = std::fs::canonicalize(&file).unwrap_or_else(|_| file.into());
+            if !modules.iter().any(|(seen, ..)| *seen == file) {
+                modules.push((file, name.as_str(), ast, symbols));
+            }
+        }
+        modules
+    }
+
     /// extract module namespace from module block if present
     fn extract_module_namespace(&self, ast: &Ast) -> Option<Vec<String>> {
         // look 4 module blocks in the ast
@@ -445,6 +697,19 @@ impl<'a> SemanticAnalyzer<'a> {
         None
     }
 
+    /// typed holes of the last analysis, in the order they were found
+    pub fn holes(&self) -> &[Hole] {
+        &self.holes
+    }
+
+    /// innermost hole around `offset`, what completion in broken code starts frm
+    pub fn hole_at(&self, offset: ByteIndex) -> Option<&Hole> {
+        self.holes
+            .iter()
+            .filter(|h| h.span.start() <= offset && offset <= h.span.end())
+            .min_by_key(|h| h.span.end() - h.span.start())
+    }
+
     /// get the module registry
     pub fn module_registry(&self) -> &ModuleRegistry {
         &self.module_registry
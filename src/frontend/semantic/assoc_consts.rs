@@ -0,0 +1,66 @@
+use crate::core::ast::expr::Expr;
+use crate::core::ast::item::{AssocConst, Item};
+use crate::core::types::ty::Type;
+use crate::frontend::semantic::comptime::ComptimeValue;
+use std::collections::HashMap;
+
+/// assoc consts of evry trait + impl, `Type::NAME` reads the impl's value or the trait's default
+#[derive(Debug, Clone, Default)]
+pub struct AssocConsts {
+    traits: HashMap<String, Vec<AssocConst>>,
+    /// (type, trait, consts the impl gives)
+    impls: Vec<(String, String, Vec<AssocConst>)>,
+}
+
+impl AssocConsts {
+    pub fn collect(items: &[Item]) -> Self {
+        let mut consts = Self::default();
+        for item in items {
+            match item {
+                Item::Trait(t) => {
+                    consts.traits.insert(t.name.clone(), t.consts.clone());
+                }
+                Item::TraitImpl(ti) => {
+                    consts.impls.push((ti.type_name.clone(), ti.trait_name.clone(), ti.consts.clone()));
+                }
+                _ => {}
+            }
+        }
+        consts
+    }
+
+    /// decl of `name` in `trait_name`
+    pub fn declared(&self, trait_name: &str, name: &str) -> Option<&AssocConst> {
+        self.traits.get(trait_name)?.iter().find(|c| c.name == name)
+    }
+
+    /// trait decl + value of `type_name::name`, none if no impl of `type_name` has it
+    pub fn lookup(&self, type_name: &str, name: &str) -> Option<(&AssocConst, &Expr)> {
+        self.impls.iter().filter(|(ty, _, _)| ty == type_name).find_map(|(_, trait_name, given)| {
+            let decl = self.declared(trait_name, name)?;
+            let value = given.iter().find(|c| c.name == name).and_then(|c| c.value.as_ref()).or(decl.value.as_ref())?;
+            Some((decl, value))
+        })
+    }
+
+    pub fn declares_consts(&self, trait_name: &str) -> bool {
+        self.traits.get(trait_name).is_some_and(|c| !c.is_empty())
+    }
+
+    /// true if `type_name` implements a trait w/ consts, so `type_name::x` is a const read
+    pub fn has_consts(&self, type_name: &str) -> bool {
+        self.impls.iter().any(|(ty, t, _)| ty == type_name && self.declares_consts(t))
+    }
+}
+
+/// true if a folded value can be stored in a const of type `ty`
+pub fn value_fits(value: &ComptimeValue, ty: &Type) -> bool {
+    match (value, ty) {
+        (ComptimeValue::Int(_), Type::Primitive(p)) => p.is_integer(),
+        (ComptimeValue::Float(_), Type::Primitive(p)) => p.is_float(),
+        (ComptimeValue::Bool(_), Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)) => true,
+        (ComptimeValue::Char(_), Type::Primitive(crate::core::types::primitive::PrimitiveType::Char)) => true,
+        (ComptimeValue::String(_), Type::String) => true,
+        _ => false,
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::ast::*;
+use crate::core::stack;
 use crate::error::{Diagnostic, DiagnosticKind, Reporter};
 use codespan::FileId;
 
@@ -35,6 +36,10 @@ impl<'a> BorrowChecker<'a> {
     }
 
     fn check_stmt(&mut self, stmt: &Stmt) {
+        stack::grow(|| self.check_stmt_inner(stmt))
+    }
+
+    fn check_stmt_inner(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Let(s) => {
                 if let Some(value) = &s.value {
@@ -58,6 +63,10 @@ impl<'a> BorrowChecker<'a> {
     }
 
     fn check_expr(&mut self, expr: &Expr) {
+        stack::grow(|| self.check_expr_inner(expr))
+    }
+
+    fn check_expr_inner(&mut self, expr: &Expr) {
         match expr {
             Expr::At(e) => {
                 // addrss of operator creates a brrw
@@ -81,11 +90,21 @@ impl<'a> BorrowChecker<'a> {
                     self.check_expr(element);
                 }
             }
+            Expr::Tuple(t) => {
+                for element in &t.elements {
+                    self.check_expr(element);
+                }
+            }
             _ => {}
         }
     }
 
     fn check_assignment(&mut self, target: &Expr, value: &Expr) {
+        self.check_target(target);
+        self.check_expr(value);
+    }
+
+    fn check_target(&mut self, target: &Expr) {
         // chk that target is mut
         match target {
             Expr::Variable(_v) => {
@@ -101,11 +120,16 @@ impl<'a> BorrowChecker<'a> {
                 self.check_expr(&i.array);
                 self.check_expr(&i.index);
             }
+            // `a, b = pair`
+            Expr::Tuple(t) => {
+                for element in &t.elements {
+                    self.check_target(element);
+                }
+            }
             _ => {
                 self.error(target.span(), "Cannot assign to this expression");
             }
         }
-        self.check_expr(value);
     }
 
     fn error(&mut self, span: codespan::Span, message: &str) {
@@ -50,6 +50,18 @@ impl<'a> SymbolCollector<'a> {
             defined: true,
         };
         let _ = self.symbol_table.define("print".to_string(), print_symbol);
+
+        // panic(msg : string) - reports msg w/ the call's location + aborts
+        let panic_symbol = Symbol {
+            name: "panic".to_string(),
+            kind: SymbolKind::Function {
+                params: vec![Type::String],
+                return_type: Some(Type::Primitive(PrimitiveType::Void)),
+            },
+            span: Span::new(0, 0),
+            defined: true,
+        };
+        let _ = self.symbol_table.define("panic".to_string(), panic_symbol);
     }
 
     fn collect_item(&mut self, item: &Item) {
@@ -68,6 +80,7 @@ impl<'a> SymbolCollector<'a> {
                 if let Err(e) = self.symbol_table.define(f.name.clone(), symbol) {
                     self.error(f.span, &e);
                 }
+                self.check_target_features(f);
             }
             Item::Struct(s) => {
                 // collect strct name w/ placeholder fields
@@ -99,6 +112,23 @@ impl<'a> SymbolCollector<'a> {
                     }
                 }
             }
+            Item::Enum(e) => {
+                // payloads only name types, no need 2 wait 4 pass 2
+                let variants = e
+                    .variants
+                    .iter()
+                    .map(|v| (v.name.clone(), v.fields.iter().map(crate::core::types::resolver::resolve_ast_type).collect()))
+                    .collect();
+                let symbol = Symbol { name: e.name.clone(), kind: SymbolKind::Enum { variants }, span: e.span, defined: true };
+                if let Err(err) = self.symbol_table.define(e.name.clone(), symbol) {
+                    self.error(e.span, &err);
+                }
+                for (i, v) in e.variants.iter().enumerate() {
+                    if e.variants[..i].iter().any(|prev| prev.name == v.name) {
+                        self.error(v.span, &format!("Variant '{}' is declared twice in enum '{}'", v.name, e.name));
+                    }
+                }
+            }
             Item::Trait(t) => {
                 // cllct trait name
                 let symbol = Symbol {
@@ -196,12 +226,39 @@ impl<'a> SymbolCollector<'a> {
                     }
                 }
             }
-            Item::Require(_) | Item::Use(_) => {
-                // these dont crt symbols in the symbol table
+            Item::Require(_) | Item::Import(_) | Item::Use(_) => {
+                // these dont crt symbols in the symbol table, imported members r added by the analyzer
             }
         }
     }
 
+    /// clones r only emitted 4 isas the runtime dispatcher can test 4
+    fn check_target_features(&mut self, f: &Function) {
+        if f.target_features.is_empty() {
+            return;
+        }
+        if f.body.is_none() {
+            self.semantic_error(f.span, &format!("@target_feature function '{}' must have a body", f.name));
+        }
+        for (idx, feature) in f.target_features.iter().enumerate() {
+            if crate::core::target::x86_cpu_feature(feature).is_none() {
+                self.semantic_error(f.span, &format!("unknown target feature '{}' on '{}'", feature, f.name));
+            } else if f.target_features[..idx].contains(feature) {
+                self.semantic_error(f.span, &format!("duplicate target feature '{}' on '{}'", feature, f.name));
+            }
+        }
+    }
+
+    fn semantic_error(&mut self, span: codespan::Span, message: &str) {
+        let diagnostic = Diagnostic::error(
+            DiagnosticKind::SemanticError,
+            span,
+            self.file_id,
+            message.to_string(),
+        );
+        self.reporter.add_diagnostic(diagnostic);
+    }
+
     fn error(&mut self, span: codespan::Span, message: &str) {
         let diagnostic = Diagnostic::error(
             DiagnosticKind::NameResolutionError,
@@ -1,21 +1,44 @@
 use crate::core::ast::expr::*;
+use crate::core::hir::expr::{HirExpr, HirLiteralKind, HirUnaryOp};
+use crate::core::target::TargetInfo;
+use crate::core::stack;
 use crate::error::{Diagnostic, DiagnosticKind, Reporter};
 use codespan::{FileId, Span};
+use std::collections::HashMap;
 
 /// cmptm evltr 4 compile time cnstnt evluation
 pub struct ComptimeEvaluator<'a> {
     reporter: &'a mut Reporter,
     file_id: FileId,
+    target: TargetInfo,
+    /// comptime params of the fn being specialized, known by value
+    bindings: HashMap<String, ComptimeValue>,
 }
 
 impl<'a> ComptimeEvaluator<'a> {
     pub fn new(reporter: &'a mut Reporter, file_id: FileId) -> Self {
-        Self { reporter, file_id }
+        Self { reporter, file_id, target: TargetInfo::host(), bindings: HashMap::new() }
+    }
+
+    /// evaluate `target.*` against the given target instead of the host
+    pub fn with_target(mut self, target: TargetInfo) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// let variables w/ a known value (comptime params) appear in expressions
+    pub fn with_bindings(mut self, bindings: HashMap<String, ComptimeValue>) -> Self {
+        self.bindings = bindings;
+        self
     }
 
     /// evaluate a comptime expression at cmpl time
     /// rtrns the constant value if evlbl or none if not constant
     pub fn evaluate(&mut self, expr: &Expr) -> Option<ComptimeValue> {
+        stack::grow(|| self.evaluate_inner(expr))
+    }
+
+    fn evaluate_inner(&mut self, expr: &Expr) -> Option<ComptimeValue> {
         match expr {
             Expr::Literal(l) => match &l.kind {
                 LiteralKind::Int(n) => Some(ComptimeValue::Int(*n)),
@@ -36,6 +59,30 @@ impl<'a> ComptimeEvaluator<'a> {
             Expr::Comptime(c) => {
                 self.evaluate(&c.expr)
             }
+            Expr::ArrayLiteral(a) => {
+                let mut elements = Vec::with_capacity(a.elements.len());
+                for element in &a.elements {
+                    elements.push(self.evaluate(element)?);
+                }
+                // [value; count] expands 2 count copies of value
+                if let Some(count) = a.repeat {
+                    let value = elements.into_iter().next()?;
+                    return Some(ComptimeValue::Array(vec![value; count]));
+                }
+                Some(ComptimeValue::Array(elements))
+            }
+            Expr::StructLiteral(s) => {
+                let mut fields = Vec::with_capacity(s.fields.len());
+                for (name, value) in &s.fields {
+                    fields.push((name.clone(), self.evaluate(value)?));
+                }
+                Some(ComptimeValue::Struct { name: s.struct_name.clone(), fields })
+            }
+            Expr::FieldAccess(f) if is_target_access(f) => {
+                // unknown fields r reported by the type checker which knows about shadowing
+                target_constant(&self.target, &f.field)
+            }
+            Expr::Variable(v) if self.bindings.contains_key(&v.name) => self.bindings.get(&v.name).cloned(),
             Expr::Variable(v) => {
                 self.error(v.span, &format!("Variable '{}' cannot be used in comptime expression - only constants are allowed", v.name));
                 None
@@ -238,9 +285,85 @@ pub enum ComptimeValue {
     Bool(bool),
     Char(char),
     String(String),
+    Array(Vec<ComptimeValue>),
+    Struct { name: String, fields: Vec<(String, ComptimeValue)> },
+}
+
+/// `target.<field>` - platform constants from the configured triple
+pub fn is_target_access(f: &FieldAccessExpr) -> bool {
+    matches!(&*f.object, Expr::Variable(v) if v.name == "target")
+}
+
+/// branch an `if comptime c` takes, none if `c` doesnt fold 2 a bool
+/// `bindings` r the comptime params of a specialization, errors r the type checker's 2 report
+pub fn comptime_branch(condition: &Expr, target: &TargetInfo, bindings: &HashMap<String, ComptimeValue>) -> Option<bool> {
+    let Expr::Comptime(c) = condition else { return None };
+    let mut scratch = Reporter::new();
+    let file_id = scratch.add_file(String::new(), String::new());
+    let value = ComptimeEvaluator::new(&mut scratch, file_id)
+        .with_target(target.clone())
+        .with_bindings(bindings.clone())
+        .evaluate(&c.expr)?;
+    match value {
+        ComptimeValue::Bool(b) => Some(b),
+        _ => None,
+    }
+}
+
+/// value of a `target.*` constant or none if the field is unknown
+pub fn target_constant(target: &TargetInfo, field: &str) -> Option<ComptimeValue> {
+    match field {
+        "endian" => Some(ComptimeValue::String(target.endian.as_str().to_string())),
+        "pointer_width" => Some(ComptimeValue::Int(target.pointer_width as i64)),
+        "os" => Some(ComptimeValue::String(target.os.clone())),
+        "arch" => Some(ComptimeValue::String(target.arch.clone())),
+        _ => None,
+    }
+}
+
+/// fold a lowered initializer 2 a constant w/o reporting
+/// used by mir lowering 2 emit comptime-known aggregates as static data
+pub fn fold_hir_constant(expr: &HirExpr) -> Option<ComptimeValue> {
+    match expr {
+        HirExpr::Literal(l) => Some(ComptimeValue::from_literal(&l.kind)),
+        HirExpr::Comptime(c) => match &c.evaluated {
+            Some(l) => Some(ComptimeValue::from_literal(&l.kind)),
+            None => fold_hir_constant(&c.expr),
+        },
+        HirExpr::Unary(u) if u.op == HirUnaryOp::Neg => match fold_hir_constant(&u.expr)? {
+            ComptimeValue::Int(n) => Some(ComptimeValue::Int(-n)),
+            ComptimeValue::Float(n) => Some(ComptimeValue::Float(-n)),
+            _ => None,
+        },
+        HirExpr::ArrayLiteral(a) => {
+            let elements = a.elements.iter().map(fold_hir_constant).collect::<Option<Vec<_>>>()?;
+            match a.repeat {
+                Some(count) => Some(ComptimeValue::Array(vec![elements.into_iter().next()?; count])),
+                None => Some(ComptimeValue::Array(elements)),
+            }
+        }
+        HirExpr::StructLiteral(s) => {
+            let fields = s.fields.iter()
+                .map(|(name, value)| Some((name.clone(), fold_hir_constant(value)?)))
+                .collect::<Option<Vec<_>>>()?;
+            Some(ComptimeValue::Struct { name: s.struct_name.clone(), fields })
+        }
+        _ => None,
+    }
 }
 
 impl ComptimeValue {
+    fn from_literal(kind: &HirLiteralKind) -> Self {
+        match kind {
+            HirLiteralKind::Int(n) => ComptimeValue::Int(*n),
+            HirLiteralKind::Float(n) => ComptimeValue::Float(*n),
+            HirLiteralKind::Bool(b) => ComptimeValue::Bool(*b),
+            HirLiteralKind::Char(c) => ComptimeValue::Char(*c),
+            HirLiteralKind::String(s) => ComptimeValue::String(s.clone()),
+        }
+    }
+
+
     pub fn to_constant(&self) -> crate::core::mir::operand::Constant {
         match self {
             ComptimeValue::Int(n) => crate::core::mir::operand::Constant::Int(*n),
@@ -248,6 +371,12 @@ impl ComptimeValue {
             ComptimeValue::Bool(b) => crate::core::mir::operand::Constant::Bool(*b),
             ComptimeValue::Char(c) => crate::core::mir::operand::Constant::Char(*c),
             ComptimeValue::String(s) => crate::core::mir::operand::Constant::String(s.clone()),
+            ComptimeValue::Array(elements) => crate::core::mir::operand::Constant::Aggregate(
+                elements.iter().map(|e| e.to_constant()).collect(),
+            ),
+            ComptimeValue::Struct { fields, .. } => crate::core::mir::operand::Constant::Aggregate(
+                fields.iter().map(|(_, v)| v.to_constant()).collect(),
+            ),
         }
     }
 }
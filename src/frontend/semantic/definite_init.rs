@@ -0,0 +1,390 @@
+use crate::core::ast::*;
+use crate::core::stack;
+use crate::error::{Diagnostic, DiagnosticKind, Reporter};
+use crate::frontend::semantic::noreturn::NoReturn;
+use codespan::FileId;
+use std::collections::{BTreeSet, HashMap};
+
+/// struct vars declared w/o a value -> the field paths (`a`, `a.b`) set so far
+/// a var thats fully set is dropped, so a missing entry means initialized
+type InitState = HashMap<String, Partial>;
+
+#[derive(Debug, Clone)]
+struct Partial {
+    struct_name: String,
+    set: BTreeSet<String>,
+}
+
+/// struct values must have evry field set b4 they r used
+/// literals must name all fields, `p : Point` w/o a value is tracked field by field thru its assignments
+pub struct DefiniteInit<'a> {
+    reporter: &'a mut Reporter,
+    file_id: FileId,
+    /// struct -> (field, its struct type if it has 1)
+    structs: HashMap<String, Vec<(String, Option<String>)>>,
+    noreturn: NoReturn,
+}
+
+impl<'a> DefiniteInit<'a> {
+    pub fn new(reporter: &'a mut Reporter, file_id: FileId) -> Self {
+        Self {
+            reporter,
+            file_id,
+            structs: HashMap::new(),
+            noreturn: NoReturn::default(),
+        }
+    }
+
+    pub fn check(&mut self, ast: &Ast) {
+        for item in &ast.items {
+            if let Item::Struct(s) = item {
+                let fields = s.fields.iter().map(|f| (f.name.clone(), named(&f.type_).map(String::from))).collect();
+                self.structs.insert(s.name.clone(), fields);
+            }
+        }
+        // field types that arent structs have nothing 2 recurse in2
+        let names: Vec<String> = self.structs.keys().cloned().collect();
+        for fields in self.structs.values_mut() {
+            for (_, nested) in fields.iter_mut() {
+                if nested.as_ref().is_some_and(|n| !names.contains(n)) {
+                    *nested = None;
+                }
+            }
+        }
+        self.noreturn = NoReturn::analyze(&ast.items);
+
+        for item in &ast.items {
+            match item {
+                Item::Function(f) => self.check_function(f),
+                Item::TraitImpl(ti) => ti.methods.iter().for_each(|m| self.check_function(m)),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_function(&mut self, f: &Function) {
+        if let Some(body) = &f.body {
+            self.check_block(body, &mut InitState::new());
+        }
+    }
+
+    fn check_block(&mut self, stmts: &[Stmt], state: &mut InitState) {
+        for stmt in stmts {
+            self.check_stmt(stmt, state);
+        }
+    }
+
+    /// state after `stmts`, or none if they never fall thru
+    fn branch(&mut self, stmts: &[Stmt], state: &InitState) -> Option<InitState> {
+        let mut branch = state.clone();
+        self.check_block(stmts, &mut branch);
+        (!self.noreturn.diverges(stmts)).then_some(branch)
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt, state: &mut InitState) {
+        stack::grow(|| self.check_stmt_inner(stmt, state))
+    }
+
+    fn check_stmt_inner(&mut self, stmt: &Stmt, state: &mut InitState) {
+        match stmt {
+            Stmt::Let(s) => {
+                if let Some(value) = &s.value {
+                    self.check_expr(value, state);
+                }
+                state.remove(&s.name);
+                let declared = s.type_annotation.as_ref().and_then(named);
+                if let (None, Some(name)) = (&s.value, declared) {
+                    if self.structs.get(name).is_some_and(|f| !f.is_empty()) {
+                        state.insert(s.name.clone(), Partial { struct_name: name.to_string(), set: BTreeSet::new() });
+                    }
+                }
+            }
+            Stmt::Expr(s) => self.check_expr(&s.expr, state),
+            Stmt::Return(s) => {
+                if let Some(value) = &s.value {
+                    self.check_expr(value, state);
+                }
+            }
+            Stmt::If(s) => {
+                self.check_expr(&s.condition, state);
+                let then = self.branch(&s.then_branch, state);
+                let otherwise = match &s.else_branch {
+                    Some(stmts) => self.branch(stmts, state),
+                    None => Some(state.clone()),
+                };
+                if let Some(merged) = self.merge([then, otherwise]) {
+                    *state = merged;
+                }
+            }
+            // the body may not run, so only whats set b4 the loop counts after it
+            Stmt::While(s) => {
+                self.check_expr(&s.condition, state);
+                self.branch(&s.body, state);
+            }
+            Stmt::For(s) => {
+                if let Some(init) = &s.init {
+                    self.check_stmt(init, state);
+                }
+                if let Some(condition) = &s.condition {
+                    self.check_expr(condition, state);
+                }
+                let mut body = state.clone();
+                self.check_block(&s.body, &mut body);
+                if let Some(increment) = &s.increment {
+                    self.check_expr(increment, &mut body);
+                }
+            }
+            Stmt::Guard(s) => {
+                self.check_expr(&s.condition, state);
+                self.branch(&s.else_branch, state);
+            }
+            // runs later but is chkd where it's written, so it only reads whats set by then
+            Stmt::Defer(s) => {
+                self.branch(&s.body, state);
+            }
+            // a match w/o else is exhaustive, 1 of the arms always runs
+            Stmt::Match(s) => {
+                self.check_expr(&s.scrutinee, state);
+                let mut arms: Vec<Option<InitState>> = s.arms.iter().map(|a| self.branch(&a.body, state)).collect();
+                if let Some(stmts) = &s.else_branch {
+                    let otherwise = self.branch(stmts, state);
+                    arms.push(otherwise);
+                }
+                if let Some(merged) = self.merge(arms) {
+                    *state = merged;
+                }
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+        }
+    }
+
+    fn check_expr(&mut self, expr: &Expr, state: &mut InitState) {
+        stack::grow(|| self.check_expr_inner(expr, state))
+    }
+
+    fn check_expr_inner(&mut self, expr: &Expr, state: &mut InitState) {
+        match expr {
+            Expr::Variable(v) => {
+                if let Some(partial) = state.get(&v.name) {
+                    let missing = self.missing_fields(partial);
+                    if !missing.is_empty() {
+                        let message = format!(
+                            "'{}' is used before field(s) {} are initialized",
+                            v.name,
+                            missing.iter().map(|f| format!("'{}'", f)).collect::<Vec<_>>().join(", ")
+                        );
+                        self.error(v.span, &message);
+                    }
+                    // reported once, later uses dont repeat it
+                    state.remove(&v.name);
+                }
+            }
+            Expr::FieldAccess(f) => match field_path(expr) {
+                Some((root, path)) if state.contains_key(root) => {
+                    let partial = &state[root];
+                    if !self.path_set(partial, &path) {
+                        self.error(f.span, &format!("Field '{}.{}' is read before it's initialized", root, path));
+                        state.get_mut(root).unwrap().set.insert(path);
+                    }
+                }
+                _ => self.check_expr(&f.object, state),
+            },
+            Expr::Assignment(a) => {
+                self.check_expr(&a.value, state);
+                self.assign(&a.target, state);
+            }
+            Expr::StructLiteral(s) => {
+                for (_, value) in &s.fields {
+                    self.check_expr(value, state);
+                }
+                if let Some(fields) = self.structs.get(&s.struct_name) {
+                    let missing: Vec<String> = fields
+                        .iter()
+                        .filter(|(name, _)| !s.fields.iter().any(|(given, _)| given == name))
+                        .map(|(name, _)| format!("'{}'", name))
+                        .collect();
+                    if !missing.is_empty() {
+                        let message = format!("Struct literal of '{}' is missing field(s) {}", s.struct_name, missing.join(", "));
+                        self.error(s.span, &message);
+                    }
+                }
+            }
+            Expr::Binary(b) => {
+                self.check_expr(&b.left, state);
+                self.check_expr(&b.right, state);
+            }
+            Expr::Unary(u) => self.check_expr(&u.expr, state),
+            Expr::Call(c) => {
+                self.check_expr(&c.callee, state);
+                for arg in &c.args {
+                    self.check_expr(arg, state);
+                }
+            }
+            Expr::MethodCall(m) => {
+                self.check_expr(&m.receiver, state);
+                for arg in &m.args {
+                    self.check_expr(arg, state);
+                }
+            }
+            Expr::Index(i) => {
+                self.check_expr(&i.array, state);
+                self.check_expr(&i.index, state);
+            }
+            Expr::Block(b) => {
+                self.check_block(&b.stmts, state);
+                if let Some(e) = &b.expr {
+                    self.check_expr(e, state);
+                }
+            }
+            Expr::If(i) => {
+                self.check_expr(&i.condition, state);
+                let mut then = state.clone();
+                self.check_expr(&i.then_branch, &mut then);
+                let mut otherwise = state.clone();
+                if let Some(e) = &i.else_branch {
+                    self.check_expr(e, &mut otherwise);
+                }
+                if let Some(merged) = self.merge([Some(then), Some(otherwise)]) {
+                    *state = merged;
+                }
+            }
+            Expr::Closure(c) => {
+                self.branch(&c.body, state);
+            }
+            Expr::Ref(r) => self.check_expr(&r.expr, state),
+            Expr::At(a) => self.check_expr(&a.expr, state),
+            Expr::Exists(e) => self.check_expr(&e.expr, state),
+            Expr::Comptime(c) => self.check_expr(&c.expr, state),
+            Expr::New(n) => self.check_expr(&n.value, state),
+            Expr::Delete(d) => self.check_expr(&d.pointer, state),
+            Expr::Try(t) => self.check_expr(&t.expr, state),
+            Expr::ArrayLiteral(a) => {
+                for element in &a.elements {
+                    self.check_expr(element, state);
+                }
+            }
+            Expr::Tuple(t) => {
+                for element in &t.elements {
+                    self.check_expr(element, state);
+                }
+            }
+            Expr::Literal(_) | Expr::ModuleAccess(_) | Expr::Null => {}
+        }
+    }
+
+    /// `target` written, it + whatever it completes r initialized frm here on
+    fn assign(&mut self, target: &Expr, state: &mut InitState) {
+        match target {
+            Expr::Variable(v) => {
+                state.remove(&v.name);
+            }
+            // `a, b = pair` writes each of its targets
+            Expr::Tuple(t) => {
+                for element in &t.elements {
+                    self.assign(element, state);
+                }
+            }
+            target => match field_path(target) {
+                Some((root, path)) if state.contains_key(root) => {
+                    let partial = state.get_mut(root).unwrap();
+                    partial.set.insert(path);
+                    if self.missing_fields(&state[root]).is_empty() {
+                        state.remove(root);
+                    }
+                }
+                // writing an element of a field array doesnt read the field
+                _ => match target {
+                    Expr::Index(i) if field_path(&i.array).is_some_and(|(root, _)| state.contains_key(root)) => {
+                        self.check_expr(&i.index, state);
+                    }
+                    _ => self.check_expr(target, state),
+                },
+            },
+        }
+    }
+
+    /// top level fields of the var not set yet, a nested struct counts once all its fields r
+    fn missing_fields(&self, partial: &Partial) -> Vec<String> {
+        let fields = self.structs.get(&partial.struct_name).cloned().unwrap_or_default();
+        fields.into_iter().map(|(name, _)| name).filter(|name| !self.path_set(partial, name)).collect()
+    }
+
+    /// true if `path` or a field its in was assigned, or evry field under it was
+    fn path_set(&self, partial: &Partial, path: &str) -> bool {
+        let mut prefix = String::new();
+        let mut ty = Some(partial.struct_name.clone());
+        for part in path.split('.') {
+            if !prefix.is_empty() {
+                prefix.push('.');
+            }
+            prefix.push_str(part);
+            if partial.set.contains(&prefix) {
+                return true;
+            }
+            ty = ty.and_then(|t| self.field_struct(&t, part));
+        }
+        ty.is_some_and(|t| self.subtree_set(partial, &t, &prefix))
+    }
+
+    fn subtree_set(&self, partial: &Partial, struct_name: &str, prefix: &str) -> bool {
+        let Some(fields) = self.structs.get(struct_name) else { return false };
+        !fields.is_empty()
+            && fields.iter().all(|(name, nested)| {
+                let path = format!("{}.{}", prefix, name);
+                partial.set.contains(&path) || nested.as_ref().is_some_and(|n| self.subtree_set(partial, n, &path))
+            })
+    }
+
+    /// a field is set after the branches only if evry branch that falls thru set it
+    /// none if no branch falls thru
+    fn merge(&self, branches: impl IntoIterator<Item = Option<InitState>>) -> Option<InitState> {
+        branches.into_iter().flatten().reduce(|mut merged, branch| {
+            for (name, partial) in branch {
+                match merged.get_mut(&name) {
+                    Some(m) => {
+                        let mut set: BTreeSet<String> = m.set.iter().filter(|p| self.path_set(&partial, p)).cloned().collect();
+                        set.extend(partial.set.iter().filter(|p| self.path_set(m, p)).cloned());
+                        m.set = set;
+                    }
+                    None => {
+                        merged.insert(name, partial);
+                    }
+                }
+            }
+            merged
+        })
+    }
+
+    fn field_struct(&self, struct_name: &str, field: &str) -> Option<String> {
+        self.structs.get(struct_name)?.iter().find(|(name, _)| name == field)?.1.clone()
+    }
+
+    fn error(&mut self, span: codespan::Span, message: &str) {
+        let diagnostic = Diagnostic::error(
+            DiagnosticKind::SemanticError,
+            span,
+            self.file_id,
+            message.to_string(),
+        );
+        self.reporter.add_diagnostic(diagnostic);
+    }
+}
+
+/// name of a struct annotation, generic args dont change the fields
+fn named(ty: &crate::core::ast::types::Type) -> Option<&str> {
+    match ty {
+        crate::core::ast::types::Type::Named(n) => Some(&n.name),
+        _ => None,
+    }
+}
+
+/// `p.a.b` -> (p, "a.b")
+fn field_path(expr: &Expr) -> Option<(&str, String)> {
+    match expr {
+        Expr::FieldAccess(f) => match f.object.as_ref() {
+            Expr::Variable(v) => Some((&v.name, f.field.clone())),
+            object => field_path(object).map(|(root, path)| (root, format!("{}.{}", path, f.field))),
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,149 @@
+use crate::core::ast::item::{Deprecation, Item};
+use crate::core::ast::types::Type;
+use crate::error::{Diagnostic, DiagnosticKind, Lint, Reporter};
+use codespan::{ByteIndex, FileId, Span};
+use std::collections::HashMap;
+
+/// a `@deprecated` item + where its declared
+#[derive(Debug, Clone)]
+pub struct Deprecated {
+    pub reason: Option<String>,
+    pub file_id: FileId,
+    pub span: Span,
+}
+
+/// `@deprecated` fns, types + fields by name, the type checker looks uses up here
+#[derive(Debug, Clone, Default)]
+pub struct Deprecations {
+    functions: HashMap<String, Deprecated>,
+    types: HashMap<String, Deprecated>,
+    /// (struct, field)
+    fields: HashMap<(String, String), Deprecated>,
+}
+
+impl Deprecations {
+    pub fn collect(items: &[Item], file_id: FileId) -> Self {
+        let mut deprecations = Self::default();
+        let entry = |d: &Deprecation, span: Span| Deprecated { reason: d.reason.clone(), file_id, span };
+        for item in items {
+            match item {
+                Item::Function(f) => {
+                    if let Some(d) = &f.deprecated {
+                        deprecations.functions.insert(f.name.clone(), entry(d, f.span));
+                    }
+                }
+                Item::Struct(s) => {
+                    if let Some(d) = &s.deprecated {
+                        deprecations.types.insert(s.name.clone(), entry(d, s.span));
+                    }
+                    for field in &s.fields {
+                        if let Some(d) = &field.deprecated {
+                            deprecations.fields.insert((s.name.clone(), field.name.clone()), entry(d, field.span));
+                        }
+                    }
+                }
+                Item::Enum(e) => {
+                    if let Some(d) = &e.deprecated {
+                        deprecations.types.insert(e.name.clone(), entry(d, e.span));
+                    }
+                }
+                _ => {}
+            }
+        }
+        deprecations
+    }
+
+    /// an imported `module::fn`
+    pub fn add_function(&mut self, name: String, deprecated: Deprecated) {
+        self.functions.insert(name, deprecated);
+    }
+
+    pub fn extend(&mut self, other: Deprecations) {
+        self.functions.extend(other.functions);
+        self.types.extend(other.types);
+        self.fields.extend(other.fields);
+    }
+
+    pub fn function(&self, name: &str) -> Option<&Deprecated> {
+        self.functions.get(name)
+    }
+
+    pub fn type_(&self, name: &str) -> Option<&Deprecated> {
+        self.types.get(name)
+    }
+
+    pub fn field(&self, struct_name: &str, field: &str) -> Option<&Deprecated> {
+        self.fields.get(&(struct_name.to_string(), field.to_string()))
+    }
+
+    /// deprecated struct + enum names `ty` mentions, `List[Old]` uses `Old`
+    pub fn types_in<'t>(&self, ty: &'t Type) -> Vec<&'t str> {
+        let mut names = Vec::new();
+        self.collect_types(ty, &mut names);
+        names
+    }
+
+    fn collect_types<'t>(&self, ty: &'t Type, names: &mut Vec<&'t str>) {
+        match ty {
+            Type::Named(n) => {
+                if self.types.contains_key(&n.name) {
+                    names.push(&n.name);
+                }
+                for generic in &n.generics {
+                    self.collect_types(generic, names);
+                }
+            }
+            Type::Array(a) => self.collect_types(&a.element, names),
+            Type::Pointer(p) => self.collect_types(&p.pointee, names),
+            Type::Function(f) => {
+                for param in &f.params {
+                    self.collect_types(param, names);
+                }
+                self.collect_types(&f.return_type, names);
+            }
+            Type::Record(fields) => {
+                for (_, field) in fields {
+                    self.collect_types(field, names);
+                }
+            }
+            Type::Tuple(elements) => {
+                for element in elements {
+                    self.collect_types(element, names);
+                }
+            }
+            Type::Fallible(f) => {
+                self.collect_types(&f.value, names);
+                self.collect_types(&f.error, names);
+            }
+            Type::Primitive(_) | Type::Generic(_) | Type::TraitObject(_) => {}
+        }
+    }
+}
+
+/// `deprecated` lint 4 a use of `what` (eg "function 'old'") at `span`
+pub fn report_use(reporter: &mut Reporter, file_id: FileId, span: Span, what: &str, deprecated: &Deprecated) {
+    let message = match &deprecated.reason {
+        Some(reason) => format!("Use of deprecated {}: {}", what, reason),
+        None => format!("Use of deprecated {}", what),
+    };
+    let declared = decl_line(reporter.files().source(deprecated.file_id), deprecated.span);
+    let diagnostic = Diagnostic::warning(DiagnosticKind::SemanticError, span, file_id, message)
+        .with_label(deprecated.file_id, declared, "declared here".to_string());
+    reporter.add_lint(Lint::Deprecated, diagnostic);
+}
+
+/// 1st line of an item w/o the `@attribute` lines above it, labels pointing at a decl shouldnt
+/// print the whole body
+pub fn decl_line(source: &str, span: Span) -> Span {
+    let end = span.end().to_usize().min(source.len());
+    let mut start = span.start().to_usize().min(end);
+    loop {
+        let line_end = source[start..end].find('\n').map_or(end, |n| start + n);
+        let line = &source[start..line_end];
+        if !line.trim_start().starts_with('@') || line_end == end {
+            let indent = line.len() - line.trim_start().len();
+            return Span::new(ByteIndex((start + indent) as u32), ByteIndex(line_end as u32));
+        }
+        start = line_end + 1;
+    }
+}
@@ -0,0 +1,211 @@
+use crate::core::ast::expr::LiteralKind;
+use crate::core::ast::pattern::Pattern;
+use crate::core::ast::stmt::MatchStmt;
+use crate::core::types::composite::spelling;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::Desugaring;
+use crate::frontend::semantic::usefulness::{integer_bounds, witnesses, Ctor, Domain, Pat};
+use codespan::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchIssue {
+    pub span: Span,
+    pub message: String,
+    /// unreachable + overlapping patterns only warn
+    pub error: bool,
+    /// the arms that would make a match exhaustive, or why an arm never runs
+    pub note: Option<String>,
+}
+
+impl MatchIssue {
+    fn error(span: Span, message: String) -> Self {
+        Self { span, message, error: true, note: None }
+    }
+}
+
+/// pattern types, empty + out of type ranges, overlapping / unreachable patterns and
+/// exhaustiveness - a match w/o `else` must cover evry value of the scrutinee. `origin` is the
+/// syntax the match was desugared frm, if any. both r usefulness questions, see `witnesses`
+pub fn check_match(stmt: &MatchStmt, scrutinee: &Type, origin: Option<&Desugaring>) -> Vec<MatchIssue> {
+    let domains = [Domain::of(scrutinee)];
+    let mut issues = Vec::new();
+    let mut rows: Vec<Vec<Pat>> = Vec::new();
+    // int ranges seen so far + the text they were written as, 4 overlaps
+    let mut ranges: Vec<(i128, i128, String)> = Vec::new();
+
+    for arm in &stmt.arms {
+        let mut reachable = arm.patterns.is_empty();
+        for pattern in &arm.patterns {
+            let Some(pat) = lower(pattern, scrutinee, &mut issues) else {
+                // already an error, dont also call the arm unreachable
+                reachable = true;
+                continue;
+            };
+            let text = describe(pattern);
+            let row = vec![pat];
+            if witnesses(&rows, &row, &domains).is_empty() {
+                issues.push(unreachable(pattern.span(), &text));
+            } else {
+                reachable = true;
+                if let Ctor::Range(lo, hi) = row[0].ctor {
+                    if let Some((_, _, other)) = ranges.iter().find(|(l, h, _)| *l <= hi && lo <= *h) {
+                        let message = format!("Pattern {} overlaps {}", text, other);
+                        issues.push(MatchIssue { span: pattern.span(), message, error: false, note: None });
+                    }
+                    ranges.push((lo, hi, text));
+                }
+            }
+            rows.push(row);
+        }
+        if !reachable {
+            if let Some(last) = issues.last_mut() {
+                last.note = Some("every pattern of this arm is matched by an earlier arm, so its body never runs".to_string());
+            }
+        }
+    }
+
+    let missing: Vec<Pat> = witnesses(&rows, &[Pat::wild()], &domains).into_iter().map(|mut w| w.remove(0)).collect();
+    let name = spelling(scrutinee);
+    let (not_exhaustive, note) = if missing.is_empty() || missing.iter().any(|p| p.ctor == Ctor::Wild) {
+        (format!("Match on {} is not exhaustive, add an `else` arm", name), None)
+    } else {
+        let names: Vec<String> = missing.iter().map(|p| bare(&domains[0], p)).collect();
+        let arms: Vec<String> = missing.iter().map(|p| format!("`when {}`", domains[0].show(p))).collect();
+        let note = format!("add {} or an `else` arm", arms.join(", "));
+        (format!("Match on {} is not exhaustive, missing {}", name, names.join(", ")), Some(note))
+    };
+    issues.extend(check_else(stmt, missing.is_empty(), not_exhaustive, note, origin));
+    issues
+}
+
+/// a pattern as a matrix row, None after reporting why it cant match the scrutinee
+fn lower(pattern: &Pattern, scrutinee: &Type, issues: &mut Vec<MatchIssue>) -> Option<Pat> {
+    let span = pattern.span();
+    let text = describe(pattern);
+    if let Type::Enum(e) = scrutinee {
+        let v = match pattern {
+            Pattern::Variant(v) if v.enum_name.as_ref().is_none_or(|n| *n == e.name) => v,
+            _ => {
+                issues.push(MatchIssue::error(span, format!("Pattern {} can't match a value of type {}", text, e.name)));
+                return None;
+            }
+        };
+        let Some(index) = e.variants.iter().position(|x| x.name == v.variant) else {
+            issues.push(MatchIssue::error(span, format!("Enum '{}' has no variant '{}'", e.name, v.variant)));
+            return None;
+        };
+        let variant = &e.variants[index];
+        if v.bindings.len() != variant.fields.len() {
+            let message = format!(
+                "Pattern {} binds {} value(s) but '{}::{}' has {}",
+                text,
+                v.bindings.len(),
+                e.name,
+                variant.name,
+                variant.fields.len()
+            );
+            issues.push(MatchIssue::error(span, message));
+        }
+        // bindings + `_` match any field
+        return Some(Pat::new(Ctor::Variant(index), variant.fields.len()));
+    }
+    if let Some(message) = type_mismatch(pattern, scrutinee) {
+        issues.push(MatchIssue::error(span, message));
+        return None;
+    }
+    let (lo, hi) = match pattern {
+        Pattern::Range(r) => (r.start as i128, r.end as i128),
+        Pattern::Literal(l) => match &l.kind {
+            LiteralKind::Int(n) => (*n as i128, *n as i128),
+            LiteralKind::Bool(b) => return Some(Pat::new(Ctor::Bool(*b), 0)),
+            _ => return Some(Pat::new(Ctor::Opaque(text), 0)),
+        },
+        Pattern::Variant(_) => return None,
+    };
+    if lo > hi {
+        issues.push(MatchIssue::error(span, format!("Range pattern {} is empty", text)));
+        return None;
+    }
+    if let Some((min, max)) = integer_bounds(scrutinee) {
+        if lo < min || hi > max {
+            let message = format!("Pattern {} is out of range for {} ({} .. {})", text, spelling(scrutinee), min, max);
+            issues.push(MatchIssue::error(span, message));
+            return None;
+        }
+    }
+    Some(Pat::new(Ctor::Range(lo, hi), 0))
+}
+
+/// a missing variant by its name alone, other values as they r written
+fn bare(domain: &Domain, pat: &Pat) -> String {
+    match (domain, &pat.ctor) {
+        (Domain::Enum(e), Ctor::Variant(i)) => e.variants[*i].name.clone(),
+        _ => domain.show(pat),
+    }
+}
+
+/// a missing `else` on a partial match is an error, one after exhaustive arms only warns
+/// an empty else is what `if let` w/o an else desugars 2, so it never warns. the else `while let`
+/// adds is the loop's exit, that 1 is reported as the loop never ending
+fn check_else(
+    stmt: &MatchStmt,
+    exhaustive: bool,
+    not_exhaustive: String,
+    note: Option<String>,
+    origin: Option<&Desugaring>,
+) -> Option<MatchIssue> {
+    match &stmt.else_branch {
+        None if !exhaustive => Some(MatchIssue { span: stmt.span, message: not_exhaustive, error: true, note }),
+        Some(e) if exhaustive && !e.is_empty() => {
+            let message = match origin {
+                Some(Desugaring::WhileLet) => {
+                    "Irrefutable `while let`: the pattern always matches, so the loop only ends through `break` or `return`"
+                }
+                Some(Desugaring::IfLet) => "Unreachable `else`: the `if let` pattern always matches",
+                _ => "Unreachable `else`: every value is already matched",
+            };
+            Some(MatchIssue { span: stmt.span, message: message.to_string(), error: false, note: None })
+        }
+        _ => None,
+    }
+}
+
+fn unreachable(span: Span, text: &str) -> MatchIssue {
+    MatchIssue { span, message: format!("Unreachable pattern: {} is already matched", text), error: false, note: None }
+}
+
+fn type_mismatch(pattern: &Pattern, scrutinee: &Type) -> Option<String> {
+    let fits = match pattern {
+        Pattern::Range(_) => integer_bounds(scrutinee).is_some(),
+        Pattern::Variant(_) => false,
+        Pattern::Literal(l) => match &l.kind {
+            LiteralKind::Int(_) => integer_bounds(scrutinee).is_some(),
+            LiteralKind::Float(_) => false,
+            LiteralKind::Bool(_) => matches!(scrutinee, Type::Primitive(PrimitiveType::Bool)),
+            LiteralKind::Char(_) => matches!(scrutinee, Type::Primitive(PrimitiveType::Char)),
+            LiteralKind::String(_) => matches!(scrutinee, Type::String),
+        },
+    };
+    (!fits).then(|| format!("Pattern {} can't match a value of type {}", describe(pattern), spelling(scrutinee)))
+}
+
+fn describe(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Range(r) => format!("{} .. {}", r.start, r.end),
+        Pattern::Variant(v) => {
+            let path = match &v.enum_name {
+                Some(e) => format!("{}::{}", e, v.variant),
+                None => v.variant.clone(),
+            };
+            if v.bindings.is_empty() { path } else { format!("{}({})", path, v.bindings.join(", ")) }
+        }
+        Pattern::Literal(l) => match &l.kind {
+            LiteralKind::Int(n) => n.to_string(),
+            LiteralKind::Float(f) => f.to_string(),
+            LiteralKind::Bool(b) => b.to_string(),
+            LiteralKind::Char(c) => format!("'{}'", c),
+            LiteralKind::String(s) => format!("\"{}\"", s),
+        },
+    }
+}
@@ -1,4 +1,5 @@
 use crate::core::ast::*;
+use crate::core::target::CallConv;
 use crate::core::types::resolver::resolve_ast_type;
 use crate::core::types::ty::Type;
 use crate::error::{Diagnostic, DiagnosticKind, Reporter};
@@ -21,12 +22,26 @@ impl<'a> FfiChecker<'a> {
     }
 
     pub fn check_foreign(&mut self, foreign: &Foreign) {
+        if CallConv::from_abi(&foreign.abi).is_none() {
+            self.error(foreign.span, &format!("Unknown calling convention '{}'", foreign.abi));
+        }
         for func in &foreign.functions {
-            self.check_foreign_function(func);
+            self.check_foreign_function(func, &foreign.abi);
         }
     }
 
-    fn check_foreign_function(&mut self, func: &ForeignFunction) {
+    fn check_foreign_function(&mut self, func: &ForeignFunction, block_abi: &str) {
+        // `with abi = "..."` overrides the block's convention
+        let abi = func.abi.as_deref().unwrap_or(block_abi);
+        match CallConv::from_abi(abi) {
+            None if func.abi.is_some() => self.error(func.span, &format!("Unknown calling convention '{}'", abi)),
+            // callee cleanup conventions cant pop a var arg list they dont know the size of
+            Some(CallConv::Stdcall | CallConv::Fastcall | CallConv::Thiscall | CallConv::Vectorcall) if func.variadic => self.error(
+                func.span,
+                &format!("Variadic function '{}' must use the C calling convention, not '{}'", func.name, abi),
+            ),
+            _ => {}
+        }
         for param in &func.params {
             if !self.is_c_compatible_type(&param.type_) {
                 self.error(
@@ -56,11 +71,13 @@ impl<'a> FfiChecker<'a> {
                     matches!(f.type_, Type::Primitive(_) | Type::Pointer(_))
                 })
             }
+            Type::Enum(_) => false, // tag + payload has no C equivalent
             Type::Array(_) => false,
             Type::Generic(_) => false,
             Type::Function(_) => false,
             Type::String => false,
             Type::TraitObject(_) => false, // trait objects not supported in FFI
+            Type::Error => false,
         }
     }
 
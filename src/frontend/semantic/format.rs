@@ -0,0 +1,175 @@
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+
+/// one argument a printf style format string consumes
+/// `start..end` is the byte range of its specifier in the (unescaped) format text
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatArg {
+    pub start: usize,
+    pub end: usize,
+    pub expect: FormatExpect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormatExpect {
+    /// d i u x X o, varargs promote anything narrower 2 int
+    Int(IntWidth),
+    /// c
+    Char,
+    /// f F e E g G a A
+    Float,
+    /// s, a nul terminated `ref char`
+    CString,
+    /// p
+    Pointer,
+    /// `*` width or precision
+    Count,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntWidth {
+    /// no length modifier, or h / hh
+    Int,
+    /// l ll j
+    Long,
+    /// z t
+    Size,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatError {
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+/// args consumed by `format` in order, C printf rules
+pub fn parse_format(format: &str) -> Result<Vec<FormatArg>, FormatError> {
+    let bytes = format.as_bytes();
+    let mut args = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        if bytes.get(i) == Some(&b'%') {
+            i += 1;
+            continue;
+        }
+        let mut counts = Vec::new();
+        while i < bytes.len() && b"-+ #0".contains(&bytes[i]) {
+            i += 1;
+        }
+        // width then .precision, either can come frm a `*` arg
+        if bytes.get(i) == Some(&b'*') {
+            counts.push(i);
+            i += 1;
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if bytes.get(i) == Some(&b'.') {
+            i += 1;
+            if bytes.get(i) == Some(&b'*') {
+                counts.push(i);
+                i += 1;
+            }
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        let modifier_start = i;
+        while i < bytes.len() && b"hljztL".contains(&bytes[i]) {
+            i += 1;
+        }
+        let modifier = &format[modifier_start..i];
+        let Some(&conversion) = bytes.get(i) else {
+            return Err(FormatError { start, end: i, message: "Incomplete format specifier at end of string".to_string() });
+        };
+        // a multibyte char here is still an unknown conversion, keep the range on a char boundary
+        let end = i + format[i..].chars().next().map_or(1, |c| c.len_utf8());
+        let spec = &format[start..end];
+        let width = match modifier {
+            "" | "h" | "hh" => IntWidth::Int,
+            "l" | "ll" | "j" => IntWidth::Long,
+            "z" | "t" => IntWidth::Size,
+            _ => {
+                return Err(FormatError { start, end, message: format!("Unsupported length modifier in '{}'", spec) });
+            }
+        };
+        let expect = match conversion {
+            b'd' | b'i' | b'u' | b'x' | b'X' | b'o' => FormatExpect::Int(width),
+            b'c' | b's' | b'p' if !modifier.is_empty() => {
+                return Err(FormatError { start, end, message: format!("Unsupported length modifier in '{}'", spec) });
+            }
+            b'c' => FormatExpect::Char,
+            b's' => FormatExpect::CString,
+            b'p' => FormatExpect::Pointer,
+            // `L` is long double, no emerald type has that layout
+            b'f' | b'F' | b'e' | b'E' | b'g' | b'G' | b'a' | b'A' if modifier.is_empty() || modifier == "l" => {
+                FormatExpect::Float
+            }
+            b'f' | b'F' | b'e' | b'E' | b'g' | b'G' | b'a' | b'A' => {
+                return Err(FormatError { start, end, message: format!("Unsupported length modifier in '{}'", spec) });
+            }
+            b'n' => {
+                return Err(FormatError { start, end, message: "'%n' writes thru an argument and is not allowed".to_string() });
+            }
+            _ => {
+                return Err(FormatError { start, end, message: format!("Unknown format specifier '{}'", spec) });
+            }
+        };
+        for at in counts {
+            args.push(FormatArg { start: at, end: at + 1, expect: FormatExpect::Count });
+        }
+        args.push(FormatArg { start, end, expect });
+        i = end;
+    }
+    Ok(args)
+}
+
+impl FormatExpect {
+    pub fn accepts(&self, ty: &Type) -> bool {
+        match (self, ty) {
+            (FormatExpect::Int(width), Type::Primitive(p)) if p.is_integer() => match width {
+                IntWidth::Int => p.size_in_bytes() <= 4,
+                IntWidth::Long => p.size_in_bytes() == 8,
+                IntWidth::Size => matches!(p, PrimitiveType::Size | PrimitiveType::Long | PrimitiveType::ULong),
+            },
+            // bool gets promoted 2 int on the way thru `...`
+            (FormatExpect::Int(IntWidth::Int), Type::Primitive(PrimitiveType::Bool)) => true,
+            (FormatExpect::Count,Type::Primitive(p)) => p.is_integer() && p.size_in_bytes() <= 4,
+            (FormatExpect::Char, Type::Primitive(p)) => {
+                matches!(p, PrimitiveType::Char | PrimitiveType::Byte | PrimitiveType::UByte | PrimitiveType::Int)
+            }
+            (FormatExpect::Float, Type::Primitive(p)) => p.is_float(),
+            // `string` is a (ptr, len) pair, passing it thru varargs isnt a char*
+            (FormatExpect::CString, Type::Pointer(p)) => {
+                matches!(&*p.pointee, Type::Primitive(PrimitiveType::Char | PrimitiveType::Byte | PrimitiveType::UByte))
+            }
+            (FormatExpect::Pointer, Type::Pointer(_)) => true,
+            _ => false,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            FormatExpect::Int(IntWidth::Int) => "an integer of at most 32 bits",
+            FormatExpect::Int(IntWidth::Long) => "a 64 bit integer",
+            FormatExpect::Int(IntWidth::Size) => "a size",
+            FormatExpect::Count => "an int width/precision",
+            FormatExpect::Char => "a char",
+            FormatExpect::Float => "a float",
+            FormatExpect::CString => "a ref char",
+            FormatExpect::Pointer => "a pointer",
+        }
+    }
+}
+
+/// variadic C fns whose last fixed param is a printf format string
+pub fn is_printf_like(name: &str) -> bool {
+    matches!(name, "printf" | "fprintf" | "sprintf" | "snprintf" | "dprintf")
+}
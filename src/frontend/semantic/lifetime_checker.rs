@@ -1,4 +1,5 @@
 use crate::core::ast::*;
+use crate::core::stack;
 use crate::error::{Diagnostic, DiagnosticKind, Reporter};
 use codespan::FileId;
 use std::collections::HashMap;
@@ -55,8 +56,8 @@ impl<'a> LifetimeChecker<'a> {
                 }
                 self.exit_scope();
             }
-            Item::Struct(_) | Item::Trait(_) | Item::TraitImpl(_) | Item::Module(_) 
-            | Item::Foreign(_) | Item::Require(_) | Item::Use(_) | Item::Global(_) 
+            Item::Struct(_) | Item::Enum(_) | Item::Trait(_) | Item::TraitImpl(_) | Item::Module(_) 
+            | Item::Foreign(_) | Item::Require(_) | Item::Import(_) | Item::Use(_) | Item::Global(_) 
             | Item::ForwardDecl(_) => {
                 // these dont need lifetime checking
             }
@@ -64,6 +65,10 @@ impl<'a> LifetimeChecker<'a> {
     }
 
     fn check_stmt(&mut self, stmt: &Stmt) {
+        stack::grow(|| self.check_stmt_inner(stmt))
+    }
+
+    fn check_stmt_inner(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Let(s) => {
                 // chk if value is a ref that might outlive its scope
@@ -123,11 +128,40 @@ impl<'a> LifetimeChecker<'a> {
                 }
                 self.exit_scope();
             }
+            Stmt::Guard(s) => {
+                self.check_expr(&s.condition);
+                self.enter_scope();
+                for stmt in &s.else_branch {
+                    self.check_stmt(stmt);
+                }
+                self.exit_scope();
+            }
+            Stmt::Defer(s) => {
+                self.enter_scope();
+                for stmt in &s.body {
+                    self.check_stmt(stmt);
+                }
+                self.exit_scope();
+            }
+            Stmt::Match(s) => {
+                self.check_expr(&s.scrutinee);
+                for body in s.arms.iter().map(|a| &a.body).chain(s.else_branch.as_ref()) {
+                    self.enter_scope();
+                    for stmt in body {
+                        self.check_stmt(stmt);
+                    }
+                    self.exit_scope();
+                }
+            }
             Stmt::Break(_) | Stmt::Continue(_) => {}
         }
     }
 
     fn check_expr(&mut self, expr: &Expr) {
+        stack::grow(|| self.check_expr_inner(expr))
+    }
+
+    fn check_expr_inner(&mut self, expr: &Expr) {
         match expr {
             Expr::Ref(r) => {
                 // chk that ref expr is valid in current scope
@@ -177,6 +211,11 @@ impl<'a> LifetimeChecker<'a> {
                     self.check_expr(elem);
                 }
             }
+            Expr::Tuple(t) => {
+                for elem in &t.elements {
+                    self.check_expr(elem);
+                }
+            }
             Expr::Block(b) => {
                 self.enter_scope();
                 for stmt in &b.stmts {
@@ -216,6 +255,15 @@ impl<'a> LifetimeChecker<'a> {
             Expr::Exists(e) => {
                 self.check_expr(&e.expr);
             }
+            Expr::New(n) => {
+                self.check_expr(&n.value);
+            }
+            Expr::Delete(d) => {
+                self.check_expr(&d.pointer);
+            }
+            Expr::Try(t) => {
+                self.check_expr(&t.expr);
+            }
             Expr::ModuleAccess(_) => {
                 // module access doesnt need lifetime checking
             }
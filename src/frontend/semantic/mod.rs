@@ -1,12 +1,20 @@
 pub mod analyzer;
+pub mod assoc_consts;
 pub mod borrow_checker;
 pub mod collector;
 pub mod comptime;
+pub mod definite_init;
+pub mod deprecation;
+pub mod exhaustiveness;
 pub mod ffi;
+pub mod format;
 pub mod lifetime_checker;
 pub mod module_registry;
 pub mod module_resolver;
 pub mod monomorphizer;
+pub mod noreturn;
+pub mod ownership;
+pub mod prelude;
 pub mod resolver;
 pub mod specializer;
 pub mod symbol_table;
@@ -14,17 +22,20 @@ pub mod trait_checker;
 pub mod trait_resolver;
 pub mod type_checker;
 pub mod type_resolver;
+pub mod usefulness;
 
 pub use analyzer::SemanticAnalyzer;
 pub use collector::SymbolCollector;
-pub use comptime::{ComptimeEvaluator, ComptimeValue};
+pub use comptime::{fold_hir_constant, ComptimeEvaluator, ComptimeValue};
 pub use ffi::FfiChecker;
 pub use lifetime_checker::LifetimeChecker;
 pub use module_registry::ModuleRegistry;
 pub use module_resolver::ModuleResolver;
 pub use monomorphizer::Monomorphizer;
+pub use prelude::Prelude;
 pub use specializer::Specializer;
 pub use trait_checker::TraitChecker;
 pub use trait_resolver::TraitResolver;
+pub use type_checker::Hole;
 pub use type_resolver::TypeResolver;
 pub use symbol_table::{Symbol, SymbolKind, SymbolTable};
@@ -136,6 +136,21 @@ impl ModuleRegistry {
     pub fn get_module_file_id(&self, path: &str) -> Option<FileId> {
         self.modules.get(path).map(|info| info.file_id)
     }
+
+    /// get the symbol table 4 a module
+    pub fn get_module_symbols(&self, path: &str) -> Option<&SymbolTable> {
+        self.modules.get(path).map(|info| &info.symbol_table)
+    }
+
+    /// take over the modules another registry loaded, ones already here win
+    pub fn absorb(&mut self, other: ModuleRegistry) {
+        for (name, module) in other.namespace_map {
+            self.namespace_map.entry(name).or_insert(module);
+        }
+        for (path, info) in other.modules {
+            self.modules.entry(path).or_insert(info);
+        }
+    }
 }
 
 impl Default for ModuleRegistry {
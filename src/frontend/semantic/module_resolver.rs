@@ -1,5 +1,6 @@
 use crate::core::ast::Ast;
-use crate::error::{Diagnostic, DiagnosticKind, Reporter};
+use crate::core::edition::Edition;
+use crate::error::{Diagnostic, DiagnosticKind, Reporter, Severity};
 use crate::frontend::lexer::Lexer;
 use crate::frontend::parser::Parser;
 use codespan::FileId;
@@ -12,6 +13,7 @@ pub struct ModuleResolver<'a> {
     reporter: &'a mut Reporter,
     loaded_modules: HashMap<String, (Ast, FileId)>,
     search_paths: Vec<PathBuf>,
+    edition: Edition,
 }
 
 impl<'a> ModuleResolver<'a> {
@@ -20,9 +22,16 @@ impl<'a> ModuleResolver<'a> {
             reporter,
             loaded_modules: HashMap::new(),
             search_paths: Vec::new(),
+            edition: Edition::default(),
         }
     }
 
+    /// modules r part of the same package so they r read w/ its edition
+    pub fn with_edition(mut self, edition: Edition) -> Self {
+        self.edition = edition;
+        self
+    }
+
     /// add a search path 4 module resolution
     /// modules will be searched in these paths in order
     pub fn add_search_path(&mut self, path: PathBuf) {
@@ -79,13 +88,14 @@ impl<'a> ModuleResolver<'a> {
         );
 
         // parse the module
-        let mut lexer = Lexer::new(&contents, file_id, self.reporter);
+        let mut lexer = Lexer::new(&contents, file_id, self.reporter).with_edition(self.edition);
         let tokens = lexer.tokenize();
-        let mut parser = Parser::new(tokens, file_id, self.reporter);
+        let mut parser = Parser::new(tokens, file_id, self.reporter).with_edition(self.edition);
         let ast = parser.parse();
 
-        // if parsing failed dont cache the module
-        if self.reporter.has_errors() {
+        // if parsing failed dont cache the module, errors frm other files dont count
+        let failed = self.reporter.diagnostics().iter().any(|d| d.file_id == file_id && d.severity == Severity::Error);
+        if failed {
             return None;
         }
 
@@ -95,6 +105,16 @@ impl<'a> ModuleResolver<'a> {
         Some((ast, file_id))
     }
 
+    /// `import util::strings` -> `./util/strings`, next 2 the importing file
+    /// `std::..` imports r looked up like `require "std/.."`
+    pub fn import_path(path: &[String]) -> String {
+        if path.first().is_some_and(|p| p == "std") {
+            path.join("/")
+        } else {
+            format!("./{}", path.join("/"))
+        }
+    }
+
     /// resolve a module path 2 an actual file path
     /// handles relative paths and standard library paths
     fn resolve_file_path(&self, path: &str, current_file: FileId) -> Option<PathBuf> {
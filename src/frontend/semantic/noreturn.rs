@@ -0,0 +1,151 @@
+use crate::core::ast::expr::{Expr, LiteralKind};
+use crate::core::ast::item::Item;
+use crate::core::ast::stmt::Stmt;
+use crate::core::stack;
+use codespan::Span;
+use std::collections::HashSet;
+
+/// libc functions that never return 2 their caller
+const NORETURN_FOREIGN: [&str; 5] = ["exit", "_exit", "_Exit", "abort", "quick_exit"];
+
+/// which fns never return + whether a block can fall off its end
+#[derive(Debug, Clone, Default)]
+pub struct NoReturn {
+    functions: HashSet<String>,
+}
+
+impl NoReturn {
+    /// the `panic` builtin + foreign libc exits, then fns w/o a `return` whose body always diverges, until nothing changes
+    pub fn analyze(items: &[Item]) -> Self {
+        let mut analysis = Self::default();
+        analysis.functions.insert("panic".to_string());
+        for item in items {
+            if let Item::Foreign(f) = item {
+                for func in &f.functions {
+                    if NORETURN_FOREIGN.contains(&func.name.as_str()) {
+                        analysis.functions.insert(func.name.clone());
+                    }
+                }
+            }
+        }
+        loop {
+            let mut changed = false;
+            for item in items {
+                let Item::Function(f) = item else { continue };
+                let Some(body) = &f.body else { continue };
+                if analysis.functions.contains(&f.name) || body.iter().any(contains_return) {
+                    continue;
+                }
+                if analysis.diverges(body) {
+                    analysis.functions.insert(f.name.clone());
+                    changed = true;
+                }
+            }
+            if !changed {
+                return analysis;
+            }
+        }
+    }
+
+    pub fn is_noreturn(&self, name: &str) -> bool {
+        self.functions.contains(name)
+    }
+
+    /// true if control can never reach the end of `stmts`
+    pub fn diverges(&self, stmts: &[Stmt]) -> bool {
+        stmts.iter().any(|s| self.stmt_diverges(s))
+    }
+
+    fn stmt_diverges(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Return(_) | Stmt::Break(_) | Stmt::Continue(_) => true,
+            Stmt::Expr(s) => self.expr_diverges(&s.expr),
+            Stmt::Let(s) => s.value.as_ref().is_some_and(|v| self.expr_diverges(v)),
+            Stmt::If(s) => {
+                self.expr_diverges(&s.condition)
+                    || s.else_branch.as_ref().is_some_and(|e| self.diverges(&s.then_branch) && self.diverges(e))
+            }
+            // matches r exhaustive, w/o an else the arms cover evry value
+            Stmt::Match(s) => {
+                self.expr_diverges(&s.scrutinee)
+                    || (s.arms.iter().all(|a| self.diverges(&a.body))
+                        && s.else_branch.as_ref().is_none_or(|e| self.diverges(e)))
+            }
+            Stmt::Guard(s) => self.expr_diverges(&s.condition),
+            // its body runs later, at the scope's exit
+            Stmt::Defer(_) => false,
+            // `while true` only ends thru a break
+            Stmt::While(s) => {
+                matches!(&s.condition, Expr::Literal(l) if matches!(l.kind, LiteralKind::Bool(true)))
+                    && !s.body.iter().any(breaks_out)
+            }
+            Stmt::For(_) => false,
+        }
+    }
+
+    fn expr_diverges(&self, expr: &Expr) -> bool {
+        stack::grow(|| self.expr_diverges_inner(expr))
+    }
+
+    fn expr_diverges_inner(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Call(c) => {
+                matches!(c.callee.as_ref(), Expr::Variable(v) if self.is_noreturn(&v.name))
+                    || c.args.iter().any(|a| self.expr_diverges(a))
+            }
+            Expr::Binary(b) => self.expr_diverges(&b.left),
+            Expr::Unary(u) => self.expr_diverges(&u.expr),
+            Expr::Try(t) => self.expr_diverges(&t.expr),
+            Expr::Assignment(a) => self.expr_diverges(&a.value),
+            _ => false,
+        }
+    }
+}
+
+fn contains_return(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Return(_) => true,
+        Stmt::If(s) => s.then_branch.iter().chain(s.else_branch.iter().flatten()).any(contains_return),
+        Stmt::While(s) => s.body.iter().any(contains_return),
+        Stmt::For(s) => s.body.iter().any(contains_return),
+        Stmt::Match(s) => s.arms.iter().flat_map(|a| &a.body).chain(s.else_branch.iter().flatten()).any(contains_return),
+        Stmt::Guard(s) => s.else_branch.iter().any(contains_return),
+        _ => false,
+    }
+}
+
+/// the 1st `return`, `break` or `continue` in a `defer` body that would jump out of it, a loop in
+/// the body keeps its own breaks. none if control always reaches the body's end
+pub fn leaves_defer(stmts: &[Stmt]) -> Option<(&'static str, Span)> {
+    fn leaves(stmt: &Stmt, in_loop: bool) -> Option<(&'static str, Span)> {
+        let any = |stmts: &[Stmt], in_loop| stmts.iter().find_map(|s| leaves(s, in_loop));
+        match stmt {
+            Stmt::Return(s) => Some(("return", s.span)),
+            Stmt::Break(s) if !in_loop => Some(("break", s.span)),
+            Stmt::Continue(s) if !in_loop => Some(("continue", s.span)),
+            Stmt::If(s) => any(&s.then_branch, in_loop).or_else(|| s.else_branch.as_deref().and_then(|e| any(e, in_loop))),
+            Stmt::Match(s) => s
+                .arms
+                .iter()
+                .map(|a| a.body.as_slice())
+                .chain(s.else_branch.as_deref())
+                .find_map(|body| any(body, in_loop)),
+            Stmt::Guard(s) => any(&s.else_branch, in_loop),
+            Stmt::While(s) => any(&s.body, true),
+            Stmt::For(s) => any(&s.body, true),
+            _ => None,
+        }
+    }
+    stmts.iter().find_map(|s| leaves(s, false))
+}
+
+/// a break that leaves this loop, ones inside nested loops dont count
+fn breaks_out(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Break(_) => true,
+        Stmt::If(s) => s.then_branch.iter().chain(s.else_branch.iter().flatten()).any(breaks_out),
+        Stmt::Match(s) => s.arms.iter().flat_map(|a| &a.body).chain(s.else_branch.iter().flatten()).any(breaks_out),
+        Stmt::Guard(s) => s.else_branch.iter().any(breaks_out),
+        _ => false,
+    }
+}
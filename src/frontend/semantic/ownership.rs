@@ -0,0 +1,600 @@
+use crate::core::ast::*;
+use crate::core::stack;
+use crate::error::{Diagnostic, DiagnosticKind, Reporter};
+use crate::frontend::semantic::noreturn::NoReturn;
+use codespan::{FileId, Span};
+use std::collections::{HashMap, HashSet};
+
+/// what happened 2 the allocation a local got frm `new`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Owner {
+    /// the local still owns what it got at `since`, MIR lowering frees it when the local's scope ends
+    Owned { since: Span },
+    /// gone at `at`, on evry path or only some
+    Gone { at: Span, how: Gone, maybe: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Gone {
+    Moved,
+    Deleted,
+    /// owned in only some branches, the ones that own it free it at their end
+    Freed,
+}
+
+/// a local by its name + where it was declared, so an inner let that shadows an outer 1 is
+/// tracked apart frm it
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Binding {
+    name: String,
+    declared: Span,
+}
+
+/// locals that got a `new` allocation -> what became of it, a local not in here owns nothing
+type Owners = HashMap<Binding, Owner>;
+
+/// a local holding a `new` allocation, or the result of a fn in `owned_results`, owns it until
+/// it's moved or deleted. a move is the local as the value of a let, an assignment, a `return`, a
+/// struct or array literal or a `new`, the new holder owns it after a let or assignment + nothing
+/// does otherwise. call args, field reads + comparisons only borrow it. any use of a moved or
+/// deleted local is an error, + so is deleting it again
+pub struct OwnershipChecker<'a> {
+    reporter: &'a mut Reporter,
+    file_id: FileId,
+    noreturn: NoReturn,
+    /// fns whose result the caller owns
+    owned_results: HashSet<String>,
+    /// the `defer` bodies of each open block, innermost last, chkd where they run
+    deferred: Vec<Vec<Vec<Stmt>>>,
+    /// the params + lets each open block declared, innermost last
+    scopes: Vec<Vec<Binding>>,
+}
+
+impl<'a> OwnershipChecker<'a> {
+    pub fn new(reporter: &'a mut Reporter, file_id: FileId) -> Self {
+        Self {
+            reporter,
+            file_id,
+            noreturn: NoReturn::default(),
+            owned_results: HashSet::new(),
+            deferred: Vec::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn check(&mut self, ast: &Ast) {
+        self.noreturn = NoReturn::analyze(&ast.items);
+        self.owned_results = owned_results(&ast.items);
+        for item in &ast.items {
+            match item {
+                Item::Function(f) => self.check_function(f),
+                Item::TraitImpl(ti) => ti.methods.iter().for_each(|m| self.check_function(m)),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_function(&mut self, f: &Function) {
+        if let Some(body) = &f.body {
+            self.scopes.push(f.params.iter().map(|p| Binding { name: p.name.clone(), declared: p.span }).collect());
+            self.check_block(body, &mut Owners::new());
+            self.scopes.pop();
+        }
+    }
+
+    /// a block's deferred bodies run at its end, the last 1 first
+    fn check_block(&mut self, stmts: &[Stmt], owners: &mut Owners) {
+        self.deferred.push(Vec::new());
+        self.scopes.push(Vec::new());
+        for stmt in stmts {
+            self.check_stmt(stmt, owners);
+        }
+        let deferred = self.deferred.pop().unwrap_or_default();
+        if !self.noreturn.diverges(stmts) {
+            self.run_deferred(deferred.iter().rev(), owners);
+        }
+        self.scopes.pop();
+    }
+
+    /// the local `name` means here, none 4 a global or fn
+    fn binding(&self, name: &str) -> Option<Binding> {
+        self.scopes.iter().rev().flat_map(|scope| scope.iter().rev()).find(|b| b.name == name).cloned()
+    }
+
+    fn owner(&self, name: &str, owners: &Owners) -> Option<(Binding, Owner)> {
+        let binding = self.binding(name)?;
+        owners.get(&binding).map(|owner| (binding, *owner))
+    }
+
+    /// chks deferred bodies as they run, a `return` in 1 (already an error) doesnt run them again
+    fn run_deferred<'b>(&mut self, bodies: impl Iterator<Item = &'b Vec<Stmt>>, owners: &mut Owners) {
+        let open = std::mem::take(&mut self.deferred);
+        for body in bodies {
+            self.check_block(body, owners);
+        }
+        self.deferred = open;
+    }
+
+    /// owners after `stmts`, none if they never fall thru
+    fn branch(&mut self, stmts: &[Stmt], owners: &Owners) -> Option<Owners> {
+        let mut branch = owners.clone();
+        self.check_block(stmts, &mut branch);
+        (!self.noreturn.diverges(stmts)).then_some(branch)
+    }
+
+    fn check_stmt(&mut self, stmt: &Stmt, owners: &mut Owners) {
+        stack::grow(|| self.check_stmt_inner(stmt, owners))
+    }
+
+    fn check_stmt_inner(&mut self, stmt: &Stmt, owners: &mut Owners) {
+        match stmt {
+            Stmt::Let(s) => {
+                let owning = s.value.as_ref().is_some_and(|v| self.owning(v, owners));
+                if let Some(value) = &s.value {
+                    self.consume(value, owners);
+                }
+                let binding = Binding { name: s.name.clone(), declared: s.span };
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.push(binding.clone());
+                }
+                owners.remove(&binding);
+                if owning {
+                    owners.insert(binding, Owner::Owned { since: s.span });
+                }
+            }
+            Stmt::Expr(s) => self.read(&s.expr, owners),
+            Stmt::Return(s) => {
+                if let Some(value) = &s.value {
+                    self.consume(value, owners);
+                }
+                // evry open block's deferred bodies run b4 the fn returns
+                let deferred = self.deferred.clone();
+                self.run_deferred(deferred.iter().rev().flat_map(|bodies| bodies.iter().rev()), owners);
+            }
+            Stmt::If(s) => {
+                self.read(&s.condition, owners);
+                let then = self.branch(&s.then_branch, owners);
+                let otherwise = match &s.else_branch {
+                    Some(stmts) => self.branch(stmts, owners),
+                    None => Some(owners.clone()),
+                };
+                if let Some(merged) = merge([then, otherwise]) {
+                    *owners = merged;
+                }
+            }
+            Stmt::While(s) => {
+                self.read(&s.condition, owners);
+                let body = self.branch(&s.body, owners);
+                self.check_loop(body, owners, s.span);
+            }
+            Stmt::For(s) => {
+                // the init's let is the loop's own
+                self.scopes.push(Vec::new());
+                if let Some(init) = &s.init {
+                    self.check_stmt(init, owners);
+                }
+                if let Some(condition) = &s.condition {
+                    self.read(condition, owners);
+                }
+                let mut body = owners.clone();
+                self.check_block(&s.body, &mut body);
+                if let Some(increment) = &s.increment {
+                    self.read(increment, &mut body);
+                }
+                let body = (!self.noreturn.diverges(&s.body)).then_some(body);
+                self.check_loop(body, owners, s.span);
+                self.scopes.pop();
+            }
+            Stmt::Guard(s) => {
+                self.read(&s.condition, owners);
+                self.branch(&s.else_branch, owners);
+            }
+            Stmt::Match(s) => {
+                self.read(&s.scrutinee, owners);
+                let mut arms: Vec<Option<Owners>> = s.arms.iter().map(|a| self.branch(&a.body, owners)).collect();
+                if let Some(stmts) = &s.else_branch {
+                    let otherwise = self.branch(stmts, owners);
+                    arms.push(otherwise);
+                }
+                if let Some(merged) = merge(arms) {
+                    *owners = merged;
+                }
+            }
+            Stmt::Defer(s) => {
+                if let Some(bodies) = self.deferred.last_mut() {
+                    bodies.push(s.body.clone());
+                }
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+        }
+    }
+
+    /// a local owned b4 the loop has 2 still b owned after its body, the next iteration uses it again
+    fn check_loop(&mut self, body: Option<Owners>, owners: &Owners, span: Span) {
+        let Some(body) = body else { return };
+        let mut lost: Vec<(&Binding, Option<Owner>)> = owners
+            .iter()
+            .filter(|(binding, owner)| matches!(owner, Owner::Owned { .. }) && !matches!(body.get(*binding), Some(Owner::Owned { .. })))
+            .map(|(binding, _)| (binding, body.get(binding).copied()))
+            .collect();
+        lost.sort_by(|a, b| a.0.cmp(b.0));
+        for (Binding { name, .. }, owner) in lost {
+            match owner {
+                Some(Owner::Gone { at, how, .. }) => {
+                    let message = format!("'{}' is {} inside a loop, the next iteration would use it again", name, how.verb());
+                    self.error(at, message);
+                }
+                _ => self.error(span, format!("'{}' owns an allocation when the loop starts but not when its body ends", name)),
+            }
+        }
+    }
+
+    /// true if `value` hands its holder an allocation 2 own
+    fn owning(&self, value: &Expr, owners: &Owners) -> bool {
+        match value {
+            Expr::New(_) => true,
+            Expr::Variable(v) => matches!(self.owner(&v.name, owners), Some((_, Owner::Owned { .. }))),
+            Expr::Call(c) => {
+                matches!(c.callee.as_ref(), Expr::Variable(v) if self.owned_results.contains(&v.name) && self.binding(&v.name).is_none())
+            }
+            _ => false,
+        }
+    }
+
+    /// `expr` used as a value that ends up somewhere else, a local in it is moved
+    fn consume(&mut self, expr: &Expr, owners: &mut Owners) {
+        self.read(expr, owners);
+        if let Expr::Variable(v) = expr {
+            if let Some((binding, Owner::Owned { .. })) = self.owner(&v.name, owners) {
+                owners.insert(binding, Owner::Gone { at: v.span, how: Gone::Moved, maybe: false });
+            }
+        }
+    }
+
+    fn read(&mut self, expr: &Expr, owners: &mut Owners) {
+        stack::grow(|| self.read_inner(expr, owners))
+    }
+
+    fn read_inner(&mut self, expr: &Expr, owners: &mut Owners) {
+        match expr {
+            Expr::Variable(v) => self.use_local(v, owners),
+            Expr::Assignment(a) => {
+                let owning = self.owning(&a.value, owners);
+                self.consume(&a.value, owners);
+                match a.target.as_ref() {
+                    // the old allocation is freed, the local owns whatever it got
+                    Expr::Variable(v) => {
+                        if let Some(binding) = self.binding(&v.name) {
+                            owners.remove(&binding);
+                            if owning {
+                                owners.insert(binding, Owner::Owned { since: a.span });
+                            }
+                        }
+                    }
+                    // each target gets an element, whatever it held b4 isnt tracked any more
+                    Expr::Tuple(t) => {
+                        for target in &t.elements {
+                            match target {
+                                Expr::Variable(v) => {
+                                    if let Some(binding) = self.binding(&v.name) {
+                                        owners.remove(&binding);
+                                    }
+                                }
+                                target => self.read(target, owners),
+                            }
+                        }
+                    }
+                    target => self.read(target, owners),
+                }
+            }
+            Expr::Delete(d) => match d.pointer.as_ref() {
+                Expr::Variable(v) => match self.owner(&v.name, owners) {
+                    Some((binding, Owner::Owned { .. })) => {
+                        owners.insert(binding, Owner::Gone { at: d.span, how: Gone::Deleted, maybe: false });
+                    }
+                    Some((binding, Owner::Gone { at, how, maybe })) => {
+                        let message = match (how, maybe) {
+                            (Gone::Deleted, false) => format!("'{}' is deleted twice", v.name),
+                            (Gone::Moved, _) => format!("'{}' is deleted after it was moved, its new owner frees it", v.name),
+                            _ => format!("'{}' may already have been freed", v.name),
+                        };
+                        self.error_at(d.span, message, at, how.label(&v.name));
+                        owners.remove(&binding);
+                    }
+                    None => {}
+                },
+                pointer => self.read(pointer, owners),
+            },
+            Expr::New(n) => self.consume(&n.value, owners),
+            Expr::StructLiteral(s) => {
+                for (_, value) in &s.fields {
+                    self.consume(value, owners);
+                }
+            }
+            Expr::ArrayLiteral(a) => {
+                for element in &a.elements {
+                    self.consume(element, owners);
+                }
+            }
+            Expr::Tuple(t) => {
+                for element in &t.elements {
+                    self.consume(element, owners);
+                }
+            }
+            Expr::FieldAccess(f) => self.read(&f.object, owners),
+            Expr::Binary(b) => {
+                self.read(&b.left, owners);
+                self.read(&b.right, owners);
+            }
+            Expr::Unary(u) => self.read(&u.expr, owners),
+            Expr::Try(t) => self.read(&t.expr, owners),
+            Expr::Call(c) => {
+                self.read(&c.callee, owners);
+                for arg in &c.args {
+                    self.read(arg, owners);
+                }
+            }
+            Expr::MethodCall(m) => {
+                self.read(&m.receiver, owners);
+                for arg in &m.args {
+                    self.read(arg, owners);
+                }
+            }
+            Expr::Index(i) => {
+                self.read(&i.array, owners);
+                self.read(&i.index, owners);
+            }
+            Expr::Block(b) => {
+                self.check_block(&b.stmts, owners);
+                if let Some(e) = &b.expr {
+                    self.read(e, owners);
+                }
+            }
+            Expr::If(i) => {
+                self.read(&i.condition, owners);
+                let mut then = owners.clone();
+                self.read(&i.then_branch, &mut then);
+                let mut otherwise = owners.clone();
+                if let Some(e) = &i.else_branch {
+                    self.read(e, &mut otherwise);
+                }
+                if let Some(merged) = merge([Some(then), Some(otherwise)]) {
+                    *owners = merged;
+                }
+            }
+            Expr::Closure(c) => {
+                self.branch(&c.body, owners);
+            }
+            Expr::Ref(r) => self.read(&r.expr, owners),
+            Expr::At(a) => self.read(&a.expr, owners),
+            Expr::Exists(e) => self.read(&e.expr, owners),
+            Expr::Comptime(c) => self.read(&c.expr, owners),
+            Expr::Literal(_) | Expr::ModuleAccess(_) | Expr::Null => {}
+        }
+    }
+
+    fn use_local(&mut self, v: &VariableExpr, owners: &mut Owners) {
+        if let Some((binding, Owner::Gone { at, how, maybe })) = self.owner(&v.name, owners) {
+            let message = match maybe {
+                false => format!("'{}' is used after it was {}", v.name, how.verb()),
+                true => format!("'{}' is used after it may have been {}", v.name, how.verb()),
+            };
+            self.error_at(v.span, message, at, how.label(&v.name));
+            // reported once, later uses dont repeat it
+            owners.remove(&binding);
+        }
+    }
+
+    fn error(&mut self, span: Span, message: String) {
+        let diagnostic = Diagnostic::error(DiagnosticKind::SemanticError, span, self.file_id, message);
+        self.reporter.add_diagnostic(diagnostic);
+    }
+
+    fn error_at(&mut self, span: Span, message: String, gone: Span, label: String) {
+        let diagnostic = Diagnostic::error(DiagnosticKind::SemanticError, span, self.file_id, message)
+            .with_label(self.file_id, gone, label);
+        self.reporter.add_diagnostic(diagnostic);
+    }
+}
+
+impl Gone {
+    fn verb(self) -> &'static str {
+        match self {
+            Gone::Moved => "moved",
+            Gone::Deleted => "deleted",
+            Gone::Freed => "freed",
+        }
+    }
+
+    /// the note on where it went
+    fn label(self, name: &str) -> String {
+        match self {
+            Gone::Freed => format!("'{}' gets an allocation here that's freed when the branch ends", name),
+            how => format!("'{}' is {} here", name, how.verb()),
+        }
+    }
+}
+
+/// fns w/ a `ref` result the caller owns: evry `return` hands over a `new`, a local that only
+/// ever got 1 or the result of another such fn. 1 that returns a param or a field only lends it
+pub fn owned_results(items: &[Item]) -> HashSet<String> {
+    let functions: Vec<&Function> = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Function(f) if f.body.is_some() && matches!(&f.return_type, Some(Type::Pointer(p)) if !p.nullable) => Some(f),
+            _ => None,
+        })
+        .collect();
+    // assume all of them, then drop the ones returning something else until nothing changes
+    let mut owned: HashSet<String> = functions.iter().map(|f| f.name.clone()).collect();
+    loop {
+        let before = owned.len();
+        for f in &functions {
+            let mut found = Stores::default();
+            found.stmts(f.body.as_deref().unwrap_or_default());
+            let locals: HashSet<&str> = f.params.iter().map(|p| p.name.as_str()).chain(found.locals.iter().map(|(name, _)| *name)).collect();
+            let owning = |value: &Option<&Expr>| match value {
+                Some(Expr::New(_)) => true,
+                Some(Expr::Call(c)) => {
+                    matches!(c.callee.as_ref(), Expr::Variable(v) if owned.contains(&v.name) && !locals.contains(v.name.as_str()))
+                }
+                _ => false,
+            };
+            let hands_over = found.returns.iter().all(|value| match value {
+                // a param is the caller's own, a local has 2 own whatever it was ever given
+                Some(Expr::Variable(v)) => {
+                    let mut given = found.locals.iter().filter(|(name, _)| *name == v.name).map(|(_, value)| value).peekable();
+                    !f.params.iter().any(|p| p.name == v.name) && given.peek().is_some() && given.all(owning)
+                }
+                value => owning(value),
+            });
+            if !hands_over {
+                owned.remove(&f.name);
+            }
+        }
+        if owned.len() == before {
+            return owned;
+        }
+    }
+}
+
+/// evry `return`'s value + evry value a let or assignment gives a local, outside closures
+#[derive(Default)]
+struct Stores<'b> {
+    returns: Vec<Option<&'b Expr>>,
+    locals: Vec<(&'b str, Option<&'b Expr>)>,
+}
+
+impl<'b> Stores<'b> {
+    fn stmts(&mut self, stmts: &'b [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'b Stmt) {
+        match stmt {
+            Stmt::Let(s) => {
+                self.locals.push((&s.name, s.value.as_ref()));
+                s.value.iter().for_each(|v| self.expr(v));
+            }
+            Stmt::Return(s) => {
+                self.returns.push(s.value.as_ref());
+                s.value.iter().for_each(|v| self.expr(v));
+            }
+            Stmt::Expr(s) => self.expr(&s.expr),
+            Stmt::If(s) => {
+                self.expr(&s.condition);
+                self.stmts(&s.then_branch);
+                s.else_branch.iter().for_each(|stmts| self.stmts(stmts));
+            }
+            Stmt::While(s) => {
+                self.expr(&s.condition);
+                self.stmts(&s.body);
+            }
+            Stmt::For(s) => {
+                s.init.iter().for_each(|init| self.stmt(init));
+                s.condition.iter().chain(&s.increment).for_each(|e| self.expr(e));
+                self.stmts(&s.body);
+            }
+            Stmt::Guard(s) => {
+                self.expr(&s.condition);
+                self.stmts(&s.else_branch);
+            }
+            Stmt::Match(s) => {
+                self.expr(&s.scrutinee);
+                s.arms.iter().for_each(|arm| self.stmts(&arm.body));
+                s.else_branch.iter().for_each(|stmts| self.stmts(stmts));
+            }
+            Stmt::Defer(s) => self.stmts(&s.body),
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &'b Expr) {
+        stack::grow(|| self.expr_inner(expr))
+    }
+
+    fn expr_inner(&mut self, expr: &'b Expr) {
+        match expr {
+            Expr::Assignment(a) => {
+                match a.target.as_ref() {
+                    Expr::Variable(v) => self.locals.push((&v.name, Some(&a.value))),
+                    Expr::Tuple(t) => {
+                        for target in &t.elements {
+                            match target {
+                                Expr::Variable(v) => self.locals.push((&v.name, None)),
+                                target => self.expr(target),
+                            }
+                        }
+                    }
+                    target => self.expr(target),
+                }
+                self.expr(&a.value);
+            }
+            Expr::Block(b) => {
+                self.stmts(&b.stmts);
+                b.expr.iter().for_each(|e| self.expr(e));
+            }
+            Expr::If(i) => {
+                self.expr(&i.condition);
+                self.expr(&i.then_branch);
+                i.else_branch.iter().for_each(|e| self.expr(e));
+            }
+            Expr::Call(c) => {
+                self.expr(&c.callee);
+                c.args.iter().for_each(|arg| self.expr(arg));
+            }
+            Expr::MethodCall(m) => {
+                self.expr(&m.receiver);
+                m.args.iter().for_each(|arg| self.expr(arg));
+            }
+            Expr::Binary(b) => {
+                self.expr(&b.left);
+                self.expr(&b.right);
+            }
+            Expr::Index(i) => {
+                self.expr(&i.array);
+                self.expr(&i.index);
+            }
+            Expr::StructLiteral(s) => s.fields.iter().for_each(|(_, value)| self.expr(value)),
+            Expr::ArrayLiteral(a) => a.elements.iter().for_each(|e| self.expr(e)),
+            Expr::Tuple(t) => t.elements.iter().for_each(|e| self.expr(e)),
+            Expr::New(n) => self.expr(&n.value),
+            Expr::Delete(d) => self.expr(&d.pointer),
+            Expr::FieldAccess(f) => self.expr(&f.object),
+            Expr::Unary(u) => self.expr(&u.expr),
+            Expr::Try(t) => self.expr(&t.expr),
+            Expr::Ref(r) => self.expr(&r.expr),
+            Expr::At(a) => self.expr(&a.expr),
+            Expr::Exists(e) => self.expr(&e.expr),
+            Expr::Comptime(c) => self.expr(&c.expr),
+            // a closure's returns r its own
+            Expr::Closure(_) | Expr::Variable(_) | Expr::Literal(_) | Expr::ModuleAccess(_) | Expr::Null => {}
+        }
+    }
+}
+
+/// a local is owned after the branches only if evry branch that falls thru still owns it, else
+/// it may b gone. none if no branch falls thru
+fn merge(branches: impl IntoIterator<Item = Option<Owners>>) -> Option<Owners> {
+    branches.into_iter().flatten().reduce(|merged, branch| {
+        let mut bindings: Vec<&Binding> = merged.keys().chain(branch.keys()).collect();
+        bindings.sort();
+        bindings.dedup();
+        bindings
+            .into_iter()
+            .map(|binding| {
+                let owner = match (merged.get(binding).copied(), branch.get(binding).copied()) {
+                    (Some(Owner::Owned { since }), Some(Owner::Owned { .. })) => Owner::Owned { since },
+                    (Some(Owner::Gone { at, how, .. }), _) | (_, Some(Owner::Gone { at, how, .. })) => Owner::Gone { at, how, maybe: true },
+                    (Some(Owner::Owned { since }), None) | (None, Some(Owner::Owned { since })) => {
+                        Owner::Gone { at: since, how: Gone::Freed, maybe: true }
+                    }
+                    (None, None) => unreachable!("the name came frm 1 of them"),
+                };
+                (binding.clone(), owner)
+            })
+            .collect()
+    })
+}
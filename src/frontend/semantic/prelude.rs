@@ -0,0 +1,127 @@
+use crate::core::ast::item::{Foreign, ForeignFunction};
+use crate::core::ast::{Ast, Item};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use std::collections::HashSet;
+
+/// names evry module sees w/o importing them. `print` is a compiler builtin so its not in here
+const SOURCE: &str = r#"
+struct Option [ Type T ]
+  some : bool
+  value : T
+end
+
+struct Result [ Type T, Type E ]
+  ok : bool
+  value : T
+  error : E
+end
+
+struct Error
+  code : int
+end
+
+struct List [ Type T ]
+  data : ref T
+  len : int
+  capacity : int
+end
+
+foreign "C" prelude
+  def abort
+end
+
+def assert(cond : bool)
+  if not cond
+    abort()
+  end
+end
+"#;
+
+/// the parsed prelude, `--no-prelude` leaves it out 4 freestanding builds
+#[derive(Debug, Clone)]
+pub struct Prelude {
+    ast: Ast,
+}
+
+impl Prelude {
+    pub fn load(reporter: &mut Reporter) -> Self {
+        let file_id = reporter.files_mut().add("<prelude>".to_string(), SOURCE.to_string());
+        let tokens = Lexer::new(SOURCE, file_id, reporter).tokenize();
+        let ast = Parser::new(tokens, file_id, reporter).parse();
+        Self { ast }
+    }
+
+    /// whether `inject` gives `ast` the prelude's fn `name` rather than leaving the program's own
+    pub fn provides(&self, ast: &Ast, name: &str) -> bool {
+        self.ast.items.iter().any(|i| matches!(i, Item::Function(f) if f.name == name)) && !defined_names(ast).contains(name)
+    }
+
+    /// `ast` w/ the prelude items it doesnt define itself in front, a module's own `Option` wins.
+    /// only the program defines the prelude's fns, an imported module gets them as foreign decls
+    /// so evry object doesnt carry its own copy
+    pub fn inject(&self, ast: &Ast, defines_fns: bool) -> Ast {
+        let taken = defined_names(ast);
+        let mut items = Vec::new();
+        let mut decls = Vec::new();
+        for item in &self.ast.items {
+            match item {
+                Item::Function(f) if taken.contains(&f.name) => {}
+                Item::Function(f) if defines_fns => items.push(item.clone()),
+                Item::Function(f) => decls.push(ForeignFunction {
+                    name: f.name.clone(),
+                    params: f.params.clone(),
+                    return_type: f.return_type.clone(),
+                    abi: None,
+                    variadic: false,
+                    span: f.span,
+                }),
+                Item::Foreign(block) => {
+                    let functions: Vec<_> = block.functions.iter().filter(|f| !taken.contains(&f.name)).cloned().collect();
+                    if !functions.is_empty() {
+                        items.push(Item::Foreign(Foreign { functions, ..block.clone() }));
+                    }
+                }
+                _ => {
+                    if item_name(item).is_none_or(|name| !taken.contains(name)) {
+                        items.push(item.clone());
+                    }
+                }
+            }
+        }
+        if !decls.is_empty() {
+            items.push(Item::Foreign(Foreign {
+                abi: "C".to_string(),
+                name: "prelude".to_string(),
+                functions: decls,
+                span: self.ast.span,
+            }));
+        }
+        items.extend(ast.items.iter().cloned());
+        Ast { items, span: ast.span }
+    }
+}
+
+fn item_name(item: &Item) -> Option<&String> {
+    match item {
+        Item::Function(f) => Some(&f.name),
+        Item::Struct(s) => Some(&s.name),
+        Item::Enum(e) => Some(&e.name),
+        Item::Trait(t) => Some(&t.name),
+        Item::Module(m) => Some(&m.name),
+        Item::Global(g) => Some(&g.name),
+        Item::ForwardDecl(d) => Some(&d.name),
+        _ => None,
+    }
+}
+
+fn defined_names(ast: &Ast) -> HashSet<String> {
+    let mut names: HashSet<String> = ast.items.iter().filter_map(item_name).cloned().collect();
+    for item in &ast.items {
+        if let Item::Foreign(block) = item {
+            names.extend(block.functions.iter().map(|f| f.name.clone()));
+        }
+    }
+    names
+}
@@ -223,6 +223,29 @@ impl<'a> NameResolver<'a> {
                     self.symbol_table.exit_scope();
                 }
             }
+            crate::core::ast::stmt::Stmt::Guard(s) => {
+                self.resolve_expr(&s.condition);
+                for stmt in &s.else_branch {
+                    self.symbol_table.enter_scope();
+                    self.resolve_stmt(stmt);
+                    self.symbol_table.exit_scope();
+                }
+            }
+            crate::core::ast::stmt::Stmt::Defer(s) => {
+                for stmt in &s.body {
+                    self.symbol_table.enter_scope();
+                    self.resolve_stmt(stmt);
+                    self.symbol_table.exit_scope();
+                }
+            }
+            crate::core::ast::stmt::Stmt::Match(s) => {
+                self.resolve_expr(&s.scrutinee);
+                for stmt in s.arms.iter().flat_map(|a| &a.body).chain(s.else_branch.iter().flatten()) {
+                    self.symbol_table.enter_scope();
+                    self.resolve_stmt(stmt);
+                    self.symbol_table.exit_scope();
+                }
+            }
             _ => {
                 // 4 othr statements just resolve expressions
                 if let crate::core::ast::stmt::Stmt::Expr(e) = stmt {
@@ -244,6 +267,9 @@ impl<'a> NameResolver<'a> {
             crate::core::ast::expr::Expr::Unary(u) => {
                 self.resolve_expr(&u.expr);
             }
+            crate::core::ast::expr::Expr::Try(t) => {
+                self.resolve_expr(&t.expr);
+            }
             crate::core::ast::expr::Expr::Call(c) => {
                 self.resolve_expr(&c.callee);
                 for arg in &c.args {
@@ -1,4 +1,5 @@
 use crate::core::ast::*;
+use crate::core::mir::specialized_name;
 use crate::core::types::generic::GenericContext;
 use crate::core::types::ty::Type as ResolvedType;
 use std::collections::HashMap;
@@ -66,6 +67,7 @@ impl Specializer {
             Param {
                 name: p.name.clone(),
                 type_: self.substitute_ast_type(&p.type_, context),
+                comptime: p.comptime,
                 span: p.span,
             }
         }).collect();
@@ -89,6 +91,8 @@ impl Specializer {
             return_type: specialized_return_type,
             body: specialized_body,
             uses: f.uses.clone(),
+            target_features: f.target_features.clone(),
+            deprecated: f.deprecated.clone(),
             span: f.span,
         })
     }
@@ -102,6 +106,7 @@ impl Specializer {
             Field {
                 name: f.name.clone(),
                 type_: self.substitute_ast_type(&f.type_, context),
+                deprecated: f.deprecated.clone(),
                 span: f.span,
             }
         }).collect();
@@ -110,6 +115,8 @@ impl Specializer {
             name: specialized_name,
             generics: Vec::new(), // specialized structs have no generics
             fields: specialized_fields,
+            derives: s.derives.clone(),
+            deprecated: s.deprecated.clone(),
             span: s.span,
         })
     }
@@ -164,11 +171,15 @@ impl Specializer {
                     crate::core::types::primitive::PrimitiveType::Float => crate::core::ast::types::PrimitiveType::Float,
                     crate::core::types::primitive::PrimitiveType::Bool => crate::core::ast::types::PrimitiveType::Bool,
                     crate::core::types::primitive::PrimitiveType::Char => crate::core::ast::types::PrimitiveType::Char,
+                    crate::core::types::primitive::PrimitiveType::UByte => crate::core::ast::types::PrimitiveType::UByte,
+                    crate::core::types::primitive::PrimitiveType::UInt => crate::core::ast::types::PrimitiveType::UInt,
+                    crate::core::types::primitive::PrimitiveType::ULong => crate::core::ast::types::PrimitiveType::ULong,
                 })
             }
-            ResolvedType::Struct(s) => {
+            ResolvedType::Enum(crate::core::types::composite::EnumType { name, .. })
+            | ResolvedType::Struct(crate::core::types::composite::StructType { name, .. }) => {
                 crate::core::ast::types::Type::Named(crate::core::ast::types::NamedType {
-                    name: s.name.clone(),
+                    name: name.clone(),
                     generics: Vec::new(), // struct types in context r already monomorphized
                 })
             }
@@ -195,7 +206,7 @@ impl Specializer {
                     name: g.name.clone(),
                 })
             }
-            ResolvedType::Function(_) | ResolvedType::TraitObject(_) => {
+            ResolvedType::Function(_) | ResolvedType::TraitObject(_) | ResolvedType::Error => {
                 // functions and trait objects cant be in generic context directly
                 // fallback 2 void
                 crate::core::ast::types::Type::Primitive(crate::core::ast::types::PrimitiveType::Void)
@@ -273,6 +284,39 @@ impl Specializer {
                     span: s.span,
                 })
             }
+            Stmt::Guard(s) => {
+                Stmt::Guard(GuardStmt {
+                    condition: self.specialize_expr(&s.condition, context),
+                    else_branch: s.else_branch.iter().map(|stmt| {
+                        self.specialize_stmt(stmt, context)
+                    }).collect(),
+                    span: s.span,
+                })
+            }
+            Stmt::Defer(s) => {
+                Stmt::Defer(DeferStmt {
+                    body: s.body.iter().map(|stmt| self.specialize_stmt(stmt, context)).collect(),
+                    span: s.span,
+                })
+            }
+            Stmt::Match(s) => {
+                Stmt::Match(MatchStmt {
+                    scrutinee: self.specialize_expr(&s.scrutinee, context),
+                    arms: s.arms.iter().map(|arm| MatchArm {
+                        patterns: arm.patterns.clone(),
+                        body: arm.body.iter().map(|stmt| {
+                            self.specialize_stmt(stmt, context)
+                        }).collect(),
+                        span: arm.span,
+                    }).collect(),
+                    else_branch: s.else_branch.as_ref().map(|branch| {
+                        branch.iter().map(|stmt| {
+                            self.specialize_stmt(stmt, context)
+                        }).collect()
+                    }),
+                    span: s.span,
+                })
+            }
             Stmt::Break(s) => Stmt::Break(s.clone()),
             Stmt::Continue(s) => Stmt::Continue(s.clone()),
         }
@@ -343,9 +387,14 @@ impl Specializer {
                     elements: a.elements.iter().map(|e| {
                         self.specialize_expr(e, context)
                     }).collect(),
+                    repeat: a.repeat,
                     span: a.span,
                 })
             }
+            Expr::Tuple(t) => Expr::Tuple(TupleExpr {
+                elements: t.elements.iter().map(|e| self.specialize_expr(e, context)).collect(),
+                span: t.span,
+            }),
             Expr::Null => Expr::Null,
             Expr::Comptime(c) => {
                 Expr::Comptime(ComptimeExpr {
@@ -402,6 +451,24 @@ impl Specializer {
                     span: e.span,
                 })
             }
+            Expr::New(n) => {
+                Expr::New(NewExpr {
+                    value: Box::new(self.specialize_expr(&n.value, context)),
+                    span: n.span,
+                })
+            }
+            Expr::Delete(d) => {
+                Expr::Delete(DeleteExpr {
+                    pointer: Box::new(self.specialize_expr(&d.pointer, context)),
+                    span: d.span,
+                })
+            }
+            Expr::Try(t) => {
+                Expr::Try(TryExpr {
+                    expr: Box::new(self.specialize_expr(&t.expr, context)),
+                    span: t.span,
+                })
+            }
             Expr::ModuleAccess(m) => {
                 Expr::ModuleAccess(m.clone())
             }
@@ -418,9 +485,8 @@ impl Specializer {
     }
 
     /// gen unique name 4 specialized item
-    /// format: original_name_type1_type2_...
+    /// format: original_name$type1$type2...
     fn generate_specialized_name(&self, base_name: &str, context: &GenericContext) -> String {
-        let mut name = base_name.to_string();
         // get type names in order (need 2 track generic param order)
         // 4 now use simple approach: append type names
         let mut type_names = Vec::new();
@@ -430,12 +496,8 @@ impl Specializer {
         // sort by param name 2 get consistent ordering
         type_names.sort_by_key(|(name, _)| name.clone());
         
-        for (_, type_) in type_names {
-            let type_str = self.type_to_string(&type_);
-            name.push('_');
-            name.push_str(&type_str);
-        }
-        name
+        let types: Vec<String> = type_names.iter().map(|(_, type_)| self.type_to_string(type_)).collect();
+        specialized_name(base_name, &types)
     }
 
     fn type_to_string(&self, type_: &ResolvedType) -> String {
@@ -449,8 +511,12 @@ impl Specializer {
                 crate::core::types::primitive::PrimitiveType::Long => "long".to_string(),
                 crate::core::types::primitive::PrimitiveType::Size => "size".to_string(),
                 crate::core::types::primitive::PrimitiveType::Void => "void".to_string(),
+                crate::core::types::primitive::PrimitiveType::UByte => "ubyte".to_string(),
+                crate::core::types::primitive::PrimitiveType::UInt => "uint".to_string(),
+                crate::core::types::primitive::PrimitiveType::ULong => "ulong".to_string(),
             }
             ResolvedType::Struct(s) => s.name.clone(),
+            ResolvedType::Enum(e) => e.name.clone(),
             ResolvedType::Array(a) => {
                 format!("{}_arr{}", self.type_to_string(a.element.as_ref()), a.size)
             }
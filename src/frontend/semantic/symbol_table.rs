@@ -7,6 +7,7 @@ pub enum SymbolKind {
     Variable { mutable: bool, type_: Type },
     Function { params: Vec<Type>, return_type: Option<Type> },
     Struct { fields: Vec<(String, Type)> },
+    Enum { variants: Vec<(String, Vec<Type>)> },
     Trait { methods: Vec<String> },
     Module { name: String },
     Type { type_: Type },
@@ -107,6 +108,17 @@ impl SymbolTable {
         found_count > 1 // shadowing occrs when symbol exsts in mltpl scopes
     }
 
+    /// symbols reachable frm the current scope, innermost first, shadowed ones left out
+    pub fn visible(&self) -> Vec<(String, Symbol)> {
+        let mut symbols: Vec<(String, Symbol)> = Vec::new();
+        for scope in self.scopes.iter().rev() {
+            let mut names: Vec<&String> = scope.keys().filter(|n| !symbols.iter().any(|(s, _)| s == *n)).collect();
+            names.sort();
+            symbols.extend(names.into_iter().map(|n| (n.clone(), scope[n].clone())));
+        }
+        symbols
+    }
+
     /// get all symbols from all scopes
     /// returns a vec of (name, symbol) pairs
     pub fn all_symbols(&self) -> Vec<(String, Symbol)> {
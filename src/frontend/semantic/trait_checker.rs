@@ -2,6 +2,8 @@ use crate::core::ast::*;
 use crate::core::types::resolver::resolve_ast_type;
 use crate::core::types::ty::Type;
 use crate::error::{Diagnostic, DiagnosticKind, Reporter};
+use crate::frontend::semantic::assoc_consts::value_fits;
+use crate::frontend::semantic::comptime::ComptimeEvaluator;
 use crate::frontend::semantic::symbol_table::{SymbolKind, SymbolTable};
 use codespan::FileId;
 
@@ -79,6 +81,42 @@ impl<'a> TraitChecker<'a> {
                 self.check_method_signature(trait_def, impl_method);
             }
         }
+        if let Some(trait_def) = trait_def_opt {
+            self.check_consts(&trait_def, impl_);
+        }
+    }
+
+    /// evry const w/o a default is given, given ones r declared + fold 2 their type
+    fn check_consts(&mut self, trait_def: &Trait, impl_: &TraitImpl) {
+        for decl in &trait_def.consts {
+            if decl.value.is_none() && !impl_.consts.iter().any(|c| c.name == decl.name) {
+                let msg = format!("Trait '{}' requires constant '{}' but it's not provided", impl_.trait_name, decl.name);
+                self.error(impl_.span, &msg);
+            }
+        }
+        let defaults = trait_def.consts.iter().filter(|c| c.value.is_some()).map(|c| (c, c.value.as_ref()));
+        let given = impl_.consts.iter().map(|c| (c, c.value.as_ref()));
+        for (c, value) in defaults.chain(given) {
+            let Some(decl) = trait_def.consts.iter().find(|d| d.name == c.name) else {
+                let msg = format!("Constant '{}' is not part of trait '{}'", c.name, impl_.trait_name);
+                self.error(c.span, &msg);
+                continue;
+            };
+            let ty = resolve_ast_type(&decl.type_);
+            if resolve_ast_type(&c.type_) != ty {
+                self.error(c.span, &format!("Constant '{}' type mismatch, trait declares {:?}", c.name, ty));
+                continue;
+            }
+            let Some(value) = value else {
+                self.error(c.span, &format!("Constant '{}' needs a value", c.name));
+                continue;
+            };
+            match ComptimeEvaluator::new(self.reporter, self.file_id).evaluate(value) {
+                Some(v) if value_fits(&v, &ty) => {}
+                Some(_) => self.error(c.span, &format!("Value of constant '{}' is not a {:?}", c.name, ty)),
+                None => self.error(c.span, &format!("Value of constant '{}' must be known at compile time", c.name)),
+            }
+        }
     }
 
     fn check_method_signature(&mut self, trait_def: &Trait, impl_method: &Function) {
@@ -94,12 +132,12 @@ impl<'a> TraitChecker<'a> {
             for (i, (trait_param, impl_param)) in trait_method_def.params.iter().zip(impl_method.params.iter()).enumerate() {
                 let trait_param_type = resolve_ast_type(&trait_param.type_);
                 let impl_param_type = resolve_ast_type(&impl_param.type_);
-                // first param (self) can be more specific in impl or omitted in trait
+                // void is the placeholder 4 the impl type (untyped self, derived traits' other)
+                if matches!(trait_param_type, Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)) {
+                    continue;
+                }
+                // first param (self) can be more specific in impl
                 if i == 0 {
-                    // if trait has void (placeholder for self without type), allow any impl type
-                    if matches!(trait_param_type, Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)) {
-                        continue;
-                    }
                     // allow impl to have more specific pointer type for self
                     if let (Type::Pointer(_), Type::Pointer(_)) = (&trait_param_type, &impl_param_type) {
                         continue;
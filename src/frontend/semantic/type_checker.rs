@@ -1,17 +1,64 @@
 use crate::core::ast::*;
+use crate::core::types::composite::{spelling, EnumType, StructType};
 use crate::core::types::ty::Type;
 use crate::core::types::resolver::resolve_ast_type;
+use crate::core::stack;
 use crate::error::{Diagnostic, DiagnosticKind, Reporter};
-use crate::frontend::semantic::comptime::ComptimeEvaluator;
+use crate::frontend::semantic::assoc_consts::AssocConsts;
+use crate::frontend::semantic::comptime::{comptime_branch, ComptimeEvaluator};
+use crate::frontend::semantic::deprecation::{report_use, Deprecations};
+use crate::frontend::semantic::exhaustiveness::check_match;
+use crate::frontend::semantic::format::{is_printf_like, parse_format};
+use crate::frontend::semantic::noreturn::{leaves_defer, NoReturn};
 use crate::frontend::semantic::symbol_table::SymbolTable;
 use crate::frontend::semantic::trait_resolver::TraitResolver;
 use codespan::FileId;
+use std::collections::{HashMap, HashSet};
+
+// arrays this big w/o an initializer get a warning
+const LARGE_UNINIT_ARRAY_BYTES: usize = 1024;
 
 pub struct TypeChecker<'a> {
     symbol_table: SymbolTable,
     reporter: &'a mut Reporter,
     file_id: FileId,
     trait_resolver: TraitResolver,
+    target: crate::core::target::TargetInfo,
+    /// fn name -> (param name, is comptime) 4 fns w/ comptime params
+    comptime_params: HashMap<String, Vec<(String, bool)>>,
+    /// comptime params of the fn being checked, known at cmpl time in its body
+    comptime_locals: HashSet<String>,
+    /// variadic printf-like foreign fn -> index of its format param
+    format_fns: HashMap<String, usize>,
+    /// trait decls by name, calls on `dyn Trait` r checked against them
+    traits: HashMap<String, Trait>,
+    /// noreturn fns, a guard's else block has 2 diverge
+    noreturn: NoReturn,
+    /// enum decls by name, variants r checked at construction + in match arms
+    enums: HashMap<String, EnumType>,
+    /// trait + impl consts, `Type::NAME` reads them
+    assoc_consts: AssocConsts,
+    /// bounded generic params of the fn being checked -> their trait, 4 `T::NAME`
+    generic_bounds: HashMap<String, String>,
+    /// name + `value or error` return type of the fn being checked, if it can fail
+    fallible: Option<(String, EnumType)>,
+    /// `ref?` vars known 2 be non-null, 1 frame per nested block
+    /// an `exists?` branch or an early return on null narrows them, assigning widens them again
+    narrowed: Vec<Vec<String>>,
+    /// file + `def` line of imported fns, their call errors point there 2
+    origins: HashMap<String, (FileId, codespan::Span)>,
+    /// `@deprecated` items, this file's + imported fns
+    deprecations: Deprecations,
+    holes: Vec<Hole>,
+}
+
+/// an expr that failed 2 type check, typed `Type::Error` so the code around it still gets checked
+/// an editor completes frm `scope` when the cursor is in broken code
+#[derive(Debug, Clone)]
+pub struct Hole {
+    pub span: codespan::Span,
+    /// vars + fns visible at the hole, innermost first
+    pub scope: Vec<(String, Type)>,
 }
 
 impl<'a> TypeChecker<'a> {
@@ -21,10 +68,80 @@ impl<'a> TypeChecker<'a> {
             reporter,
             file_id,
             trait_resolver: TraitResolver::new(symbol_table),
+            target: crate::core::target::TargetInfo::host(),
+            comptime_params: HashMap::new(),
+            comptime_locals: HashSet::new(),
+            format_fns: HashMap::new(),
+            traits: HashMap::new(),
+            noreturn: NoReturn::default(),
+            enums: HashMap::new(),
+            assoc_consts: AssocConsts::default(),
+            generic_bounds: HashMap::new(),
+            fallible: None,
+            narrowed: Vec::new(),
+            origins: HashMap::new(),
+            deprecations: Deprecations::default(),
+            holes: Vec::new(),
         }
     }
 
+    /// configured target 4 `target.*` comptime constants
+    pub fn with_target(mut self, target: crate::core::target::TargetInfo) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// where imported `module::fn`s r defined
+    pub fn with_origins(mut self, origins: HashMap<String, (FileId, codespan::Span)>) -> Self {
+        self.origins = origins;
+        self
+    }
+
+    /// deprecated fns of imported modules as `module::fn`
+    pub fn with_deprecations(mut self, deprecations: Deprecations) -> Self {
+        self.deprecations = deprecations;
+        self
+    }
+
     pub fn check(&mut self, ast: &Ast) {
+        // calls can come b4 the callee so collect comptime params, format fns + trait impls first
+        for item in &ast.items {
+            match item {
+                Item::Function(f) if f.params.iter().any(|p| p.comptime) => {
+                    let params = f.params.iter().map(|p| (p.name.clone(), p.comptime)).collect();
+                    self.comptime_params.insert(f.name.clone(), params);
+                }
+                Item::Trait(t) => {
+                    self.traits.insert(t.name.clone(), t.clone());
+                }
+                Item::Enum(e) => {
+                    let variants = e
+                        .variants
+                        .iter()
+                        .map(|v| crate::core::types::composite::Variant {
+                            name: v.name.clone(),
+                            fields: v.fields.iter().map(resolve_ast_type).collect(),
+                        })
+                        .collect();
+                    self.enums.insert(e.name.clone(), EnumType { name: e.name.clone(), variants });
+                }
+                Item::TraitImpl(ti) => {
+                    let methods = ti.methods.iter().map(|m| m.name.clone()).collect();
+                    self.trait_resolver.register_impl(&ti.trait_name, &ti.type_name, methods);
+                }
+                Item::Foreign(foreign) => {
+                    for func in &foreign.functions {
+                        if func.variadic && !func.params.is_empty() && is_printf_like(&func.name) {
+                            self.format_fns.insert(func.name.clone(), func.params.len() - 1);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.noreturn = NoReturn::analyze(&ast.items);
+        self.assoc_consts = AssocConsts::collect(&ast.items);
+        self.deprecations.extend(Deprecations::collect(&ast.items, self.file_id));
         for item in &ast.items {
             self.check_item(item);
         }
@@ -36,9 +153,34 @@ impl<'a> TypeChecker<'a> {
                 // pass 3: fn bds r rslvd here
                 // typs r already resolved in pass 2 so we can use them
                 self.symbol_table.enter_scope();
+                self.comptime_locals.clear();
+                self.narrowed = vec![Vec::new()];
+                self.generic_bounds = f.generics.iter()
+                    .filter_map(|g| Some((g.name.clone(), g.constraint.clone()?)))
+                    .collect();
+                self.fallible = match f.return_type.as_ref().map(resolve_ast_type) {
+                    Some(Type::Enum(e)) if e.fallible_parts().is_some() => Some((f.name.clone(), e)),
+                    _ => None,
+                };
+                if let Some((value, error)) = self.fallible.as_ref().and_then(|(_, e)| e.fallible_parts()).filter(|(v, e)| v == e) {
+                    let message = format!("'{} or {}' can't tell a value from an error, the two types must differ", spelling(value), spelling(error));
+                    self.error(f.span, &message);
+                }
+                if let Some(return_type) = f.return_type.as_ref().filter(|t| !self.deprecations.types_in(t).is_empty()) {
+                    let span = crate::frontend::semantic::deprecation::decl_line(self.reporter.files().source(self.file_id), f.span);
+                    self.check_deprecated_types(return_type, span);
+                }
                 // add parameters 2 scope
                 for param in &f.params {
+                    self.check_deprecated_types(&param.type_, param.span);
                     let type_ = resolve_ast_type(&param.type_);
+                    if param.comptime {
+                        // specializations substitute the value as a literal
+                        if !matches!(type_, Type::Primitive(_) | Type::String) {
+                            self.error(param.span, &format!("Comptime parameter '{}' must have a primitive or string type", param.name));
+                        }
+                        self.comptime_locals.insert(param.name.clone());
+                    }
                     let symbol = crate::frontend::semantic::symbol_table::Symbol {
                         name: param.name.clone(),
                         kind: crate::frontend::semantic::symbol_table::SymbolKind::Variable {
@@ -59,11 +201,21 @@ impl<'a> TypeChecker<'a> {
                 }
                 self.symbol_table.exit_scope();
             }
+            Item::Struct(s) => {
+                for field in &s.fields {
+                    self.check_deprecated_types(&field.type_, field.span);
+                }
+            }
+            Item::Global(g) => self.check_deprecated_types(&g.type_, g.span),
             _ => {}
         }
     }
 
     fn check_stmt(&mut self, stmt: &Stmt) {
+        stack::grow(|| self.check_stmt_inner(stmt))
+    }
+
+    fn check_stmt_inner(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Let(s) => {
                 // require explicit type annotation for all variables
@@ -73,11 +225,12 @@ impl<'a> TypeChecker<'a> {
                 }
                 
                 let annotated_type = resolve_ast_type(s.type_annotation.as_ref().unwrap());
+                self.check_deprecated_types(s.type_annotation.as_ref().unwrap(), s.span);
                 
                 // if comptime, evaluate at compile time
                 if s.comptime {
                     if let Some(value) = &s.value {
-                        let mut evaluator = crate::frontend::semantic::comptime::ComptimeEvaluator::new(self.reporter, self.file_id);
+                        let mut evaluator = crate::frontend::semantic::comptime::ComptimeEvaluator::new(self.reporter, self.file_id).with_target(self.target.clone());
                         if let Some(_comptime_value) = evaluator.evaluate(value) {
                             // comptime var evaluated - store value 4 later use
                             // 4 now just type check normally
@@ -99,6 +252,23 @@ impl<'a> TypeChecker<'a> {
                     let _ = self.symbol_table.define(s.name.clone(), symbol);
                 }
                 
+                // large arrays w/o initializer hold garbage - suggest zero-init
+                if s.value.is_none() {
+                    if let Type::Array(arr) = &annotated_type {
+                        if let Some(bytes) = annotated_type.size_in_bytes() {
+                            if bytes >= LARGE_UNINIT_ARRAY_BYTES {
+                                self.warning(
+                                    s.span,
+                                    &format!(
+                                        "Array '{}' ({} bytes) is left uninitialized; use `[0; {}]` to zero-initialize it",
+                                        s.name, bytes, arr.size
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                }
+
                 // now chk the vl expression
                 if let Some(value) = &s.value {
                     let value_type = self.check_expr(value);
@@ -117,18 +287,29 @@ impl<'a> TypeChecker<'a> {
                             // Arrays are compatible if element types match and:
                             // 1. Empty array literal (size 0) can match any array size
                             // 2. Array literal size <= declared array size
-                            if annotated_arr.element == value_arr.element {
-                                // Allow empty arrays or literals with fewer elements
-                                value_arr.size == 0 || value_arr.size <= annotated_arr.size
+                            if annotated_arr.element == value_arr.element || self.is_int_repeat_fill(value, &annotated_arr.element) {
+                                if let Expr::ArrayLiteral(ArrayLiteralExpr { repeat: Some(count), .. }) = value {
+                                    // repeat initializer must fill the whole array
+                                    *count == annotated_arr.size
+                                } else {
+                                    // Allow empty arrays or literals with fewer elements
+                                    value_arr.size == 0 || value_arr.size <= annotated_arr.size
+                                }
                             } else {
                                 false
                             }
                         } else {
                             // Not both arrays, use standard compatibility check
                             self.types_compatible_strict(&annotated_type, &value_type)
+                                || self.is_int_literal_fit(value, &annotated_type)
+                                || self.is_record_literal_fit(value, &value_type, &annotated_type)
                         };
                         
-                        if !compatible {
+                        let unhandled = matches!(&value_type, Type::Enum(e) if e.fallible_parts().is_some_and(|(v, _)| *v == annotated_type));
+                        if !compatible && unhandled {
+                            let message = format!("'{}' gets an '{}', propagate its error with '?' or match on it", s.name, spelling(&value_type));
+                            self.error(s.span, &message);
+                        } else if !compatible {
                             self.error(
                                 s.span,
                                 &format!(
@@ -140,6 +321,9 @@ impl<'a> TypeChecker<'a> {
                     }
                 }
                 
+                // a new `x` isnt the one an outer check narrowed
+                self.widen(&s.name);
+
                 // update symbol type if needed
                 if let Some(existing_symbol) = self.symbol_table.resolve_mut(&s.name) {
                     if let crate::frontend::semantic::symbol_table::SymbolKind::Variable { mutable: _, type_ } = &mut existing_symbol.kind {
@@ -151,14 +335,21 @@ impl<'a> TypeChecker<'a> {
                 eprintln!("[DEBUG] chking return stmt");
                 if let Some(value) = &s.value {
                     eprintln!("[DEBUG] return has value expr");
-                    self.check_expr(value);
+                    let value_type = self.check_expr(value);
+                    self.check_fallible_return(&value_type, value.span());
                 } else {
                     eprintln!("[DEBUG] return has no value");
                 }
             }
             Stmt::Expr(s) => {
                 eprintln!("[DEBUG] chking expr stmt");
-                self.check_expr(&s.expr);
+                let value_type = self.check_expr(&s.expr);
+                if let Type::Enum(e) = &value_type {
+                    if e.fallible_parts().is_some() {
+                        let message = format!("The error this '{}' can hold is not handled, propagate it with '?' or match on it", e.name);
+                        self.error(s.expr.span(), &message);
+                    }
+                }
             }
             Stmt::If(s) => {
                 // Check if condition is an exists? expression (either Exists or FieldAccess with exists?)
@@ -180,13 +371,25 @@ impl<'a> TypeChecker<'a> {
                 if !is_exists_check && !self.is_bool_type(&cond_type) {
                     self.error(s.condition.span(), "Condition must be bool");
                 }
-                for stmt in &s.then_branch {
-                    self.check_stmt(stmt);
+                let proven = |taken: bool| non_null_when(&s.condition, taken);
+                // the branch an `if comptime` doesnt take is never checked, it can hold code 4 other targets
+                let live = comptime_branch(&s.condition, &self.target, &HashMap::new());
+                if live != Some(false) {
+                    self.check_block(&s.then_branch, &proven(true));
                 }
-                if let Some(stmts) = &s.else_branch {
-                    for stmt in stmts {
-                        self.check_stmt(stmt);
-                    }
+                if let (Some(stmts), false) = (&s.else_branch, live == Some(true)) {
+                    self.check_block(stmts, &proven(false));
+                }
+                // `if not p.exists? return end` - p is non-null 4 the rest of the block
+                let rest = if self.noreturn.diverges(&s.then_branch) {
+                    proven(false)
+                } else if s.else_branch.as_ref().is_some_and(|e| self.noreturn.diverges(e)) {
+                    proven(true)
+                } else {
+                    Vec::new()
+                };
+                for name in rest {
+                    self.narrow(name);
                 }
             }
             Stmt::While(s) => {
@@ -209,8 +412,65 @@ impl<'a> TypeChecker<'a> {
                 if !is_exists_check && !self.is_bool_type(&cond_type) {
                     self.error(s.condition.span(), "Condition must be bool");
                 }
-                for stmt in &s.body {
-                    self.check_stmt(stmt);
+                // a check b4 the loop doesnt hold once the body reassigns the var, the next
+                // iteration starts w/ whatever it was set 2
+                let mut assigned = HashSet::new();
+                assigned_vars(&s.body, &mut assigned);
+                for name in &assigned {
+                    self.widen(name);
+                }
+                self.check_block(&s.body, &non_null_when(&s.condition, true));
+            }
+            Stmt::Guard(s) => {
+                let cond_type = self.check_expr(&s.condition);
+                if !self.is_bool_type(&cond_type) {
+                    self.error(s.condition.span(), "Condition must be bool");
+                }
+                self.check_block(&s.else_branch, &non_null_when(&s.condition, false));
+                for name in non_null_when(&s.condition, true) {
+                    self.narrow(name);
+                }
+                if !self.noreturn.diverges(&s.else_branch) {
+                    self.error(
+                        s.span,
+                        "Guard's else block must not fall through: end it with return, break, continue or a call that never returns",
+                    );
+                }
+            }
+            Stmt::Defer(s) => {
+                self.check_block(&s.body, &[]);
+                if let Some((keyword, span)) = leaves_defer(&s.body) {
+                    self.error(span, &format!("'{}' cannot leave a deferred block, it runs while its scope is already exiting", keyword));
+                }
+            }
+            Stmt::Match(s) => {
+                // enum values check as their name, the patterns need the variants
+                let scrutinee = match self.check_expr(&s.scrutinee) {
+                    Type::Struct(st) if self.enums.contains_key(&st.name) => Type::Enum(self.enums[&st.name].clone()),
+                    other => other,
+                };
+                let origin = self.reporter.source_map().origin(self.file_id, s.span).cloned();
+                for issue in check_match(s, &scrutinee, origin.as_ref()) {
+                    let diagnostic = if issue.error {
+                        Diagnostic::error(DiagnosticKind::TypeError, issue.span, self.file_id, issue.message)
+                    } else {
+                        Diagnostic::warning(DiagnosticKind::SemanticError, issue.span, self.file_id, issue.message)
+                    };
+                    self.reporter.add_diagnostic(match issue.note {
+                        Some(note) => diagnostic.with_note(note),
+                        None => diagnostic,
+                    });
+                }
+                for arm in &s.arms {
+                    self.symbol_table.enter_scope();
+                    if let Type::Enum(e) = &scrutinee {
+                        self.define_pattern_bindings(arm, e);
+                    }
+                    self.check_block(&arm.body, &[]);
+                    self.symbol_table.exit_scope();
+                }
+                if let Some(stmts) = &s.else_branch {
+                    self.check_block(stmts, &[]);
                 }
             }
             _ => {}
@@ -218,6 +478,10 @@ impl<'a> TypeChecker<'a> {
     }
 
     fn check_expr(&mut self, expr: &Expr) -> Type {
+        stack::grow(|| self.check_expr_inner(expr))
+    }
+
+    fn check_expr_inner(&mut self, expr: &Expr) -> Type {
         match expr {
             Expr::Literal(l) => match &l.kind {
                 LiteralKind::Int(_) => Type::Primitive(crate::core::types::primitive::PrimitiveType::Int),
@@ -228,21 +492,52 @@ impl<'a> TypeChecker<'a> {
                     Type::String
                 }
             },
+            Expr::ModuleAccess(m) if self.enums.contains_key(&m.module) => self.check_variant(m, &[], m.span),
+            // a fn of an imported module, the analyzer defined it as `module::fn`
+            Expr::ModuleAccess(m) if self.is_function(&format!("{}::{}", m.module, m.member)) => {
+                let name = format!("{}::{}", m.module, m.member);
+                self.check_expr(&Expr::Variable(crate::core::ast::expr::VariableExpr { name, span: m.span }))
+            }
+            Expr::ModuleAccess(m) if self.generic_bounds.contains_key(&m.module) => {
+                let bound = self.generic_bounds[&m.module].clone();
+                match self.assoc_consts.declared(&bound, &m.member) {
+                    Some(c) => resolve_ast_type(&c.type_),
+                    None => {
+                        self.hole(m.span, &format!("Trait '{}' has no constant '{}'", bound, m.member))
+                    }
+                }
+            }
+            Expr::ModuleAccess(m) if self.assoc_consts.has_consts(&m.module) => {
+                match self.assoc_consts.lookup(&m.module, &m.member) {
+                    Some((c, _)) => resolve_ast_type(&c.type_),
+                    None => {
+                        self.hole(m.span, &format!("Type '{}' has no constant '{}'", m.module, m.member))
+                    }
+                }
+            }
+            Expr::ModuleAccess(m) if self.symbol_table.all_symbols().iter().any(|(n, _)| n.starts_with(&format!("{}::", m.module))) => {
+                self.hole(m.span, &format!("Module '{}' has no function '{}'", m.module, m.member))
+            }
             Expr::ModuleAccess(m) => {
                 // resolve module access: Utils::helper
                 // lookup module in symbol table and resolve member
                 // 4 now return void - proper impl wld resolve module members
-                self.error(m.span, &format!("Module access '{}::{}' not yet fully supported", m.module, m.member));
-                Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                self.hole(m.span, &format!("Module access '{}::{}' not yet fully supported", m.module, m.member))
             }
             Expr::Variable(v) => {
                 eprintln!("[DEBUG] chking var: {}", v.name);
+                self.check_deprecated_function(&v.name, v.span);
                 if let Some(symbol) = self.symbol_table.resolve(&v.name) {
                     eprintln!("[DEBUG] var {} found in sym tbl, kind: {:?}", v.name, std::mem::discriminant(&symbol.kind));
                     match &symbol.kind {
                         crate::frontend::semantic::symbol_table::SymbolKind::Variable { type_, .. } => {
                             eprintln!("[DEBUG] var {} is variable, type: {:?}", v.name, type_);
-                            type_.clone()
+                            match type_ {
+                                Type::Pointer(p) if p.nullable && self.is_narrowed(&v.name) => {
+                                    Type::Pointer(crate::core::types::pointer::PointerType::ref_(*p.pointee.clone()))
+                                }
+                                _ => type_.clone(),
+                            }
                         }
                         crate::frontend::semantic::symbol_table::SymbolKind::Function { params, return_type } => {
                             eprintln!("[DEBUG] var {} is function", v.name);
@@ -256,17 +551,16 @@ impl<'a> TypeChecker<'a> {
                         }
                         _ => {
                             eprintln!("[DEBUG] var {} is not var or fn", v.name);
-                            self.error(v.span, &format!("'{}' is not a variable or function", v.name));
-                            Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                            self.hole(v.span, &format!("'{}' is not a variable or function", v.name))
                         }
                     }
                 } else {
-                    eprintln!("[DEBUG] ERROR: var {} not found in sym tbl! defining w/ void type for err recovery", v.name);
+                    eprintln!("[DEBUG] ERROR: var {} not found in sym tbl! defining w/ error type for err recovery", v.name);
                     let placeholder_symbol = crate::frontend::semantic::symbol_table::Symbol {
                         name: v.name.clone(),
                         kind: crate::frontend::semantic::symbol_table::SymbolKind::Variable {
                             mutable: false,
-                            type_: Type::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+                            type_: Type::Error,
                         },
                         span: v.span,
                         defined: true,
@@ -276,8 +570,7 @@ impl<'a> TypeChecker<'a> {
                     } else {
                         eprintln!("[DEBUG] defined placeholder var {} for err recovery", v.name);
                     }
-                    self.error(v.span, &format!("Undefined variable '{}'", v.name));
-                    Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                    self.hole(v.span, &format!("Undefined variable '{}'", v.name))
                 }
             }
             Expr::Binary(b) => {
@@ -289,6 +582,11 @@ impl<'a> TypeChecker<'a> {
                 let expr_type = self.check_expr(&u.expr);
                 self.check_unary_op(&u.op, &expr_type, u.span)
             }
+            Expr::Call(c) if self.is_volatile_builtin(c) => self.check_volatile_access(c),
+            Expr::Call(c) if matches!(c.callee.as_ref(), Expr::ModuleAccess(m) if self.enums.contains_key(&m.module)) => {
+                let Expr::ModuleAccess(m) = c.callee.as_ref() else { unreachable!() };
+                self.check_variant(m, &c.args, c.span)
+            }
             Expr::Call(c) => {
                 let callee_type = self.check_expr(&c.callee);
                 // chk fn call get ret type frmo fn type
@@ -324,19 +622,52 @@ impl<'a> TypeChecker<'a> {
                                     }
                                 }
                             } else if !compatible && !self.types_compatible(param_type, &arg_type) {
-                                self.error(arg.span(), &format!("Argument {} type mismatch: expected {:?}, got {:?}", i, param_type, arg_type));
+                                let message = format!("Argument {} type mismatch: expected {:?}, got {:?}", i, param_type, arg_type);
+                                self.error_at_callee(arg.span(), &message, &c.callee);
                             }
                         }
+                        self.check_comptime_args(c);
+                        self.check_format_args(c);
                         *return_type
                     }
+                    // the callee is a hole, its args still get checked
+                    Type::Error => {
+                        for arg in &c.args {
+                            self.check_expr(arg);
+                        }
+                        Type::Error
+                    }
                     _ => {
-                        self.error(c.span, "Calling non-function value");
-                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                        self.hole(c.span, "Calling non-function value")
                     }
                 }
             }
             Expr::MethodCall(m) => {
                 let receiver_type = self.check_expr(&m.receiver);
+                if receiver_type.is_error() {
+                    for arg in &m.args {
+                        self.check_expr(arg);
+                    }
+                    return Type::Error;
+                }
+                if matches!(receiver_type, Type::String) && m.method == "len" {
+                    if !m.args.is_empty() {
+                        self.error(m.span, "String len() takes no arguments");
+                    }
+                    // byte count, strings r (ptr, len) w/ a 64 bit len
+                    return Type::Primitive(crate::core::types::primitive::PrimitiveType::Long);
+                }
+                if let Type::TraitObject(t) = &receiver_type {
+                    return self.check_trait_method_call(m, &t.trait_name, None);
+                }
+                if let Some((trait_name, self_type)) = self.implementing_trait(&receiver_type, &m.method) {
+                    return self.check_trait_method_call(m, &trait_name, Some(self_type));
+                }
+                if let Type::Pointer(p) = &receiver_type {
+                    if m.method == "offset" || m.method == "add" {
+                        return self.check_pointer_arithmetic(m, p);
+                    }
+                }
                 if let Some((_method_name, _params, return_type)) = self.trait_resolver.resolve_method_call(&receiver_type, &m.method) {
                     return_type.clone().unwrap_or(Type::Primitive(crate::core::types::primitive::PrimitiveType::Void))
                 } else {
@@ -370,19 +701,19 @@ impl<'a> TypeChecker<'a> {
                                         // This is actually a field access, not a method call
                                         field.type_.clone()
                                     } else {
-                                        self.error(m.span, &format!("Method '{}' not found on type", m.method));
-                                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                                        let mut names = self.method_names(&receiver_type);
+                                        names.extend(fields.iter().map(|field| field.name.clone()));
+                                        self.member_hole(m.span, &format!("Method '{}' not found on type", m.method), &m.method, &names)
                                     }
                                 }
                                 _ => {
-                                    self.error(m.span, &format!("Method '{}' not found on type", m.method));
-                                    Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                                    self.hole(m.span, &format!("Method '{}' not found on type", m.method))
                                 }
                             }
                         }
                         _ => {
-                            self.error(m.span, &format!("Method '{}' not found on type", m.method));
-                            Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                            let names = self.method_names(&receiver_type);
+                            self.member_hole(m.span, &format!("Method '{}' not found on type", m.method), &m.method, &names)
                         }
                     }
                 }
@@ -391,43 +722,55 @@ impl<'a> TypeChecker<'a> {
                 let array_type = self.check_expr(&i.array);
                 let _index_type = self.check_expr(&i.index);
                 match array_type {
+                    Type::Error => Type::Error,
                     Type::Array(a) => {
-                        // chk array bounds 4 compile-time const indices
+                        // chk array bounds 4 compile-time const indices, a runtime index isnt an err
+                        let mut scratch = crate::error::Reporter::new();
+                        let file_id = scratch.add_file(String::new(), String::new());
                         let mut comptime_eval = crate::frontend::semantic::comptime::ComptimeEvaluator::new(
-                            self.reporter,
-                            self.file_id,
-                        );
+                            &mut scratch,
+                            file_id,
+                        ).with_target(self.target.clone());
                         if let Some(index_value) = comptime_eval.evaluate(&i.index) {
                             // compile-time const index - chk bounds
                             let array_size = a.size;
                             let index_int = match index_value {
                                 crate::frontend::semantic::comptime::ComptimeValue::Int(n) => {
                                     if n < 0 {
-                                        self.error(i.index.span(), &format!("Array index cannot be negative: {}", n));
-                                        return Type::Primitive(crate::core::types::primitive::PrimitiveType::Void);
+                                        return self.hole(i.index.span(), &format!("Array index cannot be negative: {}", n));
                                     }
                                     n as usize
                                 }
                                 _ => {
-                                    self.error(i.index.span(), "Array index must be an integer");
-                                    return Type::Primitive(crate::core::types::primitive::PrimitiveType::Void);
+                                    return self.hole(i.index.span(), "Array index must be an integer");
                                 }
                             };
                             
                             if index_int >= array_size {
-                                self.error(
+                                return self.hole(
                                     i.index.span(),
                                     &format!("Array index out of bounds: index {} is >= array size {}", index_int, array_size)
                                 );
-                                return Type::Primitive(crate::core::types::primitive::PrimitiveType::Void);
                             }
                         }
                         // Runtime bounds checking will be added in MIR generation
                         *a.element.clone()
                     }
                     _ => {
-                        self.error(i.span, "Indexing non-array value");
-                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                        self.hole(i.span, "Indexing non-array value")
+                    }
+                }
+            }
+            Expr::FieldAccess(f) if crate::frontend::semantic::comptime::is_target_access(f)
+                && self.symbol_table.resolve("target").is_none() => {
+                // target.endian / pointer_width / os / arch - comptime platform constants
+                match crate::frontend::semantic::comptime::target_constant(&self.target, &f.field) {
+                    Some(crate::frontend::semantic::comptime::ComptimeValue::Int(_)) => {
+                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Int)
+                    }
+                    Some(_) => Type::String,
+                    None => {
+                        self.hole(f.span, &format!("Unknown target property '{}' - expected endian, pointer_width, os or arch", f.field))
                     }
                 }
             }
@@ -436,6 +779,7 @@ impl<'a> TypeChecker<'a> {
                 let object_type = self.check_expr(&f.object);
                 eprintln!("[DEBUG] field access object type: {:?}", object_type);
                 match object_type {
+                    Type::Error => Type::Error,
                     Type::Struct(s) => {
                         eprintln!("[DEBUG] object is struct: {}", s.name);
                         // always lookup struct in sym tbl to get fields
@@ -466,13 +810,18 @@ impl<'a> TypeChecker<'a> {
                         };
                         
                         eprintln!("[DEBUG] looking for field {} in {} fields", f.field, fields.len());
+                        self.check_deprecated_field(&s.name, &f.field, f.span);
                         if let Some(field) = fields.iter().find(|field| field.name == f.field) {
                             eprintln!("[DEBUG] found field {}, type: {:?}", f.field, field.type_);
                             field.type_.clone()
                         } else {
                             eprintln!("[DEBUG] field {} not found in struct {}", f.field, s.name);
-                            self.error(f.span, &format!("Field '{}' not found on struct '{}'", f.field, s.name));
-                            Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                            if s.is_tuple() {
+                                let message = format!("Tuple '{}' has no element '{}', it has {} elements", s.name, f.field, fields.len());
+                                return self.hole(f.span, &message);
+                            }
+                            let names: Vec<String> = fields.iter().map(|field| field.name.clone()).collect();
+                            self.member_hole(f.span, &format!("Field '{}' not found on struct '{}'", f.field, s.name), &f.field, &names)
                         }
                     }
                     Type::Pointer(p) => {
@@ -480,18 +829,27 @@ impl<'a> TypeChecker<'a> {
                         // ptrvalue dereferenc
                         if f.field == "value" {
                             eprintln!("[DEBUG] accessing pointer.value");
+                            if p.nullable {
+                                self.null_deref(&f.object, f.span);
+                            }
                             *p.pointee.clone()
                         } else if f.field == "exists?" {
                             eprintln!("[DEBUG] accessing pointer.exists?");
                             // exists? chk 4 nullable pntrs
                             if p.nullable {
                                 Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
+                            } else if self.narrowed_var(&f.object) {
+                                self.redundant_exists(&f.object, f.span);
+                                Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
                             } else {
                                 self.error(f.span, "exists? can only be used on nullable pointers");
                                 Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
                             }
                         } else {
                             eprintln!("[DEBUG] accessing field {} on pointer pointee", f.field);
+                            if p.nullable {
+                                self.null_deref(&f.object, f.span);
+                            }
                             // field access on pointer pointee - chk if pointee is struct
                             match &*p.pointee {
                                 Type::Struct(s) => {
@@ -524,27 +882,26 @@ impl<'a> TypeChecker<'a> {
                                     };
                                     
                                     eprintln!("[DEBUG] looking for field {} in {} fields on pointer pointee", f.field, fields.len());
+                                    self.check_deprecated_field(&s.name, &f.field, f.span);
                                     if let Some(field) = fields.iter().find(|field| field.name == f.field) {
                                         eprintln!("[DEBUG] found field {} on pointer pointee, type: {:?}", f.field, field.type_);
                                         field.type_.clone()
                                     } else {
                                         eprintln!("[DEBUG] field {} not found on pointer pointee {}", f.field, s.name);
-                                        self.error(f.span, &format!("Field '{}' not found on pointer pointee '{}'", f.field, s.name));
-                                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                                        let names: Vec<String> = fields.iter().map(|field| field.name.clone()).collect();
+                                        self.member_hole(f.span, &format!("Field '{}' not found on pointer pointee '{}'", f.field, s.name), &f.field, &names)
                                     }
                                 }
                                 _ => {
                                     eprintln!("[DEBUG] pointer pointee is not struct, cannot access field {}", f.field);
-                                    self.error(f.span, &format!("Field '{}' not found on pointer", f.field));
-                                    Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                                    self.hole(f.span, &format!("Field '{}' not found on pointer", f.field))
                                 }
                             }
                         }
                     }
                     _ => {
                         eprintln!("[DEBUG] field access on non-struct/pointer value, type: {:?}", object_type);
-                        self.error(f.span, "Field access on non-struct/pointer value");
-                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                        self.hole(f.span, "Field access on non-struct/pointer value")
                     }
                 }
             }
@@ -554,6 +911,10 @@ impl<'a> TypeChecker<'a> {
                     Type::Pointer(p) if p.nullable => {
                         Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
                     }
+                    _ if self.narrowed_var(&e.expr) => {
+                        self.redundant_exists(&e.expr, e.span);
+                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
+                    }
                     _ => {
                         self.error(e.span, "exists? can only be used on nullable pointers");
                         Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
@@ -574,6 +935,42 @@ impl<'a> TypeChecker<'a> {
                     false,
                 ))
             }
+            Expr::New(n) => {
+                let value_type = self.check_expr(&n.value);
+                let void = Type::Primitive(crate::core::types::primitive::PrimitiveType::Void);
+                if value_type == void || matches!(value_type, Type::Function(_)) {
+                    return self.hole(n.span, &format!("'new' needs a value to put on the heap, got {:?}", value_type));
+                }
+                Type::Pointer(crate::core::types::pointer::PointerType::new(value_type, false))
+            }
+            Expr::Delete(d) => {
+                let pointer_type = self.check_expr(&d.pointer);
+                if !matches!(pointer_type, Type::Pointer(_)) {
+                    self.error(d.pointer.span(), &format!("'delete' needs a ref, got {:?}", pointer_type));
+                }
+                Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+            }
+            Expr::Try(t) => {
+                let operand = self.check_expr(&t.expr);
+                let Some((value, error)) = (match &operand {
+                    Type::Enum(e) => e.fallible_parts(),
+                    _ => None,
+                }) else {
+                    return self.hole(t.span, &format!("'?' needs a value that can fail, got {}", spelling(&operand)));
+                };
+                match &self.fallible {
+                    Some((_, enclosing)) if enclosing.fallible_parts().is_some_and(|(_, e)| spelling(e) == spelling(error)) => {}
+                    Some((name, enclosing)) => {
+                        let message = format!("'?' passes on an error of type {} but '{}' returns '{}'", spelling(error), name, enclosing.name);
+                        self.error(t.span, &message);
+                    }
+                    None => {
+                        let message = format!("'?' passes the error on, the enclosing function has to return '... or {}'", spelling(error));
+                        self.error(t.span, &message);
+                    }
+                }
+                value.clone()
+            }
             Expr::Block(b) => {
                 for stmt in &b.stmts {
                     self.check_stmt(stmt);
@@ -600,9 +997,14 @@ impl<'a> TypeChecker<'a> {
                 } else {
                     let _ = self.check_expr(&i.condition);
                 }
+                self.narrowed.push(non_null_when(&i.condition, true).into_iter().map(String::from).collect());
                 let then_type = self.check_expr(&i.then_branch);
+                self.narrowed.pop();
                 let else_type = if let Some(e) = &i.else_branch {
-                    self.check_expr(e)
+                    self.narrowed.push(non_null_when(&i.condition, false).into_iter().map(String::from).collect());
+                    let else_type = self.check_expr(e);
+                    self.narrowed.pop();
+                    else_type
                 } else {
                     Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
                 };
@@ -611,6 +1013,7 @@ impl<'a> TypeChecker<'a> {
                 }
                 then_type
             }
+            Expr::Assignment(a) if matches!(a.target.as_ref(), Expr::Tuple(_)) => self.check_destructure(a),
             Expr::Assignment(a) => {
                 eprintln!("[DEBUG] chking assignment expr");
                 let var_name = if let Expr::Variable(v) = &*a.target {
@@ -651,6 +1054,10 @@ impl<'a> TypeChecker<'a> {
                 eprintln!("[DEBUG] chking rhs expr");
                 let value_type = self.check_expr(&a.value);
                 eprintln!("[DEBUG] rhs expr type: {:?}", value_type);
+                // the new vl may be null, the old exists? chk no longer holds
+                if let Some(name) = &var_name {
+                    self.widen(name);
+                }
                 
                 eprintln!("[DEBUG] chking target type");
                 let target_type = self.check_expr(&a.target);
@@ -660,7 +1067,12 @@ impl<'a> TypeChecker<'a> {
                     eprintln!("[DEBUG] updting var {} type to {:?}", name, value_type);
                     if let Some(symbol) = self.symbol_table.resolve_mut(name) {
                         if let crate::frontend::semantic::symbol_table::SymbolKind::Variable { type_, .. } = &mut symbol.kind {
-                            *type_ = value_type.clone();
+                            // a checked `ref` stored in a `ref?` var keeps it nullable
+                            let keeps_nullable = matches!((&*type_, &value_type), (Type::Pointer(old), Type::Pointer(new))
+                                if old.nullable && !new.nullable && old.pointee == new.pointee);
+                            if !keeps_nullable {
+                                *type_ = value_type.clone();
+                            }
                             eprintln!("[DEBUG] var {} type updtd to {:?}", name, value_type);
                         }
                     } else {
@@ -696,9 +1108,11 @@ impl<'a> TypeChecker<'a> {
                 eprintln!("[DEBUG] assignment chk complete, ret type: {:?}", value_type);
                 value_type
             }
+            // comptime params r only known per specialization, `comptime n == 0` folds there
+            Expr::Comptime(c) if !self.comptime_locals.is_empty() && self.is_comptime_known(&c.expr) => self.check_expr(&c.expr),
             Expr::Comptime(c) => {
                 // evaluate comptime expression at compile time
-                let mut evaluator = ComptimeEvaluator::new(self.reporter, self.file_id);
+                let mut evaluator = ComptimeEvaluator::new(self.reporter, self.file_id).with_target(self.target.clone());
                 if let Some(comptime_value) = evaluator.evaluate(&c.expr) {
                     // comptime expression evaluated successfully
                     // ret the type of the computed value
@@ -718,6 +1132,11 @@ impl<'a> TypeChecker<'a> {
                         crate::frontend::semantic::comptime::ComptimeValue::String(_) => {
                             Type::String
                         }
+                        crate::frontend::semantic::comptime::ComptimeValue::Array(_)
+                        | crate::frontend::semantic::comptime::ComptimeValue::Struct { .. } => {
+                            // aggregate shape comes from the literal itself
+                            self.check_expr(&c.expr)
+                        }
                     }
                 } else {
                     // comptime evaluation failed err alrdy reported
@@ -731,8 +1150,14 @@ impl<'a> TypeChecker<'a> {
                     return_type: Box::new(Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)),
                 })
             }
+            Expr::StructLiteral(s) if s.is_record() => self.check_record(s),
+            Expr::StructLiteral(s) if self.is_function(&s.struct_name) => self.check_expr(&Expr::Call(s.as_record_call())),
             Expr::StructLiteral(s) => {
                 // chk struct literal: Circle { radius: 5.0 }
+                self.check_deprecated_type(&s.struct_name, s.span);
+                for (field_name, field_value) in &s.fields {
+                    self.check_deprecated_field(&s.struct_name, field_name, field_value.span());
+                }
                 // lookup struct definition
                 if let Some(symbol) = self.symbol_table.resolve(&s.struct_name) {
                     if let crate::frontend::semantic::symbol_table::SymbolKind::Struct { fields } = &symbol.kind {
@@ -746,7 +1171,12 @@ impl<'a> TypeChecker<'a> {
                                     self.error(field_value.span(), &format!("Field '{}' type mismatch: expected {:?}, got {:?}", field_name, expected_type, value_type));
                                 }
                             } else {
-                                self.error(s.span, &format!("Field '{}' not found in struct '{}'", field_name, s.struct_name));
+                                let names: Vec<String> = fields_clone.iter().map(|(name, _)| name.clone()).collect();
+                                let mut diagnostic = Diagnostic::error(DiagnosticKind::TypeError, s.span, self.file_id, format!("Field '{}' not found in struct '{}'", field_name, s.struct_name));
+                                if let Some(name) = closest_name(field_name, &names) {
+                                    diagnostic = diagnostic.with_note(format!("did you mean '{}'?", name));
+                                }
+                                self.reporter.add_diagnostic(diagnostic);
                             }
                         }
                         // return struct type
@@ -763,12 +1193,10 @@ impl<'a> TypeChecker<'a> {
                             align: None,
                         })
                     } else {
-                        self.error(s.span, &format!("'{}' is not a struct", s.struct_name));
-                        Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                        self.hole(s.span, &format!("'{}' is not a struct", s.struct_name))
                     }
                 } else {
-                    self.error(s.span, &format!("Undefined struct '{}'", s.struct_name));
-                    Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                    self.hole(s.span, &format!("Undefined struct '{}'", s.struct_name))
                 }
             }
             Expr::ArrayLiteral(a) => {
@@ -796,7 +1224,7 @@ impl<'a> TypeChecker<'a> {
                     if all_same {
                         Type::Array(crate::core::types::composite::ArrayType {
                             element: Box::new(first_type),
-                            size: a.elements.len(),
+                            size: a.repeat.unwrap_or(a.elements.len()),
                         })
                     } else {
                         // err case - return void
@@ -804,6 +1232,10 @@ impl<'a> TypeChecker<'a> {
                     }
                 }
             }
+            Expr::Tuple(t) => {
+                let elements = t.elements.iter().map(|e| self.check_expr(e)).collect();
+                Type::Struct(crate::core::types::composite::StructType::tuple(elements))
+            }
             Expr::Null => {
                 Type::Pointer(crate::core::types::pointer::PointerType::new(
                     Type::Primitive(crate::core::types::primitive::PrimitiveType::Void),
@@ -814,7 +1246,15 @@ impl<'a> TypeChecker<'a> {
     }
 
     fn check_binary_op(&mut self, op: &BinaryOp, left: &Type, right: &Type, span: codespan::Span) -> Type {
+        if left.is_error() || right.is_error() {
+            return match op {
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => Type::Error,
+                _ => Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
+            };
+        }
         match op {
+            // concatenation
+            BinaryOp::Add if matches!(left, Type::String) && matches!(right, Type::String) => Type::String,
             BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
                 if self.is_numeric_type(left) && self.is_numeric_type(right) {
                     // type promotion
@@ -824,12 +1264,11 @@ impl<'a> TypeChecker<'a> {
                         left.clone()
                     }
                 } else {
-                    self.error(span, "Binary operator requires numeric operands");
-                    Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                    self.hole(span, "Binary operator requires numeric operands")
                 }
             }
             BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
-                if self.types_compatible(left, right) {
+                if self.types_compatible(left, right) || widens_to_nullable(right, left) {
                     Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
                 } else {
                     self.error(span, "Comparison requires compatible types");
@@ -850,15 +1289,14 @@ impl<'a> TypeChecker<'a> {
     fn check_unary_op(&mut self, op: &UnaryOp, expr_type: &Type, span: codespan::Span) -> Type {
         match op {
             UnaryOp::Neg => {
-                if self.is_numeric_type(expr_type) {
+                if self.is_numeric_type(expr_type) || expr_type.is_error() {
                     expr_type.clone()
                 } else {
-                    self.error(span, "Negation requires numeric operand");
-                    Type::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                    self.hole(span, "Negation requires numeric operand")
                 }
             }
             UnaryOp::Not => {
-                if self.is_bool_type(expr_type) {
+                if self.is_bool_type(expr_type) || expr_type.is_error() {
                     Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool)
                 } else {
                     self.error(span, "Not operator requires bool operand");
@@ -868,8 +1306,62 @@ impl<'a> TypeChecker<'a> {
         }
     }
 
+    /// `{ x: 1, y: 2 }` - the record of its field types
+    fn check_record(&mut self, s: &crate::core::ast::expr::StructLiteralExpr) -> Type {
+        let mut fields: Vec<(String, Type)> = Vec::new();
+        for (name, value) in &s.fields {
+            let type_ = self.check_expr(value);
+            if fields.iter().any(|(n, _)| n == name) {
+                self.error(value.span(), &format!("Field '{}' is given more than once", name));
+                continue;
+            }
+            fields.push((name.clone(), type_));
+        }
+        Type::Struct(crate::core::types::composite::StructType::record(fields))
+    }
+
+    /// `a, b = pair`, each target takes the element at its position
+    fn check_destructure(&mut self, a: &crate::core::ast::expr::AssignmentExpr) -> Type {
+        let Expr::Tuple(targets) = a.target.as_ref() else { return Type::Error };
+        let value_type = self.check_expr(&a.value);
+        let elements: Vec<Type> = match &value_type {
+            Type::Error => return Type::Error,
+            Type::Struct(s) if s.is_tuple() => s.fields.iter().map(|f| f.type_.clone()).collect(),
+            other => {
+                return self.hole(a.value.span(), &format!("Cannot destructure {:?}, only a tuple can be assigned to several targets", other));
+            }
+        };
+        if elements.len() != targets.elements.len() {
+            let message = format!("Cannot destructure a {}-tuple into {} targets", elements.len(), targets.elements.len());
+            return self.hole(a.span, &message);
+        }
+        let values = match a.value.as_ref() {
+            Expr::Tuple(t) => t.elements.iter().map(Some).collect(),
+            _ => vec![None; elements.len()],
+        };
+        for ((target, element), value) in targets.elements.iter().zip(&elements).zip(values) {
+            if let Expr::Variable(v) = target {
+                self.widen(&v.name);
+            }
+            let target_type = self.check_expr(target);
+            let fits = value.is_some_and(|v| self.is_int_literal_fit(v, &target_type));
+            if !fits && !self.types_compatible(&target_type, element) {
+                self.error(target.span(), &format!("Type mismatch in assignment: expected {:?}, got {:?}", target_type, element));
+            }
+        }
+        value_type
+    }
+
+    fn is_function(&self, name: &str) -> bool {
+        matches!(
+            self.symbol_table.resolve(name).map(|s| &s.kind),
+            Some(crate::frontend::semantic::symbol_table::SymbolKind::Function { .. })
+        )
+    }
+
     fn types_compatible(&self, a: &Type, b: &Type) -> bool {
-        if a == b {
+        // a hole's error is already reported
+        if a == b || a.is_error() || b.is_error() || widens_to_nullable(a, b) {
             return true;
         }
         // null is compatible with any pointer (nullable or not)
@@ -900,11 +1392,23 @@ impl<'a> TypeChecker<'a> {
         if matches!(a, Type::Generic(_)) || matches!(b, Type::Generic(_)) {
             return true;
         }
-        false
+        // structs r nominal - annotations resolve w/o their field list
+        // records + tuples r structural, the same field names w/ compatible types
+        if let (Type::Struct(sa), Type::Struct(sb)) = (a, b) {
+            if sa.is_structural() && sb.is_structural() {
+                return sa.fields.len() == sb.fields.len()
+                    && sa.fields.iter().zip(&sb.fields).all(|(x, y)| x.name == y.name && self.types_compatible(&x.type_, &y.type_));
+            }
+            return sa.name == sb.name;
+        }
+        if same_struct_ref(a, b) {
+            return true;
+        }
+        self.coerces_to_dyn(a, b)
     }
 
     fn types_compatible_strict(&self, a: &Type, b: &Type) -> bool {
-        if a == b {
+        if a == b || a.is_error() || b.is_error() || widens_to_nullable(a, b) {
             return true;
         }
         // null is compatible with any pointer (nullable or not)
@@ -922,6 +1426,13 @@ impl<'a> TypeChecker<'a> {
                 }
             }
         }
+        // records + tuples by field, each strictly
+        if let (Type::Struct(sa), Type::Struct(sb)) = (a, b) {
+            if sa.is_structural() && sb.is_structural() {
+                return sa.fields.len() == sb.fields.len()
+                    && sa.fields.iter().zip(&sb.fields).all(|(x, y)| x.name == y.name && self.types_compatible_strict(&x.type_, &y.type_));
+            }
+        }
         // no numeric promotion in strict mode
         // str literals can be assigned 2 str type
         if matches!(a, Type::String) && matches!(b, Type::String) {
@@ -930,7 +1441,351 @@ impl<'a> TypeChecker<'a> {
         if matches!(a, Type::Generic(_)) || matches!(b, Type::Generic(_)) {
             return true;
         }
-        false
+        // structs r nominal - annotations resolve w/o their field list
+        if let (Type::Struct(sa), Type::Struct(sb)) = (a, b) {
+            return sa.name == sb.name;
+        }
+        if same_struct_ref(a, b) {
+            return true;
+        }
+        self.coerces_to_dyn(a, b)
+    }
+
+    /// `ref T` -> `dyn Trait` when T implements it, the ref becomes the data ptr
+    fn coerces_to_dyn(&self, target: &Type, value: &Type) -> bool {
+        let (Type::TraitObject(t), Type::Pointer(p)) = (target, value) else { return false };
+        match &*p.pointee {
+            Type::Struct(s) if !p.nullable => self.trait_resolver.type_implements_trait(&s.name, &t.trait_name),
+            _ => false,
+        }
+    }
+
+    /// args 4 comptime params must fold 2 a constant
+    fn check_comptime_args(&mut self, c: &CallExpr) {
+        let Expr::Variable(callee) = &*c.callee else { return };
+        // a local shadowing the fn isnt the comptime fn
+        let kind = self.symbol_table.resolve(&callee.name).map(|s| &s.kind);
+        if !matches!(kind, Some(crate::frontend::semantic::symbol_table::SymbolKind::Function { .. })) {
+            return;
+        }
+        let Some(params) = self.comptime_params.get(&callee.name).cloned() else { return };
+        for (arg, (name, comptime)) in c.args.iter().zip(params) {
+            if comptime && !self.is_comptime_known(arg) {
+                self.error(
+                    arg.span(),
+                    &format!("Argument for comptime parameter '{}' of '{}' must be known at compile time", name, callee.name),
+                );
+            }
+        }
+    }
+
+    /// whether `expr` folds 2 a constant once the enclosing fn is specialized
+    fn is_comptime_known(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Literal(_) => true,
+            Expr::Variable(v) => self.comptime_locals.contains(&v.name),
+            Expr::Binary(b) => self.is_comptime_known(&b.left) && self.is_comptime_known(&b.right),
+            Expr::Unary(u) => self.is_comptime_known(&u.expr),
+            Expr::Comptime(c) => self.is_comptime_known(&c.expr),
+            Expr::FieldAccess(f) => crate::frontend::semantic::comptime::is_target_access(f),
+            _ => false,
+        }
+    }
+
+    /// printf style format strings r checked against the variadic args, specifier errors point into the literal
+    fn check_format_args(&mut self, c: &CallExpr) {
+        let Expr::Variable(callee) = &*c.callee else { return };
+        let Some(&format_index) = self.format_fns.get(&callee.name) else { return };
+        let kind = self.symbol_table.resolve(&callee.name).map(|s| &s.kind);
+        if !matches!(kind, Some(crate::frontend::semantic::symbol_table::SymbolKind::Function { .. })) {
+            return;
+        }
+        let Some(format_expr) = c.args.get(format_index) else { return };
+        let variadic = &c.args[format_index + 1..];
+        let Expr::Literal(LiteralExpr { kind: LiteralKind::String(format), span, .. }) = format_expr else {
+            if !variadic.is_empty() {
+                self.warning(
+                    format_expr.span(),
+                    &format!("Format string of '{}' is not a literal, its arguments can't be checked", callee.name),
+                );
+            }
+            return;
+        };
+        // escapes shift offsets, those fall back 2 the whole literal
+        let exact = (span.end().to_usize() - span.start().to_usize()) == format.len() + 2;
+        let spec_span = |start: usize, end: usize| {
+            if exact {
+                let base = span.start().to_usize() + 1;
+                codespan::Span::new((base + start) as u32, (base + end) as u32)
+            } else {
+                *span
+            }
+        };
+        let specs = match parse_format(format) {
+            Ok(specs) => specs,
+            Err(e) => {
+                self.error(spec_span(e.start, e.end), &e.message);
+                return;
+            }
+        };
+        for (spec, arg) in specs.iter().zip(variadic) {
+            let arg_type = self.check_expr(arg);
+            if !spec.expect.accepts(&arg_type) {
+                self.error(
+                    arg.span(),
+                    &format!(
+                        "Format '{}' expects {}, got {:?}",
+                        &format[spec.start..spec.end],
+                        spec.expect.describe(),
+                        arg_type
+                    ),
+                );
+            }
+        }
+        if let Some(spec) = specs.get(variadic.len()) {
+            self.error(
+                spec_span(spec.start, spec.end),
+                &format!("Format '{}' has no matching argument", &format[spec.start..spec.end]),
+            );
+        } else if variadic.len() > specs.len() {
+            self.error(
+                variadic[specs.len()].span(),
+                &format!("'{}' got {} argument(s) but its format string uses {}", callee.name, variadic.len(), specs.len()),
+            );
+        }
+    }
+
+    // volatile_load(ptr) / volatile_store(ptr, value) unless shadowed by a user fn
+    fn is_volatile_builtin(&self, c: &CallExpr) -> bool {
+        match &*c.callee {
+            Expr::Variable(v) => {
+                (v.name == "volatile_load" || v.name == "volatile_store")
+                    && self.symbol_table.resolve(&v.name).is_none()
+            }
+            _ => false,
+        }
+    }
+
+    // volatile accesses go thru a non-void ref so the access width is known
+    fn check_volatile_access(&mut self, c: &CallExpr) -> Type {
+        let void = Type::Primitive(crate::core::types::primitive::PrimitiveType::Void);
+        let is_load = matches!(&*c.callee, Expr::Variable(v) if v.name == "volatile_load");
+        let (name, arity) = if is_load { ("volatile_load", 1) } else { ("volatile_store", 2) };
+        if c.args.len() != arity {
+            self.error(c.span, &format!("'{}' takes {} argument(s), got {}", name, arity, c.args.len()));
+            return void;
+        }
+        let ptr_type = self.check_expr(&c.args[0]);
+        let pointee = match &ptr_type {
+            Type::Pointer(p) if *p.pointee != void => *p.pointee.clone(),
+            _ => {
+                self.error(c.args[0].span(), &format!("'{}' needs a ref to a sized type, got {:?}", name, ptr_type));
+                return void;
+            }
+        };
+        if is_load {
+            return pointee;
+        }
+        let value_type = self.check_expr(&c.args[1]);
+        if !self.types_compatible(&pointee, &value_type) {
+            self.error(c.args[1].span(), &format!("'volatile_store' value type mismatch: expected {:?}, got {:?}", pointee, value_type));
+        }
+        void
+    }
+
+    /// trait the receiver's struct implements that has `method`, w/ `ref` of the struct
+    fn implementing_trait(&self, receiver: &Type, method: &str) -> Option<(String, Type)> {
+        let s = match receiver {
+            Type::Struct(s) => s,
+            Type::Pointer(p) => match &*p.pointee {
+                Type::Struct(s) => s,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        self.traits
+            .values()
+            .find(|t| t.methods.iter().any(|x| x.name == method) && self.trait_resolver.type_implements_trait(&s.name, &t.name))
+            .map(|t| {
+                let self_type = crate::core::types::pointer::PointerType { pointee: Box::new(Type::Struct(s.clone())), nullable: false };
+                (t.name.clone(), Type::Pointer(self_type))
+            })
+    }
+
+    /// trait method called on a value or thru a vtable (no `self_type`), only methods taking `self` can be
+    /// untyped params stand 4 the implementing type, behind a vtable its erased
+    /// `Enum::Variant` or `Enum::Variant(args)` - the variant exists + args match its payload
+    /// the value types as the enum's name, like a struct annotation does
+    fn check_variant(&mut self, m: &ModuleAccessExpr, args: &[Expr], span: codespan::Span) -> Type {
+        self.check_deprecated_type(&m.module, m.span);
+        let type_ = Type::Struct(StructType { name: m.module.clone(), fields: Vec::new(), size: None, align: None });
+        let Some(variant) = self.enums[&m.module].variants.iter().find(|v| v.name == m.member).cloned() else {
+            self.error(m.span, &format!("Enum '{}' has no variant '{}'", m.module, m.member));
+            return type_;
+        };
+        if args.len() != variant.fields.len() {
+            self.error(
+                span,
+                &format!("Variant '{}::{}' takes {} value(s), got {}", m.module, m.member, variant.fields.len(), args.len()),
+            );
+        }
+        for (i, (arg, field)) in args.iter().zip(&variant.fields).enumerate() {
+            let arg_type = self.check_expr(arg);
+            if !self.types_compatible(field, &arg_type) {
+                self.error(arg.span(), &format!("Argument {} type mismatch: expected {:?}, got {:?}", i, field, arg_type));
+            }
+        }
+        type_
+    }
+
+    /// a fn returning `value or error` returns either half, or a whole `value or error` it got
+    fn check_fallible_return(&mut self, value_type: &Type, span: codespan::Span) {
+        let Some((name, enclosing)) = &self.fallible else { return };
+        let Some((value, error)) = enclosing.fallible_parts() else { return };
+        let whole = matches!(value_type, Type::Enum(e) if e.name == enclosing.name);
+        if whole || self.types_compatible(value, value_type) || self.types_compatible(error, value_type) {
+            return;
+        }
+        let message = format!("Type mismatch: '{}' returns '{}', got {}", name, enclosing.name, spelling(value_type));
+        self.error(span, &message);
+    }
+
+    /// payload bindings of an arm's variant patterns, arms w/ several patterns cant bind
+    fn define_pattern_bindings(&mut self, arm: &MatchArm, e: &EnumType) {
+        let binds = |p: &Pattern| matches!(p, Pattern::Variant(v) if v.bindings.iter().any(|b| b != "_"));
+        if arm.patterns.len() > 1 && arm.patterns.iter().any(binds) {
+            self.error(arm.span, "Patterns that bind values can't share an arm");
+            return;
+        }
+        for pattern in &arm.patterns {
+            let Pattern::Variant(v) = pattern else { continue };
+            let Some(variant) = e.variants.iter().find(|x| x.name == v.variant) else { continue };
+            for (name, type_) in v.bindings.iter().zip(&variant.fields) {
+                if name == "_" {
+                    continue;
+                }
+                let symbol = crate::frontend::semantic::symbol_table::Symbol {
+                    name: name.clone(),
+                    kind: crate::frontend::semantic::symbol_table::SymbolKind::Variable { mutable: false, type_: type_.clone() },
+                    span: v.span,
+                    defined: true,
+                };
+                let _ = self.symbol_table.define(name.clone(), symbol);
+            }
+        }
+    }
+
+    fn check_trait_method_call(&mut self, m: &MethodCallExpr, trait_name: &str, self_type: Option<Type>) -> Type {
+        let void = Type::Primitive(crate::core::types::primitive::PrimitiveType::Void);
+        let method = self.traits.get(trait_name).and_then(|t| t.methods.iter().find(|x| x.name == m.method)).cloned();
+        let Some(method) = method else {
+            self.error(m.span, &format!("Trait '{}' has no method '{}'", trait_name, m.method));
+            return void;
+        };
+        if method.params.first().map(|p| p.name.as_str()) != Some("self") {
+            self.error(m.span, &format!("'{}::{}' has no self parameter so it can't be called as a method", trait_name, m.method));
+            return void;
+        }
+        let params = &method.params[1..];
+        if m.args.len() != params.len() {
+            self.error(m.span, &format!("'{}' takes {} argument(s), got {}", m.method, params.len(), m.args.len()));
+        }
+        for (arg, param) in m.args.iter().zip(params) {
+            let arg_type = self.check_expr(arg);
+            let mut param_type = resolve_ast_type(&param.type_);
+            if param_type == void {
+                let Some(self_type) = self_type.clone() else {
+                    self.error(arg.span(), &format!("'{}' takes the implementing type so it can't be called on dyn {}", m.method, trait_name));
+                    continue;
+                };
+                param_type = self_type;
+            }
+            if !self.types_compatible(&param_type, &arg_type) {
+                self.error(arg.span(), &format!("Argument type mismatch: expected {:?}, got {:?}", param_type, arg_type));
+            }
+        }
+        method.return_type.as_ref().map(resolve_ast_type).unwrap_or(void)
+    }
+
+    // ptr.offset(n) / ptr.add(n) - unchecked arithmetic in units of the pointee
+    // offset takes a signed count, add only counts forward
+    fn check_pointer_arithmetic(&mut self, m: &MethodCallExpr, p: &crate::core::types::pointer::PointerType) -> Type {
+        let result = Type::Pointer(p.clone());
+        if m.args.len() != 1 {
+            self.error(m.span, &format!("Pointer '{}' takes exactly one element count, got {} arguments", m.method, m.args.len()));
+            return result;
+        }
+        let count_type = self.check_expr(&m.args[0]);
+        let is_int = matches!(&count_type, Type::Primitive(prim) if prim.is_integer());
+        if !is_int {
+            self.error(m.args[0].span(), &format!("Pointer '{}' count must be an integer, got {:?}", m.method, count_type));
+        }
+        if *p.pointee == Type::Primitive(crate::core::types::primitive::PrimitiveType::Void) {
+            self.error(m.span, &format!("Cannot '{}' a pointer to void - element size is unknown", m.method));
+        }
+        if m.method == "add" {
+            let negative = match &m.args[0] {
+                Expr::Unary(u) => matches!(u.op, UnaryOp::Neg),
+                Expr::Literal(LiteralExpr { kind: LiteralKind::Int(n), .. }) => *n < 0,
+                _ => false,
+            };
+            if negative {
+                self.error(m.args[0].span(), "Pointer 'add' cannot move backwards - use 'offset' for negative counts");
+            }
+        }
+        result
+    }
+
+    // [n; N] w/ int literal n can fill any integer element type n fits in
+    fn is_int_repeat_fill(&self, value: &Expr, element: &Type) -> bool {
+        match value {
+            Expr::ArrayLiteral(ArrayLiteralExpr { elements, repeat: Some(_), .. }) => {
+                elements.first().is_some_and(|fill| self.is_int_literal_fit(fill, element))
+            }
+            _ => false,
+        }
+    }
+
+    // int literal that fits the declared integer type, eg `x : uint = 4`
+    fn is_int_literal_fit(&self, value: &Expr, ty: &Type) -> bool {
+        match (value, ty) {
+            (Expr::Literal(LiteralExpr { kind: LiteralKind::Int(n), .. }), Type::Primitive(p)) if p.is_integer() => {
+                match p {
+                    crate::core::types::primitive::PrimitiveType::Byte
+                    | crate::core::types::primitive::PrimitiveType::UByte => (0..=255).contains(n),
+                    crate::core::types::primitive::PrimitiveType::Int => i32::try_from(*n).is_ok(),
+                    crate::core::types::primitive::PrimitiveType::UInt => u32::try_from(*n).is_ok(),
+                    crate::core::types::primitive::PrimitiveType::ULong => *n >= 0,
+                    _ => true,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// `{ x: 1 }` initializing a `{ x: long }` - each field fits like its own initializer would
+    fn is_record_literal_fit(&self, value: &Expr, value_type: &Type, ty: &Type) -> bool {
+        if let (Expr::Tuple(l), Type::Struct(v), Type::Struct(t)) = (value, value_type, ty) {
+            // `(1, 2)` initializing a `(long, byte)`, by position
+            return t.is_tuple()
+                && l.elements.len() == t.fields.len()
+                && l.elements.iter().zip(&v.fields).zip(&t.fields).all(|((element, vf), tf)| {
+                    self.types_compatible_strict(&tf.type_, &vf.type_)
+                        || self.is_int_literal_fit(element, &tf.type_)
+                        || self.is_record_literal_fit(element, &vf.type_, &tf.type_)
+                });
+        }
+        let (Expr::StructLiteral(l), Type::Struct(v), Type::Struct(t)) = (value, value_type, ty) else { return false };
+        if !l.is_record() || !t.is_record() || v.fields.len() != t.fields.len() {
+            return false;
+        }
+        v.fields.iter().zip(&t.fields).all(|(vf, tf)| {
+            let Some((_, field_value)) = l.fields.iter().find(|(name, _)| *name == tf.name) else { return false };
+            vf.name == tf.name
+                && (self.types_compatible_strict(&tf.type_, &vf.type_)
+                    || self.is_int_literal_fit(field_value, &tf.type_)
+                    || self.is_record_literal_fit(field_value, &vf.type_, &tf.type_))
+        })
     }
 
     fn is_bool_type(&self, t: &Type) -> bool {
@@ -944,6 +1799,9 @@ impl<'a> TypeChecker<'a> {
                 crate::core::types::primitive::PrimitiveType::Int
                     | crate::core::types::primitive::PrimitiveType::Long
                     | crate::core::types::primitive::PrimitiveType::Float
+                    | crate::core::types::primitive::PrimitiveType::UByte
+                    | crate::core::types::primitive::PrimitiveType::UInt
+                    | crate::core::types::primitive::PrimitiveType::ULong
             )
         )
     }
@@ -952,6 +1810,153 @@ impl<'a> TypeChecker<'a> {
         matches!(t, Type::Primitive(crate::core::types::primitive::PrimitiveType::Float))
     }
 
+    /// checks a nested block, w/ `non_null` narrowed inside it
+    /// narrowings frm early returns in the block end w/ it
+    fn check_block(&mut self, stmts: &[Stmt], non_null: &[&str]) {
+        self.narrowed.push(non_null.iter().map(|n| n.to_string()).collect());
+        for stmt in stmts {
+            self.check_stmt(stmt);
+        }
+        self.narrowed.pop();
+    }
+
+    fn narrow(&mut self, name: &str) {
+        if let Some(frame) = self.narrowed.last_mut() {
+            frame.push(name.to_string());
+        }
+    }
+
+    fn widen(&mut self, name: &str) {
+        for frame in &mut self.narrowed {
+            frame.retain(|n| n != name);
+        }
+    }
+
+    fn is_narrowed(&self, name: &str) -> bool {
+        self.narrowed.iter().flatten().any(|n| n == name)
+    }
+
+    fn narrowed_var(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::Variable(v) if self.is_narrowed(&v.name))
+    }
+
+    fn null_deref(&mut self, object: &Expr, span: codespan::Span) {
+        let message = match object {
+            Expr::Variable(v) => format!("'{}' may be null, check it with exists? before dereferencing it", v.name),
+            _ => "Nullable pointer may be null, bind it to a variable and check it with exists? before dereferencing it".to_string(),
+        };
+        self.error(span, &message);
+    }
+
+    fn redundant_exists(&mut self, object: &Expr, span: codespan::Span) {
+        if let Expr::Variable(v) = object {
+            self.warning(span, &format!("'{}' was already checked, exists? is always true here", v.name));
+        }
+    }
+
+    /// a use of a `@deprecated` fn, locals shadowing it dont count
+    fn check_deprecated_function(&mut self, name: &str, span: codespan::Span) {
+        let is_function = matches!(
+            self.symbol_table.resolve(name).map(|s| &s.kind),
+            Some(crate::frontend::semantic::symbol_table::SymbolKind::Function { .. })
+        );
+        if let Some(deprecated) = self.deprecations.function(name).filter(|_| is_function).cloned() {
+            report_use(self.reporter, self.file_id, span, &format!("function '{}'", name), &deprecated);
+        }
+    }
+
+    fn check_deprecated_type(&mut self, name: &str, span: codespan::Span) {
+        if let Some(deprecated) = self.deprecations.type_(name).cloned() {
+            report_use(self.reporter, self.file_id, span, &format!("type '{}'", name), &deprecated);
+        }
+    }
+
+    /// deprecated types named in an annotation
+    fn check_deprecated_types(&mut self, ty: &crate::core::ast::types::Type, span: codespan::Span) {
+        for name in self.deprecations.types_in(ty) {
+            self.check_deprecated_type(name, span);
+        }
+    }
+
+    fn check_deprecated_field(&mut self, struct_name: &str, field: &str, span: codespan::Span) {
+        if let Some(deprecated) = self.deprecations.field(struct_name, field).cloned() {
+            report_use(self.reporter, self.file_id, span, &format!("field '{}.{}'", struct_name, field), &deprecated);
+        }
+    }
+
+    fn warning(&mut self, span: codespan::Span, message: &str) {
+        let diagnostic = Diagnostic::warning(
+            DiagnosticKind::SemanticError,
+            span,
+            self.file_id,
+            message.to_string(),
+        );
+        self.reporter.add_diagnostic(diagnostic);
+    }
+
+    /// error w/ a "defined here" label when the callee comes frm another file
+    fn error_at_callee(&mut self, span: codespan::Span, message: &str, callee: &Expr) {
+        let mut diagnostic = Diagnostic::error(DiagnosticKind::TypeError, span, self.file_id, message.to_string());
+        if let Expr::ModuleAccess(m) = callee {
+            let name = format!("{}::{}", m.module, m.member);
+            if let Some(&(file_id, def)) = self.origins.get(&name) {
+                diagnostic = diagnostic.with_label(file_id, def, format!("'{}' is defined here", name));
+            }
+        }
+        self.reporter.add_diagnostic(diagnostic);
+    }
+
+    /// report the error + type the expr as a hole
+    fn hole(&mut self, span: codespan::Span, message: &str) -> Type {
+        self.error(span, message);
+        self.record_hole(span)
+    }
+
+    /// hole 4 an unknown field / method, notes the closest member the type does have
+    fn member_hole(&mut self, span: codespan::Span, message: &str, wanted: &str, names: &[String]) -> Type {
+        let mut diagnostic = Diagnostic::error(DiagnosticKind::TypeError, span, self.file_id, message.to_string());
+        if let Some(name) = closest_name(wanted, names) {
+            diagnostic = diagnostic.with_note(format!("did you mean '{}'?", name));
+        }
+        self.reporter.add_diagnostic(diagnostic);
+        self.record_hole(span)
+    }
+
+    /// methods callable on a struct (or a ptr 2 one) thru the traits it implements
+    fn method_names(&self, receiver: &Type) -> Vec<String> {
+        let s = match receiver {
+            Type::Struct(s) => s,
+            Type::Pointer(p) => match &*p.pointee {
+                Type::Struct(s) => s,
+                _ => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+        let mut names = self.trait_resolver.get_trait_methods(&s.name);
+        for t in self.traits.values().filter(|t| self.trait_resolver.type_implements_trait(&s.name, &t.name)) {
+            names.extend(t.methods.iter().map(|m| m.name.clone()));
+        }
+        names
+    }
+
+    fn record_hole(&mut self, span: codespan::Span) -> Type {
+        let scope = self.symbol_table.visible().into_iter().filter_map(|(name, symbol)| match symbol.kind {
+            crate::frontend::semantic::symbol_table::SymbolKind::Variable { type_, .. } if !type_.is_error() => Some((name, type_)),
+            crate::frontend::semantic::symbol_table::SymbolKind::Function { params, return_type } => {
+                let return_type = return_type.unwrap_or(Type::Primitive(crate::core::types::primitive::PrimitiveType::Void));
+                Some((name, Type::Function(crate::core::types::composite::FunctionType { params, return_type: Box::new(return_type) })))
+            }
+            _ => None,
+        });
+        self.holes.push(Hole { span, scope: scope.collect() });
+        Type::Error
+    }
+
+    /// holes left by exprs that failed 2 check
+    pub fn take_holes(&mut self) -> Vec<Hole> {
+        std::mem::take(&mut self.holes)
+    }
+
     fn error(&mut self, span: codespan::Span, message: &str) {
         let diagnostic = Diagnostic::error(
             DiagnosticKind::TypeError,
@@ -962,3 +1967,153 @@ impl<'a> TypeChecker<'a> {
         self.reporter.add_diagnostic(diagnostic);
     }
 }
+
+/// refs 2 the same struct, eg a `new` literal's w/ its fields + an annotation's w/o. a `ref` goes
+/// where a `ref?` is wanted but not the other way
+fn same_struct_ref(target: &Type, value: &Type) -> bool {
+    match (target, value) {
+        (Type::Pointer(t), Type::Pointer(v)) => {
+            (t.nullable || !v.nullable) && matches!((&*t.pointee, &*v.pointee), (Type::Struct(a), Type::Struct(b)) if a.name == b.name)
+        }
+        _ => false,
+    }
+}
+
+/// the name nearest 2 `wanted` by edit distance, if its close enough 2 b a typo
+/// a case-only difference always wins, ties go 2 the first declared
+pub(crate) fn closest_name<'a>(wanted: &str, names: &'a [String]) -> Option<&'a str> {
+    if let Some(name) = names.iter().find(|n| n.eq_ignore_ascii_case(wanted)) {
+        return Some(name);
+    }
+    let limit = (wanted.chars().count() / 3).max(1);
+    names
+        .iter()
+        .map(|n| (edit_distance(wanted, n), n))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, n)| n.as_str())
+}
+
+/// levenshtein distance, an adjacent swap counts as 1 edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>(); a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut d = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d = d.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = d;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// the vars `cond` proves non-null once it evaluated 2 `holds`: `p.exists?` / `exists? p` when
+/// true, w/ a `not` in front when false
+fn non_null_when(cond: &Expr, holds: bool) -> Vec<&str> {
+    match cond {
+        Expr::Exists(e) => match e.expr.as_ref() {
+            Expr::Variable(v) if holds => vec![&v.name],
+            _ => Vec::new(),
+        },
+        Expr::FieldAccess(f) if f.field == "exists?" => match f.object.as_ref() {
+            Expr::Variable(v) if holds => vec![&v.name],
+            _ => Vec::new(),
+        },
+        Expr::Unary(u) if u.op == UnaryOp::Not => non_null_when(&u.expr, !holds),
+        // both sides of an `&&` that held, + of an `||` that didnt
+        Expr::Binary(b) if (b.op == BinaryOp::And && holds) || (b.op == BinaryOp::Or && !holds) => {
+            let mut names = non_null_when(&b.left, holds);
+            names.extend(non_null_when(&b.right, holds));
+            names
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// vars `stmts` assign anywhere, nested blocks + closures included
+fn assigned_vars(stmts: &[Stmt], names: &mut HashSet<String>) {
+    fn expr(e: &Expr, names: &mut HashSet<String>) {
+        match e {
+            Expr::Assignment(a) => {
+                match a.target.as_ref() {
+                    Expr::Variable(v) => {
+                        names.insert(v.name.clone());
+                    }
+                    Expr::Tuple(t) => {
+                        names.extend(t.elements.iter().filter_map(|e| match e {
+                            Expr::Variable(v) => Some(v.name.clone()),
+                            _ => None,
+                        }));
+                    }
+                    _ => {}
+                }
+                expr(&a.value, names);
+            }
+            Expr::Block(b) => {
+                assigned_vars(&b.stmts, names);
+                b.expr.iter().for_each(|e| expr(e, names));
+            }
+            Expr::If(i) => {
+                expr(&i.condition, names);
+                expr(&i.then_branch, names);
+                i.else_branch.iter().for_each(|e| expr(e, names));
+            }
+            Expr::Closure(c) => assigned_vars(&c.body, names),
+            Expr::Binary(b) => {
+                expr(&b.left, names);
+                expr(&b.right, names);
+            }
+            Expr::Unary(u) => expr(&u.expr, names),
+            Expr::Call(c) => c.args.iter().for_each(|a| expr(a, names)),
+            Expr::MethodCall(m) => {
+                expr(&m.receiver, names);
+                m.args.iter().for_each(|a| expr(a, names));
+            }
+            _ => {}
+        }
+    }
+    for stmt in stmts {
+        match stmt {
+            Stmt::Expr(s) => expr(&s.expr, names),
+            Stmt::Let(s) => s.value.iter().for_each(|e| expr(e, names)),
+            Stmt::Return(s) => s.value.iter().for_each(|e| expr(e, names)),
+            Stmt::If(s) => {
+                expr(&s.condition, names);
+                assigned_vars(&s.then_branch, names);
+                s.else_branch.iter().for_each(|b| assigned_vars(b, names));
+            }
+            Stmt::While(s) => {
+                expr(&s.condition, names);
+                assigned_vars(&s.body, names);
+            }
+            Stmt::For(s) => {
+                s.init.iter().for_each(|i| assigned_vars(std::slice::from_ref(i), names));
+                s.increment.iter().for_each(|e| expr(e, names));
+                assigned_vars(&s.body, names);
+            }
+            Stmt::Guard(s) => {
+                expr(&s.condition, names);
+                assigned_vars(&s.else_branch, names);
+            }
+            Stmt::Defer(s) => assigned_vars(&s.body, names),
+            Stmt::Match(s) => {
+                s.arms.iter().for_each(|arm| assigned_vars(&arm.body, names));
+                s.else_branch.iter().for_each(|b| assigned_vars(b, names));
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+        }
+    }
+}
+
+/// a checked `ref T` goes anywhere a `ref? T` is expected
+fn widens_to_nullable(expected: &Type, actual: &Type) -> bool {
+    matches!((expected, actual), (Type::Pointer(e), Type::Pointer(a)) if e.nullable && !a.nullable && e.pointee == a.pointee)
+}
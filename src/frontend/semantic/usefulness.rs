@@ -0,0 +1,226 @@
+use crate::core::types::composite::EnumType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+
+/// the head of a pattern, what it tests b4 its fields
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ctor {
+    /// `_`, a binding or an `else`, matches anything
+    Wild,
+    /// index in2 the enum's variants
+    Variant(usize),
+    /// ints, both ends inclusive. i128 so ulong fits
+    Range(i128, i128),
+    Bool(bool),
+    /// strings, chars + floats, only ever equal 2 the same text
+    Opaque(String),
+}
+
+/// a pattern as the matrix sees it, a ctor + 1 pattern per field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pat {
+    pub ctor: Ctor,
+    pub fields: Vec<Pat>,
+}
+
+impl Pat {
+    pub fn wild() -> Self {
+        Self { ctor: Ctor::Wild, fields: Vec::new() }
+    }
+
+    pub fn new(ctor: Ctor, arity: usize) -> Self {
+        Self { ctor, fields: vec![Pat::wild(); arity] }
+    }
+}
+
+/// the values a column can hold
+#[derive(Debug, Clone, Copy)]
+pub enum Domain<'a> {
+    Enum(&'a EnumType),
+    Int(i128, i128),
+    Bool,
+    /// 2 many values 2 list, only `_` covers them all
+    Open,
+}
+
+impl<'a> Domain<'a> {
+    pub fn of(ty: &'a Type) -> Self {
+        match ty {
+            Type::Enum(e) => Domain::Enum(e),
+            Type::Primitive(PrimitiveType::Bool) => Domain::Bool,
+            _ => match integer_bounds(ty) {
+                Some((min, max)) => Domain::Int(min, max),
+                None => Domain::Open,
+            },
+        }
+    }
+
+    fn fields(&self, ctor: &Ctor) -> Vec<Domain<'a>> {
+        match (self, ctor) {
+            (Domain::Enum(e), Ctor::Variant(i)) => e.variants[*i].fields.iter().map(Domain::of).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn arity(&self, ctor: &Ctor) -> usize {
+        match (self, ctor) {
+            (Domain::Enum(e), Ctor::Variant(i)) => e.variants[*i].fields.len(),
+            _ => 0,
+        }
+    }
+
+    /// evry ctor of the domain, int ranges cut where `used` starts + ends so each piece is
+    /// either inside or outside of evry used range. None when the ctors cant be listed
+    fn signature(&self, used: &[&Ctor]) -> Option<Vec<Ctor>> {
+        match self {
+            Domain::Enum(e) => Some((0..e.variants.len()).map(Ctor::Variant).collect()),
+            Domain::Bool => Some(vec![Ctor::Bool(false), Ctor::Bool(true)]),
+            Domain::Int(min, max) => Some(split_range(*min, *max, used)),
+            Domain::Open => None,
+        }
+    }
+
+    /// `pat` as it would be written in a `when`
+    pub fn show(&self, pat: &Pat) -> String {
+        match &pat.ctor {
+            Ctor::Wild => "_".to_string(),
+            Ctor::Variant(i) => {
+                let Domain::Enum(e) = self else { return "_".to_string() };
+                let variant = &e.variants[*i];
+                if pat.fields.is_empty() {
+                    return variant.name.clone();
+                }
+                let domains = self.fields(&pat.ctor);
+                let fields: Vec<String> = pat.fields.iter().zip(&domains).map(|(f, d)| d.show(f)).collect();
+                format!("{}({})", variant.name, fields.join(", "))
+            }
+            Ctor::Range(lo, hi) if lo == hi => lo.to_string(),
+            Ctor::Range(lo, hi) => format!("{} .. {}", lo, hi),
+            Ctor::Bool(b) => b.to_string(),
+            Ctor::Opaque(text) => text.clone(),
+        }
+    }
+}
+
+/// min + max of an integer type
+pub fn integer_bounds(ty: &Type) -> Option<(i128, i128)> {
+    let Type::Primitive(p) = ty else { return None };
+    if !p.is_integer() {
+        return None;
+    }
+    let bits = p.size_in_bytes() as u32 * 8;
+    Some(if p.is_signed() { (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1) } else { (0, (1i128 << bits) - 1) })
+}
+
+/// Maranget's usefulness ("Warnings for pattern matching", JFP 2007) w/ witnesses: evry row of
+/// values `v` matches + no row of `rows` does, written as patterns. empty means `v` is useless,
+/// ie an arm w/ it is unreachable, + `v = [_]` lists what a match is missing
+pub fn witnesses(rows: &[Vec<Pat>], v: &[Pat], domains: &[Domain]) -> Vec<Vec<Pat>> {
+    let Some((head, rest)) = v.split_first() else {
+        return if rows.is_empty() { vec![Vec::new()] } else { Vec::new() };
+    };
+    let domain = domains[0];
+    let used: Vec<&Ctor> = rows.iter().map(|row| &row[0].ctor).filter(|c| **c != Ctor::Wild).collect();
+
+    let ctors = match &head.ctor {
+        // a range is useful if any piece of it is
+        Ctor::Range(lo, hi) => split_range(*lo, *hi, &used),
+        Ctor::Wild => match domain.signature(&used) {
+            Some(all) => {
+                let missing: Vec<Ctor> = all.iter().filter(|c| !used.iter().any(|u| contains(u, c))).cloned().collect();
+                if !missing.is_empty() {
+                    return default_witnesses(rows, rest, domains, &used, missing);
+                }
+                all
+            }
+            None => return default_witnesses(rows, rest, domains, &used, Vec::new()),
+        },
+        other => vec![other.clone()],
+    };
+
+    let mut found = Vec::new();
+    for ctor in ctors {
+        let arity = domain.arity(&ctor);
+        let specialized: Vec<Vec<Pat>> = rows.iter().filter_map(|row| specialize(row, &ctor, arity)).collect();
+        let Some(v) = specialize(v, &ctor, arity) else { continue };
+        let field_domains: Vec<Domain> = domain.fields(&ctor).into_iter().chain(domains[1..].iter().copied()).collect();
+        for mut w in witnesses(&specialized, &v, &field_domains) {
+            let fields = w.drain(..arity).collect();
+            w.insert(0, Pat { ctor: ctor.clone(), fields });
+            found.push(w);
+        }
+    }
+    found
+}
+
+/// `v` starts w/ `_` + some ctors r missing frm the column, only the rows starting w/ `_` can
+/// match those so the rest of `v` is checked against them alone
+fn default_witnesses(rows: &[Vec<Pat>], rest: &[Pat], domains: &[Domain], used: &[&Ctor], missing: Vec<Ctor>) -> Vec<Vec<Pat>> {
+    let defaults: Vec<Vec<Pat>> = rows.iter().filter(|row| row[0].ctor == Ctor::Wild).map(|row| row[1..].to_vec()).collect();
+    let tails = witnesses(&defaults, rest, &domains[1..]);
+    // nothing in the column yet, `_` says it better than listing evry ctor
+    let heads: Vec<Pat> = if used.is_empty() || missing.is_empty() {
+        vec![Pat::wild()]
+    } else {
+        merge_ranges(missing).into_iter().map(|c| Pat::new(c.clone(), domains[0].arity(&c))).collect()
+    };
+    tails.iter().flat_map(|tail| heads.iter().map(move |head| std::iter::once(head.clone()).chain(tail.iter().cloned()).collect())).collect()
+}
+
+/// the row w/ its head replaced by its fields, None if its head cant match `ctor`
+fn specialize(row: &[Pat], ctor: &Ctor, arity: usize) -> Option<Vec<Pat>> {
+    let head = &row[0];
+    let fields = match &head.ctor {
+        Ctor::Wild => vec![Pat::wild(); arity],
+        c if contains(c, ctor) => head.fields.clone(),
+        _ => return None,
+    };
+    Some(fields.into_iter().chain(row[1..].iter().cloned()).collect())
+}
+
+/// true if evry value `inner` matches `outer` matches 2
+fn contains(outer: &Ctor, inner: &Ctor) -> bool {
+    match (outer, inner) {
+        (Ctor::Wild, _) => true,
+        (Ctor::Range(a, b), Ctor::Range(lo, hi)) => a <= lo && hi <= b,
+        _ => outer == inner,
+    }
+}
+
+/// `lo ..= hi` cut at evry start + end of the ranges in `used`
+fn split_range(lo: i128, hi: i128, used: &[&Ctor]) -> Vec<Ctor> {
+    let mut cuts: Vec<i128> = used
+        .iter()
+        .filter_map(|c| match c {
+            Ctor::Range(a, b) => Some([*a, b + 1]),
+            _ => None,
+        })
+        .flatten()
+        .filter(|cut| lo < *cut && *cut <= hi)
+        .collect();
+    cuts.sort();
+    cuts.dedup();
+    let mut pieces = Vec::new();
+    let mut start = lo;
+    for cut in cuts {
+        pieces.push(Ctor::Range(start, cut - 1));
+        start = cut;
+    }
+    pieces.push(Ctor::Range(start, hi));
+    pieces
+}
+
+/// adjacent pieces back in2 1 range, so whats missing reads like `10 .. 255`
+fn merge_ranges(ctors: Vec<Ctor>) -> Vec<Ctor> {
+    let mut merged: Vec<Ctor> = Vec::new();
+    for ctor in ctors {
+        if let (Some(Ctor::Range(_, end)), Ctor::Range(lo, hi)) = (merged.last_mut(), &ctor) {
+            if *end + 1 == *lo {
+                *end = *hi;
+                continue;
+            }
+        }
+        merged.push(ctor);
+    }
+    merged
+}
@@ -1,9 +1,16 @@
+// `llvm_sys` is whichever llvm-sys the llvm* feature selected, see backend/llvm/ffi_compat.rs
+#[cfg(feature = "llvm21")]
+extern crate llvm_sys_211 as llvm_sys;
+#[cfg(all(feature = "llvm18", not(feature = "llvm21")))]
+extern crate llvm_sys_181 as llvm_sys;
+
 pub mod core;
 pub mod error;
 pub mod frontend;
 pub mod middle;
 pub mod backend;
 pub mod cli;
+pub mod playground;
 
 #[cfg(test)]
 mod tests;
@@ -2,26 +2,116 @@ use crate::core::ast::*;
 use crate::core::hir::*;
 use crate::core::hir::expr::Capture;
 use crate::core::hir::symbol::HirSymbol;
+use crate::core::mir::{specialized_name, SPECIALIZATION_MARKER};
+use crate::core::target::TargetInfo;
+use crate::core::types::composite::{spelling, EnumType};
 use crate::core::types::resolver::resolve_ast_type;
 use crate::core::types::ty::Type as ResolvedType;
+use crate::core::stack;
+use crate::frontend::semantic::assoc_consts::AssocConsts;
+use crate::frontend::semantic::comptime::{comptime_branch, is_target_access, target_constant, ComptimeEvaluator, ComptimeValue};
+use crate::frontend::semantic::ownership::owned_results;
 use crate::frontend::semantic::symbol_table::SymbolTable;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+// stop specializing here, recursion on a comptime param w/o an `if comptime` base case never ends
+const MAX_COMPTIME_SPECIALIZATIONS: usize = 256;
 
 pub struct HirLowerer {
     symbol_table: SymbolTable,
+    target: TargetInfo,
+    /// fns w/ comptime params, only lowered as specializations
+    comptime_fns: HashMap<String, Function>,
+    /// (fn, comptime arg values) -> specialized name
+    specializations: HashMap<(String, Vec<String>), String>,
+    /// specializations still 2 lower (name, fn, comptime param values)
+    pending_specializations: Vec<(String, Function, HashMap<String, ComptimeValue>)>,
+    /// comptime params of the specialization being lowered
+    comptime_bindings: HashMap<String, ComptimeValue>,
+    /// declared types of those params
+    comptime_types: HashMap<String, ResolvedType>,
+    /// (trait, method) -> ret type, 4 calls on `dyn Trait`
+    trait_returns: HashMap<(String, String), ResolvedType>,
+    /// (type, method) -> ret type of the impl method, 4 calls on a struct
+    impl_returns: HashMap<(String, String), ResolvedType>,
+    /// ret type of the fn being lowered, record literals in a `return` take it
+    return_type: Option<ResolvedType>,
+    /// trait + impl consts, `Type::NAME` folds 2 a literal
+    assoc_consts: AssocConsts,
+    /// fns w/ a generic param bound by a trait w/ consts, lowered once per concrete type
+    bound_generic_fns: HashMap<String, Function>,
+    /// specialization name -> generic param -> concrete type
+    type_specializations: HashMap<String, HashMap<String, String>>,
+    /// generic params of the specialization being lowered, `T::MAX` reads the concrete type's const
+    type_bindings: HashMap<String, String>,
+    /// fns whose result the caller owns, see ownership::owned_results
+    owned_results: HashSet<String>,
 }
 
 impl HirLowerer {
     pub fn new(symbol_table: SymbolTable) -> Self {
-        Self { symbol_table }
+        Self {
+            symbol_table,
+            target: TargetInfo::host(),
+            comptime_fns: HashMap::new(),
+            specializations: HashMap::new(),
+            pending_specializations: Vec::new(),
+            comptime_bindings: HashMap::new(),
+            comptime_types: HashMap::new(),
+            trait_returns: HashMap::new(),
+            impl_returns: HashMap::new(),
+            return_type: None,
+            assoc_consts: AssocConsts::default(),
+            bound_generic_fns: HashMap::new(),
+            type_specializations: HashMap::new(),
+            type_bindings: HashMap::new(),
+            owned_results: HashSet::new(),
+        }
+    }
+
+    /// target whose `target.*` constants get substituted
+    pub fn with_target(mut self, target: TargetInfo) -> Self {
+        self.target = target;
+        self
     }
 
     pub fn lower(&mut self, ast: &Ast) -> Hir {
-        let items: Vec<_> = ast
+        self.assoc_consts = AssocConsts::collect(&ast.items);
+        self.owned_results = owned_results(&ast.items);
+        for item in &ast.items {
+            match item {
+                Item::Function(f) if f.params.iter().any(|p| p.comptime) => {
+                    self.comptime_fns.insert(f.name.clone(), f.clone());
+                }
+                Item::Function(f) if f.generics.iter().any(|g| g.constraint.as_ref().is_some_and(|t| self.assoc_consts.declares_consts(t))) => {
+                    self.bound_generic_fns.insert(f.name.clone(), f.clone());
+                }
+                Item::Trait(t) => {
+                    for m in &t.methods {
+                        if let Some(ret) = &m.return_type {
+                            self.trait_returns.insert((t.name.clone(), m.name.clone()), resolve_ast_type(ret));
+                        }
+                    }
+                }
+                Item::TraitImpl(ti) => {
+                    for m in &ti.methods {
+                        if let Some(ret) = &m.return_type {
+                            self.impl_returns.insert((ti.type_name.clone(), m.name.clone()), resolve_ast_type(ret));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut items: Vec<_> = ast
             .items
             .iter()
             .filter_map(|item| self.lower_item(item))
             .collect();
+        // one copy per distinct set of comptime args, lowering one can queue more
+        while let Some((name, f, bindings)) = self.pending_specializations.pop() {
+            items.push(HirItem::Function(self.lower_specialization(&name, &f, bindings)));
+        }
 
         Hir {
             items,
@@ -31,8 +121,11 @@ impl HirLowerer {
 
     fn lower_item(&mut self, item: &Item) -> Option<HirItem> {
         match item {
+            Item::Function(f) if self.comptime_fns.contains_key(&f.name) || self.bound_generic_fns.contains_key(&f.name) => None,
             Item::Function(f) => Some(HirItem::Function(self.lower_function(f))),
             Item::Struct(s) => Some(HirItem::Struct(self.lower_struct(s))),
+            // variants become `Type::Enum` at each use, nothing 2 emit 4 the decl
+            Item::Enum(_) => None,
             Item::Trait(t) => Some(HirItem::Trait(self.lower_trait(t))),
             Item::TraitImpl(ti) => Some(HirItem::TraitImpl(self.lower_trait_impl(ti))),
             Item::Module(m) => Some(HirItem::Module(self.lower_module(m))),
@@ -41,6 +134,8 @@ impl HirLowerer {
                 path: r.path.clone(),
                 span: r.span,
             })),
+            // an imported module is its own unit, calls into it name its qualified symbols
+            Item::Import(_) => None,
             Item::Use(u) => Some(HirItem::Use(HirUse {
                 path: u.path.clone(),
                 span: u.span,
@@ -55,29 +150,310 @@ impl HirLowerer {
     }
 
     fn lower_function(&mut self, f: &Function) -> HirFunction {
+        // fn scope so params + lets carry their types into body exprs
+        self.symbol_table.enter_scope();
+        let params: Vec<HirParam> = f
+            .params
+            .iter()
+            .map(|p| HirParam {
+                name: p.name.clone(),
+                type_: self.complete_struct_type(resolve_ast_type(&p.type_)),
+                span: p.span,
+            })
+            .collect();
+        for param in &params {
+            self.define_local(&param.name, param.type_.clone(), false, param.span);
+        }
+        let return_type = f.return_type.as_ref().map(|t| self.complete_struct_type(resolve_ast_type(t)));
+        self.return_type = return_type.clone();
+        let body = f.body.as_ref().map(|b| {
+            b.iter()
+                .filter_map(|s| self.lower_stmt(s))
+                .collect()
+        });
+        self.symbol_table.exit_scope();
+
         HirFunction {
             name: f.name.clone(),
             generics: f.generics.iter().map(|g| g.name.clone()).collect(),
-            params: f
-                .params
+            params,
+            return_type,
+            body,
+            uses: f.uses.clone(),
+            target_features: f.target_features.clone(),
+            // a specialization hands over what its generic fn does
+            owned_result: self.owned_results.contains(f.name.split(SPECIALIZATION_MARKER).next().unwrap_or(&f.name)),
+            span: f.span,
+        }
+    }
+
+    /// `f` w/ its comptime params bound 2 `bindings` and dropped frm the signature
+    fn lower_specialization(&mut self, name: &str, f: &Function, bindings: HashMap<String, ComptimeValue>) -> HirFunction {
+        let mut specialized = f.clone();
+        specialized.name = name.to_string();
+        specialized.params.retain(|p| !p.comptime);
+        let types = f.params.iter()
+            .filter(|p| p.comptime)
+            .map(|p| (p.name.clone(), resolve_ast_type(&p.type_)))
+            .collect();
+        let type_bindings = self.type_specializations.get(name).cloned().unwrap_or_default();
+        let outer_bindings = std::mem::replace(&mut self.comptime_bindings, bindings);
+        let outer_types = std::mem::replace(&mut self.comptime_types, types);
+        let outer_type_bindings = std::mem::replace(&mut self.type_bindings, type_bindings);
+        let function = self.lower_function(&specialized);
+        self.comptime_bindings = outer_bindings;
+        self.comptime_types = outer_types;
+        self.type_bindings = outer_type_bindings;
+        function
+    }
+
+    /// call 2 a fn w/ const-bearing trait bounds - call its copy 4 the arg types
+    /// each generic is inferred frm the first param declared w/ that type
+    fn lower_bound_generic_call(&mut self, c: &CallExpr, callee: &VariableExpr) -> Option<HirExpr> {
+        let f = self.bound_generic_fns.get(&callee.name)?.clone();
+        let args: Vec<HirExpr> = c.args.iter().map(|a| self.lower_expr(a)).collect();
+        let mut bindings = HashMap::new();
+        let mut concrete = Vec::new();
+        for g in &f.generics {
+            let (_, arg) = f.params.iter().zip(&args).find(|(p, _)| matches!(&p.type_, Type::Named(n) if n.name == g.name))?;
+            let type_name = match arg.type_() {
+                ResolvedType::Struct(s) => s.name.clone(),
+                ResolvedType::Enum(e) => e.name.clone(),
+                _ => return None,
+            };
+            bindings.insert(g.name.clone(), type_name.clone());
+            concrete.push(type_name);
+        }
+
+        let key = (f.name.clone(), concrete.clone());
+        let name = match self.specializations.get(&key) {
+            Some(name) => name.clone(),
+            None => {
+                let name = specialized_name(&f.name, &concrete);
+                let mut specialized = f.clone();
+                specialized.generics.clear();
+                for p in &mut specialized.params {
+                    substitute_named(&mut p.type_, &bindings);
+                }
+                if let Some(ret) = &mut specialized.return_type {
+                    substitute_named(ret, &bindings);
+                }
+                self.type_specializations.insert(name.clone(), bindings);
+                self.specializations.insert(key, name.clone());
+                self.pending_specializations.push((name.clone(), specialized, HashMap::new()));
+                name
+            }
+        };
+
+        let function_type = ResolvedType::Function(crate::core::types::composite::FunctionType {
+            params: args.iter().map(|a| a.type_().clone()).collect(),
+            return_type: Box::new(f.return_type.as_ref().map(resolve_ast_type).unwrap_or(
+                ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+            )),
+        });
+        let return_type = match &function_type {
+            ResolvedType::Function(ft) => self.complete_struct_type(*ft.return_type.clone()),
+            _ => unreachable!(),
+        };
+        Some(HirExpr::Call(HirCallExpr {
+            callee: Box::new(HirExpr::Variable(HirVariableExpr {
+                name: name.clone(),
+                symbol: HirSymbol::new(name, function_type.clone(), false, 0, callee.span),
+                type_: function_type,
+                span: callee.span,
+            })),
+            args,
+            type_: return_type,
+            span: c.span,
+        }))
+    }
+
+    /// `if comptime` whose cond folded - only the taken branch is lowered, under an always true cond
+    fn lower_comptime_if(&mut self, s: &IfStmt, taken: bool) -> Option<HirStmt> {
+        let live = if taken { Some(&s.then_branch) } else { s.else_branch.as_ref() };
+        Some(HirStmt::If(HirIfStmt {
+            condition: value_literal(&ComptimeValue::Bool(true), None, s.span)?,
+            then_branch: live.into_iter().flatten().filter_map(|st| self.lower_stmt(st)).collect(),
+            else_branch: None,
+            span: s.span,
+        }))
+    }
+
+    /// `Type::NAME` or `T::NAME` in a specialization, folded 2 the const's value
+    fn lower_assoc_const(&self, m: &ModuleAccessExpr) -> Option<HirExpr> {
+        let type_name = self.type_bindings.get(&m.module).unwrap_or(&m.module);
+        let (decl, value) = self.assoc_consts.lookup(type_name, &m.member)?;
+        // the trait checker reported values that dont fold
+        let mut scratch = crate::error::Reporter::new();
+        let file_id = scratch.add_file(String::new(), String::new());
+        let value = ComptimeEvaluator::new(&mut scratch, file_id).with_target(self.target.clone()).evaluate(value)?;
+        value_literal(&value, Some(resolve_ast_type(&decl.type_)), m.span)
+    }
+
+    /// call 2 a fn w/ comptime params - call the specialization 4 these arg values
+    /// none if an arg doesnt fold, the type checker alrdy reported it
+    fn lower_comptime_call(&mut self, c: &CallExpr, callee: &VariableExpr) -> Option<HirExpr> {
+        let f = self.comptime_fns.get(&callee.name)?.clone();
+        // a local shadowing the fn isnt the comptime fn
+        let kind = self.symbol_table.resolve(&callee.name).map(|s| &s.kind);
+        if !matches!(kind, Some(crate::frontend::semantic::symbol_table::SymbolKind::Function { .. })) {
+            return None;
+        }
+        let mut bindings = HashMap::new();
+        let mut runtime_args = Vec::new();
+        for (param, arg) in f.params.iter().zip(&c.args) {
+            if !param.comptime {
+                runtime_args.push(self.lower_expr(arg));
+                continue;
+            }
+            // errors were reported by the type checker, the scratch reporter only satisfies the evaluator
+            let mut scratch = crate::error::Reporter::new();
+            let file_id = scratch.add_file(String::new(), String::new());
+            let value = ComptimeEvaluator::new(&mut scratch, file_id)
+                .with_target(self.target.clone())
+                .with_bindings(self.comptime_bindings.clone())
+                .evaluate(arg)?;
+            bindings.insert(param.name.clone(), value);
+        }
+
+        // key in param order so f(1, 2) and f(2, 1) differ
+        let key_values = f.params.iter()
+            .filter_map(|p| bindings.get(&p.name).map(|v| format!("{:?}", v)))
+            .collect();
+        let key = (f.name.clone(), key_values);
+        let name = match self.specializations.get(&key) {
+            Some(name) => name.clone(),
+            None if self.specializations.len() >= MAX_COMPTIME_SPECIALIZATIONS => return None,
+            None => {
+                let name = specialized_name(&f.name, &[format!("ct{}", self.specializations.len())]);
+                self.specializations.insert(key, name.clone());
+                self.pending_specializations.push((name.clone(), f.clone(), bindings));
+                name
+            }
+        };
+
+        let function_type = ResolvedType::Function(crate::core::types::composite::FunctionType {
+            params: f.params.iter().filter(|p| !p.comptime).map(|p| resolve_ast_type(&p.type_)).collect(),
+            return_type: Box::new(f.return_type.as_ref().map(resolve_ast_type).unwrap_or(
+                ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+            )),
+        });
+        let return_type = match &function_type {
+            ResolvedType::Function(ft) => self.complete_struct_type(*ft.return_type.clone()),
+            _ => unreachable!(),
+        };
+        Some(HirExpr::Call(HirCallExpr {
+            callee: Box::new(HirExpr::Variable(HirVariableExpr {
+                name: name.clone(),
+                symbol: HirSymbol::new(name, function_type.clone(), false, 0, callee.span),
+                type_: function_type,
+                span: callee.span,
+            })),
+            args: runtime_args,
+            type_: return_type,
+            span: c.span,
+        }))
+    }
+
+    /// literal 4 a comptime param inside its specialization
+    fn comptime_literal(&self, name: &str, span: codespan::Span) -> Option<HirExpr> {
+        let value = self.comptime_bindings.get(name)?;
+        // the declared param type, so `comptime n : ulong` stays a ulong
+        value_literal(value, self.comptime_types.get(name).cloned(), span)
+    }
+
+    /// struct type w/ its declared fields, annotations only carry the name
+    fn struct_type(&self, name: &str) -> Option<ResolvedType> {
+        let fields = match &self.symbol_table.resolve(name)?.kind {
+            crate::frontend::semantic::symbol_table::SymbolKind::Struct { fields } => fields,
+            _ => return None,
+        };
+        Some(ResolvedType::Struct(crate::core::types::composite::StructType {
+            name: name.to_string(),
+            fields: fields.iter().map(|(name, type_)| {
+                crate::core::types::composite::Field {
+                    name: name.clone(),
+                    type_: type_.clone(),
+                    offset: None,
+                }
+            }).collect(),
+            size: None,
+            align: None,
+        }))
+    }
+
+    /// enum type w/ its variants, annotations + construction only carry the name
+    fn enum_type(&self, name: &str) -> Option<crate::core::types::composite::EnumType> {
+        let variants = match &self.symbol_table.resolve(name)?.kind {
+            crate::frontend::semantic::symbol_table::SymbolKind::Enum { variants } => variants,
+            _ => return None,
+        };
+        Some(crate::core::types::composite::EnumType {
+            name: name.to_string(),
+            variants: variants
                 .iter()
-                .map(|p| HirParam {
-                    name: p.name.clone(),
-                    type_: resolve_ast_type(&p.type_),
-                    span: p.span,
+                .map(|(name, fields)| crate::core::types::composite::Variant {
+                    name: name.clone(),
+                    fields: fields.iter().map(|f| self.complete_struct_type(f.clone())).collect(),
                 })
                 .collect(),
-            return_type: f.return_type.as_ref().map(|t| resolve_ast_type(t)),
-            body: f.body.as_ref().map(|b| {
-                b.iter()
-                    .filter_map(|s| self.lower_stmt(s))
-                    .collect()
-            }),
-            uses: f.uses.clone(),
-            span: f.span,
+        })
+    }
+
+    /// fill in fields of a struct (or ptr 2 struct) type so mir can index them
+    /// and the backend can lay out nested structs, self refs stay name only
+    fn complete_struct_type(&self, type_: ResolvedType) -> ResolvedType {
+        self.complete_struct_type_in(type_, &mut Vec::new())
+    }
+
+    fn complete_struct_type_in(&self, type_: ResolvedType, visiting: &mut Vec<String>) -> ResolvedType {
+        match type_ {
+            ResolvedType::Struct(s) if visiting.contains(&s.name) => ResolvedType::Struct(s),
+            ResolvedType::Struct(s) if s.fields.is_empty() && self.enum_type(&s.name).is_some() => {
+                ResolvedType::Enum(self.enum_type(&s.name).unwrap())
+            }
+            ResolvedType::Struct(s) => {
+                let mut completed = match self.struct_type(&s.name) {
+                    Some(ResolvedType::Struct(completed)) if s.fields.is_empty() => completed,
+                    _ => s,
+                };
+                visiting.push(completed.name.clone());
+                for field in &mut completed.fields {
+                    field.type_ = self.complete_struct_type_in(field.type_.clone(), visiting);
+                }
+                visiting.pop();
+                ResolvedType::Struct(completed)
+            }
+            ResolvedType::Pointer(mut p) => {
+                p.pointee = Box::new(self.complete_struct_type_in(*p.pointee, visiting));
+                ResolvedType::Pointer(p)
+            }
+            ResolvedType::Array(mut a) => {
+                a.element = Box::new(self.complete_struct_type_in(*a.element, visiting));
+                ResolvedType::Array(a)
+            }
+            // a `value or error` is built frm the annotation, its payload structs still need their fields
+            ResolvedType::Enum(mut e) => {
+                for variant in &mut e.variants {
+                    variant.fields = variant.fields.drain(..).map(|f| self.complete_struct_type_in(f, visiting)).collect();
+                }
+                ResolvedType::Enum(e)
+            }
+            other => other,
         }
     }
 
+    fn define_local(&mut self, name: &str, type_: ResolvedType, mutable: bool, span: codespan::Span) {
+        let symbol = crate::frontend::semantic::symbol_table::Symbol {
+            name: name.to_string(),
+            kind: crate::frontend::semantic::symbol_table::SymbolKind::Variable { mutable, type_ },
+            span,
+            defined: true,
+        };
+        // redefinition in same scope keeps the first type
+        let _ = self.symbol_table.define(name.to_string(), symbol);
+    }
+
     fn lower_struct(&mut self, s: &Struct) -> HirStruct {
         HirStruct {
             name: s.name.clone(),
@@ -166,6 +542,7 @@ impl HirLowerer {
                         })
                         .collect(),
                     return_type: ff.return_type.as_ref().map(|t| resolve_ast_type(t)),
+                    variadic: ff.variadic,
                     abi: ff.abi.clone(),
                     span: ff.span,
                 })
@@ -185,6 +562,10 @@ impl HirLowerer {
     }
 
     fn lower_stmt(&mut self, stmt: &Stmt) -> Option<HirStmt> {
+        stack::grow(|| self.lower_stmt_inner(stmt))
+    }
+
+    fn lower_stmt_inner(&mut self, stmt: &Stmt) -> Option<HirStmt> {
         match stmt {
             Stmt::Expr(s) => Some(HirStmt::Expr(HirExprStmt {
                 expr: self.lower_expr(&s.expr),
@@ -216,37 +597,61 @@ impl HirLowerer {
                 } else {
                     inferred_type
                 };
+                let final_type = self.complete_struct_type(final_type);
                 
+                let mut value = s.value.as_ref().map(|e| retype_record(self.lower_expr(e), &final_type));
+                // a let shadowing a comptime param is a runtime value from here on
+                self.comptime_bindings.remove(&s.name);
+                self.define_local(&s.name, final_type.clone(), s.mutable, s.span);
+                // repeat initializers take the declared array type so `byte[N] = [0; N]` fills bytes
+                if let Some(HirExpr::ArrayLiteral(a)) = &mut value {
+                    if a.repeat.is_some() && matches!(final_type, ResolvedType::Array(_)) {
+                        a.type_ = final_type.clone();
+                    }
+                }
+
                 Some(HirStmt::Let(HirLetStmt {
                     name: s.name.clone(),
                     mutable: s.mutable,
                     type_: final_type,
-                    value: s.value.as_ref().map(|e| self.lower_expr(e)),
+                    value,
                     span: s.span,
                 }))
             }
             Stmt::Return(s) => Some(HirStmt::Return(HirReturnStmt {
-                value: s.value.as_ref().map(|e| self.lower_expr(e)),
-                span: s.span,
-            })),
-            Stmt::If(s) => Some(HirStmt::If(HirIfStmt {
-                condition: self.lower_expr(&s.condition),
-                then_branch: s
-                    .then_branch
-                    .iter()
-                    .filter_map(|st| self.lower_stmt(st))
-                    .collect(),
-                else_branch: s
-                    .else_branch
-                    .as_ref()
-                    .map(|stmts| {
-                        stmts
-                            .iter()
-                            .filter_map(|st| self.lower_stmt(st))
-                            .collect()
-                    }),
+                value: s.value.as_ref().map(|e| {
+                    let value = self.lower_expr(e);
+                    match &self.return_type {
+                        Some(ResolvedType::Enum(ret)) if ret.fallible_parts().is_some() => wrap_fallible(value, ret),
+                        Some(ret) => retype_record(value, ret),
+                        None => value,
+                    }
+                }),
                 span: s.span,
             })),
+            Stmt::If(s) => {
+                if let Some(taken) = comptime_branch(&s.condition, &self.target, &self.comptime_bindings) {
+                    return self.lower_comptime_if(s, taken);
+                }
+                Some(HirStmt::If(HirIfStmt {
+                    condition: self.lower_expr(&s.condition),
+                    then_branch: s
+                        .then_branch
+                        .iter()
+                        .filter_map(|st| self.lower_stmt(st))
+                        .collect(),
+                    else_branch: s
+                        .else_branch
+                        .as_ref()
+                        .map(|stmts| {
+                            stmts
+                                .iter()
+                                .filter_map(|st| self.lower_stmt(st))
+                                .collect()
+                        }),
+                    span: s.span,
+                }))
+            }
             Stmt::While(s) => Some(HirStmt::While(HirWhileStmt {
                 condition: self.lower_expr(&s.condition),
                 body: s
@@ -267,12 +672,53 @@ impl HirLowerer {
                     .collect(),
                 span: s.span,
             })),
+            Stmt::Guard(s) => Some(HirStmt::Guard(HirGuardStmt {
+                condition: self.lower_expr(&s.condition),
+                else_branch: s.else_branch.iter().filter_map(|st| self.lower_stmt(st)).collect(),
+                span: s.span,
+            })),
+            Stmt::Defer(s) => Some(HirStmt::Defer(HirDeferStmt {
+                body: s.body.iter().filter_map(|st| self.lower_stmt(st)).collect(),
+                span: s.span,
+            })),
+            Stmt::Match(s) => {
+                let scrutinee = self.lower_expr(&s.scrutinee);
+                let type_ = scrutinee.type_().clone();
+                let mut arms = Vec::new();
+                for arm in &s.arms {
+                    let patterns: Vec<HirPattern> = arm.patterns.iter().filter_map(|p| lower_pattern(p, &type_)).collect();
+                    self.symbol_table.enter_scope();
+                    for pattern in &patterns {
+                        if let HirPattern::Variant { bindings, .. } = pattern {
+                            for (_, name, binding_type) in bindings {
+                                self.define_local(name, binding_type.clone(), false, arm.span);
+                            }
+                        }
+                    }
+                    let body = arm.body.iter().filter_map(|st| self.lower_stmt(st)).collect();
+                    self.symbol_table.exit_scope();
+                    arms.push(HirMatchArm { patterns, body, span: arm.span });
+                }
+                Some(HirStmt::Match(HirMatchStmt {
+                    scrutinee,
+                    arms,
+                    else_branch: s
+                        .else_branch
+                        .as_ref()
+                        .map(|stmts| stmts.iter().filter_map(|st| self.lower_stmt(st)).collect()),
+                    span: s.span,
+                }))
+            }
             Stmt::Break(s) => Some(HirStmt::Break(HirBreakStmt { span: s.span })),
             Stmt::Continue(s) => Some(HirStmt::Continue(HirContinueStmt { span: s.span })),
         }
     }
 
     fn lower_expr(&mut self, expr: &Expr) -> HirExpr {
+        stack::grow(|| self.lower_expr_inner(expr))
+    }
+
+    fn lower_expr_inner(&mut self, expr: &Expr) -> HirExpr {
         match expr {
             Expr::Literal(l) => {
                 let type_ = match &l.kind {
@@ -363,6 +809,9 @@ impl HirLowerer {
                     span: u.span,
                 })
             }
+            Expr::Variable(v) if self.comptime_bindings.contains_key(&v.name) => {
+                self.comptime_literal(&v.name, v.span).unwrap_or(HirExpr::Null)
+            }
             Expr::Variable(v) => {
                 let semantic_symbol = self
                     .symbol_table
@@ -426,11 +875,47 @@ impl HirLowerer {
                 })
             }
             Expr::Call(c) => {
+                if let Expr::Variable(v) = &*c.callee {
+                    if let Some(call) = self.lower_comptime_call(c, v) {
+                        return call;
+                    }
+                    if let Some(call) = self.lower_bound_generic_call(c, v) {
+                        return call;
+                    }
+                }
+                if let Expr::ModuleAccess(m) = &*c.callee {
+                    if let Some(variant) = self.lower_variant(m, &c.args, c.span) {
+                        return variant;
+                    }
+                }
                 let callee = self.lower_expr(&c.callee);
-                let args: Vec<HirExpr> = c.args.iter().map(|e| self.lower_expr(e)).collect();
+                let params = match callee.type_() {
+                    ResolvedType::Function(f) => f.params.iter().map(|p| self.complete_struct_type(p.clone())).collect(),
+                    _ => Vec::new(),
+                };
+                let args: Vec<HirExpr> = c
+                    .args
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| {
+                        let arg = self.lower_expr(e);
+                        match params.get(i) {
+                            Some(param) => retype_record(arg, param),
+                            None => arg,
+                        }
+                    })
+                    .collect();
                 // get ret type from callee
                 let return_type = match callee.type_() {
-                    ResolvedType::Function(f) => *f.return_type.clone(),
+                    // the symbol's sig still has placeholder structs / enums
+                    ResolvedType::Function(f) => self.complete_struct_type(*f.return_type.clone()),
+                    // volatile_load(ptr) yields the pointee
+                    _ if matches!(&callee, HirExpr::Variable(v) if v.name == "volatile_load") => {
+                        match args.first().map(|a| a.type_()) {
+                            Some(ResolvedType::Pointer(p)) => *p.pointee.clone(),
+                            _ => ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+                        }
+                    }
                     _ => ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
                 };
                 HirExpr::Call(HirCallExpr {
@@ -444,7 +929,24 @@ impl HirLowerer {
                 let receiver = self.lower_expr(&m.receiver);
                 let args: Vec<HirExpr> = m.args.iter().map(|e| self.lower_expr(e)).collect();
                 // method ret type would come from trt rltn
-                let return_type = ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void);
+                // ptr.offset/ptr.add return the receiver ptr type
+                let return_type = match receiver.type_() {
+                    ResolvedType::Pointer(_) if m.method == "offset" || m.method == "add" => receiver.type_().clone(),
+                    ResolvedType::String if m.method == "len" => {
+                        ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Long)
+                    }
+                    ResolvedType::TraitObject(t) => {
+                        self.trait_returns.get(&(t.trait_name.clone(), m.method.clone())).cloned().unwrap_or(
+                            ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+                        )
+                    }
+                    ResolvedType::Struct(s) => {
+                        self.impl_returns.get(&(s.name.clone(), m.method.clone())).cloned().unwrap_or(
+                            ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+                        )
+                    }
+                    _ => ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+                };
                 HirExpr::MethodCall(HirMethodCallExpr {
                     receiver: Box::new(receiver),
                     method: m.method.clone(),
@@ -467,10 +969,34 @@ impl HirLowerer {
                     span: i.span,
                 })
             }
+            Expr::FieldAccess(f) if is_target_access(f) && self.symbol_table.resolve("target").is_none() => {
+                // target.* is a platform constant - substitute the literal
+                let (kind, type_) = match target_constant(&self.target, &f.field) {
+                    Some(ComptimeValue::Int(n)) => (
+                        HirLiteralKind::Int(n),
+                        ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Int),
+                    ),
+                    Some(ComptimeValue::String(s)) => (HirLiteralKind::String(s), ResolvedType::String),
+                    // unknown field alrdy reported by type checker
+                    _ => return HirExpr::Null,
+                };
+                HirExpr::Literal(HirLiteralExpr { kind, type_, span: f.span })
+            }
             Expr::FieldAccess(f) => {
                 let object = self.lower_expr(&f.object);
-                // field type would come from strct definition
-                let field_type = ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void);
+                // field type comes frm the strct definition, ptrs auto deref
+                let struct_fields = match object.type_() {
+                    ResolvedType::Struct(s) => Some(&s.fields),
+                    ResolvedType::Pointer(p) => match &*p.pointee {
+                        ResolvedType::Struct(s) => Some(&s.fields),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let field_type = struct_fields
+                    .and_then(|fields| fields.iter().find(|field| field.name == f.field))
+                    .map(|field| self.complete_struct_type(field.type_.clone()))
+                    .unwrap_or(ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void));
                 HirExpr::FieldAccess(HirFieldAccessExpr {
                     object: Box::new(object),
                     field: f.field.clone(),
@@ -510,6 +1036,7 @@ impl HirLowerer {
                     span: i.span,
                 })
             }
+            Expr::Assignment(a) if matches!(a.target.as_ref(), Expr::Tuple(_)) => self.lower_destructure(a),
             Expr::Assignment(a) => {
                 let target = self.lower_expr(&a.target);
                 let value = self.lower_expr(&a.value);
@@ -546,6 +1073,25 @@ impl HirLowerer {
                     span: a.span,
                 })
             }
+            Expr::New(n) => {
+                let value = self.lower_expr(&n.value);
+                let type_ = ResolvedType::Pointer(crate::core::types::pointer::PointerType::new(value.type_().clone(), false));
+                HirExpr::New(HirNewExpr { value: Box::new(value), type_, span: n.span })
+            }
+            Expr::Delete(d) => HirExpr::Delete(HirDeleteExpr {
+                pointer: Box::new(self.lower_expr(&d.pointer)),
+                type_: ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void),
+                span: d.span,
+            }),
+            Expr::Try(t) => {
+                let expr = self.lower_expr(&t.expr);
+                let type_ = match expr.type_() {
+                    ResolvedType::Enum(e) => e.fallible_parts().map(|(value, _)| value.clone()),
+                    _ => None,
+                };
+                let type_ = type_.unwrap_or(ResolvedType::Error);
+                HirExpr::Try(HirTryExpr { expr: Box::new(expr), type_, span: t.span })
+            }
             Expr::Exists(e) => {
                 let expr = self.lower_expr(&e.expr);
                 HirExpr::Exists(HirExistsExpr {
@@ -598,38 +1144,85 @@ impl HirLowerer {
                 })
             }
             Expr::Null => HirExpr::Null,
+            // a tuple is an anonymous struct w/ fields "0", "1", ..
+            Expr::Tuple(t) => {
+                let fields: Vec<(String, HirExpr)> = t.elements.iter().enumerate().map(|(i, e)| (i.to_string(), self.lower_expr(e))).collect();
+                let tuple = crate::core::types::composite::StructType::tuple(fields.iter().map(|(_, e)| e.type_().clone()).collect());
+                HirExpr::StructLiteral(HirStructLiteralExpr {
+                    struct_name: tuple.name.clone(),
+                    fields,
+                    type_: ResolvedType::Struct(tuple),
+                    span: t.span,
+                })
+            }
+            Expr::StructLiteral(s) if s.is_record() => {
+                // sorted like the record type, repeated fields were reported by the checker
+                let mut fields: Vec<(String, HirExpr)> = s.fields.iter().map(|(n, e)| (n.clone(), self.lower_expr(e))).collect();
+                fields.sort_by(|a, b| a.0.cmp(&b.0));
+                fields.dedup_by(|a, b| a.0 == b.0);
+                let record = crate::core::types::composite::StructType::record(
+                    fields.iter().map(|(n, e)| (n.clone(), e.type_().clone())).collect(),
+                );
+                HirExpr::StructLiteral(HirStructLiteralExpr {
+                    struct_name: record.name.clone(),
+                    fields,
+                    type_: ResolvedType::Struct(record),
+                    span: s.span,
+                })
+            }
+            Expr::StructLiteral(s)
+                if matches!(
+                    self.symbol_table.resolve(&s.struct_name).map(|sym| &sym.kind),
+                    Some(crate::frontend::semantic::symbol_table::SymbolKind::Function { .. })
+                ) =>
+            {
+                self.lower_expr(&Expr::Call(s.as_record_call()))
+            }
             Expr::StructLiteral(s) => {
                 // struct literal: Circle { radius: 5.0 }
-                // lower field values
-                let mut field_values = Vec::new();
-                for (_field_name, value) in &s.fields {
-                    field_values.push(self.lower_expr(value));
-                }
-                // get struct type
-                let _struct_type = if let Some(symbol) = self.symbol_table.resolve(&s.struct_name) {
-                    if let crate::frontend::semantic::symbol_table::SymbolKind::Struct { fields } = &symbol.kind {
-                        ResolvedType::Struct(crate::core::types::composite::StructType {
-                            name: s.struct_name.clone(),
-                            fields: fields.iter().map(|(name, type_)| {
-                                crate::core::types::composite::Field {
-                                    name: name.clone(),
-                                    type_: type_.clone(),
-                                    offset: None,
-                                }
-                            }).collect(),
-                            size: None,
-                            align: None,
-                        })
-                    } else {
-                        ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                // fields r reordered 2 match the declaration so mir can index them
+                let declared = match self.symbol_table.resolve(&s.struct_name) {
+                    Some(symbol) => match &symbol.kind {
+                        crate::frontend::semantic::symbol_table::SymbolKind::Struct { fields } => fields.clone(),
+                        _ => return HirExpr::Null,
+                    },
+                    // undefined struct alrdy reported by type checker
+                    None => return HirExpr::Null,
+                };
+                let mut fields = Vec::with_capacity(declared.len());
+                for (name, _) in &declared {
+                    if let Some((_, value)) = s.fields.iter().find(|(field_name, _)| field_name == name) {
+                        fields.push((name.clone(), self.lower_expr(value)));
                     }
-                } else {
-                    ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void)
+                }
+                let struct_type = match self.struct_type(&s.struct_name) {
+                    Some(struct_type) => self.complete_struct_type(struct_type),
+                    None => return HirExpr::Null,
                 };
-                // 4 now return null - proper impl wld create struct instance
-                HirExpr::Null
+                HirExpr::StructLiteral(HirStructLiteralExpr {
+                    struct_name: s.struct_name.clone(),
+                    fields,
+                    type_: struct_type,
+                    span: s.span,
+                })
             }
             Expr::ModuleAccess(m) => {
+                if let Some(variant) = self.lower_variant(m, &[], m.span) {
+                    return variant;
+                }
+                if let Some(value) = self.lower_assoc_const(m) {
+                    return value;
+                }
+                // imported fns keep the qualified name, its their symbol in the module's object
+                let name = format!("{}::{}", m.module, m.member);
+                if let Some(symbol) = self.symbol_table.resolve(&name).cloned() {
+                    let mut expr = self.lower_expr(&Expr::Variable(VariableExpr { name, span: m.span }));
+                    // an aliased or re exported fn, the symbol has the name the defining module uses
+                    if let HirExpr::Variable(v) = &mut expr {
+                        v.name = symbol.name;
+                    }
+                    return expr;
+                }
                 // module access: Utils::helper
                 // 4 now treat as variable - proper impl wld resolve module members
                 HirExpr::Variable(HirVariableExpr {
@@ -656,10 +1249,11 @@ impl HirLowerer {
                 };
                 let array_type = ResolvedType::Array(crate::core::types::composite::ArrayType {
                     element: Box::new(element_type),
-                    size: elements.len(),
+                    size: a.repeat.unwrap_or(elements.len()),
                 });
                 HirExpr::ArrayLiteral(HirArrayLiteralExpr {
                     elements,
+                    repeat: a.repeat,
                     type_: array_type,
                     span: a.span,
                 })
@@ -667,6 +1261,41 @@ impl HirLowerer {
         }
     }
 
+    /// `a, b = pair` as `{ tmp = pair; a = tmp.0; b = tmp.1 }`, the whole value is read b4
+    /// any target is written so `a, b = b, a` swaps
+    fn lower_destructure(&mut self, a: &crate::core::ast::expr::AssignmentExpr) -> HirExpr {
+        let Expr::Tuple(targets) = a.target.as_ref() else { return HirExpr::Null };
+        let value = self.lower_expr(&a.value);
+        let type_ = value.type_().clone();
+        let ResolvedType::Struct(tuple) = &type_ else { return HirExpr::Null };
+        let name = format!("__tuple{}", a.span.start());
+        let tmp = HirExpr::Variable(HirVariableExpr {
+            name: name.clone(),
+            symbol: HirSymbol::new(name.clone(), type_.clone(), false, 0, a.span),
+            type_: type_.clone(),
+            span: a.span,
+        });
+        let mut stmts = vec![HirStmt::Let(HirLetStmt { name, mutable: false, type_: type_.clone(), value: Some(value), span: a.span })];
+        for (target, field) in targets.elements.iter().zip(&tuple.fields) {
+            let element = HirExpr::FieldAccess(HirFieldAccessExpr {
+                object: Box::new(tmp.clone()),
+                field: field.name.clone(),
+                type_: field.type_.clone(),
+                span: target.span(),
+            });
+            stmts.push(HirStmt::Expr(HirExprStmt {
+                expr: HirExpr::Assignment(HirAssignmentExpr {
+                    target: Box::new(self.lower_expr(target)),
+                    value: Box::new(element),
+                    type_: field.type_.clone(),
+                    span: target.span(),
+                }),
+                span: target.span(),
+            }));
+        }
+        HirExpr::Block(HirBlockExpr { stmts, expr: None, type_: ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void), span: a.span })
+    }
+
     /// analyze closure body 2 find cptrd variables
     /// returns a list of vrbls that r used in the clsr but not dfnd as parameters
     fn analyze_captures(&self, body: &[Stmt], param_names: &HashSet<String>) -> Vec<Capture> {
@@ -740,6 +1369,23 @@ impl HirLowerer {
                     self.collect_variables_in_stmt(stmt, used_vars, defined_vars);
                 }
             }
+            Stmt::Guard(s) => {
+                self.collect_variables_in_expr(&s.condition, used_vars, defined_vars);
+                for stmt in &s.else_branch {
+                    self.collect_variables_in_stmt(stmt, used_vars, defined_vars);
+                }
+            }
+            Stmt::Defer(s) => {
+                for stmt in &s.body {
+                    self.collect_variables_in_stmt(stmt, used_vars, defined_vars);
+                }
+            }
+            Stmt::Match(s) => {
+                self.collect_variables_in_expr(&s.scrutinee, used_vars, defined_vars);
+                for stmt in s.arms.iter().flat_map(|a| &a.body).chain(s.else_branch.iter().flatten()) {
+                    self.collect_variables_in_stmt(stmt, used_vars, defined_vars);
+                }
+            }
             Stmt::For(s) => {
                 if let Some(init) = &s.init {
                     self.collect_variables_in_stmt(init, used_vars, defined_vars);
@@ -818,6 +1464,12 @@ impl HirLowerer {
             Expr::Exists(e) => {
                 self.collect_variables_in_expr(&e.expr, used_vars, _defined_vars);
             }
+            Expr::New(n) => {
+                self.collect_variables_in_expr(&n.value, used_vars, _defined_vars);
+            }
+            Expr::Delete(d) => {
+                self.collect_variables_in_expr(&d.pointer, used_vars, _defined_vars);
+            }
             Expr::Closure(c) => {
                 for stmt in &c.body {
                     self.collect_variables_in_stmt(stmt, used_vars, _defined_vars);
@@ -830,6 +1482,19 @@ impl HirLowerer {
         }
     }
 
+    /// `Enum::Variant(args)`, None if `m` doesnt name a variant
+    fn lower_variant(&mut self, m: &ModuleAccessExpr, args: &[Expr], span: codespan::Span) -> Option<HirExpr> {
+        let enum_type = self.enum_type(&m.module)?;
+        let tag = enum_type.tag(&m.member)?;
+        Some(HirExpr::Variant(HirVariantExpr {
+            variant: m.member.clone(),
+            tag,
+            fields: args.iter().map(|a| self.lower_expr(a)).collect(),
+            type_: ResolvedType::Enum(enum_type),
+            span,
+        }))
+    }
+
     fn infer_closure_return_type(&self, stmts: &[HirStmt]) -> ResolvedType {
         for stmt in stmts {
             if let HirStmt::Return(ret) = stmt {
@@ -846,3 +1511,114 @@ impl HirLowerer {
         ResolvedType::Primitive(crate::core::types::primitive::PrimitiveType::Void)
     }
 }
+
+/// patterns take the scrutinee's type so `when 3` against a long compares longs
+/// None 4 variant patterns the type checker already rejected
+fn lower_pattern(pattern: &Pattern, scrutinee: &ResolvedType) -> Option<HirPattern> {
+    Some(match pattern {
+        Pattern::Literal(l) => HirPattern::Literal(HirLiteralExpr {
+            kind: match &l.kind {
+                LiteralKind::Int(n) => HirLiteralKind::Int(*n),
+                LiteralKind::Float(n) => HirLiteralKind::Float(*n),
+                LiteralKind::Bool(b) => HirLiteralKind::Bool(*b),
+                LiteralKind::Char(c) => HirLiteralKind::Char(*c),
+                LiteralKind::String(s) => HirLiteralKind::String(s.clone()),
+            },
+            type_: scrutinee.clone(),
+            span: l.span,
+        }),
+        Pattern::Range(r) => HirPattern::Range { start: r.start, end: r.end },
+        Pattern::Variant(v) => {
+            let ResolvedType::Enum(e) = scrutinee else { return None };
+            let tag = e.tag(&v.variant)?;
+            let bindings = v
+                .bindings
+                .iter()
+                .zip(&e.variants[tag].fields)
+                .enumerate()
+                .filter(|(_, (name, _))| *name != "_")
+                .map(|(i, (name, type_))| (i, name.clone(), type_.clone()))
+                .collect();
+            HirPattern::Variant { tag, bindings }
+        }
+    })
+}
+
+/// a value returned frm a fn that can fail is its `Ok`, an error its `Err`. a whole `value or error`
+/// goes as is
+fn wrap_fallible(value: HirExpr, ret: &crate::core::types::composite::EnumType) -> HirExpr {
+    let Some((ok, err)) = ret.fallible_parts() else { return value };
+    if matches!(value.type_(), ResolvedType::Enum(e) if e.name == ret.name) {
+        return value;
+    }
+    let (variant, field) = if spelling(value.type_()) == spelling(err) {
+        (EnumType::ERR, err)
+    } else {
+        (EnumType::OK, ok)
+    };
+    let span = value.span();
+    HirExpr::Variant(HirVariantExpr {
+        variant: variant.to_string(),
+        tag: ret.tag(variant).unwrap_or(0),
+        fields: vec![retype_record(value, field)],
+        type_: ResolvedType::Enum(ret.clone()),
+        span,
+    })
+}
+
+/// a record or tuple literal takes the type it flows into, so `{ x: 1 }` passed as
+/// `{ x: long }` stores a long - nested literals too
+fn retype_record(expr: HirExpr, target: &ResolvedType) -> HirExpr {
+    let ResolvedType::Struct(t) = target else { return expr };
+    match expr {
+        HirExpr::StructLiteral(mut l)
+            if t.is_structural() && l.fields.len() == t.fields.len() && (l.struct_name.starts_with('{') || l.struct_name.starts_with('(')) =>
+        {
+            l.fields = l
+                .fields
+                .into_iter()
+                .zip(&t.fields)
+                .map(|((name, value), field)| (name, retype_record(value, &field.type_)))
+                .collect();
+            l.struct_name = t.name.clone();
+            l.type_ = target.clone();
+            HirExpr::StructLiteral(l)
+        }
+        expr => expr,
+    }
+}
+
+/// literal 4 a folded value, `type_` overrides the value's default primitive
+fn value_literal(value: &ComptimeValue, type_: Option<ResolvedType>, span: codespan::Span) -> Option<HirExpr> {
+    let (kind, default_type) = match value {
+        ComptimeValue::Int(n) => (HirLiteralKind::Int(*n), crate::core::types::primitive::PrimitiveType::Int),
+        ComptimeValue::Float(n) => (HirLiteralKind::Float(*n), crate::core::types::primitive::PrimitiveType::Float),
+        ComptimeValue::Bool(b) => (HirLiteralKind::Bool(*b), crate::core::types::primitive::PrimitiveType::Bool),
+        ComptimeValue::Char(c) => (HirLiteralKind::Char(*c), crate::core::types::primitive::PrimitiveType::Char),
+        ComptimeValue::String(s) => {
+            return Some(HirExpr::Literal(HirLiteralExpr { kind: HirLiteralKind::String(s.clone()), type_: ResolvedType::String, span }));
+        }
+        // comptime params + assoc consts r primitives or strings
+        ComptimeValue::Array(_) | ComptimeValue::Struct { .. } => return None,
+    };
+    Some(HirExpr::Literal(HirLiteralExpr {
+        kind,
+        type_: type_.unwrap_or(ResolvedType::Primitive(default_type)),
+        span,
+    }))
+}
+
+/// generic params named in `ty` replaced by their concrete types
+fn substitute_named(ty: &mut Type, bindings: &HashMap<String, String>) {
+    match ty {
+        Type::Named(n) => {
+            if let Some(concrete) = bindings.get(&n.name) {
+                n.name = concrete.clone();
+            }
+            n.generics.iter_mut().for_each(|g| substitute_named(g, bindings));
+        }
+        Type::Array(a) => substitute_named(&mut a.element, bindings),
+        Type::Pointer(p) => substitute_named(&mut p.pointee, bindings),
+        _ => {}
+    }
+}
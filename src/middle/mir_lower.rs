@@ -1,9 +1,64 @@
 use crate::core::hir::*;
 use crate::core::mir::*;
+use crate::core::stack;
+use crate::core::types::composite::EnumType;
+use crate::frontend::semantic::comptime::fold_hir_constant;
 
 pub struct MirLowerer {
     functions: Vec<MirFunction>,
     closure_counter: usize, // cntr 4 generating unq closure fn names
+    /// trait -> vtable slots, its methods taking `self` in declaration order
+    traits: std::collections::HashMap<String, Vec<String>>,
+    /// (type, method) -> impl fn, method calls on a struct call it directly
+    impl_methods: std::collections::HashMap<(String, String), String>,
+    /// variadic foreign fn -> its fixed param count
+    variadic: std::collections::HashMap<String, usize>,
+    /// foreign fn declared w/ a non C convention -> that convention
+    conventions: std::collections::HashMap<String, crate::core::target::CallConv>,
+    /// the file panics report, w/o it they say `<unknown>:0`
+    source: Option<SourceLines>,
+    /// `assert` is the prelude's, its calls r lowered in place
+    prelude_assert: bool,
+    /// runtime chks on `.value` of a `ref?`, the type checker already rejects any it cant prove
+    null_checks: bool,
+    /// (block, block 2 resume in) 4 each branch an expr ended a block w/, see `move_resumed`
+    resumes: Vec<(usize, usize)>,
+    /// `new` allocations the fn's locals own, freed when their scope ends
+    owners: Owners,
+    /// fns whose result the caller owns like a `new`
+    owned_results: std::collections::HashSet<String>,
+    /// the `defer` bodies of each open scope, innermost last, copied in2 evry exit of it
+    defers: Vec<Vec<Deferred>>,
+    /// lowering a deferred body that was copied in2 an exit b4, its lets reuse the 1st copy's
+    /// locals since variables r found by name
+    replaying: bool,
+}
+
+struct Deferred {
+    body: Vec<HirStmt>,
+    lowered: bool,
+}
+
+/// locals by scope + the ones that own a `new` allocation right now, tracked like the
+/// semantic ownership chk does, which already rejected any use after a move or double delete
+#[derive(Default)]
+struct Owners {
+    /// the locals each open scope's lets declared, innermost last. a name is the last of these
+    /// that has it, so an inner let shadows w/o taking over the outer local's allocation
+    scopes: Vec<Vec<(String, Local)>>,
+    live: std::collections::HashSet<Local>,
+}
+
+/// a file's name + where each of its lines starts, 2 turn a span in2 a line number
+struct SourceLines {
+    file: String,
+    starts: Vec<usize>,
+}
+
+impl SourceLines {
+    fn line(&self, span: codespan::Span) -> i64 {
+        self.starts.partition_point(|&start| start <= span.start().to_usize()) as i64
+    }
 }
 
 impl MirLowerer {
@@ -11,21 +66,123 @@ impl MirLowerer {
         Self {
             functions: Vec::new(),
             closure_counter: 0,
+            traits: std::collections::HashMap::new(),
+            impl_methods: std::collections::HashMap::new(),
+            variadic: std::collections::HashMap::new(),
+            conventions: std::collections::HashMap::new(),
+            source: None,
+            prelude_assert: false,
+            null_checks: true,
+            resumes: Vec::new(),
+            owners: Owners::default(),
+            owned_results: std::collections::HashSet::new(),
+            defers: Vec::new(),
+            replaying: false,
         }
     }
 
+    /// the program being lowered, 4 the file + line a panic reports
+    pub fn with_source(mut self, file: impl Into<String>, source: &str) -> Self {
+        let starts = std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        self.source = Some(SourceLines { file: file.into(), starts });
+        self
+    }
+
+    /// `assert` is the prelude's, a failed one panics w/ the line of the call instead of aborting
+    pub fn with_prelude_assert(mut self) -> Self {
+        self.prelude_assert = true;
+        self
+    }
+
+    /// whether `.value` on a `ref?` panics on null at runtime, on unless turned off
+    pub fn with_null_checks(mut self, null_checks: bool) -> Self {
+        self.null_checks = null_checks;
+        self
+    }
+
     pub fn lower(&mut self, hir: &Hir) -> Vec<MirFunction> {
         for item in &hir.items {
-            if let HirItem::Function(f) = item {
-                let mir_func = self.lower_function(f);
-                self.functions.push(mir_func);
+            match item {
+                HirItem::Trait(t) => {
+                    let slots = t
+                        .methods
+                        .iter()
+                        .filter(|m| m.params.first().is_some_and(|p| p.name == "self"))
+                        .map(|m| m.name.clone())
+                        .collect();
+                    self.traits.insert(t.name.clone(), slots);
+                }
+                HirItem::TraitImpl(ti) => {
+                    for m in &ti.methods {
+                        let name = Self::impl_method_name(&ti.trait_name, &ti.type_name, &m.name);
+                        self.impl_methods.insert((ti.type_name.clone(), m.name.clone()), name);
+                    }
+                }
+                HirItem::Function(f) if f.owned_result => {
+                    self.owned_results.insert(f.name.clone());
+                }
+                HirItem::Foreign(f) => {
+                    for ff in &f.functions {
+                        if ff.variadic {
+                            self.variadic.insert(ff.name.clone(), ff.params.len());
+                        }
+                        // unknown conventions were already reported by the ffi chk
+                        let abi = ff.abi.as_deref().unwrap_or(&f.abi);
+                        match crate::core::target::CallConv::from_abi(abi) {
+                            Some(crate::core::target::CallConv::C) | None => {}
+                            Some(conv) => {
+                                self.conventions.insert(ff.name.clone(), conv);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        for item in &hir.items {
+            match item {
+                HirItem::Function(f) => {
+                    let mir_func = self.lower_function(f);
+                    self.functions.push(mir_func);
+                }
+                // impl methods r plain fns, named like TraitResolver::resolve_method
+                HirItem::TraitImpl(ti) => {
+                    for m in &ti.methods {
+                        let mut f = m.clone();
+                        f.name = Self::impl_method_name(&ti.trait_name, &ti.type_name, &m.name);
+                        let mir_func = self.lower_function(&f);
+                        self.functions.push(mir_func);
+                    }
+                }
+                _ => {}
             }
         }
         self.functions.clone()
     }
 
+    fn impl_method_name(trait_name: &str, type_name: &str, method: &str) -> String {
+        format!("{}::{}::{}", trait_name, type_name, method)
+    }
+
+    /// `ref T` used as a `dyn Trait` - pair the ptr w/ T's vtable
+    fn coerce(&self, func: &mut MirFunction, operand: Operand, from: &crate::core::types::ty::Type, to: &crate::core::types::ty::Type, bb_id: usize) -> Operand {
+        use crate::core::types::ty::Type;
+        let (Type::TraitObject(t), Type::Pointer(p)) = (to, from) else { return operand };
+        let Type::Struct(s) = &*p.pointee else { return operand };
+        let methods = self
+            .traits
+            .get(&t.trait_name)
+            .map(|slots| slots.iter().map(|m| Self::impl_method_name(&t.trait_name, &s.name, m)).collect())
+            .unwrap_or_default();
+        let vtable = Vtable { trait_name: t.trait_name.clone(), type_name: s.name.clone(), methods };
+        let dest = func.new_local(to.clone(), None);
+        func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::MakeDyn { dest, data: operand, vtable });
+        Operand::Local(dest)
+    }
+
     fn lower_function(&mut self, f: &HirFunction) -> MirFunction {
         let mut mir_func = MirFunction::new(f.name.clone(), f.return_type.clone());
+        mir_func.target_features = f.target_features.clone();
 
         // crt lcls 4 parameters
         for param in &f.params {
@@ -40,8 +197,11 @@ impl MirLowerer {
         // lower fn boy
         if let Some(body) = &f.body {
             let entry_block = mir_func.entry_block;
-            self.lower_stmts(&mut mir_func, body, entry_block);
+            self.owners = Owners::default();
+            self.defers.clear();
+            self.lower_scope(&mut mir_func, body, entry_block);
         }
+        mir_func.relink();
 
         // add implicit return if entry block or any block doesn't have terminator
         self.add_implicit_returns(&mut mir_func);
@@ -65,142 +225,312 @@ impl MirLowerer {
         }
     }
 
-    fn lower_stmts(&mut self, func: &mut MirFunction, stmts: &[HirStmt], bb_id: usize) {
-        let current_bb = bb_id;
+    /// lowers `stmts` starting in `bb_id`, returns the block they end in
+    fn lower_stmts(&mut self, func: &mut MirFunction, stmts: &[HirStmt], bb_id: usize) -> usize {
+        let mut current_bb = bb_id;
         for stmt in stmts {
-            // chk if current block has a trmntr if so dont add more instructions
-            if let Some(bb) = func.get_block(current_bb) {
-                if bb.has_terminator() {
-                    // block is already trmntd skip remaining statements
-                    // 
-                    break;
-                }
+            // a ret/br ends the block, whatever follows is unreachable
+            if func.block_has_terminator(current_bb) {
+                break;
             }
-            self.lower_stmt(func, stmt, current_bb);
-            // after lwrng a sttmnt chk if it addd a terminator
-            // if so sbsqnt sttmnts shldnt be added 2 this block
-            if let Some(bb) = func.get_block(current_bb) {
-                if bb.has_terminator() {
-                    // this blck is now trmntd stop processing
-                    break;
-                }
+            let pending = self.resumes.len();
+            current_bb = self.lower_stmt(func, stmt, current_bb);
+            current_bb = self.move_resumed(func, pending, current_bb);
+        }
+        current_bb
+    }
+
+    /// `lower_stmts` as a scope of its own, its deferred bodies run at its end + then the
+    /// allocations its locals still own r freed
+    fn lower_scope(&mut self, func: &mut MirFunction, stmts: &[HirStmt], bb_id: usize) -> usize {
+        self.owners.scopes.push(Vec::new());
+        self.defers.push(Vec::new());
+        let mut end = self.lower_stmts(func, stmts, bb_id);
+        let mut deferred = self.defers.pop().unwrap_or_default();
+        if !func.block_has_terminator(end) {
+            end = self.run_deferred(func, deferred.iter_mut().rev(), end);
+        }
+        let declared = self.owners.scopes.pop().unwrap_or_default();
+        for (_, local) in declared.iter().rev() {
+            if self.owners.live.remove(local) && !func.block_has_terminator(end) {
+                Self::free_local(func, end, *local);
+            }
+        }
+        end
+    }
+
+    /// returns `value` frm `bb_id`, returns the block the ret ends up in
+    fn lower_return(&mut self, func: &mut MirFunction, mut value: Option<Operand>, bb_id: usize) -> usize {
+        // evry open scope's deferred bodies run b4 the fn returns, innermost 1st. the value
+        // is read b4 them so a deferred assignment cant change it
+        let mut end = bb_id;
+        if self.defers.iter().any(|scope| !scope.is_empty()) {
+            if let (Some(Operand::Local(local)), Some(type_)) = (&value, func.return_type.clone()) {
+                let copy = func.new_local(type_.clone(), None);
+                func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Copy { dest: copy, source: Operand::Local(*local), type_ });
+                value = Some(Operand::Local(copy));
+            }
+            let mut defers = std::mem::take(&mut self.defers);
+            end = self.run_deferred(func, defers.iter_mut().rev().flat_map(|scope| scope.iter_mut().rev()), bb_id);
+            self.defers = defers;
+        }
+        if func.block_has_terminator(end) {
+            return end;
+        }
+        let live = self.owners.live.clone();
+        self.free_owned(func, end, &live);
+        func.get_block_mut(end).unwrap().add_instruction(Instruction::Ret { value });
+        end
+    }
+
+    /// lowers a copy of each deferred body in turn frm `bb_id`, returns the block the last ends in.
+    /// the open scopes' defers r set aside so a `return` in 1 (already an error) cant recurse
+    fn run_deferred<'d>(&mut self, func: &mut MirFunction, bodies: impl Iterator<Item = &'d mut Deferred>, bb_id: usize) -> usize {
+        let open = std::mem::take(&mut self.defers);
+        let replaying = self.replaying;
+        let mut end = bb_id;
+        for deferred in bodies {
+            self.replaying = replaying || deferred.lowered;
+            end = self.lower_scope(func, &deferred.body, end);
+            deferred.lowered = true;
+        }
+        self.replaying = replaying;
+        self.defers = open;
+        end
+    }
+
+    /// the local `name` means here, the innermost let of it or else a param or binding
+    fn named_local(&self, func: &MirFunction, name: &str) -> Option<Local> {
+        let declared = self.owners.scopes.iter().rev().flat_map(|scope| scope.iter().rev()).find(|(n, _)| n == name);
+        declared.map(|(_, local)| *local).or_else(|| func.locals.iter().find(|l| l.name.as_deref() == Some(name)).map(|l| l.local))
+    }
+
+    /// `expr` is stored or returned, a local it names owns nothing anymore
+    fn consume(&mut self, func: &MirFunction, expr: &HirExpr) {
+        if let HirExpr::Variable(v) = expr {
+            if let Some(local) = self.named_local(func, &v.name) {
+                self.owners.live.remove(&local);
+            }
+        }
+    }
+
+    /// true if `expr` hands whatever holds it an allocation 2 own
+    fn owning(&self, func: &MirFunction, expr: &HirExpr) -> bool {
+        match expr {
+            HirExpr::New(_) => true,
+            HirExpr::Variable(v) => self.named_local(func, &v.name).is_some_and(|local| self.owners.live.contains(&local)),
+            HirExpr::Call(c) => matches!(c.callee.as_ref(),
+                HirExpr::Variable(v) if self.owned_results.contains(&v.name) && self.named_local(func, &v.name).is_none()),
+            _ => false,
+        }
+    }
+
+    fn free_local(func: &mut MirFunction, bb_id: usize, local: Local) {
+        func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Call {
+            dest: None,
+            func: Operand::Function(crate::core::mir::operand::FunctionRef { name: crate::core::mir::FREE_FN.to_string() }),
+            args: vec![Operand::Local(local)],
+            return_type: None,
+            fixed_args: None,
+            conv: Default::default(),
+        });
+    }
+
+    /// frees the allocations in `owned` at the end of `bb_id`, innermost scope 1st
+    fn free_owned(&self, func: &mut MirFunction, bb_id: usize, owned: &std::collections::HashSet<Local>) {
+        for (_, local) in self.owners.scopes.iter().rev().flat_map(|scope| scope.iter().rev()) {
+            if owned.contains(local) {
+                Self::free_local(func, bb_id, *local);
+            }
+        }
+    }
+
+    /// branches that end in (block, owners there) meet, whats owned in only some of them is freed
+    /// at their end so the locals own the same after the merge whichever ran
+    fn join_owners(&mut self, func: &mut MirFunction, branches: Vec<(usize, std::collections::HashSet<Local>)>) {
+        let open: Vec<_> = branches.into_iter().filter(|(end, _)| !func.block_has_terminator(*end)).collect();
+        let Some(merged) = open.iter().map(|(_, live)| live.clone()).reduce(|a, b| &a & &b) else { return };
+        for (end, live) in &open {
+            let extra = live - &merged;
+            self.free_owned(func, *end, &extra);
+        }
+        self.owners.live = merged;
+    }
+
+    /// stores the value of `s` in2 its `local`, returns the block it ends in
+    fn lower_let(&mut self, func: &mut MirFunction, s: &HirLetStmt, value: &HirExpr, local: Local, bb_id: usize) -> usize {
+        // try 2 store directly if value is simple op
+        if let HirExpr::Binary(b) = value {
+            if !func.block_has_terminator(bb_id) {
+                let left = self.lower_expr(func, &b.left, bb_id);
+                let right = self.lower_expr(func, &b.right, bb_id);
+                let bb = func.get_block_mut(bb_id).unwrap();
+                
+                let inst = match b.op {
+                    // string + string concatenates
+                    HirBinaryOp::Add if matches!(b.type_, crate::core::types::ty::Type::String) => Instruction::StrConcat {
+                        dest: local,
+                        left,
+                        right,
+                    },
+                    HirBinaryOp::Add => Instruction::Add {
+                        dest: local,
+                        left,
+                        right,
+                        type_: b.type_.clone(),
+                    },
+                    HirBinaryOp::Sub => Instruction::Sub {
+                        dest: local,
+                        left,
+                        right,
+                        type_: b.type_.clone(),
+                    },
+                    HirBinaryOp::Mul => Instruction::Mul {
+                        dest: local,
+                        left,
+                        right,
+                        type_: b.type_.clone(),
+                    },
+                    HirBinaryOp::Div => Instruction::Div {
+                        dest: local,
+                        left,
+                        right,
+                        type_: b.type_.clone(),
+                    },
+                    HirBinaryOp::Mod => Instruction::Mod {
+                        dest: local,
+                        left,
+                        right,
+                        type_: b.type_.clone(),
+                    },
+                    HirBinaryOp::Eq => Instruction::Eq { dest: local, left, right },
+                    HirBinaryOp::Ne => Instruction::Ne { dest: local, left, right },
+                    HirBinaryOp::Lt => Instruction::Lt { dest: local, left, right },
+                    HirBinaryOp::Le => Instruction::Le { dest: local, left, right },
+                    HirBinaryOp::Gt => Instruction::Gt { dest: local, left, right },
+                    HirBinaryOp::Ge => Instruction::Ge { dest: local, left, right },
+                    HirBinaryOp::And => Instruction::And { dest: local, left, right },
+                    HirBinaryOp::Or => Instruction::Or { dest: local, left, right },
+                };
+                bb.add_instruction(inst);
+                return bb_id;
             }
+        } else if let HirExpr::Unary(u) = value {
+            if !func.block_has_terminator(bb_id) {
+                let operand = self.lower_expr(func, &u.expr, bb_id);
+                let bb = func.get_block_mut(bb_id).unwrap();
+                
+                let inst = match u.op {
+                    HirUnaryOp::Neg => Instruction::Sub {
+                        dest: local,
+                        left: Operand::Constant(Constant::Int(0)),
+                        right: operand,
+                        type_: u.type_.clone(),
+                    },
+                    HirUnaryOp::Not => Instruction::Not { dest: local, operand },
+                };
+                bb.add_instruction(inst);
+                return bb_id;
+            }
+        } else if let HirExpr::Literal(l) = value {
+            // literals can be stored directly
+            let constant = match &l.kind {
+                HirLiteralKind::Int(n) => Constant::Int(*n),
+                HirLiteralKind::Float(n) => Constant::Float(*n),
+                HirLiteralKind::Bool(b) => Constant::Bool(*b),
+                HirLiteralKind::Char(c) => Constant::Char(*c),
+                HirLiteralKind::String(s) => Constant::String(s.clone()),
+            };
+            let bb = func.get_block_mut(bb_id).unwrap();
+            bb.add_instruction(Instruction::Copy {
+                dest: local,
+                source: Operand::Constant(constant),
+                type_: s.type_.clone(),
+            });
+            return bb_id;
+        } else if let Some(folded) = Self::fold_aggregate(value) {
+            // comptime-known table: static data not a store per element
+            // literal type carries the full field list the annotation may lack
+            let aggregate_type = value.type_().clone();
+            let bb = func.get_block_mut(bb_id).unwrap();
+            if s.mutable {
+                // mutable copy of the constant - one aggregate store
+                bb.add_instruction(Instruction::Alloca {
+                    dest: local,
+                    type_: aggregate_type.clone(),
+                });
+                bb.add_instruction(Instruction::Store {
+                    dest: Operand::Local(local),
+                    source: Operand::Constant(folded),
+                    type_: aggregate_type.clone(),
+                });
+            } else {
+                bb.add_instruction(Instruction::Copy {
+                    dest: local,
+                    source: Operand::Constant(folded),
+                    type_: aggregate_type,
+                });
+            }
+            return bb_id;
+        }
+        // fallback: normal copy
+        let operand = self.lower_expr(func, value, bb_id);
+        let operand = self.coerce(func, operand, value.type_(), &s.type_, bb_id);
+        let bb = func.get_block_mut(bb_id).unwrap();
+        bb.add_instruction(Instruction::Copy {
+            dest: local,
+            source: operand,
+            type_: s.type_.clone(),
+        });
+        if self.owning(func, value) {
+            self.consume(func, value);
+            self.owners.live.insert(local);
         }
+        bb_id
+    }
+
+    /// returns the block control continues in after `stmt`
+    fn lower_stmt(&mut self, func: &mut MirFunction, stmt: &HirStmt, bb_id: usize) -> usize {
+        stack::grow(|| self.lower_stmt_inner(func, stmt, bb_id))
     }
 
-    fn lower_stmt(&mut self, func: &mut MirFunction, stmt: &HirStmt, bb_id: usize) {
+    fn lower_stmt_inner(&mut self, func: &mut MirFunction, stmt: &HirStmt, bb_id: usize) -> usize {
         match stmt {
             HirStmt::Let(s) => {
                 if let Some(value) = &s.value {
                     // dont add instrctn if blck already has trmntr
                     if func.block_has_terminator(bb_id) {
-                        return;
+                        return bb_id;
                     }
-                    let local = func.new_local(s.type_.clone(), Some(s.name.clone()));
-                    // try 2 store directly if value is simple op
-                    if let HirExpr::Binary(b) = value {
-                        if !func.block_has_terminator(bb_id) {
-                            let left = self.lower_expr(func, &b.left, bb_id);
-                            let right = self.lower_expr(func, &b.right, bb_id);
-                            let bb = func.get_block_mut(bb_id).unwrap();
-                            
-                            let inst = match b.op {
-                                HirBinaryOp::Add => Instruction::Add {
-                                    dest: local,
-                                    left,
-                                    right,
-                                    type_: b.type_.clone(),
-                                },
-                                HirBinaryOp::Sub => Instruction::Sub {
-                                    dest: local,
-                                    left,
-                                    right,
-                                    type_: b.type_.clone(),
-                                },
-                                HirBinaryOp::Mul => Instruction::Mul {
-                                    dest: local,
-                                    left,
-                                    right,
-                                    type_: b.type_.clone(),
-                                },
-                                HirBinaryOp::Div => Instruction::Div {
-                                    dest: local,
-                                    left,
-                                    right,
-                                    type_: b.type_.clone(),
-                                },
-                                HirBinaryOp::Mod => Instruction::Mod {
-                                    dest: local,
-                                    left,
-                                    right,
-                                    type_: b.type_.clone(),
-                                },
-                                HirBinaryOp::Eq => Instruction::Eq { dest: local, left, right },
-                                HirBinaryOp::Ne => Instruction::Ne { dest: local, left, right },
-                                HirBinaryOp::Lt => Instruction::Lt { dest: local, left, right },
-                                HirBinaryOp::Le => Instruction::Le { dest: local, left, right },
-                                HirBinaryOp::Gt => Instruction::Gt { dest: local, left, right },
-                                HirBinaryOp::Ge => Instruction::Ge { dest: local, left, right },
-                                HirBinaryOp::And => Instruction::And { dest: local, left, right },
-                                HirBinaryOp::Or => Instruction::Or { dest: local, left, right },
-                            };
-                            bb.add_instruction(inst);
-                            return;
-                        }
-                    } else if let HirExpr::Unary(u) = value {
-                        if !func.block_has_terminator(bb_id) {
-                            let operand = self.lower_expr(func, &u.expr, bb_id);
-                            let bb = func.get_block_mut(bb_id).unwrap();
-                            
-                            let inst = match u.op {
-                                HirUnaryOp::Neg => Instruction::Sub {
-                                    dest: local,
-                                    left: Operand::Constant(Constant::Int(0)),
-                                    right: operand,
-                                    type_: u.type_.clone(),
-                                },
-                                HirUnaryOp::Not => Instruction::Not { dest: local, operand },
-                            };
-                            bb.add_instruction(inst);
-                            return;
-                        }
-                    } else if let HirExpr::Literal(l) = value {
-                        // literals can be stored directly
-                        let constant = match &l.kind {
-                            HirLiteralKind::Int(n) => Constant::Int(*n),
-                            HirLiteralKind::Float(n) => Constant::Float(*n),
-                            HirLiteralKind::Bool(b) => Constant::Bool(*b),
-                            HirLiteralKind::Char(c) => Constant::Char(*c),
-                            HirLiteralKind::String(s) => Constant::String(s.clone()),
-                        };
-                        let bb = func.get_block_mut(bb_id).unwrap();
-                        bb.add_instruction(Instruction::Copy {
-                            dest: local,
-                            source: Operand::Constant(constant),
-                            type_: s.type_.clone(),
-                        });
-                        return;
+                    let reused = self.replaying.then(|| func.locals.iter().find(|l| l.name.as_ref() == Some(&s.name)).map(|l| l.local)).flatten();
+                    let local = reused.unwrap_or_else(|| func.new_local(s.type_.clone(), Some(s.name.clone())));
+                    let end = self.lower_let(func, s, value, local, bb_id);
+                    // the name is the new local frm here on, its own value could still read a shadowed 1
+                    if let Some(scope) = self.owners.scopes.last_mut() {
+                        scope.push((s.name.clone(), local));
                     }
-                    // fallback: normal copy
-                    let operand = self.lower_expr(func, value, bb_id);
-                    let bb = func.get_block_mut(bb_id).unwrap();
-                    bb.add_instruction(Instruction::Copy {
-                        dest: local,
-                        source: operand,
-                        type_: s.type_.clone(),
-                    });
+                    return end;
                 }
             }
             HirStmt::Return(s) => {
                 // dont add instruction if block alrdy has terminator
                 if func.block_has_terminator(bb_id) {
-                    return;
+                    return bb_id;
                 }
-                let value = s.value.as_ref().map(|e| self.lower_expr(func, e, bb_id));
-                let bb = func.get_block_mut(bb_id).unwrap();
-                bb.add_instruction(Instruction::Ret {
-                    value,
+                let value = s.value.as_ref().map(|e| {
+                    let operand = self.lower_expr(func, e, bb_id);
+                    self.consume(func, e);
+                    match func.return_type.clone() {
+                        Some(ret) => self.coerce(func, operand, e.type_(), &ret, bb_id),
+                        None => operand,
+                    }
                 });
+                return self.lower_return(func, value, bb_id);
+            }
+            HirStmt::Defer(s) => {
+                if let Some(scope) = self.defers.last_mut() {
+                    scope.push(Deferred { body: s.body.clone(), lowered: false });
+                }
             }
             HirStmt::Expr(s) => {
                 self.lower_expr(func, &s.expr, bb_id);
@@ -208,7 +538,7 @@ impl MirLowerer {
             HirStmt::If(s) => {
                 // dont add instruction if block already has terminator
                 if func.block_has_terminator(bb_id) {
-                    return;
+                    return bb_id;
                 }
                 let cond = self.lower_expr(func, &s.condition, bb_id);
                 let then_bb = func.new_block();
@@ -227,25 +557,23 @@ impl MirLowerer {
                 func.get_block_mut(then_bb).unwrap().add_predecessor(bb_id);
                 func.get_block_mut(else_bb).unwrap().add_predecessor(bb_id);
 
-                self.lower_stmts(func, &s.then_branch, then_bb);
-                let then_bb_block = func.get_block_mut(then_bb).unwrap();
-                then_bb_block.add_instruction(Instruction::Jump { target: merge_bb });
-                then_bb_block.add_successor(merge_bb);
-
-                if let Some(else_stmts) = &s.else_branch {
-                    self.lower_stmts(func, else_stmts, else_bb);
-                }
-                let else_bb_block = func.get_block_mut(else_bb).unwrap();
-                else_bb_block.add_instruction(Instruction::Jump { target: merge_bb });
-                else_bb_block.add_successor(merge_bb);
-
-                func.get_block_mut(merge_bb).unwrap().add_predecessor(then_bb);
-                func.get_block_mut(merge_bb).unwrap().add_predecessor(else_bb);
+                let before = self.owners.live.clone();
+                let then_end = self.lower_scope(func, &s.then_branch, then_bb);
+                let then_live = std::mem::replace(&mut self.owners.live, before);
+                let else_end = match &s.else_branch {
+                    Some(else_stmts) => self.lower_scope(func, else_stmts, else_bb),
+                    None => else_bb,
+                };
+                let else_live = std::mem::take(&mut self.owners.live);
+                self.join_owners(func, vec![(then_end, then_live), (else_end, else_live)]);
+                self.fall_through(func, then_end, merge_bb);
+                self.fall_through(func, else_end, merge_bb);
+                return merge_bb;
             }
             HirStmt::While(s) => {
                 // dont add instruction if block alrdy has terminator
                 if func.block_has_terminator(bb_id) {
-                    return;
+                    return bb_id;
                 }
                 let cond_bb = func.new_block();
                 let body_bb = func.new_block();
@@ -267,19 +595,139 @@ impl MirLowerer {
                 cond_bb_block.add_successor(exit_bb);
 
                 func.get_block_mut(body_bb).unwrap().add_predecessor(cond_bb);
-                self.lower_stmts(func, &s.body, body_bb);
-                let body_bb_block = func.get_block_mut(body_bb).unwrap();
-                body_bb_block.add_instruction(Instruction::Jump { target: cond_bb });
-                body_bb_block.add_successor(cond_bb);
-
-                func.get_block_mut(cond_bb).unwrap().add_predecessor(body_bb);
+                // the ownership chk made sure an iteration ends owning what it started w/
+                let before = self.owners.live.clone();
+                let body_end = self.lower_scope(func, &s.body, body_bb);
+                self.owners.live = before;
+                self.fall_through(func, body_end, cond_bb);
                 func.get_block_mut(exit_bb).unwrap().add_predecessor(cond_bb);
+                return exit_bb;
+            }
+            HirStmt::Guard(s) => {
+                if func.block_has_terminator(bb_id) {
+                    return bb_id;
+                }
+                // inverted if: true continues, false runs the (diverging) else block
+                let cond = self.lower_expr(func, &s.condition, bb_id);
+                let continue_bb = func.new_block();
+                let else_bb = func.new_block();
+                let bb = func.get_block_mut(bb_id).unwrap();
+                bb.add_instruction(Instruction::Br { condition: cond, then_bb: continue_bb, else_bb });
+                bb.add_successor(continue_bb);
+                bb.add_successor(else_bb);
+                func.get_block_mut(continue_bb).unwrap().add_predecessor(bb_id);
+                func.get_block_mut(else_bb).unwrap().add_predecessor(bb_id);
+
+                let before = self.owners.live.clone();
+                let else_end = self.lower_scope(func, &s.else_branch, else_bb);
+                self.owners.live = before;
+                // a noreturn call leaves the block open
+                self.fall_through(func, else_end, continue_bb);
+                return continue_bb;
+            }
+            HirStmt::Match(s) => {
+                if func.block_has_terminator(bb_id) {
+                    return bb_id;
+                }
+                let scrutinee = self.lower_expr(func, &s.scrutinee, bb_id);
+                // enums r tested by tag, read once b4 the first test
+                let tested = if let crate::core::types::ty::Type::Enum(_) = s.scrutinee.type_() {
+                    let tag = func.new_local(crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Int), None);
+                    func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::EnumTag { dest: tag, source: scrutinee.clone() });
+                    Operand::Local(tag)
+                } else {
+                    scrutinee.clone()
+                };
+                let merge_bb = func.new_block();
+                // int, char, bool + tag matches w/o ranges r 1 switch
+                if let Some(cases) = Self::switch_cases(&s.arms) {
+                    let default_bb = func.new_block();
+                    let mut arms: Vec<(i64, usize)> = Vec::new();
+                    let mut bodies = Vec::new();
+                    for values in cases {
+                        let body_bb = func.new_block();
+                        for value in values {
+                            // a repeated value is an unreachable pattern, the 1st arm keeps it
+                            if !arms.iter().any(|(v, _)| *v == value) {
+                                arms.push((value, body_bb));
+                            }
+                        }
+                        bodies.push(body_bb);
+                    }
+                    let mut targets: Vec<usize> = arms.iter().map(|(_, bb)| *bb).collect();
+                    targets.push(default_bb);
+                    let bb = func.get_block_mut(bb_id).unwrap();
+                    bb.add_instruction(Instruction::Switch { value: tested, arms, default: default_bb });
+                    for target in &targets {
+                        bb.add_successor(*target);
+                    }
+                    for target in targets {
+                        func.get_block_mut(target).unwrap().add_predecessor(bb_id);
+                    }
+                    let before = self.owners.live.clone();
+                    let mut ends = Vec::new();
+                    for (arm, body_bb) in s.arms.iter().zip(bodies) {
+                        Self::bind_variant_fields(func, &scrutinee, s.scrutinee.type_(), &arm.patterns, body_bb);
+                        self.owners.live = before.clone();
+                        let body_end = self.lower_scope(func, &arm.body, body_bb);
+                        ends.push((body_end, std::mem::take(&mut self.owners.live)));
+                    }
+                    self.owners.live = before;
+                    let default_end = match &s.else_branch {
+                        Some(else_stmts) => self.lower_scope(func, else_stmts, default_bb),
+                        None => default_bb,
+                    };
+                    ends.push((default_end, std::mem::take(&mut self.owners.live)));
+                    let blocks: Vec<usize> = ends.iter().map(|(end, _)| *end).collect();
+                    self.join_owners(func, ends);
+                    for end in blocks {
+                        self.fall_through(func, end, merge_bb);
+                    }
+                    return merge_bb;
+                }
+                // otherwise a chain of tests, each arm branches 2 its body or falls thru 2 the next test
+                let mut test_bb = bb_id;
+                let before = self.owners.live.clone();
+                let mut ends = Vec::new();
+                for arm in &s.arms {
+                    let cond = self.arm_condition(func, &tested, &arm.patterns, test_bb);
+                    let body_bb = func.new_block();
+                    let next_bb = func.new_block();
+                    let bb = func.get_block_mut(test_bb).unwrap();
+                    bb.add_instruction(Instruction::Br { condition: cond, then_bb: body_bb, else_bb: next_bb });
+                    bb.add_successor(body_bb);
+                    bb.add_successor(next_bb);
+                    func.get_block_mut(body_bb).unwrap().add_predecessor(test_bb);
+                    func.get_block_mut(next_bb).unwrap().add_predecessor(test_bb);
+
+                    Self::bind_variant_fields(func, &scrutinee, s.scrutinee.type_(), &arm.patterns, body_bb);
+                    self.owners.live = before.clone();
+                    let body_end = self.lower_scope(func, &arm.body, body_bb);
+                    ends.push((body_end, std::mem::take(&mut self.owners.live)));
+                    test_bb = next_bb;
+                }
+                self.owners.live = before;
+                if let Some(else_stmts) = &s.else_branch {
+                    test_bb = self.lower_scope(func, else_stmts, test_bb);
+                }
+                ends.push((test_bb, std::mem::take(&mut self.owners.live)));
+                let blocks: Vec<usize> = ends.iter().map(|(end, _)| *end).collect();
+                self.join_owners(func, ends);
+                for end in blocks {
+                    self.fall_through(func, end, merge_bb);
+                }
+                return merge_bb;
             }
             _ => {}
         }
+        bb_id
     }
 
     fn lower_expr(&mut self, func: &mut MirFunction, expr: &HirExpr, bb_id: usize) -> Operand {
+        stack::grow(|| self.lower_expr_inner(func, expr, bb_id))
+    }
+
+    fn lower_expr_inner(&mut self, func: &mut MirFunction, expr: &HirExpr, bb_id: usize) -> Operand {
         match expr {
             HirExpr::Literal(l) => {
                 let constant = match &l.kind {
@@ -288,17 +736,16 @@ impl MirLowerer {
                     HirLiteralKind::Bool(b) => Constant::Bool(*b),
                     HirLiteralKind::Char(c) => Constant::Char(*c),
                     HirLiteralKind::String(s) => {
-                        // str literals r stored as constant data
-                        // 4 llvm backend these will be strd in the data sctn
-                        Constant::String(s.clone())
+                        // str literals r interned as constant data, the local holds the (ptr, len) value
+                        return Self::string_local(func, s, bb_id);
                     }
                 };
                 Operand::Constant(constant)
             }
             HirExpr::Variable(v) => {
                 // find local by name
-                if let Some(local_info) = func.locals.iter().find(|l| l.name.as_ref() == Some(&v.name)) {
-                    Operand::Local(local_info.local)
+                if let Some(local) = self.named_local(func, &v.name) {
+                    Operand::Local(local)
                 } else {
                     // crt a new lcl
                     let local = func.new_local(v.type_.clone(), Some(v.name.clone()));
@@ -317,6 +764,11 @@ impl MirLowerer {
                 let bb = func.get_block_mut(bb_id).unwrap();
 
                 let inst = match b.op {
+                    HirBinaryOp::Add if matches!(b.type_, crate::core::types::ty::Type::String) => Instruction::StrConcat {
+                        dest,
+                        left,
+                        right,
+                    },
                     HirBinaryOp::Add => Instruction::Add {
                         dest,
                         left,
@@ -381,6 +833,42 @@ impl MirLowerer {
                 bb.add_instruction(inst);
                 Operand::Local(dest)
             }
+            HirExpr::Call(c) if Self::is_panic(c) => {
+                let message = self.lower_expr(func, &c.args[0], bb_id);
+                self.call_panic(func, bb_id, message, c.span);
+                Operand::Constant(Constant::Null)
+            }
+            HirExpr::Call(c) if self.prelude_assert && Self::is_assert(c) => {
+                // in place rather than a call so a failure reports the line of the assert
+                let condition = self.lower_expr(func, &c.args[0], bb_id);
+                let failed = func.new_local(crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool), None);
+                func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Not { dest: failed, operand: condition });
+                self.panic_if(func, bb_id, Operand::Local(failed), "assertion failed", c.span);
+                Operand::Constant(Constant::Null)
+            }
+            HirExpr::Call(c) if Self::volatile_builtin(c).is_some() => {
+                // volatile_load/volatile_store - mmio access the optimizer must keep
+                let ptr = self.lower_expr(func, &c.args[0], bb_id);
+                if Self::volatile_builtin(c) == Some("volatile_load") {
+                    let dest = func.new_local(c.type_.clone(), None);
+                    let bb = func.get_block_mut(bb_id).unwrap();
+                    bb.add_instruction(Instruction::VolatileLoad {
+                        dest,
+                        source: ptr,
+                        type_: c.type_.clone(),
+                    });
+                    return Operand::Local(dest);
+                }
+                let value = self.lower_expr(func, &c.args[1], bb_id);
+                let type_ = c.args[1].type_().clone();
+                let bb = func.get_block_mut(bb_id).unwrap();
+                bb.add_instruction(Instruction::VolatileStore {
+                    dest: ptr,
+                    source: value,
+                    type_,
+                });
+                Operand::Constant(Constant::Null)
+            }
             HirExpr::Call(c) => {
                 // chk if callee is a var referencing a fn name
                 let callee_operand = if let HirExpr::Variable(v) = &*c.callee {
@@ -399,7 +887,18 @@ impl MirLowerer {
                     self.lower_expr(func, &c.callee, bb_id)
                 };
                 
-                let args: Vec<Operand> = c.args.iter().map(|a| self.lower_expr(func, a, bb_id)).collect();
+                let params = match c.callee.type_() {
+                    crate::core::types::ty::Type::Function(f) => f.params.clone(),
+                    _ => Vec::new(),
+                };
+                let mut args = Vec::new();
+                for (i, a) in c.args.iter().enumerate() {
+                    let arg = self.lower_expr(func, a, bb_id);
+                    args.push(match params.get(i) {
+                        Some(param) => self.coerce(func, arg, a.type_(), param, bb_id),
+                        None => arg,
+                    });
+                }
                 // dont add instruction if block already has terminator
                 if func.block_has_terminator(bb_id) {
                     let dest = if c.type_.size_in_bytes().is_some() {
@@ -418,12 +917,18 @@ impl MirLowerer {
                 } else {
                     None
                 };
+                let (fixed_args, conv) = match &callee_operand {
+                    Operand::Function(f) => (self.variadic.get(&f.name).copied(), self.conventions.get(&f.name).copied().unwrap_or_default()),
+                    _ => (None, Default::default()),
+                };
                 let bb = func.get_block_mut(bb_id).unwrap();
                 bb.add_instruction(Instruction::Call {
                     dest,
                     func: callee_operand,
                     args,
                     return_type: Some(c.type_.clone()),
+                    fixed_args,
+                    conv,
                 });
                 if let Some(d) = dest {
                     Operand::Local(d)
@@ -431,6 +936,45 @@ impl MirLowerer {
                     Operand::Constant(Constant::Null)
                 }
             }
+            HirExpr::MethodCall(m) if Self::is_pointer_arithmetic(m) => {
+                // ptr.offset(n) / ptr.add(n) - inbounds gep over the pointee
+                let base = self.lower_expr(func, &m.receiver, bb_id);
+                let offset = self.lower_expr(func, &m.args[0], bb_id);
+                let pointee = match m.receiver.type_() {
+                    crate::core::types::ty::Type::Pointer(p) => *p.pointee.clone(),
+                    other => other.clone(),
+                };
+                let dest = func.new_local(m.type_.clone(), None);
+                let bb = func.get_block_mut(bb_id).unwrap();
+                bb.add_instruction(Instruction::PtrOffset {
+                    dest,
+                    base,
+                    offset,
+                    type_: pointee,
+                });
+                Operand::Local(dest)
+            }
+            HirExpr::MethodCall(m) if m.method == "len" && matches!(m.receiver.type_(), crate::core::types::ty::Type::String) => {
+                let source = self.lower_expr(func, &m.receiver, bb_id);
+                let dest = func.new_local(m.type_.clone(), None);
+                func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::StrLen { dest, source });
+                Operand::Local(dest)
+            }
+            HirExpr::MethodCall(m) if matches!(m.receiver.type_(), crate::core::types::ty::Type::TraitObject(_)) => {
+                let crate::core::types::ty::Type::TraitObject(t) = m.receiver.type_() else { unreachable!() };
+                let slot = self.traits.get(&t.trait_name).and_then(|slots| slots.iter().position(|n| *n == m.method)).unwrap_or(0);
+                let object = self.lower_expr(func, &m.receiver, bb_id);
+                let args: Vec<Operand> = m.args.iter().map(|a| self.lower_expr(func, a, bb_id)).collect();
+                let dest = m.type_.size_in_bytes().is_some().then(|| func.new_local(m.type_.clone(), None));
+                func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::DynCall {
+                    dest,
+                    object,
+                    slot,
+                    args,
+                    return_type: Some(m.type_.clone()),
+                });
+                dest.map_or(Operand::Constant(Constant::Null), Operand::Local)
+            }
             HirExpr::MethodCall(m) => {
                 let receiver = self.lower_expr(func, &m.receiver, bb_id);
                 let args: Vec<Operand> = m.args.iter().map(|a| self.lower_expr(func, a, bb_id)).collect();
@@ -443,13 +987,23 @@ impl MirLowerer {
                 // mthd calls r lowered as regulra clls w/ receiver as frst arg
                 let mut method_args = vec![receiver];
                 method_args.extend(args);
+                let impl_fn = match m.receiver.type_() {
+                    crate::core::types::ty::Type::Struct(s) => self.impl_methods.get(&(s.name.clone(), m.method.clone())).cloned(),
+                    crate::core::types::ty::Type::Pointer(p) => match &*p.pointee {
+                        crate::core::types::ty::Type::Struct(s) => self.impl_methods.get(&(s.name.clone(), m.method.clone())).cloned(),
+                        _ => None,
+                    },
+                    _ => None,
+                };
                 bb.add_instruction(Instruction::Call {
                     dest,
                     func: Operand::Function(crate::core::mir::operand::FunctionRef {
-                        name: format!("{}.{}", "method", m.method),
+                        name: impl_fn.unwrap_or_else(|| format!("{}.{}", "method", m.method)),
                     }),
                     args: method_args,
                     return_type: Some(m.type_.clone()),
+                    fixed_args: None,
+                    conv: Default::default(),
                 });
                 if let Some(d) = dest {
                     Operand::Local(d)
@@ -458,95 +1012,10 @@ impl MirLowerer {
                 }
             }
             HirExpr::Index(i) => {
-                let array = self.lower_expr(func, &i.array, bb_id);
-                let index = self.lower_expr(func, &i.index, bb_id);
-                
-                // get array type 2 chk bounds
-                let array_type = i.array.type_();
-                if let crate::core::types::ty::Type::Array(arr) = array_type {
-                    // runtime bounds chk 4 non-const indices
-                    // if const index bounds alrdy chkd at compile time
-                    let is_constant = matches!(*i.index, HirExpr::Literal(_) | HirExpr::Comptime(_));
-                    
-                    if !is_constant && arr.size > 0 {
-                        // gen bounds chk: if index >= array_size branch 2 err block
-                        // crt err and continue blocks first
-                        let error_bb_id = func.new_block();
-                        let continue_bb_id = func.new_block();
-                        let merge_bb_id = func.new_block();
-                        
-                        // cmp index w/ array size
-                        let size_operand = Operand::Constant(Constant::Int(arr.size as i64));
-                        let cmp_dest = func.new_local(crate::core::types::ty::Type::Primitive(
-                            crate::core::types::primitive::PrimitiveType::Bool
-                        ), None);
-                        
-                        // chk if index >= array_size
-                        let bb = func.get_block_mut(bb_id).unwrap();
-                        bb.add_instruction(Instruction::Ge {
-                            dest: cmp_dest,
-                            left: index.clone(),
-                            right: size_operand,
-                        });
-                        
-                        // branch: if index >= size go 2 err block else continue
-                        bb.add_instruction(Instruction::Br {
-                            condition: Operand::Local(cmp_dest),
-                            then_bb: error_bb_id,
-                            else_bb: continue_bb_id,
-                        });
-                        
-                        // err block: gen null val 4 out of bounds
-                        let error_val = func.new_local(i.type_.clone(), None);
-                        let error_bb = func.get_block_mut(error_bb_id).unwrap();
-                        // load null as err indicator (cld be improved w/ actual panic fn)
-                        error_bb.add_instruction(Instruction::Load {
-                            dest: error_val,
-                            source: Operand::Constant(Constant::Null),
-                            type_: i.type_.clone(),
-                        });
-                        error_bb.add_instruction(Instruction::Jump {
-                            target: merge_bb_id,
-                        });
-                        
-                        // continue block: normal array access
-                        let valid_dest = func.new_local(i.type_.clone(), None);
-                        let continue_bb = func.get_block_mut(continue_bb_id).unwrap();
-                        continue_bb.add_instruction(Instruction::Gep {
-                            dest: valid_dest,
-                            base: array,
-                            index,
-                            type_: i.type_.clone(),
-                        });
-                        continue_bb.add_instruction(Instruction::Jump {
-                            target: merge_bb_id,
-                        });
-                        
-                        // merge block: use phi node 2 merge results frm both paths
-                        let phi_dest = func.new_local(i.type_.clone(), None);
-                        let merge_bb = func.get_block_mut(merge_bb_id).unwrap();
-                        merge_bb.add_instruction(Instruction::Phi {
-                            dest: phi_dest,
-                            type_: i.type_.clone(),
-                            incoming: vec![
-                                (Operand::Local(error_val), error_bb_id),
-                                (Operand::Local(valid_dest), continue_bb_id),
-                            ],
-                        });
-                        
-                        return Operand::Local(phi_dest);
-                    }
-                }
-                
-                // normal array access (const index or no bounds chk needed)
+                let pointer = self.index_pointer(func, i, bb_id);
                 let dest = func.new_local(i.type_.clone(), None);
                 let bb = func.get_block_mut(bb_id).unwrap();
-                bb.add_instruction(Instruction::Gep {
-                    dest,
-                    base: array,
-                    index,
-                    type_: i.type_.clone(),
-                });
+                bb.add_instruction(Instruction::Load { dest, source: pointer, type_: i.type_.clone() });
                 Operand::Local(dest)
             }
             HirExpr::FieldAccess(f) => {
@@ -590,7 +1059,7 @@ impl MirLowerer {
                     crate::core::types::ty::Type::Pointer(p) => {
                         // handle ptr field accss: ptrvalue or ptrexists?
                         if f.field == "value" {
-                            // drfrnc ptr
+                            self.check_not_null(func, &object, p.nullable, f.span, bb_id);
                             let bb = func.get_block_mut(bb_id).unwrap();
                             bb.add_instruction(Instruction::Load {
                                 dest,
@@ -689,6 +1158,8 @@ impl MirLowerer {
                 });
                 bb.add_successor(then_bb);
                 bb.add_successor(else_bb);
+                // whatever uses the value goes in2 the merge block
+                self.resume_in(bb_id, merge_bb);
 
                 func.get_block_mut(then_bb).unwrap().add_predecessor(bb_id);
                 let then_val = self.lower_expr(func, &i.then_branch, then_bb);
@@ -720,9 +1191,24 @@ impl MirLowerer {
                 Operand::Local(dest)
             }
             HirExpr::Assignment(a) => {
-                let target = self.lower_expr(func, &a.target, bb_id);
+                // an element is stored thru its address, not its value
+                let target = match &*a.target {
+                    HirExpr::Index(i) => self.index_pointer(func, i, bb_id),
+                    HirExpr::FieldAccess(f) if Self::is_deref(f) => {
+                        let pointer = self.lower_expr(func, &f.object, bb_id);
+                        let nullable = matches!(f.object.type_(), crate::core::types::ty::Type::Pointer(p) if p.nullable);
+                        self.check_not_null(func, &pointer, nullable, f.span, bb_id);
+                        pointer
+                    }
+                    target => self.lower_expr(func, target, bb_id),
+                };
+                let element = match &*a.target {
+                    HirExpr::Index(_) => true,
+                    HirExpr::FieldAccess(f) => Self::is_deref(f),
+                    _ => false,
+                };
                 // try 2 store directly 2 target if value is simple op
-                if let Some(target_local) = self.get_local_from_operand(&target) {
+                if let Some(target_local) = self.get_local_from_operand(&target).filter(|_| !element) {
                     // if target is a local we can store directly
                     if let HirExpr::Binary(b) = &*a.value {
                         // lower binary op directly 2 target local
@@ -732,6 +1218,11 @@ impl MirLowerer {
                             let bb = func.get_block_mut(bb_id).unwrap();
                             
                             let inst = match b.op {
+                                HirBinaryOp::Add if matches!(b.type_, crate::core::types::ty::Type::String) => Instruction::StrConcat {
+                                    dest: target_local,
+                                    left,
+                                    right,
+                                },
                                 HirBinaryOp::Add => Instruction::Add {
                                     dest: target_local,
                                     left,
@@ -796,11 +1287,64 @@ impl MirLowerer {
                 }
                 // fallback: normal lowering w/ store
                 let value = self.lower_expr(func, &a.value, bb_id);
+                let owning = self.owning(func, &a.value);
+                self.consume(func, &a.value);
+                // the allocation the local owned goes w/ the value it held
+                let target_local = match &*a.target {
+                    HirExpr::Variable(v) => self.named_local(func, &v.name),
+                    _ => None,
+                };
+                if let Some(local) = target_local {
+                    if self.owners.live.remove(&local) {
+                        Self::free_local(func, bb_id, local);
+                    }
+                    if owning {
+                        self.owners.live.insert(local);
+                    }
+                }
                 let bb = func.get_block_mut(bb_id).unwrap();
-                bb.add_instruction(Instruction::Store {
-                    dest: target,
-                    source: value,
-                    type_: a.type_.clone(),
+                match self.get_local_from_operand(&target) {
+                    // a scalar or `ref` local holds its value, a store would go thru it. struct +
+                    // array locals r addresses + r stored thru
+                    Some(dest) if !element && !matches!(a.target.type_(), crate::core::types::ty::Type::Struct(_) | crate::core::types::ty::Type::Array(_)) => {
+                        bb.add_instruction(Instruction::Copy { dest, source: value, type_: a.target.type_().clone() });
+                    }
+                    _ => bb.add_instruction(Instruction::Store {
+                        dest: target,
+                        source: value,
+                        type_: a.type_.clone(),
+                    }),
+                }
+                Operand::Constant(Constant::Null)
+            }
+            HirExpr::New(n) => {
+                let value = self.lower_expr(func, &n.value, bb_id);
+                self.consume(func, &n.value);
+                let value_type = n.value.type_().clone();
+                let size = crate::core::types::composite::layout_size(&value_type);
+                let pointer = func.new_local(n.type_.clone(), None);
+                let bb = func.get_block_mut(bb_id).unwrap();
+                bb.add_instruction(Instruction::Call {
+                    dest: Some(pointer),
+                    func: Operand::Function(crate::core::mir::operand::FunctionRef { name: crate::core::mir::ALLOC_FN.to_string() }),
+                    args: vec![Operand::Constant(Constant::Int(size as i64))],
+                    return_type: Some(n.type_.clone()),
+                    fixed_args: None,
+                    conv: Default::default(),
+                });
+                bb.add_instruction(Instruction::Store { dest: Operand::Local(pointer), source: value, type_: value_type });
+                Operand::Local(pointer)
+            }
+            HirExpr::Delete(d) => {
+                let pointer = self.lower_expr(func, &d.pointer, bb_id);
+                self.consume(func, &d.pointer);
+                func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Call {
+                    dest: None,
+                    func: Operand::Function(crate::core::mir::operand::FunctionRef { name: crate::core::mir::FREE_FN.to_string() }),
+                    args: vec![pointer],
+                    return_type: None,
+                    fixed_args: None,
+                    conv: Default::default(),
                 });
                 Operand::Constant(Constant::Null)
             }
@@ -817,8 +1361,8 @@ impl MirLowerer {
                     HirExpr::Variable(v) => {
                         // variables r already addresses
                         // find the local
-                        if let Some(local_info) = func.locals.iter().find(|l| l.name.as_ref() == Some(&v.name)) {
-                            Operand::Local(local_info.local)
+                        if let Some(local) = self.named_local(func, &v.name) {
+                            Operand::Local(local)
                         } else {
                             // crt a new local 4 the var
                             let local = func.new_local(v.type_.clone(), Some(v.name.clone()));
@@ -941,9 +1485,15 @@ impl MirLowerer {
                     });
                 }
                 
-                // lower the closure body
+                // lower the closure body, its locals own their allocations apart frm the fn's
                 let entry_block = closure_func.entry_block;
-                self.lower_stmts(&mut closure_func, &c.body, entry_block);
+                let outer = std::mem::take(&mut self.owners);
+                let defers = std::mem::take(&mut self.defers);
+                let replaying = std::mem::replace(&mut self.replaying, false);
+                self.lower_scope(&mut closure_func, &c.body, entry_block);
+                self.owners = outer;
+                self.defers = defers;
+                self.replaying = replaying;
                 
                 // add the closure fn 2 the fn list
                 self.functions.push(closure_func);
@@ -972,7 +1522,7 @@ impl MirLowerer {
                         HirLiteralKind::Float(n) => Constant::Float(*n),
                         HirLiteralKind::Bool(b) => Constant::Bool(*b),
                         HirLiteralKind::Char(c) => Constant::Char(*c),
-                        HirLiteralKind::String(s) => Constant::String(s.clone()),
+                        HirLiteralKind::String(s) => return Self::string_local(func, s, bb_id),
                     };
                     Operand::Constant(constant)
                 } else if let HirExpr::Literal(l) = &*c.expr {
@@ -982,7 +1532,7 @@ impl MirLowerer {
                         HirLiteralKind::Float(n) => Constant::Float(*n),
                         HirLiteralKind::Bool(b) => Constant::Bool(*b),
                         HirLiteralKind::Char(c) => Constant::Char(*c),
-                        HirLiteralKind::String(s) => Constant::String(s.clone()),
+                        HirLiteralKind::String(s) => return Self::string_local(func, s, bb_id),
                     };
                     Operand::Constant(constant)
                 } else {
@@ -992,6 +1542,7 @@ impl MirLowerer {
                 }
             }
             HirExpr::ArrayLiteral(a) => {
+                a.elements.iter().for_each(|element| self.consume(func, element));
                 // array literals need 2 be allocated and initialized
                 // 4 now we'll create a temporary local and store each element
                 let array_type = match &a.type_ {
@@ -1005,7 +1556,37 @@ impl MirLowerer {
                 // allocate local 4 the array
                 let array_local = func.new_local(a.type_.clone(), None);
                 let array_operand = Operand::Local(array_local);
-                
+
+                // repeat initializer: one fill instead of a store per element
+                if let Some(count) = a.repeat {
+                    let value = match a.elements.first() {
+                        Some(element) => self.lower_expr(func, element, bb_id),
+                        None => Operand::Constant(Constant::Int(0)),
+                    };
+                    let bb = func.get_block_mut(bb_id).unwrap();
+                    bb.add_instruction(Instruction::Alloca {
+                        dest: array_local,
+                        type_: a.type_.clone(),
+                    });
+                    bb.add_instruction(Instruction::Memset {
+                        dest: array_operand.clone(),
+                        value,
+                        count,
+                        type_: array_type.element.as_ref().clone(),
+                    });
+                    return array_operand;
+                }
+
+                if let Some(folded) = Self::fold_aggregate(expr) {
+                    let bb = func.get_block_mut(bb_id).unwrap();
+                    bb.add_instruction(Instruction::Copy {
+                        dest: array_local,
+                        source: Operand::Constant(folded),
+                        type_: a.type_.clone(),
+                    });
+                    return array_operand;
+                }
+
                 // store each element
                 for (i, element) in a.elements.iter().enumerate() {
                     let element_val = self.lower_expr(func, element, bb_id);
@@ -1032,10 +1613,402 @@ impl MirLowerer {
                 
                 array_operand
             }
+            HirExpr::StructLiteral(l) => {
+                l.fields.iter().for_each(|(_, value)| self.consume(func, value));
+                let struct_local = func.new_local(l.type_.clone(), None);
+                let struct_operand = Operand::Local(struct_local);
+                if let Some(folded) = Self::fold_aggregate(expr) {
+                    let bb = func.get_block_mut(bb_id).unwrap();
+                    bb.add_instruction(Instruction::Copy {
+                        dest: struct_local,
+                        source: Operand::Constant(folded),
+                        type_: l.type_.clone(),
+                    });
+                    return struct_operand;
+                }
+
+                // runtime fields - alloca then store each field thru its gep
+                func.basic_blocks[bb_id].instructions.push(Instruction::Alloca {
+                    dest: struct_local,
+                    type_: l.type_.clone(),
+                });
+                for (field_idx, (_, value)) in l.fields.iter().enumerate() {
+                    let field_val = self.lower_expr(func, value, bb_id);
+                    // declared type so eg `null` gets the fields ptr type
+                    let field_type = match &l.type_ {
+                        crate::core::types::ty::Type::Struct(s) if field_idx < s.fields.len() => s.fields[field_idx].type_.clone(),
+                        _ => value.type_().clone(),
+                    };
+                    let gep_dest = func.new_local(
+                        crate::core::types::ty::Type::Pointer(
+                            crate::core::types::pointer::PointerType::new(field_type.clone(), false)
+                        ),
+                        None,
+                    );
+                    let bb = func.get_block_mut(bb_id).unwrap();
+                    bb.add_instruction(Instruction::Gep {
+                        dest: gep_dest,
+                        base: struct_operand.clone(),
+                        index: Operand::Constant(Constant::Int(field_idx as i64)),
+                        type_: field_type.clone(),
+                    });
+                    bb.add_instruction(Instruction::Store {
+                        dest: Operand::Local(gep_dest),
+                        source: field_val,
+                        type_: field_type,
+                    });
+                }
+                struct_operand
+            }
+            HirExpr::Variant(v) => {
+                let fields = v.fields.iter().map(|f| self.lower_expr(func, f, bb_id)).collect();
+                let dest = func.new_local(v.type_.clone(), None);
+                func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::MakeVariant {
+                    dest,
+                    type_: v.type_.clone(),
+                    tag: v.tag,
+                    fields,
+                });
+                Operand::Local(dest)
+            }
+            HirExpr::Try(t) => {
+                let type_ = t.expr.type_().clone();
+                let result = self.lower_expr(func, &t.expr, bb_id);
+                let (ok_tag, err_tag) = match &type_ {
+                    crate::core::types::ty::Type::Enum(e) => (e.tag(EnumType::OK).unwrap_or(0), e.tag(EnumType::ERR).unwrap_or(1)),
+                    _ => (0, 1),
+                };
+                let tag = func.new_local(crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Int), None);
+                let err_bb = func.new_block();
+                let ok_bb = func.new_block();
+                let bb = func.get_block_mut(bb_id).unwrap();
+                bb.add_instruction(Instruction::EnumTag { dest: tag, source: result.clone() });
+                bb.add_instruction(Instruction::Switch { value: Operand::Local(tag), arms: vec![(err_tag as i64, err_bb)], default: ok_bb });
+                bb.add_successor(err_bb);
+                bb.add_successor(ok_bb);
+                func.get_block_mut(err_bb).unwrap().add_predecessor(bb_id);
+                func.get_block_mut(ok_bb).unwrap().add_predecessor(bb_id);
+                self.resume_in(bb_id, ok_bb);
+
+                // the error goes back out as the enclosing fn's own `Err`
+                if let Some(crate::core::types::ty::Type::Enum(ret)) = func.return_type.clone() {
+                    let error_type = ret.fallible_parts().map(|(_, error)| error.clone()).unwrap_or(crate::core::types::ty::Type::Error);
+                    let error = func.new_local(error_type, None);
+                    let wrapped = func.new_local(crate::core::types::ty::Type::Enum(ret.clone()), None);
+                    let bb = func.get_block_mut(err_bb).unwrap();
+                    bb.add_instruction(Instruction::VariantField { dest: error, source: result.clone(), type_: type_.clone(), tag: err_tag, index: 0 });
+                    bb.add_instruction(Instruction::MakeVariant {
+                        dest: wrapped,
+                        type_: crate::core::types::ty::Type::Enum(ret.clone()),
+                        tag: ret.tag(EnumType::ERR).unwrap_or(1),
+                        fields: vec![Operand::Local(error)],
+                    });
+                    self.lower_return(func, Some(Operand::Local(wrapped)), err_bb);
+                }
+
+                let value = func.new_local(t.type_.clone(), None);
+                func.get_block_mut(ok_bb).unwrap().add_instruction(Instruction::VariantField {
+                    dest: value,
+                    source: result,
+                    type_,
+                    tag: ok_tag,
+                    index: 0,
+                });
+                Operand::Local(value)
+            }
             HirExpr::Null => Operand::Constant(Constant::Null),
         }
     }
 
+    /// `p.value` on a pointer, which reads or writes what it points 2
+    fn is_deref(f: &HirFieldAccessExpr) -> bool {
+        f.field == "value" && matches!(f.object.type_(), crate::core::types::ty::Type::Pointer(_))
+    }
+
+    /// a `ref?` is chkd b4 its dereferenced in case the chkr's proof has a gap
+    fn check_not_null(&mut self, func: &mut MirFunction, pointer: &Operand, nullable: bool, span: codespan::Span, bb_id: usize) {
+        if !nullable || !self.null_checks {
+            return;
+        }
+        let is_null = func.new_local(
+            crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool),
+            None,
+        );
+        let bb = func.get_block_mut(bb_id).unwrap();
+        bb.add_instruction(Instruction::Eq { dest: is_null, left: pointer.clone(), right: Operand::Constant(Constant::Null) });
+        self.panic_if(func, bb_id, Operand::Local(is_null), "null pointer dereference", span);
+    }
+
+    /// the address of `i`'s element, after a bounds chk that panics when the index isnt known 2 b in range
+    fn index_pointer(&mut self, func: &mut MirFunction, i: &HirIndexExpr, bb_id: usize) -> Operand {
+        let array = self.lower_expr(func, &i.array, bb_id);
+        let index = self.lower_expr(func, &i.index, bb_id);
+        
+        // get array type 2 chk bounds
+        let array_type = i.array.type_();
+        if let crate::core::types::ty::Type::Array(arr) = array_type {
+            // runtime bounds chk 4 non-const indices
+            // if const index bounds alrdy chkd at compile time
+            let is_constant = matches!(*i.index, HirExpr::Literal(_) | HirExpr::Comptime(_));
+            
+            if !is_constant && arr.size > 0 {
+                // index < 0 or index >= size panics, the access goes on in a new block
+                let bool_type = crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool);
+                let negative = func.new_local(bool_type.clone(), None);
+                let past_end = func.new_local(bool_type.clone(), None);
+                let out_of_bounds = func.new_local(bool_type, None);
+                let bb = func.get_block_mut(bb_id).unwrap();
+                bb.add_instruction(Instruction::Lt { dest: negative, left: index.clone(), right: Operand::Constant(Constant::Int(0)) });
+                bb.add_instruction(Instruction::Ge {
+                    dest: past_end,
+                    left: index.clone(),
+                    right: Operand::Constant(Constant::Int(arr.size as i64)),
+                });
+                bb.add_instruction(Instruction::Or { dest: out_of_bounds, left: Operand::Local(negative), right: Operand::Local(past_end) });
+                let message = format!("index out of bounds for length {}", arr.size);
+                self.panic_if(func, bb_id, Operand::Local(out_of_bounds), &message, i.span);
+            }
+        }
+        
+        // the address itself, past the chk when there is 1
+        let dest = func.new_local(
+            crate::core::types::ty::Type::Pointer(crate::core::types::pointer::PointerType::new(i.type_.clone(), false)),
+            None,
+        );
+        let bb = func.get_block_mut(bb_id).unwrap();
+        bb.add_instruction(Instruction::Gep {
+            dest,
+            base: array,
+            index,
+            type_: i.type_.clone(),
+        });
+        Operand::Local(dest)
+    }
+
+    /// the `panic(msg)` builtin, nothing can define a fn of that name
+    fn is_panic(c: &HirCallExpr) -> bool {
+        matches!(&*c.callee, HirExpr::Variable(v) if v.name == "panic") && c.args.len() == 1
+    }
+
+    /// a call 2 the prelude's `assert(cond)`
+    fn is_assert(c: &HirCallExpr) -> bool {
+        matches!(&*c.callee, HirExpr::Variable(v) if v.name == "assert" && matches!(v.type_, crate::core::types::ty::Type::Function(_)))
+            && c.args.len() == 1
+    }
+
+    /// `emerald_panic(message, file, line)` w/ the location of `span`
+    fn call_panic(&self, func: &mut MirFunction, bb_id: usize, message: Operand, span: codespan::Span) {
+        let (file, line) = match &self.source {
+            Some(source) => (source.file.clone(), source.line(span)),
+            None => ("<unknown>".to_string(), 0),
+        };
+        let string = crate::core::types::ty::Type::String;
+        let file_local = func.new_local(string.clone(), None);
+        let bb = func.get_block_mut(bb_id).unwrap();
+        bb.add_instruction(Instruction::Copy { dest: file_local, source: Operand::Constant(Constant::String(file)), type_: string });
+        bb.add_instruction(Instruction::Call {
+            dest: None,
+            func: Operand::Function(crate::core::mir::operand::FunctionRef { name: crate::core::mir::PANIC_FN.to_string() }),
+            args: vec![message, Operand::Local(file_local), Operand::Constant(Constant::Int(line))],
+            return_type: None,
+            fixed_args: None,
+            conv: Default::default(),
+        });
+    }
+
+    /// panics w/ `message` when `failed` is true. the block ends in the branch, the
+    /// instructions the caller adds after it r moved 2 the block the chk passes in2
+    fn panic_if(&mut self, func: &mut MirFunction, bb_id: usize, failed: Operand, message: &str, span: codespan::Span) {
+        let panic_bb = func.new_block();
+        let ok_bb = func.new_block();
+        func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Br { condition: failed, then_bb: panic_bb, else_bb: ok_bb });
+        self.resume_in(bb_id, ok_bb);
+
+        let string = crate::core::types::ty::Type::String;
+        let message_local = func.new_local(string.clone(), None);
+        func.get_block_mut(panic_bb).unwrap().add_instruction(Instruction::Copy {
+            dest: message_local,
+            source: Operand::Constant(Constant::String(message.to_string())),
+            type_: string,
+        });
+        self.call_panic(func, panic_bb, Operand::Local(message_local), span);
+        // emerald_panic doesnt return, the jump only gives the block a terminator
+        func.get_block_mut(panic_bb).unwrap().add_instruction(Instruction::Jump { target: ok_bb });
+    }
+
+    /// an expr ended `bb_id` w/ a branch, what its caller goes on adding 2 `bb_id` belongs in `resume`
+    fn resume_in(&mut self, bb_id: usize, resume: usize) {
+        self.resumes.push((bb_id, resume));
+    }
+
+    /// once a stmt is lowered, moves evry instruction after a branch its exprs ended a block w/ 2
+    /// the block that branch resumes in. `pending` is how many resumes there were b4 the stmt, the
+    /// ones b4 it belong 2 an expr still being lowered. returns where `current` goes on
+    fn move_resumed(&mut self, func: &mut MirFunction, pending: usize, current: usize) -> usize {
+        // where each block's tail is now, a 2nd chk in the same block follows the 1st
+        let mut moved: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
+        for (block, resume) in self.resumes.split_off(pending) {
+            let from = moved.get(&block).copied().unwrap_or(block);
+            let instructions = &mut func.basic_blocks[from].instructions;
+            let Some(end) = instructions.iter().position(Instruction::is_terminator) else { continue };
+            let tail = instructions.split_off(end + 1);
+            func.basic_blocks[resume].instructions.extend(tail);
+            moved.insert(block, resume);
+        }
+        // phis after a moved terminator now come frm the block it moved 2
+        for (&block, &to) in &moved {
+            for succ in func.basic_blocks[to].instructions.last().map(Instruction::successors).unwrap_or_default() {
+                for inst in &mut func.basic_blocks[succ].instructions {
+                    if let Instruction::Phi { incoming, .. } = inst {
+                        for (_, pred) in incoming.iter_mut().filter(|(_, pred)| *pred == block) {
+                            *pred = to;
+                        }
+                    }
+                }
+            }
+        }
+        moved.get(&current).copied().unwrap_or(current)
+    }
+
+    // builtin name if this call is a volatile access (user fns named the same r Function typed)
+    fn volatile_builtin(c: &HirCallExpr) -> Option<&'static str> {
+        let name = match &*c.callee {
+            HirExpr::Variable(v) if !matches!(v.type_, crate::core::types::ty::Type::Function(_)) => v.name.as_str(),
+            _ => return None,
+        };
+        match (name, c.args.len()) {
+            ("volatile_load", 1) => Some("volatile_load"),
+            ("volatile_store", 2) => Some("volatile_store"),
+            _ => None,
+        }
+    }
+
+    fn is_pointer_arithmetic(m: &HirMethodCallExpr) -> bool {
+        (m.method == "offset" || m.method == "add")
+            && m.args.len() == 1
+            && matches!(m.receiver.type_(), crate::core::types::ty::Type::Pointer(_))
+    }
+
+    // fold an array/struct literal whose leaves r all comptime-known
+    fn fold_aggregate(expr: &HirExpr) -> Option<Constant> {
+        match expr {
+            HirExpr::ArrayLiteral(a) if a.repeat.is_none() => {}
+            HirExpr::StructLiteral(_) => {}
+            _ => return None,
+        }
+        fold_hir_constant(expr).map(|value| value.to_constant())
+    }
+
+    // string literal copied in2 a temp, backends intern it once per distinct value
+    /// true if the scrutinee matches any of `patterns`, variant patterns test an enum's tag
+    fn arm_condition(&mut self, func: &mut MirFunction, scrutinee: &Operand, patterns: &[HirPattern], bb_id: usize) -> Operand {
+        let bool_type = crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Bool);
+        let mut cond: Option<Operand> = None;
+        for pattern in patterns {
+            let test = match pattern {
+                HirPattern::Literal(l) => {
+                    let value = self.lower_expr(func, &HirExpr::Literal(l.clone()), bb_id);
+                    let dest = func.new_local(bool_type.clone(), None);
+                    let left = scrutinee.clone();
+                    let inst = if matches!(l.type_, crate::core::types::ty::Type::String) {
+                        Instruction::StrEq { dest, left, right: value }
+                    } else {
+                        Instruction::Eq { dest, left, right: value }
+                    };
+                    func.get_block_mut(bb_id).unwrap().add_instruction(inst);
+                    Operand::Local(dest)
+                }
+                HirPattern::Range { start, end } => {
+                    let low = func.new_local(bool_type.clone(), None);
+                    let high = func.new_local(bool_type.clone(), None);
+                    let dest = func.new_local(bool_type.clone(), None);
+                    let bb = func.get_block_mut(bb_id).unwrap();
+                    bb.add_instruction(Instruction::Ge { dest: low, left: scrutinee.clone(), right: Operand::Constant(Constant::Int(*start)) });
+                    bb.add_instruction(Instruction::Le { dest: high, left: scrutinee.clone(), right: Operand::Constant(Constant::Int(*end)) });
+                    bb.add_instruction(Instruction::And { dest, left: Operand::Local(low), right: Operand::Local(high) });
+                    Operand::Local(dest)
+                }
+                HirPattern::Variant { tag, .. } => {
+                    let dest = func.new_local(bool_type.clone(), None);
+                    func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Eq {
+                        dest,
+                        left: scrutinee.clone(),
+                        right: Operand::Constant(Constant::Int(*tag as i64)),
+                    });
+                    Operand::Local(dest)
+                }
+            };
+            cond = Some(match cond {
+                Some(prev) => {
+                    let dest = func.new_local(bool_type.clone(), None);
+                    func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Or { dest, left: prev, right: test });
+                    Operand::Local(dest)
+                }
+                None => test,
+            });
+        }
+        cond.unwrap_or(Operand::Constant(Constant::Bool(false)))
+    }
+
+    /// case values of evry arm, None if a pattern needs more than an equality test (ranges, strings)
+    fn switch_cases(arms: &[HirMatchArm]) -> Option<Vec<Vec<i64>>> {
+        arms.iter()
+            .map(|arm| {
+                arm.patterns
+                    .iter()
+                    .map(|pattern| match pattern {
+                        HirPattern::Literal(l) => match l.kind {
+                            HirLiteralKind::Int(n) => Some(n),
+                            HirLiteralKind::Char(c) => Some(c as i64),
+                            HirLiteralKind::Bool(b) => Some(b as i64),
+                            _ => None,
+                        },
+                        HirPattern::Variant { tag, .. } => Some(*tag as i64),
+                        HirPattern::Range { .. } => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// the fields a variant pattern binds, read at the start of its arm's body
+    fn bind_variant_fields(func: &mut MirFunction, scrutinee: &Operand, type_: &crate::core::types::ty::Type, patterns: &[HirPattern], body_bb: usize) {
+        for pattern in patterns {
+            let HirPattern::Variant { tag, bindings } = pattern else { continue };
+            for (index, name, field_type) in bindings {
+                let dest = func.new_local(field_type.clone(), Some(name.clone()));
+                func.get_block_mut(body_bb).unwrap().add_instruction(Instruction::VariantField {
+                    dest,
+                    source: scrutinee.clone(),
+                    type_: type_.clone(),
+                    tag: *tag,
+                    index: *index,
+                });
+            }
+        }
+    }
+
+    /// jump `from` -> `target` unless `from` already ended in a ret/branch
+    fn fall_through(&self, func: &mut MirFunction, from: usize, target: usize) {
+        if func.block_has_terminator(from) {
+            return;
+        }
+        let bb = func.get_block_mut(from).unwrap();
+        bb.add_instruction(Instruction::Jump { target });
+        bb.add_successor(target);
+        func.get_block_mut(target).unwrap().add_predecessor(from);
+    }
+
+    fn string_local(func: &mut MirFunction, s: &str, bb_id: usize) -> Operand {
+        let dest = func.new_local(crate::core::types::ty::Type::String, None);
+        func.get_block_mut(bb_id).unwrap().add_instruction(Instruction::Copy {
+            dest,
+            source: Operand::Constant(Constant::String(s.to_string())),
+            type_: crate::core::types::ty::Type::String,
+        });
+        Operand::Local(dest)
+    }
+
     // helper 2 extract local from operand if it's a local
     fn get_local_from_operand(&self, op: &Operand) -> Option<Local> {
         if let Operand::Local(l) = op {
@@ -0,0 +1,141 @@
+// compile emerald snippets held in a string, the entry point of the web playground
+// only the frontend, MIR + the MIR interpreter run here so it builds 4 wasm32-unknown-unknown w/o llvm-sys:
+//   cargo rustc --lib --release --no-default-features --features playground --target wasm32-unknown-unknown --crate-type cdylib
+// no Compiler either, std::time::Instant panics on wasm32-unknown-unknown
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::{InterpBackendFactory, Interpreter};
+use crate::backend::ports::ExecutionEngine;
+use crate::cli::error_display::convert_diagnostic;
+use crate::core::mir::MirFunction;
+use crate::core::optimizations::{HirOptimizer, MirOptimizer};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan_reporting::term::termcolor::NoColor;
+use codespan_reporting::term::{self, Config};
+
+/// file name diagnostics point at
+pub const PLAYGROUND_FILE: &str = "playground.em";
+
+#[derive(Debug, Clone)]
+pub struct PlaygroundResult {
+    pub success: bool,
+    /// rendered w/o color, warnings included on success
+    pub diagnostics: String,
+    pub output: String,
+    /// what main returned, None when it wasnt run or trapped
+    pub exit_code: Option<i32>,
+}
+
+/// run `source` thru the frontend + MIR pipeline, output lists the lowered fns
+pub fn compile_str(source: &str) -> PlaygroundResult {
+    let (reporter, functions) = lower(source);
+    let success = !reporter.has_errors();
+    PlaygroundResult {
+        success,
+        diagnostics: render_diagnostics(&reporter),
+        output: if success { summary(&functions) } else { String::new() },
+        exit_code: None,
+    }
+}
+
+/// compile `source` + run its main on the interp backend, output is what it printed
+pub fn run_str(source: &str) -> PlaygroundResult {
+    let (reporter, functions) = lower(source);
+    let mut result = PlaygroundResult {
+        success: !reporter.has_errors(),
+        diagnostics: render_diagnostics(&reporter),
+        output: String::new(),
+        exit_code: None,
+    };
+    if !result.success {
+        return result;
+    }
+
+    let factory = InterpBackendFactory;
+    let codegen = factory.create_codegen().map_err(|e| e.to_string());
+    let module = match codegen.and_then(|mut codegen| codegen.generate_from_mir(&functions).map_err(|e| e.to_string())) {
+        Ok(module) => module,
+        Err(e) => {
+            result.success = false;
+            result.diagnostics.push_str(&format!("error: {}\n", e));
+            return result;
+        }
+    };
+    // the engine the factory makes, held concretely so its output can be read back
+    let mut engine = Interpreter::new();
+    engine.capture_output();
+    let run = engine.run_main(&module);
+    result.output = String::from_utf8_lossy(&engine.take_output()).to_string();
+    match run {
+        Ok(code) => result.exit_code = Some(code),
+        Err(e) => {
+            result.success = false;
+            result.diagnostics.push_str(&format!("error: {}\n", e));
+        }
+    }
+    result
+}
+
+fn lower(source: &str) -> (Reporter, Vec<MirFunction>) {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file(PLAYGROUND_FILE.to_string(), source.to_string());
+
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let mut functions = Vec::new();
+    if !reporter.has_errors() {
+        let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+        if !reporter.has_errors() {
+            let mut hir = HirLowerer::new(symbol_table).lower(&ast);
+            HirOptimizer::new().optimize(&mut hir);
+            functions = MirLowerer::new().lower(&hir);
+            MirOptimizer::new().optimize_all(&mut functions);
+        }
+    }
+
+    (reporter, functions)
+}
+
+fn render_diagnostics(reporter: &Reporter) -> String {
+    let mut writer = NoColor::new(Vec::new());
+    let config = Config::default();
+    for diag in reporter.diagnostics() {
+        // writing 2 a vec cant fail
+        let _ = term::emit(&mut writer, &config, reporter.files(), &convert_diagnostic(diag));
+    }
+    String::from_utf8_lossy(&writer.into_inner()).to_string()
+}
+
+/// one line per lowered fn
+fn summary(functions: &[MirFunction]) -> String {
+    let mut out = String::new();
+    for func in functions {
+        let blocks = func.basic_blocks.len();
+        out.push_str(&format!(
+            "fn {}: {} param(s), {} block{}\n",
+            func.name,
+            func.params.len(),
+            blocks,
+            if blocks == 1 { "" } else { "s" }
+        ));
+    }
+    out
+}
+
+/// js entry point, diagnostics, what the program printed then main's exit code
+#[cfg(feature = "playground")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub fn compile_and_run_str(source: &str) -> String {
+    let result = run_str(source);
+    let mut out = format!("{}{}", result.diagnostics, result.output);
+    if let Some(code) = result.exit_code {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&format!("exited with code {}\n", code));
+    }
+    out
+}
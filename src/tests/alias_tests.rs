@@ -0,0 +1,195 @@
+use crate::core::mir::alias::{AliasAnalysis, AliasResult, Base};
+use crate::core::mir::{Constant, FunctionRef, Instruction, Local, MirFunction, Operand, Param};
+use crate::core::optimizations::MirOptimizer;
+use crate::core::types::pointer::PointerType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+
+fn int(n: i64) -> Operand {
+    Operand::Constant(Constant::Int(n))
+}
+
+fn local(l: Local) -> Operand {
+    Operand::Local(l)
+}
+
+/// `f(p : ref int, i : int)` w/ an int slot `a`, an int[4] slot `arr` + `arr[0]`, `arr[1]`, `arr[i]`
+struct Slots {
+    func: MirFunction,
+    p: Local,
+    a: Local,
+    arr: Local,
+    elements: [Local; 3],
+}
+
+fn slots() -> Slots {
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let array = Type::Array(crate::core::types::composite::ArrayType { element: Box::new(ty.clone()), size: 4 });
+    let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+    let pointer = Type::Pointer(PointerType::ref_(ty.clone()));
+    let p = func.new_local(pointer.clone(), Some("p".to_string()));
+    func.params.push(Param { name: "p".to_string(), type_: pointer.clone(), local: p });
+    let a = func.new_local(pointer.clone(), None);
+    let arr = func.new_local(array.clone(), None);
+    let i = func.new_local(ty.clone(), Some("i".to_string()));
+    func.params.push(Param { name: "i".to_string(), type_: ty.clone(), local: i });
+    let elements = [(); 3].map(|_| func.new_local(pointer.clone(), None));
+    func.basic_blocks[0].instructions = vec![
+        Instruction::Alloca { dest: a, type_: ty.clone() },
+        Instruction::Alloca { dest: arr, type_: array },
+        Instruction::Gep { dest: elements[0], base: local(arr), index: int(0), type_: ty.clone() },
+        Instruction::Gep { dest: elements[1], base: local(arr), index: int(1), type_: ty.clone() },
+        Instruction::Gep { dest: elements[2], base: local(arr), index: local(i), type_: ty },
+    ];
+    Slots { func, p, a, arr, elements }
+}
+
+fn store(dest: Local) -> Instruction {
+    Instruction::Store { dest: local(dest), source: int(2), type_: Type::Primitive(PrimitiveType::Int) }
+}
+
+fn load(dest: Local, source: Local) -> Instruction {
+    Instruction::Load { dest, source: local(source), type_: Type::Primitive(PrimitiveType::Int) }
+}
+
+fn call(args: Vec<Operand>) -> Instruction {
+    let func = Operand::Function(FunctionRef { name: "g".to_string() });
+    Instruction::Call { dest: None, func, args, return_type: None, fixed_args: None, conv: Default::default() }
+}
+
+#[test]
+fn test_alias_analysis_separates_slots_fields_and_params() {
+    let Slots { mut func, p, a, arr, elements: [e0, e1, ei] } = slots();
+    func.basic_blocks[0].instructions.push(Instruction::Ret { value: None });
+    let aa = AliasAnalysis::new(&func);
+    assert_eq!(aa.place(&local(e1)).base, Base::Object(arr.id));
+    assert_eq!(aa.place(&local(e1)).path, Some(vec![1]));
+    assert_eq!(aa.place(&local(ei)).path, None);
+
+    assert_eq!(aa.alias(&local(a), &local(arr)), AliasResult::No);
+    assert_eq!(aa.alias(&local(e0), &local(e1)), AliasResult::No);
+    assert_eq!(aa.alias(&local(e0), &local(e0)), AliasResult::Must);
+    // the element at `i` could be either, the array holds both
+    assert_eq!(aa.alias(&local(ei), &local(e1)), AliasResult::May);
+    assert_eq!(aa.alias(&local(arr), &local(e1)), AliasResult::May);
+    // a param cant point 2 a slot the fn made + never gave away
+    assert_eq!(aa.alias(&local(p), &local(a)), AliasResult::No);
+    assert_eq!(aa.alias(&local(p), &local(p)), AliasResult::Must);
+
+    let store = Instruction::Store { dest: local(e0), source: int(1), type_: Type::Primitive(PrimitiveType::Int) };
+    assert!(aa.may_write(&store, &local(arr)));
+    assert!(!aa.may_write(&store, &local(e1)));
+    assert!(!aa.may_write(&call(vec![]), &local(a)));
+    assert!(aa.may_write(&call(vec![]), &local(p)));
+    assert!(aa.may_read(&Instruction::Ret { value: None }, &local(p)));
+    assert!(!aa.may_read(&Instruction::Ret { value: None }, &local(a)));
+}
+
+#[test]
+fn test_alias_analysis_tracks_escaping_slots() {
+    let Slots { mut func, p, a, arr, elements: [_, e1, _] } = slots();
+    // `a` is passed 2 a call, `arr[1]` is stored thru the param
+    func.basic_blocks[0].instructions.extend([
+        call(vec![local(a)]),
+        Instruction::Store { dest: local(p), source: local(e1), type_: Type::Pointer(PointerType::ref_(Type::Primitive(PrimitiveType::Int))) },
+        Instruction::Ret { value: None },
+    ]);
+    let aa = AliasAnalysis::new(&func);
+    assert!(aa.escapes(a.id));
+    assert!(aa.escapes(arr.id));
+    assert!(!aa.is_local(&local(a)));
+    assert_eq!(aa.alias(&local(p), &local(a)), AliasResult::May);
+    assert!(aa.may_write(&call(vec![]), &local(a)));
+}
+
+#[test]
+fn test_alias_analysis_treats_heap_allocations_as_objects() {
+    let Slots { mut func, p, .. } = slots();
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let pointer = Type::Pointer(PointerType::ref_(ty.clone()));
+    let [kept, freed] = [(); 2].map(|_| func.new_local(pointer.clone(), None));
+    let named = |name: &str, dest: Option<Local>, args: Vec<Operand>| {
+        let func = Operand::Function(FunctionRef { name: name.to_string() });
+        Instruction::Call { dest, func, args, return_type: None, fixed_args: None, conv: Default::default() }
+    };
+    func.basic_blocks[0].instructions.extend([
+        named("malloc", Some(kept), vec![int(4)]),
+        named("malloc", Some(freed), vec![int(4)]),
+        named("g", None, vec![local(kept)]),
+        named("free", None, vec![local(freed)]),
+        Instruction::Ret { value: None },
+    ]);
+    let aa = AliasAnalysis::new(&func);
+    assert_eq!(aa.place(&local(freed)).base, Base::Object(freed.id));
+    assert_eq!(aa.alias(&local(kept), &local(freed)), AliasResult::No);
+    // freeing it isnt letting it out
+    assert!(aa.escapes(kept.id));
+    assert!(!aa.escapes(freed.id));
+    assert_eq!(aa.alias(&local(p), &local(freed)), AliasResult::No);
+    assert_eq!(aa.alias(&local(p), &local(kept)), AliasResult::May);
+}
+
+/// `f` after `arr[0] = 1`, `between`, then whatever `after` adds, optimized
+fn optimized(between: impl Fn(&Slots, Local) -> Instruction, after: impl Fn(&Slots, Local) -> Vec<Instruction>) -> MirFunction {
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let mut slots = slots();
+    let x = slots.func.new_local(ty.clone(), None);
+    let mut body = vec![Instruction::Store { dest: local(slots.elements[0]), source: int(1), type_: ty }, between(&slots, x)];
+    body.extend(after(&slots, x));
+    slots.func.basic_blocks[0].instructions.extend(body);
+    MirOptimizer::new().optimize(&mut slots.func);
+    slots.func
+}
+
+fn count(func: &MirFunction, what: fn(&Instruction) -> bool) -> usize {
+    func.basic_blocks.iter().flat_map(|bb| &bb.instructions).filter(|i| what(i)).count()
+}
+
+#[test]
+fn test_store_load_forwarding_respects_aliasing() {
+    // loads `arr[0]` in2 x + passes x on, w/ `arr[1]` 2 when `escape`
+    let loads = |between: fn(&Slots) -> Instruction, escape: bool| {
+        let func = optimized(
+            |s, _| between(s),
+            move |s, x| {
+                let mut args = vec![local(x)];
+                if escape {
+                    args.push(local(s.elements[1]));
+                }
+                vec![load(x, s.elements[0]), call(args), Instruction::Ret { value: None }]
+            },
+        );
+        count(&func, |i| matches!(i, Instruction::Load { .. }))
+    };
+    // another slot, another element + memory the fn was given cant be `arr[0]`
+    assert_eq!(loads(|s| store(s.a), false), 0);
+    assert_eq!(loads(|s| store(s.elements[1]), false), 0);
+    assert_eq!(loads(|s| store(s.p), false), 0);
+    assert_eq!(loads(|_| call(vec![]), false), 0);
+    // `arr[i]` might be, + so might anything once `arr` escapes 2 a call
+    assert_eq!(loads(|s| store(s.elements[2]), false), 1);
+    assert_eq!(loads(|s| store(s.p), true), 1);
+    assert_eq!(loads(|_| call(vec![]), true), 1);
+}
+
+#[test]
+fn test_overwritten_stores_are_kept_when_read_in_between() {
+    let stores = |between: fn(&Slots, Local) -> Instruction| {
+        let func = optimized(between, |s, x| {
+            let ty = Type::Primitive(PrimitiveType::Int);
+            vec![
+                Instruction::Store { dest: local(s.elements[0]), source: int(3), type_: ty },
+                call(vec![local(s.a), local(s.elements[0]), local(x)]),
+                Instruction::Ret { value: None },
+            ]
+        });
+        count(&func, |i| matches!(i, Instruction::Store { .. }))
+    };
+    // the 1st store 2 `arr[0]` is overwritten, the one 2 `a` stays
+    assert_eq!(stores(|s, _| store(s.a)), 2);
+    // `arr[i]` might read it, a call might once `arr` escapes
+    assert_eq!(stores(|s, x| load(x, s.elements[2])), 2);
+    assert_eq!(stores(|_, _| call(vec![])), 2);
+    // a load of `arr[0]` itself is forwarded the stored value, then nothing reads the store
+    assert_eq!(stores(|s, x| load(x, s.elements[0])), 1);
+}
@@ -0,0 +1,145 @@
+use crate::core::mir::MirFunction;
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan::Files;
+
+fn analyze_errors(source: &str) -> Vec<String> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    assert!(!reporter.has_errors(), "parse failed");
+    SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    reporter.diagnostics().iter().filter(|d| d.severity == Severity::Error).map(|d| d.message.clone()).collect()
+}
+
+fn lower_mir(source: &str) -> Vec<MirFunction> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+const BOUNDED: &str = r#"
+trait Bounded
+  const MAX : int
+  const MIN : int = 0
+end
+
+struct Score
+  v : int
+end
+
+struct Level
+  v : int
+end
+
+implement Bounded for Score
+  const MAX : int = 100
+end
+
+implement Bounded for Level
+  const MAX : int = 9
+  const MIN : int = 1
+end
+"#;
+
+#[test]
+fn test_impl_consts_are_checked_against_the_trait() {
+    let source = r#"
+trait Bounded
+  const MAX : int
+  const STEP : int = 1
+end
+
+struct A
+  v : int
+end
+
+struct B
+  v : int
+end
+
+implement Bounded for A
+  const STEP : bool = true
+  const MIN : int = 0
+end
+
+def limit() returns int
+  return 5
+end
+
+implement Bounded for B
+  const MAX : int = limit()
+end
+"#;
+    let errors = analyze_errors(source);
+    assert!(errors.iter().any(|m| m == "Trait 'Bounded' requires constant 'MAX' but it's not provided"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m.starts_with("Constant 'STEP' type mismatch")), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Constant 'MIN' is not part of trait 'Bounded'"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Value of constant 'MAX' must be known at compile time"), "{:?}", errors);
+}
+
+#[test]
+fn test_const_reads_are_typed_from_the_declaration() {
+    let source = format!(
+        "{}{}",
+        BOUNDED,
+        r#"
+def top() returns int
+  return Score::MAX + Level::MIN
+end
+
+def bad [Type T for Bounded](x : T) returns int
+  return T::AVG + Score::MEDIAN
+end
+"#
+    );
+    let errors = analyze_errors(&source);
+    assert!(errors.iter().any(|m| m == "Trait 'Bounded' has no constant 'AVG'"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Type 'Score' has no constant 'MEDIAN'"), "{:?}", errors);
+    // `top` reads declared consts, only the unknown ones error
+    assert!(!errors.iter().any(|m| m.contains("'MAX'") || m.contains("'MIN'")), "{:?}", errors);
+}
+
+#[test]
+fn test_generic_fn_is_specialized_per_type_with_consts_folded() {
+    let source = format!(
+        "{}{}",
+        BOUNDED,
+        r#"
+def clamp [Type T for Bounded](x : T, v : int) returns int
+  if v > T::MAX
+    return T::MAX
+  end
+  if v < T::MIN
+    return T::MIN
+  end
+  return v
+end
+
+def main() returns int
+  s : Score = Score { v: 1 }
+  l : Level = Level { v: 2 }
+  return clamp(s, 250) + clamp(l, 0) + clamp(s, 3)
+end
+"#
+    );
+    let mir = lower_mir(&source);
+    let names: Vec<&str> = mir.iter().map(|f| f.name.as_str()).collect();
+    assert!(!names.contains(&"clamp"), "{:?}", names);
+    // clamp(s, ..) twice shares one copy
+    assert_eq!(names.iter().filter(|n| n.starts_with("clamp$")).count(), 2, "{:?}", names);
+
+    let body = |name: &str| format!("{:?}", mir.iter().find(|f| f.name == name).unwrap().basic_blocks);
+    assert!(body("clamp$Score").contains("Int(100)") && !body("clamp$Score").contains("Int(9)"));
+    assert!(body("clamp$Level").contains("Int(9)") && body("clamp$Level").contains("Int(1)"));
+}
@@ -0,0 +1,113 @@
+use crate::core::mir::analysis::CallGraph;
+use crate::core::mir::{FunctionRef, Instruction, MirFunction, Operand};
+
+fn function(name: &str) -> Operand {
+    Operand::Function(FunctionRef { name: name.to_string() })
+}
+
+/// `name` calling each of `calls` in turn, then returning
+fn caller(name: &str, calls: &[&str]) -> MirFunction {
+    let mut func = MirFunction::new(name.to_string(), None);
+    for callee in calls {
+        func.basic_blocks[0].instructions.push(Instruction::Call {
+            dest: None,
+            func: function(callee),
+            args: vec![],
+            return_type: None,
+            fixed_args: None,
+            conv: Default::default(),
+        });
+    }
+    func.basic_blocks[0].instructions.push(Instruction::Ret { value: None });
+    func
+}
+
+/// main calls `leaf` + the mutually recursive `even`/`odd`, `fact` calls itself, `unused` calls
+/// `leaf` + passes `callback` 2 `printf`, which the program doesnt define
+fn program() -> Vec<MirFunction> {
+    let mut unused = caller("unused", &["leaf", "leaf"]);
+    let printf = Instruction::Call {
+        dest: None,
+        func: function("printf"),
+        args: vec![function("callback")],
+        return_type: None,
+        fixed_args: None,
+        conv: Default::default(),
+    };
+    unused.basic_blocks[0].instructions.insert(0, printf);
+    vec![
+        caller("main", &["even", "leaf", "fact"]),
+        caller("even", &["odd", "leaf"]),
+        caller("odd", &["even"]),
+        caller("fact", &["fact"]),
+        caller("leaf", &[]),
+        unused,
+        caller("callback", &[]),
+    ]
+}
+
+#[test]
+fn test_call_graph_edges() {
+    let graph = CallGraph::new(&program());
+    let [main, even, odd, fact, leaf, unused, callback] = ["main", "even", "odd", "fact", "leaf", "unused", "callback"]
+        .map(|name| graph.index_of(name).unwrap());
+    assert_eq!(graph.index_of("printf"), None);
+    assert_eq!(graph.callees(main), [even, leaf, fact]);
+    // called twice, an edge once
+    assert_eq!(graph.callees(unused), [leaf]);
+    assert_eq!(graph.callers(leaf), [main, even, unused]);
+    assert_eq!(graph.callers(even), [main, odd]);
+    assert!(graph.callers(callback).is_empty());
+    assert!(graph.is_address_taken(callback));
+    assert!(!graph.is_address_taken(leaf));
+}
+
+#[test]
+fn test_call_graph_sccs_are_bottom_up() {
+    let functions = program();
+    let graph = CallGraph::new(&functions);
+    let names = |scc: &Vec<usize>| scc.iter().map(|f| functions[*f].name.as_str()).collect::<Vec<_>>();
+    let sccs: Vec<Vec<&str>> = graph.sccs().iter().map(names).collect();
+    assert_eq!(sccs, [vec!["leaf"], vec!["even", "odd"], vec!["fact"], vec!["main"], vec!["unused"], vec!["callback"]]);
+
+    // evry fn comes after the ones it calls outside its own cycle
+    let order = graph.bottom_up();
+    let position = |f: usize| order.iter().position(|g| *g == f).unwrap();
+    for f in 0..graph.len() {
+        for &callee in graph.callees(f) {
+            assert!(graph.scc_of(callee) == graph.scc_of(f) || position(callee) < position(f));
+        }
+    }
+
+    let recursive: Vec<&str> = (0..graph.len()).filter(|f| graph.is_recursive(*f)).map(|f| functions[f].name.as_str()).collect();
+    assert_eq!(recursive, ["even", "odd", "fact"]);
+}
+
+#[test]
+fn test_call_graph_reachability() {
+    let functions = program();
+    let graph = CallGraph::new(&functions);
+    let main = graph.index_of("main").unwrap();
+    let reachable = graph.reachable_from([main]);
+    let dead: Vec<&str> = functions.iter().zip(&reachable).filter(|(_, r)| !**r).map(|(f, _)| f.name.as_str()).collect();
+    assert_eq!(dead, ["unused", "callback"]);
+    // a fn whose address is taken is a root of its own
+    let roots = (0..graph.len()).filter(|f| *f == main || graph.is_address_taken(*f));
+    assert_eq!(graph.reachable_from(roots).iter().filter(|r| !**r).count(), 1);
+}
+
+#[test]
+fn test_call_graph_handles_long_call_chains() {
+    // deeper than a recursive walk would survive on a test thread's stack
+    let depth = 100_000;
+    let names: Vec<String> = (0..depth).map(|i| format!("f{}", i)).collect();
+    let functions: Vec<MirFunction> = (0..depth)
+        .map(|i| match names.get(i + 1) {
+            Some(next) => caller(&names[i], &[next.as_str()]),
+            None => caller(&names[i], &[]),
+        })
+        .collect();
+    let graph = CallGraph::new(&functions);
+    assert_eq!(graph.sccs().len(), depth);
+    assert_eq!(graph.bottom_up().first(), Some(&(depth - 1)));
+}
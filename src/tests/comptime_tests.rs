@@ -102,3 +102,226 @@ end
     let (_ast, reporter) = analyze_source(source);
     assert!(reporter.has_errors());
 }
+
+
+#[test]
+fn test_comptime_aggregate_evaluation() {
+    use crate::frontend::semantic::{ComptimeEvaluator, ComptimeValue};
+    let source = r#"
+def main()
+  t : int[2] = comptime [1 + 1, 2 * 2]
+  z : int[3] = comptime [0; 3]
+end
+"#;
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let (ast, mut reporter) = analyze_source(source);
+    assert!(!reporter.has_errors());
+    let body = match &ast.items[0] {
+        crate::core::ast::Item::Function(f) => f.body.clone().unwrap(),
+        _ => panic!("expected function"),
+    };
+    let mut evaluator = ComptimeEvaluator::new(&mut reporter, file_id);
+    let values: Vec<_> = body.iter().filter_map(|stmt| match stmt {
+        crate::core::ast::Stmt::Let(l) => l.value.as_ref().and_then(|v| evaluator.evaluate(v)),
+        _ => None,
+    }).collect();
+    assert_eq!(values, vec![
+        ComptimeValue::Array(vec![ComptimeValue::Int(2), ComptimeValue::Int(4)]),
+        ComptimeValue::Array(vec![ComptimeValue::Int(0); 3]),
+    ]);
+}
+
+#[test]
+fn test_comptime_target_constants() {
+    let source = r#"
+def main()
+  width : int = comptime target.pointer_width
+  if comptime target.endian == "little"
+    x = 1
+  end
+  if comptime target.os == "linux"
+    y = 2
+  end
+  arch = target.arch
+end
+"#;
+    let (_ast, reporter) = analyze_source(source);
+    assert!(!reporter.has_errors());
+}
+
+#[test]
+fn test_comptime_unknown_target_property() {
+    let source = r#"
+def main()
+  x = target.word_size
+end
+"#;
+    let (_ast, reporter) = analyze_source(source);
+    assert!(reporter.has_errors());
+}
+
+#[test]
+fn test_target_info_from_triple() {
+    use crate::core::target::{Endian, TargetInfo};
+    let darwin = TargetInfo::from_triple("aarch64-apple-darwin");
+    assert_eq!((darwin.arch.as_str(), darwin.os.as_str()), ("aarch64", "macos"));
+    assert_eq!((darwin.endian, darwin.pointer_width), (Endian::Little, 64));
+
+    let ppc = TargetInfo::from_triple("powerpc64-unknown-linux-gnu");
+    assert_eq!((ppc.endian, ppc.os.as_str()), (Endian::Big, "linux"));
+
+    let wasm = TargetInfo::from_triple("wasm32-wasi");
+    assert_eq!((wasm.os.as_str(), wasm.pointer_width), ("wasi", 32));
+
+    let mcu = TargetInfo::from_triple("thumbv7em-none-eabihf");
+    assert_eq!((mcu.os.as_str(), mcu.pointer_width), ("none", 32));
+}
+
+#[test]
+fn test_comptime_target_follows_configured_triple() {
+    use crate::core::target::TargetInfo;
+    use crate::frontend::semantic::{ComptimeEvaluator, ComptimeValue};
+    let source = r#"
+def main()
+  e : string = comptime target.endian
+end
+"#;
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let (ast, mut reporter) = analyze_source(source);
+    assert!(!reporter.has_errors());
+    let body = match &ast.items[0] {
+        crate::core::ast::Item::Function(f) => f.body.clone().unwrap(),
+        _ => panic!("expected function"),
+    };
+    let value = match &body[0] {
+        crate::core::ast::Stmt::Let(l) => l.value.clone().unwrap(),
+        _ => panic!("expected let"),
+    };
+    let mut evaluator = ComptimeEvaluator::new(&mut reporter, file_id)
+        .with_target(TargetInfo::from_triple("mips-unknown-linux-gnu"));
+    assert_eq!(evaluator.evaluate(&value), Some(ComptimeValue::String("big".to_string())));
+}
+
+#[test]
+fn test_comptime_params_specialize_per_value() {
+    use crate::core::hir::{HirExpr, HirItem, HirLiteralKind, HirStmt};
+    use crate::middle::HirLowerer;
+
+    let source = r#"
+def scale(comptime factor : int, x : int) returns int
+  return x * factor
+end
+
+def twice(comptime n : int, x : int) returns int
+  return scale(n + 1, x) + scale(n + 1, x)
+end
+
+def main() returns int
+  a : int = scale(3, 4)
+  b : int = scale(3, 5)
+  return a + b + twice(2, a) + scale(1 + 1, b)
+end
+"#;
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let hir = HirLowerer::new(symbol_table).lower(&ast);
+
+    let functions: Vec<_> = hir.items.iter().filter_map(|item| match item {
+        HirItem::Function(f) => Some(f),
+        _ => None,
+    }).collect();
+    let mut names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+    names.sort();
+    // scale(3), twice(2), scale(2) from main, twice(2) reuses scale(3); the unspecialized fns r gone
+    assert_eq!(names, ["main", "scale$ct0", "scale$ct2", "twice$ct1"]);
+
+    let scale3 = functions.iter().find(|f| f.name == "scale$ct0").unwrap();
+    assert_eq!(scale3.params.len(), 1);
+    let ret = match &scale3.body.as_ref().unwrap()[0] {
+        HirStmt::Return(r) => r.value.clone().unwrap(),
+        _ => panic!("expected return"),
+    };
+    match ret {
+        HirExpr::Binary(b) => assert!(matches!(&*b.right, HirExpr::Literal(l) if matches!(l.kind, HirLiteralKind::Int(3)))),
+        other => panic!("expected binary, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_comptime_param_rejects_runtime_arg() {
+    let source = r#"
+def scale(comptime factor : int, x : int) returns int
+  return x * factor
+end
+
+def main() returns int
+  n : int = 3
+  return scale(n, 4)
+end
+"#;
+    let (_, reporter) = analyze_source(source);
+    assert!(reporter.diagnostics().iter().any(|d| {
+        d.message == "Argument for comptime parameter 'factor' of 'scale' must be known at compile time"
+    }));
+
+    let source = r#"
+def pick(comptime items : int[4]) returns int
+  return 0
+end
+"#;
+    let (_, reporter) = analyze_source(source);
+    assert!(reporter.has_errors());
+}
+
+#[test]
+fn test_comptime_if_skips_the_untaken_branch() {
+    use crate::core::hir::{HirItem, HirStmt};
+    use crate::middle::HirLowerer;
+
+    let source = r#"
+def fact(comptime n : int) returns int
+  if comptime n == 0
+    return 1
+  else
+    return n * fact(n - 1)
+  end
+end
+
+def main() returns int
+  if comptime target.os == "no-such-os"
+    return platform_only_call(1)
+  else
+    return fact(3)
+  end
+end
+"#;
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let hir = HirLowerer::new(symbol_table).lower(&ast);
+
+    let functions: Vec<_> = hir.items.iter().filter_map(|item| match item {
+        HirItem::Function(f) => Some(f),
+        _ => None,
+    }).collect();
+    // fact(3) down 2 fact(0), which only returns 1
+    let mut names: Vec<_> = functions.iter().map(|f| f.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["fact$ct0", "fact$ct1", "fact$ct2", "fact$ct3", "main"]);
+    for f in functions {
+        let Some(HirStmt::If(s)) = f.body.as_ref().unwrap().first() else { panic!("expected an if in {}", f.name) };
+        assert_eq!(s.then_branch.len(), 1);
+        assert!(s.else_branch.is_none());
+    }
+}
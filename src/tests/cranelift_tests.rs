@@ -0,0 +1,152 @@
+use crate::backend::factory::{BackendRegistry, BackendType};
+
+#[test]
+fn test_backend_type_cranelift() {
+    assert_eq!(BackendType::from_str("cranelift"), Some(BackendType::Cranelift));
+    assert_eq!(BackendType::from_str("CLIF"), Some(BackendType::Cranelift));
+    assert_eq!(BackendType::Cranelift.as_str(), "cranelift");
+    // only registered when built w/ the feature
+    let registered = BackendRegistry::new().get_factory(BackendType::Cranelift).is_some();
+    assert_eq!(registered, cfg!(feature = "cranelift"));
+}
+
+#[cfg(feature = "cranelift")]
+mod codegen {
+    use crate::backend::cranelift::codegen::CraneliftModuleData;
+    use crate::backend::cranelift::types::struct_layout;
+    use crate::backend::cranelift::{CraneliftCodeGen, CraneliftEmitter};
+    use crate::backend::ports::codegen::{CodeGen, CodeGenError, Module};
+    use crate::backend::ports::emitter::Emitter;
+    use crate::core::types::composite::{Field, StructType};
+    use crate::core::types::primitive::PrimitiveType;
+    use crate::core::types::ty::Type;
+    use crate::error::Reporter;
+    use crate::frontend::lexer::Lexer;
+    use crate::frontend::parser::Parser;
+    use crate::frontend::semantic::SemanticAnalyzer;
+    use crate::middle::{HirLowerer, MirLowerer};
+    use codespan::Files;
+
+    fn compile(source: &str) -> Result<Module, CodeGenError> {
+        let mut files = Files::new();
+        let file_id = files.add("test.em", source.to_string());
+        let mut reporter = Reporter::new();
+        let source_str = files.source(file_id).to_string();
+        let tokens = Lexer::new(&source_str, file_id, &mut reporter).tokenize();
+        let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+        let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+        assert!(!reporter.has_errors());
+        let hir = HirLowerer::new(symbol_table).lower(&ast);
+        // panics name test.em like the driver names the input
+        let mir = MirLowerer::new().with_source("test.em", source).lower(&hir);
+        CraneliftCodeGen::new().generate_from_mir(&mir)
+    }
+
+    fn data(module: &Module) -> &CraneliftModuleData {
+        module.data.as_ref().and_then(|d| d.downcast_ref::<CraneliftModuleData>()).unwrap()
+    }
+
+    #[test]
+    fn test_cranelift_compiles_object() {
+        let module = compile(
+            r#"
+struct Point
+  x : int
+  y : int
+end
+
+def sum(p : ref Point) returns int
+  return p.x + p.y
+end
+
+def main() returns int
+  p : Point = Point { x: 40, y: 2 }
+  return sum(@p)
+end
+"#,
+        )
+        .unwrap();
+        let data = data(&module);
+        assert!(!data.object.is_empty());
+        assert!(data.assembly.contains("sum:"));
+        let main = data.frames.iter().find(|f| f.name == "main").unwrap();
+        assert_eq!(main.callees, vec!["sum".to_string()]);
+    }
+
+    #[test]
+    fn test_cranelift_rejects_struct_by_value() {
+        let err = compile(
+            r#"
+struct Point
+  x : int
+  y : int
+end
+
+def make(a : int) returns Point
+  return Point { x: a, y: 2 }
+end
+"#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, CodeGenError::UnsupportedFeature(ref m) if m.contains("struct 'Point'")));
+    }
+
+    #[test]
+    fn test_cranelift_struct_layout() {
+        let field = |name: &str, p: PrimitiveType, offset: Option<usize>| Field {
+            name: name.to_string(),
+            type_: Type::Primitive(p),
+            offset,
+        };
+        let natural = StructType {
+            name: "Mixed".to_string(),
+            fields: vec![field("tag", PrimitiveType::Byte, None), field("value", PrimitiveType::Float, None)],
+            size: None,
+            align: None,
+        };
+        let layout = struct_layout(&natural, 8);
+        assert_eq!(layout.offsets, vec![0, 8]);
+        assert_eq!((layout.size, layout.align), (16, 8));
+
+        // declared offsets + size win over the natural layout
+        let declared = StructType {
+            name: "Reg".to_string(),
+            fields: vec![field("ctrl", PrimitiveType::Int, Some(0)), field("data", PrimitiveType::Int, Some(12))],
+            size: Some(32),
+            align: Some(4),
+        };
+        let layout = struct_layout(&declared, 8);
+        assert_eq!(layout.offsets, vec![0, 12]);
+        assert_eq!(layout.size, 32);
+    }
+
+    #[test]
+    fn test_cranelift_objects_carry_the_panic_runtime() {
+        let source = r#"
+def get(i : int) returns int
+  a : int[4] = [1, 2, 3, 4]
+  return a[i]
+end
+
+def main() returns int
+  return get(N)
+end
+"#;
+        let dir = std::env::temp_dir().join(format!("emc-cranelift-panic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let run = |index: &str| {
+            let output = dir.join(format!("get{}", index));
+            let module = compile(&source.replace('N', index)).unwrap();
+            CraneliftEmitter::new().emit_binary(&module, &output).unwrap();
+            std::process::Command::new(&output).output().unwrap()
+        };
+        let in_bounds = run("2");
+        let out_of_bounds = run("4");
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(in_bounds.status.code(), Some(3));
+        // links w/o an emerald_panic from elsewhere, + prints like the llvm copy b4 aborting
+        let stderr = String::from_utf8_lossy(&out_of_bounds.stderr);
+        assert!(!out_of_bounds.status.success());
+        assert!(stderr == "panicked at test.em:4: index out of bounds for length 4\n", "{}", stderr);
+    }
+}
@@ -0,0 +1,165 @@
+use crate::cli::daemon::{read_address, send, write_address, Daemon, DaemonReply, DaemonRequest, Endpoint};
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("emc-daemon-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn request(cwd: &Path, args: &[&str]) -> DaemonRequest {
+    DaemonRequest { cwd: cwd.to_path_buf(), args: args.iter().map(|a| a.to_string()).collect(), color: false }
+}
+
+const REUSED: &str = "is unchanged, reusing its last build";
+
+#[test]
+fn test_requests_and_replies_survive_the_wire() {
+    let sent = DaemonRequest { cwd: PathBuf::from("/work/app"), args: vec!["check".to_string(), "main.em".to_string()], color: true };
+    assert_eq!(DaemonRequest::decode(&sent.encode()).unwrap(), sent);
+    let reply = DaemonReply { code: -1, stdout: "done\n".to_string(), stderr: "error[E0003]: ✗\n".to_string() };
+    assert_eq!(DaemonReply::decode(&reply.encode()).unwrap(), reply);
+}
+
+#[test]
+fn test_daemon_reuses_a_check_until_a_source_changes() {
+    let dir = scratch("check");
+    std::fs::write(dir.join("mathx.em"), "def double(n : int) returns int\n  return n * 2\nend\n").unwrap();
+    std::fs::write(dir.join("main.em"), "import mathx\n\ndef main() returns int\n  x : int = \"s\"\n  return mathx::double(x)\nend\n").unwrap();
+    let daemon = Daemon::new();
+    // relative to the client's dir, not the daemon's
+    let check = request(&dir, &["--verbose", "--no-prelude", "check", "main.em"]);
+
+    let first = daemon.handle(&check);
+    assert_eq!(first.code, 1);
+    assert!(first.stderr.contains("Type mismatch") && first.stderr.contains("main.em:4:"), "{}", first.stderr);
+    assert!(!first.stdout.contains(REUSED));
+    let again = daemon.handle(&check);
+    assert!(again.stdout.contains(REUSED), "{}", again.stdout);
+    assert_eq!((again.code, again.stderr), (first.code, first.stderr));
+
+    // another config is another build
+    assert!(!daemon.handle(&request(&dir, &["--verbose", "--no-prelude", "--null", "check", "main.em"])).stdout.contains(REUSED));
+
+    // an edit 2 an imported module counts like 1 2 the input
+    std::fs::write(dir.join("main.em"), "import mathx\n\ndef main() returns int\n  return mathx::double(2)\nend\n").unwrap();
+    assert_eq!(daemon.handle(&check).code, 0);
+    assert!(daemon.handle(&check).stdout.contains(REUSED));
+    std::fs::write(dir.join("mathx.em"), "def twice(n : int) returns int\n  return n * 2\nend\n").unwrap();
+    let changed = daemon.handle(&check);
+    assert!(changed.code == 1 && !changed.stdout.contains(REUSED), "{:?}", changed);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_daemon_builds_again_once_the_output_is_gone() {
+    let dir = scratch("build");
+    std::fs::write(dir.join("seven.em"), "def seven() returns int\n  return 7\nend\n").unwrap();
+    let daemon = Daemon::new();
+    let build = request(&dir, &["--verbose", "--no-prelude", "--null", "--emit=obj", "seven.em"]);
+    let first = daemon.handle(&build);
+    assert_eq!(first.code, 0, "{:?}", first);
+    assert!(first.stdout.contains(&format!("Output: {}", dir.join("seven.o").display())), "{}", first.stdout);
+    assert!(daemon.handle(&build).stdout.contains(REUSED));
+    std::fs::remove_file(dir.join("seven.o")).unwrap();
+    assert!(!daemon.handle(&build).stdout.contains(REUSED));
+    assert!(dir.join("seven.o").is_file());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_daemon_keeps_an_inputs_analysis_across_options() {
+    let dir = scratch("front");
+    std::fs::write(dir.join("main.em"), "def main() returns int\n  x : int = \"s\"\n  return x\nend\n").unwrap();
+    let daemon = Daemon::new();
+    let first = daemon.handle(&request(&dir, &["--no-prelude", "check", "main.em"]));
+    assert!(first.stderr.contains("Type mismatch"), "{}", first.stderr);
+    assert_eq!(daemon.front_ends().len(), 1);
+    // another opt level is another build but the same analysis
+    let other = daemon.handle(&request(&dir, &["--no-prelude", "-O2", "--null", "main.em"]));
+    assert_eq!((other.code, other.stderr), (first.code, first.stderr));
+    assert_eq!(daemon.front_ends().len(), 1);
+    // an edit isnt hidden by what was kept
+    std::fs::write(dir.join("main.em"), "def main() returns int\n  return 0\nend\n").unwrap();
+    let fixed = daemon.handle(&request(&dir, &["--no-prelude", "check", "main.em"]));
+    assert_eq!(fixed.code, 0, "{:?}", fixed);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_daemon_refuses_what_it_cant_run() {
+    let daemon = Daemon::new();
+    let cwd = std::env::temp_dir();
+    assert_eq!(daemon.handle(&request(&cwd, &["--no-such-flag"])).code, 2);
+    let reply = daemon.handle(&request(&cwd, &["fmt", "a.em"]));
+    assert_eq!(reply, DaemonReply { code: 2, stdout: String::new(), stderr: "error: The daemon only runs builds and 'check'\n".to_string() });
+}
+
+#[test]
+fn test_daemon_serves_clients_until_stopped() {
+    let dir = scratch("serve");
+    std::fs::write(dir.join("ok.em"), "def main() returns int\n  return 0\nend\n").unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let daemon = Daemon::new();
+    let endpoint = Endpoint { address: listener.local_addr().unwrap().to_string(), token: daemon.token().to_string() };
+    let server = std::thread::spawn(move || daemon.serve(listener));
+    let run = request(&dir, &["--no-prelude", "check", "ok.em"]).encode();
+    let reply = send(&endpoint, "RUN", &run).unwrap();
+    assert_eq!(reply.code, 0, "{:?}", reply);
+    assert_eq!(send(&endpoint, "PING", &[]).unwrap().stderr, "error: Unknown request 'PING'\n");
+    // a client that couldnt read the token gets nothing run, not even a stop
+    let stranger = Endpoint { token: "0".repeat(endpoint.token.len()), ..endpoint.clone() };
+    for kind in ["RUN", "STOP"] {
+        let refused = send(&stranger, kind, &run).unwrap();
+        assert_eq!(refused, DaemonReply { code: 2, stdout: String::new(), stderr: "error: The daemon refused the request, its token doesn't match\n".to_string() });
+    }
+    send(&endpoint, "STOP", &[]).unwrap();
+    server.join().unwrap().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_daemon_drops_a_client_that_goes_quiet() {
+    let dir = scratch("quiet");
+    std::fs::write(dir.join("ok.em"), "def main() returns int\n  return 0\nend\n").unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let daemon = Daemon::new().with_client_timeout(Duration::from_millis(200));
+    let endpoint = Endpoint { address: listener.local_addr().unwrap().to_string(), token: daemon.token().to_string() };
+    let server = std::thread::spawn(move || daemon.serve(listener));
+    // connected but never sends a whole frame
+    let mut quiet = TcpStream::connect(&endpoint.address).unwrap();
+    quiet.write_all(b"RUN 10").unwrap();
+    let reply = send(&endpoint, "RUN", &request(&dir, &["--no-prelude", "check", "ok.em"]).encode()).unwrap();
+    assert_eq!(reply.code, 0, "{:?}", reply);
+    send(&endpoint, "STOP", &[]).unwrap();
+    server.join().unwrap().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_the_address_file_is_private_to_its_user() {
+    let dir = scratch("address");
+    let file = dir.join("daemon.addr");
+    let endpoint = Endpoint { address: "127.0.0.1:4000".to_string(), token: Daemon::new().token().to_string() };
+    assert_eq!(endpoint.token.len(), 32);
+    assert_ne!(endpoint.token, Daemon::new().token());
+    write_address(&file, &endpoint).unwrap();
+    assert_eq!(read_address(&file), Some(endpoint.clone()));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o600);
+        // 1 others can read isnt trusted
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(read_address(&file), None);
+    }
+    // an earlier daemon's file is replaced
+    let next = Endpoint { address: "127.0.0.1:4001".to_string(), ..endpoint };
+    write_address(&file, &next).unwrap();
+    assert_eq!(read_address(&file), Some(next));
+    let _ = std::fs::remove_dir_all(&dir);
+}
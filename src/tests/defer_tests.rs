@@ -0,0 +1,135 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::core::ast::{Item, Stmt};
+use crate::core::mir::{Instruction, MirFunction, Operand, FREE_FN};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+
+/// the errors checking `source` reports, + its MIR when there r none
+fn compile(source: &str) -> Result<Vec<MirFunction>, Vec<String>> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    if reporter.has_errors() {
+        return Err(reporter.diagnostics().iter().map(|d| d.message.clone()).collect());
+    }
+    Ok(MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast)))
+}
+
+fn run(source: &str) -> i32 {
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&compile(source).unwrap()).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module).unwrap()
+}
+
+/// each deferred call appends a digit 2 `log`
+const LOG: &str = r#"
+def bump(p : ref int, by : int)
+  p.value = p.value * 10 + by
+end
+
+def work(log : ref int, early : bool) returns int
+  defer bump(log, 1)
+  if early
+    defer bump(log, 2)
+    return 5
+  end
+  defer do
+    step : int = 3
+    bump(log, step)
+    bump(log, step + 1)
+  end
+  return 7
+end
+"#;
+
+#[test]
+fn test_defer_parses_an_expression_or_a_block() {
+    let source = "def f()\n  defer g()\n  defer do\n    g()\n    g()\n  end\nend\n";
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    assert!(!reporter.has_errors());
+    let Item::Function(f) = &ast.items[0] else { panic!("expected a fn") };
+    let bodies: Vec<usize> = f
+        .body
+        .iter()
+        .flatten()
+        .map(|s| match s {
+            Stmt::Defer(d) => d.body.len(),
+            other => panic!("expected a defer, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(bodies, [1, 2]);
+}
+
+#[test]
+fn test_deferred_code_runs_at_every_exit_last_first() {
+    // the early return runs 2 then 1, the last return runs the block (3, 4) then 1
+    let source = format!(
+        "{}\ndef main() returns int\n  log : ref int = new 0\n  a : int = work(log, true)\n  b : int = work(log, false)\n  total : int = log.value\n  delete log\n  return total * 100 + a + b\nend\n",
+        LOG
+    );
+    assert_eq!(run(&source), 21341 * 100 + 5 + 7);
+
+    // each exit gets its own copy of the deferred code
+    let mir = compile(&source).unwrap();
+    let work = mir.iter().find(|f| f.name == "work").unwrap();
+    let calls = work.basic_blocks.iter().flat_map(|bb| &bb.instructions).filter(|inst| matches!(inst, Instruction::Call { .. })).count();
+    assert_eq!(calls, 2 + 3);
+}
+
+#[test]
+fn test_the_return_value_is_read_before_deferred_code_runs() {
+    let source = r#"
+def seed() returns int
+  return 1
+end
+
+def first() returns int
+  x : int = seed()
+  defer x = 2
+  return x
+end
+
+def main() returns int
+  return first()
+end
+"#;
+    assert_eq!(run(source), 1);
+}
+
+#[test]
+fn test_deferred_code_cannot_jump_out() {
+    let errors = compile("def f() returns int\n  defer do\n    return 1\n  end\n  return 0\nend\n").unwrap_err();
+    assert_eq!(errors, ["'return' cannot leave a deferred block, it runs while its scope is already exiting"]);
+
+    let errors = compile("def f(n : int)\n  while n > 0\n    defer do\n      break\n    end\n    n = n - 1\n  end\nend\n").unwrap_err();
+    assert_eq!(errors, ["'break' cannot leave a deferred block, it runs while its scope is already exiting"]);
+
+    // a loop inside the deferred code keeps its own breaks
+    assert!(compile("def f(n : int)\n  defer do\n    while n > 0\n      break\n    end\n  end\nend\n").is_ok());
+}
+
+#[test]
+fn test_a_deferred_delete_frees_once_at_the_scope_end() {
+    let source = "def main() returns int\n  x : ref int = new 41\n  defer delete x\n  x.value = x.value + 1\n  return x.value\nend\n";
+    let mir = compile(source).unwrap();
+    let frees = mir[0]
+        .basic_blocks
+        .iter()
+        .flat_map(|bb| &bb.instructions)
+        .filter(|inst| matches!(inst, Instruction::Call { func: Operand::Function(f), .. } if f.name == FREE_FN))
+        .count();
+    assert_eq!(frees, 1);
+    assert_eq!(run(source), 42);
+
+    let errors = compile("def main() returns int\n  x : ref int = new 41\n  defer delete x\n  delete x\n  return 0\nend\n").unwrap_err();
+    assert_eq!(errors, ["'x' is deleted twice"]);
+}
@@ -0,0 +1,117 @@
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use codespan::Files;
+
+fn errors(source: &str) -> Vec<String> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    assert!(!reporter.has_errors());
+    SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    reporter.diagnostics().iter().filter(|d| d.severity == Severity::Error).map(|d| d.message.clone()).collect()
+}
+
+const SHAPES: &str = r#"
+struct Point
+  x : int
+  y : int
+end
+
+struct Line
+  from : Point
+  to : Point
+end
+
+def length(l : Line) returns int
+  return l.to.x - l.from.x
+end
+"#;
+
+#[test]
+fn test_struct_literal_must_name_every_field() {
+    let source = format!(
+        "{}{}",
+        SHAPES,
+        r#"
+def origin() returns Point
+  return Point { x: 0 }
+end
+"#
+    );
+    assert_eq!(errors(&source), ["Struct literal of 'Point' is missing field(s) 'y'"]);
+}
+
+#[test]
+fn test_fields_set_one_by_one_initialize_the_struct() {
+    let source = format!(
+        "{}{}",
+        SHAPES,
+        r#"
+def make(flag : bool) returns int
+  l : Line
+  l.from = Point { x: 0, y: 0 }
+  l.to.x = 3
+  if flag == true
+    l.to.y = 1
+  else
+    l.to.y = 2
+  end
+  return length(l)
+end
+
+def pick(flag : bool) returns int
+  p : Point
+  if flag == true
+    p = Point { x: 1, y: 1 }
+  else
+    return 0
+  end
+  return p.x + p.y
+end
+"#
+    );
+    assert!(errors(&source).is_empty(), "{:?}", errors(&source));
+}
+
+#[test]
+fn test_partially_initialized_struct_is_rejected() {
+    let source = format!(
+        "{}{}",
+        SHAPES,
+        r#"
+def make(flag : bool) returns int
+  l : Line
+  l.from.x = 1
+  if flag == true
+    l.from.y = 2
+  end
+  l.to = Point { x: l.from.y, y: 0 }
+  return length(l)
+end
+
+def loop(n : int) returns int
+  p : Point
+  p.x = 1
+  while n > 0
+    n = n - 1
+    p.y = 2
+  end
+  return p.x + p.y
+end
+"#
+    );
+    let errors = errors(&source);
+    assert_eq!(
+        errors,
+        [
+            "Field 'l.from.y' is read before it's initialized",
+            "Field 'p.y' is read before it's initialized"
+        ],
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,143 @@
+use crate::cli::args::{Cli, CompileConfig};
+use crate::error::{Lint, LintLevel, Reporter, Severity};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use clap::Parser as _;
+
+fn analyze(reporter: &mut Reporter, name: &str, source: &str) {
+    let file_id = reporter.add_file(name.to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, reporter).parse();
+    assert!(!reporter.has_errors(), "{:?}", messages(reporter));
+    SemanticAnalyzer::new(reporter, file_id).analyze(&ast);
+}
+
+fn messages(reporter: &Reporter) -> Vec<String> {
+    reporter.diagnostics().iter().map(|d| d.message.clone()).collect()
+}
+
+/// text of the "declared here" label of each diagnostic
+fn declared_at(reporter: &Reporter) -> Vec<String> {
+    reporter
+        .diagnostics()
+        .iter()
+        .map(|d| {
+            let label = &d.labels[0];
+            reporter.files().source(label.file_id)[label.span.start().to_usize()..label.span.end().to_usize()].to_string()
+        })
+        .collect()
+}
+
+const SOURCE: &str = r#"
+@deprecated("use add instead")
+def plus(a : int, b : int) returns int
+  return a + b
+end
+
+@deprecated
+enum Shape
+  Dot
+  Circle(int)
+end
+
+struct Point
+  @deprecated("use y")
+  x : int
+  y : int
+end
+
+def area(s : Shape) returns int
+  return 0
+end
+
+def main() returns int
+  p : Point = Point { x: 1, y: 2 }
+  return plus(p.y, 2) + area(Shape::Dot)
+end
+"#;
+
+#[test]
+fn test_every_use_of_a_deprecated_item_warns() {
+    let mut reporter = Reporter::new();
+    analyze(&mut reporter, "test.em", SOURCE);
+    assert!(!reporter.has_errors());
+    assert_eq!(
+        messages(&reporter),
+        vec![
+            "Use of deprecated type 'Shape'",
+            "Use of deprecated field 'Point.x': use y",
+            "Use of deprecated function 'plus': use add instead",
+            "Use of deprecated type 'Shape'",
+        ]
+    );
+    // the decl line, not the attribute above it or the whole body
+    assert_eq!(declared_at(&reporter), vec!["enum Shape", "x : int", "def plus(a : int, b : int) returns int", "enum Shape"]);
+    let diagnostic = &reporter.diagnostics()[2];
+    assert_eq!(diagnostic.severity, Severity::Warning);
+    assert_eq!(diagnostic.lint, Some(Lint::Deprecated));
+    assert_eq!(diagnostic.notes, vec!["`-A deprecated` silences this warning"]);
+}
+
+#[test]
+fn test_deprecated_lint_levels() {
+    let mut reporter = Reporter::new();
+    reporter.set_lint_level(Lint::Deprecated, LintLevel::Allow);
+    analyze(&mut reporter, "test.em", SOURCE);
+    assert!(reporter.diagnostics().is_empty());
+
+    let mut reporter = Reporter::new();
+    reporter.set_lint_level(Lint::Deprecated, LintLevel::Deny);
+    analyze(&mut reporter, "test.em", SOURCE);
+    assert!(reporter.has_errors());
+    assert!(reporter.diagnostics().iter().all(|d| d.severity == Severity::Error));
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-A", "deprecated", "-D", "deprecated"]);
+    let config = CompileConfig::from_cli(&cli).unwrap();
+    assert_eq!(config.lints, vec![(Lint::Deprecated, LintLevel::Allow), (Lint::Deprecated, LintLevel::Deny)]);
+    let cli = Cli::parse_from(["emerald", "main.em", "-W", "unused"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap_err(), "Unknown lint 'unused'");
+}
+
+#[test]
+fn test_params_shadowing_a_deprecated_fn_dont_warn() {
+    let source = r#"
+@deprecated
+def count() returns int
+  return 1
+end
+
+def twice(count : int) returns int
+  return count * 2
+end
+"#;
+    let mut reporter = Reporter::new();
+    analyze(&mut reporter, "test.em", source);
+    assert!(reporter.diagnostics().is_empty(), "{:?}", messages(&reporter));
+}
+
+#[test]
+fn test_deprecated_imported_fn_points_at_its_module() {
+    let dir = std::env::temp_dir().join(format!("emc-deprecated-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("mathx.em"), "@deprecated(\"use mathx::twice\")\ndef double(n : int) returns int\n  return n * 2\nend\n").unwrap();
+    let main = "import mathx\n\ndef main() returns int\n  return mathx::double(2)\nend\n";
+
+    let mut reporter = Reporter::new();
+    analyze(&mut reporter, &dir.join("main.em").to_string_lossy(), main);
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(messages(&reporter), vec!["Use of deprecated function 'mathx::double': use mathx::twice"]);
+    assert_eq!(declared_at(&reporter), vec!["def double(n : int) returns int"]);
+    let label = &reporter.diagnostics()[0].labels[0];
+    assert!(reporter.files().name(label.file_id).to_string_lossy().ends_with("mathx.em"));
+}
+
+#[test]
+fn test_deprecated_attribute_placement() {
+    let mut reporter = Reporter::new();
+    let source = "@deprecated(\"old\")\ntrait Shape\n  def area(self) returns int\nend\n";
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    Parser::new(tokens, file_id, &mut reporter).parse();
+    assert_eq!(messages(&reporter)[0], "Expected function, struct or enum after deprecated attribute");
+}
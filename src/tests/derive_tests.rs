@@ -0,0 +1,119 @@
+use crate::core::ast::{Expr, Item, LiteralKind, Stmt};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use codespan::Files;
+
+fn analyze_source(source: &str) -> (crate::core::ast::Ast, Reporter) {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    if !reporter.has_errors() {
+        SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    }
+    for diag in reporter.diagnostics() {
+        eprintln!("[{:?}] {:?}: {}", diag.kind, diag.severity, diag.message);
+    }
+    (ast, reporter)
+}
+
+fn derived_method<'a>(ast: &'a crate::core::ast::Ast, trait_name: &str) -> &'a crate::core::ast::Function {
+    ast.items
+        .iter()
+        .find_map(|i| match i {
+            Item::TraitImpl(ti) if ti.trait_name == trait_name => ti.methods.first(),
+            _ => None,
+        })
+        .unwrap()
+}
+
+const POINT: &str = r#"
+@derive(Display, Eq)
+struct Point
+  x : int
+  y : float
+  id : long
+end
+"#;
+
+#[test]
+fn test_derive_synthesizes_impls_and_declarations() {
+    let source = POINT.replace("  y : float\n", "").replace("Display, Eq", "Display, Eq, Hash");
+    let (ast, reporter) = analyze_source(&source);
+    assert!(!reporter.has_errors());
+    for name in ["Display", "Eq", "Hash"] {
+        assert!(ast.items.iter().any(|i| matches!(i, Item::Trait(t) if t.name == name)));
+        assert!(ast.items.iter().any(|i| matches!(i, Item::TraitImpl(ti) if ti.trait_name == name && ti.type_name == "Point")));
+    }
+    // Display needs printf, declared if the file doesnt
+    assert!(ast.items.iter().any(|i| matches!(i, Item::Foreign(f) if f.functions.iter().any(|func| func.name == "printf" && func.variadic))));
+    let eq = derived_method(&ast, "Eq");
+    assert_eq!(eq.params.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), vec!["self", "other"]);
+}
+
+#[test]
+fn test_derived_display_format_follows_field_types() {
+    let (ast, _reporter) = analyze_source(&POINT.replace("Display, Eq", "Display"));
+    let fmt = derived_method(&ast, "Display");
+    let Some(Stmt::Return(ret)) = fmt.body.as_ref().and_then(|b| b.first()) else { panic!("expected a return") };
+    let Some(Expr::Call(call)) = &ret.value else { panic!("expected a printf call") };
+    let Expr::Literal(format) = &call.args[0] else { panic!("expected a format literal") };
+    assert!(matches!(&format.kind, LiteralKind::String(s) if s == "Point { x: %d, y: %f, id: %ld }"));
+    assert_eq!(call.args.len(), 4);
+}
+
+#[test]
+fn test_derive_rejects_unsupported_fields_and_traits() {
+    let source = r#"
+@derive(Eq, Hash)
+struct Tagged
+  label : string
+  weight : float
+end
+
+@derive(Clone)
+struct Other
+  n : int
+end
+"#;
+    let (_ast, reporter) = analyze_source(source);
+    let messages: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+    assert!(messages.contains(&"Cannot derive 'Eq' for 'Tagged': field 'label' can't be compared with ==".to_string()));
+    // strings hash by len, floats cant be hashed
+    assert!(messages.contains(&"Cannot derive 'Hash' for 'Tagged': field 'weight' can't be hashed".to_string()));
+    assert!(messages.contains(&"Cannot derive 'Clone', only Display, Eq, Hash can be derived".to_string()));
+}
+
+#[test]
+fn test_derived_methods_callable_on_structs() {
+    use crate::core::mir::{Instruction, Operand};
+    use crate::middle::{HirLowerer, MirLowerer};
+
+    let source = format!(
+        "{}\ndef same returns bool\n  p : Point = Point {{ x: 1, y: 2.0, id: 3 }}\n  q : Point = Point {{ x: 1, y: 2.0, id: 4 }}\n  return p.eq(@q)\nend\n",
+        POINT
+    );
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.clone());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(&source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let mir = MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast));
+
+    assert!(mir.iter().any(|f| f.name == "Eq::Point::eq"));
+    let same = mir.iter().find(|f| f.name == "same").unwrap();
+    let calls_eq = same.basic_blocks.iter().flat_map(|b| &b.instructions).any(|i| {
+        matches!(i, Instruction::Call { dest: Some(_), func: Operand::Function(f), .. } if f.name == "Eq::Point::eq")
+    });
+    assert!(calls_eq);
+
+    // other's type is checked against the receiver
+    let bad = format!("{}\ndef bad(p : Point) returns bool\n  return p.eq(1)\nend\n", POINT);
+    let (_ast, reporter) = analyze_source(&bad);
+    assert!(reporter.has_errors());
+}
@@ -0,0 +1,87 @@
+use crate::core::mir::analysis::{Cfg, Dominators};
+use crate::core::mir::{Constant, Instruction, MirFunction, Operand};
+
+/// bb0 -> bb1, bb1 branches 2 bb2 or bb3 which meet in bb4, bb4 loops back 2 bb1 or exits thru
+/// bb5. bb6 jumps 2 bb5 but nothing reaches it
+fn looping_diamond() -> Cfg {
+    Cfg::from_edges(0, vec![vec![1], vec![2, 3], vec![4], vec![4], vec![1, 5], vec![], vec![5]])
+}
+
+#[test]
+fn test_cfg_orders_and_edges() {
+    let cfg = looping_diamond();
+    assert_eq!(cfg.predecessors(1), [0, 4]);
+    assert_eq!(cfg.predecessors(5), [4, 6]);
+    assert_eq!(cfg.exits(), [5]);
+    assert_eq!(cfg.postorder(), [5, 4, 2, 3, 1, 0]);
+    assert_eq!(cfg.reverse_postorder(), [0, 1, 3, 2, 4, 5]);
+    assert_eq!(cfg.reachable(), [true, true, true, true, true, true, false]);
+    let loop_blocks: Vec<bool> = (0..7).map(|b| cfg.on_cycle(b)).collect();
+    assert_eq!(loop_blocks, [false, true, true, true, true, false, false]);
+
+    // edges given twice or 2 blocks that dont exist dont count
+    let cfg = Cfg::from_edges(0, vec![vec![1, 1, 9], vec![]]);
+    assert_eq!(cfg.successors(0), [1]);
+    assert_eq!(cfg.predecessors(1), [0]);
+}
+
+#[test]
+fn test_dominator_tree() {
+    let dom = Dominators::of(&looping_diamond());
+    let idoms: Vec<_> = (0..7).map(|b| dom.idom(b)).collect();
+    assert_eq!(idoms, [None, Some(0), Some(1), Some(1), Some(1), Some(4), None]);
+    assert_eq!(dom.children(1), [2, 3, 4]);
+    assert!(dom.dominates(1, 5));
+    assert!(dom.dominates(4, 4));
+    assert!(!dom.strictly_dominates(4, 4));
+    assert!(!dom.dominates(2, 4));
+    // unreachable code isnt in the tree
+    assert!(!dom.contains(6));
+    assert!(!dom.dominates(0, 6));
+
+    let frontiers = dom.frontiers();
+    assert_eq!(frontiers[2], [4]);
+    assert_eq!(frontiers[3], [4]);
+    // the loop header is in its own frontier + the latch's
+    assert_eq!(frontiers[4], [1]);
+    assert_eq!(frontiers[1], [1]);
+    assert!(frontiers[0].is_empty());
+}
+
+#[test]
+fn test_post_dominator_tree() {
+    let post = Dominators::post_of(&looping_diamond());
+    let ipdoms: Vec<_> = (0..7).map(|b| post.idom(b)).collect();
+    assert_eq!(ipdoms, [Some(1), Some(4), Some(4), Some(4), Some(5), None, Some(5)]);
+    assert!(post.dominates(4, 2));
+    assert!(post.dominates(5, 0));
+    assert!(!post.dominates(2, 1));
+
+    // post-dominance frontiers r control dependence: the arms on the branch in bb1, the loop body
+    // on the latch's
+    let frontiers = post.frontiers();
+    assert_eq!(frontiers[2], [1]);
+    assert_eq!(frontiers[3], [1]);
+    assert_eq!(frontiers[1], [4]);
+    assert_eq!(frontiers[4], [4]);
+    assert!(frontiers[5].is_empty());
+}
+
+#[test]
+fn test_dominators_of_a_function_with_an_endless_loop() {
+    // bb0 branches 2 bb1, which spins forever, or bb2, which returns
+    let mut func = MirFunction::new("spin".to_string(), None);
+    let (spin, done) = (func.new_block(), func.new_block());
+    let flag = Operand::Constant(Constant::Bool(true));
+    func.basic_blocks[0].instructions.push(Instruction::Br { condition: flag, then_bb: spin, else_bb: done });
+    func.basic_blocks[spin].instructions.push(Instruction::Jump { target: spin });
+    func.basic_blocks[done].instructions.push(Instruction::Ret { value: None });
+
+    let dom = Dominators::new(&func);
+    assert_eq!((dom.idom(spin), dom.idom(done)), (Some(0), Some(0)));
+    // bb1 never reaches an exit so nothing post-dominates it, the only way out of bb0 is bb2
+    let post = Dominators::post(&func);
+    assert!(!post.contains(spin));
+    assert_eq!(post.idom(0), Some(done));
+    assert_eq!(post.idom(done), None);
+}
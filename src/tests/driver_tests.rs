@@ -0,0 +1,367 @@
+use crate::backend::factory::{BackendRegistry, BackendType, TargetSupport};
+use crate::cli::args::{Cli, CompileConfig, ErrorFormat};
+use crate::cli::backends::{describe, describe_json};
+use crate::cli::cache::{clean, BackendFingerprint, CleanFilter};
+use crate::cli::compiler::{strict_summary, CompileError, Compiler};
+use crate::backend::ports::optimizer::OptimizationPass;
+use crate::cli::error_display::diagnostic_json;
+use crate::cli::manifest::Manifest;
+use crate::cli::passes::PassPlan;
+use crate::core::mir::dump::DumpFilter;
+use crate::core::target::TargetInfo;
+use crate::error::columns::{display_column, DEFAULT_TAB_WIDTH};
+use crate::error::{Diagnostic, DiagnosticKind, Lint, Reporter};
+use clap::Parser;
+use codespan::{ByteIndex, Span};
+use std::path::{Path, PathBuf};
+
+fn configs(args: &[&str]) -> Result<Vec<CompileConfig>, String> {
+    let mut argv = vec!["emerald"];
+    argv.extend_from_slice(args);
+    CompileConfig::for_inputs(&Cli::parse_from(argv))
+}
+
+#[test]
+fn test_driver_compiles_each_input_to_its_own_output() {
+    let outputs = |args: &[&str]| -> Vec<PathBuf> {
+        configs(args).unwrap().into_iter().map(|c| c.output.unwrap()).collect()
+    };
+    assert_eq!(outputs(&["src/main.em"]), vec![PathBuf::from("main")]);
+    assert_eq!(outputs(&["a.em", "lib/b.em", "--emit=obj"]), vec![PathBuf::from("a.o"), PathBuf::from("b.o")]);
+    assert_eq!(outputs(&["a.em", "--emit", "ir"]), vec![PathBuf::from("a.ll")]);
+    assert_eq!(outputs(&["a.em", "-S"]), vec![PathBuf::from("a.s")]);
+    assert_eq!(outputs(&["a.em", "--emit=mir"]), vec![PathBuf::from("a.mir")]);
+    assert_eq!(outputs(&["a.em", "--crate-type", "staticlib"]), vec![PathBuf::from("liba.a")]);
+    assert_eq!(outputs(&["a.em", "-o", "out/app"]), vec![PathBuf::from("out/app")]);
+
+    let config = &configs(&["a.em", "-O", "3", "--target", "aarch64-unknown-linux-gnu"]).unwrap()[0];
+    assert_eq!(config.opt_level, "3");
+    assert_eq!(config.target.as_deref(), Some("aarch64-unknown-linux-gnu"));
+    assert_eq!(config.error_format, ErrorFormat::Human);
+    assert_eq!(config.tab_width, DEFAULT_TAB_WIDTH);
+    assert_eq!(configs(&["a.em", "--tab-width", "8"]).unwrap()[0].tab_width, 8);
+    assert!(Cli::try_parse_from(["emerald", "a.em", "--tab-width", "0"]).is_err());
+
+    assert_eq!(configs(&["a.em", "-C", "inline-threshold=0"]).unwrap()[0].inline_threshold, 0);
+    assert!(configs(&["a.em", "-C", "inline-threshold=lots"]).is_err());
+    assert_eq!(configs(&["a.em", "-Z", "inline-threshold=50"]).unwrap()[0].inline_threshold, 50);
+    assert!(configs(&["a.em", "-Z", "inline-threshold=-1"]).is_err());
+    assert_eq!(configs(&["a.em"]).unwrap()[0].profile_use, None);
+    let config = &configs(&["a.em", "-Z", "dump-mir=dce", "-Z", "dump-mir-dir=out/mir"]).unwrap()[0];
+    assert_eq!((config.dump_mir.clone(), config.dump_mir_dir.clone()), (Some(DumpFilter::Pass("dce".to_string())), PathBuf::from("out/mir")));
+    assert_eq!(configs(&["a.em", "-Z", "dump-mir=all"]).unwrap()[0].dump_mir, Some(DumpFilter::All));
+    assert!(configs(&["a.em", "-Z", "dump-mir=dse"]).unwrap_err().starts_with("Unknown MIR pass 'dse'"));
+    let profile_use = configs(&["a.em", "-C", "profile-use=calls.prof"]).unwrap()[0].profile_use.clone();
+    assert_eq!(profile_use, Some(PathBuf::from("calls.prof")));
+    assert!(configs(&["a.em"]).unwrap()[0].null_checks);
+    assert!(!configs(&["a.em", "-C", "null-checks=no"]).unwrap()[0].null_checks);
+    assert_eq!(configs(&["a.em", "b.em", "-o", "app"]).unwrap_err(), "Cannot use -o with multiple input files");
+    assert_eq!(configs(&["a.em", "--emit", "exe2"]).unwrap_err(), "Unknown emit type: exe2");
+    assert_eq!(configs(&[]).unwrap_err(), "No input file specified");
+}
+
+#[test]
+fn test_driver_pass_pipeline() {
+    let plan = |spec: &str| configs(&["a.em", &format!("--passes={}", spec)]).map(|c| c[0].passes.clone().unwrap());
+    let passes = plan("dce, const-prop,llvm:gvn,llvm:mem2reg").unwrap();
+    assert_eq!(passes.mir, ["dce", "const-prop"]);
+    assert_eq!(passes.llvm, [OptimizationPass::Custom("gvn".to_string()), OptimizationPass::Mem2Reg]);
+    assert_eq!(plan("default,dce").unwrap().mir, ["const-prop", "stack-promote", "load-store", "dce", "dce"]);
+    assert_eq!(plan("").unwrap(), PassPlan::default());
+    assert_eq!(configs(&["a.em"]).unwrap()[0].passes, None);
+
+    assert_eq!(plan("cosnt-prop").unwrap_err(), "Unknown optimization pass 'cosnt-prop', did you mean 'const-prop'?");
+    assert_eq!(plan("sroa").unwrap_err(), "Unknown optimization pass 'sroa', did you mean 'llvm:sroa'?");
+    assert_eq!(plan("llvm:gnv").unwrap_err(), "Unknown optimization pass 'llvm:gnv', did you mean 'llvm:gvn'?");
+    assert!(plan("sparkle").unwrap_err().starts_with("Unknown optimization pass 'sparkle', expected one of: default, const-prop"));
+
+    // only the MIR passes move, inlining + the cleanup around them stay put
+    let config = configs(&["a.em", "--passes=load-store,const-prop"]).unwrap().remove(0);
+    let names = Compiler::new(config).mir_optimizer().pass_names();
+    assert_eq!(names, ["inline", "inst-combine", "copy-prop", "load-store", "const-prop", "dead-locals", "renumber-locals", "phi-opt", "simplify-blocks"]);
+
+    let manifest = Manifest::parse(Path::new("emerald.toml"), "[package]\nname = \"app\"\n\n[build]\npasses = \"default,llvm:licm\"\n").unwrap();
+    assert_eq!(manifest.passes.as_deref(), Some("default,llvm:licm"));
+}
+
+#[test]
+fn test_driver_checks_flags_against_the_backend() {
+    // the chk runs b4 the input is read, so it needn't exist
+    let error = |args: &[&str]| match Compiler::new(configs(args).unwrap().remove(0)).compile() {
+        Err(CompileError::Unsupported(message)) => message,
+        other => panic!("expected an unsupported flag, got {:?}", other.map(|r| r.success)),
+    };
+    let host = TargetInfo::host().arch;
+    let foreign = if host == "aarch64" { "x86_64-unknown-linux-gnu" } else { "aarch64-unknown-linux-gnu" };
+    assert_eq!(
+        error(&["missing.em", "--interp", "--target", foreign, "-o", "out"]),
+        format!("interp backend cannot generate code for '{}', it only targets the host ({})", foreign, host)
+    );
+    assert_eq!(error(&["missing.em", "--null", "-g", "-o", "out"]), "null backend cannot emit debug info, drop -g or use --llvm");
+
+    assert!(TargetSupport::Host.supports(&TargetInfo::host().triple));
+    assert!(!TargetSupport::Host.supports(foreign));
+    assert!(TargetSupport::Any.supports(foreign));
+}
+
+#[test]
+fn test_driver_lists_backends() {
+    let registry = BackendRegistry::new();
+    let text = describe(&registry);
+    let line = |name: &str| text.lines().find(|l| l.starts_with(name)).unwrap().split_whitespace().collect::<Vec<_>>();
+    // llvm is the default when built w/ it, null otherwise
+    let default = if cfg!(feature = "llvm") { "llvm" } else { "null" };
+    assert_eq!(line(default)[..2], [default, "*"]);
+    let null = line("null");
+    assert_eq!(null.iter().filter(|w| **w != "*").copied().collect::<Vec<_>>(), ["null", "any", "no", "no", "mir"]);
+    assert_eq!(line("cranelift").get(1) == Some(&"not"), !cfg!(feature = "cranelift"), "{}", text);
+    assert_eq!(line("native"), ["native", "not", "implemented", "yet"]);
+    assert_eq!(text.lines().count(), BackendType::ALL.len() + 2);
+
+    let json = describe_json(&registry);
+    assert!(json.contains(r#"{"name":"interp","default":false,"available":true,"targets":"host","debug_info":false,"jit":true,"preferred_input":"mir"}"#), "{}", json);
+    assert!(json.contains(&format!(r#"{{"name":"{}","default":true,"available":true"#, default)), "{}", json);
+    assert!(json.starts_with("[{") && json.ends_with("}]"));
+}
+
+#[test]
+fn test_driver_json_diagnostics() {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("main.em".to_string(), "def main()\n  x : int = \"a\\tb\"\nend\n".to_string());
+    let diagnostic = Diagnostic::error(
+        DiagnosticKind::TypeError,
+        Span::new(ByteIndex(13), ByteIndex(14)),
+        file_id,
+        "Type mismatch: expected int, found \"string\"".to_string(),
+    )
+    .with_note("notes\ttoo".to_string())
+    .with_label(file_id, Span::new(ByteIndex(0), ByteIndex(10)), "in this fn".to_string());
+    assert_eq!(
+        diagnostic_json(&diagnostic, reporter.files(), DEFAULT_TAB_WIDTH),
+        concat!(
+            r#"{"severity":"error","code":"E0003","message":"Type mismatch: expected int, found \"string\"","lint":null,"#,
+            r#""file":"main.em","line":2,"column":3,"end_line":2,"end_column":4,"display_column":3,"end_display_column":4,"#,
+            r#""notes":["notes\ttoo"],"labels":[{"file":"main.em","line":1,"column":1,"end_line":1,"end_column":11,"#,
+            r#""display_column":1,"end_display_column":11,"message":"in this fn"}]}"#,
+        )
+    );
+
+    let mut warning = Diagnostic::warning(DiagnosticKind::SemanticError, Span::new(ByteIndex(0), ByteIndex(3)), file_id, "old".to_string());
+    warning.lint = Some(Lint::Deprecated);
+    assert!(diagnostic_json(&warning, reporter.files(), DEFAULT_TAB_WIDTH).starts_with(r#"{"severity":"warning","code":"E0006","message":"old","lint":"deprecated","#));
+}
+
+#[test]
+fn test_driver_columns_follow_display_width() {
+    assert_eq!(display_column("x = 1", 4, 4), 5);
+    // wide chars take 2 cells, a combining accent none
+    assert_eq!(display_column("s = \"日本語\" + y", 16, 4), 14);
+    assert_eq!(display_column("e\u{301}x", 3, 4), 2);
+    assert_eq!(display_column("🎉 z", 5, 4), 4);
+    // tabs run 2 the next stop, an offset inside a char points at it
+    assert_eq!(display_column("\tx", 1, 4), 5);
+    assert_eq!(display_column("ab\tx", 3, 8), 9);
+    assert_eq!(display_column("日x", 1, 4), 1);
+
+    // the lexer's spans r byte offsets, so the token after a CJK string is where it looks
+    let source = "def main() returns int\n  s : string = \"日本語\"\n\treturn missing\nend\n";
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("main.em".to_string(), source.to_string());
+    let tokens = crate::frontend::lexer::Lexer::new(source, file_id, &mut reporter).tokenize();
+    let missing = tokens.iter().find(|t| matches!(&t.kind, crate::frontend::lexer::TokenKind::Identifier(n) if n == "missing")).unwrap();
+    assert_eq!(&source[missing.span.start().to_usize()..missing.span.end().to_usize()], "missing");
+    let diagnostic = Diagnostic::error(DiagnosticKind::NameResolutionError, missing.span, file_id, "Undefined".to_string());
+    let json = diagnostic_json(&diagnostic, reporter.files(), 8);
+    assert!(json.contains(r#""line":3,"column":9,"end_line":3,"end_column":16,"display_column":16,"end_display_column":23"#), "{}", json);
+}
+
+#[test]
+fn test_driver_writes_nothing_for_a_program_with_errors() {
+    let dir = std::env::temp_dir().join(format!("emc-driver-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("bad.em");
+    std::fs::write(&input, "def main() returns int\n  return missing\nend\n").unwrap();
+    let output = dir.join("bad.o");
+    let cli = Cli::parse_from(["emerald", input.to_str().unwrap(), "--emit=obj", "-o", output.to_str().unwrap()]);
+    let config = CompileConfig::from_cli(&cli).unwrap();
+    let result = Compiler::new(config).compile().unwrap();
+    assert!(!result.success);
+    assert!(!output.exists());
+
+    // a backend that cant write the output fails the build instead of only warning
+    std::fs::write(&input, "def main() returns int\n  return 0\nend\n").unwrap();
+    let output = dir.join("missing-dir").join("bad.o");
+    let cli = Cli::parse_from(["emerald", input.to_str().unwrap(), "--emit=obj", "-o", output.to_str().unwrap()]);
+    let err = Compiler::new(CompileConfig::from_cli(&cli).unwrap()).compile().unwrap_err();
+    assert!(matches!(err, CompileError::BackendFailed(_)), "{}", err);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_driver_emits_mir() {
+    let dir = std::env::temp_dir().join(format!("emc-driver-mir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("twice.em");
+    std::fs::write(&input, "def twice(x : int) returns int\n  return x + x\nend\n").unwrap();
+    let output = dir.join("twice.mir");
+    // no backend is picked, so even one thats not built in works
+    let cli = Cli::parse_from(["emerald", input.to_str().unwrap(), "--no-prelude", "--emit=mir", "--cranelift", "-o", output.to_str().unwrap()]);
+    let result = Compiler::new(CompileConfig::from_cli(&cli).unwrap()).compile().unwrap();
+    assert!(result.success);
+    assert_eq!(
+        std::fs::read_to_string(&output).unwrap(),
+        concat!(
+            "fn @twice(%0: int) -> int {\n",
+            "    let %0: int  ; x\n",
+            "    let %1: int\n",
+            "\n",
+            "  bb0:\n",
+            "    %1 = add int %0, %0\n",
+            "    ret %1\n",
+            "}\n",
+        )
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_driver_emits_cfg_dot() {
+    assert_eq!(configs(&["a.em", "--emit=cfg-dot"]).unwrap()[0].output, Some(PathBuf::from("a.dot")));
+
+    let dir = std::env::temp_dir().join(format!("emc-driver-dot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("sign.em");
+    std::fs::write(&input, "def sign(x : int) returns int\n  if x < 0\n    return 0 - 1\n  end\n  return 1\nend\n").unwrap();
+    let output = dir.join("sign.dot");
+    let cli = Cli::parse_from(["emerald", input.to_str().unwrap(), "--no-prelude", "--emit=cfg-dot", "-o", output.to_str().unwrap()]);
+    let result = Compiler::new(CompileConfig::from_cli(&cli).unwrap()).compile().unwrap();
+    assert!(result.success);
+    let dot = std::fs::read_to_string(&output).unwrap();
+    assert!(dot.starts_with("digraph \"sign\" {\n"), "{}", dot);
+    assert!(dot.contains("  bb0 [label=\"bb0:\\l  %1 = lt %0, 0\\l  br %1, bb1, bb2\\l\", penwidth=2];\n"), "{}", dot);
+    assert!(dot.contains("  bb0 -> bb1 [label=\"true\"];\n  bb0 -> bb2 [label=\"false\"];\n"), "{}", dot);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_driver_caches_objects_per_backend_fingerprint() {
+    let dir = std::env::temp_dir().join(format!("emc-driver-cache-{}", std::process::id()));
+    let cache = dir.join("incremental");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("seven.em");
+    let output = dir.join("seven.o");
+    let incremental = format!("incremental={}", cache.display());
+    let compile_with = |opt_level: &str, extra: &[&str]| {
+        let argv = [input.to_str().unwrap(), "--no-prelude", "--null", "--emit=obj", "-C", &incremental, "-O", opt_level];
+        let argv = ["emerald", "-o", output.to_str().unwrap()].into_iter().chain(argv).chain(extra.iter().copied());
+        let result = Compiler::new(CompileConfig::from_cli(&Cli::parse_from(argv)).unwrap()).compile().unwrap();
+        assert!(result.success);
+        std::fs::read_to_string(&output).unwrap()
+    };
+    let compile = |opt_level: &str| compile_with(opt_level, &[]);
+    let fingerprints = || {
+        let mut names: Vec<String> = std::fs::read_dir(&cache).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().to_string()).collect();
+        names.sort();
+        names
+    };
+    let objects = |name: &str| std::fs::read_dir(cache.join(name)).unwrap().filter(|e| e.as_ref().unwrap().path().extension().is_some_and(|x| x == "o")).count();
+
+    std::fs::write(&input, "def seven() returns int\n  return 7\nend\n").unwrap();
+    let first = compile("2");
+    // a hit is copied back even when the output was changed since
+    std::fs::write(&output, "stale").unwrap();
+    assert_eq!(compile("2"), first);
+    assert_eq!(fingerprints().len(), 1);
+
+    // another opt lvl never sees the -O2 object
+    compile("0");
+    let host = TargetInfo::host().triple;
+    let names = fingerprints();
+    assert!(names[0].starts_with(&format!("null-{}-O0-", host)) && names[1].starts_with(&format!("null-{}-O2-", host)), "{:?}", names);
+    let text = std::fs::read_to_string(cache.join(&names[0]).join(BackendFingerprint::FILE_NAME)).unwrap();
+    let fingerprint = BackendFingerprint::parse(&text).unwrap();
+    assert_eq!((fingerprint.backend, fingerprint.opt_level.as_str()), (BackendType::Null, "0"));
+    assert!(fingerprint.settings.contains(&("panic".to_string(), "abort".to_string())));
+    assert!(fingerprint.settings.contains(&("shared".to_string(), "false".to_string())));
+
+    // hidden symbols r baked in2 the object, so they get their own
+    compile_with("0", &["--visibility", "hidden", "--export", "seven"]);
+    let hidden = fingerprints();
+    assert_eq!(hidden.len(), 3, "{:?}", hidden);
+    let name = hidden.iter().find(|n| !names.contains(n)).unwrap();
+    let text = std::fs::read_to_string(cache.join(name).join(BackendFingerprint::FILE_NAME)).unwrap();
+    let settings = BackendFingerprint::parse(&text).unwrap().settings;
+    assert!(settings.contains(&("visibility".to_string(), "Hidden".to_string())) && settings.contains(&("exports".to_string(), "seven".to_string())), "{:?}", settings);
+    std::fs::remove_dir_all(cache.join(name)).unwrap();
+
+    // changed source is a new object under the same fingerprint
+    std::fs::write(&input, "def seven() returns int\n  return 8\nend\n").unwrap();
+    compile("0");
+    assert_eq!((objects(&names[0]), objects(&names[1])), (2, 1));
+
+    let registry = BackendRegistry::new();
+    let only = |opt_level: &str| CleanFilter { opt_level: Some(opt_level.to_string()), ..Default::default() };
+    assert_eq!(clean(&cache, &only("0"), &registry), Ok(2));
+    assert_eq!(fingerprints(), [names[1].clone()]);
+    assert_eq!(clean(&cache, &CleanFilter { backend: Some(BackendType::Llvm), ..Default::default() }, &registry), Ok(0));
+    assert_eq!(clean(&cache, &CleanFilter { stale: true, ..Default::default() }, &registry), Ok(0));
+
+    // a fingerprint frm an older emc is stale
+    let file = cache.join(&names[1]).join(BackendFingerprint::FILE_NAME);
+    let old = std::fs::read_to_string(&file).unwrap().replace(env!("CARGO_PKG_VERSION"), "0.0.0-old");
+    std::fs::write(&file, old).unwrap();
+    assert_eq!(clean(&cache, &CleanFilter { stale: true, ..Default::default() }, &registry), Ok(1));
+    assert!(fingerprints().is_empty());
+
+    // a dir that isnt the cache's survives even an unfiltered clean
+    std::fs::create_dir_all(cache.join("src")).unwrap();
+    std::fs::write(cache.join("src").join("main.em"), "def main() returns int\n  return 0\nend\n").unwrap();
+    std::fs::create_dir_all(cache.join(".git")).unwrap();
+    std::fs::write(cache.join(".git").join(BackendFingerprint::FILE_NAME), "not a fingerprint").unwrap();
+    compile("0");
+    assert_eq!(clean(&cache, &CleanFilter::default(), &registry), Ok(1));
+    assert!(cache.join("src").join("main.em").exists() && cache.join(".git").join(BackendFingerprint::FILE_NAME).exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_driver_strict_mode_fails_on_warnings() {
+    assert!(!configs(&["a.em"]).unwrap()[0].strict);
+    assert!(configs(&["a.em", "-Z", "strict"]).unwrap()[0].strict);
+    assert_eq!(configs(&["a.em", "-Z", "loose"]).unwrap_err(), "Unknown -Z option: loose");
+
+    let dir = std::env::temp_dir().join(format!("emc-driver-strict-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("strict.em");
+    let compile = |source: &str, strict: bool| {
+        std::fs::write(&input, source).unwrap();
+        let output = dir.join("strict.mir");
+        let mut args = vec!["emerald", input.to_str().unwrap(), "--no-prelude", "--emit=mir", "-o", output.to_str().unwrap()];
+        if strict {
+            args.extend(["-Z", "strict"]);
+        }
+        let config = CompileConfig::from_cli(&Cli::parse_from(args)).unwrap();
+        let result = Compiler::new(config.clone()).compile();
+        (result.as_ref().map(|r| r.success).unwrap(), strict_summary(&config, result.as_ref()))
+    };
+    let warns = "def f(x : int) returns int\n  match x\n  when 1\n    return 1\n  when 1\n    return 2\n  else\n    return 0\n  end\n  return 0\nend\n";
+    assert!(compile(warns, false).0);
+    let (success, summary) = compile(warns, true);
+    assert!(!success);
+    assert_eq!(
+        summary,
+        format!(
+            "{{\"strict\":true,\"input\":{:?},\"passed\":false,\"errors\":0,\"warnings\":1,\"nondeterministic\":[],\"ice\":null,\"failure\":null}}",
+            input.to_str().unwrap()
+        )
+    );
+    let (success, summary) = compile("def f(x : int) returns int\n  return x\nend\n", true);
+    assert!(success);
+    assert!(summary.contains("\"passed\":true"), "{}", summary);
+
+    std::fs::remove_file(&input).unwrap();
+    let config = CompileConfig::from_cli(&Cli::parse_from(["emerald", input.to_str().unwrap(), "-Z", "strict"])).unwrap();
+    let error = Compiler::new(config.clone()).compile().unwrap_err();
+    assert!(strict_summary(&config, Err(&error)).contains("\"passed\":false"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
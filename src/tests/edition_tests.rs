@@ -0,0 +1,111 @@
+use crate::cli::args::{Cli, CompileConfig};
+use crate::cli::manifest::Manifest;
+use crate::core::edition::{Edition, EditionFeature};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use clap::Parser as _;
+use std::path::Path;
+
+/// messages of the errors `source` gets when lexed, parsed + analyzed in `edition`
+fn check(source: &str, edition: Edition) -> Vec<String> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).with_edition(edition).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).with_edition(edition).parse();
+    if !reporter.has_errors() {
+        SemanticAnalyzer::new(&mut reporter, file_id).with_edition(edition).analyze(&ast);
+    }
+    reporter.diagnostics().iter().map(|d| d.message.clone()).collect()
+}
+
+#[test]
+fn test_edition_features() {
+    assert_eq!(Edition::default(), Edition::E2025);
+    assert_eq!(Edition::from_str("2026"), Some(Edition::E2026));
+    assert_eq!(Edition::from_str("2024"), None);
+    assert!(!Edition::E2025.has(EditionFeature::ReservedKeywords));
+    assert!(Edition::LATEST.has(EditionFeature::ReservedKeywords));
+    assert!(Edition::E2026.is_reserved("yield"));
+    assert!(!Edition::E2025.is_reserved("yield"));
+}
+
+#[test]
+fn test_reserved_words_are_identifiers_before_2026() {
+    let source = r#"
+def main() returns int
+  yield : int = 1
+  await : int = 2
+  return yield + await
+end
+"#;
+    assert!(check(source, Edition::E2025).is_empty());
+    assert_eq!(
+        check(source, Edition::E2026),
+        vec![
+            "'yield' is a reserved keyword in edition 2026",
+            "'await' is a reserved keyword in edition 2026",
+            "'yield' is a reserved keyword in edition 2026",
+            "'await' is a reserved keyword in edition 2026",
+        ]
+    );
+}
+
+#[test]
+fn test_require_is_gone_in_2026() {
+    let source = r#"
+require "std/io"
+
+def main() returns int
+  return 0
+end
+"#;
+    let errors = check(source, Edition::E2026);
+    assert_eq!(errors, vec!["'require' was replaced by 'import' in edition 2026"]);
+    assert!(!check(source, Edition::E2025).iter().any(|e| e.contains("replaced by 'import'")));
+}
+
+#[test]
+fn test_manifest_edition() {
+    let path = Path::new("emerald.toml");
+    let manifest = Manifest::parse(path, "# app\n[package]\nname = \"app\"\nedition = \"2026\" # latest\n\n[deps]\nfoo = 1\n").unwrap();
+    assert_eq!(manifest.name.as_deref(), Some("app"));
+    assert_eq!(manifest.edition, Some(Edition::E2026));
+
+    assert_eq!(Manifest::parse(path, "[package]\nname = \"app\"\n").unwrap().edition, None);
+    let err = Manifest::parse(path, "[package]\nedition = \"2030\"\n").unwrap_err();
+    assert_eq!(err, "emerald.toml:2: unknown edition '2030', expected 2025 or 2026");
+    let err = Manifest::parse(path, "[package]\nedition = 2026\n").unwrap_err();
+    assert_eq!(err, "emerald.toml:2: 'edition' must be a string");
+}
+
+#[test]
+fn test_edition_comes_from_the_closest_manifest() {
+    let dir = std::env::temp_dir().join(format!("emc-edition-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("src/util")).unwrap();
+    std::fs::write(dir.join("emerald.toml"), "[package]\nedition = \"2026\"\n").unwrap();
+    let main = dir.join("src/main.em");
+    std::fs::write(&main, "import util::gen\n\ndef main() returns int\n  return gen::next()\nend\n").unwrap();
+    std::fs::write(dir.join("src/util/gen.em"), "def next() returns int\n  yield : int = 1\n  return yield\nend\n").unwrap();
+
+    assert_eq!(Manifest::edition_for(&main).unwrap(), Edition::E2026);
+    let config = CompileConfig::from_cli(&Cli::parse_from(["emerald", main.to_str().unwrap()])).unwrap();
+    assert_eq!(config.edition, Edition::E2026);
+    // the flag wins over the manifest
+    let cli = Cli::parse_from(["emerald", main.to_str().unwrap(), "--edition", "2025"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap().edition, Edition::E2025);
+    let cli = Cli::parse_from(["emerald", main.to_str().unwrap(), "--edition", "2030"]);
+    assert!(CompileConfig::from_cli(&cli).is_err());
+
+    // imported modules r read w/ the package's edition too
+    let source = std::fs::read_to_string(&main).unwrap();
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file(main.to_string_lossy().to_string(), source.clone());
+    let tokens = Lexer::new(&source, file_id, &mut reporter).with_edition(Edition::E2026).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).with_edition(Edition::E2026).parse();
+    SemanticAnalyzer::new(&mut reporter, file_id).with_edition(Edition::E2026).analyze(&ast);
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+    std::fs::remove_dir_all(&dir).ok();
+    assert!(errors.contains(&"'yield' is a reserved keyword in edition 2026".to_string()), "{:?}", errors);
+}
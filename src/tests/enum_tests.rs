@@ -0,0 +1,178 @@
+use crate::core::ast::Item;
+use crate::core::mir::{Instruction, MirFunction};
+use crate::core::types::composite::{EnumType, Variant};
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan::Files;
+
+fn analyze_source(source: &str) -> (crate::core::ast::Ast, Reporter) {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    if !reporter.has_errors() {
+        SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    }
+    (ast, reporter)
+}
+
+fn messages(reporter: &Reporter, severity: Severity) -> Vec<String> {
+    reporter.diagnostics().iter().filter(|d| d.severity == severity).map(|d| d.message.clone()).collect()
+}
+
+fn lower_mir(source: &str) -> Vec<MirFunction> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+const SHAPE: &str = r#"
+enum Shape
+  Circle(int)
+  Rect(int, long)
+  Empty
+end
+"#;
+
+#[test]
+fn test_enum_layout_is_tag_plus_largest_payload() {
+    let (ast, reporter) = analyze_source(SHAPE);
+    assert!(!reporter.has_errors());
+    let Item::Enum(e) = &ast.items[0] else { panic!("expected an enum") };
+    let arities: Vec<_> = e.variants.iter().map(|v| (v.name.as_str(), v.fields.len())).collect();
+    assert_eq!(arities, [("Circle", 1), ("Rect", 2), ("Empty", 0)]);
+
+    let variant = |name: &str, fields: Vec<Type>| Variant { name: name.to_string(), fields };
+    let shape = EnumType {
+        name: "Shape".to_string(),
+        variants: vec![
+            variant("Circle", vec![Type::Primitive(PrimitiveType::Int)]),
+            variant("Rect", vec![Type::Primitive(PrimitiveType::Int), Type::Primitive(PrimitiveType::Long)]),
+            variant("Empty", vec![]),
+        ],
+    };
+    // Rect is i32 + pad + i64 = 16 bytes, 2 words after the tag
+    assert_eq!(shape.payload_words(), 2);
+    assert_eq!(Type::Enum(shape.clone()).size_in_bytes(), Some(24));
+    assert_eq!(shape.tag("Empty"), Some(2));
+
+    let unit = EnumType { name: "Color".to_string(), variants: vec![variant("Red", vec![]), variant("Blue", vec![])] };
+    assert_eq!(Type::Enum(unit).size_in_bytes(), Some(4));
+}
+
+#[test]
+fn test_variant_construction_is_checked_against_the_declaration() {
+    let source = format!(
+        "{}{}",
+        SHAPE,
+        r#"
+def make() returns Shape
+  a : Shape = Shape::Rect(2)
+  b : Shape = Shape::Square
+  return Shape::Circle(true)
+end
+"#
+    );
+    let (_, reporter) = analyze_source(&source);
+    let errors = messages(&reporter, Severity::Error);
+    assert!(errors.iter().any(|m| m == "Variant 'Shape::Rect' takes 2 value(s), got 1"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Enum 'Shape' has no variant 'Square'"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m.starts_with("Argument 0 type mismatch")), "{:?}", errors);
+}
+
+#[test]
+fn test_enum_match_reports_missing_unknown_and_duplicate_variants() {
+    let source = format!(
+        "{}{}",
+        SHAPE,
+        r#"
+def area(s : Shape) returns int
+  match s
+    when Circle(r, q)
+      return 1
+    when Triangle
+      return 2
+    when Shape::Circle(r)
+      return 3
+    when 4
+      return 4
+  end
+  match s
+    when Circle(_), Rect(_, _)
+      return 5
+    when Empty
+      return 6
+    else
+      return 7
+  end
+  return 0
+end
+"#
+    );
+    let (_, reporter) = analyze_source(&source);
+    let errors = messages(&reporter, Severity::Error);
+    assert!(errors.iter().any(|m| m == "Pattern Circle(r, q) binds 2 value(s) but 'Shape::Circle' has 1"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Enum 'Shape' has no variant 'Triangle'"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Pattern 4 can't match a value of type Shape"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Match on Shape is not exhaustive, missing Rect, Empty"), "{:?}", errors);
+
+    let warnings = messages(&reporter, Severity::Warning);
+    assert!(warnings.iter().any(|m| m == "Unreachable pattern: Shape::Circle(r) is already matched"), "{:?}", warnings);
+    assert!(warnings.iter().any(|m| m == "Unreachable `else`: every value is already matched"), "{:?}", warnings);
+}
+
+#[test]
+fn test_enum_match_tests_the_tag_and_reads_bound_fields() {
+    let source = format!(
+        "{}{}",
+        SHAPE,
+        r#"
+def area(s : Shape) returns long
+  match s
+    when Shape::Circle(r)
+      return r * r * 3
+    when Rect(_, h)
+      return h
+    when Empty
+      return 0
+  end
+  return 0
+end
+
+def make() returns Shape
+  return Shape::Rect(2, 5)
+end
+"#
+    );
+    let mir = lower_mir(&source);
+    let insts = |name: &str| -> Vec<Instruction> {
+        let f = mir.iter().find(|f| f.name == name).unwrap();
+        f.basic_blocks.iter().flat_map(|bb| bb.instructions.clone()).collect()
+    };
+
+    let area = insts("area");
+    assert_eq!(area.iter().filter(|i| matches!(i, Instruction::EnumTag { .. })).count(), 1);
+    let reads: Vec<_> = area
+        .iter()
+        .filter_map(|i| match i {
+            Instruction::VariantField { tag, index, .. } => Some((*tag, *index)),
+            _ => None,
+        })
+        .collect();
+    // `_` binds nothing, Rect only reads h
+    assert_eq!(reads, [(0, 0), (1, 1)]);
+
+    let make = insts("make");
+    assert!(make.iter().any(|i| matches!(i, Instruction::MakeVariant { tag: 1, fields, .. } if fields.len() == 2)));
+}
@@ -0,0 +1,99 @@
+use crate::cli::args::{Cli, CompileConfig, MessageFormat};
+use crate::cli::compiler::Compiler;
+use crate::cli::daemon::{Daemon, DaemonRequest};
+use crate::cli::events::{BuildEvent, EventStream};
+use crate::cli::progress::CompilePhase;
+use clap::Parser as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("emc-events-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// the event lines of a build w/ `args`
+fn events(args: &[&str]) -> Vec<String> {
+    let cli = Cli::parse_from(["emerald", "--no-prelude", "--message-format=json"].iter().chain(args));
+    let config = CompileConfig::from_cli(&cli).unwrap();
+    assert_eq!(config.message_format, MessageFormat::Json);
+    let events = EventStream::buffered(&config.input);
+    let _ = Compiler::new(config).with_events(events.clone()).compile();
+    events.take().lines().map(str::to_string).collect()
+}
+
+fn named<'a>(lines: &'a [String], event: &str) -> Vec<&'a String> {
+    lines.iter().filter(|l| l.starts_with(&format!("{{\"event\":\"{}\"", event))).collect()
+}
+
+#[test]
+fn test_events_are_json_lines() {
+    let stream = EventStream::buffered(Path::new("dir/a \"b\".em"));
+    stream.emit(BuildEvent::PhaseFinished(CompilePhase::SemanticAnalysis, Duration::from_millis(12)));
+    stream.emit(BuildEvent::Artifact { kind: "obj", path: Path::new("out/a.o"), cached: true });
+    stream.emit(BuildEvent::BuildFinished { success: false, error: Some("Backend compilation failed: \"x\"") });
+    let lines: Vec<String> = stream.take().lines().map(str::to_string).collect();
+    assert_eq!(lines[0], r#"{"event":"phase-finished","input":"dir/a \"b\".em","phase":"semantic-analysis","ms":12}"#);
+    assert_eq!(lines[1], r#"{"event":"artifact","input":"dir/a \"b\".em","kind":"obj","path":"out/a.o","cached":true}"#);
+    assert!(lines[2].starts_with(r#"{"event":"build-finished","input":"dir/a \"b\".em","success":false,"error":"Backend compilation failed: \"x\"","ms":"#), "{}", lines[2]);
+    // taken once
+    assert_eq!(stream.take(), "");
+}
+
+#[test]
+fn test_phases_pair_up_and_diagnostics_arrive_with_their_phase() {
+    let dir = scratch("phases");
+    let input = dir.join("bad.em");
+    std::fs::write(&input, "def main() returns int\n  x : int = \"s\"\n  return x\nend\n").unwrap();
+    let lines = events(&["--null", input.to_str().unwrap(), "-o", dir.join("bad").to_str().unwrap()]);
+
+    assert!(lines[0].starts_with(r#"{"event":"build-started""#));
+    let started = named(&lines, "phase-started");
+    let finished = named(&lines, "phase-finished");
+    assert_eq!(started.len(), finished.len());
+    assert!(started[0].contains(r#""phase":"loading""#) && started[3].contains(r#""phase":"semantic-analysis""#), "{:?}", started);
+    // no codegen 4 a program w/ errors
+    assert!(!lines.iter().any(|l| l.contains(r#""phase":"codegen""#) || l.contains(r#""event":"artifact""#)));
+
+    let at = |line: &String| lines.iter().position(|l| l == line).unwrap();
+    let diagnostic = named(&lines, "diagnostic");
+    assert_eq!(diagnostic.len(), 1);
+    assert!(diagnostic[0].contains(r#""message":"Type mismatch"#), "{}", diagnostic[0]);
+    assert!(at(started[3]) < at(diagnostic[0]) && at(diagnostic[0]) < at(finished[3]));
+    assert!(lines.last().unwrap().starts_with(r#"{"event":"build-finished","input":"#) && lines.last().unwrap().contains(r#""success":false,"error":null"#));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_artifacts_say_when_they_came_from_the_cache() {
+    let dir = scratch("artifacts");
+    let input = dir.join("ok.em");
+    std::fs::write(&input, "def seven() returns int\n  return 7\nend\n").unwrap();
+    let output = dir.join("ok.o");
+    let cache = format!("incremental={}", dir.join("cache").display());
+    let args = ["--null", "--emit=obj", "-C", &cache, input.to_str().unwrap(), "-o", output.to_str().unwrap()];
+    let artifact = |cached: bool| format!(r#""kind":"obj","path":"{}","cached":{}}}"#, output.display(), cached);
+
+    let first = events(&args);
+    assert_eq!(named(&first, "artifact").len(), 1);
+    assert!(named(&first, "artifact")[0].ends_with(&artifact(false)), "{:?}", first);
+    assert!(first.last().unwrap().contains(r#""success":true"#));
+    let again = events(&args);
+    assert!(named(&again, "artifact")[0].ends_with(&artifact(true)), "{:?}", again);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_daemon_sends_the_events_back() {
+    let dir = scratch("daemon");
+    std::fs::write(dir.join("ok.em"), "def main() returns int\n  return 0\nend\n").unwrap();
+    let request = DaemonRequest { cwd: dir.clone(), args: ["--no-prelude", "--message-format=json", "check", "ok.em"].map(String::from).to_vec(), color: false };
+    let reply = Daemon::new().handle(&request);
+    assert_eq!((reply.code, reply.stderr.as_str()), (0, ""));
+    let lines: Vec<String> = reply.stdout.lines().map(str::to_string).collect();
+    assert!(lines.iter().all(|l| l.starts_with("{\"event\":")), "{}", reply.stdout);
+    assert!(lines.last().unwrap().contains(r#""success":true"#));
+    let _ = std::fs::remove_dir_all(&dir);
+}
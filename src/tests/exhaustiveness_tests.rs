@@ -0,0 +1,136 @@
+use crate::core::types::composite::{EnumType, Variant};
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::usefulness::{witnesses, Ctor, Domain, Pat};
+use crate::frontend::semantic::SemanticAnalyzer;
+use codespan::Files;
+
+fn analyze_source(source: &str) -> Reporter {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    assert!(!reporter.has_errors(), "{:?}", reporter.diagnostics());
+    SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    reporter
+}
+
+/// (message, notes) of evry diagnostic w/ `severity`
+fn diagnostics(reporter: &Reporter, severity: Severity) -> Vec<(String, Vec<String>)> {
+    reporter.diagnostics().iter().filter(|d| d.severity == severity).map(|d| (d.message.clone(), d.notes.clone())).collect()
+}
+
+fn shape() -> EnumType {
+    let variant = |name: &str, fields: usize| Variant { name: name.to_string(), fields: vec![Type::Primitive(PrimitiveType::Int); fields] };
+    EnumType { name: "Shape".to_string(), variants: vec![variant("Circle", 1), variant("Rect", 2), variant("Empty", 0)] }
+}
+
+fn range(lo: i128, hi: i128) -> Vec<Pat> {
+    vec![Pat::new(Ctor::Range(lo, hi), 0)]
+}
+
+#[test]
+fn test_witnesses_list_what_no_row_matches() {
+    let shape = shape();
+    let domain = Domain::Enum(&shape);
+    let rows = vec![vec![Pat::new(Ctor::Variant(1), 2)]];
+    let missing: Vec<String> = witnesses(&rows, &[Pat::wild()], &[domain]).iter().map(|w| domain.show(&w[0])).collect();
+    assert_eq!(missing, ["Circle(_)", "Empty"]);
+
+    // nothing matched yet, the whole column is 1 `_`
+    assert_eq!(witnesses(&[], &[Pat::wild()], &[domain]), vec![vec![Pat::wild()]]);
+    // a row thats already covered is useless
+    let rows = vec![vec![Pat::wild()]];
+    assert!(witnesses(&rows, &[Pat::new(Ctor::Variant(2), 0)], &[domain]).is_empty());
+}
+
+#[test]
+fn test_int_ranges_split_at_every_arm() {
+    let domain = Domain::Int(0, 255);
+    let rows = vec![range(0, 9), range(20, 29), range(10, 14)];
+    let missing: Vec<String> = witnesses(&rows, &[Pat::wild()], &[domain]).iter().map(|w| domain.show(&w[0])).collect();
+    assert_eq!(missing, ["15 .. 19", "30 .. 255"]);
+    // covered by 2 arms together, no single 1
+    assert!(witnesses(&rows, &range(5, 12), &[domain]).is_empty());
+    assert_eq!(witnesses(&rows, &range(12, 16), &[domain]), vec![range(15, 16)]);
+}
+
+#[test]
+fn test_two_columns_check_the_fields_together() {
+    let domains = [Domain::Bool, Domain::Int(-128, 127)];
+    let bool_ = |b: bool| Pat::new(Ctor::Bool(b), 0);
+    let rows = vec![vec![bool_(true), Pat::wild()], vec![Pat::wild(), Pat::new(Ctor::Range(0, 127), 0)]];
+    assert_eq!(witnesses(&rows, &[Pat::wild(), Pat::wild()], &domains), vec![vec![bool_(false), Pat::new(Ctor::Range(-128, -1), 0)]]);
+    assert!(witnesses(&rows, &[bool_(false), Pat::new(Ctor::Range(3, 3), 0)], &domains).is_empty());
+}
+
+#[test]
+fn test_missing_arms_are_suggested() {
+    let reporter = analyze_source(
+        r#"
+enum Shape
+  Circle(int)
+  Rect(int, int)
+  Empty
+end
+
+def pick(s : Shape, b : ubyte, name : string) returns int
+  match s
+    when Circle(r)
+      return r
+  end
+  match b
+    when 0 .. 9, 20 .. 29
+      return 1
+  end
+  match name
+    when "a"
+      return 2
+  end
+  return 0
+end
+"#,
+    );
+    let errors = diagnostics(&reporter, Severity::Error);
+    let note = |message: &str| errors.iter().find(|(m, _)| m == message).map(|(_, notes)| notes.clone());
+    assert_eq!(note("Match on Shape is not exhaustive, missing Rect, Empty"), Some(vec!["add `when Rect(_, _)`, `when Empty` or an `else` arm".to_string()]));
+    assert_eq!(note("Match on ubyte is not exhaustive, missing 10 .. 19, 30 .. 255"), Some(vec!["add `when 10 .. 19`, `when 30 .. 255` or an `else` arm".to_string()]));
+    // strings cant all be listed
+    assert_eq!(note("Match on string is not exhaustive, add an `else` arm"), Some(Vec::new()));
+}
+
+#[test]
+fn test_arms_covered_by_earlier_arms_are_unreachable() {
+    let reporter = analyze_source(
+        r#"
+def pick(n : int) returns int
+  match n
+    when 0 .. 9
+      return 1
+    when 10 .. 19
+      return 2
+    when 5 .. 15, 3
+      return 3
+    when 25, 12
+      return 4
+    else
+      return 0
+  end
+  return 0
+end
+"#,
+    );
+    assert!(!reporter.has_errors(), "{:?}", reporter.diagnostics());
+    let warnings = diagnostics(&reporter, Severity::Warning);
+    let never_runs = vec!["every pattern of this arm is matched by an earlier arm, so its body never runs".to_string()];
+    // no single arm covers 5 .. 15, the 2 of them do
+    assert!(warnings.contains(&("Unreachable pattern: 5 .. 15 is already matched".to_string(), Vec::new())), "{:?}", warnings);
+    assert!(warnings.contains(&("Unreachable pattern: 3 is already matched".to_string(), never_runs)), "{:?}", warnings);
+    // 25 is new so the arm still runs
+    assert!(warnings.contains(&("Unreachable pattern: 12 is already matched".to_string(), Vec::new())), "{:?}", warnings);
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+}
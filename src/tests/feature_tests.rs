@@ -0,0 +1,129 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+
+fn parse_errors(source: &str) -> Vec<(String, Vec<String>)> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    Parser::new(tokens, file_id, &mut reporter).parse();
+    reporter.diagnostics().iter().map(|d| (d.message.clone(), d.notes.clone())).collect()
+}
+
+/// main's exit code on the interpreter
+fn run(source: &str) -> i32 {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbols = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+    assert!(!reporter.has_errors(), "{:?}", errors);
+    let mir = MirLowerer::new().lower(&HirLowerer::new(symbols).lower(&ast));
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&mir).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module).unwrap()
+}
+
+#[test]
+fn test_macros_expand_behind_their_gate() {
+    let source = r#"
+@feature("macros")
+macro sq(x) = x * x
+macro add3(a, b, c) = a + b + c
+
+def main() returns int
+  return add3!(sq!(1 + 2), 1, sq!(2)) - 1
+end
+"#;
+    // args + body keep their precedence: (1 + 2) * (1 + 2)
+    assert_eq!(run(source), 13);
+
+    // a fn w/ the macro's name is still called w/o the `!`
+    let source = r#"
+@feature("macros")
+macro twice(x) = x + x
+
+def twice(x : int) returns int
+  return x * 2 + 1
+end
+
+def main() returns int
+  return twice!(2) + twice(2)
+end
+"#;
+    assert_eq!(run(source), 9);
+}
+
+#[test]
+fn test_gate_error_explains_how_to_enable() {
+    let errors = parse_errors("macro sq(x) = x * x\n");
+    assert_eq!(
+        errors,
+        vec![(
+            "Macros are experimental".to_string(),
+            vec!["add `@feature(\"macros\")` at the top of the file to enable it".to_string()]
+        )]
+    );
+
+    let errors = parse_errors("@feature(\"macros\", \"asm\")\n");
+    assert_eq!(errors[0].0, "Unknown feature 'asm', expected one of: macros");
+}
+
+#[test]
+fn test_macro_invocation_errors() {
+    let source = r#"
+@feature("macros")
+macro sq(x) = x * x
+macro sq(y) = y
+
+def main() returns int
+  return sq!(1, 2)
+end
+"#;
+    let errors: Vec<_> = parse_errors(source).into_iter().map(|(message, _)| message).collect();
+    assert!(errors.contains(&"Macro 'sq' is already defined".to_string()), "{:?}", errors);
+    assert!(errors.contains(&"Macro 'sq' takes 1 argument(s) but 2 were given".to_string()), "{:?}", errors);
+
+    // the body cant see the macro being defined, so it only expands 2 itself when used
+    let source = r#"
+@feature("macros")
+macro forever(x) = forever!(x)
+
+def main() returns int
+  return forever!(1)
+end
+"#;
+    let errors: Vec<_> = parse_errors(source).into_iter().map(|(message, _)| message).collect();
+    assert!(errors.contains(&"Macro 'forever' expands more than 64 levels deep".to_string()), "{:?}", errors);
+}
+
+#[test]
+fn test_errors_in_a_macro_body_point_at_the_call() {
+    let source = r#"
+@feature("macros")
+macro bad(x) = x + missing
+
+def main() returns int
+  return bad!(2)
+end
+"#;
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    let error = reporter.diagnostics().iter().find(|d| d.message == "Undefined variable 'missing'").unwrap();
+    let span = |text: &str| {
+        let start = source.find(text).unwrap();
+        (start, start + text.len())
+    };
+    assert_eq!((error.span.start().to_usize(), error.span.end().to_usize()), span("bad!(2)"));
+    assert_eq!(error.notes, vec!["this happens in the expansion of macro 'bad'".to_string()]);
+    let definition = error.labels[0].span;
+    assert_eq!((definition.start().to_usize(), definition.end().to_usize()), span("macro bad(x) = x + missing"));
+}
@@ -91,14 +91,121 @@ end
 fn test_foreign_variadic_function() {
     let source = r#"
 foreign "C" stdio
-  def printf(format : ref char) returns int
-  def sprintf(buffer : ref char, format : ref char) returns int
+  def printf(format : ref char, ...) returns int
+  def sprintf(buffer : ref char, format : ref char, ...) returns int
 end
 
 def main
-  result : int = printf(null)
+  n : int = 3
+  result : int = printf("%d %d\n", n, 4)
+  buf : ref char = null
+  written : int = sprintf(buf, "%d", n)
 end
 "#;
-    let (_ast, reporter) = analyze_source(source);
+    let (ast, reporter) = analyze_source(source);
     assert!(!reporter.has_errors());
+    let crate::core::ast::Item::Foreign(f) = &ast.items[0] else { panic!("expected a foreign block") };
+    let variadic: Vec<_> = f.functions.iter().map(|ff| (ff.name.as_str(), ff.params.len(), ff.variadic)).collect();
+    assert_eq!(variadic, [("printf", 1, true), ("sprintf", 2, true)]);
+}
+
+#[test]
+fn test_printf_format_checked_against_args() {
+    let source = r#"
+foreign "C" stdio
+  def printf(format : ref char, ...) returns int
+  def snprintf(buffer : ref char, size : size, format : ref char, ...) returns int
+end
+
+def main
+  n : int = 3
+  big : long = 4
+  x : float = 1.5
+  ok : int = printf("%d items, %5.2f avg, %ld total %%\n", n, x, big)
+  bad : int = printf("%d and %s", x)
+  buf : ref char = null
+  cap : size = 8
+  more : int = snprintf(buf, cap, "%x", n, n)
+  unknown : int = printf("%q", n)
+end
+"#;
+    let (_ast, reporter) = analyze_source(source);
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| (d.message.clone(), d.span)).collect();
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    assert!(errors[0].0.starts_with("Format '%d' expects an integer"));
+    // points at the specifier inside the literal
+    let line = source.find("\"%d and %s\"").unwrap() + 1;
+    assert_eq!(errors[1].0, "Format '%s' has no matching argument");
+    assert_eq!(errors[1].1, codespan::Span::new((line + 7) as u32, (line + 9) as u32));
+    assert_eq!(errors[2].0, "'snprintf' got 2 argument(s) but its format string uses 1");
+    let q = source.find("%q").unwrap();
+    assert_eq!(errors[3].0, "Unknown format specifier '%q'");
+    assert_eq!(errors[3].1, codespan::Span::new(q as u32, (q + 2) as u32));
+}
+
+#[test]
+fn test_parse_format_specifiers() {
+    use crate::frontend::semantic::format::{parse_format, FormatExpect, IntWidth};
+
+    let args = parse_format("%-*.*s|%zu|%p|%c").unwrap();
+    let expects: Vec<_> = args.iter().map(|a| a.expect).collect();
+    assert_eq!(
+        expects,
+        vec![
+            FormatExpect::Count,
+            FormatExpect::Count,
+            FormatExpect::CString,
+            FormatExpect::Int(IntWidth::Size),
+            FormatExpect::Pointer,
+            FormatExpect::Char,
+        ]
+    );
+    assert!(parse_format("%n").is_err());
+    assert!(parse_format("%Lf").is_err());
+    assert_eq!(parse_format("100%").unwrap_err().message, "Incomplete format specifier at end of string");
+}
+
+#[test]
+fn test_foreign_calling_conventions() {
+    use crate::core::target::{CallConv, TargetInfo};
+
+    let source = r#"
+foreign "stdcall" kernel32
+  def GetTickCount returns int
+  def Sleep(ms : int) with abi = "system"
+end
+
+foreign "pascal" old
+  def legacy(x : int) returns int
+end
+
+foreign "fastcall" fmt
+  def log(format : ref char, ...) returns int
+  def shout(x : int) returns int with abi = "loud"
+end
+"#;
+    let (ast, reporter) = analyze_source(source);
+    let crate::core::ast::Item::Foreign(f) = &ast.items[0] else { panic!("expected a foreign block") };
+    assert_eq!(f.abi, "stdcall");
+    assert_eq!(f.functions[1].abi.as_deref(), Some("system"));
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        errors,
+        [
+            "Unknown calling convention 'pascal'",
+            "Variadic function 'log' must use the C calling convention, not 'fastcall'",
+            "Unknown calling convention 'loud'",
+        ]
+    );
+
+    // the x86 conventions only mean something on 32 bit x86, `system` is stdcall only on win32
+    let win32 = TargetInfo::from_triple("i686-pc-windows-msvc");
+    let win64 = TargetInfo::from_triple("x86_64-pc-windows-msvc");
+    let arm = TargetInfo::from_triple("aarch64-unknown-linux-gnu");
+    assert_eq!(CallConv::System.resolve(&win32), CallConv::Stdcall);
+    assert_eq!(CallConv::System.resolve(&win64), CallConv::C);
+    assert_eq!(CallConv::Stdcall.resolve(&win64), CallConv::C);
+    assert_eq!(CallConv::Fastcall.resolve(&win32), CallConv::Fastcall);
+    assert_eq!(CallConv::Vectorcall.resolve(&win64), CallConv::Vectorcall);
+    assert_eq!(CallConv::Win64.resolve(&arm), CallConv::C);
 }
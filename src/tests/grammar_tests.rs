@@ -0,0 +1,60 @@
+use crate::core::edition::Edition;
+use crate::error::Reporter;
+use crate::frontend::lexer::token::{TokenKind, KEYWORDS};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::grammar::{ebnf, RULES};
+use crate::frontend::parser::precedence::POSTFIX_OPERATORS;
+use crate::frontend::parser::Parser;
+
+fn rule<'a>(grammar: &'a str, name: &str) -> &'a str {
+    let prefix = format!("{} ::= ", name);
+    grammar
+        .lines()
+        .find_map(|line| line.strip_prefix(prefix.as_str()))
+        .unwrap_or_else(|| panic!("no rule '{}' in\n{}", name, grammar))
+}
+
+#[test]
+fn test_grammar_operator_levels_follow_the_parser() {
+    let grammar = ebnf(Edition::LATEST);
+    assert_eq!(rule(&grammar, "expression"), "assignment");
+    assert_eq!(rule(&grammar, "assignment"), r#"or_expr ( "=" assignment )?"#);
+    assert_eq!(rule(&grammar, "or_expr"), r#"and_expr ( "||" and_expr )*"#);
+    assert_eq!(rule(&grammar, "comparison"), r#"term ( ( "<" | "<=" | ">" | ">=" ) term )*"#);
+    assert_eq!(rule(&grammar, "factor"), r#"unary ( ( "*" | "/" | "%" ) unary )*"#);
+    // `not` is lexed as `!`
+    assert_eq!(rule(&grammar, "unary"), r#"( "-" | "!" | "not" ) unary | "try" unary | call"#);
+    assert_eq!(rule(&ebnf(Edition::E2025), "unary"), r#"( "-" | "!" | "not" ) unary | call"#);
+    for token in POSTFIX_OPERATORS {
+        let symbol = format!("\"{}\"", token.symbol().unwrap());
+        assert!(rule(&grammar, "postfix").contains(&symbol), "{} missing frm postfix", symbol);
+    }
+}
+
+#[test]
+fn test_grammar_lists_every_keyword_and_edition_changes() {
+    let latest = ebnf(Edition::E2026);
+    for (word, _) in KEYWORDS {
+        assert!(latest.contains(&format!("\"{}\"", word)), "keyword {} missing", word);
+    }
+    assert_eq!(TokenKind::keyword_from_str("implement"), Some(TokenKind::Implement));
+    assert!(rule(&latest, "reserved").contains("\"async\""));
+    assert!(!rule(&latest, "item").contains("require"));
+
+    let old = ebnf(Edition::E2025);
+    assert!(rule(&old, "item").contains("| require |"));
+    assert!(!old.contains("reserved ::="));
+}
+
+#[test]
+fn test_grammar_rule_examples_parse() {
+    for r in RULES {
+        let mut reporter = Reporter::new();
+        let file_id = reporter.add_file(format!("{}.em", r.name), r.example.to_string());
+        let tokens = Lexer::new(r.example, file_id, &mut reporter).tokenize();
+        let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+        let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+        assert!(errors.is_empty(), "example of '{}' doesnt parse: {:?}", r.name, errors);
+        assert!(!ast.items.is_empty() || r.name == "feature_gate" || r.name == "macro_def", "{}", r.name);
+    }
+}
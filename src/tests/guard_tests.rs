@@ -0,0 +1,122 @@
+use crate::core::ast::{Item, Stmt};
+use crate::core::mir::{Instruction, MirFunction};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::noreturn::NoReturn;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan::Files;
+
+fn analyze_source(source: &str) -> (crate::core::ast::Ast, Reporter) {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    if !reporter.has_errors() {
+        SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    }
+    (ast, reporter)
+}
+
+fn lower_mir(source: &str) -> Vec<MirFunction> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+const EXITS: &str = r#"
+foreign "C" stdlib
+  def exit(code : int)
+end
+
+def fail(code : int)
+  exit(code)
+end
+
+def spin
+  while true
+    fail(2)
+  end
+end
+"#;
+
+#[test]
+fn test_noreturn_analysis_follows_calls() {
+    let (ast, reporter) = analyze_source(EXITS);
+    assert!(!reporter.has_errors());
+    let noreturn = NoReturn::analyze(&ast.items);
+    assert!(noreturn.is_noreturn("exit"));
+    assert!(noreturn.is_noreturn("fail"));
+    assert!(noreturn.is_noreturn("spin"));
+
+    // a fn w/ a return can come back even if every path looks like it diverges
+    let (ast, _reporter) = analyze_source("def maybe(n : int) returns int\n  if n > 0\n    return 1\n  end\n  while true\n  end\nend\n");
+    assert!(!NoReturn::analyze(&ast.items).is_noreturn("maybe"));
+}
+
+#[test]
+fn test_guard_else_must_diverge() {
+    let source = format!(
+        r#"{}
+def check(n : int) returns int
+  guard n >= 0 else
+    return 0
+  end
+  mut i : int = 0
+  while i < n
+    i = i + 1
+    guard i != 3 else
+      continue
+    end
+    guard i < 10 else
+      break
+    end
+  end
+  guard n < 100 else
+    fail(1)
+  end
+  return n
+end
+"#,
+        EXITS
+    );
+    let (ast, reporter) = analyze_source(&source);
+    assert!(!reporter.has_errors());
+    let check = ast.items.iter().find_map(|i| match i {
+        Item::Function(f) if f.name == "check" => f.body.as_ref(),
+        _ => None,
+    });
+    assert!(matches!(check.and_then(|b| b.first()), Some(Stmt::Guard(_))));
+
+    let falls_through = source.replace("    fail(1)\n", "    n = 1\n");
+    let (_ast, reporter) = analyze_source(&falls_through);
+    assert!(reporter.diagnostics().iter().any(|d| d.message.starts_with("Guard's else block must not fall through")));
+}
+
+#[test]
+fn test_guard_lowers_to_inverted_branch() {
+    let source = r#"
+def clamp(n : int) returns int
+  guard n >= 0 else
+    return 0
+  end
+  return n * 2
+end
+"#;
+    let mir = lower_mir(source);
+    let clamp = mir.iter().find(|f| f.name == "clamp").unwrap();
+    let entry = &clamp.basic_blocks[clamp.entry_block];
+    let Some(Instruction::Br { then_bb, else_bb, .. }) = entry.instructions.last() else { panic!("expected a branch") };
+    // true carries on w/ the rest of the fn, false runs the else block
+    let rest = &clamp.basic_blocks[*then_bb].instructions;
+    assert!(rest.iter().any(|i| matches!(i, Instruction::Mul { .. })));
+    assert!(matches!(rest.last(), Some(Instruction::Ret { value: Some(_) })));
+    assert!(matches!(clamp.basic_blocks[*else_bb].instructions.last(), Some(Instruction::Ret { value: Some(_) })));
+}
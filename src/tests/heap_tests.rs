@@ -0,0 +1,337 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::core::mir::passes::{MirPass, StackPromote};
+use crate::core::mir::{Constant, Instruction, MirFunction, Operand, ALLOC_FN, FREE_FN};
+use crate::core::types::pointer::PointerType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+
+/// the errors checking `source` reports, + its MIR when there r none
+fn compile(source: &str) -> Result<Vec<MirFunction>, Vec<String>> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    if reporter.has_errors() {
+        return Err(reporter.diagnostics().iter().map(|d| d.message.clone()).collect());
+    }
+    Ok(MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast)))
+}
+
+/// evry error checking `source` reports as its message + the messages of its labels
+fn errors(source: &str) -> Vec<(String, Vec<String>)> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    reporter
+        .diagnostics()
+        .iter()
+        .map(|d| (d.message.clone(), d.labels.iter().map(|l| l.message.clone()).collect()))
+        .collect()
+}
+
+fn run(mir: &[MirFunction]) -> i32 {
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(mir).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module).unwrap()
+}
+
+fn function<'a>(mir: &'a [MirFunction], name: &str) -> &'a MirFunction {
+    mir.iter().find(|f| f.name == name).unwrap()
+}
+
+/// (callee, args) of evry call `func` makes 2 the allocator
+fn allocator_calls(func: &MirFunction) -> Vec<(String, Vec<Operand>)> {
+    func.basic_blocks
+        .iter()
+        .flat_map(|bb| &bb.instructions)
+        .filter_map(|inst| match inst {
+            Instruction::Call { func: Operand::Function(f), args, .. } if f.name == ALLOC_FN || f.name == FREE_FN => {
+                Some((f.name.clone(), args.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+const LIST: &str = r#"
+struct Node
+  item : int
+  next : ref? Node
+end
+
+def push(head : ref? Node, item : int) returns ref Node
+  return new Node { item: item, next: head }
+end
+
+def main() returns int
+  n : ref int = new 40
+  list : ref Node = push(push(null, 1), 2)
+  total : int = n.value + list.item
+  delete n
+  delete list
+  return total
+end
+"#;
+
+#[test]
+fn test_new_and_delete_go_thru_the_runtime_allocator() {
+    let mir = compile(LIST).unwrap();
+    let push = mir.iter().find(|f| f.name == "push").unwrap();
+    // an int + a ptr, 16 bytes
+    assert_eq!(allocator_calls(push), [(ALLOC_FN.to_string(), vec![Operand::Constant(Constant::Int(16))])]);
+    let main = mir.iter().find(|f| f.name == "main").unwrap();
+    let calls: Vec<String> = allocator_calls(main).into_iter().map(|(name, _)| name).collect();
+    assert_eq!(calls, [ALLOC_FN, FREE_FN, FREE_FN]);
+    let alloc = main.basic_blocks[0].instructions.iter().find_map(|inst| match inst {
+        Instruction::Call { func: Operand::Function(f), return_type, .. } if f.name == ALLOC_FN => return_type.clone(),
+        _ => None,
+    });
+    let int = Type::Primitive(PrimitiveType::Int);
+    assert_eq!(alloc, Some(Type::Pointer(PointerType::new(int, false))));
+
+    assert_eq!(run(&mir), 42);
+}
+
+#[test]
+fn test_new_and_delete_are_type_checked() {
+    let errors = compile("def main() returns int\n  delete 1\n  return 0\nend\n").unwrap_err();
+    assert!(errors.iter().any(|e| e == "'delete' needs a ref, got Primitive(Int)"), "{:?}", errors);
+
+    let errors = compile("def main() returns int\n  n : int = new 1\n  return n\nend\n").unwrap_err();
+    assert!(errors.iter().any(|e| e.contains("mismatch")), "{:?}", errors);
+}
+
+#[test]
+fn test_a_program_can_bring_its_own_allocator() {
+    // the interpreter calls these instead of its own, `delete` exits w/ what it frees
+    let source = r#"
+foreign "C" libc
+  def malloc(size : int) returns ref int
+  def exit(code : int)
+end
+
+def emerald_alloc(size : int) returns ref int
+  return malloc(size)
+end
+
+def emerald_free(p : ref int)
+  exit(p.value + 100)
+end
+
+def main() returns int
+  a : ref int = new 7
+  delete a
+  return 0
+end
+"#;
+    assert_eq!(run(&compile(source).unwrap()), 107);
+}
+
+/// a custom allocator that marks what it frees, + `main` calling `work`
+const MARKING: &str = r#"
+foreign "C" libc
+  def malloc(size : int) returns ref int
+end
+
+def emerald_alloc(size : int) returns ref int
+  return malloc(size)
+end
+
+def emerald_free(p : ref int)
+  p.value = p.value + 100
+end
+
+def main() returns int
+  return work(1)
+end
+"#;
+
+#[test]
+fn test_a_copy_made_thru_a_call_isnt_tracked() {
+    // `b` aliases `a` but the chk only follows locals, so reading it after the delete goes unreported
+    let source = format!(
+        "{}{}",
+        MARKING,
+        r#"
+def alias(p : ref int) returns ref int
+  return p
+end
+
+def work(n : int) returns int
+  a : ref int = new 7
+  b : ref int = alias(a)
+  delete a
+  return b.value
+end
+"#
+    );
+    assert_eq!(errors(&source), []);
+    assert_eq!(run(&compile(&source).unwrap()), 107);
+}
+
+#[test]
+fn test_a_new_that_stays_local_is_promoted_to_the_stack() {
+    let mut mir = compile(LIST).unwrap();
+    let main = mir.iter_mut().find(|f| f.name == "main").unwrap();
+    let mut promote = StackPromote::default();
+    assert!(promote.run(main));
+    // `n` is gone, `list` came frm push + isnt main's 2 place
+    let left: Vec<String> = allocator_calls(main).into_iter().map(|(name, _)| name).collect();
+    assert_eq!(left, [FREE_FN]);
+    assert!(main.basic_blocks[0].instructions.iter().any(|inst| matches!(inst, Instruction::Alloca { type_: Type::Primitive(_), .. })));
+    assert_eq!(run(&mir), 42);
+}
+
+const OWNERS: &str = r#"
+struct Pair
+  a : int
+  b : int
+end
+
+def make(n : int) returns ref Pair
+  p : ref Pair = new Pair { a: n, b: 1 }
+  return p
+end
+
+def pick(flag : bool) returns int
+  p : ref int = new 10
+  q : ref int = new 20
+  if flag
+    delete p
+    return q.value
+  end
+  r : ref int = q
+  return p.value + r.value
+end
+
+def main() returns int
+  total : int = pick(true) + pick(false)
+  x : ref int = new 1
+  if total > 5
+    x = new 2
+  end
+  return total + x.value
+end
+"#;
+
+fn names(calls: Vec<(String, Vec<Operand>)>) -> Vec<String> {
+    calls.into_iter().map(|(name, _)| name).collect()
+}
+
+#[test]
+fn test_what_a_local_still_owns_is_freed_when_its_scope_ends() {
+    let mir = compile(OWNERS).unwrap();
+    // `p` moves out w/ the return
+    assert_eq!(names(allocator_calls(function(&mir, "make"))), [ALLOC_FN]);
+    // the early return frees `q` + the deleted `p` isnt freed again, the fallthrough frees `p` + `r`
+    assert_eq!(names(allocator_calls(function(&mir, "pick"))), [ALLOC_FN, ALLOC_FN, FREE_FN, FREE_FN, FREE_FN, FREE_FN]);
+    // the old `x` goes b4 `new 2` replaces it
+    assert_eq!(names(allocator_calls(function(&mir, "main"))), [ALLOC_FN, ALLOC_FN, FREE_FN, FREE_FN]);
+    assert_eq!(run(&mir), 52);
+}
+
+#[test]
+fn test_use_after_move_and_double_delete_are_errors() {
+    let source = "def main() returns int\n  x : ref int = new 1\n  y : ref int = x\n  return x.value\nend\n";
+    assert_eq!(errors(source), [("'x' is used after it was moved".to_string(), vec!["'x' is moved here".to_string()])]);
+
+    let source = "def main() returns int\n  x : ref int = new 1\n  delete x\n  delete x\n  return 0\nend\n";
+    assert_eq!(errors(source), [("'x' is deleted twice".to_string(), vec!["'x' is deleted here".to_string()])]);
+
+    let source = "def main() returns int\n  x : ref int = new 1\n  delete x\n  return x.value\nend\n";
+    assert_eq!(errors(source), [("'x' is used after it was deleted".to_string(), vec!["'x' is deleted here".to_string()])]);
+
+    let source = "def f(flag : bool) returns int\n  x : ref int = new 1\n  if flag\n    delete x\n  end\n  return x.value\nend\n";
+    let errors = errors(source);
+    assert_eq!(errors[0].0, "'x' is used after it may have been deleted", "{:?}", errors);
+}
+
+#[test]
+fn test_a_move_inside_a_loop_is_an_error() {
+    let source = r#"
+def main() returns int
+  x : ref int = new 1
+  i : int = 0
+  while i < 3
+    i = i + 1
+    y : ref int = x
+  end
+  return i
+end
+"#;
+    let errors = errors(source);
+    assert!(errors.iter().any(|(e, _)| e == "'x' is moved inside a loop, the next iteration would use it again"), "{:?}", errors);
+}
+
+#[test]
+fn test_a_shadowing_let_owns_its_own_allocation() {
+    let source = format!(
+        "{}{}",
+        MARKING,
+        r#"
+def work(n : int) returns int
+  a : ref int = new 1
+  if n > 0
+    a : ref int = new 2
+  end
+  return a.value
+end
+"#
+    );
+    let mir = compile(&source).unwrap();
+    // the inner `a` is freed when the if ends, the outer 1 when the fn returns
+    assert_eq!(names(allocator_calls(function(&mir, "work"))), [ALLOC_FN, ALLOC_FN, FREE_FN, FREE_FN]);
+    assert_eq!(run(&mir), 1);
+
+    // deleting the inner `a` leaves the outer 1 usable
+    let source = "def f(n : int) returns int\n  a : ref int = new 1\n  if n > 0\n    a : ref int = new 2\n    delete a\n  end\n  return a.value\nend\n";
+    assert_eq!(errors(source), []);
+}
+
+#[test]
+fn test_a_call_that_hands_over_an_allocation_gives_it_an_owner() {
+    let source = format!(
+        "{}{}",
+        MARKING,
+        r#"
+def make(n : int) returns ref int
+  if n > 5
+    return new n
+  end
+  m : int = n + 1
+  p : ref int = new m
+  return p
+end
+
+def again(n : int) returns ref int
+  return make(n)
+end
+
+def alias(p : ref int) returns ref int
+  return p
+end
+
+def work(n : int) returns int
+  a : ref int = again(n)
+  b : ref int = alias(a)
+  return b.value
+end
+"#
+    );
+    let mir = compile(&source).unwrap();
+    // `a` is freed like a `new` it made itself, `b` only borrows it
+    assert_eq!(names(allocator_calls(function(&mir, "work"))), [FREE_FN]);
+    assert_eq!(run(&mir), 2);
+
+    let source = "def make() returns ref int\n  return new 1\nend\n\ndef main() returns int\n  a : ref int = make()\n  b : ref int = a\n  return a.value\nend\n";
+    assert_eq!(errors(source), [("'a' is used after it was moved".to_string(), vec!["'a' is moved here".to_string()])]);
+}
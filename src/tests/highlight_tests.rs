@@ -0,0 +1,57 @@
+use crate::cli::highlight::{spellings, textmate, tree_sitter};
+use crate::core::edition::Edition;
+use crate::frontend::lexer::token::{TokenClass, EDITION_KEYWORDS, KEYWORDS, SYMBOLS};
+use crate::frontend::lexer::Lexer;
+use crate::error::Reporter;
+
+const CLASSES: &[TokenClass] = &[
+    TokenClass::Constant,
+    TokenClass::Type,
+    TokenClass::Control,
+    TokenClass::Keyword,
+    TokenClass::Operator,
+    TokenClass::Punctuation,
+];
+
+#[test]
+fn test_every_keyword_and_symbol_is_highlighted() {
+    let mut covered: Vec<&str> = CLASSES.iter().flat_map(|&c| {
+        let (words, symbols) = spellings(c);
+        words.into_iter().chain(symbols)
+    }).collect();
+    covered.sort();
+    let mut expected: Vec<&str> = KEYWORDS.iter().map(|(word, _)| *word).collect();
+    expected.extend(EDITION_KEYWORDS.iter().map(|(word, _, _)| *word));
+    expected.extend(SYMBOLS.iter().map(|kind| kind.symbol().unwrap()));
+    expected.sort();
+    assert_eq!(covered, expected);
+
+    // each spelling lexes 2 a token of the class it's highlighted as
+    for &class in CLASSES {
+        let (words, symbols) = spellings(class);
+        for spelling in words.into_iter().chain(symbols) {
+            let mut reporter = Reporter::new();
+            let file_id = reporter.add_file("test.em".to_string(), spelling.to_string());
+            let tokens = Lexer::new(spelling, file_id, &mut reporter).with_edition(Edition::LATEST).tokenize();
+            assert_eq!(tokens[0].kind.class(), class, "{}", spelling);
+        }
+    }
+
+    let tree_sitter = tree_sitter();
+    for word in ["\"def\"", "\"not\"", "\"ref?\"", "\"::\"", "\"true\""] {
+        assert!(tree_sitter.contains(word), "{} missing frm\n{}", word, tree_sitter);
+    }
+}
+
+#[test]
+fn test_textmate_grammar() {
+    let grammar = textmate();
+    assert!(grammar.contains(r#""scopeName": "source.emerald""#));
+    assert!(grammar.contains(r##"{"name": "comment.line.number-sign.emerald", "match": "#.*$"}"##));
+    assert!(grammar.contains(r#"{"name": "storage.type.emerald", "match": "ref\\?|\\b(?:ref|void|byte|int|long|size|float|bool|char|string|ubyte|uint|ulong)\\b"}"#), "{}", grammar);
+    // longer operators r tried first
+    let operators = grammar.lines().find(|l| l.contains("keyword.operator")).unwrap();
+    assert!(operators.contains(r#""match": "\\?\\?\\?exists|\\.\\.\\.|"#), "{}", operators);
+    assert!(operators.ends_with(r#"|\\b(?:not)\\b"},"#), "{}", operators);
+    assert!(grammar.find("storage.type").unwrap() < grammar.find("keyword.operator").unwrap());
+}
@@ -3,6 +3,8 @@ use crate::frontend::lexer::Lexer;
 use crate::frontend::parser::Parser;
 use crate::frontend::semantic::SemanticAnalyzer;
 use crate::middle::HirLowerer;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
 use codespan::Files;
 
 fn print_diagnostics(reporter: &Reporter, files: &Files<String>) {
@@ -84,3 +86,41 @@ end
     let (_hir, reporter) = lower_to_hir(source);
     assert!(!reporter.has_errors());
 }
+
+#[test]
+fn test_hir_field_access_uses_struct_definition() {
+    let source = r#"
+struct Inner
+  count : long
+end
+
+struct Outer
+  tag : int
+  inner : Inner
+end
+
+def get(o : Outer) returns long
+  return o.inner.count
+end
+"#;
+    let (hir, reporter) = lower_to_hir(source);
+    assert!(!reporter.has_errors());
+    let get = hir.items.iter().find_map(|item| match item {
+        crate::core::hir::HirItem::Function(f) if f.name == "get" => Some(f),
+        _ => None,
+    }).unwrap();
+    // annotation only names the struct, the param carries the full layout
+    match &get.params[0].type_ {
+        Type::Struct(s) => {
+            assert_eq!(s.fields.len(), 2);
+            assert!(matches!(&s.fields[1].type_, Type::Struct(inner) if inner.fields.len() == 1));
+        }
+        other => panic!("expected struct param, got {:?}", other),
+    }
+    match &get.body.as_ref().unwrap()[0] {
+        crate::core::hir::HirStmt::Return(r) => {
+            assert_eq!(r.value.as_ref().unwrap().type_(), &Type::Primitive(PrimitiveType::Long));
+        }
+        other => panic!("expected return, got {:?}", other),
+    }
+}
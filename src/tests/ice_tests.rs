@@ -0,0 +1,43 @@
+use crate::cli::ice::{catch, IceReport, ISSUE_URL, SNIPPET_LINES};
+use crate::cli::progress::CompilePhase;
+
+#[test]
+fn test_catch_turns_a_panic_into_its_message_and_location() {
+    assert_eq!(catch(|| 1).unwrap(), 1);
+
+    let panic = catch(|| -> i32 { panic!("bad block {}", 3) }).unwrap_err();
+    assert_eq!(panic.message, "bad block 3");
+    assert!(panic.location.as_deref().is_some_and(|l| l.contains("ice_tests.rs")), "{:?}", panic.location);
+    assert!(panic.backtrace.is_some());
+
+    // nested boundaries each catch their own
+    let outer = catch(|| catch(|| panic!("inner")).unwrap_err().message);
+    assert_eq!(outer.unwrap(), "inner");
+}
+
+#[test]
+fn test_ice_report_is_written_with_phase_and_source() {
+    let dir = std::env::temp_dir().join(format!("emc-ice-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("crash.em");
+    let source: String = (1..=SNIPPET_LINES + 5).map(|i| format!("# line {}\n", i)).collect();
+    std::fs::write(&input, source).unwrap();
+
+    let panic = catch(|| panic!("no block 7")).unwrap_err();
+    let mut report = IceReport::new(Some(CompilePhase::MirLowering), &input, panic);
+    let path = report.write_to(&dir).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains(&format!("version: emc {}", env!("CARGO_PKG_VERSION"))), "{}", text);
+    assert!(text.contains("phase: MIR lowering"), "{}", text);
+    assert!(text.contains("no block 7"), "{}", text);
+    assert!(text.contains("   1 | # line 1\n"), "{}", text);
+    assert!(!text.contains(&format!("# line {}\n", SNIPPET_LINES + 1)), "{}", text);
+    assert!(text.contains("(5 more lines)"), "{}", text);
+    assert!(text.contains("backtrace:"), "{}", text);
+
+    let shown = report.to_string();
+    assert!(shown.starts_with("internal compiler error (MIR lowering): no block 7"), "{}", shown);
+    assert!(shown.contains(&path.display().to_string()), "{}", shown);
+    assert!(shown.contains(ISSUE_URL), "{}", shown);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
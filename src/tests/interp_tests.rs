@@ -0,0 +1,147 @@
+use crate::backend::factory::{BackendFactory, BackendRegistry, BackendType};
+use crate::backend::interp::{InterpBackendFactory, Interpreter, Value};
+use crate::backend::ports::execution::ExecutionError;
+use crate::core::mir::MirFunction;
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+
+fn lower_mir(source: &str) -> Vec<MirFunction> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+    assert!(!reporter.has_errors(), "{:?}", errors);
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+/// main's exit code thru the same codegen -> execution engine path `emerald run --interp` takes
+fn interpret(source: &str) -> Result<i32, ExecutionError> {
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&lower_mir(source)).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module)
+}
+
+const FIB: &str = r#"
+def fib(n : int) returns int
+  if n < 2
+    return n
+  end
+  return fib(n - 1) + fib(n - 2)
+end
+
+def main() returns int
+  return fib(10) + 3
+end
+"#;
+
+#[test]
+fn test_interp_backend_is_always_registered() {
+    assert_eq!(BackendType::from_str("interp"), Some(BackendType::Interp));
+    assert_eq!(BackendType::from_str("Interpreter"), Some(BackendType::Interp));
+    let registry = BackendRegistry::new();
+    let factory = registry.get_factory(BackendType::Interp).unwrap();
+    assert!(factory.create_execution_engine().is_ok());
+    // still not picked unless asked 4
+    assert_ne!(registry.default_factory().backend_type(), BackendType::Interp);
+
+    let module = factory.create_codegen().unwrap().generate_from_mir(&lower_mir(FIB)).unwrap();
+    let err = factory.create_emitter().unwrap().emit_object(&module, std::path::Path::new("out.o")).unwrap_err();
+    assert!(err.to_string().contains("Interpreter backend cannot emit object files"), "{}", err);
+}
+
+#[test]
+fn test_interp_runs_calls_structs_enums_and_strings() {
+    assert_eq!(interpret(FIB).unwrap(), 58);
+
+    let source = r#"
+struct Point
+  x : int
+  y : int
+end
+
+enum Shape
+  Circle(int)
+  Rect(int, int)
+end
+
+def area(s : Shape) returns int
+  match s
+    when Circle(r)
+      return 3 * r * r
+    when Rect(w, h)
+      return w * h
+  end
+  return 0
+end
+
+def sum(p : Point) returns int
+  return p.x + p.y
+end
+
+def main() returns int
+  p : Point = Point { x: 1, y: 2 }
+  s : string = "ab" + "cde"
+  return sum(p) + area(Shape::Rect(2, 3)) + s.len()
+end
+"#;
+    assert_eq!(interpret(source).unwrap(), 14);
+
+    // any fn can be called directly, eg 4 comptime evaluation
+    let mut interpreter = Interpreter::new();
+    interpreter.add_functions(&lower_mir(FIB));
+    assert_eq!(interpreter.call("fib", vec![Value::Int(12)]).unwrap(), Value::Int(144));
+}
+
+#[test]
+fn test_interp_traps_instead_of_crashing() {
+    let source = r#"
+def div(a : int, b : int) returns int
+  return a / b
+end
+
+def main() returns int
+  return div(1, 0)
+end
+"#;
+    let err = interpret(source).unwrap_err();
+    assert_eq!(err.to_string(), "Execution trapped: division by zero in 'div'");
+
+    let source = r#"
+foreign "C" libc
+  def abort
+  def getpid returns int
+end
+
+def fail(code : int) returns int
+  if code > 0
+    abort()
+  end
+  return getpid()
+end
+"#;
+    let mut interpreter = Interpreter::new();
+    interpreter.add_functions(&lower_mir(source));
+    let err = interpreter.call("fail", vec![Value::Int(1)]).unwrap_err();
+    assert_eq!(err.to_string(), "Execution trapped: abort called in 'fail'");
+    // only a few host fns exist, the rest of libc isnt there
+    let err = interpreter.call("fail", vec![Value::Int(0)]).unwrap_err();
+    assert!(matches!(&err, ExecutionError::SymbolNotFound(name) if name == "getpid"), "{}", err);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_interp_agrees_with_llvm_jit() {
+    use crate::backend::llvm::LlvmBackendFactory;
+
+    let factory = LlvmBackendFactory::new();
+    // the module lives in its codegen's context
+    let mut codegen = factory.create_codegen().unwrap();
+    let module = codegen.generate_from_mir(&lower_mir(FIB)).unwrap();
+    let jitted = factory.create_execution_engine().unwrap().run_main(&module).unwrap();
+    assert_eq!(interpret(FIB).unwrap(), jitted);
+}
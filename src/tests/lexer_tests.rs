@@ -75,3 +75,47 @@ fn test_lexer_string_escape() {
         panic!("Expected string literal");
     }
 }
+
+#[test]
+fn test_token_stream_keeps_trivia() {
+    use crate::core::edition::Edition;
+    use crate::frontend::lexer::{token_stream, TriviaKind};
+
+    let source = "# header\ndef main()  // entry\n  return 1\nend\n";
+    let stream = token_stream(source, Edition::LATEST);
+    assert!(stream.errors.is_empty());
+
+    // trivia + text of evry token is the source again
+    let rebuilt: String = stream
+        .tokens
+        .iter()
+        .flat_map(|t| t.leading_trivia.iter().map(|tr| tr.text(source)).chain([t.text(source)]))
+        .collect();
+    assert_eq!(rebuilt, source);
+
+    let def = &stream.tokens[0];
+    assert_eq!(def.kind, TokenKind::Def);
+    let trivia: Vec<_> = def.leading_trivia.iter().map(|t| (t.kind, t.text(source))).collect();
+    assert_eq!(trivia, vec![(TriviaKind::Comment, "# header"), (TriviaKind::Whitespace, "\n")]);
+    let ret = stream.tokens.iter().find(|t| t.kind == TokenKind::Return).unwrap();
+    let trivia: Vec<_> = ret.leading_trivia.iter().map(|t| (t.kind, t.text(source))).collect();
+    assert_eq!(trivia, vec![(TriviaKind::Whitespace, "  "), (TriviaKind::Comment, "// entry"), (TriviaKind::Whitespace, "\n  ")]);
+
+    let eof = stream.tokens.last().unwrap();
+    assert_eq!(eof.kind, TokenKind::Eof);
+    assert_eq!(eof.text(source), "");
+    assert_eq!(eof.leading_trivia.len(), 1);
+}
+
+#[test]
+fn test_token_stream_reports_errors() {
+    use crate::core::edition::Edition;
+    use crate::frontend::lexer::token_stream;
+
+    let source = "x = \"open\nasync";
+    let stream = token_stream(source, Edition::LATEST);
+    assert!(matches!(stream.tokens[2].kind, TokenKind::Error(_)));
+    let messages: Vec<_> = stream.errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["Unterminated string literal", "'async' is a reserved keyword in edition 2026"]);
+    assert_eq!(token_stream(source, Edition::E2025).errors.len(), 1);
+}
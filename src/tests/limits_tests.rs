@@ -0,0 +1,103 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::cli::args::{Cli, CompileConfig};
+use crate::core::limits::Limits;
+use crate::core::optimizations::{HirOptimizer, MirOptimizer};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use clap::Parser as _;
+use codespan::Files;
+
+/// the error messages parsing `source` under `limits` reports
+fn errors(source: &str, limits: Limits) -> Vec<String> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).with_limits(limits).tokenize();
+    Parser::new(tokens, file_id, &mut reporter).with_limits(limits).parse();
+    reporter.diagnostics().iter().map(|d| d.message.clone()).collect()
+}
+
+#[test]
+fn test_limits_parse_from_the_command_line() {
+    let limits = Limits::parse("string-literal=4k,nesting=64").unwrap();
+    assert_eq!(limits.string_literal, 4096);
+    assert_eq!(limits.nesting, 64);
+    assert_eq!(limits.array_elements, Limits::default().array_elements);
+    assert_eq!(Limits::parse("array-elements=2M").unwrap().array_elements, 2 << 20);
+
+    assert_eq!(Limits::parse("depth=3").unwrap_err(), "Unknown limit 'depth', expected one of: string-literal, array-elements, nesting");
+    assert_eq!(Limits::parse("nesting").unwrap_err(), "Expected key=value in -Z limits, got 'nesting'");
+    assert_eq!(Limits::parse("nesting=lots").unwrap_err(), "Invalid value 'lots' for limit 'nesting'");
+
+    let config = |args: &[&str]| {
+        let mut argv = vec!["emerald", "a.em"];
+        argv.extend_from_slice(args);
+        CompileConfig::for_inputs(&Cli::parse_from(argv)).map(|c| c[0].limits)
+    };
+    assert_eq!(config(&[]).unwrap(), Limits::default());
+    assert_eq!(config(&["-Z", "limits=nesting=8"]).unwrap().nesting, 8);
+    assert!(config(&["-Z", "limits=nesting=0"]).is_err());
+}
+
+#[test]
+fn test_oversized_literals_are_errors() {
+    let limits = Limits { string_literal: 8, array_elements: 50, ..Limits::default() };
+
+    let source = "def main\n  s : string = \"0123456789\"\nend\n";
+    assert_eq!(errors(source, limits), vec!["String literal is 10 bytes, over the limit of 8"]);
+    assert!(errors("def main\n  s : string = \"01234567\"\nend\n", limits).is_empty());
+
+    // nested repeats multiply
+    let source = "def main\n  a : int[10][10] = [[0; 10]; 10]\nend\n";
+    assert_eq!(errors(source, limits)[0], "Array literal has 100 elements, over the limit of 50");
+    assert!(errors("def main\n  a : int[5][10] = [[0; 5]; 10]\nend\n", limits).is_empty());
+}
+
+#[test]
+fn test_deep_nesting_is_reported_once() {
+    let limits = Limits { nesting: 16, ..Limits::default() };
+    let nested = |depth: usize| format!("def main\n  x : int = {}1{}\nend\n", "(".repeat(depth), ")".repeat(depth));
+    let reported = errors(&nested(40), limits);
+    assert_eq!(reported[0], "Nesting is deeper than 16 levels");
+    assert_eq!(reported.iter().filter(|m| m.starts_with("Nesting")).count(), 1);
+    assert!(errors(&nested(4), limits).is_empty());
+
+    // deep enough 4 generated code, the parser grows its stack so no thread needs a big one
+    let reported = errors(&nested(5000), Limits::default());
+    assert_eq!(reported[0], "Nesting is deeper than 4096 levels");
+    assert!(errors(&nested(4000), Limits::default()).is_empty());
+}
+
+/// what main returns when `source` is compiled thru evry phase w/ no nesting limit + interpreted
+fn run_unlimited(source: &str) -> i32 {
+    let limits = Limits { nesting: usize::MAX, ..Limits::default() };
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).with_limits(limits).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).with_limits(limits).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let mut hir = HirLowerer::new(symbol_table).lower(&ast);
+    HirOptimizer::new().optimize(&mut hir);
+    let mut functions = MirLowerer::new().lower(&hir);
+    MirOptimizer::new().optimize_all(&mut functions);
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&functions).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module).unwrap()
+}
+
+#[test]
+fn test_deeply_nested_source_compiles_on_a_small_stack() {
+    // test threads get 2MB, each of these recursed past that b4 the phases grew their stack
+    let depth = 10_000;
+    let source = format!("def main returns int\n  x : int = {}1{}\n  return x - {}\nend\n", "(1+".repeat(depth), ")".repeat(depth), depth);
+    assert_eq!(run_unlimited(&source), 1);
+
+    let depth = 2_000;
+    let source = format!("def main returns int\n  x : int = 0\n{}  return 7\n{}  return x\nend\n", "  if x == 0\n".repeat(depth), "  end\n".repeat(depth));
+    assert_eq!(run_unlimited(&source), 7);
+}
@@ -0,0 +1,1418 @@
+use crate::backend::link::{self, DebugCompression, LinkOptions, LinkerFlavor, SplitDebugInfo, Strip, SymbolVisibility};
+use crate::core::target::TargetInfo;
+use crate::backend::objcopy::{ihex_record, LoadImage, Segment};
+use crate::backend::ports::emitter::{EmitError, EmitType};
+use std::path::{Path, PathBuf};
+
+/// minimal little endian elf32 w/ one PT_LOAD per (paddr, data, memsz)
+fn elf32(entry: u32, segments: &[(u32, &[u8], u32)]) -> Vec<u8> {
+    let phoff = 52u32;
+    let mut data_off = phoff + 32 * segments.len() as u32;
+
+    let mut elf = vec![0x7f, b'E', b'L', b'F', 1, 1, 1, 0];
+    elf.resize(16, 0);
+    elf.extend_from_slice(&2u16.to_le_bytes()); // ET_EXEC
+    elf.extend_from_slice(&40u16.to_le_bytes()); // EM_ARM
+    elf.extend_from_slice(&1u32.to_le_bytes());
+    elf.extend_from_slice(&entry.to_le_bytes());
+    elf.extend_from_slice(&phoff.to_le_bytes());
+    elf.extend_from_slice(&0u32.to_le_bytes()); // shoff
+    elf.extend_from_slice(&0u32.to_le_bytes()); // flags
+    elf.extend_from_slice(&52u16.to_le_bytes());
+    elf.extend_from_slice(&32u16.to_le_bytes());
+    elf.extend_from_slice(&(segments.len() as u16).to_le_bytes());
+    elf.extend_from_slice(&[0; 6]);
+
+    for (paddr, data, memsz) in segments {
+        for field in [1, data_off, *paddr, *paddr, data.len() as u32, *memsz, 5, 4] {
+            elf.extend_from_slice(&field.to_le_bytes());
+        }
+        data_off += data.len() as u32;
+    }
+    for (_, data, _) in segments {
+        elf.extend_from_slice(data);
+    }
+    elf
+}
+
+/// MIR 4 `source`, which must check w/o errors
+#[cfg(feature = "llvm")]
+fn lower_mir(source: &str) -> Vec<crate::core::mir::MirFunction> {
+    use crate::error::Reporter;
+    use crate::frontend::lexer::Lexer;
+    use crate::frontend::parser::Parser;
+    use crate::frontend::semantic::SemanticAnalyzer;
+    use crate::middle::{HirLowerer, MirLowerer};
+    use codespan::Files;
+
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let hir = HirLowerer::new(symbol_table).lower(&ast);
+    MirLowerer::new().lower(&hir)
+}
+
+/// textual ir of an llvm backend module
+#[cfg(all(feature = "llvm", not(feature = "inkwell")))]
+fn llvm_ir(module: &crate::backend::ports::codegen::Module) -> String {
+    use crate::backend::llvm::codegen::LlvmModuleWrapper;
+
+    let wrapper = module.data.as_ref().and_then(|d| d.downcast_ref::<LlvmModuleWrapper>()).unwrap();
+    unsafe {
+        let ir = llvm_sys::core::LLVMPrintModuleToString(wrapper.get());
+        let text = std::ffi::CStr::from_ptr(ir).to_string_lossy().to_string();
+        llvm_sys::core::LLVMDisposeMessage(ir);
+        text
+    }
+}
+
+#[cfg(feature = "inkwell")]
+fn llvm_ir(module: &crate::backend::ports::codegen::Module) -> String {
+    use crate::backend::llvm::safe::BitcodeModule;
+    use inkwell::context::Context;
+    use inkwell::memory_buffer::MemoryBuffer;
+
+    let data = module.data.as_ref().and_then(|d| d.downcast_ref::<BitcodeModule>()).unwrap();
+    let context = Context::create();
+    let buffer = MemoryBuffer::create_from_memory_range_copy(&data.bitcode, "test");
+    let module = inkwell::module::Module::parse_bitcode_from_buffer(&buffer, &context).unwrap();
+    module.print_to_string().to_string()
+}
+
+#[test]
+fn test_emit_type_flat_outputs() {
+    assert_eq!(EmitType::from_str("raw-bin"), Some(EmitType::RawBinary));
+    assert_eq!(EmitType::from_str("raw"), Some(EmitType::RawBinary));
+    assert_eq!(EmitType::from_str("ihex"), Some(EmitType::Ihex));
+    assert_eq!(EmitType::from_str("hex"), Some(EmitType::Ihex));
+}
+
+#[test]
+fn test_ihex_record_checksum() {
+    assert_eq!(ihex_record(0x01, 0, &[]), ":00000001FF\n");
+    assert_eq!(ihex_record(0x04, 0, &[0x08, 0x00]), ":020000040800F2\n");
+    assert_eq!(
+        ihex_record(0x00, 0x0100, &[0x21, 0x46, 0x01, 0x36]),
+        ":04010000214601365D\n"
+    );
+}
+
+#[test]
+fn test_load_image_from_elf() {
+    // bss segment (filesz 0) is dropped
+    let elf = elf32(0x0800_0009, &[(0x0800_0010, &[3, 4], 2), (0x0800_0000, &[1, 2], 2), (0x2000_0000, &[], 64)]);
+    let image = LoadImage::from_elf(&elf).unwrap();
+
+    assert_eq!(image.entry, 0x0800_0009);
+    assert_eq!(
+        image.segments,
+        vec![
+            Segment { address: 0x0800_0000, data: vec![1, 2] },
+            Segment { address: 0x0800_0010, data: vec![3, 4] },
+        ]
+    );
+
+    let raw = image.to_raw_binary();
+    assert_eq!(raw.len(), 0x12);
+    assert_eq!(&raw[0..2], &[1, 2]);
+    assert!(raw[2..0x10].iter().all(|b| *b == 0), "gap should be zero filled");
+    assert_eq!(&raw[0x10..], &[3, 4]);
+}
+
+#[test]
+fn test_load_image_to_ihex() {
+    // 20 bytes straddling a 64k boundary split into 3 records
+    let data: Vec<u8> = (0..20).collect();
+    let elf = elf32(0x0800_fff8, &[(0x0800_fff8, &data, 20)]);
+    let hex = LoadImage::from_elf(&elf).unwrap().to_ihex().unwrap();
+    let records: Vec<&str> = hex.lines().collect();
+
+    assert_eq!(records[0], ":020000040800F2");
+    assert!(records[1].starts_with(":08FFF800"), "got {}", records[1]);
+    assert_eq!(records[2], ":020000040801F1");
+    assert!(records[3].starts_with(":0C000000"), "got {}", records[3]);
+    assert!(records[4].starts_with(":04000005"), "got {}", records[4]);
+    assert_eq!(records.last(), Some(&":00000001FF"));
+}
+
+#[test]
+fn test_load_image_rejects_unlinked_object() {
+    let elf = elf32(0, &[]);
+    assert!(matches!(LoadImage::from_elf(&elf), Err(EmitError::EmissionFailed(_))));
+    assert!(LoadImage::from_elf(b"not an elf at all").is_err());
+}
+
+#[test]
+fn test_link_args_with_script() {
+    let options = LinkOptions::new()
+        .with_script(PathBuf::from("stm32.ld"))
+        .with_library_paths(vec![PathBuf::from("vendor")])
+        .with_libs(vec!["m".to_string()]);
+    let args = options.args(&[Path::new("main.o")], Path::new("firmware.elf"));
+
+    assert_eq!(args, vec!["main.o", "-o", "firmware.elf", "-T", "stm32.ld", "-Lvendor", "-lm"]);
+}
+
+#[test]
+fn test_link_missing_script() {
+    let options = LinkOptions::new().with_script(PathBuf::from("/nonexistent/emerald/link.ld"));
+    let result = link::link(&[Path::new("main.o")], Path::new("a.out"), &options);
+    assert!(matches!(result, Err(EmitError::InvalidPath(_))));
+}
+
+#[test]
+fn test_linker_flavor_for_target() {
+    let msvc = LinkOptions::new().with_target(TargetInfo::from_triple("x86_64-pc-windows-msvc"));
+    assert_eq!(msvc.flavor, LinkerFlavor::Msvc);
+    assert!(msvc.is_windows());
+
+    // mingw uses the gnu driver but still needs dll storage classes
+    let gnu = LinkOptions::new().with_target(TargetInfo::from_triple("x86_64-pc-windows-gnu"));
+    assert_eq!(gnu.flavor, LinkerFlavor::Gnu);
+    assert!(gnu.is_windows());
+
+    let linux = LinkOptions::new().with_target(TargetInfo::from_triple("x86_64-unknown-linux-gnu"));
+    assert_eq!(linux.flavor, LinkerFlavor::Gnu);
+    assert!(!linux.is_windows());
+}
+
+#[test]
+fn test_msvc_link_args_for_dll() {
+    let options = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("x86_64-pc-windows-msvc"))
+        .with_library_paths(vec![PathBuf::from("sdk")])
+        .with_libs(vec!["kernel32".to_string(), "user32.lib".to_string()])
+        .with_shared(true);
+    let args = options.args(&[Path::new("plugin.obj")], Path::new("plugin.dll"));
+
+    assert_eq!(
+        args,
+        vec![
+            "/NOLOGO",
+            "/OUT:plugin.dll",
+            "/DLL",
+            "/IMPLIB:plugin.lib",
+            "plugin.obj",
+            "/LIBPATH:sdk",
+            "kernel32.lib",
+            "user32.lib",
+            "/DEFAULTLIB:libcmt.lib",
+            "/DEFAULTLIB:libucrt.lib",
+            "/DEFAULTLIB:libvcruntime.lib",
+        ]
+    );
+}
+
+#[test]
+fn test_msvc_link_args_for_exe() {
+    let options = LinkOptions::new().with_target(TargetInfo::from_triple("x86_64-pc-windows-msvc"));
+    let args = options.args(&[Path::new("app.obj")], Path::new("app.exe"));
+
+    assert_eq!(
+        args,
+        vec![
+            "/NOLOGO",
+            "/OUT:app.exe",
+            "/SUBSYSTEM:CONSOLE",
+            "app.obj",
+            "/DEFAULTLIB:libcmt.lib",
+            "/DEFAULTLIB:libucrt.lib",
+            "/DEFAULTLIB:libvcruntime.lib",
+        ]
+    );
+}
+
+#[test]
+fn test_library_emit_types_and_archive_args() {
+    assert_eq!(EmitType::from_str("staticlib"), Some(EmitType::StaticLib));
+    assert_eq!(EmitType::from_str("cdylib"), Some(EmitType::SharedLib));
+    assert_eq!(EmitType::from_str("shared-lib"), Some(EmitType::SharedLib));
+
+    let gnu = LinkOptions::new().with_target(TargetInfo::from_triple("x86_64-unknown-linux-gnu"));
+    assert_eq!(gnu.archive_args(&[Path::new("a.o"), Path::new("b.o")], Path::new("libx.a")), vec!["crs", "libx.a", "a.o", "b.o"]);
+    assert_eq!(gnu.flavor.default_archiver(), link::DEFAULT_ARCHIVER);
+
+    let msvc = LinkOptions::new().with_target(TargetInfo::from_triple("x86_64-pc-windows-msvc"));
+    assert_eq!(msvc.archive_args(&[Path::new("a.obj")], Path::new("x.lib")), vec!["/NOLOGO", "/OUT:x.lib", "a.obj"]);
+}
+
+#[test]
+fn test_symbol_visibility_exports() {
+    assert_eq!(SymbolVisibility::from_str("hidden"), Some(SymbolVisibility::Hidden));
+    assert_eq!(SymbolVisibility::from_str("protected"), None);
+
+    let options = LinkOptions::new();
+    assert!(options.exports_symbol("anything"));
+    let hidden = options.with_visibility(SymbolVisibility::Hidden).with_exports(vec!["answer".to_string()]);
+    assert!(hidden.exports_symbol("answer"));
+    assert!(!hidden.exports_symbol("helper"));
+}
+
+#[test]
+fn test_darwin_link_args() {
+    let options = LinkOptions::new().with_target(TargetInfo::from_triple("aarch64-apple-darwin"));
+    assert_eq!(options.flavor, LinkerFlavor::Darwin);
+    let args = options.args(&[Path::new("main.o")], Path::new("main"));
+    assert_eq!(args, vec!["main.o", "-o", "main", "-arch", "arm64", "-mmacosx-version-min=11.0"]);
+
+    let options = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("x86_64-apple-macosx"))
+        .with_sdk_root(PathBuf::from("/sdk/MacOSX.sdk"))
+        .with_deployment_target("13.0".to_string())
+        .with_shared(true);
+    let args = options.args(&[Path::new("lib.o")], Path::new("libfoo.dylib"));
+    assert_eq!(
+        args,
+        vec![
+            "lib.o",
+            "-o",
+            "libfoo.dylib",
+            "-arch",
+            "x86_64",
+            "-mmacosx-version-min=13.0",
+            "-isysroot",
+            "/sdk/MacOSX.sdk",
+            "-dynamiclib",
+        ]
+    );
+}
+
+#[test]
+fn test_msvc_rejects_linker_script() {
+    let options = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("x86_64-pc-windows-msvc"))
+        .with_script(PathBuf::from("layout.ld"));
+    let result = link::link(&[Path::new("main.obj")], Path::new("main.exe"), &options);
+    assert!(matches!(result, Err(EmitError::EmissionFailed(_))));
+}
+
+#[test]
+fn test_codegen_embed_bitcode_option() {
+    use crate::cli::args::{Cli, CompileConfig};
+    use clap::Parser;
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "embed-bitcode", "-o", "main.o"]);
+    assert!(CompileConfig::from_cli(&cli).unwrap().embed_bitcode);
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "embed-bitcode", "-C", "embed-bitcode=no"]);
+    assert!(!CompileConfig::from_cli(&cli).unwrap().embed_bitcode);
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "lto"]);
+    assert!(CompileConfig::from_cli(&cli).is_err());
+}
+
+#[test]
+fn test_llvm_backend_optional() {
+    use crate::backend::factory::{BackendRegistry, BackendType};
+    use crate::cli::args::{Cli, CompileConfig};
+    use crate::cli::compiler::Compiler;
+    use clap::Parser;
+
+    let registered = BackendRegistry::new().get_factory(BackendType::Llvm).is_some();
+    assert_eq!(registered, cfg!(feature = "llvm"));
+
+    assert!(!CompileConfig::from_cli(&Cli::parse_from(["emerald", "main.em"])).unwrap().explicit_backend);
+    let dir = std::env::temp_dir().join(format!("emc-nollvm-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("main.em");
+    std::fs::write(&input, "def main() returns int\n  return 0\nend\n").unwrap();
+    let output = dir.join("main.o");
+    let cli = Cli::parse_from(["emerald", input.to_str().unwrap(), "--llvm", "-o", output.to_str().unwrap()]);
+    let config = CompileConfig::from_cli(&cli).unwrap();
+    assert!(config.explicit_backend);
+    if !cfg!(feature = "llvm") {
+        // asked 4 by name, so no fallback 2 the null backend
+        let err = Compiler::new(config).compile().unwrap_err();
+        assert!(err.to_string().contains("--features llvm21"));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_split_debuginfo_paths() {
+    let linux = LinkOptions::new().with_target(TargetInfo::from_triple("x86_64-unknown-linux-gnu"));
+    assert_eq!(linux.debuginfo_path(Path::new("out/app")), None);
+    let packed = linux.clone().with_split_debuginfo(SplitDebugInfo::Packed);
+    assert_eq!(packed.debuginfo_path(Path::new("out/app")), Some(PathBuf::from("out/app.debug")));
+    let unpacked = linux.with_split_debuginfo(SplitDebugInfo::Unpacked);
+    assert_eq!(unpacked.debuginfo_path(Path::new("out/app")), Some(PathBuf::from("out/app.dwo")));
+    assert!(!unpacked.keeps_objects());
+
+    let mac = LinkOptions::new().with_target(TargetInfo::from_triple("aarch64-apple-darwin"));
+    let packed = mac.clone().with_split_debuginfo(SplitDebugInfo::Packed);
+    assert_eq!(packed.debuginfo_path(Path::new("app")), Some(PathBuf::from("app.dSYM")));
+    // unpacked leaves the dwarf in main.o, the executable only has a debug map
+    let unpacked = mac.with_split_debuginfo(SplitDebugInfo::Unpacked);
+    assert_eq!(unpacked.debuginfo_path(Path::new("app")), None);
+    assert!(unpacked.keeps_objects());
+}
+
+#[test]
+fn test_msvc_link_args_with_pdb() {
+    let options = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("x86_64-pc-windows-msvc"))
+        .with_split_debuginfo(SplitDebugInfo::Packed);
+    let args = options.args(&[Path::new("main.obj")], Path::new("main.exe"));
+    assert_eq!(args[..6], ["/NOLOGO", "/OUT:main.exe", "/SUBSYSTEM:CONSOLE", "/DEBUG", "/PDB:main.pdb", "main.obj"]);
+}
+
+#[test]
+fn test_codegen_debuginfo_options() {
+    use crate::backend::ports::codegen::DebugInfoLevel;
+    use crate::cli::args::{Cli, CompileConfig};
+    use clap::Parser;
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-g", "-C", "split-debuginfo=packed"]);
+    let config = CompileConfig::from_cli(&cli).unwrap();
+    assert_eq!(config.debuginfo, DebugInfoLevel::Full);
+    assert_eq!(config.split_debuginfo, SplitDebugInfo::Packed);
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-g", "-C", "debuginfo=1"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap().debuginfo, DebugInfoLevel::LineTablesOnly);
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "split-debuginfo=dwp"]);
+    assert!(CompileConfig::from_cli(&cli).is_err());
+}
+
+#[test]
+fn test_debugger_script_paths() {
+    use crate::backend::debugger::{gdb_script_path, lldb_script_path, GDB_PRETTY_PRINTERS, LLDB_PRETTY_PRINTERS};
+
+    assert_eq!(gdb_script_path(Path::new("out/app")), PathBuf::from("out/app-gdb.py"));
+    assert_eq!(gdb_script_path(Path::new("app.exe")), PathBuf::from("app.exe-gdb.py"));
+    assert_eq!(lldb_script_path(Path::new("out/app")), PathBuf::from("out/emerald_lldb.py"));
+    // the printers match the names debuginfo gives these types
+    assert!(GDB_PRETTY_PRINTERS.contains(r#"name == "string""#));
+    assert!(GDB_PRETTY_PRINTERS.contains(r#"startswith("ref? ")"#));
+    assert!(LLDB_PRETTY_PRINTERS.contains(r#"string_summary string"#));
+    assert!(LLDB_PRETTY_PRINTERS.contains(r#"-x "^ref\\? ""#));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_debuginfo_describes_types() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::{CodeGen, DebugInfo, DebugInfoLevel};
+    use std::collections::HashMap;
+
+    let source = r#"
+struct Node
+  value : int
+  next : ref? Node
+end
+
+def peek(n : ref? Node, label : string) returns int
+  return 1
+end
+"#;
+    let mir = lower_mir(source);
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_debug_info(DebugInfo {
+        level: DebugInfoLevel::Full,
+        file: PathBuf::from("test.em"),
+        function_lines: HashMap::from([("peek".to_string(), 7)]),
+    });
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let ir = llvm_ir(&module);
+    assert!(ir.contains(r#"DW_TAG_typedef, name: "ref? Node""#));
+    assert!(ir.contains(r#"DW_TAG_structure_type, name: "string""#));
+    assert!(ir.contains(r#"DW_TAG_member, name: "len""#));
+    assert!(ir.contains(r#"DW_TAG_member, name: "next""#));
+    assert!(ir.contains(r#"DILocalVariable(name: "label", arg: 2"#));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_allocator_is_weak_so_a_custom_one_links_over_it() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+def main() returns int
+  a : ref int = new 1
+  b : ref float = new 2.5
+  delete a
+  delete b
+  return 0
+end
+"#;
+    // the module lives in its codegen's context
+    let mut codegen = LlvmCodeGen::new();
+    let ir = llvm_ir(&codegen.generate_from_mir(&lower_mir(source)).unwrap());
+    let defines = |ir: &str, name: &str| ir.lines().any(|l| l.starts_with("define weak ") && l.contains(&format!("@{}(", name)));
+    assert!(defines(&ir, "emerald_alloc") && defines(&ir, "emerald_free"), "{}", ir);
+    assert!(ir.contains("@malloc(i64") && ir.contains("@free("), "{}", ir);
+
+    // a program that defines them keeps its own
+    let own = format!(
+        "foreign \"C\" libc\n  def calloc(n : int, size : int) returns ref int\nend\n\n\
+         def emerald_alloc(size : int) returns ref int\n  return calloc(1, size)\nend\n{}",
+        source
+    );
+    let mut codegen = LlvmCodeGen::new();
+    let ir = llvm_ir(&codegen.generate_from_mir(&lower_mir(&own)).unwrap());
+    assert!(!defines(&ir, "emerald_alloc") && defines(&ir, "emerald_free"), "{}", ir);
+    assert!(ir.contains("@calloc(") && !ir.contains("@malloc("), "{}", ir);
+}
+
+#[test]
+fn test_link_args_strip_and_compression() {
+    let linux = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("x86_64-unknown-linux-gnu"))
+        .with_debug_compression(DebugCompression::Zstd);
+    let args = linux.args(&[Path::new("main.o")], Path::new("main"));
+    assert_eq!(args, vec!["main.o", "-o", "main", "-Wl,--compress-debug-sections=zstd"]);
+
+    // nothing left 2 compress once its stripped
+    let args = linux.with_strip(Strip::DebugInfo).args(&[Path::new("main.o")], Path::new("main"));
+    assert_eq!(args, vec!["main.o", "-o", "main", "-Wl,--strip-debug"]);
+
+    let mac = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("aarch64-apple-darwin"))
+        .with_strip(Strip::Symbols);
+    let args = mac.args(&[Path::new("main.o")], Path::new("main"));
+    assert!(args.ends_with(&["-Wl,-S".to_string(), "-Wl,-x".to_string()]));
+}
+
+#[test]
+fn test_debug_compression_requires_elf() {
+    let options = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("x86_64-pc-windows-msvc"))
+        .with_debug_compression(DebugCompression::Zlib);
+    let result = link::link(&[Path::new("main.obj")], Path::new("main.exe"), &options);
+    assert!(matches!(result, Err(EmitError::EmissionFailed(ref m)) if m.contains("ELF")));
+}
+
+#[test]
+fn test_strip_option() {
+    use crate::cli::args::{Cli, CompileConfig};
+    use clap::Parser;
+
+    let cli = Cli::parse_from(["emerald", "main.em", "-g", "--strip=debuginfo", "-C", "debuginfo-compression=zlib"]);
+    let config = CompileConfig::from_cli(&cli).unwrap();
+    assert_eq!(config.strip, Strip::DebugInfo);
+    assert_eq!(config.debuginfo_compression, Some(DebugCompression::Zlib));
+
+    let cli = Cli::parse_from(["emerald", "main.em", "--strip", "everything"]);
+    assert!(CompileConfig::from_cli(&cli).is_err());
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "debuginfo-compression=lz4"]);
+    assert!(CompileConfig::from_cli(&cli).is_err());
+}
+
+#[test]
+fn test_wasm_link_args() {
+    let wasi = TargetInfo::from_triple("wasm32-wasi");
+    assert!(wasi.is_wasm());
+    assert_eq!(wasi.pointer_width, 32);
+    assert_eq!(EmitType::from_str("wasm"), Some(EmitType::Wasm));
+
+    let options = LinkOptions::new().with_target(wasi).with_libs(vec!["m".to_string()]);
+    assert_eq!(options.flavor, LinkerFlavor::Wasm);
+    assert_eq!(options.flavor.default_linker(), "wasm-ld");
+    let args = options.args(&[Path::new("app.o")], Path::new("app.wasm"));
+    assert_eq!(args, vec!["app.o", "-o", "app.wasm", "--no-entry", "--export-dynamic", "-lm"]);
+
+    let options = LinkOptions::new()
+        .with_target(TargetInfo::from_triple("wasm32-unknown-unknown"))
+        .with_script(PathBuf::from("layout.ld"));
+    let result = link::link(&[Path::new("app.o")], Path::new("app.wasm"), &options);
+    assert!(matches!(result, Err(EmitError::EmissionFailed(ref m)) if m.contains("wasm-ld")));
+}
+
+#[test]
+#[cfg(all(feature = "llvm", not(feature = "inkwell")))]
+fn test_llvm_module_keeps_target_triple() {
+    use crate::backend::llvm::codegen::LlvmModuleWrapper;
+    use crate::backend::llvm::target::module_triple;
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::{CodeGen, CodeGenError};
+
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_target_triple("aarch64-unknown-linux-gnu".to_string());
+    let module = codegen.generate_from_mir(&[]).unwrap();
+    let wrapper = module.data.as_ref().and_then(|d| d.downcast_ref::<LlvmModuleWrapper>()).unwrap();
+    assert_eq!(unsafe { module_triple(wrapper.get()) }, "aarch64-unknown-linux-gnu");
+
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_target_triple("sparkle-unknown-none".to_string());
+    let err = codegen.generate_from_mir(&[]).unwrap_err();
+    assert!(matches!(err, CodeGenError::InvalidTarget(ref m) if m.contains("sparkle-unknown-none")));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_version_feature() {
+    use crate::backend::llvm::ffi_compat::LLVM_VERSION_MAJOR;
+
+    let expected = if cfg!(feature = "llvm21") { 21 } else { 18 };
+    assert_eq!(LLVM_VERSION_MAJOR, expected);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_optimizer_pipelines() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::{LlvmBackendFactory, LlvmCodeGen, LlvmOptimizer};
+    use crate::backend::ports::codegen::{CodeGen, OptimizationLevel};
+
+    assert_eq!(LlvmOptimizer::pass_pipeline(OptimizationLevel::None), "default<O0>");
+    assert_eq!(LlvmOptimizer::pass_pipeline(OptimizationLevel::Aggressive), "default<O3>");
+    assert_eq!(LlvmOptimizer::pass_pipeline(OptimizationLevel::SizePerformance), "default<Oz>");
+
+    let source = r#"
+struct Pair
+  a : int
+  b : int
+end
+
+def first(p : Pair) returns int
+  return p.a + 1
+end
+"#;
+    let mir = lower_mir(source);
+    let optimized = |level: OptimizationLevel| {
+        // the codegen owns the llvm context, it has 2 outlive the module
+        let mut codegen = LlvmCodeGen::new();
+        let mut module = codegen.generate_from_mir(&mir).unwrap();
+        // w/ the inkwell feature this is the safe optimizer
+        let mut optimizer = LlvmBackendFactory::new().create_optimizer().unwrap();
+        optimizer.set_optimization_level(level);
+        optimizer.optimize(&mut module).unwrap();
+        llvm_ir(&module)
+    };
+    // the by value struct is spilled 2 the stack, sroa removes the slot
+    assert!(optimized(OptimizationLevel::None).contains("alloca"));
+    assert!(!optimized(OptimizationLevel::Default).contains("alloca"));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_optimizer_added_passes() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::{LlvmBackendFactory, LlvmCodeGen, LlvmOptimizer};
+    use crate::backend::ports::codegen::{CodeGen, OptimizationLevel};
+    use crate::backend::ports::optimizer::{OptimizationError, OptimizationPass};
+
+    let passes = [OptimizationPass::Mem2Reg, OptimizationPass::Custom("loop(licm)".to_string())];
+    assert_eq!(LlvmOptimizer::pipeline(OptimizationLevel::None, &passes), "default<O0>,mem2reg,loop(licm)");
+    assert_eq!(LlvmOptimizer::pass_name(&OptimizationPass::LoopUnroll), "loop-unroll");
+
+    let source = r#"
+struct Pair
+  a : int
+  b : int
+end
+
+def first(p : Pair) returns int
+  return p.a + 1
+end
+"#;
+    let mir = lower_mir(source);
+    let optimized = |passes: &[OptimizationPass]| {
+        let mut codegen = LlvmCodeGen::new();
+        let mut module = codegen.generate_from_mir(&mir).unwrap();
+        let mut optimizer = LlvmBackendFactory::new().create_optimizer().unwrap();
+        optimizer.set_optimization_level(OptimizationLevel::None);
+        for pass in passes {
+            optimizer.add_pass(pass.clone());
+        }
+        optimizer.optimize(&mut module).map(|_| llvm_ir(&module))
+    };
+    // O0 keeps the spill slot, an added sroa splits + promotes it
+    assert!(optimized(&[]).unwrap().contains("alloca"));
+    assert!(!optimized(&[OptimizationPass::Custom("sroa".to_string())]).unwrap().contains("alloca"));
+    // evry builtin pass is a name the pass builder knows
+    let builtin = [
+        OptimizationPass::Inline,
+        OptimizationPass::Mem2Reg,
+        OptimizationPass::DeadCodeElimination,
+        OptimizationPass::LoopUnroll,
+        OptimizationPass::Sccp,
+    ];
+    assert!(optimized(&builtin).is_ok());
+    // + so is evry fragment `--passes` knows by name
+    let named: Vec<OptimizationPass> = OptimizationPass::NAMES.iter().filter_map(|n| OptimizationPass::from_name(n)).collect();
+    assert_eq!(named.len(), OptimizationPass::NAMES.len());
+    assert!(optimized(&named).is_ok());
+    let err = optimized(&[OptimizationPass::Custom("sparkle".to_string())]).unwrap_err();
+    assert!(matches!(err, OptimizationError::InvalidPass(ref m) if m.contains("sparkle")));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_unsigned_div_rem_and_compare() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+def half(x : uint, y : uint) returns uint
+  return x / y
+end
+
+def rem(x : ulong) returns ulong
+  return x % 10
+end
+
+def below(x : ubyte, y : ubyte) returns bool
+  return x < y
+end
+
+def signed(x : int, y : int) returns bool
+  return x / y >= 1
+end
+"#;
+    let mir = lower_mir(source);
+    let mut codegen = LlvmCodeGen::new();
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let ir = llvm_ir(&module);
+    assert!(ir.contains("udiv i32"));
+    // the literal takes the ulong width
+    assert!(ir.contains("urem i64 %0, 10"));
+    assert!(ir.contains("icmp ult i8"));
+    assert!(ir.contains("sdiv i32") && ir.contains("icmp sge i32"));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_float_arithmetic_and_compare() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+def area(w : float, h : float) returns float
+  return w * h / 2.0
+end
+
+def rest(a : float, b : float) returns float
+  return a % b - 1.5
+end
+
+def mixed(a : float, n : int) returns float
+  return a + n
+end
+
+def differ(a : float, b : float) returns bool
+  return a != b
+end
+
+def wider(a : float, b : float) returns bool
+  return a > b
+end
+"#;
+    let mir = lower_mir(source);
+    let mut codegen = LlvmCodeGen::new();
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let ir = llvm_ir(&module);
+    assert!(ir.contains("fmul double %0, %1") && ir.contains("fdiv double"));
+    assert!(ir.contains("frem double") && ir.contains("fsub double"));
+    // the int side is converted, not added as is
+    assert!(ir.contains("sitofp i32 %1 to double") && ir.contains("fadd double"));
+    assert!(ir.contains("fcmp une double") && ir.contains("fcmp ogt double"));
+    assert!(!ir.contains("sdiv double") && !ir.contains("icmp sgt double"));
+}
+
+#[test]
+#[cfg(feature = "inkwell")]
+fn test_inkwell_emitter_reads_bitcode_module() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::LlvmBackendFactory;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let mir = lower_mir("def answer() returns int\n  return 42\nend\n");
+    let module = LlvmBackendFactory::new().create_codegen().unwrap().generate_from_mir(&mir).unwrap();
+    // the codegen is gone, the module has 2 stand on its own
+    let dir = std::env::temp_dir().join(format!("emc-inkwell-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("answer.ll");
+    LlvmBackendFactory::new().create_emitter().unwrap().emit_llvm_ir(&module, &output).unwrap();
+    let ir = std::fs::read_to_string(&output).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(ir.contains("@answer") && ir.contains("42"));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_embedded_bitcode_section() {
+    use crate::backend::llvm::emitter::embedded_bitcode_section;
+
+    assert_eq!(embedded_bitcode_section("x86_64-unknown-linux-gnu"), ".llvmbc");
+    assert_eq!(embedded_bitcode_section("aarch64-apple-darwin"), "__LLVM,__bitcode");
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_panic_strategy() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::{CodeGen, PanicStrategy};
+    use crate::cli::args::{Cli, CompileConfig};
+    use clap::Parser;
+
+    assert_eq!(CompileConfig::from_cli(&Cli::parse_from(["emerald", "main.em"])).unwrap().panic, PanicStrategy::Abort);
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "panic=abort"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap().panic, PanicStrategy::Abort);
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "panic=crash"]);
+    assert!(CompileConfig::from_cli(&cli).is_err());
+    // nothing raises or catches yet, so unwinding isnt pretended
+    let cli = Cli::parse_from(["emerald", "main.em", "-C", "panic=unwind"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap_err(), "-C panic=unwind is not supported yet, panics always abort");
+
+    let source = r#"
+def add(a : int, b : int) returns int
+  return a + b
+end
+"#;
+    let mir = lower_mir(source);
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_target_triple("x86_64-unknown-linux-gnu".to_string());
+    let ir = llvm_ir(&codegen.generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("nounwind") && !ir.contains("personality"));
+    assert!(ir.contains("@emerald_panic_strategy = weak_odr constant i8 1"));
+
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_panic_strategy(PanicStrategy::Unwind);
+    let err = codegen.generate_from_mir(&mir).err().unwrap();
+    assert_eq!(err.to_string(), "Unsupported feature: panic=unwind, panics always abort");
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_panic_runtime_writes_to_stderr() {
+    use crate::backend::llvm::emitter::LlvmEmitter;
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+    use crate::backend::ports::emitter::Emitter;
+
+    let source = r#"
+def get(i : int) returns int
+  a : int[4] = [1, 2, 3, 4]
+  return a[i]
+end
+
+def main() returns int
+  return get(4)
+end
+"#;
+    let mir = lower_mir(source);
+    // msvc's crt has no dprintf, only _write
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_target_triple("x86_64-pc-windows-msvc".to_string());
+    let ir = llvm_ir(&codegen.generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("declare i32 @_write(i32, i8*, i32)") && !ir.contains("printf"), "{}", ir);
+
+    let mut codegen = LlvmCodeGen::new();
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    assert!(!llvm_ir(&module).contains("printf"));
+    let dir = std::env::temp_dir().join(format!("emc-llvm-panic-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("get");
+    LlvmEmitter::new().emit_binary(&module, &output).unwrap();
+    let run = std::process::Command::new(&output).output().unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(!run.status.success());
+    // lowered w/o its source so theres no location, line 0 still gets its digit
+    assert_eq!(String::from_utf8_lossy(&run.stderr), "panicked at <unknown>:0: index out of bounds for length 4\n");
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_strings_are_ptr_len_pairs() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+def greet(name : string) returns long
+  a : string = "hi"
+  b : string = "hi"
+  s : string = a + name
+  return s.len() + b.len()
+end
+"#;
+    let mir = lower_mir(source);
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("%str = type { i8*, i64 }") || ir.contains("%str = type { ptr, i64 }"));
+    // one interned global per distinct literal
+    assert_eq!(ir.matches("private unnamed_addr constant [3 x i8] c\"hi\\00\"").count(), 1);
+    // len of a literal folds 2 its byte count
+    assert!(ir.contains("add i64 2, %rlen"));
+    assert!(ir.contains("@malloc(i64 %catlen)") && ir.contains("llvm.memcpy"));
+    assert!(ir.contains("extractvalue %str"));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_dyn_calls_go_through_vtable() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+trait Shape
+  def area(self) returns float
+  def scale(self, k : float) returns float
+end
+
+struct Circle
+  radius : float
+end
+
+implement Shape for Circle
+  def area(self : ref Circle) returns float
+    return 3.14 * self.radius * self.radius
+  end
+  def scale(self : Circle, k : float) returns float
+    return self.radius * k
+  end
+end
+
+def total(s : dyn Shape) returns float
+  return s.area()
+end
+
+def run returns float
+  c : Circle = Circle { radius: 5.0 }
+  d : dyn Shape = @c
+  return d.area() + d.scale(2.0)
+end
+"#;
+    let mir = lower_mir(source);
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("@total(%dyn %0)"));
+    assert!(ir.contains("%dyn = type { i8*, i8** }") || ir.contains("%dyn = type { ptr, ptr }"));
+    assert!(ir.contains("@vtable.Circle.Shape = private unnamed_addr constant [2 x"));
+    // by value self goes thru a shim that loads it frm the data ptr
+    assert!(ir.contains("@\"Shape::Circle::scale.dyn\""));
+    assert!(ir.contains("%dyncall = call double"));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_string_patterns_compare_len_then_bytes() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+def command(s : string) returns int
+  match s
+    when "stop"
+      return 1
+    else
+      return 0
+  end
+  return 0
+end
+"#;
+    let mir = lower_mir(source);
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("%samelen = icmp eq i64 %llen, 4"));
+    assert!(ir.contains("declare i32 @memcmp("));
+    assert!(ir.contains("%streq = and i1 %samelen, %samebytes"));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_enum_is_tag_and_payload_words() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+enum Shape
+  Circle(int)
+  Rect(int, long)
+  Empty
+end
+
+def height(s : Shape) returns long
+  match s
+    when Rect(_, h)
+      return h
+    else
+      return 0
+  end
+  return 0
+end
+
+def make() returns Shape
+  return Shape::Rect(2, 5)
+end
+"#;
+    let mir = lower_mir(source);
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("%enum.Shape = type { i32, [2 x i64] }"), "{}", ir);
+    assert!(ir.contains("%tag = extractvalue %enum.Shape %0, 0"));
+    assert!(ir.contains("bitcast [2 x i64]* %payload to { i32, i64 }*"));
+    // int literal widened 2 the long field
+    assert!(ir.contains("store i64 5, i64* %fieldptr"));
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_record_is_a_struct_named_by_its_fields() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+def sum(n : int) returns int
+  p : { y: long, x: int } = { x: n, y: 2 }
+  return p.x
+end
+"#;
+    let mir = lower_mir(source);
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&mir).unwrap());
+    assert!(ir.contains(r#"%"struct.{ x: int, y: long }" = type { i32, i64 }"#), "{}", ir);
+    // y's literal is stored as the declared long
+    assert!(ir.contains("store i64 2, i64* %field1"), "{}", ir);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_match_is_a_switch() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+def grade(c : char) returns int
+  match c
+    when 'a', 'b'
+      return 1
+    when 'c'
+      return 2
+    else
+      return 0
+  end
+  return 0
+end
+"#;
+    let mir = lower_mir(source);
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&mir).unwrap());
+    // bb1 is the merge block, bb2 the else
+    assert!(ir.contains("switch i32 %0, label %bb2 ["), "{}", ir);
+    assert!(ir.contains("i32 97, label %bb3\n    i32 98, label %bb3\n    i32 99, label %bb4"), "{}", ir);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_module_unit_calls_by_qualified_symbol() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+    use crate::core::mir::MirModule;
+
+    let source = r#"
+def square(x : int) returns int
+  return x * x
+end
+
+def add(a : int, b : int) returns int
+  return a + square(b)
+end
+"#;
+    let unit = MirModule::new("mathx".to_string(), lower_mir(source));
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&unit.functions).unwrap());
+    assert!(ir.contains(r#"define i32 @"mathx::add"(i32 %0, i32 %1)"#), "{}", ir);
+    assert!(ir.contains(r#"call i32 @"mathx::square"(i32 %1)"#), "{}", ir);
+
+    // a fn compiled in another unit is declared frm the call 4 the linker
+    let mut functions = lower_mir(&format!("{}{}", source, "\ndef main() returns int\n  return add(1, 3)\nend\n"));
+    functions.retain(|f| f.name == "main");
+    let ir = llvm_ir(&LlvmCodeGen::new().generate_from_mir(&functions).unwrap());
+    assert!(ir.contains("declare i32 @add(i32, i32)"), "{}", ir);
+    assert!(ir.contains("%call = call i32 @add(i32 1, i32 3)"), "{}", ir);
+}
+
+#[test]
+fn test_codegen_units_partition_balances_functions() {
+    use crate::backend::codegen_units::partition;
+    use crate::core::mir::{Instruction, MirFunction, Operand};
+    use crate::core::mir::operand::{Constant, Local};
+    use crate::core::types::{primitive::PrimitiveType, ty::Type};
+
+    let function = |name: &str, instructions: usize| {
+        let mut f = MirFunction::new(name.to_string(), None);
+        for _ in 0..instructions {
+            f.basic_blocks[0].instructions.push(Instruction::Copy {
+                dest: Local::new(0),
+                source: Operand::Constant(Constant::Int(0)),
+                type_: Type::Primitive(PrimitiveType::Int),
+            });
+        }
+        f
+    };
+    let functions = vec![function("big", 13), function("a", 3), function("b", 3), function("c", 3), function("d", 1)];
+    let units = partition(&functions, 2);
+    // big alone outweighs the rest, names keep their input order
+    assert_eq!(units, vec![vec!["big".to_string()], vec!["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()]]);
+    assert_eq!(partition(&functions, 2), units);
+
+    // never more units than fns
+    assert_eq!(partition(&functions, 16).len(), 5);
+    assert_eq!(partition(&functions, 0).len(), 1);
+}
+
+#[test]
+fn test_codegen_units_option() {
+    use crate::backend::factory::{BackendRegistry, BackendType};
+    use crate::cli::args::{Cli, CompileConfig};
+    use clap::Parser;
+
+    let cli = Cli::parse_from(["emerald", "main.em", "--codegen-units", "4"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap().codegen_units, 4);
+    assert_eq!(CompileConfig::from_cli(&Cli::parse_from(["emerald", "main.em"])).unwrap().codegen_units, 1);
+
+    let registry = BackendRegistry::new().with_codegen_units(4);
+    if let Some(llvm) = registry.get_factory(BackendType::Llvm) {
+        assert_eq!(llvm.codegen_units(), 4);
+    }
+    assert_eq!(registry.get_factory(BackendType::Null).unwrap().codegen_units(), 1);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_codegen_unit_defines_only_its_functions() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let mir = lower_mir(r#"
+def pair(a : int, b : long) returns long
+  return b
+end
+
+def main() returns int
+  pair(1, 2)
+  return 0
+end
+"#);
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_codegen_unit(vec!["main".to_string()]);
+    let ir = llvm_ir(&codegen.generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("define i32 @main()"), "{}", ir);
+    // declared w/ its real signature, not one guessed frm the call's args
+    assert!(ir.contains("declare i64 @pair(i32, i64)"), "{}", ir);
+    assert!(!ir.contains("define i64 @pair"), "{}", ir);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_emit_bitcode() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::LlvmBackendFactory;
+
+    assert_eq!(EmitType::from_str("llvm-bc"), Some(EmitType::Bitcode));
+    assert_eq!(EmitType::from_str("bc"), Some(EmitType::Bitcode));
+
+    let mir = lower_mir("def answer() returns int\n  return 42\nend\n");
+    // the module lives in the codegen's context
+    let mut codegen = LlvmBackendFactory::new().create_codegen().unwrap();
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let dir = std::env::temp_dir().join(format!("emc-bitcode-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("answer.bc");
+    LlvmBackendFactory::new().create_emitter().unwrap().emit_bitcode(&module, &output).unwrap();
+    let bitcode = std::fs::read(&output).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    // raw bitcode magic, no wrapper header
+    assert_eq!(&bitcode[..4], b"BC\xc0\xde");
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_emit_static_lib_hides_unexported() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::LlvmBackendFactory;
+
+    let mir = lower_mir("def helper() returns int\n  return 41\nend\n\ndef answer() returns int\n  return helper() + 1\nend\n");
+    let mut codegen = LlvmBackendFactory::new().create_codegen().unwrap();
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let dir = std::env::temp_dir().join(format!("emc-staticlib-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("libanswer.a");
+    let mut emitter = LlvmBackendFactory::new().create_emitter().unwrap();
+    let options = LinkOptions::new().with_visibility(SymbolVisibility::Hidden).with_exports(vec!["answer".to_string()]);
+    emitter.set_link_options(options);
+    emitter.emit_static_lib(&module, &output).unwrap();
+    let archive = std::fs::read(&output).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(archive.starts_with(b"!<arch>\n"));
+    // the intermediate object is cleaned up
+    assert!(!output.with_extension("o").exists());
+
+    #[cfg(not(feature = "inkwell"))]
+    {
+        let ir = llvm_ir(&module);
+        assert!(ir.contains("define hidden i32 @helper"), "{}", ir);
+        assert!(ir.contains("define i32 @answer"), "{}", ir);
+    }
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_variadic_call_promotes_extra_args() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::LlvmBackendFactory;
+    use crate::core::mir::Instruction;
+
+    let mir = lower_mir(
+        r#"
+foreign "C" stdio
+  def printf(format : ref char, ...) returns int
+end
+
+def show(flag : bool, b : byte, x : float) returns int
+  return printf("%d %d %f", flag, b, x)
+end
+"#,
+    );
+    let show = mir.iter().find(|f| f.name == "show").unwrap();
+    let call = show.basic_blocks.iter().flat_map(|bb| &bb.instructions).find_map(|i| match i {
+        Instruction::Call { fixed_args, .. } => Some(*fixed_args),
+        _ => None,
+    });
+    assert_eq!(call, Some(Some(1)));
+
+    let mut codegen = LlvmBackendFactory::new().create_codegen().unwrap();
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let ir = llvm_ir(&module);
+    assert!(ir.lines().any(|l| l.starts_with("declare i32 @printf(") && l.ends_with(", ...)")), "{}", ir);
+    // bool + byte go as int, float already is a double
+    assert!(ir.contains("zext i1 %") && ir.contains("zext i8 %"), "{}", ir);
+    assert!(ir.contains(", ...) @printf("), "{}", ir);
+    assert!(!ir.contains("fpext"), "{}", ir);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_foreign_call_conventions() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::LlvmBackendFactory;
+
+    let mir = lower_mir(
+        r#"
+foreign "system" kernel32
+  def GetTickCount returns int
+end
+
+foreign "fastcall" fast
+  def twice(x : int) returns int
+end
+
+def ticks returns int
+  return GetTickCount() + twice(2)
+end
+"#,
+    );
+    let mut codegen = LlvmBackendFactory::new().create_codegen().unwrap();
+    codegen.set_target_triple("i686-pc-windows-msvc".to_string());
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let ir = llvm_ir(&module);
+    // declaration + call site agree
+    assert!(ir.contains("declare x86_stdcallcc i32 @GetTickCount("), "{}", ir);
+    assert!(ir.contains("call x86_stdcallcc i32 @GetTickCount("), "{}", ir);
+    assert!(ir.contains("declare x86_fastcallcc i32 @twice("), "{}", ir);
+    assert!(ir.contains("call x86_fastcallcc i32 @twice("), "{}", ir);
+
+    // `system` is plain C off win32
+    let mut codegen = LlvmBackendFactory::new().create_codegen().unwrap();
+    codegen.set_target_triple("x86_64-pc-windows-msvc".to_string());
+    let ir = llvm_ir(&codegen.generate_from_mir(&mir).unwrap());
+    assert!(ir.contains("declare i32 @GetTickCount("), "{}", ir);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_jit_runs_main() {
+    use crate::backend::factory::BackendFactory;
+    use crate::backend::llvm::LlvmBackendFactory;
+
+    let factory = LlvmBackendFactory::new();
+    // a separately compiled unit, main only declares what it calls
+    let lib = lower_mir(
+        r#"
+def fib(n : int) returns int
+  if n < 2
+    return n
+  end
+  return fib(n - 1) + fib(n - 2)
+end
+"#,
+    );
+    let program = lower_mir(
+        r#"
+foreign "C" lib
+  def fib(n : int) returns int
+end
+
+def main() returns int
+  return fib(7) + 1
+end
+"#,
+    );
+    // the modules live in their codegens' contexts
+    let mut lib_codegen = factory.create_codegen().unwrap();
+    let lib = lib_codegen.generate_from_mir(&lib).unwrap();
+    let mut codegen = factory.create_codegen().unwrap();
+    let program = codegen.generate_from_mir(&program).unwrap();
+
+    let mut jit = factory.create_execution_engine().unwrap();
+    jit.add_module(&lib).unwrap();
+    assert_eq!(jit.run_main(&program).unwrap(), 14);
+
+    // a void main exits w/ 0
+    let mut codegen = factory.create_codegen().unwrap();
+    let void_main = codegen.generate_from_mir(&lower_mir("def main\n  x : int = 1\nend\n")).unwrap();
+    let mut jit = factory.create_execution_engine().unwrap();
+    assert_eq!(jit.run_main(&void_main).unwrap(), 0);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_stack_frames_are_the_ones_codegen_laid_out() {
+    use crate::backend::llvm::{LlvmCodeGen, LlvmEmitter};
+    use crate::backend::ports::codegen::CodeGen;
+    use crate::backend::ports::emitter::Emitter;
+    use crate::core::mir::{Constant, FunctionRef, Instruction, MirFunction, Operand, Param};
+    use crate::core::types::composite::ArrayType;
+    use crate::core::types::pointer::PointerType;
+    use crate::core::types::primitive::PrimitiveType;
+    use crate::core::types::ty::Type;
+
+    // big(i, j) writes leaf() 2 arr[i] + reads arr[j] of an int[64] slot, built by hand since the lowerer
+    // folds a literal array in2 a global. it calls leaf so the slot cant sit in a red zone
+    let int = Type::Primitive(PrimitiveType::Int);
+    let mut big = MirFunction::new("big".to_string(), Some(int.clone()));
+    let array = Type::Array(ArrayType { element: Box::new(int.clone()), size: 64 });
+    let arr = big.new_local(array.clone(), None);
+    let [i, j] = ["i", "j"].map(|name| {
+        let local = big.new_local(int.clone(), Some(name.to_string()));
+        big.params.push(Param { name: name.to_string(), type_: int.clone(), local });
+        local
+    });
+    let [at_i, at_j] = [(); 2].map(|_| big.new_local(Type::Pointer(PointerType::ref_(int.clone())), None));
+    let value = big.new_local(int.clone(), None);
+    let one = big.new_local(int.clone(), None);
+    big.basic_blocks[0].instructions = vec![
+        Instruction::Alloca { dest: arr, type_: array },
+        Instruction::Call {
+            dest: Some(one),
+            func: Operand::Function(FunctionRef { name: "leaf".to_string() }),
+            args: Vec::new(),
+            return_type: Some(int.clone()),
+            fixed_args: None,
+            conv: Default::default(),
+        },
+        Instruction::Gep { dest: at_i, base: Operand::Local(arr), index: Operand::Local(i), type_: int.clone() },
+        Instruction::Store { dest: Operand::Local(at_i), source: Operand::Local(one), type_: int.clone() },
+        Instruction::Gep { dest: at_j, base: Operand::Local(arr), index: Operand::Local(j), type_: int.clone() },
+        Instruction::Load { dest: value, source: Operand::Local(at_j), type_: int.clone() },
+        Instruction::Ret { value: Some(Operand::Local(value)) },
+    ];
+    let mut leaf = MirFunction::new("leaf".to_string(), Some(int.clone()));
+    leaf.basic_blocks[0].instructions = vec![Instruction::Ret { value: Some(Operand::Constant(Constant::Int(1))) }];
+    let mut main = MirFunction::new("main".to_string(), Some(int.clone()));
+    let result = main.new_local(int.clone(), None);
+    main.basic_blocks[0].instructions = vec![
+        Instruction::Call {
+            dest: Some(result),
+            func: Operand::Function(FunctionRef { name: "big".to_string() }),
+            args: vec![Operand::Constant(Constant::Int(3)), Operand::Constant(Constant::Int(4))],
+            return_type: Some(int.clone()),
+            fixed_args: None,
+            conv: Default::default(),
+        },
+        Instruction::Ret { value: Some(Operand::Local(result)) },
+    ];
+
+    // rel relocations on i686, coff + mach-o targets r compiled 2 elf 4 the section
+    for triple in ["x86_64-unknown-linux-gnu", "i686-unknown-linux-gnu", "x86_64-pc-windows-msvc", "aarch64-apple-darwin"] {
+        let mut codegen = LlvmCodeGen::new();
+        codegen.set_target_triple(triple.to_string());
+        let module = codegen.generate_from_mir(&[big.clone(), leaf.clone(), main.clone()]).unwrap();
+        let frames = LlvmEmitter::new().stack_frames(&module).unwrap();
+        let frame = |name: &str| frames.iter().find(|f| f.name == name).unwrap_or_else(|| panic!("{}: no frame 4 {}: {:?}", triple, name, frames));
+
+        // the 256 byte array is in the frame, + whatever codegen spilled next 2 it
+        assert!(frame("big").frame_size >= 256, "{}: {:?}", triple, frames);
+        assert!(!frame("big").dynamic, "{:?}", frames);
+        assert_eq!(frame("leaf").frame_size, 0, "{}: {:?}", triple, frames);
+        assert!(frame("main").frame_size > 0, "{}: {:?}", triple, frames);
+        assert!(frame("main").callees.iter().any(|c| c == "big"), "{:?}", frames);
+    }
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_dispatch_thunk_resolves_once() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::CodeGen;
+
+    let source = r#"
+@target_feature("avx2")
+def dot(a : int, b : int) returns int
+  return a * b
+end
+"#;
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_target_triple("x86_64-apple-darwin".to_string());
+    let ir = llvm_ir(&codegen.generate_from_mir(&lower_mir(source)).unwrap());
+    let thunk: Vec<&str> = ir.lines().skip_while(|l| !l.starts_with("define") || !l.contains("@dot(")).take_while(|l| *l != "}").collect();
+
+    // the clone is cached, the resolver only runs while the cache is empty
+    assert!(ir.contains("@dot.target = internal global"), "{}", ir);
+    assert!(thunk.iter().any(|l| l.contains("load atomic") && l.contains("@dot.target") && l.contains("monotonic")), "{}", ir);
+    let resolve = thunk.iter().position(|l| l.starts_with("resolve:")).unwrap_or_else(|| panic!("{}", ir));
+    let calls: Vec<usize> = thunk.iter().enumerate().filter(|(_, l)| l.contains("@dot.resolver()")).map(|(i, _)| i).collect();
+    assert_eq!(calls.len(), 1, "{}", ir);
+    assert!(calls[0] > resolve, "{}", ir);
+    assert!(thunk.iter().any(|l| l.contains("store atomic") && l.contains("@dot.target")), "{}", ir);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_dllimport_is_only_for_library_fns() {
+    use crate::backend::llvm::{LlvmCodeGen, LlvmEmitter};
+    use crate::backend::ports::codegen::CodeGen;
+    use crate::backend::ports::emitter::Emitter;
+    use crate::core::mir::{Instruction, Operand};
+
+    let source = r#"
+foreign "C" libc
+  def abs(n : int) returns int
+end
+
+def helper(n : int) returns int
+  return abs(n)
+end
+
+def twice(n : int) returns int
+  return n * 2
+end
+
+def main() returns int
+  return helper(-2) + twice(3) + abs(-1)
+end
+"#;
+    // main's unit only declares helper, + twice stands in 4 an imported module's `util::twice`
+    let mut mir = lower_mir(source);
+    mir.retain(|f| f.name != "twice");
+    for inst in mir.iter_mut().flat_map(|f| f.basic_blocks.iter_mut()).flat_map(|bb| bb.instructions.iter_mut()) {
+        if let Instruction::Call { func: Operand::Function(f), .. } = inst {
+            if f.name == "twice" {
+                f.name = "util::twice".to_string();
+            }
+        }
+    }
+    let mut codegen = LlvmCodeGen::new();
+    codegen.set_target_triple("x86_64-pc-windows-msvc".to_string());
+    codegen.set_codegen_unit(vec!["main".to_string()]);
+    let module = codegen.generate_from_mir(&mir).unwrap();
+    let dir = std::env::temp_dir().join(format!("emc-dllimport-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    LlvmEmitter::new().emit_object(&module, &dir.join("main.obj")).unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let ir = llvm_ir(&module);
+    let declares = |name: &str| ir.lines().find(|l| l.starts_with("declare") && l.contains(&format!("@{}(", name))).unwrap_or_else(|| panic!("{}", ir)).to_string();
+    assert!(!declares("helper").contains("dllimport"), "{}", ir);
+    assert!(!declares("\"util::twice\"").contains("dllimport"), "{}", ir);
+    assert!(declares("abs").contains("dllimport"), "{}", ir);
+}
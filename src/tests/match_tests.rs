@@ -0,0 +1,189 @@
+use crate::core::ast::{Pattern, Stmt};
+use crate::core::mir::{Instruction, MirFunction};
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::token::TokenKind;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan::Files;
+
+fn analyze_source(source: &str) -> (crate::core::ast::Ast, Reporter) {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    if !reporter.has_errors() {
+        SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    }
+    (ast, reporter)
+}
+
+fn messages(reporter: &Reporter, severity: Severity) -> Vec<String> {
+    reporter.diagnostics().iter().filter(|d| d.severity == severity).map(|d| d.message.clone()).collect()
+}
+
+fn lower_mir(source: &str) -> Vec<MirFunction> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+const CLASSIFY: &str = r#"
+def classify(n : int) returns int
+  match n
+    when 0
+      return 0
+    when 1 .. 9, -5 .. -1
+      return 1
+    else
+      return 2
+  end
+  return 3
+end
+"#;
+
+#[test]
+fn test_range_pattern_lexes_and_parses() {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", "1..10".to_string());
+    let mut reporter = Reporter::new();
+    let kinds: Vec<_> = Lexer::new("1..10", file_id, &mut reporter).tokenize().into_iter().map(|t| t.kind).collect();
+    assert_eq!(kinds[..3], [TokenKind::IntLiteral(1), TokenKind::DotDot, TokenKind::IntLiteral(10)]);
+
+    let (ast, reporter) = analyze_source(CLASSIFY);
+    assert!(!reporter.has_errors());
+    let crate::core::ast::Item::Function(f) = &ast.items[0] else { panic!("expected a function") };
+    let Some(Stmt::Match(m)) = f.body.as_ref().and_then(|b| b.first()) else { panic!("expected a match") };
+    assert_eq!(m.arms.len(), 2);
+    assert!(m.else_branch.is_some());
+    assert!(matches!(m.arms[1].patterns[..], [Pattern::Range(ref a), Pattern::Range(ref b)] if (a.start, a.end, b.start, b.end) == (1, 9, -5, -1)));
+}
+
+#[test]
+fn test_overlapping_and_unreachable_patterns_warn() {
+    let source = r#"
+def pick(n : int, s : string) returns int
+  match n
+    when 1 .. 10
+      return 1
+    when 5 .. 20
+      return 2
+    when 7
+      return 3
+    else
+      return 0
+  end
+  match s
+    when "go", "run"
+      return 4
+    when "go"
+      return 5
+    else
+      return 0
+  end
+  return 0
+end
+"#;
+    let (_ast, reporter) = analyze_source(source);
+    assert!(!reporter.has_errors());
+    let warnings = messages(&reporter, Severity::Warning);
+    assert!(warnings.contains(&"Pattern 5 .. 20 overlaps 1 .. 10".to_string()));
+    assert!(warnings.contains(&"Unreachable pattern: 7 is already matched".to_string()));
+    assert!(warnings.contains(&"Unreachable pattern: \"go\" is already matched".to_string()));
+}
+
+#[test]
+fn test_match_needs_else_unless_exhaustive() {
+    let exhaustive = r#"
+def half(b : ubyte, f : bool) returns int
+  match b
+    when 0 .. 127
+      return 0
+    when 128 .. 255
+      return 1
+  end
+  match f
+    when true
+      return 2
+    when false
+      return 3
+  end
+  return 4
+end
+"#;
+    let (_ast, reporter) = analyze_source(exhaustive);
+    assert!(!reporter.has_errors());
+
+    let partial = exhaustive.replace("    when 128 .. 255\n      return 1\n", "").replace("    when false\n      return 3\n", "");
+    let (_ast, reporter) = analyze_source(&partial);
+    let errors = messages(&reporter, Severity::Error);
+    assert_eq!(errors.iter().filter(|m| m.contains("is not exhaustive")).count(), 2);
+
+    let bad = exhaustive.replace("128 .. 255", "128 .. 300").replace("when true", "when \"yes\"");
+    let (_ast, reporter) = analyze_source(&bad);
+    let errors = messages(&reporter, Severity::Error);
+    assert!(errors.iter().any(|m| m.starts_with("Pattern 128 .. 300 is out of range")));
+    assert!(errors.iter().any(|m| m.starts_with("Pattern \"yes\" can't match a value of type")));
+}
+
+#[test]
+fn test_match_lowers_to_comparison_chain() {
+    let mir = lower_mir(CLASSIFY);
+    let classify = mir.iter().find(|f| f.name == "classify").unwrap();
+    let insts: Vec<_> = classify.basic_blocks.iter().flat_map(|b| &b.instructions).collect();
+    // 1 test per arm, ranges r a Ge/Le pair
+    assert_eq!(insts.iter().filter(|i| matches!(i, Instruction::Br { .. })).count(), 2);
+    assert_eq!(insts.iter().filter(|i| matches!(i, Instruction::Ge { .. })).count(), 2);
+    assert_eq!(insts.iter().filter(|i| matches!(i, Instruction::Or { .. })).count(), 1);
+
+    let source = r#"
+def command(s : string) returns int
+  match s
+    when "go"
+      return 1
+    else
+      return 0
+  end
+  return 0
+end
+"#;
+    let mir = lower_mir(source);
+    let command = mir.iter().find(|f| f.name == "command").unwrap();
+    assert!(command.basic_blocks.iter().flat_map(|b| &b.instructions).any(|i| matches!(i, Instruction::StrEq { .. })));
+}
+
+#[test]
+fn test_match_on_literals_lowers_to_switch() {
+    let source = r#"
+def digit(n : int) returns int
+  match n
+    when 0, -1
+      return 10
+    when 1, 0
+      return 11
+    else
+      return 12
+  end
+  return 0
+end
+"#;
+    let mir = lower_mir(source);
+    let digit = mir.iter().find(|f| f.name == "digit").unwrap();
+    let insts: Vec<_> = digit.basic_blocks.iter().flat_map(|b| &b.instructions).collect();
+    assert!(!insts.iter().any(|i| matches!(i, Instruction::Br { .. } | Instruction::Eq { .. })));
+    let Some(Instruction::Switch { arms, default, .. }) = insts.iter().find(|i| matches!(i, Instruction::Switch { .. })) else {
+        panic!("expected a switch")
+    };
+    // the 2nd 0 is unreachable, it stays w/ the 1st arm
+    let values: Vec<i64> = arms.iter().map(|(v, _)| *v).collect();
+    assert_eq!(values, [0, -1, 1]);
+    assert_eq!(arms[0].1, arms[1].1);
+    assert!(arms.iter().all(|(_, bb)| bb != default));
+}
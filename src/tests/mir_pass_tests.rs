@@ -0,0 +1,492 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::core::mir::dump::{DumpFilter, MirDump};
+use crate::core::mir::passes::{ConstProp, Dce, LoadStore, MirPass, PassManager};
+use crate::core::mir::profile::{CallProfile, Hotness, ProfileError};
+use crate::core::mir::{Constant, Instruction, Local, MirFunction, Operand};
+use crate::core::optimizations::MirOptimizer;
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use crate::core::types::pointer::PointerType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use std::sync::Arc;
+
+fn int(n: i64) -> Operand {
+    Operand::Constant(Constant::Int(n))
+}
+
+fn returned(func: &MirFunction) -> Option<&Operand> {
+    func.basic_blocks.iter().flat_map(|bb| &bb.instructions).find_map(|i| match i {
+        Instruction::Ret { value } => value.as_ref(),
+        _ => None,
+    })
+}
+
+/// `a = 2; b = a <op> 3; ret b` w/ `b` of type `ty`
+fn binary(ty: PrimitiveType, op: fn(Local, Operand, Operand, Type) -> Instruction, left: i64, right: i64) -> MirFunction {
+    let ty = Type::Primitive(ty);
+    let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+    let a = func.new_local(ty.clone(), Some("a".to_string()));
+    let b = func.new_local(ty.clone(), None);
+    func.basic_blocks[0].instructions = vec![
+        Instruction::Copy { dest: a, source: int(left), type_: ty.clone() },
+        op(b, Operand::Local(a), int(right), ty),
+        Instruction::Ret { value: Some(Operand::Local(b)) },
+    ];
+    func
+}
+
+#[test]
+fn test_const_prop_folds_through_copies() {
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+    let a = func.new_local(ty.clone(), Some("a".to_string()));
+    let b = func.new_local(ty.clone(), Some("b".to_string()));
+    let c = func.new_local(ty.clone(), Some("c".to_string()));
+    let big = func.new_local(Type::Primitive(PrimitiveType::Bool), None);
+    func.basic_blocks[0].instructions = vec![
+        Instruction::Copy { dest: a, source: int(2), type_: ty.clone() },
+        Instruction::Mul { dest: b, left: Operand::Local(a), right: int(3), type_: ty.clone() },
+        Instruction::Add { dest: c, left: Operand::Local(b), right: int(1), type_: ty.clone() },
+        Instruction::Gt { dest: big, left: Operand::Local(c), right: int(5) },
+        Instruction::Br { condition: Operand::Local(big), then_bb: 0, else_bb: 0 },
+    ];
+    let mut passes = PassManager::new().with_pass(ConstProp);
+    assert_eq!(passes.run(&mut func), vec!["const-prop"]);
+    assert!(matches!(&func.basic_blocks[0].instructions[2], Instruction::Copy { source, .. } if *source == int(7)));
+    assert!(matches!(&func.basic_blocks[0].instructions[4], Instruction::Br { condition: Operand::Constant(Constant::Bool(true)), .. }));
+    // nothing left 2 do the 2nd time
+    assert!(passes.run(&mut func).is_empty());
+}
+
+#[test]
+fn test_const_prop_wraps_to_the_type_and_leaves_traps() {
+    let add = |dest, left, right, type_| Instruction::Add { dest, left, right, type_ };
+    let div = |dest, left, right, type_| Instruction::Div { dest, left, right, type_ };
+    let cases = [
+        (binary(PrimitiveType::UByte, add, 200, 100), Some(int(44))),
+        (binary(PrimitiveType::Int, add, i32::MAX as i64, 1), Some(int(i32::MIN as i64))),
+        (binary(PrimitiveType::Long, add, i64::MAX, 1), Some(int(i64::MIN))),
+        // -1 is u64::MAX, halved
+        (binary(PrimitiveType::ULong, div, -1, 2), Some(int(i64::MAX))),
+        (binary(PrimitiveType::Int, div, 7, 0), None),
+    ];
+    for (mut func, expected) in cases {
+        ConstProp.run(&mut func);
+        match expected {
+            Some(value) => assert_eq!(returned(&func), Some(&value), "{}", func),
+            None => assert!(matches!(returned(&func), Some(Operand::Local(_))), "{}", func),
+        }
+    }
+}
+
+#[test]
+fn test_const_prop_skips_reassigned_locals_and_params() {
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+    let p = func.new_local(ty.clone(), Some("p".to_string()));
+    func.params.push(crate::core::mir::Param { name: "p".to_string(), type_: ty.clone(), local: p });
+    let i = func.new_local(ty.clone(), Some("i".to_string()));
+    let sum = func.new_local(ty.clone(), None);
+    let cond = func.new_local(Type::Primitive(PrimitiveType::Bool), None);
+    let body = func.new_block();
+    let exit = func.new_block();
+    func.basic_blocks[0].instructions = vec![
+        Instruction::Copy { dest: i, source: int(0), type_: ty.clone() },
+        Instruction::Copy { dest: p, source: int(1), type_: ty.clone() },
+        Instruction::Lt { dest: cond, left: Operand::Local(i), right: int(5) },
+        Instruction::Br { condition: Operand::Local(cond), then_bb: body, else_bb: exit },
+    ];
+    func.basic_blocks[body].instructions = vec![
+        Instruction::Add { dest: i, left: Operand::Local(i), right: int(1), type_: ty.clone() },
+        Instruction::Jump { target: 0 },
+    ];
+    func.basic_blocks[exit].instructions = vec![
+        Instruction::Add { dest: sum, left: Operand::Local(i), right: Operand::Local(p), type_: ty.clone() },
+        Instruction::Ret { value: Some(Operand::Local(sum)) },
+    ];
+    let before = func.to_string();
+    assert!(!ConstProp.run(&mut func));
+    assert_eq!(func.to_string(), before);
+}
+
+#[test]
+fn test_const_prop_compares_ints_of_unknown_sign() {
+    let ty = Type::Primitive(PrimitiveType::Bool);
+    let compare = |inst: fn(Local, Operand, Operand) -> Instruction, left: i64, right: i64| {
+        let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+        let dest = func.new_local(ty.clone(), None);
+        func.basic_blocks[0].instructions = vec![inst(dest, int(left), int(right)), Instruction::Ret { value: Some(Operand::Local(dest)) }];
+        ConstProp.run(&mut func);
+        returned(&func).cloned()
+    };
+    let bool = |b| Some(Operand::Constant(Constant::Bool(b)));
+    let lt = |dest, left, right| Instruction::Lt { dest, left, right };
+    let eq = |dest, left, right| Instruction::Eq { dest, left, right };
+    assert_eq!(compare(lt, 1, 2), bool(true));
+    assert_eq!(compare(lt, -2, -1), bool(true));
+    // -1 could be a ulong above 2, the order depends on a type MIR didnt keep
+    assert!(matches!(compare(lt, -1, 2), Some(Operand::Local(_))));
+    assert_eq!(compare(eq, -1, 2), bool(false));
+}
+
+#[test]
+fn test_dce_removes_dead_chains_and_unused_slots() {
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+    let [a, b, c, slot, q] = [(); 5].map(|_| func.new_local(ty.clone(), None));
+    let print = Operand::Constant(Constant::String("print".to_string()));
+    func.basic_blocks[0].instructions = vec![
+        // b only feeds c, c is never read
+        Instruction::Add { dest: a, left: int(1), right: int(2), type_: ty.clone() },
+        Instruction::Mul { dest: b, left: Operand::Local(a), right: int(3), type_: ty.clone() },
+        Instruction::Copy { dest: c, source: Operand::Local(b), type_: ty.clone() },
+        // written, never read
+        Instruction::Alloca { dest: slot, type_: ty.clone() },
+        Instruction::Store { dest: Operand::Local(slot), source: Operand::Local(a), type_: ty.clone() },
+        // a call + a division that may trap stay even though their results r unused
+        Instruction::Call { dest: Some(q), func: print, args: vec![], return_type: Some(ty.clone()), fixed_args: None, conv: Default::default() },
+        Instruction::Div { dest: q, left: int(1), right: Operand::Local(a), type_: ty.clone() },
+        Instruction::Ret { value: Some(int(0)) },
+    ];
+    let mut dce = Dce::default();
+    assert!(dce.run(&mut func));
+    assert_eq!(func.basic_blocks[0].instructions.len(), 4, "{}", func);
+    assert!(matches!(func.basic_blocks[0].instructions[0], Instruction::Add { .. }), "{}", func);
+    assert_eq!(dce.counters(), vec![("instructions removed", 4), ("blocks removed", 0)]);
+    assert!(!dce.run(&mut func));
+}
+
+#[test]
+fn test_dce_removes_unreachable_blocks_and_renumbers() {
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+    let x = func.new_local(ty.clone(), None);
+    let dead = func.new_block();
+    let exit = func.new_block();
+    func.basic_blocks[0].instructions = vec![Instruction::Jump { target: exit }];
+    func.basic_blocks[dead].instructions = vec![Instruction::Jump { target: exit }];
+    func.basic_blocks[exit].instructions = vec![
+        Instruction::Phi { dest: x, type_: ty.clone(), incoming: vec![(int(1), 0), (int(2), dead)] },
+        Instruction::Ret { value: Some(Operand::Local(x)) },
+    ];
+    let mut passes = PassManager::default_pipeline();
+    assert_eq!(passes.names(), vec!["const-prop", "stack-promote", "load-store", "dce"]);
+    assert_eq!(passes.run(&mut func), vec!["dce"]);
+    assert_eq!(func.basic_blocks.len(), 2);
+    assert!(matches!(func.basic_blocks[0].instructions[0], Instruction::Jump { target: 1 }));
+    assert!(matches!(&func.basic_blocks[1].instructions[0], Instruction::Phi { incoming, .. } if *incoming == vec![(int(1), 0)]));
+
+    let stats = passes.stats();
+    assert_eq!(stats[3].to_string(), "dce: changed 1 of 1 functions, 0 instructions removed, 1 blocks removed");
+    assert_eq!(stats[0].changed, 0);
+}
+
+fn load(dest: Local, source: Local) -> Instruction {
+    Instruction::Load { dest, source: Operand::Local(source), type_: Type::Primitive(PrimitiveType::Int) }
+}
+
+fn store(dest: Local, source: Operand) -> Instruction {
+    Instruction::Store { dest: Operand::Local(dest), source, type_: Type::Primitive(PrimitiveType::Int) }
+}
+
+fn instructions(func: &MirFunction, what: fn(&Instruction) -> bool) -> usize {
+    func.basic_blocks.iter().flat_map(|bb| &bb.instructions).filter(|i| what(i)).count()
+}
+
+#[test]
+fn test_load_store_forwards_into_single_predecessor_blocks() {
+    // `*a = 5` then a jump, the next block loads `*a` twice + `*p` twice
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let pointer = Type::Pointer(PointerType::ref_(ty.clone()));
+    let mut func = MirFunction::new("f".to_string(), Some(ty.clone()));
+    let p = func.new_local(pointer.clone(), Some("p".to_string()));
+    func.params.push(crate::core::mir::Param { name: "p".to_string(), type_: pointer, local: p });
+    let a = func.new_local(ty.clone(), None);
+    let [x, y, z, w, sum] = [(); 5].map(|_| func.new_local(ty.clone(), None));
+    let next = func.new_block();
+    func.basic_blocks[0].instructions =
+        vec![Instruction::Alloca { dest: a, type_: ty.clone() }, store(a, int(5)), Instruction::Jump { target: next }];
+    func.basic_blocks[next].instructions = vec![
+        load(x, a),
+        load(y, a),
+        load(z, p),
+        load(w, p),
+        Instruction::Add { dest: sum, left: Operand::Local(x), right: Operand::Local(w), type_: ty.clone() },
+        Instruction::Add { dest: sum, left: Operand::Local(sum), right: Operand::Local(y), type_: ty.clone() },
+        Instruction::Add { dest: sum, left: Operand::Local(sum), right: Operand::Local(z), type_: ty },
+        Instruction::Ret { value: Some(Operand::Local(sum)) },
+    ];
+    let mut pass = LoadStore::default();
+    assert!(pass.run(&mut func));
+    // both loads of `a` get the stored 5, the 2nd load of `p` reuses the 1st
+    assert!(matches!(&func.basic_blocks[next].instructions[0], Instruction::Copy { source, .. } if *source == int(5)), "{}", func);
+    assert!(matches!(&func.basic_blocks[next].instructions[3], Instruction::Copy { source, .. } if *source == Operand::Local(z)), "{}", func);
+    // + once nothing loads `a` the store is dead 2
+    assert_eq!(pass.counters(), vec![("loads removed", 3), ("stores removed", 1)]);
+    assert!(!pass.run(&mut func));
+}
+
+#[test]
+fn test_load_store_keeps_stores_read_later_or_outside() {
+    // `*a = 1; *p = 2` in a loop that reads `*a` at the top 4 the next trip round
+    let ty = Type::Primitive(PrimitiveType::Int);
+    let pointer = Type::Pointer(PointerType::ref_(ty.clone()));
+    let mut func = MirFunction::new("f".to_string(), None);
+    let p = func.new_local(pointer.clone(), Some("p".to_string()));
+    func.params.push(crate::core::mir::Param { name: "p".to_string(), type_: pointer, local: p });
+    let a = func.new_local(ty.clone(), None);
+    let x = func.new_local(ty.clone(), None);
+    let more = func.new_local(Type::Primitive(PrimitiveType::Bool), None);
+    let (head, body, exit) = (func.new_block(), func.new_block(), func.new_block());
+    func.basic_blocks[0].instructions =
+        vec![Instruction::Alloca { dest: a, type_: ty.clone() }, store(a, int(0)), Instruction::Jump { target: head }];
+    func.basic_blocks[head].instructions = vec![
+        load(x, a),
+        Instruction::Lt { dest: more, left: Operand::Local(x), right: int(10) },
+        Instruction::Br { condition: Operand::Local(more), then_bb: body, else_bb: exit },
+    ];
+    func.basic_blocks[body].instructions = vec![store(a, int(1)), store(p, int(2)), Instruction::Jump { target: head }];
+    func.basic_blocks[exit].instructions = vec![store(a, int(3)), store(p, int(4)), Instruction::Ret { value: None }];
+    let mut pass = LoadStore::default();
+    assert!(pass.run(&mut func));
+    // only `*a = 3` goes, the fn's slot is never read again + the caller cant see it
+    assert_eq!(pass.counters(), vec![("loads removed", 0), ("stores removed", 1)]);
+    assert_eq!(instructions(&func, |i| matches!(i, Instruction::Store { .. })), 4);
+    assert_eq!(func.basic_blocks[exit].instructions.len(), 2);
+}
+
+/// `source` lowered + optimized w/ the inliner at `threshold`, w/ what main returns on the interpreter
+fn inlined(source: &str, threshold: usize) -> (Vec<MirFunction>, i32) {
+    inlined_by(source, MirOptimizer::new().with_inline_threshold(threshold))
+}
+
+fn inlined_by(source: &str, mut optimizer: MirOptimizer) -> (Vec<MirFunction>, i32) {
+    let functions = optimized(source, &mut optimizer);
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&functions).unwrap();
+    let result = factory.create_execution_engine().unwrap().run_main(&module).unwrap();
+    (functions, result)
+}
+
+fn optimized(source: &str, optimizer: &mut MirOptimizer) -> Vec<MirFunction> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let mut functions = MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast));
+    optimizer.optimize_all(&mut functions);
+    functions
+}
+
+fn calls(func: &MirFunction) -> Vec<String> {
+    func.basic_blocks
+        .iter()
+        .flat_map(|bb| &bb.instructions)
+        .filter_map(|i| match i {
+            Instruction::Call { func: Operand::Function(f), .. } => Some(f.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_inliner_replaces_calls_to_small_functions() {
+    let source = r#"
+def square(x : int) returns int
+  return x * x
+end
+
+def pick(c : bool, a : int, b : int) returns int
+  if c
+    return a
+  end
+  return b
+end
+
+def fib(n : int) returns int
+  if n < 2
+    return n
+  end
+  return fib(n - 1) + fib(n - 2)
+end
+
+def main() returns int
+  s : int = square(3) + square(4)
+  return pick(s > 30, s, fib(5))
+end
+"#;
+    let (functions, result) = inlined(source, 20);
+    let main = functions.iter().find(|f| f.name == "main").unwrap();
+    // fib calls itself, it stays a call
+    assert_eq!(calls(main), vec!["fib"], "{}", main);
+    assert_eq!(result, 5);
+
+    let (functions, result) = inlined(source, 0);
+    let main = functions.iter().find(|f| f.name == "main").unwrap();
+    assert_eq!(calls(main), vec!["square", "square", "fib", "pick"], "{}", main);
+    assert_eq!(result, 5);
+}
+
+#[test]
+fn test_call_profile_parsing() {
+    let profile = CallProfile::parse("# 2 runs\nmain hot 900\nmain warm 50\n\nmain cold 0\nmain hot 100  # again\n").unwrap();
+    assert_eq!(profile.count("main", "hot"), Some(1000));
+    assert_eq!(profile.count("main", "none"), None);
+    // hot alone makes up 90% of the calls
+    assert_eq!(profile.hotness("main", "hot"), Hotness::Hot);
+    assert_eq!(profile.hotness("main", "warm"), Hotness::Cold);
+    assert_eq!(profile.hotness("main", "cold"), Hotness::Cold);
+    assert_eq!(profile.hotness("main", "none"), Hotness::Cold);
+    assert_eq!(profile.hotness("helper", "hot"), Hotness::Unknown);
+
+    assert_eq!(
+        CallProfile::parse("main hot 9\nmain hot lots\n"),
+        Err(ProfileError::Malformed { line: 2, text: "main hot lots".to_string() })
+    );
+    assert!(CallProfile::parse("main hot\n").is_err());
+    assert!(CallProfile::parse("").unwrap().is_empty());
+}
+
+#[test]
+fn test_inliner_weighs_call_sites_by_profile() {
+    let source = r#"
+def scale(x : int) returns int
+  y : int = x * 3
+  if y > 100
+    y = y - 100
+  end
+  if y < 10
+    y = y + 10
+  end
+  return y + 1
+end
+
+def twice(x : int) returns int
+  return x + x
+end
+
+def main() returns int
+  return scale(7) + twice(4)
+end
+"#;
+    let profiled = |threshold: usize, profile: &str| {
+        let profile = Arc::new(CallProfile::parse(profile).unwrap());
+        // the threshold set after the profile keeps it
+        let optimizer = MirOptimizer::new().with_profile(profile).with_inline_threshold(threshold);
+        let (functions, result) = inlined_by(source, optimizer);
+        assert_eq!(result, 30);
+        let main = functions.iter().find(|f| f.name == "main").unwrap();
+        calls(main)
+    };
+    let unprofiled = |threshold: usize| calls(inlined(source, threshold).0.iter().find(|f| f.name == "main").unwrap());
+    assert!(unprofiled(20).is_empty());
+    // scale is too big 4 the threshold on size alone, a hot call gets more room
+    assert_eq!(unprofiled(8), vec!["scale"]);
+    assert!(profiled(8, "main scale 1000\nmain twice 1\n").is_empty());
+    // a call main made rarely only inlines a callee no bigger than the call
+    assert_eq!(profiled(20, "main twice 1000\nmain scale 1\n"), vec!["scale"]);
+    // a profile that doesnt know main leaves it 2 size
+    assert_eq!(profiled(8, "other scale 1000\n"), vec!["scale"]);
+}
+
+#[test]
+fn test_dump_mir_snapshots_around_passes() {
+    let source = r#"
+def twice(x : int) returns int
+  return x + x
+end
+
+def main() returns int
+  unused : int = 5
+  return twice(4)
+end
+"#;
+    let dir = std::env::temp_dir().join(format!("emc-dump-mir-{}", std::process::id()));
+    let dump = |filter: &str| {
+        std::fs::remove_dir_all(&dir).ok();
+        let optimizer = MirOptimizer::new().with_dump(MirDump::new(&dir, DumpFilter::parse(filter)));
+        assert_eq!(inlined_by(source, optimizer).1, 8);
+        let mut files: Vec<String> =
+            std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        files.sort();
+        files
+    };
+    // the step number is where the pass is in the pipeline, picked or not
+    let steps = MirOptimizer::new().pass_names().len();
+    assert_eq!(dump("dce"), ["main.06.dce.after.mir", "main.06.dce.before.mir", "twice.06.dce.after.mir", "twice.06.dce.before.mir"]);
+    assert_eq!(dump("all").len(), 2 * 2 * steps);
+
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+    assert!(read("main.00.inline.before.mir").contains("call int @twice"));
+    assert!(!read("main.00.inline.after.mir").contains("call int @twice"));
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+const HEAP: &str = r#"
+foreign "C" libc
+  def malloc(size : int) returns ref int
+  def free(p : ref int)
+end
+
+def keep(p : ref int) returns int
+  return 0
+end
+"#;
+
+#[test]
+fn test_stack_promote_moves_local_allocations_to_the_stack() {
+    let source = format!(
+        "{}{}",
+        HEAP,
+        r#"
+def main() returns int
+  p : ref int = malloc(4)
+  p.value = 41
+  x : int = p.value + 1
+  free(p)
+  return x
+end
+"#
+    );
+    let mut optimizer = MirOptimizer::new();
+    let functions = optimized(&source, &mut optimizer);
+    let main = functions.iter().find(|f| f.name == "main").unwrap();
+    // the free goes w/ the malloc
+    assert!(calls(main).is_empty(), "{}", main);
+    assert_eq!(optimizer.remarks(), ["promoted a 4 byte heap allocation in 'main' to the stack"]);
+    // the interpreter has no malloc, it only runs once there isnt 1
+    assert_eq!(inlined(&source, 0).1, 42);
+}
+
+#[test]
+fn test_stack_promote_leaves_allocations_it_cant_move() {
+    let cases = [
+        // passed 2 a fn, which could keep it
+        "p : ref int = malloc(4)\n  keep(p)\n  free(p)",
+        // bigger than the int it's used as
+        "p : ref int = malloc(8)\n  p.value = 1\n  free(p)",
+        // a new allocation each time round the loop
+        "i : int = 0\n  while i < 3\n    p : ref int = malloc(4)\n    free(p)\n    i = i + 1\n  end",
+    ];
+    for body in cases {
+        let source = format!("{}\ndef main() returns int\n  {}\n  return 0\nend\n", HEAP, body);
+        let mut optimizer = MirOptimizer::new().with_inline_threshold(0);
+        let functions = optimized(&source, &mut optimizer);
+        let main = functions.iter().find(|f| f.name == "main").unwrap();
+        assert!(calls(main).contains(&"malloc".to_string()), "{}", main);
+        assert!(optimizer.remarks().is_empty());
+    }
+
+    // a returned allocation outlives the fn
+    let source = format!("{}\ndef make() returns ref int\n  p : ref int = malloc(4)\n  return p\nend\n", HEAP);
+    let functions = optimized(&source, &mut MirOptimizer::new());
+    let make = functions.iter().find(|f| f.name == "make").unwrap();
+    assert_eq!(calls(make), ["malloc"]);
+}
@@ -94,3 +94,319 @@ end
         assert!(func.basic_blocks.len() > 1); // shuold have multiple blocks
     }
 }
+
+#[test]
+fn test_mir_constant_table_is_static_data() {
+    let source = r#"
+struct Point
+  x : int
+  y : int
+end
+
+def main()
+  table : int[4] = [1, 2, 4, 8]
+  origin : Point = Point { y: 0, x: -1 }
+end
+"#;
+    let (mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let main_fn = mir_funcs.iter().find(|f| f.name == "main").unwrap();
+    let insts: Vec<_> = main_fn.basic_blocks.iter().flat_map(|bb| bb.instructions.iter()).collect();
+    // one aggregate copy each, no per-element stores
+    let aggregates: Vec<_> = insts.iter().filter_map(|i| match i {
+        crate::core::mir::Instruction::Copy {
+            source: crate::core::mir::Operand::Constant(crate::core::mir::Constant::Aggregate(values)),
+            ..
+        } => Some(values.clone()),
+        _ => None,
+    }).collect();
+    assert_eq!(aggregates.len(), 2);
+    assert_eq!(aggregates[0].len(), 4);
+    // struct fields follow declaration order
+    assert_eq!(aggregates[1], vec![crate::core::mir::Constant::Int(-1), crate::core::mir::Constant::Int(0)]);
+    assert!(!insts.iter().any(|i| matches!(i, crate::core::mir::Instruction::Store { .. })));
+}
+
+#[test]
+fn test_mir_runtime_struct_literal_stores_fields() {
+    let source = r#"
+struct Point
+  x : int
+  y : int
+end
+
+def make(a : int) returns Point
+  return Point { x: a, y: 2 }
+end
+"#;
+    let (mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let make_fn = mir_funcs.iter().find(|f| f.name == "make").unwrap();
+    let insts: Vec<_> = make_fn.basic_blocks.iter().flat_map(|bb| bb.instructions.iter()).collect();
+    let stores = insts.iter().filter(|i| matches!(i, crate::core::mir::Instruction::Store { .. })).count();
+    assert_eq!(stores, 2);
+}
+
+#[test]
+fn test_mir_pointer_offset_lowers_to_ptr_offset() {
+    let source = r#"
+def second(buf : ref int) returns int
+  p : ref int = buf.offset(1)
+  return p.value
+end
+"#;
+    let (mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let func = mir_funcs.iter().find(|f| f.name == "second").unwrap();
+    let insts: Vec<_> = func.basic_blocks.iter().flat_map(|bb| bb.instructions.iter()).collect();
+    // element type is the pointee, not the pointer
+    assert!(insts.iter().any(|i| matches!(
+        i,
+        crate::core::mir::Instruction::PtrOffset {
+            type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Int),
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_mir_volatile_access_survives_optimization() {
+    let source = r#"
+def poke(reg : ref int)
+  volatile_store(reg, 1)
+  ignored : int = volatile_load(reg)
+end
+"#;
+    let (mut mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let func = mir_funcs.iter_mut().find(|f| f.name == "poke").unwrap();
+    crate::core::optimizations::MirOptimizer::new().optimize(func);
+    let insts: Vec<_> = func.basic_blocks.iter().flat_map(|bb| bb.instructions.iter()).collect();
+    // unused volatile load and store thru a never-read param both stay
+    assert!(insts.iter().any(|i| matches!(i, crate::core::mir::Instruction::VolatileStore { .. })));
+    assert!(insts.iter().any(|i| matches!(i, crate::core::mir::Instruction::VolatileLoad { .. })));
+}
+
+#[test]
+fn test_mir_call_graph_flags_recursion() {
+    let source = r#"
+def ping(n: int) returns int
+  return pong(n - 1)
+end
+
+def pong(n: int) returns int
+  return ping(n)
+end
+
+def leaf(n: int) returns int
+  return n + 1
+end
+
+def main() returns int
+  return leaf(1)
+end
+"#;
+    let (mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let graph = crate::backend::stack_usage::mir_call_graph(&mir_funcs);
+    assert_eq!(graph["main"], vec!["leaf".to_string()]);
+    assert_eq!(graph["ping"], vec!["pong".to_string()]);
+
+    let frames = mir_funcs
+        .iter()
+        .map(|f| crate::backend::stack_usage::FrameInfo {
+            name: f.name.clone(),
+            frame_size: 16,
+            dynamic: false,
+            callees: Vec::new(),
+        })
+        .collect();
+    let usage = crate::backend::stack_usage::StackUsage::new(frames).with_calls(&graph);
+    let recursive = usage.recursive_functions();
+    assert!(recursive.contains("ping") && recursive.contains("pong"));
+    assert!(!recursive.contains("main"));
+    assert_eq!(usage.worst_case("main"), Some(32));
+    assert_eq!(usage.worst_case("ping"), None);
+}
+
+#[test]
+fn test_mir_multiversioned_function_versions() {
+    let source = r#"
+@target_feature("sse4.2", "avx2")
+def dot(a: int, b: int) returns int
+  return a * b
+end
+"#;
+    let (mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let dot = mir_funcs.iter().find(|f| f.name == "dot").unwrap();
+    assert_eq!(dot.target_features, vec!["sse4.2".to_string(), "avx2".to_string()]);
+
+    // newest isa is tried first, baseline last
+    let versions = crate::backend::multiversion::versions(&dot.name, &dot.target_features).unwrap();
+    let symbols: Vec<&str> = versions.iter().map(|v| v.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["dot.avx2", "dot.sse4.2", "dot.default"]);
+    assert_eq!(versions[0].cpu_bit, Some(10));
+    assert_eq!(versions[0].llvm_target_features().as_deref(), Some("+avx2"));
+    assert_eq!(versions[2].llvm_target_features(), None);
+
+    use crate::backend::multiversion::{dispatch_kind, DispatchKind};
+    use crate::core::target::TargetInfo;
+    assert_eq!(dispatch_kind(&TargetInfo::from_triple("x86_64-unknown-linux-gnu")).unwrap(), DispatchKind::IFunc);
+    assert_eq!(dispatch_kind(&TargetInfo::from_triple("x86_64-apple-darwin")).unwrap(), DispatchKind::Thunk);
+    assert!(dispatch_kind(&TargetInfo::from_triple("aarch64-unknown-linux-gnu")).is_err());
+    // no __cpu_model in the msvc runtime, windows-gnu has libgcc's
+    assert!(dispatch_kind(&TargetInfo::from_triple("x86_64-pc-windows-msvc")).is_err());
+    assert_eq!(dispatch_kind(&TargetInfo::from_triple("x86_64-pc-windows-gnu")).unwrap(), DispatchKind::Thunk);
+}
+
+#[test]
+fn test_mir_field_access_geps_declared_field() {
+    let source = r#"
+struct Pair
+  a : int
+  b : float
+end
+
+def second(p : Pair) returns float
+  return p.b
+end
+"#;
+    let (mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let second = mir_funcs.iter().find(|f| f.name == "second").unwrap();
+    let insts: Vec<_> = second.basic_blocks.iter().flat_map(|bb| bb.instructions.iter()).collect();
+    assert!(insts.iter().any(|i| matches!(
+        i,
+        crate::core::mir::Instruction::Gep { index: crate::core::mir::Operand::Constant(crate::core::mir::Constant::Int(1)), .. }
+    )));
+    assert!(insts.iter().any(|i| matches!(
+        i,
+        crate::core::mir::Instruction::Load { type_: crate::core::types::ty::Type::Primitive(crate::core::types::primitive::PrimitiveType::Float), .. }
+    )));
+}
+
+#[test]
+fn test_mir_string_concat_and_len() {
+    let source = r#"
+def greet(name : string) returns long
+  s : string = "hello, " + name
+  return s.len()
+end
+"#;
+    let (mut mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    let greet = mir_funcs.iter_mut().find(|f| f.name == "greet").unwrap();
+    crate::core::optimizations::MirOptimizer::new().optimize(greet);
+    let insts: Vec<_> = greet.basic_blocks.iter().flat_map(|bb| bb.instructions.iter()).collect();
+    // the literal stays a copy so the backend can intern it
+    assert!(insts.iter().any(|i| matches!(i, crate::core::mir::Instruction::Copy {
+        source: crate::core::mir::Operand::Constant(crate::core::mir::Constant::String(s)),
+        ..
+    } if s == "hello, ")));
+    assert!(insts.iter().any(|i| matches!(i, crate::core::mir::Instruction::StrConcat { .. })));
+    assert!(insts.iter().any(|i| matches!(i, crate::core::mir::Instruction::StrLen { .. })));
+}
+
+#[test]
+fn test_mir_verifier_accepts_lowered_programs() {
+    let source = r#"
+def classify(n : int) returns int
+  match n
+    when 0
+      return 0
+    when 1 .. 9
+      return 1
+    else
+      n = n - 1
+  end
+  mut i : int = 0
+  while i < n
+    i = i + 1
+  end
+  return i
+end
+
+def label(name : string) returns long
+  return (name + "!").len()
+end
+"#;
+    let (mut mir_funcs, reporter) = lower_to_mir(source);
+    assert!(!reporter.has_errors());
+    assert_eq!(crate::core::mir::verify::verify_all(&mir_funcs), Ok(()));
+    for func in &mut mir_funcs {
+        crate::core::optimizations::MirOptimizer::new().optimize(func);
+    }
+    assert_eq!(crate::core::mir::verify::verify_all(&mir_funcs), Ok(()));
+}
+
+#[test]
+fn test_mir_verifier_reports_broken_mir() {
+    use crate::core::mir::verify::{verify, VerifyErrorKind};
+    use crate::core::mir::{Constant, Instruction, Local, MirFunction, Operand};
+    use crate::core::types::primitive::PrimitiveType;
+    use crate::core::types::ty::Type;
+
+    let int = Type::Primitive(PrimitiveType::Int);
+    let mut func = MirFunction::new("broken".to_string(), Some(int.clone()));
+    let x = func.new_local(int.clone(), Some("x".to_string()));
+    let merge = func.new_block();
+    let other = func.new_block();
+    func.basic_blocks[0].instructions = vec![
+        Instruction::Add { dest: x, left: Operand::Local(Local::new(7)), right: Operand::Constant(Constant::Float(1.5)), type_: int.clone() },
+        Instruction::Br { condition: Operand::Local(x), then_bb: merge, else_bb: 9 },
+    ];
+    func.basic_blocks[merge].instructions = vec![
+        Instruction::Phi { dest: Local::new(1), type_: int.clone(), incoming: vec![(Operand::Local(x), 0), (Operand::Constant(Constant::Int(0)), other)] },
+        Instruction::Ret { value: Some(Operand::Constant(Constant::String("no".to_string()))) },
+        Instruction::Jump { target: other },
+    ];
+    let errors = verify(&func).unwrap_err();
+    let kinds: Vec<_> = errors.iter().map(|e| (e.block, e.kind.clone())).collect();
+    let mismatch = |what: &str, expected: &str, found: &str| VerifyErrorKind::TypeMismatch {
+        what: what.to_string(),
+        expected: expected.to_string(),
+        found: found.to_string(),
+    };
+    assert_eq!(
+        kinds,
+        vec![
+            (0, VerifyErrorKind::UndefinedLocal(Local::new(7))),
+            (0, mismatch("operand 1.5", "int", "a float")),
+            (0, VerifyErrorKind::UnknownBlock(9)),
+            (0, mismatch("condition %0", "a bool", "an integer")),
+            (1, VerifyErrorKind::EarlyTerminator(1)),
+            (1, VerifyErrorKind::PhiFromNonPredecessor(2)),
+            (1, mismatch("returned value \"no\"", "int", "a string")),
+            (2, VerifyErrorKind::MissingTerminator),
+        ]
+    );
+    assert_eq!(errors[0].to_string(), "MIR of 'broken' is invalid in bb0: %7 is used but never assigned");
+
+    // the bridge refuses 2 hand it 2 a backend
+    let factory = crate::backend::null::NullBackendFactory;
+    let mut bridge = crate::backend::BackendBridge::from_factory(&factory).unwrap();
+    let err = bridge.compile_from_mir(&[func]).unwrap_err();
+    assert!(matches!(err, crate::backend::CompileError::InvalidMir(ref e) if e.len() == 8), "{}", err);
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_verifier_names_the_broken_function() {
+    use crate::backend::llvm::LlvmCodeGen;
+    use crate::backend::ports::codegen::{CodeGen, CodeGenError};
+    use crate::core::mir::{Instruction, MirFunction};
+    use crate::core::types::primitive::PrimitiveType;
+    use crate::core::types::ty::Type;
+
+    let (mir_funcs, _) = lower_to_mir("def main() returns int\n  return 0\nend\n");
+    assert!(LlvmCodeGen::new().generate_from_mir(&mir_funcs).is_ok());
+
+    // valid MIR the llvm verifier rejects, a bare `ret` frm an int fn
+    let mut func = MirFunction::new("answer".to_string(), Some(Type::Primitive(PrimitiveType::Int)));
+    func.basic_blocks[0].instructions = vec![Instruction::Ret { value: None }];
+    let err = LlvmCodeGen::new().generate_from_mir(&[func]).unwrap_err();
+    let CodeGenError::GenerationFailed(message) = err else { panic!("{}", err) };
+    assert!(message.starts_with("LLVM rejected the IR generated for function 'answer':\n"), "{}", message);
+    assert!(message.contains("return"), "{}", message);
+}
@@ -1,18 +1,55 @@
+pub mod alias_tests;
+pub mod assoc_const_tests;
 pub mod bounds_checking_tests;
+pub mod call_graph_tests;
 pub mod comptime_tests;
+pub mod cranelift_tests;
+pub mod daemon_tests;
+pub mod definite_init_tests;
+pub mod defer_tests;
+pub mod deprecation_tests;
+pub mod dominator_tests;
+pub mod derive_tests;
+pub mod driver_tests;
+pub mod edition_tests;
+pub mod enum_tests;
+pub mod exhaustiveness_tests;
+pub mod events_tests;
+pub mod feature_tests;
 pub mod ffi_tests;
 pub mod function_tests;
 pub mod generic_tests;
+pub mod grammar_tests;
+pub mod highlight_tests;
+pub mod ice_tests;
+pub mod guard_tests;
+pub mod heap_tests;
 pub mod hir_tests;
+pub mod interp_tests;
 pub mod lexer_tests;
 pub mod lifetime_tests;
+pub mod limits_tests;
+pub mod link_tests;
+pub mod match_tests;
 pub mod memory_tests;
+pub mod mir_pass_tests;
 pub mod mir_tests;
+pub mod null_backend_tests;
 pub mod module_tests;
 pub mod output_tests;
+pub mod panic_tests;
 pub mod parser_tests;
+pub mod playground_tests;
+pub mod record_tests;
+pub mod remote_tests;
+pub mod repl_tests;
+pub mod result_tests;
 pub mod semantic_tests;
+pub mod size_tests;
 pub mod specialization_tests;
+pub mod stack_usage_tests;
 pub mod syntax_tests;
 pub mod trait_object_tests;
-pub mod type_tests;
\ No newline at end of file
+pub mod tuple_tests;
+pub mod type_tests;
+pub mod while_let_tests;
\ No newline at end of file
@@ -1,7 +1,9 @@
+use crate::core::ast::Item;
 use crate::error::Reporter;
 use crate::frontend::lexer::Lexer;
 use crate::frontend::parser::Parser;
-use crate::frontend::semantic::SemanticAnalyzer;
+use crate::frontend::semantic::symbol_table::SymbolTable;
+use crate::frontend::semantic::{Prelude, SemanticAnalyzer};
 use codespan::Files;
 
 fn print_diagnostics(reporter: &Reporter, files: &Files<String>) {
@@ -193,3 +195,261 @@ end
     let (_ast, reporter) = analyze_source(source);
     assert!(!reporter.has_errors());
 }
+
+const MATHX: &str = r#"
+def square(x : int) returns int
+  return x * x
+end
+
+def add(a : int, b : int) returns int
+  return a + square(b)
+end
+"#;
+
+/// `main` next 2 `util/mathx.em` in a fresh dir, `main` is analyzed frm there
+fn analyze_with_module(test: &str, main: &str, f: impl FnOnce(&SemanticAnalyzer, &crate::core::ast::Ast, SymbolTable)) -> Reporter {
+    analyze_with_modules(test, main, &[("util/mathx.em", MATHX)], false, f)
+}
+
+/// same w/ any set of module files, paths r relative 2 `main`
+fn analyze_with_modules(
+    test: &str,
+    main: &str,
+    modules: &[(&str, &str)],
+    prelude: bool,
+    f: impl FnOnce(&SemanticAnalyzer, &crate::core::ast::Ast, SymbolTable),
+) -> Reporter {
+    let dir = std::env::temp_dir().join(format!("emc-import-{}-{}", test, std::process::id()));
+    for (path, source) in modules {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file(dir.join("main.em").to_string_lossy().to_string(), main.to_string());
+    let tokens = Lexer::new(main, file_id, &mut reporter).tokenize();
+    let mut ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let prelude = prelude.then(|| Prelude::load(&mut reporter));
+    if let Some(prelude) = &prelude {
+        ast = prelude.inject(&ast, true);
+    }
+    let mut analyzer = SemanticAnalyzer::new(&mut reporter, file_id);
+    if let Some(prelude) = prelude {
+        analyzer = analyzer.with_prelude(prelude);
+    }
+    let symbol_table = analyzer.analyze(&ast);
+    f(&analyzer, &ast, symbol_table);
+    drop(analyzer);
+    std::fs::remove_dir_all(&dir).ok();
+    reporter
+}
+
+fn errors(reporter: &Reporter) -> Vec<String> {
+    reporter.diagnostics().iter().map(|d| d.message.clone()).collect()
+}
+
+#[test]
+fn test_import_resolves_module_functions() {
+    let main = r#"
+import util::mathx
+
+def main() returns int
+  return mathx::add(1, 3)
+end
+"#;
+    let reporter = analyze_with_module("ok", main, |_, _, _| {});
+    assert!(!reporter.has_errors(), "{:?}", errors(&reporter));
+
+    let main = r#"
+import util::mathx
+import mathx
+import util::missing
+
+def main() returns int
+  a : int = mathx::add(true, 3)
+  return mathx::sub(1, 3)
+end
+"#;
+    let reporter = analyze_with_module("bad", main, |_, _, _| {});
+    let errors = errors(&reporter);
+    assert!(errors.iter().any(|m| m == "Module 'mathx' is imported more than once"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Module file not found: ./util/missing"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m.starts_with("Argument 0 type mismatch")), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Module 'mathx' has no function 'sub'"), "{:?}", errors);
+}
+
+#[test]
+fn test_imported_module_is_its_own_mir_unit() {
+    use crate::core::mir::{Instruction, MirModule, Operand};
+    use crate::middle::{HirLowerer, MirLowerer};
+
+    let main = r#"
+import util::mathx
+
+def main() returns int
+  return mathx::add(1, 3)
+end
+"#;
+    let calls = |functions: &[crate::core::mir::MirFunction], name: &str| -> Vec<String> {
+        let f = functions.iter().find(|f| f.name == name).unwrap();
+        f.basic_blocks
+            .iter()
+            .flat_map(|bb| &bb.instructions)
+            .filter_map(|i| match i {
+                Instruction::Call { func: Operand::Function(f), .. } => Some(f.name.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    analyze_with_module("unit", main, |analyzer, ast, symbol_table| {
+        let modules = analyzer.imported_modules();
+        assert_eq!(modules.len(), 1);
+        let (name, module_ast, symbols) = modules[0];
+        let functions = MirLowerer::new().lower(&HirLowerer::new(symbols.clone()).lower(module_ast));
+        let unit = MirModule::new(name.to_string(), functions);
+        let names: Vec<_> = unit.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["mathx::square", "mathx::add"]);
+        assert_eq!(calls(&unit.functions, "mathx::add"), ["mathx::square"]);
+
+        let program = MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(ast));
+        assert_eq!(calls(&program, "main"), ["mathx::add"]);
+    });
+}
+
+#[test]
+fn test_cross_file_errors_point_at_the_definition() {
+    use codespan_reporting::term::{self, termcolor::NoColor, Config};
+
+    let main = r#"
+import util::mathx
+
+def main() returns int
+  return mathx::add(true, 3)
+end
+"#;
+    let reporter = analyze_with_module("origin", main, |_, _, _| {});
+    let diag = reporter.diagnostics().iter().find(|d| d.message.starts_with("Argument 0 type mismatch")).unwrap();
+    let [label] = &diag.labels[..] else { panic!("expected one label, got {:?}", diag.labels) };
+    assert_ne!(label.file_id, diag.file_id);
+    assert_eq!(label.message, "'mathx::add' is defined here");
+    let def_line = &reporter.files().source(label.file_id)[label.span.start().to_usize()..label.span.end().to_usize()];
+    assert_eq!(def_line, "def add(a : int, b : int) returns int");
+
+    // each span is printed under its own file's name
+    let mut writer = NoColor::new(Vec::new());
+    term::emit(&mut writer, &Config::default(), reporter.files(), &crate::cli::error_display::convert_diagnostic(diag)).unwrap();
+    let rendered = String::from_utf8(writer.into_inner()).unwrap();
+    assert!(rendered.contains("main.em:5:"), "{}", rendered);
+    assert!(rendered.contains("mathx.em:6:"), "{}", rendered);
+    assert!(rendered.contains("def add(a : int, b : int) returns int"), "{}", rendered);
+}
+
+#[test]
+fn test_selective_aliased_and_re_exported_imports() {
+    use crate::core::mir::{Instruction, Operand};
+    use crate::middle::{HirLowerer, MirLowerer};
+
+    // `lib` only passes `add` on, `square` stays its own business
+    let lib = r#"
+pub import mathx (add)
+import mathx as hidden
+
+def twice(x : int) returns int
+  return hidden::square(x) + x
+end
+"#;
+    let main = r#"
+import util::lib as l
+import util::mathx (square) as m
+
+def main() returns int
+  return l::add(1, 2) + l::twice(3) + m::square(4)
+end
+"#;
+    let files = [("util/mathx.em", MATHX), ("util/lib.em", lib)];
+    let reporter = analyze_with_modules("alias", main, &files, false, |analyzer, ast, symbol_table| {
+        let program = MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(ast));
+        let main = program.iter().find(|f| f.name == "main").unwrap();
+        let calls: Vec<_> = main
+            .basic_blocks
+            .iter()
+            .flat_map(|bb| &bb.instructions)
+            .filter_map(|i| match i {
+                Instruction::Call { func: Operand::Function(f), .. } => Some(f.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        // calls name the symbols the modules' objects define, not the local aliases
+        assert_eq!(calls, ["mathx::add", "lib::twice", "mathx::square"]);
+        let mut names: Vec<_> = analyzer.imported_modules().iter().map(|(name, ..)| *name).collect();
+        names.sort();
+        assert_eq!(names, ["lib", "mathx"]);
+    });
+    assert!(!reporter.has_errors(), "{:?}", errors(&reporter));
+
+    let main = r#"
+import util::lib
+import util::mathx (square, cube) as m
+
+def main() returns int
+  a : int = m::add(1, 2)
+  return lib::square(2)
+end
+"#;
+    let reporter = analyze_with_modules("alias-bad", main, &files, false, |_, _, _| {});
+    assert_eq!(
+        errors(&reporter),
+        [
+            "Module 'mathx' has no function 'cube'",
+            "Module 'm' has no function 'add'",
+            "Module 'lib' has no function 'square'",
+        ]
+    );
+}
+
+#[test]
+fn test_prelude_is_in_every_modules_scope() {
+    let checks = r#"
+def twice(x : int) returns int
+  assert(x < 100)
+  return x * 2
+end
+"#;
+    let main = r#"
+import checks
+
+struct List
+  count : int
+end
+
+def main() returns int
+  found : Option[int]
+  found.some = true
+  assert(found.some)
+  items : List
+  items.count = 2
+  return checks::twice(items.count)
+end
+"#;
+    let files = [("checks.em", checks)];
+    let reporter = analyze_with_modules("prelude", main, &files, true, |analyzer, ast, _| {
+        // the program's own List shadows the prelude's
+        let lists = ast.items.iter().filter(|i| matches!(i, Item::Struct(s) if s.name == "List")).count();
+        assert_eq!(lists, 1);
+        assert!(ast.items.iter().any(|i| matches!(i, Item::Function(f) if f.name == "assert")));
+
+        // the module only declares assert, the program's object defines it
+        let (_, module, _) = analyzer.imported_modules()[0];
+        assert!(!module.items.iter().any(|i| matches!(i, Item::Function(f) if f.name == "assert")));
+        assert!(module
+            .items
+            .iter()
+            .any(|i| matches!(i, Item::Foreign(b) if b.functions.iter().any(|f| f.name == "assert"))));
+    });
+    assert!(!reporter.has_errors(), "{:?}", errors(&reporter));
+
+    let reporter = analyze_with_modules("no-prelude", main, &files, false, |_, _, _| {});
+    let errors = errors(&reporter);
+    assert!(errors.iter().any(|m| m == "Undefined variable 'assert'"), "{:?}", errors);
+}
@@ -0,0 +1,103 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::null::NullBackendFactory;
+use crate::cli::args::{Cli, CompileConfig};
+use crate::cli::compiler::Compiler;
+use crate::core::mir::{Constant, Instruction, Local, MirFunction, Operand};
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use clap::Parser as _;
+
+/// the null backend's output 4 `source`, unoptimized
+fn dump(source: &str) -> String {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbols = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let mir = MirLowerer::new().lower(&HirLowerer::new(symbols).lower(&ast));
+
+    let factory = NullBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&mir).unwrap();
+    let output = std::env::temp_dir().join(format!("emc-null-{}-{}.mir", std::process::id(), source.len()));
+    factory.create_emitter().unwrap().emit_object(&module, &output).unwrap();
+    let text = std::fs::read_to_string(&output).unwrap();
+    let _ = std::fs::remove_file(&output);
+    text
+}
+
+#[test]
+fn test_null_backend_dumps_mir() {
+    let source = r#"
+def pick(a : int, flag : bool) returns int
+  if flag
+    return a * 2
+  end
+  return a
+end
+"#;
+    let expected = concat!(
+        "; target unknown-unknown-unknown, opt level Default\n",
+        "\n",
+        "fn @pick(%0: int, %1: bool) -> int {\n",
+        "    let %0: int  ; a\n",
+        "    let %1: bool  ; flag\n",
+        "    let %2: int\n",
+        "\n",
+        "  bb0:\n",
+        "    br %1, bb1, bb2\n",
+        "\n",
+        "  bb1:\n",
+        "    %2 = mul int %0, 2\n",
+        "    ret %2\n",
+        "\n",
+        "  bb2:\n",
+        "    jump bb3\n",
+        "\n",
+        "  bb3:\n",
+        "    ret %0\n",
+        "}\n",
+    );
+    assert_eq!(dump(source), expected);
+    // same input, same text
+    assert_eq!(dump(source), dump(source));
+}
+
+#[test]
+fn test_null_backend_spells_constants() {
+    let mut func = MirFunction::new("consts".to_string(), None);
+    let dest = func.new_local(Type::String, None);
+    let string = |s: &str| Operand::Constant(Constant::String(s.to_string()));
+    func.basic_blocks[0].add_instruction(Instruction::StrConcat { dest, left: string("a\"b"), right: string("\n") });
+    func.basic_blocks[0].add_instruction(Instruction::Copy {
+        dest: Local::new(1),
+        source: Operand::Constant(Constant::Aggregate(vec![Constant::Float(1.0), Constant::Char('\t'), Constant::Null])),
+        type_: Type::Primitive(PrimitiveType::Float),
+    });
+    func.basic_blocks[0].add_instruction(Instruction::Ret { value: None });
+    assert_eq!(
+        func.to_string(),
+        "fn @consts() -> void {\n    let %0: string\n\n  bb0:\n    %0 = strconcat \"a\\\"b\", \"\\n\"\n    %1 = copy float [1.0, '\\t', null]\n    ret\n}\n"
+    );
+}
+
+#[test]
+fn test_null_backend_writes_the_requested_output() {
+    let dir = std::env::temp_dir().join(format!("emc-null-driver-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("main.em");
+    std::fs::write(&input, "def main() returns int\n  return 7\nend\n").unwrap();
+    let output = dir.join("main.o");
+    let cli = Cli::parse_from(["emerald", "--null", "--no-prelude", input.to_str().unwrap(), "--emit=obj", "-o", output.to_str().unwrap()]);
+    let result = Compiler::new(CompileConfig::from_cli(&cli).unwrap()).compile().unwrap();
+    assert!(result.success);
+    let text = std::fs::read_to_string(&output).unwrap();
+    assert!(text.contains("fn @main() -> int {"), "{}", text);
+    assert!(text.contains("ret 7"), "{}", text);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,161 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::core::mir::{Constant, Instruction, MirFunction, Operand, PANIC_FN};
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::prelude::Prelude;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+
+/// `source` as test.em, w/ the prelude + its assert lowered like the driver does when `prelude`
+fn lower_mir(source: &str, prelude: bool) -> Vec<MirFunction> {
+    lower_with(source, prelude, MirLowerer::new())
+}
+
+fn lower_with(source: &str, prelude: bool, lowerer: MirLowerer) -> Vec<MirFunction> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let mut ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let prelude = prelude.then(|| Prelude::load(&mut reporter));
+    let mut lowerer = lowerer.with_source("test.em", source);
+    if let Some(prelude) = &prelude {
+        if prelude.provides(&ast, "assert") {
+            lowerer = lowerer.with_prelude_assert();
+        }
+        ast = prelude.inject(&ast, true);
+    }
+    let mut analyzer = SemanticAnalyzer::new(&mut reporter, file_id);
+    if let Some(prelude) = prelude {
+        analyzer = analyzer.with_prelude(prelude);
+    }
+    let symbol_table = analyzer.analyze(&ast);
+    drop(analyzer);
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+    assert!(!reporter.has_errors(), "{:?}", errors);
+    lowerer.lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+/// main's exit code, or the message it stopped w/
+fn run(source: &str, prelude: bool) -> Result<i32, String> {
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&lower_mir(source, prelude)).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module).map_err(|e| e.to_string())
+}
+
+/// the messages `func` passes 2 the panic runtime
+fn panic_messages(func: &MirFunction) -> Vec<String> {
+    let strings: Vec<(usize, String)> = func
+        .basic_blocks
+        .iter()
+        .flat_map(|bb| &bb.instructions)
+        .filter_map(|inst| match inst {
+            Instruction::Copy { dest, source: Operand::Constant(Constant::String(s)), .. } => Some((dest.id, s.clone())),
+            _ => None,
+        })
+        .collect();
+    func.basic_blocks
+        .iter()
+        .flat_map(|bb| &bb.instructions)
+        .filter_map(|inst| match inst {
+            Instruction::Call { func: Operand::Function(f), args, .. } if f.name == PANIC_FN => match &args[0] {
+                Operand::Local(l) => strings.iter().find(|(id, _)| *id == l.id).map(|(_, s)| s.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+const BOUNDS: &str = r#"
+def get(a : int[4], i : int) returns int
+  return a[i]
+end
+
+def main() returns int
+  a : int[4] = [1, 2, 3, 4]
+  return get(a, N)
+end
+"#;
+
+#[test]
+fn test_out_of_bounds_index_panics_with_its_location() {
+    assert_eq!(run(&BOUNDS.replace('N', "2"), false), Ok(3));
+    for index in ["4", "-1"] {
+        let err = run(&BOUNDS.replace('N', index), false).unwrap_err();
+        assert!(err.contains("panicked at test.em:3: index out of bounds for length 4"), "{}", err);
+    }
+}
+
+#[test]
+fn test_assigning_an_element_stores_thru_its_address() {
+    let source = r#"
+def main() returns int
+  a : int[4] = [1, 2, 3, 4]
+  i : int = 1
+  a[i] = 7
+  return a[i] + a[2]
+end
+"#;
+    assert_eq!(run(source, false), Ok(10));
+}
+
+#[test]
+fn test_panic_builtin_stops_the_program() {
+    let source = r#"
+def check(n : int) returns int
+  if n > 2
+    panic("n is too big")
+  end
+  return n
+end
+
+def main() returns int
+  return check(1) + check(3)
+end
+"#;
+    let err = run(source, false).unwrap_err();
+    assert!(err.contains("panicked at test.em:4: n is too big"), "{}", err);
+}
+
+#[test]
+fn test_failed_prelude_assert_panics() {
+    let source = r#"
+def main() returns int
+  x : int = 2
+  assert(x == 2)
+  assert(x == 3)
+  return x
+end
+"#;
+    let err = run(source, true).unwrap_err();
+    assert!(err.contains("panicked at test.em:5: assertion failed"), "{}", err);
+    assert_eq!(run(&source.replace("x == 3", "x > 1"), true), Ok(2));
+}
+
+#[test]
+fn test_nullable_dereference_is_checked_at_runtime() {
+    let source = r#"
+def read(p : ref? int) returns int
+  if p.exists?
+    return p.value
+  end
+  return 0
+end
+
+def main() returns int
+  return 0
+end
+"#;
+    let mir = lower_mir(source, false);
+    let read = mir.iter().find(|f| f.name == "read").unwrap();
+    assert_eq!(panic_messages(read), ["null pointer dereference"]);
+    let main = mir.iter().find(|f| f.name == "main").unwrap();
+    assert!(panic_messages(main).is_empty());
+
+    // -C null-checks=no trusts the type checker
+    let mir = lower_with(source, false, MirLowerer::new().with_null_checks(false));
+    let read = mir.iter().find(|f| f.name == "read").unwrap();
+    assert!(panic_messages(read).is_empty());
+}
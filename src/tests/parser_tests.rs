@@ -297,3 +297,60 @@ end
     // Method calls now require parentheses to avoid ambiguity
     assert!(!reporter.has_errors());
 }
+
+#[test]
+fn test_parse_multi_dimensional_array_type() {
+    let source = r#"
+def main()
+  grid : int[10][20]
+end
+"#;
+    let (ast, reporter) = parse_source(source);
+    assert!(!reporter.has_errors());
+    let body = match &ast.items[0] {
+        crate::core::ast::Item::Function(f) => f.body.as_ref().unwrap(),
+        _ => panic!("expected function"),
+    };
+    let type_ = match &body[0] {
+        crate::core::ast::Stmt::Let(l) => l.type_annotation.as_ref().unwrap(),
+        _ => panic!("expected let"),
+    };
+    // row-major: 10 rows of int[20]
+    match type_ {
+        crate::core::ast::Type::Array(outer) => {
+            assert_eq!(outer.size, Some(10));
+            match &*outer.element {
+                crate::core::ast::Type::Array(inner) => assert_eq!(inner.size, Some(20)),
+                other => panic!("expected inner array, got {:?}", other),
+            }
+        }
+        other => panic!("expected array, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_target_feature_attribute() {
+    let source = r#"
+@target_feature("avx2", "fma")
+def dot(a: int, b: int) returns int
+  return a * b
+end
+"#;
+    let (ast, reporter) = parse_source(source);
+    assert!(!reporter.has_errors());
+    match &ast.items[0] {
+        crate::core::ast::Item::Function(f) => {
+            assert_eq!(f.name, "dot");
+            assert_eq!(f.target_features, vec!["avx2".to_string(), "fma".to_string()]);
+        }
+        _ => panic!("expected function"),
+    }
+}
+
+#[test]
+fn test_parse_unknown_attribute_error() {
+    let (_ast, reporter) = parse_source("@inline(\"always\")\ndef f()\nend\n");
+    assert!(reporter.has_errors());
+    let (_ast, reporter) = parse_source("@target_feature(\"avx2\")\nstruct P\n  x : int\nend\n");
+    assert!(reporter.has_errors());
+}
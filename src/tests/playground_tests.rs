@@ -0,0 +1,34 @@
+use crate::playground::{compile_str, run_str, PLAYGROUND_FILE};
+
+#[test]
+fn test_playground_compiles_snippet() {
+    let result = compile_str("def main() returns int\n  x : uint = 4\n  return 0\nend\n");
+    assert!(result.success, "{}", result.diagnostics);
+    assert!(result.diagnostics.is_empty());
+    assert_eq!(result.output, "fn main: 0 param(s), 1 block\n");
+}
+
+#[test]
+fn test_playground_renders_diagnostics() {
+    let result = compile_str("def main() returns int\n  return missing\nend\n");
+    assert!(!result.success);
+    assert!(result.output.is_empty());
+    // plain text, the browser has no ansi colors
+    assert!(result.diagnostics.contains(PLAYGROUND_FILE));
+    assert!(!result.diagnostics.contains('\x1b'));
+}
+
+#[test]
+fn test_playground_runs_main() {
+    let result = run_str("def main() returns int\n  print(\"hi\\n\")\n  return 6 * 7\nend\n");
+    assert!(result.success, "{}", result.diagnostics);
+    assert_eq!(result.output, "hi\n");
+    assert_eq!(result.exit_code, Some(42));
+
+    // a trap is reported like a diagnostic, what was printed b4 it is kept
+    let result = run_str("def main() returns int\n  print(\"before\")\n  panic(\"boom\")\n  return 0\nend\n");
+    assert!(!result.success);
+    assert_eq!(result.output, "before");
+    assert_eq!(result.exit_code, None);
+    assert!(result.diagnostics.contains("boom"), "{}", result.diagnostics);
+}
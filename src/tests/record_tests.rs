@@ -0,0 +1,120 @@
+use crate::core::mir::{Instruction, MirFunction};
+use crate::core::types::composite::StructType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan::Files;
+
+fn analyze_source(source: &str) -> Reporter {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    if !reporter.has_errors() {
+        SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    }
+    reporter
+}
+
+fn messages(reporter: &Reporter, severity: Severity) -> Vec<String> {
+    reporter.diagnostics().iter().filter(|d| d.severity == severity).map(|d| d.message.clone()).collect()
+}
+
+fn lower_mir(source: &str) -> Vec<MirFunction> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors(), "{:?}", messages(&reporter, Severity::Error));
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+#[test]
+fn test_record_types_are_structural() {
+    let int = || Type::Primitive(PrimitiveType::Int);
+    let xy = StructType::record(vec![("x".to_string(), int()), ("y".to_string(), Type::Primitive(PrimitiveType::Long))]);
+    let yx = StructType::record(vec![("y".to_string(), Type::Primitive(PrimitiveType::Long)), ("x".to_string(), int())]);
+    assert_eq!(xy, yx);
+    assert_eq!(xy.name, "{ x: int, y: long }");
+    assert!(xy.is_record());
+    // x is padded up 2 y's alignment
+    assert_eq!(Type::Struct(xy).size_in_bytes(), Some(16));
+
+    let reporter = analyze_source(
+        r#"
+def dist(p : { x: long, y: long }) returns long
+  return p.x + p.y
+end
+
+def main() returns int
+  p : { x: int, y: int } = { y: 2, x: 1 }
+  d : long = dist({ x: 3, y: p.y })
+  q : { y: int, x: int } = p
+  return q.x
+end
+"#,
+    );
+    assert!(!reporter.has_errors(), "{:?}", messages(&reporter, Severity::Error));
+}
+
+#[test]
+fn test_record_field_mismatches_are_reported() {
+    let reporter = analyze_source(
+        r#"
+def main() returns int
+  a : { x: int } = { x: 1, y: 2 }
+  b : { x: int } = { x: 1, x: 2 }
+  c : int = b.z
+  d : { x: bool } = { x: 1 }
+  return 0
+end
+"#,
+    );
+    let errors = messages(&reporter, Severity::Error);
+    assert!(errors.iter().any(|m| m.starts_with("Type mismatch") && m.contains("{ x: int, y: int }")), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Field 'x' is given more than once"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Field 'z' not found on struct '{ x: int }'"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m.starts_with("Type mismatch") && m.contains("{ x: bool }")), "{:?}", errors);
+}
+
+#[test]
+fn test_named_argument_call_passes_a_record_of_the_param_type() {
+    let mir = lower_mir(
+        r#"
+def area(size : { w: long, h: long }) returns long
+  return size.w * size.h
+end
+
+def main() returns long
+  return area { h: 3, w: 2 }
+end
+"#,
+    );
+    let main = mir.iter().find(|f| f.name == "main").unwrap();
+    let insts: Vec<&Instruction> = main.basic_blocks.iter().flat_map(|bb| &bb.instructions).collect();
+    let record = StructType::record(vec![
+        ("w".to_string(), Type::Primitive(PrimitiveType::Long)),
+        ("h".to_string(), Type::Primitive(PrimitiveType::Long)),
+    ]);
+    // the int literals r built as the param's long record
+    let built = insts.iter().find_map(|i| match i {
+        Instruction::Copy { dest, type_: Type::Struct(s), .. } | Instruction::Alloca { dest, type_: Type::Struct(s) }
+            if *s == record =>
+        {
+            Some(*dest)
+        }
+        _ => None,
+    });
+    let built = built.expect("expected the record 2 be built");
+    assert!(insts.iter().any(|i| matches!(
+        i,
+        Instruction::Call { args, .. } if matches!(args.as_slice(), [crate::core::mir::Operand::Local(l)] if *l == built)
+    )));
+}
@@ -0,0 +1,248 @@
+use crate::backend::factory::{BackendRegistry, BackendType};
+use crate::backend::ports::codegen::{DebugInfo, DebugInfoLevel, PanicStrategy};
+use crate::backend::ports::optimizer::OptimizationPass;
+use crate::cli::args::{Cli, CompileConfig};
+use crate::cli::cache::sha256;
+use crate::cli::remote::{job_payload, load_or_create_token, read_frame, read_token, write_frame, Builder, Dispatcher, JobRunner, RemoteJob, MAX_FRAME};
+use crate::core::edition::Edition;
+use crate::core::mir::wire::{decode_functions, encode_functions, Reader, WireError, Writer};
+use crate::core::mir::MirFunction;
+use crate::error::Reporter;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::prelude::Prelude;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use clap::Parser as _;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn lower(source: &str) -> Vec<MirFunction> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).with_edition(Edition::LATEST).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).with_edition(Edition::LATEST).parse();
+    let prelude = Prelude::load(&mut reporter);
+    let ast = prelude.inject(&ast, true);
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).with_edition(Edition::LATEST).with_prelude(prelude).analyze(&ast);
+    assert!(!reporter.has_errors(), "{:?}", reporter.diagnostics());
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+/// strings, floats, chars, structs, enums + calls, most of what MIR can hold
+const SOURCE: &str = r#"
+struct Point
+  x : int
+  y : float
+end
+
+def half(n : int) returns int or Error
+  if n < 0
+    return Error { code: 3 }
+  end
+  return n / 2
+end
+
+def describe(p : Point, c : char) returns string
+  if p.y > 1.5
+    return "high" + "!"
+  end
+  return "low"
+end
+
+def main() returns int
+  p : Point = Point { x: 4, y: 2.25 }
+  describe(p, 'z')
+  match half(p.x)
+    when Ok(h)
+      return h
+    when Err(e)
+      return e.code
+  end
+  return 0
+end
+"#;
+
+fn text(functions: &[MirFunction]) -> String {
+    functions.iter().map(|f| f.to_string()).collect::<Vec<_>>().join("\n")
+}
+
+fn job(functions: Vec<MirFunction>) -> RemoteJob {
+    RemoteJob {
+        backend: BackendType::Null,
+        opt_level: "2".to_string(),
+        target: None,
+        panic: PanicStrategy::Unwind,
+        passes: Vec::new(),
+        unit: None,
+        debug_info: None,
+        functions,
+    }
+}
+
+#[test]
+fn test_mir_survives_the_wire() {
+    let functions = lower(SOURCE);
+    let mut w = Writer::new();
+    encode_functions(&functions, &mut w);
+    let bytes = w.into_bytes();
+    let decoded = decode_functions(&mut Reader::new(&bytes)).unwrap();
+    assert_eq!(text(&decoded), text(&functions));
+    assert_eq!(decoded.iter().map(|f| f.locals.len()).collect::<Vec<_>>(), functions.iter().map(|f| f.locals.len()).collect::<Vec<_>>());
+
+    // cut short or frm another version it fails instead of decoding half a fn
+    assert!(matches!(decode_functions(&mut Reader::new(&bytes[..bytes.len() - 1])), Err(WireError::Truncated(_))));
+    let mut newer = bytes.clone();
+    newer[0] += 1;
+    assert_eq!(decode_functions(&mut Reader::new(&newer)).unwrap_err(), WireError::Version(2));
+}
+
+#[test]
+fn test_jobs_carry_every_bridge_setting() {
+    let mut job = job(lower(SOURCE));
+    job.target = Some("aarch64-unknown-linux-gnu".to_string());
+    job.panic = PanicStrategy::Abort;
+    job.passes = vec![OptimizationPass::Inline, OptimizationPass::from_name("licm").unwrap()];
+    job.unit = Some(vec!["half".to_string(), "main".to_string()]);
+    let function_lines: HashMap<String, u32> = [("half".to_string(), 7), ("main".to_string(), 21)].into_iter().collect();
+    job.debug_info = Some(DebugInfo { level: DebugInfoLevel::LineTablesOnly, file: PathBuf::from("src/main.em"), function_lines });
+    let encoded = job.encode();
+    let decoded = RemoteJob::decode(&encoded).unwrap();
+    assert_eq!((decoded.backend, decoded.opt_level.as_str(), decoded.target.as_deref()), (BackendType::Null, "2", Some("aarch64-unknown-linux-gnu")));
+    assert_eq!((decoded.panic, decoded.passes, decoded.unit), (job.panic, job.passes.clone(), job.unit.clone()));
+    let debug_info = decoded.debug_info.unwrap();
+    assert_eq!((debug_info.level, debug_info.file, debug_info.function_lines), (DebugInfoLevel::LineTablesOnly, PathBuf::from("src/main.em"), job.debug_info.clone().unwrap().function_lines));
+    assert_eq!(text(&decoded.functions), text(&job.functions));
+
+    // the key is the content's SHA-256, the same job hashes the same + any setting changes it
+    assert_eq!(RemoteJob::key(&encoded), sha256(&encoded));
+    assert_eq!(RemoteJob::key(&job.encode()), RemoteJob::key(&encoded));
+    job.opt_level = "3".to_string();
+    assert_ne!(RemoteJob::key(&job.encode()), RemoteJob::key(&encoded));
+    assert!(RemoteJob::decode(&encoded[..encoded.len() - 4]).unwrap_err().starts_with("Invalid job:"));
+}
+
+#[test]
+fn test_frames_are_read_back_in_order() {
+    let mut stream = Vec::new();
+    write_frame(&mut stream, "JOB", b"one\ntwo").unwrap();
+    write_frame(&mut stream, "ERR", b"").unwrap();
+    assert!(stream.starts_with(b"JOB 7\none\ntwo"));
+    let mut input = BufReader::new(stream.as_slice());
+    assert_eq!(read_frame(&mut input).unwrap(), Some(("JOB".to_string(), b"one\ntwo".to_vec())));
+    assert_eq!(read_frame(&mut input).unwrap(), Some(("ERR".to_string(), Vec::new())));
+    assert_eq!(read_frame(&mut input).unwrap(), None);
+    assert!(read_frame(&mut BufReader::new(&b"OBJ 9\nshort"[..])).is_err());
+    assert!(read_frame(&mut BufReader::new(&b"garbage\n"[..])).is_err());
+
+    // nothing past the limit is buffered, nor a header that never ends
+    let huge = format!("JOB {}\n", MAX_FRAME + 1);
+    assert!(read_frame(&mut BufReader::new(huge.as_bytes())).unwrap_err().to_string().contains("limit"));
+    assert!(read_frame(&mut BufReader::new(&[b'J'; 4096][..])).is_err());
+}
+
+#[test]
+fn test_sha256_matches_the_standard_vectors() {
+    assert_eq!(sha256(b"").to_string(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    assert_eq!(sha256(b"abc").to_string(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    // 2 blocks, the padding doesnt fit after 56 bytes
+    assert_eq!(
+        sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").to_string(),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(sha256(&[b'a'; 1000]).to_string(), "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3");
+}
+
+#[test]
+fn test_worker_answers_each_job_and_caches_its_objects() {
+    let dir = std::env::temp_dir().join(format!("emc-remote-worker-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let runner = JobRunner::new(BackendRegistry::new()).with_cache(dir.clone());
+    let encoded = job(lower(SOURCE)).encode();
+    let mut requests = Vec::new();
+    write_frame(&mut requests, "JOB", &job_payload("", &encoded)).unwrap();
+    write_frame(&mut requests, "PING", b"").unwrap();
+    write_frame(&mut requests, "JOB", &job_payload("", &encoded[1..])).unwrap();
+    let mut replies = Vec::new();
+    runner.serve_stream(&mut BufReader::new(requests.as_slice()), &mut replies).unwrap();
+
+    let mut replies = BufReader::new(replies.as_slice());
+    let (kind, object) = read_frame(&mut replies).unwrap().unwrap();
+    assert_eq!(kind, "OBJ");
+    assert!(!object.is_empty());
+    assert_eq!(read_frame(&mut replies).unwrap(), Some(("ERR".to_string(), b"Unknown request 'PING'".to_vec())));
+    let (kind, message) = read_frame(&mut replies).unwrap().unwrap();
+    assert!(kind == "ERR" && message.starts_with(b"Invalid job:"), "{}", String::from_utf8_lossy(&message));
+
+    // the object is kept under the job's key + handed out again
+    let fingerprint = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+    let cached = fingerprint.join(format!("{}.o", RemoteJob::key(&encoded)));
+    assert_eq!(std::fs::read(&cached).unwrap(), object);
+    std::fs::write(&cached, "cached").unwrap();
+    assert_eq!(runner.build(&encoded).unwrap(), b"cached");
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_dispatcher_builds_on_a_server_and_falls_back_when_none_answers() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    // a server w/o a token wont start
+    let open = TcpListener::bind("127.0.0.1:0").unwrap();
+    assert!(Arc::new(JobRunner::new(BackendRegistry::new())).serve(open).is_err());
+    let runner = Arc::new(JobRunner::new(BackendRegistry::new()).with_token("secret".to_string()));
+    std::thread::spawn(move || runner.serve(listener));
+
+    let dir = std::env::temp_dir().join(format!("emc-remote-client-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let object = dir.join("unit.o");
+    let functions = lower(SOURCE);
+    let local = dir.join("local.o");
+    job(functions.clone()).run(&BackendRegistry::new(), &local).unwrap();
+
+    let dispatcher = Dispatcher::new(&[Builder::Remote(address.clone())]).with_token("secret".to_string());
+    for _ in 0..2 {
+        assert_eq!(dispatcher.compile(&job(functions.clone()), &object), Ok(true));
+        assert_eq!(std::fs::read(&object).unwrap(), std::fs::read(&local).unwrap());
+    }
+    // a client w/o the token gets nothing built
+    let stranger = Dispatcher::new(&[Builder::Remote(address)]).with_token("guess".to_string());
+    assert_eq!(stranger.compile(&job(functions.clone()), &object), Err("The builder refused the job, its token doesn't match".to_string()));
+    // closed port, the caller builds the job itself
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    assert_eq!(Dispatcher::new(&[Builder::Remote(closed)]).compile(&job(functions), &object), Ok(false));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_codegen_flags_pick_the_builders() {
+    let cli = Cli::parse_from(["emerald", "a.em", "-C", "workers=2", "-C", "remote=build1:7070,build2:7070"]);
+    let config = CompileConfig::from_cli(&cli).unwrap();
+    let expected = [Builder::Worker, Builder::Worker, Builder::Remote("build1:7070".to_string()), Builder::Remote("build2:7070".to_string())];
+    assert_eq!(config.builders, expected);
+    assert_eq!(config.remote_token, None);
+    let cli = Cli::parse_from(["emerald", "a.em", "-C", "remote=build1:7070", "-C", "remote-token=build.token"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap().remote_token, Some(PathBuf::from("build.token")));
+    let cli = Cli::parse_from(["emerald", "a.em", "-C", "workers=some"]);
+    assert_eq!(CompileConfig::from_cli(&cli).unwrap_err(), "Invalid workers 'some', expected a number");
+}
+
+#[test]
+fn test_the_token_file_is_made_once_and_private() {
+    let file = std::env::temp_dir().join(format!("emc-remote-token-{}", std::process::id()));
+    let _ = std::fs::remove_file(&file);
+    let token = load_or_create_token(&file).unwrap();
+    assert_eq!(token.len(), 32);
+    assert_eq!(load_or_create_token(&file).unwrap(), token);
+    assert_eq!(read_token(&file).unwrap(), token);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(read_token(&file).is_err());
+    }
+    let _ = std::fs::remove_file(&file);
+}
@@ -0,0 +1,73 @@
+use crate::backend::ports::execution::EntryValue;
+use crate::cli::args::{Cli, CompileConfig};
+use crate::cli::repl::{Repl, ReplError};
+use crate::error::Severity;
+use clap::Parser as _;
+
+/// a session on the interpreter so it runs the same w/ or w/o llvm
+fn session() -> Repl {
+    let cli = Cli::parse_from(["emerald", "--interp", "<repl>"]);
+    Repl::new(CompileConfig::from_cli(&cli).unwrap()).unwrap()
+}
+
+fn messages(repl: &Repl) -> Vec<String> {
+    repl.reporter().diagnostics().iter().map(|d| d.message.clone()).collect()
+}
+
+#[test]
+fn test_repl_keeps_bindings_and_definitions() {
+    let mut repl = session();
+    assert_eq!(repl.eval("x : int = 2").unwrap(), None);
+    assert_eq!(repl.eval("x * 21").unwrap(), Some(EntryValue::Int(42)));
+
+    let def = "def sq(n : int) returns int\n  return n * n\nend";
+    assert_eq!(repl.eval(def).unwrap(), None);
+    assert_eq!(repl.eval("sq(x) + 1").unwrap(), Some(EntryValue::Int(5)));
+    assert_eq!(repl.eval("\"ab\" + \"c\"").unwrap(), Some(EntryValue::Str("abc".to_string())));
+    assert_eq!(repl.eval("x > 1").unwrap(), Some(EntryValue::Bool(true)));
+    assert!(repl.symbols().resolve("sq").is_some());
+}
+
+#[test]
+fn test_repl_rejected_entry_leaves_session_intact() {
+    let mut repl = session();
+    repl.eval("x : int = 2").unwrap();
+    let err = repl.eval("y : int = z").unwrap_err();
+    assert!(matches!(err, ReplError::Invalid(1)), "{}", err);
+    assert_eq!(messages(&repl), vec!["Undefined variable 'z'".to_string()]);
+    // the span is in the entry just typed
+    let source = repl.reporter().files().source(repl.reporter().diagnostics()[0].file_id);
+    let span = repl.reporter().diagnostics()[0].span;
+    assert_eq!(&source[span.start().to_usize()..span.end().to_usize()], "z");
+
+    // `y` was never bound + the bad entry isnt replayed
+    assert!(repl.eval("y").is_err());
+    assert_eq!(repl.eval("x + 1").unwrap(), Some(EntryValue::Int(3)));
+    assert!(messages(&repl).is_empty());
+}
+
+#[test]
+fn test_repl_warns_once_per_entry() {
+    let mut repl = session();
+    let def = "@deprecated(\"use add instead\")\ndef plus(a : int, b : int) returns int\n  return a + b\nend";
+    repl.eval(def).unwrap();
+    repl.eval("y : int = plus(1, 2)").unwrap();
+    assert_eq!(messages(&repl), vec!["Use of deprecated function 'plus': use add instead".to_string()]);
+    assert!(matches!(repl.reporter().diagnostics()[0].severity, Severity::Warning));
+
+    // `y`s binding is replayed, its warning isnt repeated
+    assert_eq!(repl.eval("y * 2").unwrap(), Some(EntryValue::Int(6)));
+    assert!(messages(&repl).is_empty());
+}
+
+#[test]
+fn test_repl_reads_on_until_a_block_ends() {
+    let repl = session();
+    assert!(repl.is_incomplete("def sq(n : int) returns int\n"));
+    assert!(repl.is_incomplete("def sq(n : int) returns int\n  return n * n\n"));
+    assert!(!repl.is_incomplete("def sq(n : int) returns int\n  return n * n\nend\n"));
+    assert!(repl.is_incomplete("if true\n"));
+    assert!(!repl.is_incomplete("1 + 2\n"));
+    // a mistake isnt fixed by reading more
+    assert!(!repl.is_incomplete("1 + + 2\n"));
+}
@@ -0,0 +1,158 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::core::edition::Edition;
+use crate::core::mir::{Instruction, MirFunction};
+use crate::core::types::composite::spelling;
+use crate::error::Reporter;
+use crate::frontend::lexer::token::TokenKind;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::prelude::Prelude;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+
+/// the errors checking `source` w/ the prelude in `edition` reports, + its MIR when there r none
+fn compile(source: &str, edition: Edition) -> Result<Vec<MirFunction>, Vec<String>> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).with_edition(edition).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).with_edition(edition).parse();
+    let prelude = Prelude::load(&mut reporter);
+    let ast = prelude.inject(&ast, true);
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).with_edition(edition).with_prelude(prelude).analyze(&ast);
+    if reporter.has_errors() {
+        return Err(reporter.diagnostics().iter().map(|d| d.message.clone()).collect());
+    }
+    Ok(MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast)))
+}
+
+fn run(source: &str) -> i32 {
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&compile(source, Edition::LATEST).unwrap()).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module).unwrap()
+}
+
+/// `parse` fails on negative numbers, `score` tells an `Ok` frm an `Err` by matching
+const PARSE: &str = r#"
+def parse(n : int) returns int or Error
+  if n < 0
+    return Error { code: 7 }
+  end
+  return n * 2
+end
+
+def score(r : int or Error) returns int
+  match r
+    when Ok(v)
+      return v
+    when Err(e)
+      return 100 + e.code
+  end
+  return 0
+end
+"#;
+
+#[test]
+fn test_try_is_a_keyword_from_2026() {
+    let kinds = |edition: Edition| {
+        let mut reporter = Reporter::new();
+        let file_id = reporter.add_file("test.em".to_string(), "try".to_string());
+        Lexer::new("try", file_id, &mut reporter).with_edition(edition).tokenize()[0].kind.clone()
+    };
+    assert_eq!(kinds(Edition::E2025), TokenKind::Identifier("try".to_string()));
+    assert_eq!(kinds(Edition::E2026), TokenKind::Try);
+    // still a name b4, `?` works in evry edition
+    let source = format!("{}\ndef main() returns int\n  try : int = 1\n  return try\nend\n", PARSE);
+    assert!(compile(&source, Edition::E2025).is_ok());
+}
+
+#[test]
+fn test_question_mark_and_try_return_the_error_early() {
+    let source = format!(
+        "{}\ndef total(a : int, b : int) returns int or Error\n  x : int = parse(a)?\n  y : int = try parse(b)\n  return x + y\nend\n\ndef main() returns int\n  return score(total(3, 4)) * 1000 + score(total(3, -1))\nend\n",
+        PARSE
+    );
+    assert_eq!(run(&source), (6 + 8) * 1000 + 107);
+}
+
+#[test]
+fn test_question_mark_runs_deferred_code_before_it_returns() {
+    let source = format!(
+        r#"{}
+def bump(p : ref int, by : int)
+  p.value = p.value * 10 + by
+end
+
+def work(log : ref int, n : int) returns int or Error
+  defer bump(log, 1)
+  x : int = parse(n)?
+  bump(log, 2)
+  return x
+end
+
+def main() returns int
+  log : ref int = new 0
+  a : int = score(work(log, 1))
+  b : int = score(work(log, -1))
+  total : int = log.value
+  delete log
+  return total * 1000 + a + b
+end
+"#,
+        PARSE
+    );
+    // the 1st call runs 2 then the deferred 1, the failing 1 only the deferred 1
+    assert_eq!(run(&source), 211 * 1000 + 2 + 107);
+}
+
+#[test]
+fn test_question_mark_lowers_to_a_tag_test_and_an_err_return() {
+    let source = format!("{}\ndef twice(n : int) returns long or Error\n  return parse(n)? * 2\nend\n", PARSE);
+    let mir = compile(&source, Edition::LATEST).unwrap();
+    let twice = mir.iter().find(|f| f.name == "twice").unwrap();
+    let insts: Vec<&Instruction> = twice.basic_blocks.iter().flat_map(|bb| &bb.instructions).collect();
+    assert!(insts.iter().any(|i| matches!(i, Instruction::EnumTag { .. })));
+    assert!(insts.iter().any(|i| matches!(i, Instruction::Switch { arms, .. } if arms.len() == 1 && arms[0].0 == 1)));
+    // the error is read out of `int or Error` + returned as the `long or Error` twice returns
+    assert!(insts.iter().any(|i| matches!(i, Instruction::VariantField { tag: 1, .. })));
+    assert!(insts.iter().any(|i| matches!(i, Instruction::MakeVariant { tag: 1, type_, .. } if spelling(type_) == "long or Error")));
+    assert_eq!(twice.basic_blocks.iter().filter(|bb| matches!(bb.instructions.last(), Some(Instruction::Ret { .. }))).count(), 2);
+}
+
+#[test]
+fn test_errors_have_to_be_handled() {
+    let source = format!(
+        r#"{}
+def same() returns int or int
+  return 1
+end
+
+def plain() returns int
+  return parse(1)?
+end
+
+def other() returns int or bool
+  return parse(1)?
+end
+
+def main() returns int
+  parse(2)
+  x : int = parse(3)
+  return 1?
+end
+"#,
+        PARSE
+    );
+    let errors = compile(&source, Edition::LATEST).unwrap_err();
+    let expected = [
+        "'int or int' can't tell a value from an error, the two types must differ",
+        "'?' passes the error on, the enclosing function has to return '... or Error'",
+        "'?' passes on an error of type Error but 'other' returns 'int or bool'",
+        "The error this 'int or Error' can hold is not handled, propagate it with '?' or match on it",
+        "'x' gets an 'int or Error', propagate its error with '?' or match on it",
+        "'?' needs a value that can fail, got int",
+    ];
+    for message in expected {
+        assert!(errors.iter().any(|e| e == message), "{} missing frm {:?}", message, errors);
+    }
+}
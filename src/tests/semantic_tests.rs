@@ -179,3 +179,99 @@ end
     // shadowing should be allowed
     assert!(!reporter.has_errors());
 }
+
+#[test]
+fn test_target_feature_validation() {
+    let source = r#"
+@target_feature("avx2", "sse4.2")
+def dot(a: int, b: int) returns int
+  return a * b
+end
+"#;
+    let (_ast, reporter) = analyze_source(source);
+    assert!(!reporter.has_errors());
+
+    let (_ast, reporter) = analyze_source("@target_feature(\"avx9000\")\ndef f()\n  x : int = 1\nend\n");
+    assert!(reporter.diagnostics().iter().any(|d| d.message.contains("unknown target feature 'avx9000'")));
+
+    let (_ast, reporter) = analyze_source("@target_feature(\"avx2\", \"avx2\")\ndef f()\n  x : int = 1\nend\n");
+    assert!(reporter.diagnostics().iter().any(|d| d.message.contains("duplicate target feature")));
+}
+
+#[test]
+fn test_failed_expressions_become_holes() {
+    use crate::core::types::primitive::PrimitiveType;
+    use crate::core::types::ty::Type;
+
+    let source = r#"
+def add(a : int, b : int) returns int
+  return a + b
+end
+
+def test(n : int) returns int
+  x : int = missing + 1
+  y : int = add(x, missing.field)
+  return y * n
+end
+"#;
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let mut analyzer = SemanticAnalyzer::new(&mut reporter, file_id);
+    analyzer.analyze(&ast);
+
+    // the hole unifies w/ int, nothing downstream of it is reported
+    let offset = source.find("missing").unwrap();
+    let hole = analyzer.hole_at(codespan::ByteIndex(offset as u32 + 2)).expect("a hole at the undefined variable");
+    assert_eq!(hole.span.start().to_usize(), offset);
+    assert!(hole.scope.contains(&("n".to_string(), Type::Primitive(PrimitiveType::Int))), "{:?}", hole.scope);
+    assert!(hole.scope.iter().any(|(name, ty)| name == "add" && matches!(ty, Type::Function(_))), "{:?}", hole.scope);
+    assert_eq!(analyzer.holes().len(), 1);
+    drop(analyzer);
+
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(errors, ["Undefined variable 'missing'"]);
+}
+
+
+#[test]
+fn test_unknown_members_suggest_the_closest_one() {
+    let source = r#"
+struct Window
+  width : int
+  height : int
+  border_color : int
+end
+
+trait Drawable
+  def draw(self : ref Window) returns int
+end
+
+implement Drawable for Window
+  def draw(self : ref Window) returns int
+    return 1
+  end
+end
+
+def test(w : ref Window, v : Window) returns int
+  a : int = w.widht
+  b : int = v.Height
+  c : int = w.drwa()
+  d : int = v.colour
+  return a + b + c + d
+end
+"#;
+    let (_, reporter) = analyze_source(source);
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| (d.message.as_str(), d.notes.clone())).collect();
+    assert_eq!(
+        errors,
+        [
+            ("Field 'widht' not found on pointer pointee 'Window'", vec!["did you mean 'width'?".to_string()]),
+            ("Field 'Height' not found on struct 'Window'", vec!["did you mean 'height'?".to_string()]),
+            ("Method 'drwa' not found on type", vec!["did you mean 'draw'?".to_string()]),
+            // nothing close enough, no guess
+            ("Field 'colour' not found on struct 'Window'", vec![]),
+        ]
+    );
+}
\ No newline at end of file
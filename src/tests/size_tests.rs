@@ -0,0 +1,119 @@
+use crate::cli::size::{demangle, DemangledName, SizeReport};
+
+/// elf64 little endian w/ just a symbol table of (name, STT_*, size) entries
+fn elf64_with_symbols(symbols: &[(&str, u8, u64)]) -> Vec<u8> {
+    let mut strtab = vec![0u8];
+    let mut symtab = vec![0u8; 24]; // null symbol
+    for (name, kind, size) in symbols {
+        let name_offset = strtab.len() as u32;
+        strtab.extend_from_slice(name.as_bytes());
+        strtab.push(0);
+        symtab.extend_from_slice(&name_offset.to_le_bytes());
+        symtab.push(0x10 | kind); // STB_GLOBAL
+        symtab.push(0);
+        symtab.extend_from_slice(&1u16.to_le_bytes()); // defined in section 1
+        symtab.extend_from_slice(&0u64.to_le_bytes());
+        symtab.extend_from_slice(&size.to_le_bytes());
+    }
+    let shstrtab = b"\0.symtab\0.strtab\0.shstrtab\0".to_vec();
+
+    let symtab_off = 64u64;
+    let strtab_off = symtab_off + symtab.len() as u64;
+    let shstrtab_off = strtab_off + strtab.len() as u64;
+    let shoff = shstrtab_off + shstrtab.len() as u64;
+
+    let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+    elf.resize(16, 0);
+    elf.extend_from_slice(&1u16.to_le_bytes()); // ET_REL
+    elf.extend_from_slice(&62u16.to_le_bytes()); // EM_X86_64
+    elf.extend_from_slice(&1u32.to_le_bytes());
+    elf.extend_from_slice(&0u64.to_le_bytes()); // entry
+    elf.extend_from_slice(&0u64.to_le_bytes()); // phoff
+    elf.extend_from_slice(&shoff.to_le_bytes());
+    elf.extend_from_slice(&0u32.to_le_bytes());
+    elf.extend_from_slice(&64u16.to_le_bytes());
+    elf.extend_from_slice(&0u16.to_le_bytes());
+    elf.extend_from_slice(&0u16.to_le_bytes());
+    elf.extend_from_slice(&64u16.to_le_bytes());
+    elf.extend_from_slice(&4u16.to_le_bytes()); // shnum
+    elf.extend_from_slice(&3u16.to_le_bytes()); // shstrndx
+    elf.extend_from_slice(&symtab);
+    elf.extend_from_slice(&strtab);
+    elf.extend_from_slice(&shstrtab);
+
+    // (name, type, offset, size, link, entsize)
+    let sections = [
+        (0u32, 0u32, 0u64, 0u64, 0u32, 0u64),
+        (1, 2, symtab_off, symtab.len() as u64, 2, 24),
+        (9, 3, strtab_off, strtab.len() as u64, 0, 0),
+        (17, 3, shstrtab_off, shstrtab.len() as u64, 0, 0),
+    ];
+    for (name, sh_type, offset, size, link, entsize) in sections {
+        elf.extend_from_slice(&name.to_le_bytes());
+        elf.extend_from_slice(&sh_type.to_le_bytes());
+        elf.extend_from_slice(&0u64.to_le_bytes()); // flags
+        elf.extend_from_slice(&0u64.to_le_bytes()); // addr
+        elf.extend_from_slice(&offset.to_le_bytes());
+        elf.extend_from_slice(&size.to_le_bytes());
+        elf.extend_from_slice(&link.to_le_bytes());
+        elf.extend_from_slice(&0u32.to_le_bytes()); // info
+        elf.extend_from_slice(&8u64.to_le_bytes()); // align
+        elf.extend_from_slice(&entsize.to_le_bytes());
+    }
+    elf
+}
+
+#[test]
+fn test_demangle_specializations() {
+    assert_eq!(
+        demangle("max$int"),
+        DemangledName { module: None, origin: "max".to_string(), specialization: Some("int".to_string()) }
+    );
+    assert_eq!(demangle("swap$ref_Point").specialization.as_deref(), Some("ref_Point"));
+    assert_eq!(demangle("sum$int_arr4").origin, "sum");
+    assert_eq!(demangle("pair$int$Point").specialization.as_deref(), Some("int, Point"));
+    assert_eq!(demangle("scale$ct0").origin, "scale");
+    // names that only end like a type r fns of their own
+    assert_eq!(demangle("read_file").origin, "read_file");
+    assert_eq!(demangle("max_int").origin, "max_int");
+    assert_eq!(demangle("max_int").specialization, None);
+    assert_eq!(demangle("int").origin, "int");
+}
+
+#[test]
+fn test_demangle_module_paths() {
+    let name = demangle("math::clamp$float");
+    assert_eq!(name.module.as_deref(), Some("math"));
+    assert_eq!(name.origin, "clamp");
+    assert_eq!(demangle("Display::Point::fmt").module.as_deref(), Some("Display::Point"));
+}
+
+#[test]
+fn test_size_report_breakdown() {
+    let elf = elf64_with_symbols(&[
+        ("main", 2, 40),
+        ("max$int", 2, 24),
+        ("max$float", 2, 32),
+        ("max_len", 2, 8), // not a specialization of max
+        ("math::sqrt", 2, 100),
+        ("TABLE", 1, 16),
+        ("undefined_but_sized", 0, 8), // STT_NOTYPE skipped
+    ]);
+    let report = SizeReport::from_elf(&elf).unwrap();
+
+    assert_eq!(report.symbols.len(), 6);
+    assert_eq!(report.symbols[0].symbol, "math::sqrt");
+    assert_eq!(report.total(), 220);
+    assert_eq!(report.by_module(), vec![("<root>".to_string(), 120), ("math".to_string(), 100)]);
+    assert_eq!(report.by_generic(), vec![("max".to_string(), 2, 56)]);
+
+    let rendered = report.render();
+    assert!(rendered.contains("TABLE (data)"));
+    assert!(rendered.contains("generic function"));
+}
+
+#[test]
+fn test_size_report_rejects_non_elf() {
+    assert!(SizeReport::from_elf(b"#!/bin/sh\n").is_err());
+    assert!(SizeReport::from_elf(&elf64_with_symbols(&[])).is_err());
+}
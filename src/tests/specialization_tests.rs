@@ -49,7 +49,7 @@ end
     assert!(!reporter.has_errors());
     
     // chk that specialized structs r in symbol table
-    // specialized names wld be like List_int, List_float, etc
+    // specialized names wld be like List$int, List$float, etc
     let all_symbols = symbol_table.all_symbols();
     let symbol_names: Vec<String> = all_symbols.iter().map(|(name, _)| name.clone()).collect();
     
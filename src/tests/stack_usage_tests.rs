@@ -0,0 +1,68 @@
+use crate::backend::ports::emitter::EmitType;
+use crate::backend::stack_usage::{FrameInfo, StackUsage};
+
+fn frame(name: &str, frame_size: u64, callees: &[&str]) -> FrameInfo {
+    FrameInfo {
+        name: name.to_string(),
+        frame_size,
+        dynamic: false,
+        callees: callees.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_emit_type_stack_usage() {
+    assert_eq!(EmitType::from_str("stack-usage"), Some(EmitType::StackUsage));
+}
+
+#[test]
+fn test_stack_usage_worst_case_path() {
+    // main -> a -> c is deeper than main -> b, externals (puts) r free
+    let usage = StackUsage::new(vec![
+        frame("main", 32, &["a", "b", "puts"]),
+        frame("a", 16, &["c"]),
+        frame("b", 64, &[]),
+        frame("c", 128, &[]),
+    ]);
+    assert!(usage.recursive_functions().is_empty());
+    assert_eq!(usage.worst_case("main"), Some(32 + 16 + 128));
+    assert_eq!(usage.worst_case("b"), Some(64));
+    assert_eq!(usage.worst_case("missing"), None);
+}
+
+#[test]
+fn test_stack_usage_self_recursion_is_unbounded() {
+    let usage = StackUsage::new(vec![
+        frame("main", 8, &["fact"]),
+        frame("fact", 24, &["fact"]),
+    ]);
+    let recursive = usage.recursive_functions();
+    assert!(recursive.contains("fact"));
+    assert!(!recursive.contains("main"));
+    // main is not recursive itself but reaches recursion
+    assert_eq!(usage.worst_case("main"), None);
+}
+
+#[test]
+fn test_stack_usage_dynamic_alloca_is_unbounded() {
+    let mut dynamic = frame("scratch", 8, &[]);
+    dynamic.dynamic = true;
+    let usage = StackUsage::new(vec![frame("main", 8, &["scratch"]), dynamic]);
+    assert_eq!(usage.worst_case("main"), None);
+}
+
+#[test]
+fn test_stack_usage_report() {
+    let usage = StackUsage::new(vec![
+        frame("main", 16, &["walk", "leaf"]),
+        frame("walk", 48, &["walk"]),
+        frame("leaf", 0, &[]),
+    ]);
+    let report = usage.report();
+    let lines: Vec<&str> = report.lines().collect();
+
+    assert!(lines[0].starts_with("function"));
+    assert!(lines[1].starts_with("main") && lines[1].contains("unbounded"), "got {}", lines[1]);
+    assert!(lines[2].starts_with("walk") && lines[2].ends_with("recursive"), "got {}", lines[2]);
+    assert!(lines[3].starts_with("leaf") && lines[3].ends_with(" 0"), "got {}", lines[3]);
+}
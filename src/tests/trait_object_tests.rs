@@ -117,3 +117,87 @@ fn test_trait_object_type_representation() {
     assert!(!trait_obj.is_array());
     assert!(!trait_obj.is_pointer());
 }
+
+const SHAPES: &str = r#"
+trait Shape
+  def area(self) returns float
+  def scale(self, k : float) returns float
+end
+
+struct Circle
+  radius : float
+end
+
+struct Square
+  side : float
+end
+
+implement Shape for Circle
+  def area(self : ref Circle) returns float
+    return 3.14 * self.radius * self.radius
+  end
+  def scale(self : Circle, k : float) returns float
+    return self.radius * k
+  end
+end
+"#;
+
+#[test]
+fn test_dyn_type_parses() {
+    let source = format!("{}\ndef total(s : dyn Shape) returns float\n  return s.area()\nend\n", SHAPES);
+    let (ast, reporter) = analyze_source(&source);
+    assert!(!reporter.has_errors());
+    let total = ast.items.iter().find_map(|i| match i {
+        crate::core::ast::Item::Function(f) if f.name == "total" => Some(f),
+        _ => None,
+    });
+    assert!(matches!(&total.unwrap().params[0].type_, crate::core::ast::Type::TraitObject(t) if t == "Shape"));
+}
+
+#[test]
+fn test_ref_coerces_to_dyn_only_when_implemented() {
+    let ok = format!("{}\ndef run returns float\n  c : Circle = Circle {{ radius: 1.0 }}\n  d : dyn Shape = @c\n  return d.scale(2.0)\nend\n", SHAPES);
+    let (_ast, reporter) = analyze_source(&ok);
+    assert!(!reporter.has_errors());
+
+    let bad = format!("{}\ndef run\n  s : Square = Square {{ side: 1.0 }}\n  d : dyn Shape = @s\nend\n", SHAPES);
+    let (_ast, reporter) = analyze_source(&bad);
+    assert!(reporter.has_errors());
+}
+
+#[test]
+fn test_dyn_call_checked_against_trait() {
+    let source = format!("{}\ndef run(d : dyn Shape) returns float\n  return d.perimeter() + d.scale()\nend\n", SHAPES);
+    let (_ast, reporter) = analyze_source(&source);
+    let messages: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+    assert!(messages.iter().any(|m| m.contains("Trait 'Shape' has no method 'perimeter'")));
+    assert!(messages.iter().any(|m| m.contains("'scale' takes 1 argument(s), got 0")));
+}
+
+#[test]
+fn test_dyn_call_lowers_through_vtable() {
+    use crate::core::mir::Instruction;
+    use crate::middle::{HirLowerer, MirLowerer};
+
+    let source = format!("{}\ndef run returns float\n  c : Circle = Circle {{ radius: 1.0 }}\n  d : dyn Shape = @c\n  return d.scale(2.0)\nend\n", SHAPES);
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.clone());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(&source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+    let hir = HirLowerer::new(symbol_table).lower(&ast);
+    let mir = MirLowerer::new().lower(&hir);
+
+    // impl methods r emitted under their qualified names
+    assert!(mir.iter().any(|f| f.name == "Shape::Circle::area"));
+    let run = mir.iter().find(|f| f.name == "run").unwrap();
+    let insts: Vec<_> = run.basic_blocks.iter().flat_map(|b| &b.instructions).collect();
+    let vtable = insts.iter().find_map(|i| match i {
+        Instruction::MakeDyn { vtable, .. } => Some(vtable),
+        _ => None,
+    });
+    assert_eq!(vtable.unwrap().methods, vec!["Shape::Circle::area", "Shape::Circle::scale"]);
+    assert!(insts.iter().any(|i| matches!(i, Instruction::DynCall { slot: 1, args, .. } if args.len() == 1)));
+}
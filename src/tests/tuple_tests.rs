@@ -0,0 +1,132 @@
+use crate::backend::factory::BackendFactory;
+use crate::backend::interp::InterpBackendFactory;
+use crate::core::mir::{Instruction, MirFunction};
+use crate::core::optimizations::HirOptimizer;
+use crate::core::types::composite::StructType;
+use crate::core::types::primitive::PrimitiveType;
+use crate::core::types::ty::Type;
+use crate::error::Reporter;
+use crate::frontend::lexer::token::TokenKind;
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+
+/// the errors checking `source` reports, + its optimized MIR when there r none
+fn compile(source: &str) -> Result<Vec<MirFunction>, Vec<String>> {
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), source.to_string());
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    if reporter.has_errors() {
+        return Err(reporter.diagnostics().iter().map(|d| d.message.clone()).collect());
+    }
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    if reporter.has_errors() {
+        return Err(reporter.diagnostics().iter().map(|d| d.message.clone()).collect());
+    }
+    let mut hir = HirLowerer::new(symbol_table).lower(&ast);
+    HirOptimizer::new().optimize(&mut hir);
+    Ok(MirLowerer::new().lower(&hir))
+}
+
+fn run(source: &str) -> i32 {
+    let factory = InterpBackendFactory;
+    let module = factory.create_codegen().unwrap().generate_from_mir(&compile(source).unwrap()).unwrap();
+    factory.create_execution_engine().unwrap().run_main(&module).unwrap()
+}
+
+#[test]
+fn test_tuple_types_are_structs_named_by_their_elements() {
+    let pair = StructType::tuple(vec![Type::Primitive(PrimitiveType::Int), Type::Primitive(PrimitiveType::Float)]);
+    assert_eq!(pair.name, "(int, float)");
+    assert!(pair.is_tuple() && pair.is_structural() && !pair.is_record());
+    let names: Vec<&str> = pair.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["0", "1"]);
+    // the int is padded up 2 the float's alignment
+    assert_eq!(Type::Struct(pair).size_in_bytes(), Some(16));
+
+    // `nested.0.1` is 2 elements, not the float 0.1
+    let mut reporter = Reporter::new();
+    let file_id = reporter.add_file("test.em".to_string(), "nested.0.1".to_string());
+    let kinds: Vec<TokenKind> = Lexer::new("nested.0.1", file_id, &mut reporter).tokenize().into_iter().map(|t| t.kind).collect();
+    assert!(
+        matches!(kinds.as_slice(), [TokenKind::Identifier(_), TokenKind::Dot, TokenKind::IntLiteral(0), TokenKind::Dot, TokenKind::IntLiteral(1), TokenKind::Eof]),
+        "{:?}",
+        kinds
+    );
+}
+
+#[test]
+fn test_tuples_are_built_indexed_and_destructured() {
+    let source = r#"
+def divmod(a : int, b : int) returns (int, int)
+  return (a / b, a % b)
+end
+
+def main() returns int
+  mut a : int = 10
+  mut b : int = 20
+  mut q : int = 0
+  mut r : int = 0
+  nested : ((int, int), int) = ((4, 5), 6)
+  a, b = b, a
+  q, r = divmod(17, 5)
+  return a - b + q * 10 + r + nested.0.1 + nested.1
+end
+"#;
+    // 10 + 32 + 5 + 6
+    assert_eq!(run(source), 53);
+}
+
+#[test]
+fn test_destructuring_mismatches_are_reported() {
+    let errors = compile(
+        r#"
+def main() returns int
+  pair : (int, float) = (1, 2.5)
+  wide : (long, ubyte) = (1, 2)
+  mut a : int = 0
+  mut s : string = "x"
+  n : int = 4
+  a, s = pair
+  a, s, n = pair
+  a, s = n
+  return pair.2
+end
+"#,
+    )
+    .unwrap_err();
+    let expected = [
+        "Type mismatch in assignment: expected String, got Primitive(Float)",
+        "Cannot destructure a 2-tuple into 3 targets",
+        "Cannot destructure Primitive(Int), only a tuple can be assigned to several targets",
+        "Tuple '(int, float)' has no element '2', it has 2 elements",
+    ];
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn test_tuple_literals_lower_like_structs() {
+    let mir = compile(
+        r#"
+def main() returns long
+  pair : (long, int) = (1, 2)
+  return pair.0
+end
+"#,
+    )
+    .unwrap();
+    let main = mir.iter().find(|f| f.name == "main").unwrap();
+    let insts: Vec<&Instruction> = main.basic_blocks.iter().flat_map(|bb| &bb.instructions).collect();
+    // the int literals r built as the annotated (long, int)
+    let tuple = StructType::tuple(vec![Type::Primitive(PrimitiveType::Long), Type::Primitive(PrimitiveType::Int)]);
+    assert!(
+        insts.iter().any(|i| matches!(
+            i,
+            Instruction::Copy { type_: Type::Struct(s), .. } | Instruction::Alloca { type_: Type::Struct(s), .. } if *s == tuple
+        )),
+        "{:?}",
+        insts
+    );
+}
@@ -13,6 +13,51 @@ fn test_primitive_type_sizes() {
     assert_eq!(PrimitiveType::Char.size_in_bytes(), 4);
 }
 
+#[test]
+fn test_unsigned_primitives() {
+    use crate::error::Reporter;
+    use crate::frontend::lexer::Lexer;
+    use crate::frontend::parser::Parser;
+    use crate::frontend::semantic::SemanticAnalyzer;
+    use codespan::Files;
+
+    assert_eq!(PrimitiveType::UByte.size_in_bytes(), 1);
+    assert_eq!(PrimitiveType::UInt.size_in_bytes(), 4);
+    assert_eq!(PrimitiveType::ULong.size_in_bytes(), 8);
+    assert!(PrimitiveType::ULong.is_unsigned() && PrimitiveType::Byte.is_unsigned());
+    assert!(!PrimitiveType::Long.is_unsigned() && !PrimitiveType::Float.is_unsigned());
+
+    let source = r#"
+def mix(a : ubyte, b : uint, c : ulong) returns ulong
+  small : ubyte[2] = [255; 2]
+  total : ulong = c + 1
+  if b < 4
+    return total / 2
+  end
+  return total % 3
+end
+"#;
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors());
+
+    // literals need 2 fit the declared type
+    let source = "def f() returns int\n  a : uint = 4\n  b : ubyte = 256\n  c : ulong = -1\n  return 0\nend\n";
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    let errors: Vec<_> = reporter.diagnostics().iter().map(|d| d.message.clone()).collect();
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert!(errors.iter().all(|m| !m.contains("expected Primitive(UInt)")));
+}
+
 #[test]
 fn test_type_size() {
     let int_type = Type::Primitive(PrimitiveType::Int);
@@ -335,3 +380,92 @@ end
     // should cmpl sccssflly nstd comptime exprssns
     assert!(!reporter.has_errors());
 }
+
+#[cfg(feature = "llvm")]
+fn field(name: &str, type_: Type, offset: Option<usize>) -> crate::core::types::composite::Field {
+    crate::core::types::composite::Field { name: name.to_string(), type_, offset }
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_struct_type_has_field_body() {
+    use crate::backend::llvm::types::{mir_type_to_llvm_type, struct_field_indices};
+    use llvm_sys::core::*;
+
+    // node w/ a by value struct field and a ptr back 2 itself
+    let inner = StructType {
+        name: "Inner".to_string(),
+        fields: vec![field("flag", Type::Primitive(PrimitiveType::Bool), None)],
+        size: None,
+        align: None,
+    };
+    let self_ref = Type::Pointer(crate::core::types::pointer::PointerType::new(
+        Type::Struct(StructType { name: "Node".to_string(), fields: Vec::new(), size: None, align: None }),
+        true,
+    ));
+    let node = StructType {
+        name: "Node".to_string(),
+        fields: vec![
+            field("value", Type::Primitive(PrimitiveType::Int), None),
+            field("inner", Type::Struct(inner), None),
+            field("next", self_ref.clone(), None),
+        ],
+        size: None,
+        align: None,
+    };
+
+    unsafe {
+        let context = LLVMContextCreate();
+        let ty = mir_type_to_llvm_type(context, &Type::Struct(node.clone()));
+        assert_eq!(LLVMIsOpaqueStruct(ty), 0);
+        assert_eq!(LLVMCountStructElementTypes(ty), 3);
+        // recursive ref is an (opaque) ptr, a gep thru it steps over the same named type
+        let next = LLVMStructGetTypeAtIndex(ty, 2);
+        assert_eq!(LLVMGetTypeKind(next), llvm_sys::LLVMTypeKind::LLVMPointerTypeKind);
+        let module = LLVMModuleCreateWithNameInContext(b"node\0".as_ptr() as *const i8, context);
+        let mut params = [next];
+        let fn_ty = LLVMFunctionType(LLVMVoidTypeInContext(context), params.as_mut_ptr(), 1, 0);
+        let walk = LLVMAddFunction(module, b"walk\0".as_ptr() as *const i8, fn_ty);
+        let builder = LLVMCreateBuilderInContext(context);
+        LLVMPositionBuilderAtEnd(builder, LLVMAppendBasicBlockInContext(context, walk, b"entry\0".as_ptr() as *const i8));
+        let Type::Pointer(p) = &self_ref else { unreachable!() };
+        let gep = LLVMBuildStructGEP2(builder, mir_type_to_llvm_type(context, &p.pointee), LLVMGetParam(walk, 0), 2, b"next\0".as_ptr() as *const i8);
+        assert_eq!(LLVMGetGEPSourceElementType(gep), ty);
+        LLVMDisposeBuilder(builder);
+        LLVMDisposeModule(module);
+        // cached: a later name only use gets the bodied type
+        let by_name = Type::Struct(StructType { name: "Node".to_string(), fields: Vec::new(), size: None, align: None });
+        assert_eq!(mir_type_to_llvm_type(context, &by_name), ty);
+        assert_eq!(struct_field_indices(context, &node), vec![0, 1, 2]);
+        LLVMContextDispose(context);
+    }
+}
+
+#[test]
+#[cfg(feature = "llvm")]
+fn test_llvm_struct_type_explicit_padding() {
+    use crate::backend::llvm::types::{mir_type_to_llvm_type, struct_field_indices};
+    use llvm_sys::core::*;
+
+    // `b` placed at 8 instead of its natural offset 1, 16 byte total
+    let padded = StructType {
+        name: "Padded".to_string(),
+        fields: vec![
+            field("a", Type::Primitive(PrimitiveType::Byte), Some(0)),
+            field("b", Type::Primitive(PrimitiveType::Int), Some(8)),
+        ],
+        size: Some(16),
+        align: Some(4),
+    };
+
+    unsafe {
+        let context = LLVMContextCreate();
+        let ty = mir_type_to_llvm_type(context, &Type::Struct(padded.clone()));
+        // a, [7 x i8], b, [4 x i8]
+        assert_eq!(LLVMCountStructElementTypes(ty), 4);
+        assert_eq!(LLVMGetArrayLength(LLVMStructGetTypeAtIndex(ty, 1)), 7);
+        assert_eq!(LLVMGetArrayLength(LLVMStructGetTypeAtIndex(ty, 3)), 4);
+        assert_eq!(struct_field_indices(context, &padded), vec![0, 2]);
+        LLVMContextDispose(context);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::core::ast::stmt::Stmt;
+use crate::core::ast::Item;
+use crate::core::ast::pattern::Pattern;
+use crate::core::mir::{Instruction, MirFunction};
+use crate::error::{Reporter, Severity};
+use crate::frontend::lexer::Lexer;
+use crate::frontend::parser::Parser;
+use crate::frontend::semantic::SemanticAnalyzer;
+use crate::middle::{HirLowerer, MirLowerer};
+use codespan::Files;
+
+fn analyze_source(source: &str) -> (crate::core::ast::Ast, Reporter) {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    if !reporter.has_errors() {
+        SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    }
+    (ast, reporter)
+}
+
+fn messages(reporter: &Reporter, severity: Severity) -> Vec<String> {
+    reporter.diagnostics().iter().filter(|d| d.severity == severity).map(|d| d.message.clone()).collect()
+}
+
+fn lower_mir(source: &str) -> Vec<MirFunction> {
+    let mut files = Files::new();
+    let file_id = files.add("test.em", source.to_string());
+    let mut reporter = Reporter::new();
+    let tokens = Lexer::new(source, file_id, &mut reporter).tokenize();
+    let ast = Parser::new(tokens, file_id, &mut reporter).parse();
+    let symbol_table = SemanticAnalyzer::new(&mut reporter, file_id).analyze(&ast);
+    assert!(!reporter.has_errors(), "{:?}", messages(&reporter, Severity::Error));
+    MirLowerer::new().lower(&HirLowerer::new(symbol_table).lower(&ast))
+}
+
+const NEXT: &str = r#"
+enum Next
+  Some(int)
+  None
+end
+
+def next(i : int) returns Next
+  if i < 10
+    return Next::Some(i + 1)
+  end
+  return Next::None
+end
+"#;
+
+#[test]
+fn test_if_let_and_while_let_desugar_to_match() {
+    let source = format!(
+        "{}{}",
+        NEXT,
+        r#"
+def sum() returns int
+  total : int = 0
+  while let Some(n) = next(total)
+    total = total + n
+  end
+  if let Next::Some(n) = next(total)
+    return n
+  end
+  return total
+end
+"#
+    );
+    let (ast, reporter) = analyze_source(&source);
+    assert!(!reporter.has_errors(), "{:?}", messages(&reporter, Severity::Error));
+    assert!(messages(&reporter, Severity::Warning).is_empty(), "{:?}", messages(&reporter, Severity::Warning));
+    let body = ast
+        .items
+        .iter()
+        .find_map(|i| match i {
+            Item::Function(f) if f.name == "sum" => f.body.clone(),
+            _ => None,
+        })
+        .unwrap();
+
+    let Stmt::While(w) = &body[1] else { panic!("expected a while, got {:?}", body[1]) };
+    let [Stmt::Match(m)] = w.body.as_slice() else { panic!("expected a match in the loop body") };
+    assert!(matches!(&m.arms[0].patterns[0], Pattern::Variant(v) if v.variant == "Some" && v.bindings == ["n"]));
+    // the loop ends when the value stops matching
+    assert!(matches!(m.else_branch.as_deref(), Some([Stmt::Break(_)])));
+
+    let Stmt::Match(m) = &body[2] else { panic!("expected a match, got {:?}", body[2]) };
+    assert_eq!(m.arms.len(), 1);
+    assert_eq!(m.else_branch.as_deref().map(|e| e.len()), Some(0));
+}
+
+#[test]
+fn test_let_bindings_are_typed_and_scoped_to_the_body() {
+    let source = format!(
+        "{}{}",
+        NEXT,
+        r#"
+def f() returns int
+  if let Some(n) = next(0)
+    b : bool = n
+  else
+    return n
+  end
+  while let Some(m) = 4
+    return m
+  end
+  return 0
+end
+"#
+    );
+    let (_, reporter) = analyze_source(&source);
+    let errors = messages(&reporter, Severity::Error);
+    assert!(errors.iter().any(|m| m == "Type mismatch: expected Primitive(Bool), got Primitive(Int)"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Undefined variable 'n'"), "{:?}", errors);
+    assert!(errors.iter().any(|m| m == "Pattern Some(m) can't match a value of type int"), "{:?}", errors);
+}
+
+#[test]
+fn test_while_let_re_evaluates_the_value_each_iteration() {
+    let source = format!(
+        "{}{}",
+        NEXT,
+        r#"
+def count() returns int
+  total : int = 0
+  while let Next::Some(n) = next(total)
+    total = n
+  end
+  return total
+end
+"#
+    );
+    let mir = lower_mir(&source);
+    let count = mir.iter().find(|f| f.name == "count").unwrap();
+    let header = count
+        .basic_blocks
+        .iter()
+        .find(|bb| bb.instructions.iter().any(|i| matches!(i, Instruction::EnumTag { .. })))
+        .unwrap();
+    assert!(header.instructions.iter().any(|i| matches!(i, Instruction::Call { .. })));
+    let back_edges = count
+        .basic_blocks
+        .iter()
+        .flat_map(|bb| &bb.instructions)
+        .filter(|i| matches!(i, Instruction::Jump { target } if *target <= header.id))
+        .count();
+    assert!(back_edges >= 1);
+    let reads = count.basic_blocks.iter().flat_map(|bb| &bb.instructions);
+    assert_eq!(reads.filter(|i| matches!(i, Instruction::VariantField { tag: 0, index: 0, .. })).count(), 1);
+}
+
+#[test]
+fn test_irrefutable_let_patterns_warn_in_terms_of_the_let() {
+    let source = r#"
+enum Wrap
+  Only(int)
+end
+
+def f(w : Wrap) returns int
+  while let Wrap::Only(n) = w
+    return n
+  end
+  if let Wrap::Only(n) = w
+    return n
+  else
+    return 0
+  end
+  if let Wrap::Only(n) = w
+    return n
+  end
+  return 0
+end
+"#;
+    let (_, reporter) = analyze_source(source);
+    assert_eq!(
+        messages(&reporter, Severity::Warning),
+        vec![
+            "Irrefutable `while let`: the pattern always matches, so the loop only ends through `break` or `return`",
+            "Unreachable `else`: the `if let` pattern always matches",
+        ]
+    );
+}